@@ -40,8 +40,9 @@ use std::str::FromStr;
 use anyhow::{anyhow, bail, Context};
 use serde::{Deserialize, Serialize};
 use strum_macros::{Display, EnumString};
+use utoipa::ToSchema;
 
-#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
 pub struct Feed {
     pub feed_id: String,
     pub asset_class: AssetClass,
@@ -49,7 +50,7 @@ pub struct Feed {
     pub pair_id: String,
 }
 
-#[derive(Debug, PartialEq, Display, EnumString, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Display, EnumString, Serialize, Deserialize, ToSchema)]
 pub enum AssetClass {
     Crypto = 0,
 }
@@ -69,10 +70,12 @@ impl TryFrom<u16> for AssetClass {
 // This configuration is wrong at the moment. We should include:
 // FeedType(FeedVariant).
 // For now it works because we only have 0 anyway.
-#[derive(Debug, PartialEq, Display, EnumString, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Display, EnumString, Serialize, Deserialize, ToSchema)]
 pub enum FeedType {
     #[strum(serialize = "Unique Spot Median")]
     UniqueSpotMedian = 0,
+    #[strum(serialize = "Unique Perp Median")]
+    UniquePerpMedian = 1,
 }
 
 impl TryFrom<u16> for FeedType {
@@ -81,6 +84,7 @@ impl TryFrom<u16> for FeedType {
     fn try_from(value: u16) -> anyhow::Result<Self> {
         match value {
             0 => Ok(FeedType::UniqueSpotMedian),
+            1 => Ok(FeedType::UniquePerpMedian),
             _ => Err(anyhow!("Unknown feed type: {}", value)),
         }
     }
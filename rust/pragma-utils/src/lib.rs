@@ -1,5 +1,6 @@
 pub mod bytes;
 pub mod conversions;
 pub mod http;
+pub mod redaction;
 pub mod services;
 pub mod tracing;
@@ -1,11 +1,26 @@
+use std::collections::VecDeque;
 use std::env;
+use std::fmt::{self, Write as _};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, AtomicU8, Ordering};
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+use std::time::SystemTime;
 
-use anyhow::Result;
-use tracing_subscriber::filter::filter_fn;
+use anyhow::{bail, Context, Result};
+use tracing::field::{Field, Visit};
+use tracing::subscriber::Interest;
+use tracing::{Event, Level, Metadata, Subscriber};
+use tracing_subscriber::layer;
 use tracing_subscriber::prelude::*;
 use tracing_subscriber::Layer;
 
-pub fn init_tracing(service_name: &str, level: tracing::Level) -> Result<()> {
+use crate::redaction::{redactor, RedactingStdout};
+
+/// Number of error events kept by [RecentErrors].
+const MAX_RECENT_ERRORS: usize = 100;
+
+/// Logs are written to stdout, with the secrets registered in the [redactor](crate::redaction::redactor) redacted.
+pub fn init_tracing(service_name: &str, level: LogLevel, sampler: TracingSampler) -> Result<()> {
     let axum_layer = tracing_subscriber::EnvFilter::try_from_default_env()
         .unwrap_or_else(|_| "example_tracing_aka_logging=debug,tower_http=debug,axum::rejection=trace".into());
 
@@ -15,21 +30,274 @@ pub fn init_tracing(service_name: &str, level: tracing::Level) -> Result<()> {
         .with_line_number(true)
         .with_thread_ids(true)
         .with_target(false)
+        .with_writer(RedactingStdout)
         .pretty();
 
-    let filter = filter_fn(move |metadata| metadata.target() != "hyper" && metadata.level() <= &level);
-
-    let mut layers: Vec<Box<dyn Layer<_> + Send + Sync>> =
-        vec![Box::new(fmt_layer.with_filter(filter.clone())), Box::new(axum_layer.with_filter(filter.clone()))];
+    let mut layers: Vec<Box<dyn Layer<_> + Send + Sync>> = vec![
+        Box::new(fmt_layer.with_filter(level.clone())),
+        Box::new(axum_layer.with_filter(level.clone())),
+        Box::new(recent_errors().clone()),
+    ];
 
     // Check if the Axiom token is set
     if env::var("AXIOM_TOKEN").is_ok() {
         if let Ok(axiom_layer) = tracing_axiom::builder_with_env(service_name)?.with_dataset("pragma-node")?.build() {
-            layers.push(Box::new(axiom_layer.with_filter(filter.clone())));
+            layers.push(Box::new(axiom_layer.with_filter(level.clone())));
         }
     }
 
-    tracing_subscriber::registry().with(layers).try_init()?;
+    // The sampler wraps all the layers so each span/event is only sampled once.
+    tracing_subscriber::registry().with(layers.with_filter(sampler)).try_init()?;
 
     Ok(())
 }
+
+/// Maximum level of the spans & events logged, the `hyper` ones being always left out.
+/// The level is shared between clones so it can be changed at runtime.
+#[derive(Debug, Clone)]
+pub struct LogLevel(Arc<AtomicU8>);
+
+impl LogLevel {
+    pub fn new(level: Level) -> Self {
+        Self(Arc::new(AtomicU8::new(Self::to_u8(level))))
+    }
+
+    pub fn get(&self) -> Level {
+        match self.0.load(Ordering::Relaxed) {
+            0 => Level::ERROR,
+            1 => Level::WARN,
+            2 => Level::INFO,
+            3 => Level::DEBUG,
+            _ => Level::TRACE,
+        }
+    }
+
+    pub fn set(&self, level: Level) {
+        self.0.store(Self::to_u8(level), Ordering::Relaxed);
+    }
+
+    fn to_u8(level: Level) -> u8 {
+        match level {
+            Level::ERROR => 0,
+            Level::WARN => 1,
+            Level::INFO => 2,
+            Level::DEBUG => 3,
+            Level::TRACE => 4,
+        }
+    }
+}
+
+impl Default for LogLevel {
+    fn default() -> Self {
+        Self::new(Level::INFO)
+    }
+}
+
+impl<S> layer::Filter<S> for LogLevel {
+    fn enabled(&self, metadata: &Metadata<'_>, _cx: &layer::Context<'_, S>) -> bool {
+        metadata.target() != "hyper" && *metadata.level() <= self.get()
+    }
+
+    /// The level can change at runtime, so the result must never be cached per callsite.
+    fn callsite_enabled(&self, _metadata: &'static Metadata<'static>) -> Interest {
+        Interest::sometimes()
+    }
+}
+
+/// Spans & events whose target starts with `target` are kept with a probability of `rate`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SamplingRule {
+    pub target: String,
+    pub rate: f64,
+}
+
+impl SamplingRule {
+    pub fn new(target: impl Into<String>, rate: f64) -> Result<Self> {
+        if !(0.0..=1.0).contains(&rate) {
+            bail!("Sampling rate must be between 0 and 1, got {rate}");
+        }
+        Ok(Self { target: target.into(), rate })
+    }
+}
+
+/// Parses a rule formatted as `target=rate`, e.g. `theoros::handlers=0.01`.
+impl FromStr for SamplingRule {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (target, rate) =
+            s.split_once('=').with_context(|| format!("Invalid sampling rule `{s}`, expected target=rate"))?;
+        let rate = rate.trim().parse().with_context(|| format!("Invalid sampling rate in `{s}`"))?;
+        SamplingRule::new(target.trim(), rate)
+    }
+}
+
+#[derive(Debug)]
+struct ActiveRule {
+    rule: SamplingRule,
+    seen: AtomicU64,
+}
+
+impl ActiveRule {
+    /// Keeps one out of every `1 / rate` spans or events matching the rule.
+    fn keep(&self) -> bool {
+        if self.rule.rate >= 1.0 {
+            return true;
+        }
+        if self.rule.rate <= 0.0 {
+            return false;
+        }
+        let period = (1.0 / self.rule.rate).round() as u64;
+        self.seen.fetch_add(1, Ordering::Relaxed) % period == 0
+    }
+}
+
+/// Per-target sampling of spans & events, applied on top of the level filters.
+///
+/// Errors are always kept. Other spans & events use the rule with the longest
+/// matching target prefix, and are always kept when no rule matches.
+/// The rules are shared between clones so they can be updated at runtime.
+#[derive(Debug, Clone, Default)]
+pub struct TracingSampler(Arc<RwLock<Vec<ActiveRule>>>);
+
+impl TracingSampler {
+    pub fn new(rules: Vec<SamplingRule>) -> Self {
+        let sampler = Self::default();
+        sampler.set_rules(rules);
+        sampler
+    }
+
+    /// Returns the currently applied rules.
+    pub fn rules(&self) -> Vec<SamplingRule> {
+        self.0.read().expect("Sampling rules lock poisoned").iter().map(|active| active.rule.clone()).collect()
+    }
+
+    /// Replaces all the rules.
+    pub fn set_rules(&self, rules: Vec<SamplingRule>) {
+        let rules = rules.into_iter().map(|rule| ActiveRule { rule, seen: AtomicU64::new(0) }).collect();
+        *self.0.write().expect("Sampling rules lock poisoned") = rules;
+    }
+
+    fn should_keep(&self, metadata: &Metadata<'_>) -> bool {
+        if *metadata.level() == Level::ERROR {
+            return true;
+        }
+        let rules = self.0.read().expect("Sampling rules lock poisoned");
+        rules
+            .iter()
+            .filter(|active| metadata.target().starts_with(&active.rule.target))
+            .max_by_key(|active| active.rule.target.len())
+            .map_or(true, ActiveRule::keep)
+    }
+}
+
+impl<S> layer::Filter<S> for TracingSampler {
+    fn enabled(&self, metadata: &Metadata<'_>, _cx: &layer::Context<'_, S>) -> bool {
+        self.should_keep(metadata)
+    }
+
+    /// Rules can change at runtime, so the result must never be cached per callsite.
+    fn callsite_enabled(&self, _metadata: &'static Metadata<'static>) -> Interest {
+        Interest::sometimes()
+    }
+}
+
+/// An error event, with its secrets redacted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordedError {
+    pub at: SystemTime,
+    pub target: String,
+    /// The message of the event, followed by its other fields as `name=value`.
+    pub message: String,
+}
+
+/// Ring buffer of the most recent error events, to be inspected when diagnosing an instance.
+#[derive(Debug, Clone, Default)]
+pub struct RecentErrors(Arc<Mutex<VecDeque<RecordedError>>>);
+
+/// Returns the recent errors of the whole process, recorded once the tracing is initialized.
+pub fn recent_errors() -> &'static RecentErrors {
+    static RECENT_ERRORS: OnceLock<RecentErrors> = OnceLock::new();
+    RECENT_ERRORS.get_or_init(RecentErrors::default)
+}
+
+impl RecentErrors {
+    pub fn record(&self, target: &str, message: &str) {
+        let error = RecordedError {
+            at: SystemTime::now(),
+            target: target.to_owned(),
+            message: redactor().redact(message).into_owned(),
+        };
+        let mut errors = self.0.lock().expect("Recent errors lock poisoned");
+        errors.push_back(error);
+        if errors.len() > MAX_RECENT_ERRORS {
+            errors.pop_front();
+        }
+    }
+
+    /// Returns the recorded errors, most recent first.
+    pub fn all(&self) -> Vec<RecordedError> {
+        self.0.lock().expect("Recent errors lock poisoned").iter().rev().cloned().collect()
+    }
+}
+
+impl<S: Subscriber> Layer<S> for RecentErrors {
+    fn on_event(&self, event: &Event<'_>, _ctx: layer::Context<'_, S>) {
+        if *event.metadata().level() != Level::ERROR {
+            return;
+        }
+        let mut visitor = MessageVisitor::default();
+        event.record(&mut visitor);
+        self.record(event.metadata().target(), &visitor.0);
+    }
+}
+
+/// Formats the message of an event followed by its other fields.
+#[derive(Default)]
+struct MessageVisitor(String);
+
+impl Visit for MessageVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            self.0.insert_str(0, &format!("{value:?}"));
+        } else {
+            let _ = write!(self.0, " {}={:?}", field.name(), value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sampling_rule_from_str() {
+        let rule: SamplingRule = "theoros::handlers=0.01".parse().unwrap();
+        assert_eq!(rule, SamplingRule { target: "theoros::handlers".into(), rate: 0.01 });
+
+        assert!("theoros::handlers".parse::<SamplingRule>().is_err());
+        assert!("theoros::handlers=2".parse::<SamplingRule>().is_err());
+        assert!("theoros::handlers=abc".parse::<SamplingRule>().is_err());
+    }
+
+    #[test]
+    fn test_recent_errors_are_bounded() {
+        let errors = RecentErrors::default();
+        for i in 0..=MAX_RECENT_ERRORS {
+            errors.record("theoros", &format!("error {i}"));
+        }
+        let recorded = errors.all();
+        assert_eq!(recorded.len(), MAX_RECENT_ERRORS);
+        assert_eq!(recorded[0].message, format!("error {MAX_RECENT_ERRORS}"));
+    }
+
+    #[test]
+    fn test_active_rule_keeps_one_out_of_period() {
+        let active = ActiveRule { rule: SamplingRule::new("theoros", 0.25).unwrap(), seen: AtomicU64::new(0) };
+        let kept = (0..100).filter(|_| active.keep()).count();
+        assert_eq!(kept, 25);
+
+        let never = ActiveRule { rule: SamplingRule::new("theoros", 0.0).unwrap(), seen: AtomicU64::new(0) };
+        assert!((0..10).all(|_| !never.keep()));
+    }
+}
@@ -0,0 +1,233 @@
+use std::time::Duration;
+
+use reqwest::RequestBuilder;
+use serde::de::DeserializeOwned;
+use url::Url;
+
+use crate::{
+    errors::ClientError,
+    subscription::{FeedUpdatesSubscription, ReconnectPolicy},
+    types::{
+        BuildInfo, CalldataResponse, ChainCalldataResponse, ChainInfo, DataFeed, DataFeedsQuery, FeedId,
+        GetCalldataByIdResponse, GetDataFeedResponse, GetDataFeedsResponse, ProblemDetails,
+    },
+};
+
+/// Header identifying the client, giving it the limits & the priority of its API key.
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Client of the Theoros REST & WebSocket APIs.
+///
+/// Cheap to clone, the connections being pooled by the underlying HTTP client.
+#[derive(Debug, Clone)]
+pub struct TheorosClient {
+    http: reqwest::Client,
+    base_url: Url,
+    api_key: Option<String>,
+    consumer: Option<String>,
+    reconnect_policy: ReconnectPolicy,
+}
+
+impl TheorosClient {
+    /// Client of the Theoros instance served at `base_url`, e.g. `https://api.pragma.build/theoros`.
+    pub fn new(base_url: &str) -> Result<Self, ClientError> {
+        Self::builder(base_url)?.build()
+    }
+
+    pub fn builder(base_url: &str) -> Result<TheorosClientBuilder, ClientError> {
+        let mut base_url = Url::parse(base_url)?;
+        if !matches!(base_url.scheme(), "http" | "https") {
+            return Err(ClientError::UnsupportedScheme(base_url.scheme().to_owned()));
+        }
+        // Joined paths are relative to the base, which must then end with a slash.
+        if !base_url.path().ends_with('/') {
+            base_url.set_path(&format!("{}/", base_url.path()));
+        }
+        Ok(TheorosClientBuilder {
+            base_url,
+            api_key: None,
+            consumer: None,
+            timeout: DEFAULT_TIMEOUT,
+            reconnect_policy: ReconnectPolicy::default(),
+        })
+    }
+
+    pub fn base_url(&self) -> &Url {
+        &self.base_url
+    }
+
+    /// Whether the instance is up.
+    pub async fn health(&self) -> Result<(), ClientError> {
+        let response = self.http.get(self.url("health")?).send().await?;
+        Self::check(response).await.map(|_| ())
+    }
+
+    pub async fn version(&self) -> Result<BuildInfo, ClientError> {
+        self.send(self.get("v1/version")?).await
+    }
+
+    /// Chains the calldata is served for.
+    pub async fn chains(&self) -> Result<Vec<ChainInfo>, ClientError> {
+        self.send(self.get("v1/chains")?).await
+    }
+
+    /// A page of the feeds matching the query.
+    pub async fn data_feeds(&self, query: &DataFeedsQuery) -> Result<GetDataFeedsResponse, ClientError> {
+        self.send(self.get("v1/data_feeds")?.query(query)).await
+    }
+
+    /// All the feeds matching the query, following the pages.
+    pub async fn all_data_feeds(&self, query: &DataFeedsQuery) -> Result<Vec<DataFeed>, ClientError> {
+        let mut query = query.clone();
+        let mut data_feeds = Vec::new();
+        loop {
+            let page = self.data_feeds(&query).await?;
+            data_feeds.extend(page.data_feeds);
+            match page.next_cursor {
+                Some(cursor) => query.cursor = Some(cursor),
+                None => return Ok(data_feeds),
+            }
+        }
+    }
+
+    /// The feed & its latest update.
+    pub async fn data_feed(&self, feed_id: &FeedId) -> Result<GetDataFeedResponse, ClientError> {
+        self.send(self.get(&format!("v1/data_feeds/{feed_id}"))?).await
+    }
+
+    /// Calldata of the latest updates of the feeds for the chain, in the requested order. Encrypted with the key
+    /// of the consumer when one is configured.
+    pub async fn calldata(&self, chain: &str, feed_ids: &[FeedId]) -> Result<Vec<CalldataResponse>, ClientError> {
+        let feed_ids = feed_ids.iter().map(FeedId::as_str).collect::<Vec<_>>().join(",");
+        let request = self.get("v1/calldata")?.query(&[("chain", chain), ("feed_ids", &feed_ids)]);
+        self.send(self.with_consumer(request)).await
+    }
+
+    /// Calldata of the latest update of the feed for the chain, with the transaction sending it.
+    pub async fn chain_calldata(&self, chain: &str, feed_id: &FeedId) -> Result<ChainCalldataResponse, ClientError> {
+        let request = self.get(&format!("v1/calldata/{chain}/{feed_id}"))?;
+        self.send(self.with_consumer(request)).await
+    }
+
+    /// A calldata previously served, by its id.
+    pub async fn calldata_by_id(&self, calldata_id: &str) -> Result<GetCalldataByIdResponse, ClientError> {
+        self.send(self.get(&format!("v1/calldata/by-id/{calldata_id}"))?).await
+    }
+
+    /// Subscribes to the updates of the feeds for the chain through the WebSocket API, reconnecting &
+    /// resubscribing whenever the connection is lost.
+    pub fn subscribe(&self, chain: &str, feed_ids: &[FeedId]) -> Result<FeedUpdatesSubscription, ClientError> {
+        let mut url = self.url("v1/ws")?;
+        let scheme = if url.scheme() == "https" { "wss" } else { "ws" };
+        url.set_scheme(scheme).expect("HTTP URLs can be turned into WebSocket ones");
+        Ok(FeedUpdatesSubscription::spawn(
+            url,
+            self.api_key.clone(),
+            chain.to_owned(),
+            feed_ids.to_vec(),
+            self.reconnect_policy,
+        ))
+    }
+
+    fn url(&self, path: &str) -> Result<Url, ClientError> {
+        Ok(self.base_url.join(path)?)
+    }
+
+    fn get(&self, path: &str) -> Result<RequestBuilder, ClientError> {
+        let request = self.http.get(self.url(path)?);
+        Ok(match &self.api_key {
+            Some(api_key) => request.header(API_KEY_HEADER, api_key),
+            None => request,
+        })
+    }
+
+    fn with_consumer(&self, request: RequestBuilder) -> RequestBuilder {
+        match &self.consumer {
+            Some(consumer) => request.query(&[("consumer", consumer)]),
+            None => request,
+        }
+    }
+
+    async fn send<T: DeserializeOwned>(&self, request: RequestBuilder) -> Result<T, ClientError> {
+        let response = Self::check(request.send().await?).await?;
+        Ok(response.json().await?)
+    }
+
+    /// Maps the error responses to a [`ClientError`].
+    async fn check(response: reqwest::Response) -> Result<reqwest::Response, ClientError> {
+        let status = response.status();
+        if status.is_success() {
+            return Ok(response);
+        }
+        let body = response.text().await?;
+        Err(match serde_json::from_str::<ProblemDetails>(&body) {
+            Ok(problem) => ClientError::Api(problem),
+            Err(_) => ClientError::UnexpectedResponse { status: status.as_u16(), body },
+        })
+    }
+}
+
+/// Builder of a [`TheorosClient`], see [`TheorosClient::builder`].
+#[derive(Debug, Clone)]
+pub struct TheorosClientBuilder {
+    base_url: Url,
+    api_key: Option<String>,
+    consumer: Option<String>,
+    timeout: Duration,
+    reconnect_policy: ReconnectPolicy,
+}
+
+impl TheorosClientBuilder {
+    /// API key sent with every request.
+    pub fn with_api_key(mut self, api_key: impl Into<String>) -> Self {
+        self.api_key = Some(api_key.into());
+        self
+    }
+
+    /// Consumer whose registered key encrypts the served calldata.
+    pub fn with_consumer(mut self, consumer: impl Into<String>) -> Self {
+        self.consumer = Some(consumer.into());
+        self
+    }
+
+    /// Timeout of the REST requests, 10 seconds by default.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// How the WebSocket subscriptions reconnect.
+    pub fn with_reconnect_policy(mut self, reconnect_policy: ReconnectPolicy) -> Self {
+        self.reconnect_policy = reconnect_policy;
+        self
+    }
+
+    pub fn build(self) -> Result<TheorosClient, ClientError> {
+        let http = reqwest::Client::builder().timeout(self.timeout).build()?;
+        Ok(TheorosClient {
+            http,
+            base_url: self.base_url,
+            api_key: self.api_key,
+            consumer: self.consumer,
+            reconnect_policy: self.reconnect_policy,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_urls_are_relative_to_the_base_url() {
+        let client = TheorosClient::new("https://api.pragma.build/theoros").unwrap();
+        assert_eq!(client.url("v1/chains").unwrap().as_str(), "https://api.pragma.build/theoros/v1/chains");
+
+        let client = TheorosClient::new("http://localhost:3000/").unwrap();
+        assert_eq!(client.url("health").unwrap().as_str(), "http://localhost:3000/health");
+
+        assert!(matches!(TheorosClient::new("ftp://localhost"), Err(ClientError::UnsupportedScheme(_))));
+    }
+}
@@ -0,0 +1,53 @@
+use std::time::Duration;
+
+use crate::types::ProblemDetails;
+
+/// Error returned by the [`TheorosClient`](crate::TheorosClient).
+#[derive(Debug, thiserror::Error)]
+pub enum ClientError {
+    #[error("Invalid URL: {0}")]
+    InvalidUrl(#[from] url::ParseError),
+    #[error("Unsupported URL scheme \"{0}\", expected http or https")]
+    UnsupportedScheme(String),
+    #[error("Invalid feed ID \"{0}\"")]
+    InvalidFeedId(String),
+    #[error("HTTP error: {0}")]
+    Http(#[from] reqwest::Error),
+    /// The API answered with an RFC 7807 problem.
+    #[error("{} ({}): {}", .0.title, .0.code, .0.detail)]
+    Api(ProblemDetails),
+    /// The API answered with an error which isn't a problem, e.g. from a proxy in front of it.
+    #[error("Unexpected response with status {status}: {body}")]
+    UnexpectedResponse { status: u16, body: String },
+    #[error("WebSocket error: {0}")]
+    WebSocket(#[from] tokio_tungstenite::tungstenite::Error),
+    #[error("Could not parse message: {0}")]
+    InvalidMessage(#[from] serde_json::Error),
+    #[error("WebSocket connection closed by the server")]
+    ConnectionClosed,
+    #[error("No message received from the server for {0:?}")]
+    Idle(Duration),
+}
+
+impl ClientError {
+    /// Whether the same request may succeed later, without any change.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::Api(problem) => problem.retryable,
+            Self::UnexpectedResponse { status, .. } => *status >= 500,
+            Self::Http(error) => error.is_timeout() || error.is_connect(),
+            Self::WebSocket(_) | Self::ConnectionClosed | Self::Idle(_) => true,
+            Self::InvalidUrl(_) | Self::UnsupportedScheme(_) | Self::InvalidFeedId(_) | Self::InvalidMessage(_) => {
+                false
+            }
+        }
+    }
+
+    /// Machine readable code of the error answered by the API, e.g. `feed_not_found`.
+    pub fn code(&self) -> Option<&str> {
+        match self {
+            Self::Api(problem) => Some(&problem.code),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,28 @@
+//! Typed client of the Theoros REST & WebSocket APIs.
+//!
+//! ```no_run
+//! use theoros_client::{SubscriptionEvent, TheorosClient};
+//!
+//! # async fn run() -> Result<(), theoros_client::ClientError> {
+//! let client = TheorosClient::builder("https://api.pragma.build/theoros")?.with_api_key("my-key").build()?;
+//! let feed_id = "0x4254432f555344".parse()?;
+//! let calldata = client.chain_calldata("sepolia", &feed_id).await?;
+//!
+//! let mut subscription = client.subscribe("sepolia", &[feed_id])?;
+//! while let Some(event) = subscription.next_event().await {
+//!     if let SubscriptionEvent::Update(update) = event {
+//!         println!("{} updated by dispatch #{}", update.feed_id, update.nonce);
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+pub mod client;
+pub mod errors;
+pub mod subscription;
+pub mod types;
+
+pub use client::{TheorosClient, TheorosClientBuilder};
+pub use errors::ClientError;
+pub use subscription::{FeedUpdate, FeedUpdatesSubscription, ReconnectPolicy, Subscription, SubscriptionEvent};
+pub use types::FeedId;
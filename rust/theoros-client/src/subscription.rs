@@ -0,0 +1,359 @@
+use std::collections::BTreeSet;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use futures::{SinkExt, Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use tokio::{sync::mpsc, task::JoinHandle};
+use tokio_tungstenite::tungstenite::{self, client::IntoClientRequest, http::HeaderValue, Message};
+use url::Url;
+
+use crate::{
+    client::API_KEY_HEADER,
+    errors::ClientError,
+    types::{FeedId, UpdateView},
+};
+
+/// Number of events buffered until the subscription is polled, after which the connection stops being read.
+const EVENTS_CAPACITY: usize = 1024;
+
+/// How the WebSocket subscriptions reconnect once their connection is lost: after `initial_delay`, doubled at
+/// each failed attempt up to `max_delay`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReconnectPolicy {
+    pub initial_delay: Duration,
+    pub max_delay: Duration,
+    /// The connection is considered lost when nothing was received for this long, the server pinging every
+    /// 30 seconds.
+    pub idle_timeout: Duration,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            initial_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            idle_timeout: Duration::from_secs(90),
+        }
+    }
+}
+
+impl ReconnectPolicy {
+    /// Delay before the reconnection following `attempt` consecutive failed ones.
+    pub fn delay(&self, attempt: u32) -> Duration {
+        self.initial_delay.saturating_mul(1 << attempt.min(16)).min(self.max_delay)
+    }
+}
+
+/// A feed subscribed to, for a chain.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Subscription {
+    pub feed_id: FeedId,
+    pub chain: String,
+}
+
+/// A dispatch updating a subscribed feed reached quorum, with the calldata of the update for the chain.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct FeedUpdate {
+    /// Nonce of the Dispatch message of the update.
+    pub nonce: u32,
+    pub chain: String,
+    pub feed_id: FeedId,
+    /// Deterministic id of the calldata, see [`TheorosClient::calldata_by_id`](crate::TheorosClient::calldata_by_id).
+    pub calldata_id: String,
+    /// The calldata represented as a hex string.
+    pub encoded_calldata: String,
+    pub update: Option<UpdateView>,
+}
+
+impl FeedUpdate {
+    /// Bytes of the calldata.
+    pub fn calldata_bytes(&self) -> Result<Vec<u8>, hex::FromHexError> {
+        hex::decode(self.encoded_calldata.trim_start_matches("0x"))
+    }
+}
+
+/// Event of a [`FeedUpdatesSubscription`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SubscriptionEvent {
+    /// The server acknowledged the feeds subscribed to, once connected & every time they change.
+    Subscribed(Vec<Subscription>),
+    Update(FeedUpdate),
+    /// The connection was too slow to keep up: the updates of `skipped` dispatches were not pushed.
+    Lagged {
+        skipped: u64,
+    },
+    /// The server rejected a message, e.g. a subscription to an unsupported chain.
+    Rejected(String),
+    /// The connection was lost & is retried after `retry_in`. The updates dispatched until it is back are
+    /// missed, their calldata can be fetched through the REST API.
+    Disconnected {
+        error: String,
+        retry_in: Duration,
+    },
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ClientMessage<'a> {
+    Subscribe { feed_ids: Vec<&'a FeedId>, chain: &'a str },
+    Unsubscribe { feed_ids: &'a [FeedId] },
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ServerMessage {
+    Subscriptions {
+        subscriptions: Vec<Subscription>,
+    },
+    Error {
+        error: String,
+    },
+    FeedUpdate(FeedUpdate),
+    Lagged {
+        skipped: u64,
+    },
+    /// A message unknown to this version of the client.
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Debug)]
+enum Command {
+    Subscribe(Vec<FeedId>),
+    Unsubscribe(Vec<FeedId>),
+}
+
+/// Subscription to the updates of feeds through the `/v1/ws` WebSocket API, see
+/// [`TheorosClient::subscribe`](crate::TheorosClient::subscribe).
+///
+/// Reconnects & resubscribes to the feeds whenever the connection is lost. The connection is closed once
+/// dropped.
+#[derive(Debug)]
+pub struct FeedUpdatesSubscription {
+    events: mpsc::Receiver<SubscriptionEvent>,
+    commands: mpsc::UnboundedSender<Command>,
+    task: JoinHandle<()>,
+}
+
+impl FeedUpdatesSubscription {
+    /// Connects in a background task, so must be called within a Tokio runtime.
+    pub(crate) fn spawn(
+        url: Url,
+        api_key: Option<String>,
+        chain: String,
+        feed_ids: Vec<FeedId>,
+        policy: ReconnectPolicy,
+    ) -> Self {
+        let (events_sender, events) = mpsc::channel(EVENTS_CAPACITY);
+        let (commands, commands_receiver) = mpsc::unbounded_channel();
+        let subscriber = Subscriber {
+            url,
+            api_key,
+            chain,
+            feed_ids: feed_ids.into_iter().collect(),
+            policy,
+            events: events_sender,
+            commands: commands_receiver,
+        };
+        Self { events, commands, task: tokio::spawn(subscriber.run()) }
+    }
+
+    /// Next event of the subscription.
+    pub async fn next_event(&mut self) -> Option<SubscriptionEvent> {
+        self.events.recv().await
+    }
+
+    /// Also subscribes to the feeds, from now on & after every reconnection.
+    pub fn add_feed_ids(&self, feed_ids: Vec<FeedId>) {
+        let _ = self.commands.send(Command::Subscribe(feed_ids));
+    }
+
+    pub fn remove_feed_ids(&self, feed_ids: Vec<FeedId>) {
+        let _ = self.commands.send(Command::Unsubscribe(feed_ids));
+    }
+}
+
+impl Stream for FeedUpdatesSubscription {
+    type Item = SubscriptionEvent;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.events.poll_recv(cx)
+    }
+}
+
+impl Drop for FeedUpdatesSubscription {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Background task of a [`FeedUpdatesSubscription`], holding its connection.
+struct Subscriber {
+    url: Url,
+    api_key: Option<String>,
+    chain: String,
+    feed_ids: BTreeSet<FeedId>,
+    policy: ReconnectPolicy,
+    events: mpsc::Sender<SubscriptionEvent>,
+    commands: mpsc::UnboundedReceiver<Command>,
+}
+
+impl Subscriber {
+    async fn run(mut self) {
+        let mut attempt = 0;
+        loop {
+            let error = match self.connect(&mut attempt).await {
+                Ok(()) => return,
+                Err(error) => error,
+            };
+            let retry_in = self.policy.delay(attempt);
+            attempt = attempt.saturating_add(1);
+            tracing::warn!("🔌 Theoros WebSocket connection lost, reconnecting in {:?}: {}", retry_in, error);
+            let event = SubscriptionEvent::Disconnected { error: error.to_string(), retry_in };
+            if self.events.send(event).await.is_err() {
+                return;
+            }
+            tokio::time::sleep(retry_in).await;
+        }
+    }
+
+    /// Connects & forwards the events until the connection is lost. Returns `Ok` once the subscription is dropped.
+    async fn connect(&mut self, attempt: &mut u32) -> Result<(), ClientError> {
+        let mut request = self.url.as_str().into_client_request()?;
+        if let Some(api_key) = &self.api_key {
+            let api_key = HeaderValue::from_str(api_key).map_err(|e| tungstenite::Error::HttpFormat(e.into()))?;
+            request.headers_mut().insert(API_KEY_HEADER, api_key);
+        }
+        let (mut socket, _) = tokio_tungstenite::connect_async(request).await?;
+        if !self.feed_ids.is_empty() {
+            let subscribe = ClientMessage::Subscribe { feed_ids: self.feed_ids.iter().collect(), chain: &self.chain };
+            socket.send(Message::Text(serde_json::to_string(&subscribe)?)).await?;
+        }
+
+        loop {
+            tokio::select! {
+                message = tokio::time::timeout(self.policy.idle_timeout, socket.next()) => {
+                    let message = message.map_err(|_| ClientError::Idle(self.policy.idle_timeout))?;
+                    let text = match message {
+                        Some(Ok(Message::Text(text))) => text,
+                        Some(Ok(Message::Close(_))) | None => return Err(ClientError::ConnectionClosed),
+                        // Pings are answered by tungstenite.
+                        Some(Ok(_)) => continue,
+                        Some(Err(e)) => return Err(e.into()),
+                    };
+                    let event = match serde_json::from_str(&text)? {
+                        ServerMessage::Subscriptions { subscriptions } => {
+                            *attempt = 0;
+                            SubscriptionEvent::Subscribed(subscriptions)
+                        }
+                        ServerMessage::Error { error } => SubscriptionEvent::Rejected(error),
+                        ServerMessage::FeedUpdate(update) => SubscriptionEvent::Update(update),
+                        ServerMessage::Lagged { skipped } => SubscriptionEvent::Lagged { skipped },
+                        ServerMessage::Unknown => continue,
+                    };
+                    if self.events.send(event).await.is_err() {
+                        return Ok(());
+                    }
+                }
+                Some(command) = self.commands.recv() => {
+                    let message = match command {
+                        Command::Subscribe(feed_ids) => {
+                            self.feed_ids.extend(feed_ids.iter().cloned());
+                            ClientMessage::Subscribe { feed_ids: feed_ids.iter().collect(), chain: &self.chain }
+                        }
+                        Command::Unsubscribe(feed_ids) => {
+                            for feed_id in &feed_ids {
+                                self.feed_ids.remove(feed_id);
+                            }
+                            ClientMessage::Unsubscribe { feed_ids: &feed_ids }
+                        }
+                    };
+                    socket.send(Message::Text(serde_json::to_string(&message)?)).await?;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::net::TcpListener;
+
+    use super::*;
+
+    fn feed_id() -> FeedId {
+        "0x4254432f555344".parse().unwrap()
+    }
+
+    #[test]
+    fn test_server_messages() {
+        let update: ServerMessage = serde_json::from_str(
+            r#"{"type":"feed_update","nonce":42,"chain":"sepolia","feed_id":"0x4254432f555344","calldata_id":"0xab",
+            "encoded_calldata":"0x0102"}"#,
+        )
+        .unwrap();
+        let ServerMessage::FeedUpdate(update) = update else { panic!("Expected a feed update") };
+        assert_eq!(update.nonce, 42);
+        assert_eq!(update.calldata_bytes().unwrap(), vec![1, 2]);
+
+        let unknown: ServerMessage = serde_json::from_str(r#"{"type":"maintenance","until":1700000000}"#).unwrap();
+        assert!(matches!(unknown, ServerMessage::Unknown));
+    }
+
+    #[test]
+    fn test_reconnect_delay() {
+        let policy = ReconnectPolicy::default();
+        assert_eq!(policy.delay(0), Duration::from_millis(500));
+        assert_eq!(policy.delay(2), Duration::from_secs(2));
+        assert_eq!(policy.delay(u32::MAX), Duration::from_secs(30));
+    }
+
+    #[tokio::test]
+    async fn test_resubscribes_after_reconnecting() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = Url::parse(&format!("ws://{}/v1/ws", listener.local_addr().unwrap())).unwrap();
+        let server = tokio::spawn(async move {
+            // Acknowledges the subscription, pushes an update then drops the first connection.
+            for connection in 0..2 {
+                let (stream, _) = listener.accept().await.unwrap();
+                let mut socket = tokio_tungstenite::accept_async(stream).await.unwrap();
+                let Some(Ok(Message::Text(subscribe))) = socket.next().await else { panic!("Expected a subscription") };
+                assert_eq!(
+                    subscribe,
+                    r#"{"type":"subscribe","feed_ids":["0x4254432f555344"],"chain":"sepolia"}"#.to_string()
+                );
+                let ack =
+                    r#"{"type":"subscriptions","subscriptions":[{"feed_id":"0x4254432f555344","chain":"sepolia"}]}"#;
+                socket.send(Message::Text(ack.into())).await.unwrap();
+                if connection == 0 {
+                    let update = r#"{"type":"feed_update","nonce":1,"chain":"sepolia","feed_id":"0x4254432f555344",
+                        "calldata_id":"0xab","encoded_calldata":"0x01"}"#;
+                    socket.send(Message::Text(update.into())).await.unwrap();
+                    socket.close(None).await.unwrap();
+                } else {
+                    // Kept open until the client is done.
+                    let _ = socket.next().await;
+                }
+            }
+        });
+
+        let policy = ReconnectPolicy { initial_delay: Duration::from_millis(10), ..Default::default() };
+        let mut subscription =
+            FeedUpdatesSubscription::spawn(url, None, String::from("sepolia"), vec![feed_id()], policy);
+        let subscribed =
+            SubscriptionEvent::Subscribed(vec![Subscription { feed_id: feed_id(), chain: "sepolia".into() }]);
+
+        assert_eq!(subscription.next_event().await, Some(subscribed.clone()));
+        assert!(matches!(
+            subscription.next_event().await,
+            Some(SubscriptionEvent::Update(FeedUpdate { nonce: 1, .. }))
+        ));
+        assert!(matches!(subscription.next_event().await, Some(SubscriptionEvent::Disconnected { .. })));
+        assert_eq!(subscription.next_event().await, Some(subscribed));
+
+        drop(subscription);
+        server.await.unwrap();
+    }
+}
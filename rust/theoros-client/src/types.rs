@@ -0,0 +1,402 @@
+//! Responses of the Theoros API.
+//!
+//! Unknown fields are ignored & unknown variants of the enums are mapped to a catch-all one, so that additions
+//! to the API don't break the clients built against a previous version.
+
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use pragma_feeds::Feed;
+use serde::{Deserialize, Serialize};
+
+use crate::errors::ClientError;
+
+/// Hex encoded id of a feed, e.g. `0x4254432f555344`.
+///
+/// Only its encoding is checked, so that the feeds of types unknown to this version of the client can still be
+/// requested. See [`FeedId::feed`] to decode it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct FeedId(String);
+
+impl FeedId {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Decodes the asset class, the type & the pair of the feed.
+    pub fn feed(&self) -> anyhow::Result<Feed> {
+        Feed::from_str(&self.0)
+    }
+}
+
+impl FromStr for FeedId {
+    type Err = ClientError;
+
+    fn from_str(feed_id: &str) -> Result<Self, Self::Err> {
+        let digits = feed_id.strip_prefix("0x").ok_or_else(|| ClientError::InvalidFeedId(feed_id.to_owned()))?;
+        if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(ClientError::InvalidFeedId(feed_id.to_owned()));
+        }
+        Ok(Self(feed_id.to_owned()))
+    }
+}
+
+impl fmt::Display for FeedId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Error answered by the API, as defined by RFC 7807.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct ProblemDetails {
+    /// URI identifying the kind of error, e.g. `urn:theoros:error:feed_not_found`.
+    #[serde(rename = "type")]
+    pub problem_type: String,
+    pub title: String,
+    pub status: u16,
+    pub detail: String,
+    /// Machine readable code of the error, e.g. `feed_not_found`.
+    pub code: String,
+    /// Whether the same request may succeed later, without any change.
+    #[serde(default)]
+    pub retryable: bool,
+    /// Identifier of the request, to reference when reporting an issue.
+    pub request_id: Option<String>,
+}
+
+/// Whether a feed is still updated, according to the age of its latest update.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FeedStatus {
+    Fresh,
+    Stale,
+    /// No timestamped update of the feed was indexed yet, or a status unknown to this version of the client.
+    #[serde(other)]
+    Unknown,
+}
+
+/// Stage of a feed in its lifecycle. Retired feeds are still listed, but their calldata isn't served anymore.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FeedLifecycleState {
+    #[default]
+    Active,
+    Deprecated,
+    Retired,
+    /// A state unknown to this version of the client.
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct FeedLifecycle {
+    #[serde(default)]
+    pub state: FeedLifecycleState,
+    pub deprecated_at: Option<DateTime<Utc>>,
+    pub retired_at: Option<DateTime<Utc>>,
+    /// Feed to migrate to.
+    pub replaced_by: Option<FeedId>,
+}
+
+/// A feed served by Theoros, as listed by `/v1/data_feeds`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct DataFeed {
+    #[serde(flatten)]
+    pub feed: Feed,
+    pub lifecycle: FeedLifecycle,
+    pub status: FeedStatus,
+    /// Unix timestamp of the latest update of the feed, in seconds.
+    pub last_update_timestamp: Option<u64>,
+    /// Emitters which dispatched an update of the feed.
+    #[serde(default)]
+    pub emitters: Vec<String>,
+}
+
+/// A page of the feeds served by Theoros.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct GetDataFeedsResponse {
+    pub data_feeds: Vec<DataFeed>,
+    /// Number of feeds matching the filters, all pages included.
+    pub total: usize,
+    /// Cursor of the next page, if any.
+    pub next_cursor: Option<String>,
+}
+
+/// Filters of `/v1/data_feeds`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct DataFeedsQuery {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub asset_class: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub feed_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    /// Matched against the feed ids & the pairs.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub search: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<String>,
+}
+
+/// Values of an update, as served by the API.
+///
+/// Numbers that don't fit in a JSON number are decimal strings, to be scaled by `decimals`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum UpdateView {
+    SpotMedian {
+        /// Unix timestamp of the update, in seconds.
+        timestamp: u64,
+        num_sources_aggregated: u16,
+        decimals: u8,
+        price: String,
+        volume: String,
+    },
+    Perp {
+        /// Unix timestamp of the update, in seconds.
+        timestamp: u64,
+        num_sources_aggregated: u16,
+        decimals: u8,
+        mark_price: String,
+        funding_rate: String,
+        open_interest: String,
+        volume: String,
+    },
+    /// Update of a feed type unknown to the server.
+    Opaque {
+        feed_type: u16,
+        /// Raw update data, as a hex string.
+        data: String,
+    },
+    /// Update of a type unknown to this version of the client.
+    #[serde(other)]
+    Unsupported,
+}
+
+impl UpdateView {
+    /// Unix timestamp of the update, in seconds, if known.
+    pub fn timestamp(&self) -> Option<u64> {
+        match self {
+            Self::SpotMedian { timestamp, .. } | Self::Perp { timestamp, .. } => Some(*timestamp),
+            Self::Opaque { .. } | Self::Unsupported => None,
+        }
+    }
+}
+
+/// Signatures collected for the checkpoint of an update, for a chain.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct ChainCheckpointStatus {
+    pub chain: String,
+    pub signed: usize,
+    pub validators: usize,
+    pub fully_signed: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct LatestFeedUpdate {
+    /// Nonce of the Dispatch message of the update.
+    pub nonce: u32,
+    pub emitter_chain_id: u32,
+    pub emitter_address: String,
+    /// Name of the emitter, if registered.
+    pub emitter: Option<String>,
+    pub update: UpdateView,
+    #[serde(default)]
+    pub checkpoints: Vec<ChainCheckpointStatus>,
+}
+
+/// A feed & its latest update, as served by `/v1/data_feeds/{feed_id}`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct GetDataFeedResponse {
+    #[serde(flatten)]
+    pub feed: Feed,
+    pub lifecycle: FeedLifecycle,
+    pub status: FeedStatus,
+    /// Unix timestamp of the latest update of the feed, in seconds.
+    pub last_update_timestamp: Option<u64>,
+    pub latest_update: Option<LatestFeedUpdate>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct CalldataResponse {
+    pub feed_id: FeedId,
+    /// Deterministic id of the calldata, see [`TheorosClient::calldata_by_id`](crate::TheorosClient::calldata_by_id).
+    pub calldata_id: String,
+    /// The calldata represented as a hex string, encrypted when a consumer was provided.
+    pub encoded_calldata: String,
+    /// Id of the consumer key the calldata was encrypted with.
+    pub key_id: Option<String>,
+}
+
+impl CalldataResponse {
+    /// Bytes of the calldata.
+    pub fn calldata_bytes(&self) -> Result<Vec<u8>, hex::FromHexError> {
+        hex::decode(self.encoded_calldata.trim_start_matches("0x"))
+    }
+}
+
+/// Calldata built for a chain, as served by `/v1/calldata/{chain_name}/{feed_id}`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct ChainCalldataResponse {
+    #[serde(flatten)]
+    pub calldata: CalldataResponse,
+    pub chain: String,
+    /// Nonce of the Dispatch message of the update.
+    pub nonce: u32,
+    /// Number of validators signatures included in the calldata.
+    pub num_signatures: u8,
+    /// Pragma contract to send the update to, if configured for the chain.
+    pub contract_address: Option<String>,
+    /// ABI-encoded `updateDataFeeds([calldata])` call of the Pragma contract, as a hex string. Only served for
+    /// cleartext calldata.
+    pub transaction_data: Option<String>,
+}
+
+/// Versions defining the byte layout of the encoded calldata.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub struct EncoderVersion {
+    pub hyperlane_version: u8,
+    pub pragma_major_version: u8,
+    pub pragma_minor_version: u8,
+    pub trailing_header_size: u8,
+}
+
+/// A previously served calldata, as served by `/v1/calldata/by-id/{calldata_id}`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct GetCalldataByIdResponse {
+    /// The chain the calldata was built for.
+    pub chain: String,
+    /// Version of the encoder that produced the calldata layout.
+    pub encoder: EncoderVersion,
+    /// Commit of the Theoros build that encoded the calldata.
+    pub git_commit: String,
+    #[serde(flatten)]
+    pub calldata: CalldataResponse,
+}
+
+/// A chain served by Theoros.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct ChainInfo {
+    pub name: String,
+    /// EIP-155 id of the chain.
+    pub chain_id: u64,
+    /// Address of the Hyperlane contract verifying the calldata on the chain, its ISM.
+    pub ism_address: String,
+    pub validators: usize,
+    /// Number of signatures required by the ISM.
+    pub threshold: usize,
+    pub last_served_nonce: Option<u32>,
+}
+
+/// Identifies the running Theoros build.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct BuildInfo {
+    pub version: String,
+    pub git_commit: String,
+    #[serde(default)]
+    pub features: Vec<String>,
+    pub encoder: EncoderVersion,
+}
+
+#[cfg(test)]
+mod tests {
+    use pragma_feeds::FeedType;
+
+    use super::*;
+
+    #[test]
+    fn test_feed_id_from_str() {
+        let feed_id: FeedId = "0x4254432f555344".parse().unwrap();
+        assert_eq!(feed_id.to_string(), "0x4254432f555344");
+        assert_eq!(feed_id.feed().unwrap().pair_id, "BTC/USD");
+
+        for invalid in ["", "0x", "4254432f555344", "0xBTC/USD"] {
+            assert!(matches!(invalid.parse::<FeedId>(), Err(ClientError::InvalidFeedId(_))), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn test_data_feed_response() {
+        let response: GetDataFeedResponse = serde_json::from_value(serde_json::json!({
+            "feed_id": "0x4254432f555344",
+            "asset_class": "Crypto",
+            "feed_type": "UniqueSpotMedian",
+            "pair_id": "BTC/USD",
+            "lifecycle": {"state": "deprecated", "replaced_by": "0x4254432f55534454"},
+            "status": "fresh",
+            "last_update_timestamp": 1_700_000_000,
+            "latest_update": {
+                "nonce": 42,
+                "emitter_chain_id": 6_363_709,
+                "emitter_address": "0x1",
+                "update": {
+                    "type": "spot_median",
+                    "timestamp": 1_700_000_000,
+                    "num_sources_aggregated": 5,
+                    "decimals": 8,
+                    "price": "6500000000000",
+                    "volume": "0"
+                },
+                "checkpoints": [{"chain": "sepolia", "signed": 2, "validators": 3, "fully_signed": false}]
+            },
+            "added_in_a_later_version": true
+        }))
+        .unwrap();
+
+        assert_eq!(response.feed.feed_type, FeedType::UniqueSpotMedian);
+        assert_eq!(response.lifecycle.state, FeedLifecycleState::Deprecated);
+        assert_eq!(response.status, FeedStatus::Fresh);
+        let latest_update = response.latest_update.unwrap();
+        assert_eq!(latest_update.update.timestamp(), Some(1_700_000_000));
+        assert_eq!(latest_update.checkpoints[0].signed, 2);
+    }
+
+    #[test]
+    fn test_unknown_variants_are_tolerated() {
+        let update: UpdateView = serde_json::from_str(r#"{"type":"options","strike":"1"}"#).unwrap();
+        assert_eq!(update, UpdateView::Unsupported);
+        let status: FeedStatus = serde_json::from_str(r#""paused""#).unwrap();
+        assert_eq!(status, FeedStatus::Unknown);
+    }
+
+    #[test]
+    fn test_chain_calldata_response() {
+        let response: ChainCalldataResponse = serde_json::from_str(
+            r#"{"feed_id":"0x4254432f555344","calldata_id":"0xab","encoded_calldata":"0x0102","chain":"sepolia",
+            "nonce":42,"num_signatures":2,"contract_address":"0xcafe"}"#,
+        )
+        .unwrap();
+        assert_eq!(response.calldata.calldata_bytes().unwrap(), vec![1, 2]);
+        assert_eq!(response.calldata.key_id, None);
+        assert_eq!(response.transaction_data, None);
+    }
+
+    /// The payloads locked by the fixtures of Theoros are parsed.
+    #[test]
+    fn test_api_fixtures() {
+        let calldata: CalldataResponse =
+            serde_json::from_str(include_str!("../../theoros/fixtures/api/v1/calldata.json")).unwrap();
+        assert_eq!(calldata.key_id.as_deref(), Some("consumer-1"));
+        let calldata: ChainCalldataResponse =
+            serde_json::from_str(include_str!("../../theoros/fixtures/api/v1/chain_calldata.json")).unwrap();
+        assert_eq!(calldata.chain, "sepolia");
+        let calldata: GetCalldataByIdResponse =
+            serde_json::from_str(include_str!("../../theoros/fixtures/api/v1/calldata_by_id.json")).unwrap();
+        assert_eq!(calldata.encoder.hyperlane_version, 3);
+
+        let data_feeds: GetDataFeedsResponse =
+            serde_json::from_str(include_str!("../../theoros/fixtures/api/v1/data_feeds.json")).unwrap();
+        assert_eq!(data_feeds.data_feeds[0].lifecycle.state, FeedLifecycleState::Deprecated);
+        let data_feed: GetDataFeedResponse =
+            serde_json::from_str(include_str!("../../theoros/fixtures/api/v1/data_feed.json")).unwrap();
+        assert_eq!(data_feed.latest_update.unwrap().nonce, 42);
+        let chains: Vec<ChainInfo> =
+            serde_json::from_str(include_str!("../../theoros/fixtures/api/v1/chains.json")).unwrap();
+        assert_eq!(chains[0].chain_id, 11_155_111);
+    }
+}
@@ -0,0 +1,168 @@
+//! Benchmarks of the decoding of the Dispatch events & of the assembly of the calldata, the hot paths of the
+//! indexer & of the calldata API.
+//!
+//! Run with `cargo bench -p theoros --bench dispatch`.
+
+use std::hint::black_box;
+
+use alloy::primitives::{Parity, U256};
+use alloy::signers::Signature;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use serde::Deserialize;
+use starknet::core::types::Felt;
+
+use theoros::{
+    rpc::evm::calldata::encode_spot_median,
+    types::{
+        calldata::{AsCalldata, Calldata, HyperlaneMessage, Payload, ValidatorSignature},
+        hyperlane::{
+            Checkpoint, CheckpointWithMessageId, DispatchEvent, DispatchMessageBody, DispatchUpdate,
+            FromStarknetEventData,
+        },
+    },
+};
+
+const DISPATCH_EVENT_FIXTURE: &str = include_str!("../fixtures/dispatch_event.json");
+/// Felts of the Dispatch event before its message body: the event fields & the message header.
+const HEADER_FELTS: usize = 15;
+
+/// Data of the bundled Dispatch event, with a single spot median update.
+fn fixture_data() -> Vec<Felt> {
+    #[derive(Deserialize)]
+    struct Fixture {
+        data: Vec<String>,
+    }
+    let fixture: Fixture = serde_json::from_str(DISPATCH_EVENT_FIXTURE).unwrap();
+    fixture.data.iter().map(|felt| Felt::from_hex(felt).unwrap()).collect()
+}
+
+/// Bytes of a spot median update of the feed.
+fn spot_median_update(pair: &[u8; 12]) -> Vec<u8> {
+    [
+        [0, 0, 0, 0].as_slice(),
+        &[0u8; 16],
+        pair,
+        &1_700_000_000_u64.to_be_bytes(),
+        &5_u16.to_be_bytes(),
+        &[8],
+        &[0u8; 16],
+        &6_500_000_000_000_u128.to_be_bytes(),
+        &[0u8; 32],
+    ]
+    .concat()
+}
+
+/// Message body with `num_updates` spot median updates, packed into felts of 16 bytes as emitted by the
+/// Starknet contract.
+fn body_felts(num_updates: u8) -> Vec<Felt> {
+    let mut bytes = vec![num_updates];
+    for i in 0..num_updates {
+        let mut pair = *b"PAIR/USD\0\0\0\0";
+        pair[8] = i;
+        bytes.extend_from_slice(&spot_median_update(&pair));
+    }
+    bytes
+        .chunks(16)
+        .map(|chunk| {
+            let mut padded = [0u8; 32];
+            padded[16..16 + chunk.len()].copy_from_slice(chunk);
+            Felt::from_bytes_be(&padded)
+        })
+        .collect()
+}
+
+fn calldata(num_signatures: u8, update_data: Vec<u8>) -> Calldata {
+    let signatures: Vec<ValidatorSignature> = (0..num_signatures)
+        .map(|validator_index| ValidatorSignature {
+            validator_index,
+            signature: Signature::new(U256::from(1), U256::from(2), Parity::Parity(false)),
+        })
+        .collect();
+    let payload = Payload {
+        checkpoint: CheckpointWithMessageId {
+            checkpoint: Checkpoint {
+                merkle_tree_hook_address: U256::from(1),
+                mailbox_domain: 6_363_709,
+                root: String::from("0x1"),
+                index: 42,
+            },
+            message_id: U256::from(2),
+        },
+        num_updates: 1,
+        proof_len: 0,
+        proof: vec![],
+        update_data_len: update_data.len() as u16,
+        update_data,
+        feed_id: U256::from(3),
+        publish_time: 1_700_000_000,
+    };
+    let hyperlane_msg = HyperlaneMessage {
+        hyperlane_version: 3,
+        signers_len: num_signatures,
+        signatures,
+        nonce: 42,
+        timestamp: 1_700_000_000,
+        emitter_chain_id: 6_363_709,
+        emitter_address: Felt::ONE,
+        payload,
+    };
+    Calldata {
+        major_version: 1,
+        minor_version: 0,
+        trailing_header_size: 0,
+        hyperlane_msg_size: hyperlane_msg.as_bytes().len() as u16,
+        hyperlane_msg,
+    }
+}
+
+fn bench_dispatch_event(c: &mut Criterion) {
+    let data = fixture_data();
+    let mut group = c.benchmark_group("dispatch_event");
+    group.bench_function("fixture", |b| {
+        b.iter(|| DispatchEvent::from_starknet_event_data(black_box(data.clone())).unwrap())
+    });
+    for num_updates in [16_u8, 255] {
+        let data: Vec<Felt> = data[..HEADER_FELTS].iter().copied().chain(body_felts(num_updates)).collect();
+        group.throughput(Throughput::Elements(num_updates as u64));
+        group.bench_with_input(BenchmarkId::new("updates", num_updates), &data, |b, data| {
+            b.iter(|| DispatchEvent::from_starknet_event_data(black_box(data.clone())).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn bench_dispatch_body(c: &mut Criterion) {
+    let mut group = c.benchmark_group("dispatch_body");
+    for num_updates in [1_u8, 16, 64, 255] {
+        let felts = body_felts(num_updates);
+        group.throughput(Throughput::Elements(num_updates as u64));
+        group.bench_with_input(BenchmarkId::new("updates", num_updates), &felts, |b, felts| {
+            b.iter(|| {
+                let body = DispatchMessageBody::from_starknet_event_data(black_box(felts.clone())).unwrap();
+                assert_eq!(body.updates.len(), num_updates as usize);
+                body
+            })
+        });
+    }
+    group.finish();
+}
+
+fn bench_calldata(c: &mut Criterion) {
+    let event = DispatchEvent::from_starknet_event_data(fixture_data()).unwrap();
+    let Some(DispatchUpdate::SpotMedian { update, .. }) = event.message.body.updates.first() else {
+        panic!("The fixture should contain a spot median update");
+    };
+
+    let mut group = c.benchmark_group("calldata");
+    group.bench_function("encode_spot_median", |b| b.iter(|| encode_spot_median(black_box(update))));
+    for num_signatures in [1_u8, 5, 15] {
+        let calldata = calldata(num_signatures, encode_spot_median(update));
+        group.bench_with_input(BenchmarkId::new("as_bytes", num_signatures), &calldata, |b, calldata| {
+            b.iter(|| black_box(calldata).as_bytes())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_dispatch_event, bench_dispatch_body, bench_calldata);
+criterion_main!(benches);
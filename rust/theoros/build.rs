@@ -0,0 +1,40 @@
+//! Embeds the commit Theoros is built from, so served payloads can be tied to the code that encoded them.
+//! The `GIT_COMMIT` environment variable takes precedence, e.g. for Docker builds without the `.git` directory.
+//! With the `grpc` feature, also generates the gRPC API from its protos, which requires `protoc`.
+use std::process::Command;
+
+fn main() {
+    println!("cargo:rerun-if-env-changed=GIT_COMMIT");
+
+    let git_commit = match std::env::var("GIT_COMMIT") {
+        Ok(commit) if !commit.is_empty() => commit,
+        _ => {
+            watch_git_head();
+            git(&["rev-parse", "HEAD"]).unwrap_or_else(|| String::from("unknown"))
+        }
+    };
+    println!("cargo:rustc-env=THEOROS_GIT_COMMIT={git_commit}");
+
+    #[cfg(feature = "grpc")]
+    tonic_build::compile_protos("proto/theoros/v1/theoros.proto").expect("Failed to compile the gRPC API protos");
+}
+
+/// Rebuilds when the checked out commit changes.
+fn watch_git_head() {
+    if let Some(head) = git(&["rev-parse", "--path-format=absolute", "--git-path", "HEAD"]) {
+        println!("cargo:rerun-if-changed={head}");
+    }
+    if let Some(reference) = git(&["symbolic-ref", "-q", "HEAD"]) {
+        if let Some(path) = git(&["rev-parse", "--path-format=absolute", "--git-path", &reference]) {
+            println!("cargo:rerun-if-changed={path}");
+        }
+    }
+}
+
+fn git(args: &[&str]) -> Option<String> {
+    let output = Command::new("git").args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8(output.stdout).ok().map(|s| s.trim().to_owned())
+}
@@ -0,0 +1,11 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use theoros::types::hyperlane::DispatchMessageBody;
+use theoros_fuzz::body_felts;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(body) = DispatchMessageBody::from_felts(&body_felts(data)) {
+        assert!(body.updates.len() + body.parse_failures.len() <= body.nb_updated as usize);
+    }
+});
@@ -0,0 +1,11 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use theoros::types::hyperlane::{DispatchEvent, FromStarknetEventData};
+use theoros_fuzz::felts;
+
+fuzz_target!(|data: &[u8]| {
+    let felts = felts(data);
+    let _ = DispatchEvent::destination_domain_of(&felts);
+    let _ = DispatchEvent::from_starknet_event_data(felts);
+});
@@ -0,0 +1,9 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use theoros::types::hyperlane::DispatchMessageHeader;
+use theoros_fuzz::felts;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = DispatchMessageHeader::from_felts(&felts(data));
+});
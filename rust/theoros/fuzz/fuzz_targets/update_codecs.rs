@@ -0,0 +1,18 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use starknet::core::types::U256;
+use theoros::types::hyperlane::UPDATE_CODECS;
+
+// The first byte selects the codec, the others are the fields of the update following its pair id.
+fuzz_target!(|data: &[u8]| {
+    let Some((selector, fields)) = data.split_first() else {
+        return;
+    };
+    let keys = UPDATE_CODECS.keys();
+    let (asset_class, feed_type) = keys[*selector as usize % keys.len()];
+    let codec = UPDATE_CODECS.get(asset_class, feed_type).unwrap();
+    if let Ok((_, consumed)) = codec.decode(String::from("0x00"), U256::from(0_u8), fields) {
+        assert!(consumed <= fields.len());
+    }
+});
@@ -0,0 +1,27 @@
+//! Fuzz targets of the parsers of the on-chain events, which must never panic whatever the data emitted.
+//!
+//! Run with `cargo +nightly fuzz run <target>` from this directory, e.g. `cargo +nightly fuzz run dispatch_body`.
+
+use starknet::core::types::Felt;
+
+/// Reads the fuzzed bytes as felts of 32 bytes, reduced modulo the field prime. The last felt is left-padded.
+pub fn felts(data: &[u8]) -> Vec<Felt> {
+    data.chunks(32)
+        .map(|chunk| {
+            let mut padded = [0u8; 32];
+            padded[32 - chunk.len()..].copy_from_slice(chunk);
+            Felt::from_bytes_be(&padded)
+        })
+        .collect()
+}
+
+/// Packs the fuzzed bytes into felts of 16 bytes, as the Starknet contract emits the message body.
+pub fn body_felts(data: &[u8]) -> Vec<Felt> {
+    data.chunks(16)
+        .map(|chunk| {
+            let mut padded = [0u8; 32];
+            padded[16..16 + chunk.len()].copy_from_slice(chunk);
+            Felt::from_bytes_be(&padded)
+        })
+        .collect()
+}
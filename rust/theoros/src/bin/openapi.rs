@@ -0,0 +1,34 @@
+//! Writes the OpenAPI spec of the API into the `openapi.json` snapshot, or checks that the snapshot is up to
+//! date, e.g. in the CI.
+use std::path::PathBuf;
+
+use anyhow::{bail, Result};
+use clap::Parser;
+
+use theoros::services::api::docs::{ApiDoc, OPENAPI_SNAPSHOT};
+
+#[derive(clap::Parser, Debug)]
+struct OpenApiCli {
+    /// Fails if the snapshot isn't the spec of the API, instead of writing it.
+    #[clap(long, default_value = "false")]
+    check: bool,
+
+    /// Directory of the snapshot. Defaults to the root of the crate.
+    #[clap(long, default_value = env!("CARGO_MANIFEST_DIR"))]
+    output_dir: PathBuf,
+}
+
+fn main() -> Result<()> {
+    let cli = OpenApiCli::parse();
+    let snapshot = cli.output_dir.join(OPENAPI_SNAPSHOT);
+    if !cli.check {
+        ApiDoc::generate_openapi_json(cli.output_dir)?;
+        println!("Wrote the OpenAPI spec to {}", snapshot.display());
+        return Ok(());
+    }
+    if !ApiDoc::is_snapshot_up_to_date(&cli.output_dir)? {
+        bail!("{} is outdated, update it with `cargo run -p theoros --bin openapi`", snapshot.display());
+    }
+    println!("{} is up to date", snapshot.display());
+    Ok(())
+}
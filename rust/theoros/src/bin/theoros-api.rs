@@ -0,0 +1,18 @@
+//! Serves the REST, WebSocket & gRPC APIs from the state persisted into the Postgres storage backend by
+//! `theoros-indexer`, so the API can be scaled apart from the indexing.
+//!
+//! The settings changed through the admin API (consumer keys, private feeds, feed lifecycles, chain statuses &
+//! tracing sampling rules) are persisted & followed by every replica & by `theoros-indexer`. The other admin calls,
+//! e.g. clearing the quarantine, evicting the caches or applying an EVM config (but for its chain statuses), only
+//! apply to the replica serving them: send them to every replica.
+use anyhow::Result;
+use clap::Parser;
+
+use theoros::{cli::TheorosCli, Components};
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let config = TheorosCli::parse();
+    theoros::register_secrets(&config);
+    theoros::run(&config, Components::Api).await
+}
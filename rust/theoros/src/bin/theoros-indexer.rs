@@ -0,0 +1,13 @@
+//! Indexes the Pragma dispatches & collects the signatures of the validators into the Postgres storage backend,
+//! from which `theoros-api` serves them.
+use anyhow::Result;
+use clap::Parser;
+
+use theoros::{cli::TheorosCli, Components};
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let config = TheorosCli::parse();
+    theoros::register_secrets(&config);
+    theoros::run(&config, Components::Indexer).await
+}
@@ -0,0 +1,92 @@
+use std::str::FromStr;
+
+use anyhow::{anyhow, bail, Context, Result};
+use starknet::core::types::Felt;
+
+/// A Pragma dispatcher contract, sending the updates of the feeds through the Hyperlane mailbox.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Emitter {
+    pub name: String,
+    pub address: Felt,
+}
+
+impl FromStr for Emitter {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (name, address) = s.split_once('=').ok_or_else(|| anyhow!("Expected `{{name}}={{address}}`, got `{s}`"))?;
+        let address = Felt::from_hex(address.trim()).with_context(|| format!("Invalid emitter address: {address}"))?;
+        Ok(Self { name: name.trim().to_owned(), address })
+    }
+}
+
+/// The Pragma dispatchers whose dispatches are indexed, e.g. the production & the staging ones.
+///
+/// The first one is served: its updates back the calldata & the feeds API. The updates of the others are kept
+/// apart, by emitter, & only read through the feeds API. When none is configured, every dispatch is indexed &
+/// served, whoever sent it.
+#[derive(Debug, Clone, Default)]
+pub struct Emitters(Vec<Emitter>);
+
+impl Emitters {
+    pub fn new(emitters: Vec<Emitter>) -> Result<Self> {
+        for (i, emitter) in emitters.iter().enumerate() {
+            if emitter.name.is_empty() {
+                bail!("The emitter {:#x} has no name", emitter.address);
+            }
+            if emitters[..i].iter().any(|other| other.name == emitter.name || other.address == emitter.address) {
+                bail!("The emitter `{}` ({:#x}) is configured twice", emitter.name, emitter.address);
+            }
+        }
+        Ok(Self(emitters))
+    }
+
+    /// Whether the dispatches sent by the address are indexed.
+    pub fn is_indexed(&self, address: &Felt) -> bool {
+        self.0.is_empty() || self.get(address).is_some()
+    }
+
+    /// Whether the updates dispatched by the address are served.
+    pub fn is_served(&self, address: &Felt) -> bool {
+        self.0.first().map_or(true, |served| served.address == *address)
+    }
+
+    /// The configured emitter with this address.
+    pub fn get(&self, address: &Felt) -> Option<&Emitter> {
+        self.0.iter().find(|emitter| emitter.address == *address)
+    }
+
+    /// The configured emitter with this name.
+    pub fn by_name(&self, name: &str) -> Option<&Emitter> {
+        self.0.iter().find(|emitter| emitter.name == name)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Emitter> {
+        self.0.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_emitters() {
+        let prod: Emitter = "prod=0x1".parse().unwrap();
+        let staging: Emitter = " staging = 0x2 ".parse().unwrap();
+        assert_eq!(staging, Emitter { name: String::from("staging"), address: Felt::TWO });
+        assert!("0x1".parse::<Emitter>().is_err());
+
+        let emitters = Emitters::new(vec![prod.clone(), staging]).unwrap();
+        assert!(emitters.is_indexed(&Felt::TWO) && !emitters.is_indexed(&Felt::THREE));
+        assert!(emitters.is_served(&Felt::ONE) && !emitters.is_served(&Felt::TWO));
+        assert_eq!(emitters.by_name("staging").map(|emitter| emitter.address), Some(Felt::TWO));
+
+        // Every dispatch is indexed & served when no emitter is configured.
+        let all = Emitters::default();
+        assert!(all.is_indexed(&Felt::THREE) && all.is_served(&Felt::THREE));
+
+        let duplicate = Emitter { name: String::from("prod-2"), ..prod.clone() };
+        assert!(Emitters::new(vec![prod, duplicate]).is_err());
+    }
+}
@@ -0,0 +1,105 @@
+use serde_yaml::{Mapping, Value};
+
+/// Prefix of the environment variables overriding the fields of the config files.
+pub const ENV_OVERRIDES_PREFIX: &str = "THEOROS";
+/// Separator of the segments of the overridden field, e.g. `THEOROS__EVM__MAINNET__RPC_URL`.
+const SEPARATOR: &str = "__";
+
+/// Overrides the fields of the `section` of a config with the environment variables named after them, so
+/// secrets like the RPC API keys don't need to live in the config files.
+///
+/// `THEOROS__EVM__MAINNET__RPC_URL` sets the `rpc_url` of the `mainnet` chain of the `EVM` section, adding the
+/// chain if missing. Values are parsed as YAML, e.g. `true` or `[a, b]`, hexadecimal numbers being kept as
+/// strings as they are addresses. Fails on a variable setting a field inside a value which isn't a mapping.
+pub fn apply_env_overrides(
+    config: &mut Value,
+    section: &str,
+    vars: impl IntoIterator<Item = (String, String)>,
+) -> Result<(), String> {
+    let prefix = format!("{ENV_OVERRIDES_PREFIX}{SEPARATOR}{}{SEPARATOR}", section.to_uppercase());
+    let mut overrides: Vec<(String, String)> =
+        vars.into_iter().filter(|(name, _)| name.starts_with(&prefix) && name.len() > prefix.len()).collect();
+    // Applied in a stable order, so the deeper fields override the values of their parents.
+    overrides.sort();
+    for (name, raw) in overrides {
+        let path: Vec<String> = name[prefix.len()..].split(SEPARATOR).map(str::to_lowercase).collect();
+        set_field(config, &path, parse_value(&raw)).map_err(|field| format!("{name}: `{field}` is not a mapping"))?;
+    }
+    Ok(())
+}
+
+/// Sets the field at `path`, creating its missing parents. Returns the field that isn't a mapping, if any.
+fn set_field(config: &mut Value, path: &[String], value: Value) -> Result<(), String> {
+    let Some((field, parents)) = path.split_last() else {
+        return Ok(());
+    };
+    let mut current = config;
+    for parent in parents {
+        if current.is_null() {
+            *current = Value::Mapping(Mapping::new());
+        }
+        let mapping = current.as_mapping_mut().ok_or_else(|| parent.clone())?;
+        current = mapping.entry(Value::String(parent.clone())).or_insert(Value::Null);
+    }
+    if current.is_null() {
+        *current = Value::Mapping(Mapping::new());
+    }
+    let mapping = current.as_mapping_mut().ok_or_else(|| field.clone())?;
+    mapping.insert(Value::String(field.clone()), value);
+    Ok(())
+}
+
+fn parse_value(raw: &str) -> Value {
+    match serde_yaml::from_str::<Value>(raw) {
+        Ok(Value::Number(_)) if !raw.trim().chars().all(|c| c.is_ascii_digit()) => Value::String(raw.to_owned()),
+        Ok(value @ (Value::Bool(_) | Value::Number(_) | Value::Sequence(_) | Value::Mapping(_))) => value,
+        _ => Value::String(raw.to_owned()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars(vars: &[(&str, &str)]) -> Vec<(String, String)> {
+        vars.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect()
+    }
+
+    #[test]
+    fn test_env_overrides() {
+        let mut config: Value =
+            serde_yaml::from_str("mainnet:\n  rpc_url: https://public.rpc\n  enabled: true\n").unwrap();
+        apply_env_overrides(
+            &mut config,
+            "evm",
+            vars(&[
+                ("THEOROS__EVM__MAINNET__RPC_URL", "https://rpc.example/secret-key"),
+                ("THEOROS__EVM__MAINNET__ENABLED", "false"),
+                ("THEOROS__EVM__MAINNET__FALLBACK_RPC_URLS", "[https://a.example, https://b.example]"),
+                ("THEOROS__EVM__SEPOLIA__HYPERLANE_ADDRESS", "0x45996486a06106b3D6Dce022A9d8BDDd5184c537"),
+                ("THEOROS__SETTINGS__LOG_LEVEL", "debug"),
+                ("PATH", "/usr/bin"),
+            ]),
+        )
+        .unwrap();
+
+        let expected: Value = serde_yaml::from_str(
+            "mainnet:
+  rpc_url: https://rpc.example/secret-key
+  enabled: false
+  fallback_rpc_urls: [https://a.example, https://b.example]
+sepolia:
+  hyperlane_address: '0x45996486a06106b3D6Dce022A9d8BDDd5184c537'
+",
+        )
+        .unwrap();
+        assert_eq!(config, expected);
+
+        let invalid = vars(&[("THEOROS__EVM__MAINNET__RPC_URL__KEY", "secret")]);
+        assert!(apply_env_overrides(&mut config, "evm", invalid).is_err());
+
+        let mut empty = Value::Null;
+        apply_env_overrides(&mut empty, "settings", vars(&[("THEOROS__SETTINGS__RATE_LIMIT_PER_IP", "100")])).unwrap();
+        assert_eq!(empty, serde_yaml::from_str::<Value>("rate_limit_per_ip: 100").unwrap());
+    }
+}
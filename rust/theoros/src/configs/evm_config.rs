@@ -1,16 +1,27 @@
+use alloy::primitives::Address;
+use alloy::signers::local::PrivateKeySigner;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
 use std::fs;
-use std::path::Path;
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
 use strum_macros::EnumString;
 use thiserror::Error;
+use url::Url;
+use utoipa::ToSchema;
+
+use crate::configs::env_overrides::apply_env_overrides;
 
 pub const DEFAULT_CONFIG_PATH: &str = "evm_config.yaml";
 
 /// Supported Chain identifiers
 // Must reflect the EVM chains here:
 // https://github.com/astraly-labs/pragma-monorepo/blob/main/typescript/pragma-utils/src/chains.ts
-#[derive(Debug, strum_macros::Display, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, EnumString)]
+#[derive(
+    Debug, strum_macros::Display, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, EnumString, ToSchema,
+)]
 #[strum(serialize_all = "snake_case")]
 #[serde(rename_all = "snake_case")]
 #[strum(ascii_case_insensitive)]
@@ -39,38 +50,360 @@ pub enum EvmChainName {
     ZksyncTestnet,
 }
 
+/// Native token of a chain, in which its gas & fees are paid
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct NativeToken {
+    pub symbol: String,
+    pub decimals: u8,
+}
+
+impl NativeToken {
+    fn new(symbol: &str) -> Self {
+        Self { symbol: symbol.into(), decimals: 18 }
+    }
+}
+
+impl EvmChainName {
+    /// The native token of the chain
+    pub fn native_token(&self) -> NativeToken {
+        match self {
+            Self::Bsc | Self::BscTestnet => NativeToken::new("BNB"),
+            Self::Polygon | Self::PolygonTestnet => NativeToken::new("POL"),
+            Self::Avalanche => NativeToken::new("AVAX"),
+            Self::Fantom => NativeToken::new("FTM"),
+            Self::PlumeTestnet => NativeToken::new("PLUME"),
+            Self::Mainnet
+            | Self::Sepolia
+            | Self::Holesky
+            | Self::PolygonZkEvm
+            | Self::Arbitrum
+            | Self::Optimism
+            | Self::Base
+            | Self::Scroll
+            | Self::ScrollTestnet
+            | Self::ScrollSepoliaTestnet
+            | Self::ZircuitTestnet
+            | Self::Worldchain
+            | Self::WorldchainTestnet
+            | Self::Zksync
+            | Self::ZksyncTestnet => NativeToken::new("ETH"),
+        }
+    }
+
+    /// The EIP-155 id of the chain, as deployed to by `pragma-deployer`
+    pub fn chain_id(&self) -> u64 {
+        match self {
+            Self::Mainnet => 1,
+            Self::Sepolia => 11155111,
+            Self::Holesky => 17000,
+            Self::Bsc => 56,
+            Self::BscTestnet => 97,
+            Self::Polygon => 137,
+            Self::PolygonTestnet => 80001,
+            Self::PolygonZkEvm => 1101,
+            Self::Avalanche => 43114,
+            Self::Fantom => 250,
+            Self::Arbitrum => 42161,
+            Self::Optimism => 10,
+            Self::Base => 8453,
+            Self::Scroll => 534352,
+            Self::ScrollTestnet => 534353,
+            Self::ScrollSepoliaTestnet => 534351,
+            Self::ZircuitTestnet => 48899,
+            Self::PlumeTestnet => 161221135,
+            Self::Worldchain => 480,
+            Self::WorldchainTestnet => 4801,
+            Self::Zksync => 324,
+            Self::ZksyncTestnet => 300,
+        }
+    }
+}
+
 /// Configuration for a single chain
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct EvmChainConfig {
     pub rpc_url: String,
+    /// RPC endpoints used along with `rpc_url`, so the chain is still reachable when one of them is down
+    #[serde(default)]
+    pub fallback_rpc_urls: Vec<String>,
     pub hyperlane_address: String,
+    /// Hyperlane domain of the chain, matched against the destination of the dispatches. Defaults to its chain id
+    #[serde(default)]
+    pub hyperlane_domain: Option<u32>,
+    /// Address of the Pragma contract consuming the calldata, used to simulate updates
+    #[serde(default)]
+    pub pragma_address: Option<String>,
+    /// Address of the Hyperlane `ValidatorAnnounce` contract of the chain. When set, the storage locations
+    /// announced there are used for the validators of the chain not announced on Pragma chain
+    #[serde(default)]
+    pub validator_announce_address: Option<String>,
+    /// Post-processors applied, in order, to the encoded calldata served for this chain
+    #[serde(default)]
+    pub post_processors: Vec<PostProcessorConfig>,
+    /// Submits the updates of the subscribed feeds to the Pragma contract, when the relayer is enabled
+    #[serde(default)]
+    pub relayer: Option<RelayerConfig>,
+    #[serde(flatten)]
+    pub status: ChainStatus,
+}
+
+impl EvmChainConfig {
+    /// All the RPC endpoints of the chain, starting with `rpc_url`
+    pub fn rpc_urls(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.rpc_url.as_str()).chain(self.fallback_rpc_urls.iter().map(String::as_str))
+    }
+
+    /// The Hyperlane domain of the chain, its chain id unless configured otherwise
+    pub fn domain(&self, chain_name: &EvmChainName) -> u32 {
+        // All the supported chain ids fit in a domain.
+        self.hyperlane_domain.unwrap_or(chain_name.chain_id() as u32)
+    }
+}
+
+/// Toggles of a chain, which can also be updated at runtime through the admin API
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+pub struct ChainStatus {
+    /// A disabled chain is neither indexed nor served. Its validators aren't loaded if disabled on startup
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// Serve calldata for the chain, but stop keeping its state up to date
+    #[serde(default)]
+    pub serve_only: bool,
+    /// Keep the state of the chain up to date, without serving calldata for it
+    #[serde(default)]
+    pub index_only: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl Default for ChainStatus {
+    fn default() -> Self {
+        Self { enabled: true, serve_only: false, index_only: false }
+    }
+}
+
+impl ChainStatus {
+    /// Whether calldata can be served for the chain
+    pub fn serves(&self) -> bool {
+        self.enabled && !self.index_only
+    }
+
+    /// Whether the state of the chain (e.g. its validators) is kept up to date
+    pub fn indexes(&self) -> bool {
+        self.enabled && !self.serve_only
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.serve_only && self.index_only {
+            return Err(String::from("`serve_only` & `index_only` can't both be set"));
+        }
+        Ok(())
+    }
+}
+
+/// Account of the relayer on a chain & the feeds it keeps up to date there
+#[derive(Clone, Deserialize, Serialize)]
+pub struct RelayerConfig {
+    /// Private key of the funded account sending the updates
+    pub private_key: String,
+    /// Feeds whose updates are submitted as soon as they reach quorum
+    pub feeds: Vec<String>,
+    /// How the gas of the transactions is priced. Defaults to EIP-1559 fees
+    #[serde(default)]
+    pub gas_price: GasPriceConfig,
+    /// Highest fee paid per gas, in wei. The updates which would cost more aren't sent, & the stuck transactions
+    /// aren't replaced above it
+    #[serde(default)]
+    pub max_fee_per_gas: Option<u64>,
+}
+
+/// Never prints the private key, whatever the redaction of the logs.
+impl fmt::Debug for RelayerConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RelayerConfig")
+            .field("private_key", &"<redacted>")
+            .field("feeds", &self.feeds)
+            .field("gas_price", &self.gas_price)
+            .field("max_fee_per_gas", &self.max_fee_per_gas)
+            .finish()
+    }
+}
+
+impl RelayerConfig {
+    pub fn signer(&self) -> Result<PrivateKeySigner, String> {
+        // The parsing error could quote a part of the key.
+        PrivateKeySigner::from_str(self.private_key.trim()).map_err(|_| String::from("invalid private key"))
+    }
+}
+
+/// Strategy pricing the gas of the relayer transactions, identified by its name & params
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(tag = "strategy", rename_all = "snake_case")]
+pub enum GasPriceConfig {
+    /// EIP-1559 fees: the base fee of the next block times `base_fee_multiplier`, absorbing its increases until
+    /// the transaction is mined, plus the `priority_fee_percentile` of the priority fees of the latest blocks
+    Eip1559 {
+        #[serde(default = "default_priority_fee_percentile")]
+        priority_fee_percentile: f64,
+        #[serde(default = "default_base_fee_multiplier")]
+        base_fee_multiplier: u64,
+    },
+    /// Gas price of the chain (`eth_gasPrice`), for the chains not supporting EIP-1559
+    Legacy,
+    /// Gas price in wei, whatever the activity of the chain
+    Fixed { gas_price: u64 },
+}
+
+fn default_priority_fee_percentile() -> f64 {
+    20.0
+}
+
+fn default_base_fee_multiplier() -> u64 {
+    2
+}
+
+impl Default for GasPriceConfig {
+    fn default() -> Self {
+        Self::Eip1559 {
+            priority_fee_percentile: default_priority_fee_percentile(),
+            base_fee_multiplier: default_base_fee_multiplier(),
+        }
+    }
+}
+
+impl GasPriceConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            Self::Eip1559 { priority_fee_percentile, .. } if !(0.0..=100.0).contains(priority_fee_percentile) => {
+                Err(String::from("`priority_fee_percentile` must be between 0 & 100"))
+            }
+            Self::Eip1559 { base_fee_multiplier: 0, .. } => {
+                Err(String::from("`base_fee_multiplier` must be at least 1"))
+            }
+            Self::Fixed { gas_price: 0 } => Err(String::from("`gas_price` must be positive")),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Configuration of a calldata post-processor, identified by its name & params
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(tag = "name", content = "params", rename_all = "snake_case")]
+pub enum PostProcessorConfig {
+    /// Prepends the number of feeds contained in the calldata
+    PrependFeedCount,
+    /// Appends a deadline, `validity_secs` after the calldata was built
+    AppendDeadline { validity_secs: u64 },
 }
 
 /// Main configuration structure
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Clone, Default, Deserialize, Serialize)]
 pub struct EvmConfig {
     #[serde(flatten)]
     chains: HashMap<EvmChainName, EvmChainConfig>,
 }
 
+/// Lists the chains sorted by name, so the same configuration is always formatted the same way.
+impl fmt::Debug for EvmConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let chains: BTreeMap<String, &EvmChainConfig> =
+            self.chains.iter().map(|(chain_name, chain_config)| (chain_name.to_string(), chain_config)).collect();
+        f.debug_struct("EvmConfig").field("chains", &chains).finish()
+    }
+}
+
 #[derive(Error, Debug)]
 pub enum ConfigError {
     #[error("Failed to read config file: {0}")]
     FileRead(#[from] std::io::Error),
     #[error("Failed to parse YAML: {0}")]
     YamlParse(#[from] serde_yaml::Error),
+    #[error("Invalid status for chain {0}: {1}")]
+    InvalidChainStatus(EvmChainName, String),
+    #[error("Invalid RPC URL for chain {0}: {1}")]
+    InvalidRpcUrl(EvmChainName, String),
+    #[error("Invalid {1} address for chain {0}: {2}")]
+    InvalidAddress(EvmChainName, &'static str, String),
+    #[error("Invalid relayer of chain {0}: {1}")]
+    InvalidRelayer(EvmChainName, String),
+    #[error("Invalid environment override {0}")]
+    InvalidEnvOverride(String),
+}
+
+/// EVM config loaded from a file, with its path so it can be watched for changes.
+#[derive(Debug, Clone)]
+pub struct EvmConfigFile {
+    pub path: PathBuf,
+    pub config: EvmConfig,
+}
+
+impl Deref for EvmConfigFile {
+    type Target = EvmConfig;
+
+    fn deref(&self) -> &EvmConfig {
+        &self.config
+    }
 }
 
 impl EvmConfig {
     /// Load configuration from a YAML file
     pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, ConfigError> {
         let contents = fs::read_to_string(path)?;
-        let config = serde_yaml::from_str(&contents)?;
+        Self::from_yaml(&contents)
+    }
+
+    /// Parse & validate a configuration, overridden by the `THEOROS__EVM__<CHAIN>__<FIELD>` environment variables.
+    /// JSON is accepted too, being a subset of YAML
+    pub fn from_yaml(contents: &str) -> Result<Self, ConfigError> {
+        let mut config: serde_yaml::Value = serde_yaml::from_str(contents)?;
+        apply_env_overrides(&mut config, "evm", std::env::vars()).map_err(ConfigError::InvalidEnvOverride)?;
+        let config: Self = serde_yaml::from_value(config)?;
+        config.validate()?;
         Ok(config)
     }
 
+    /// Checks the statuses, RPC URLs, addresses & relayers of all the chains
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        for (chain_name, chain_config) in self.chains() {
+            chain_config.status.validate().map_err(|e| ConfigError::InvalidChainStatus(*chain_name, e))?;
+            for rpc_url in chain_config.rpc_urls() {
+                Url::parse(rpc_url).map_err(|e| ConfigError::InvalidRpcUrl(*chain_name, e.to_string()))?;
+            }
+            Address::from_str(&chain_config.hyperlane_address)
+                .map_err(|e| ConfigError::InvalidAddress(*chain_name, "hyperlane", e.to_string()))?;
+            if let Some(pragma_address) = &chain_config.pragma_address {
+                Address::from_str(pragma_address)
+                    .map_err(|e| ConfigError::InvalidAddress(*chain_name, "pragma", e.to_string()))?;
+            }
+            if let Some(validator_announce_address) = &chain_config.validator_announce_address {
+                Address::from_str(validator_announce_address)
+                    .map_err(|e| ConfigError::InvalidAddress(*chain_name, "validator announce", e.to_string()))?;
+            }
+            if let Some(relayer) = &chain_config.relayer {
+                relayer.signer().map_err(|e| ConfigError::InvalidRelayer(*chain_name, e))?;
+                relayer.gas_price.validate().map_err(|e| ConfigError::InvalidRelayer(*chain_name, e))?;
+                if chain_config.pragma_address.is_none() {
+                    let error = String::from("`pragma_address` is required to submit the updates");
+                    return Err(ConfigError::InvalidRelayer(*chain_name, error));
+                }
+            }
+        }
+        Ok(())
+    }
+
     /// Get all configured chains
     pub fn chains(&self) -> &HashMap<EvmChainName, EvmChainConfig> {
         &self.chains
     }
+
+    /// Hyperlane domains of the enabled chains, the only destinations of the dispatches worth indexing
+    pub fn destination_domains(&self) -> HashSet<u32> {
+        self.chains
+            .iter()
+            .filter(|(_, chain_config)| chain_config.status.enabled)
+            .map(|(chain_name, chain_config)| chain_config.domain(chain_name))
+            .collect()
+    }
 }
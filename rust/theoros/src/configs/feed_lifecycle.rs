@@ -0,0 +1,139 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::Path,
+};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Stage of a feed in its lifecycle. Retired feeds are still listed, but their calldata isn't served anymore.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FeedLifecycleState {
+    #[default]
+    Active,
+    Deprecated,
+    Retired,
+}
+
+/// Lifecycle of a feed, which can also be updated at runtime through the admin API
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct FeedLifecycle {
+    #[serde(default)]
+    pub state: FeedLifecycleState,
+    /// When the feed is deprecated. The feed is considered deprecated from then on, even if still `active`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deprecated_at: Option<DateTime<Utc>>,
+    /// When the feed is retired. The feed is considered retired from then on, whatever its state.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retired_at: Option<DateTime<Utc>>,
+    /// Feed to migrate to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replaced_by: Option<String>,
+}
+
+impl FeedLifecycle {
+    /// Returns the state of the feed at `now`, taking the scheduled deprecation & retirement into account.
+    pub fn state_at(&self, now: DateTime<Utc>) -> FeedLifecycleState {
+        let scheduled = match (self.deprecated_at, self.retired_at) {
+            (_, Some(retired_at)) if retired_at <= now => FeedLifecycleState::Retired,
+            (Some(deprecated_at), _) if deprecated_at <= now => FeedLifecycleState::Deprecated,
+            _ => FeedLifecycleState::Active,
+        };
+        self.state.max(scheduled)
+    }
+
+    /// Returns the lifecycle with its current state.
+    pub fn current(&self) -> Self {
+        Self { state: self.state_at(Utc::now()), ..self.clone() }
+    }
+
+    /// Checks the consistency of the lifecycle of the feed.
+    pub fn validate(&self, feed_id: &str) -> Result<(), String> {
+        if self.replaced_by.as_deref() == Some(feed_id) {
+            return Err(String::from("a feed can't be replaced by itself"));
+        }
+        if let (Some(deprecated_at), Some(retired_at)) = (self.deprecated_at, self.retired_at) {
+            if retired_at < deprecated_at {
+                return Err(String::from("`retired_at` must not be before `deprecated_at`"));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum FeedLifecycleConfigError {
+    #[error("Failed to read config file: {0}")]
+    IoError(#[from] std::io::Error),
+    #[error("Failed to parse YAML: {0}")]
+    YamlParse(#[from] serde_yaml::Error),
+    #[error("Invalid lifecycle for feed {0}: {1}")]
+    InvalidLifecycle(String, String),
+}
+
+/// Lifecycles of the feeds that aren't simply active, by feed id, & the private feeds.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct FeedLifecycleConfig {
+    #[serde(default)]
+    feeds: HashMap<String, FeedLifecycle>,
+    /// Feeds whose calldata is only served encrypted for a registered consumer.
+    #[serde(default)]
+    private_feeds: HashSet<String>,
+}
+
+impl FeedLifecycleConfig {
+    /// Load configuration from a YAML file
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, FeedLifecycleConfigError> {
+        let contents = fs::read_to_string(path)?;
+        let config: Self = serde_yaml::from_str(&contents)?;
+        for (feed_id, lifecycle) in &config.feeds {
+            lifecycle.validate(feed_id).map_err(|e| FeedLifecycleConfigError::InvalidLifecycle(feed_id.clone(), e))?;
+        }
+        Ok(config)
+    }
+
+    pub fn feeds(&self) -> &HashMap<String, FeedLifecycle> {
+        &self.feeds
+    }
+
+    pub fn private_feeds(&self) -> &HashSet<String> {
+        &self.private_feeds
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use super::*;
+
+    #[test]
+    fn test_scheduled_states() {
+        let now = Utc::now();
+        let lifecycle = FeedLifecycle {
+            deprecated_at: Some(now - Duration::days(1)),
+            retired_at: Some(now + Duration::days(30)),
+            ..Default::default()
+        };
+        assert_eq!(lifecycle.state_at(now - Duration::days(2)), FeedLifecycleState::Active);
+        assert_eq!(lifecycle.state_at(now), FeedLifecycleState::Deprecated);
+        assert_eq!(lifecycle.state_at(now + Duration::days(30)), FeedLifecycleState::Retired);
+
+        // An explicit state is never downgraded by the schedule.
+        let retired = FeedLifecycle { state: FeedLifecycleState::Retired, ..lifecycle.clone() };
+        assert_eq!(retired.state_at(now - Duration::days(2)), FeedLifecycleState::Retired);
+
+        let config: FeedLifecycleConfig = serde_yaml::from_str(
+            "feeds:\n  '0x01':\n    state: deprecated\n    replaced_by: '0x02'\n  '0x03':\n    retired_at: 2026-01-01T00:00:00Z\n\
+             private_feeds: ['0x04']\n",
+        )
+        .unwrap();
+        assert_eq!(config.feeds()["0x01"].state, FeedLifecycleState::Deprecated);
+        assert_eq!(config.private_feeds(), &HashSet::from([String::from("0x04")]));
+        assert_eq!(config.feeds()["0x03"].state_at(now), FeedLifecycleState::Retired);
+        assert!(FeedLifecycle { replaced_by: Some("0x01".into()), ..Default::default() }.validate("0x01").is_err());
+    }
+}
@@ -0,0 +1,77 @@
+use std::str::FromStr;
+use std::time::Duration;
+
+use anyhow::{bail, Context};
+use chrono::DateTime;
+
+/// Point from which the indexer starts streaming events.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndexerStart {
+    /// A block number, e.g. `123456`
+    Block(u64),
+    /// A unix timestamp in seconds, parsed from a RFC 3339 date, e.g. `2024-10-01T00:00:00Z`
+    Timestamp(u64),
+    /// A duration before the current time, e.g. `24h` to index the last 24 hours
+    Ago(Duration),
+}
+
+impl FromStr for IndexerStart {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let s = s.trim();
+        if let Ok(block) = s.parse::<u64>() {
+            return Ok(IndexerStart::Block(block));
+        }
+        if let Ok(date) = DateTime::parse_from_rfc3339(s) {
+            let timestamp = u64::try_from(date.timestamp()).context("Indexer start date is before the unix epoch")?;
+            return Ok(IndexerStart::Timestamp(timestamp));
+        }
+        parse_duration(s).map(IndexerStart::Ago).with_context(|| {
+            format!("Invalid indexer start `{s}`: expected a block number, a RFC 3339 date or a duration")
+        })
+    }
+}
+
+/// Parses a duration formatted as an integer followed by a unit (`ms`, `s`, `m`, `h` or `d`), e.g. `30m`.
+pub fn parse_duration(s: &str) -> anyhow::Result<Duration> {
+    let unit_index = s.find(|c: char| !c.is_ascii_digit()).context("Missing duration unit")?;
+    let (value, unit) = s.split_at(unit_index);
+    let value: u64 = value.parse().context("Invalid duration value")?;
+    let unit_secs = match unit {
+        "ms" => return Ok(Duration::from_millis(value)),
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        _ => bail!("Unknown duration unit `{unit}`"),
+    };
+    Ok(Duration::from_secs(value.checked_mul(unit_secs).context("Duration too large")?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_indexer_start() {
+        assert_eq!("123456".parse::<IndexerStart>().unwrap(), IndexerStart::Block(123456));
+        assert_eq!("2024-10-01T00:00:00Z".parse::<IndexerStart>().unwrap(), IndexerStart::Timestamp(1727740800));
+        assert_eq!("24h".parse::<IndexerStart>().unwrap(), IndexerStart::Ago(Duration::from_secs(24 * 3600)));
+        assert_eq!("30m".parse::<IndexerStart>().unwrap(), IndexerStart::Ago(Duration::from_secs(30 * 60)));
+        assert_eq!("7d".parse::<IndexerStart>().unwrap(), IndexerStart::Ago(Duration::from_secs(7 * 86400)));
+
+        assert!("yesterday".parse::<IndexerStart>().is_err());
+        assert!("24w".parse::<IndexerStart>().is_err());
+        assert!("h".parse::<IndexerStart>().is_err());
+
+        assert_eq!(parse_duration("250ms").unwrap(), Duration::from_millis(250));
+    }
+
+    #[test]
+    fn test_parse_duration_overflow_is_an_error() {
+        assert!(parse_duration("999999999999999999d").is_err());
+        assert!(parse_duration(&format!("{}h", u64::MAX / 3600 + 1)).is_err());
+        assert_eq!(parse_duration(&format!("{}s", u64::MAX)).unwrap(), Duration::from_secs(u64::MAX));
+    }
+}
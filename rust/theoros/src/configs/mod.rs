@@ -1 +1,6 @@
+pub mod emitters;
+pub mod env_overrides;
 pub mod evm_config;
+pub mod feed_lifecycle;
+pub mod indexer_start;
+pub mod runtime_settings;
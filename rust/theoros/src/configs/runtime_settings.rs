@@ -0,0 +1,189 @@
+use std::{fs, path::Path, str::FromStr, time::Duration};
+
+use anyhow::{Context, Result};
+use serde::{de::Error as _, Deserialize, Deserializer};
+use tracing::Level;
+
+use crate::{
+    cli::TheorosCli,
+    configs::{env_overrides::apply_env_overrides, indexer_start::parse_duration},
+    constants::{DEFAULT_CHECKPOINTS_FETCH_INTERVAL, DEFAULT_VALIDATORS_REFRESH_INTERVAL},
+    services::api::rate_limit::RateLimits,
+    AppState,
+};
+
+/// Settings which can be changed while Theoros runs, without any restart.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RuntimeSettings {
+    pub log_level: Level,
+    pub rate_limits: RateLimits,
+    /// Interval between two refreshes of the validators of the chains. The refresh is disabled when zero.
+    pub validators_refresh_interval: Duration,
+    /// Interval between two fetches of the pending checkpoints.
+    pub checkpoints_fetch_interval: Duration,
+}
+
+impl Default for RuntimeSettings {
+    fn default() -> Self {
+        Self {
+            log_level: Level::INFO,
+            rate_limits: RateLimits::default(),
+            validators_refresh_interval: DEFAULT_VALIDATORS_REFRESH_INTERVAL,
+            checkpoints_fetch_interval: DEFAULT_CHECKPOINTS_FETCH_INTERVAL,
+        }
+    }
+}
+
+/// Overrides of the runtime settings, read from a YAML file. Settings left out keep their command line value.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RuntimeSettingsOverrides {
+    #[serde(default, deserialize_with = "deserialize_level")]
+    pub log_level: Option<Level>,
+    /// `0` lifts the limit.
+    pub rate_limit_per_ip: Option<u32>,
+    /// `0` lifts the limit.
+    pub rate_limit_per_api_key: Option<u32>,
+    #[serde(default, deserialize_with = "deserialize_duration")]
+    pub rate_limit_window: Option<Duration>,
+    #[serde(default, deserialize_with = "deserialize_duration")]
+    pub validators_refresh_interval: Option<Duration>,
+    #[serde(default, deserialize_with = "deserialize_duration")]
+    pub checkpoints_fetch_interval: Option<Duration>,
+}
+
+impl RuntimeSettingsOverrides {
+    /// Parses the overrides, themselves overridden by the `THEOROS__SETTINGS__<SETTING>` environment variables.
+    pub fn from_yaml(contents: &str) -> Result<Self> {
+        let mut overrides: serde_yaml::Value = serde_yaml::from_str(contents)?;
+        apply_env_overrides(&mut overrides, "settings", std::env::vars()).map_err(|e| anyhow::anyhow!(e))?;
+        // An empty file overrides nothing.
+        if overrides.is_null() {
+            return Ok(Self::default());
+        }
+        Ok(serde_yaml::from_value(overrides)?)
+    }
+}
+
+impl RuntimeSettings {
+    pub fn from_cli(config: &TheorosCli) -> Self {
+        Self {
+            log_level: config.log_level,
+            rate_limits: RateLimits {
+                per_ip: config.rate_limit_per_ip.and_then(limit),
+                per_api_key: config.rate_limit_per_api_key.and_then(limit),
+                window: config.rate_limit_window,
+            },
+            validators_refresh_interval: config.validators_refresh_interval,
+            checkpoints_fetch_interval: config.checkpoints_fetch_interval,
+        }
+    }
+
+    /// The command line settings, overridden by the ones of the runtime settings file if any.
+    pub fn load(config: &TheorosCli) -> Result<Self> {
+        let settings = Self::from_cli(config);
+        let Some(path) = &config.runtime_settings_path else {
+            settings.validate()?;
+            return Ok(settings);
+        };
+        let overrides = read_overrides(path)?;
+        settings.with_overrides(&overrides)
+    }
+
+    /// Applies the overrides over the settings, refusing the resulting settings if they are invalid.
+    pub fn with_overrides(mut self, overrides: &RuntimeSettingsOverrides) -> Result<Self> {
+        if let Some(log_level) = overrides.log_level {
+            self.log_level = log_level;
+        }
+        if let Some(per_ip) = overrides.rate_limit_per_ip {
+            self.rate_limits.per_ip = limit(per_ip);
+        }
+        if let Some(per_api_key) = overrides.rate_limit_per_api_key {
+            self.rate_limits.per_api_key = limit(per_api_key);
+        }
+        if let Some(window) = overrides.rate_limit_window {
+            self.rate_limits.window = window;
+        }
+        if let Some(interval) = overrides.validators_refresh_interval {
+            self.validators_refresh_interval = interval;
+        }
+        if let Some(interval) = overrides.checkpoints_fetch_interval {
+            self.checkpoints_fetch_interval = interval;
+        }
+        self.validate()?;
+        Ok(self)
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        anyhow::ensure!(!self.rate_limits.window.is_zero(), "The rate limit window can't be zero");
+        anyhow::ensure!(!self.checkpoints_fetch_interval.is_zero(), "The checkpoints fetch interval can't be zero");
+        Ok(())
+    }
+
+    /// Applies the settings to the running components.
+    pub fn apply(self, state: &AppState) {
+        state.log_level.set(self.log_level);
+        state.rate_limiter.set_limits(self.rate_limits);
+        state.runtime_settings.send_replace(self);
+    }
+}
+
+/// A rate limit of `0` lifts the limit, from the command line as from the runtime settings file.
+fn limit(limit: u32) -> Option<u32> {
+    (limit > 0).then_some(limit)
+}
+
+pub fn read_overrides(path: &Path) -> Result<RuntimeSettingsOverrides> {
+    let contents = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read the runtime settings from path: {}", path.display()))?;
+    RuntimeSettingsOverrides::from_yaml(&contents)
+        .with_context(|| format!("Failed to parse the runtime settings from path: {}", path.display()))
+}
+
+fn deserialize_level<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Level>, D::Error> {
+    let level = String::deserialize(deserializer)?;
+    Level::from_str(&level).map(Some).map_err(D::Error::custom)
+}
+
+fn deserialize_duration<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Duration>, D::Error> {
+    let duration = String::deserialize(deserializer)?;
+    parse_duration(&duration).map(Some).map_err(D::Error::custom)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_settings_overrides() {
+        let overrides = RuntimeSettingsOverrides::from_yaml(
+            "log_level: debug\nrate_limit_per_ip: 0\nrate_limit_per_api_key: 100\nvalidators_refresh_interval: 1m\n",
+        )
+        .unwrap();
+        let settings = RuntimeSettings {
+            rate_limits: RateLimits { per_ip: Some(10), ..RateLimits::default() },
+            ..RuntimeSettings::default()
+        };
+
+        let settings = settings.with_overrides(&overrides).unwrap();
+        assert_eq!(settings.log_level, Level::DEBUG);
+        assert_eq!(settings.rate_limits.per_ip, None);
+        assert_eq!(settings.rate_limits.per_api_key, Some(100));
+        assert_eq!(settings.validators_refresh_interval, Duration::from_secs(60));
+        assert_eq!(settings.checkpoints_fetch_interval, DEFAULT_CHECKPOINTS_FETCH_INTERVAL);
+
+        let invalid = RuntimeSettingsOverrides::from_yaml("checkpoints_fetch_interval: 0s").unwrap();
+        assert!(settings.with_overrides(&invalid).is_err());
+        assert!(RuntimeSettingsOverrides::from_yaml("unknown_setting: 1").is_err());
+        assert!(RuntimeSettingsOverrides::from_yaml("log_level: loud").is_err());
+    }
+
+    #[test]
+    fn test_zero_rate_limits_are_lifted() {
+        assert_eq!(limit(0), None);
+        assert_eq!(limit(100), Some(100));
+
+        let rate_limits = RateLimits { window: Duration::ZERO, ..RateLimits::default() };
+        assert!(RuntimeSettings { rate_limits, ..RuntimeSettings::default() }.validate().is_err());
+    }
+}
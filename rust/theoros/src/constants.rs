@@ -9,6 +9,121 @@ pub const TRAILING_HEADER_SIZE: u8 = 0;
 pub const PING_INTERVAL_DURATION: Duration = Duration::from_secs(30);
 pub const MAX_CLIENT_MESSAGE_SIZE: usize = 100 * 1024; // 100 KiB
 pub const FEED_UPDATED_CHANNEL_CAPACITY: usize = 1024;
+/// Default & maximum time a long-poll request waits for a new update.
+pub const DEFAULT_LONG_POLL_TIMEOUT: Duration = Duration::from_secs(30);
+pub const MAX_LONG_POLL_TIMEOUT: Duration = Duration::from_secs(60);
+/// Maximum number of pending connections of the API listener.
+pub const TCP_LISTEN_BACKLOG: i32 = 1024;
+/// Time allowed to a proxied connection to send its PROXY protocol header.
+pub const PROXY_HEADER_TIMEOUT: Duration = Duration::from_secs(5);
+/// Number of standard requests waiting for a slot before new ones are shed.
+pub const DEFAULT_MAX_QUEUED_REQUESTS: usize = 256;
+/// Maximum time a standard request waits for a slot before being shed.
+pub const DEFAULT_REQUEST_QUEUE_TIMEOUT: Duration = Duration::from_secs(5);
+/// How long browsers may cache the CORS preflight responses.
+pub const DEFAULT_CORS_MAX_AGE: Duration = Duration::from_secs(3600);
+/// Window over which the rate limits of the API clients apply.
+pub const DEFAULT_RATE_LIMIT_WINDOW: Duration = Duration::from_secs(60);
+/// Number of rate limited clients tracked before the idle ones are forgotten.
+pub const RATE_LIMIT_MAX_TRACKED_CLIENTS: usize = 100_000;
+
+/// Overall time allowed to assemble the calldata of a request.
+pub const DEFAULT_CALLDATA_DEADLINE: Duration = Duration::from_secs(10);
+/// Maximum size, in bytes, of the body of an API request.
+pub const DEFAULT_MAX_REQUEST_BODY_SIZE: usize = 64 * 1024; // 64 KiB
+/// Maximum number of feeds requested at once, e.g. in a batch of calldata.
+pub const DEFAULT_MAX_BATCH_FEEDS: usize = 100;
+/// Maximum time spent fetching a checkpoint from a single validator.
+pub const DEFAULT_VALIDATOR_FETCH_TIMEOUT: Duration = Duration::from_secs(3);
+/// Maximum time spent fetching a checkpoint from one of the storage locations of a validator,
+/// before falling back to the next one.
+pub const STORAGE_BACKEND_FETCH_TIMEOUT: Duration = Duration::from_secs(1);
+/// Attempts of a checkpoint fetch failing with a transient error, the first one included.
+pub const DEFAULT_CHECKPOINT_FETCH_MAX_ATTEMPTS: u32 = 3;
+/// Delay before the first retry of a checkpoint fetch, doubled at each retry.
+pub const DEFAULT_CHECKPOINT_FETCH_RETRY_DELAY: Duration = Duration::from_millis(100);
+/// Fraction of the retry delay randomly added or removed.
+pub const DEFAULT_CHECKPOINT_FETCH_RETRY_JITTER: f64 = 0.2;
+/// Interval between two refreshes of the validators of the chains.
+pub const DEFAULT_VALIDATORS_REFRESH_INTERVAL: Duration = Duration::from_secs(5 * 60);
+/// Interval between two fetches of the pending checkpoints.
+pub const DEFAULT_CHECKPOINTS_FETCH_INTERVAL: Duration = Duration::from_secs(1);
+/// Time waited after a change of a watched config file before reloading it, so the writes of a single
+/// change are reloaded at once.
+pub const CONFIG_RELOAD_DEBOUNCE: Duration = Duration::from_secs(1);
+/// Interval between two dispatches of the synthetic feed.
+pub const SYNTHETIC_FEED_INTERVAL: Duration = Duration::from_secs(60);
+/// Timeout of a single request to a checkpoint storage read through opendal.
+pub const STORAGE_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+/// Number of times a failed request to a checkpoint storage read through opendal is retried.
+pub const STORAGE_REQUEST_MAX_RETRIES: usize = 2;
+/// Timeout of a single request to an IPFS gateway or node, IPNS names being slow to resolve.
+pub const IPFS_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+/// Maximum number of checkpoints fetched at once from a storage location when fetching a range.
+pub const FETCH_RANGE_CONCURRENCY: usize = 16;
+/// Time an RPC endpoint which failed is skipped, unless all the endpoints of its chain are failing.
+pub const RPC_ENDPOINT_COOLDOWN: Duration = Duration::from_secs(30);
+/// Time the indexer waits before reconnecting, once the streams of all the Apibara endpoints failed.
+pub const INDEXER_RECONNECT_DELAY: Duration = Duration::from_secs(5);
+/// Number of consecutive checkpoints of a validator fetched by the same instance when sharding the fetches.
+pub const CHECKPOINT_SHARD_SIZE: u32 = 64;
+/// Time after which a checkpoint still missing from the instance owning it is fetched from the validator.
+pub const SHARD_TAKEOVER_DELAY: Duration = Duration::from_secs(30);
+/// Number of missing checkpoints from which they are fetched as a range, e.g. to catch up after a downtime.
+pub const MIN_CHECKPOINTS_FOR_RANGE_FETCH: usize = 16;
+/// Maximum time spent fetching a range of checkpoints from a single validator.
+pub const VALIDATOR_RANGE_FETCH_TIMEOUT: Duration = Duration::from_secs(30);
+/// Number of checkpoints a validator can be behind the latest dispatched message before being reported as lagging.
+pub const VALIDATOR_LAG_WARNING_THRESHOLD: u32 = 10;
+/// Number of checkpoints signed ahead of the indexed dispatches prefetched from a validator at each poll.
+pub const MAX_PREFETCHED_CHECKPOINTS: u32 = 64;
+
+/// Maximum nesting of the GraphQL queries.
+pub const GRAPHQL_MAX_DEPTH: usize = 8;
+/// Maximum complexity (roughly, the number of fields resolved) of the GraphQL queries.
+pub const GRAPHQL_MAX_COMPLEXITY: usize = 1_000;
+
+/// Number of updates buffered for a gRPC stream before waiting for the client to read them.
+pub const GRPC_STREAM_BUFFER_SIZE: usize = 64;
+
+/// Number of served calldata blobs kept to be retrieved by id.
+pub const MAX_STORED_CALLDATA_BLOBS: usize = 10_000;
+/// Number of checkpoint anomalies kept to be listed through the API.
+pub const MAX_STORED_ANOMALIES: usize = 1_000;
+pub const ANOMALIES_CHANNEL_CAPACITY: usize = 256;
+/// Number of invalid checkpoints kept in quarantine.
+pub const MAX_QUARANTINED_CHECKPOINTS: usize = 1_000;
+/// Number of updates that could not be parsed kept to be listed through the admin API.
+pub const MAX_STORED_PARSE_FAILURES: usize = 1_000;
+/// Number of events that could not be decoded kept, & persisted, to be replayed through the admin API.
+pub const MAX_STORED_DEAD_LETTERS: usize = 1_000;
+/// Number of dispatches whose raw Starknet event is kept to be inspected through the debug API.
+pub const MAX_STORED_RAW_DISPATCHES: usize = 10_000;
+/// Number of lifecycle events kept per feed for debugging.
+pub const MAX_TIMELINE_EVENTS_PER_FEED: usize = 200;
+/// Number of signed updates kept per feed, used to compute aggregates.
+pub const MAX_HISTORY_UPDATES_PER_FEED: usize = 10_000;
+pub const DEFAULT_OHLC_INTERVAL: Duration = Duration::from_secs(60);
+/// Number of updates returned by the history endpoint, by default & at most.
+pub const DEFAULT_HISTORY_LIMIT: usize = 100;
+pub const MAX_HISTORY_LIMIT: usize = 1_000;
+/// Number of feeds returned per page by the feeds list, by default & at most.
+pub const DEFAULT_DATA_FEEDS_LIMIT: usize = 100;
+pub const MAX_DATA_FEEDS_LIMIT: usize = 1_000;
+/// Age of the latest update of a feed past which it is considered stale, unless overridden for the feed.
+pub const DEFAULT_FEED_MAX_AGE: Duration = Duration::from_secs(5 * 60);
+
+/// Number of transactions sent by the relayer kept per chain to be listed through its status.
+pub const MAX_RELAYED_TRANSACTIONS_KEPT: usize = 20;
+/// Interval between two lookups of the receipt of a transaction sent by the relayer.
+pub const RELAYER_RECEIPT_POLL_INTERVAL: Duration = Duration::from_secs(2);
+/// Gas limit of the transactions sent by the relayer, as a percentage of their estimated gas.
+pub const RELAYER_GAS_LIMIT_MARGIN_PERCENT: u64 = 120;
+/// Number of latest blocks whose priority fees price the EIP-1559 transactions of the relayer.
+pub const RELAYER_FEE_HISTORY_BLOCKS: u64 = 10;
+/// Raise of the fees of a transaction of the relayer replacing a stuck one, as a percentage. Nodes only accept a
+/// replacement paying at least 10% more.
+pub const RELAYER_REPLACEMENT_FEE_BUMP_PERCENT: u128 = 12;
 
 // TODO: add support for this
 /// The maximum number of bytes that can be sent per second per IP address.
@@ -0,0 +1,170 @@
+use std::sync::atomic::Ordering;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{ToResponse, ToSchema};
+
+use pragma_utils::tracing::recent_errors;
+
+use crate::{
+    configs::evm_config::{ChainStatus, EvmChainName},
+    constants::{MAX_STORED_CALLDATA_BLOBS, MAX_STORED_RAW_DISPATCHES},
+    types::build_info::BuildInfo,
+    AppState,
+};
+
+/// State of the tokio runtime & of the long-lived tasks.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct RuntimeStats {
+    pub workers: usize,
+    /// Tasks currently alive on the runtime, including the services & the API connections.
+    pub alive_tasks: usize,
+    pub websocket_subscribers: usize,
+}
+
+/// Number of entries of each storage.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct StorageStats {
+    pub feed_ids: usize,
+    pub validators: usize,
+    pub signed_checkpoints: usize,
+    /// Dispatches whose checkpoints are still being collected.
+    pub pending_dispatches: usize,
+    pub feeds_with_update: usize,
+    pub history_updates: usize,
+    pub parse_failures: usize,
+    /// Indexed events that could not be decoded, waiting to be replayed.
+    pub dead_letters: usize,
+    pub quarantined_checkpoints: usize,
+    pub checkpoint_anomalies: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CacheStats {
+    pub calldata_blobs: usize,
+    pub max_calldata_blobs: usize,
+    pub raw_dispatch_events: usize,
+    pub max_raw_dispatch_events: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ChainHealth {
+    pub chain: EvmChainName,
+    #[serde(flatten)]
+    pub status: ChainStatus,
+    /// Whether the validators of the chain were loaded, which is required to serve calldata for it.
+    pub validators_loaded: bool,
+    pub validators: usize,
+    /// Number of signatures required by the chain, once its validators are loaded.
+    pub threshold: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct RecentError {
+    pub at: DateTime<Utc>,
+    pub target: String,
+    pub message: String,
+}
+
+/// Snapshot of the state of an instance, to diagnose it before restarting it.
+#[derive(Debug, Clone, Serialize, Deserialize, ToResponse, ToSchema)]
+pub struct DiagnosticBundle {
+    pub generated_at: DateTime<Utc>,
+    pub build: BuildInfo,
+    pub config_fingerprint: Option<String>,
+    pub runtime: RuntimeStats,
+    pub storage: StorageStats,
+    pub caches: CacheStats,
+    pub chains: Vec<ChainHealth>,
+    /// The most recent errors logged, most recent first.
+    pub recent_errors: Vec<RecentError>,
+}
+
+/// Collects the diagnostic bundle of the instance.
+pub async fn collect(state: &AppState) -> DiagnosticBundle {
+    let runtime_metrics = tokio::runtime::Handle::current().metrics();
+    let runtime = RuntimeStats {
+        workers: runtime_metrics.num_workers(),
+        alive_tasks: runtime_metrics.num_alive_tasks(),
+        websocket_subscribers: state.ws.subscriber_counter.load(Ordering::Relaxed),
+    };
+
+    let storage = &state.storage;
+    let storage_stats = StorageStats {
+        feed_ids: storage.feed_ids().len(),
+        validators: storage.validators_fetchers().all().len(),
+        signed_checkpoints: storage.signed_checkpoints().len(),
+        pending_dispatches: storage.unsigned_checkpoints().nonces().await.len(),
+        feeds_with_update: storage.latest_update_per_feed().num_feeds(),
+        history_updates: storage.feed_history().num_updates(),
+        parse_failures: storage.parse_failures().all().await.len(),
+        dead_letters: storage.dead_letters().all().await.len(),
+        quarantined_checkpoints: storage.quarantine().all().await.len(),
+        checkpoint_anomalies: storage.checkpoint_anomalies().all().await.len(),
+    };
+    let caches = CacheStats {
+        calldata_blobs: storage.calldata_blobs().num_blobs(),
+        max_calldata_blobs: MAX_STORED_CALLDATA_BLOBS,
+        raw_dispatch_events: storage.raw_dispatch_events().num_events().await,
+        max_raw_dispatch_events: MAX_STORED_RAW_DISPATCHES,
+    };
+
+    let mut chains: Vec<_> = state
+        .chain_statuses
+        .all()
+        .into_iter()
+        .map(|(chain, status)| {
+            let validator_set = state.hyperlane_validators_mapping.get_validator_set(&chain);
+            ChainHealth {
+                chain,
+                status,
+                validators_loaded: validator_set.is_some(),
+                validators: validator_set.as_ref().map_or(0, |validator_set| validator_set.validators.len()),
+                threshold: validator_set.map(|validator_set| validator_set.threshold),
+            }
+        })
+        .collect();
+    chains.sort_by_key(|chain| chain.chain.to_string());
+
+    let recent_errors = recent_errors()
+        .all()
+        .into_iter()
+        .map(|error| RecentError { at: error.at.into(), target: error.target, message: error.message })
+        .collect();
+
+    DiagnosticBundle {
+        generated_at: Utc::now(),
+        build: BuildInfo::current(),
+        config_fingerprint: state.config_fingerprint.clone(),
+        runtime,
+        storage: storage_stats,
+        caches,
+        chains,
+        recent_errors,
+    }
+}
+
+/// Logs the diagnostic bundle each time the process receives a SIGQUIT.
+#[cfg(unix)]
+pub fn dump_on_sigquit(state: AppState) -> Result<()> {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut sigquit = signal(SignalKind::quit())?;
+    tokio::spawn(async move {
+        while sigquit.recv().await.is_some() {
+            let bundle = collect(&state).await;
+            match serde_json::to_string_pretty(&bundle) {
+                Ok(bundle) => tracing::warn!("🩺 [Diagnostics] SIGQUIT received, diagnostic bundle:\n{}", bundle),
+                Err(e) => tracing::error!("🩺 [Diagnostics] Could not serialize the diagnostic bundle: {}", e),
+            }
+        }
+    });
+    Ok(())
+}
+
+/// Signals are only supported on unix.
+#[cfg(not(unix))]
+pub fn dump_on_sigquit(_state: AppState) -> Result<()> {
+    Ok(())
+}
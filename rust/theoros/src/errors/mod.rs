@@ -1,9 +1,3 @@
-pub mod app_error;
-pub mod calldata_error;
-pub mod chains_error;
-pub mod data_feeds_error;
+pub mod theoros_error;
 
-pub use app_error::AppError;
-pub use calldata_error::GetCalldataError;
-pub use chains_error::GetChainsError;
-pub use data_feeds_error::GetDataFeedsError;
+pub use theoros_error::{ProblemDetails, TheorosError};
@@ -0,0 +1,377 @@
+use std::time::Duration;
+
+use axum::http::{
+    header::{CONTENT_TYPE, RETRY_AFTER},
+    HeaderValue, StatusCode,
+};
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use serde::{Deserialize, Serialize};
+use strum_macros::IntoStaticStr;
+use utoipa::ToSchema;
+
+use crate::services::api::request_id::RequestId;
+use crate::types::{calldata::PartialQuorumError, hyperlane::DispatchParseError};
+
+/// Media type of the error responses, as defined by RFC 7807.
+pub const PROBLEM_JSON: &str = "application/problem+json";
+
+/// Error returned by the Theoros API.
+///
+/// Answered as an RFC 7807 problem, the variant giving its `code` so clients can match on it rather than on the
+/// message.
+#[derive(Debug, thiserror::Error, IntoStaticStr)]
+#[strum(serialize_all = "snake_case")]
+pub enum TheorosError {
+    #[error("Internal server error")]
+    InternalServerError,
+    #[error("Internal error: {0}")]
+    Internal(String),
+    #[error("Bad request error: {0}")]
+    BodyParsingError(String),
+    #[error("Payload too large: {0}")]
+    PayloadTooLarge(String),
+    #[error("Server overloaded, retry later")]
+    Overloaded,
+    /// Holds the time to wait before retrying.
+    #[error("Rate limit exceeded, retry later")]
+    RateLimited(Duration),
+    #[error("Missing or invalid admin API key")]
+    Unauthorized,
+    #[error("API version \"{0}\" is not supported")]
+    UnsupportedApiVersion(String),
+
+    // Internal failures
+    #[error("The storage is unavailable, retry later")]
+    Storage,
+    #[error("Could not fetch the checkpoints of the validators: {0}")]
+    CheckpointFetch(String),
+    #[error("Could not parse the dispatch: {0}")]
+    ParseFailure(String),
+    #[error("{0}")]
+    PartialQuorum(String),
+
+    // Feeds
+    #[error("Could not parse feed: {0}")]
+    InvalidFeedId(String),
+    #[error("Feed ID \"{0}\" is not registered")]
+    FeedNotFound(String),
+    #[error("Feed ID \"{feed_id}\" is retired{}", replacement(.replaced_by))]
+    FeedRetired { feed_id: String, replaced_by: Option<String> },
+    #[error(
+        "Feed ID \"{0}\" is private, its updates are only served in its calldata, encrypted for a registered consumer"
+    )]
+    PrivateFeed(String),
+    #[error("Invalid timeout: {0}")]
+    InvalidTimeout(String),
+    #[error("Invalid interval: {0}")]
+    InvalidInterval(String),
+    #[error("Invalid range: {0}")]
+    InvalidRange(String),
+    #[error("Invalid feed lifecycle: {0}")]
+    InvalidFeedLifecycle(String),
+    #[error("Emitter \"{0}\" is not configured")]
+    EmitterNotFound(String),
+
+    // Calldata
+    #[error("Could not find any Dispatch event for the provided Feed ID")]
+    DispatchNotFound,
+    #[error("The chain \"{0}\" is not supported")]
+    ChainNotSupported(String),
+    #[error("Serving calldata for the chain \"{0}\" is disabled")]
+    ChainDisabled(String),
+    #[error("No chain provided and no default chain is configured for this instance")]
+    MissingChain,
+    #[error("Consumer \"{0}\" has no registered key")]
+    ConsumerNotFound(String),
+    #[error("Calldata ID \"{0}\" is not a valid 32 bytes hash")]
+    InvalidCalldataId(String),
+    #[error("Calldata ID \"{0}\" is unknown or was served too long ago")]
+    CalldataNotFound(String),
+    #[error("Invalid batch: {0}")]
+    InvalidBatch(String),
+    #[error("{requested} feeds requested, at most {max} can be requested at once")]
+    TooManyFeeds { requested: usize, max: usize },
+
+    // Simulation
+    #[error("No Pragma contract is configured for the chain \"{0}\" and none was provided")]
+    PragmaContractNotConfigured(String),
+    #[error("Invalid fork URL: {0}")]
+    InvalidForkUrl(String),
+    #[error("Invalid address: {0}")]
+    InvalidAddress(String),
+    #[error("Invalid value: {0}")]
+    InvalidValue(String),
+    #[error("Could not simulate the update: {0}")]
+    RpcError(String),
+
+    // Checkpoints & debug
+    #[error("Validator \"{0}\" is not a valid address")]
+    InvalidValidator(String),
+    #[error("Object \"{0}\" is not a checkpoint, expected `checkpoint_{{index}}_with_id.json`")]
+    UnknownObject(String),
+    #[error("The checkpoint #{0} of the validator is not stored")]
+    CheckpointNotFound(u32),
+    #[error("No raw event is stored for the dispatch with nonce #{0}")]
+    RawDispatchNotFound(u32),
+
+    // Admin
+    #[error("Invalid sampling rule: {0}")]
+    InvalidSamplingRule(String),
+    #[error("Invalid public key: {0}")]
+    InvalidPublicKey(String),
+    #[error("Chain \"{0}\" is not present in the EVM config")]
+    ChainNotFound(String),
+    #[error("Invalid chain status: {0}")]
+    InvalidChainStatus(String),
+    #[error(
+        "Validators of chain \"{0}\" are not loaded, enable it in the EVM config & apply it through \
+         /v1/admin/config/apply"
+    )]
+    ChainNotLoaded(String),
+    #[error("Invalid config: {0}")]
+    InvalidConfig(String),
+    #[error("Config can't be applied: {0}")]
+    UnresolvableConfig(String),
+    #[error("Dead letter #{0} is unknown or was already replayed")]
+    DeadLetterNotFound(u64),
+    #[error("Dead letter still can't be decoded: {0}")]
+    DeadLetterNotDecoded(String),
+    #[error("Heap profiling requires Theoros to be built with the `jemalloc` feature")]
+    HeapProfilingUnavailable,
+    #[error("Heap profiling error: {0}")]
+    HeapProfiling(String),
+}
+
+fn replacement(replaced_by: &Option<String>) -> String {
+    match replaced_by {
+        Some(replaced_by) => format!(", migrate to the Feed ID \"{replaced_by}\""),
+        None => String::new(),
+    }
+}
+
+impl TheorosError {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            Self::InternalServerError | Self::Internal(_) | Self::ParseFailure(_) | Self::HeapProfiling(_) => {
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+            Self::BodyParsingError(_)
+            | Self::InvalidFeedId(_)
+            | Self::InvalidTimeout(_)
+            | Self::InvalidInterval(_)
+            | Self::InvalidRange(_)
+            | Self::InvalidFeedLifecycle(_)
+            | Self::MissingChain
+            | Self::InvalidCalldataId(_)
+            | Self::InvalidBatch(_)
+            | Self::InvalidForkUrl(_)
+            | Self::InvalidAddress(_)
+            | Self::InvalidValue(_)
+            | Self::InvalidValidator(_)
+            | Self::InvalidSamplingRule(_)
+            | Self::InvalidPublicKey(_)
+            | Self::InvalidChainStatus(_)
+            | Self::InvalidConfig(_) => StatusCode::BAD_REQUEST,
+            Self::Unauthorized => StatusCode::UNAUTHORIZED,
+            Self::UnsupportedApiVersion(_) => StatusCode::NOT_ACCEPTABLE,
+            Self::FeedNotFound(_)
+            | Self::DispatchNotFound
+            | Self::ChainNotSupported(_)
+            | Self::ConsumerNotFound(_)
+            | Self::CalldataNotFound(_)
+            | Self::PragmaContractNotConfigured(_)
+            | Self::UnknownObject(_)
+            | Self::CheckpointNotFound(_)
+            | Self::RawDispatchNotFound(_)
+            | Self::ChainNotFound(_)
+            | Self::DeadLetterNotFound(_)
+            | Self::EmitterNotFound(_) => StatusCode::NOT_FOUND,
+            Self::ChainNotLoaded(_) => StatusCode::CONFLICT,
+            Self::FeedRetired { .. } => StatusCode::GONE,
+            Self::PrivateFeed(_) => StatusCode::FORBIDDEN,
+            Self::PayloadTooLarge(_) | Self::TooManyFeeds { .. } => StatusCode::PAYLOAD_TOO_LARGE,
+            Self::UnresolvableConfig(_) | Self::DeadLetterNotDecoded(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            Self::RateLimited(_) => StatusCode::TOO_MANY_REQUESTS,
+            Self::HeapProfilingUnavailable => StatusCode::NOT_IMPLEMENTED,
+            Self::CheckpointFetch(_) | Self::RpcError(_) => StatusCode::BAD_GATEWAY,
+            Self::Overloaded | Self::Storage | Self::ChainDisabled(_) => StatusCode::SERVICE_UNAVAILABLE,
+            Self::PartialQuorum(_) => StatusCode::GATEWAY_TIMEOUT,
+        }
+    }
+
+    /// Machine readable code of the error, e.g. `feed_not_found`.
+    pub fn code(&self) -> &'static str {
+        self.into()
+    }
+
+    /// Whether the same request may succeed later, without any change.
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            Self::Overloaded
+                | Self::RateLimited(_)
+                | Self::Storage
+                | Self::CheckpointFetch(_)
+                | Self::PartialQuorum(_)
+                | Self::RpcError(_)
+        )
+    }
+
+    /// Seconds the client should wait before retrying, if known.
+    fn retry_after(&self) -> Option<u64> {
+        match self {
+            Self::Overloaded => Some(1),
+            // Rounded up, so the client doesn't retry before a request is allowed.
+            Self::RateLimited(retry_after) => Some(retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0)),
+            _ => None,
+        }
+    }
+}
+
+/// Maps the internal errors returned while serving a request, e.g. while building a calldata.
+impl From<anyhow::Error> for TheorosError {
+    fn from(e: anyhow::Error) -> Self {
+        if let Some(partial_quorum) = e.downcast_ref::<PartialQuorumError>() {
+            return Self::PartialQuorum(partial_quorum.to_string());
+        }
+        if let Some(parse_error) = e.chain().find_map(|cause| cause.downcast_ref::<DispatchParseError>()) {
+            return Self::ParseFailure(parse_error.to_string());
+        }
+        if e.chain().any(|cause| cause.is::<reqwest::Error>() || cause.is::<opendal::Error>()) {
+            return Self::CheckpointFetch(e.to_string());
+        }
+        if e.chain().any(is_storage_error) {
+            tracing::error!("🌐 Storage error while serving a request: {:?}", e);
+            return Self::Storage;
+        }
+        Self::Internal(e.to_string())
+    }
+}
+
+fn is_storage_error(cause: &(dyn std::error::Error + 'static)) -> bool {
+    #[cfg(feature = "rocksdb")]
+    if cause.is::<rocksdb::Error>() {
+        return true;
+    }
+    cause.is::<sqlx::Error>()
+}
+
+/// Body of the error responses, as defined by RFC 7807.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ProblemDetails {
+    /// URI identifying the kind of error, e.g. `urn:theoros:error:feed_not_found`.
+    #[serde(rename = "type")]
+    pub problem_type: String,
+    /// Reason phrase of the status, e.g. `Not Found`.
+    pub title: String,
+    pub status: u16,
+    /// Human readable explanation of this occurrence of the error.
+    pub detail: String,
+    /// Machine readable code of the error, e.g. `feed_not_found`.
+    pub code: String,
+    /// Whether the same request may succeed later, without any change.
+    pub retryable: bool,
+    /// Identifier of the request, to reference when reporting an issue.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+}
+
+impl From<&TheorosError> for ProblemDetails {
+    fn from(error: &TheorosError) -> Self {
+        let status = error.status();
+        Self {
+            problem_type: format!("urn:theoros:error:{}", error.code()),
+            title: status.canonical_reason().unwrap_or("Error").to_owned(),
+            status: status.as_u16(),
+            detail: error.to_string(),
+            code: error.code().to_owned(),
+            retryable: error.is_retryable(),
+            request_id: RequestId::current().map(|RequestId(id)| id),
+        }
+    }
+}
+
+impl IntoResponse for TheorosError {
+    fn into_response(self) -> Response {
+        let problem = ProblemDetails::from(&self);
+        let mut response = (self.status(), Json(problem)).into_response();
+        let headers = response.headers_mut();
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static(PROBLEM_JSON));
+        if let Some(retry_after) = self.retry_after() {
+            headers.insert(RETRY_AFTER, HeaderValue::from(retry_after));
+        }
+        response
+    }
+}
+
+/// Maps the error to the gRPC status closest to its HTTP status, with its code in the
+/// `theoros-error-code` metadata.
+#[cfg(feature = "grpc")]
+impl From<TheorosError> for tonic::Status {
+    fn from(error: TheorosError) -> Self {
+        let code = match error.status() {
+            StatusCode::BAD_REQUEST => tonic::Code::InvalidArgument,
+            StatusCode::UNAUTHORIZED => tonic::Code::Unauthenticated,
+            StatusCode::FORBIDDEN => tonic::Code::PermissionDenied,
+            StatusCode::NOT_FOUND => tonic::Code::NotFound,
+            StatusCode::CONFLICT | StatusCode::GONE | StatusCode::UNPROCESSABLE_ENTITY => {
+                tonic::Code::FailedPrecondition
+            }
+            StatusCode::PAYLOAD_TOO_LARGE | StatusCode::TOO_MANY_REQUESTS => tonic::Code::ResourceExhausted,
+            StatusCode::NOT_IMPLEMENTED => tonic::Code::Unimplemented,
+            StatusCode::BAD_GATEWAY | StatusCode::SERVICE_UNAVAILABLE => tonic::Code::Unavailable,
+            StatusCode::GATEWAY_TIMEOUT => tonic::Code::DeadlineExceeded,
+            _ => tonic::Code::Internal,
+        };
+        let mut status = tonic::Status::new(code, error.to_string());
+        status.metadata_mut().insert("theoros-error-code", tonic::metadata::MetadataValue::from_static(error.code()));
+        status
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::body::to_bytes;
+
+    use super::*;
+
+    async fn respond(error: TheorosError) -> (Response, ProblemDetails) {
+        let mut response = error.into_response();
+        let body = to_bytes(std::mem::take(response.body_mut()), usize::MAX).await.unwrap();
+        (response, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_errors_are_answered_as_problems() {
+        let (response, problem) = respond(TheorosError::FeedNotFound("0x42".into())).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(response.headers()[CONTENT_TYPE], PROBLEM_JSON);
+        assert_eq!(problem.problem_type, "urn:theoros:error:feed_not_found");
+        assert_eq!(problem.title, "Not Found");
+        assert_eq!(problem.detail, "Feed ID \"0x42\" is not registered");
+        assert!(!problem.retryable);
+
+        let (response, problem) = respond(TheorosError::RateLimited(Duration::from_millis(1500))).await;
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()[RETRY_AFTER], "2");
+        assert_eq!(problem.code, "rate_limited");
+        assert!(problem.retryable);
+    }
+
+    #[test]
+    fn test_internal_errors_are_mapped() {
+        let partial_quorum =
+            PartialQuorumError { nonce: 7, signed: 1, required: 2, timed_out: vec![String::from("0x1")] };
+        assert!(matches!(TheorosError::from(anyhow::Error::new(partial_quorum)), TheorosError::PartialQuorum(_)));
+
+        let parse_error = anyhow::Error::new(DispatchParseError::EmptyBody).context("Decoding the dispatch");
+        assert!(matches!(TheorosError::from(parse_error), TheorosError::ParseFailure(_)));
+
+        let storage_error = anyhow::Error::new(sqlx::Error::PoolClosed).context("Reading the history");
+        assert!(matches!(TheorosError::from(storage_error), TheorosError::Storage));
+
+        let unknown = TheorosError::from(anyhow::anyhow!("Feed type 9 is not supported"));
+        assert_eq!(unknown.code(), "internal");
+    }
+}
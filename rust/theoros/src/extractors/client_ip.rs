@@ -0,0 +1,85 @@
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+
+use axum::{
+    async_trait,
+    extract::{ConnectInfo, FromRequestParts, Request, State},
+    http::{request::Parts, HeaderMap},
+    middleware::Next,
+    response::Response,
+};
+
+use crate::errors::TheorosError;
+
+const X_FORWARDED_FOR: &str = "x-forwarded-for";
+
+/// Addresses of the proxies allowed to set the `X-Forwarded-For` header.
+pub type TrustedProxies = Arc<[IpAddr]>;
+
+/// IP address of the client that sent the request.
+///
+/// Resolved by [`resolve_client_ip`] from the peer address, which is the one announced through the PROXY
+/// protocol when enabled, and from the `X-Forwarded-For` header when the peer is a trusted proxy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClientIp(pub IpAddr);
+
+impl ClientIp {
+    pub fn resolve(peer: IpAddr, headers: &HeaderMap, trusted_proxies: &[IpAddr]) -> Self {
+        if !trusted_proxies.contains(&peer) {
+            return Self(peer);
+        }
+        // Each proxy appends the address it received the request from: the client is the right-most
+        // address that isn't one of our proxies.
+        let forwarded: Vec<IpAddr> = headers
+            .get_all(X_FORWARDED_FOR)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .filter_map(|address| address.trim().parse().ok())
+            .collect();
+        let client = forwarded.iter().rev().find(|address| !trusted_proxies.contains(address));
+        Self(client.or(forwarded.first()).copied().unwrap_or(peer))
+    }
+}
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for ClientIp {
+    type Rejection = TheorosError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts.extensions.get::<ClientIp>().copied().ok_or(TheorosError::InternalServerError)
+    }
+}
+
+/// Resolves the [`ClientIp`] of every request & stores it in the request extensions.
+pub async fn resolve_client_ip(
+    State(trusted_proxies): State<TrustedProxies>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let client_ip = ClientIp::resolve(peer.ip(), request.headers(), &trusted_proxies);
+    request.extensions_mut().insert(client_ip);
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::HeaderValue;
+
+    use super::*;
+
+    #[test]
+    fn test_resolve_client_ip() {
+        let proxy: IpAddr = "10.0.0.1".parse().unwrap();
+        let client: IpAddr = "2001:db8::1".parse().unwrap();
+        let mut headers = HeaderMap::new();
+        headers.insert(X_FORWARDED_FOR, HeaderValue::from_static("203.0.113.7, 2001:db8::1, 10.0.0.1"));
+
+        // The header is ignored when not sent by a trusted proxy.
+        assert_eq!(ClientIp::resolve(client, &headers, &[proxy]), ClientIp(client));
+        // Spoofed addresses on the left of the chain are ignored.
+        assert_eq!(ClientIp::resolve(proxy, &headers, &[proxy]), ClientIp(client));
+        assert_eq!(ClientIp::resolve(proxy, &HeaderMap::new(), &[proxy]), ClientIp(proxy));
+    }
+}
@@ -1,14 +1,44 @@
-use axum::extract::rejection::JsonRejection;
+use axum::{extract::rejection::JsonRejection, http::StatusCode};
 use axum_macros::FromRequest;
 
-use crate::errors::AppError;
+use crate::errors::TheorosError;
 
 #[derive(FromRequest)]
-#[from_request(via(axum::Json), rejection(AppError))]
+#[from_request(via(axum::Json), rejection(TheorosError))]
 pub struct JsonExtractor<T>(pub T);
 
-impl From<JsonRejection> for AppError {
+impl From<JsonRejection> for TheorosError {
     fn from(rejection: JsonRejection) -> Self {
-        AppError::BodyParsingError(rejection.to_string())
+        match rejection.status() {
+            StatusCode::PAYLOAD_TOO_LARGE => TheorosError::PayloadTooLarge(rejection.body_text()),
+            _ => TheorosError::BodyParsingError(rejection.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{body::Body, extract::DefaultBodyLimit, http::Request, routing::post, Router};
+    use tower::ServiceExt;
+
+    use super::*;
+
+    async fn echo(JsonExtractor(body): JsonExtractor<Vec<String>>) -> String {
+        body.join(",")
+    }
+
+    #[tokio::test]
+    async fn test_body_over_limit_is_rejected_with_413() {
+        let router = Router::new().route("/", post(echo)).layer(DefaultBodyLimit::max(16));
+        let request = |body: &'static str| {
+            Request::post("/").header("content-type", "application/json").body(Body::from(body)).unwrap()
+        };
+
+        let response = router.clone().oneshot(request(r#"["a","b"]"#)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = router.clone().oneshot(request(r#"["aaaa","bbbb","cccc"]"#)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        let response = router.oneshot(request(r#"["a""#)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
     }
 }
@@ -1,6 +1,8 @@
+pub mod client_ip;
 pub mod json_extractor;
 pub mod path_extractor;
 
+pub use client_ip::ClientIp;
 #[allow(unused)]
 pub use json_extractor::JsonExtractor;
 #[allow(unused)]
@@ -1,14 +1,14 @@
 use axum::extract::rejection::PathRejection;
 use axum_macros::FromRequestParts;
 
-use crate::errors::AppError;
+use crate::errors::TheorosError;
 
 #[derive(FromRequestParts, Debug)]
-#[from_request(via(axum::extract::Path), rejection(AppError))]
+#[from_request(via(axum::extract::Path), rejection(TheorosError))]
 pub struct PathExtractor<T>(pub T);
 
-impl From<PathRejection> for AppError {
+impl From<PathRejection> for TheorosError {
     fn from(rejection: PathRejection) -> Self {
-        AppError::BodyParsingError(rejection.to_string())
+        TheorosError::BodyParsingError(rejection.to_string())
     }
 }
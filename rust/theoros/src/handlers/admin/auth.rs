@@ -0,0 +1,41 @@
+use axum::{
+    extract::{Request, State},
+    http::header::AUTHORIZATION,
+    middleware::Next,
+    response::Response,
+};
+use ring::constant_time::verify_slices_are_equal;
+
+use crate::{errors::TheorosError, extractors::ClientIp, AppState};
+
+/// Rejects requests that don't carry the configured admin API key as a bearer token.
+/// Every admin call is logged with the client IP for auditing.
+pub async fn require_admin_key(
+    State(state): State<AppState>,
+    client_ip: Option<ClientIp>,
+    request: Request,
+    next: Next,
+) -> Result<Response, TheorosError> {
+    let Some(expected_key) = state.admin_api_key.as_deref() else {
+        return Err(TheorosError::Unauthorized);
+    };
+
+    let provided_key = request
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+
+    let client_ip = client_ip.map(|ClientIp(ip)| ip.to_string()).unwrap_or_else(|| String::from("unknown"));
+    match provided_key {
+        // Compared in constant time, so the key can't be guessed byte by byte from the response times.
+        Some(key) if verify_slices_are_equal(key.as_bytes(), expected_key.as_bytes()).is_ok() => {
+            tracing::info!("🛠️ [Admin] {} {} from {}", request.method(), request.uri().path(), client_ip);
+            Ok(next.run(request).await)
+        }
+        _ => {
+            tracing::warn!("🛠️ [Admin] Unauthorized {} {} from {}", request.method(), request.uri().path(), client_ip);
+            Err(TheorosError::Unauthorized)
+        }
+    }
+}
@@ -0,0 +1,58 @@
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToResponse, ToSchema};
+
+use crate::{errors::TheorosError, AppState};
+
+#[derive(Debug, Deserialize, IntoParams, ToSchema)]
+pub struct EvictCheckpointsQuery {
+    /// Only evict the checkpoints of this nonce.
+    pub nonce: Option<u32>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, ToResponse, ToSchema)]
+pub struct EvictCacheResponse {
+    pub evicted: usize,
+}
+
+#[utoipa::path(
+    delete,
+    path = "/v1/admin/caches/checkpoints",
+    params(
+        EvictCheckpointsQuery
+    ),
+    responses(
+        (status = 200, description = "Evict checkpoints from the cache of the fetched checkpoints, so they are fetched again from the validators", body = EvictCacheResponse),
+        (status = 401, description = "Missing or invalid admin API key", body = ProblemDetails)
+    ),
+)]
+pub async fn evict_checkpoints_cache(
+    State(state): State<AppState>,
+    Query(params): Query<EvictCheckpointsQuery>,
+) -> Result<Json<EvictCacheResponse>, TheorosError> {
+    let evicted = match state.storage.validators_fetchers().checkpoint_cache() {
+        Some(cache) => cache.evict(params.nonce),
+        None => 0,
+    };
+    tracing::info!("🛠️ [Admin] Evicted {} checkpoints from the cache", evicted);
+    Ok(Json(EvictCacheResponse { evicted }))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/v1/admin/caches/calldata_blobs",
+    responses(
+        (status = 200, description = "Evict all the served calldata blobs, which can't be retrieved by id anymore", body = EvictCacheResponse),
+        (status = 401, description = "Missing or invalid admin API key", body = ProblemDetails)
+    ),
+)]
+pub async fn evict_calldata_blobs_cache(
+    State(state): State<AppState>,
+) -> Result<Json<EvictCacheResponse>, TheorosError> {
+    let evicted = state.storage.calldata_blobs().clear();
+    tracing::info!("🛠️ [Admin] Evicted {} calldata blobs", evicted);
+    Ok(Json(EvictCacheResponse { evicted }))
+}
@@ -0,0 +1,98 @@
+use std::str::FromStr;
+
+use axum::{extract::State, Json};
+use serde::{Deserialize, Serialize};
+use utoipa::{ToResponse, ToSchema};
+
+use crate::{
+    configs::evm_config::{ChainStatus, EvmChainName, NativeToken},
+    errors::TheorosError,
+    extractors::{JsonExtractor, PathExtractor},
+    storage::SettingKind,
+    AppState,
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToResponse, ToSchema)]
+pub struct ChainStatusResponse {
+    pub chain: EvmChainName,
+    #[serde(flatten)]
+    pub status: ChainStatus,
+    /// Whether the validators of the chain were loaded, which is required to serve calldata for it.
+    pub validators_loaded: bool,
+    /// Number of validators of the ISM of the chain & of signatures it requires, once loaded.
+    pub validators: usize,
+    pub threshold: Option<usize>,
+    /// Token in which the gas & fees of the chain are paid.
+    pub native_token: NativeToken,
+}
+
+impl ChainStatusResponse {
+    fn new(state: &AppState, chain: EvmChainName, status: ChainStatus) -> Self {
+        let validator_set = state.hyperlane_validators_mapping.get_validator_set(&chain);
+        Self {
+            chain,
+            status,
+            validators_loaded: validator_set.is_some(),
+            validators: validator_set.as_ref().map_or(0, |validator_set| validator_set.validators.len()),
+            threshold: validator_set.map(|validator_set| validator_set.threshold),
+            native_token: chain.native_token(),
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, ToResponse, ToSchema)]
+pub struct GetChainStatusesResponse(pub Vec<ChainStatusResponse>);
+
+#[utoipa::path(
+    get,
+    path = "/v1/admin/chains",
+    responses(
+        (status = 200, description = "Get the status of every configured chain", body = GetChainStatusesResponse),
+        (status = 401, description = "Missing or invalid admin API key", body = ProblemDetails)
+    ),
+)]
+pub async fn get_chain_statuses(State(state): State<AppState>) -> Result<Json<GetChainStatusesResponse>, TheorosError> {
+    let mut statuses: Vec<_> = state
+        .chain_statuses
+        .all()
+        .into_iter()
+        .map(|(chain, status)| ChainStatusResponse::new(&state, chain, status))
+        .collect();
+    statuses.sort_by_key(|status| status.chain.to_string());
+    Ok(Json(GetChainStatusesResponse(statuses)))
+}
+
+#[utoipa::path(
+    put,
+    path = "/v1/admin/chains/{chain_name}",
+    params(
+        ("chain_name" = String, Path, description = "The configured chain to update")
+    ),
+    request_body = ChainStatus,
+    responses(
+        (status = 200, description = "Update the status of the chain", body = ChainStatusResponse),
+        (status = 400, description = "Invalid chain status", body = ProblemDetails),
+        (status = 401, description = "Missing or invalid admin API key", body = ProblemDetails),
+        (status = 404, description = "Chain not present in the EVM config", body = ProblemDetails),
+        (status = 409, description = "The validators of the chain were not loaded on startup", body = ProblemDetails)
+    ),
+)]
+pub async fn update_chain_status(
+    State(state): State<AppState>,
+    PathExtractor(chain_name): PathExtractor<String>,
+    JsonExtractor(status): JsonExtractor<ChainStatus>,
+) -> Result<Json<ChainStatusResponse>, TheorosError> {
+    let chain = EvmChainName::from_str(&chain_name).map_err(|_| TheorosError::ChainNotFound(chain_name))?;
+    status.validate().map_err(TheorosError::InvalidChainStatus)?;
+    if status.enabled && !state.hyperlane_validators_mapping.is_supported_chain(&chain) {
+        return Err(TheorosError::ChainNotLoaded(chain.to_string()));
+    }
+
+    if !state.chain_statuses.set(chain, status) {
+        return Err(TheorosError::ChainNotFound(chain.to_string()));
+    }
+    state.storage.save_setting(SettingKind::ChainStatus, &chain.to_string(), status).await;
+    tracing::info!("🛠️ [Admin] Updated the status of chain {}: {:?}", chain, status);
+
+    Ok(Json(ChainStatusResponse::new(&state, chain, status)))
+}
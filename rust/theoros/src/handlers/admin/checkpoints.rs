@@ -0,0 +1,50 @@
+use axum::{extract::State, Json};
+use serde::{Deserialize, Serialize};
+use starknet::core::types::Felt;
+use utoipa::{ToResponse, ToSchema};
+
+use crate::{errors::TheorosError, extractors::PathExtractor, services::hyperlane::HyperlaneService, AppState};
+
+#[derive(Debug, Default, Serialize, Deserialize, ToResponse, ToSchema)]
+pub struct RefetchCheckpointResponse {
+    pub nonce: u32,
+    /// Validators whose checkpoint of the nonce is stored, fetched again or before.
+    pub signed_by: Vec<String>,
+    /// Validators whose fetch timed out.
+    pub timed_out: Vec<String>,
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/admin/checkpoints/{nonce}/refetch",
+    params(
+        ("nonce" = u32, Path, description = "Index of the checkpoint to fetch again")
+    ),
+    responses(
+        (status = 200, description = "Evict the checkpoint of the nonce from the cache & fetch it again from the validators that didn't sign it yet", body = RefetchCheckpointResponse),
+        (status = 401, description = "Missing or invalid admin API key", body = ProblemDetails)
+    ),
+)]
+pub async fn refetch_checkpoint(
+    State(state): State<AppState>,
+    PathExtractor(nonce): PathExtractor<u32>,
+) -> Result<Json<RefetchCheckpointResponse>, TheorosError> {
+    let timed_out = HyperlaneService::new(state.storage.clone(), state.metrics.clone())
+        .with_fetch_timeout(state.validator_fetch_timeout)
+        .refetch_checkpoint(nonce)
+        .await;
+
+    let validators: Vec<Felt> = state.storage.validators_fetchers().all().into_keys().collect();
+    let mut signed_by: Vec<String> = state
+        .storage
+        .signed_checkpoints()
+        .get(&validators, nonce)
+        .into_iter()
+        .map(|(validator, _)| format!("{:#x}", validator))
+        .collect();
+    signed_by.sort();
+    let timed_out = timed_out.into_iter().map(|validator| format!("{:#x}", validator)).collect();
+    tracing::info!("🛠️ [Admin] Fetched again checkpoint #{}, signed by {} validators", nonce, signed_by.len());
+
+    Ok(Json(RefetchCheckpointResponse { nonce, signed_by, timed_out }))
+}
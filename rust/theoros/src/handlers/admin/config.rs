@@ -0,0 +1,72 @@
+use axum::{extract::State, Json};
+use serde::{Deserialize, Serialize};
+use utoipa::{ToResponse, ToSchema};
+
+use crate::{
+    configs::evm_config::EvmConfig,
+    errors::TheorosError,
+    types::config_deployment::{ConfigDeploymentError, ConfigDiff},
+    AppState,
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToResponse, ToSchema)]
+pub struct ConfigDeploymentResponse {
+    /// Whether the candidate config is now the running one.
+    pub applied: bool,
+    /// What the candidate config changes, compared to the config running before.
+    pub diff: ConfigDiff,
+}
+
+fn parse_candidate(body: &str) -> Result<EvmConfig, TheorosError> {
+    EvmConfig::from_yaml(body).map_err(|e| TheorosError::InvalidConfig(e.to_string()))
+}
+
+impl From<ConfigDeploymentError> for TheorosError {
+    fn from(error: ConfigDeploymentError) -> Self {
+        match error {
+            ConfigDeploymentError::ValidatorsResolution(..) => Self::UnresolvableConfig(error.to_string()),
+            _ => Self::InvalidConfig(error.to_string()),
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/admin/config/validate",
+    request_body(content = String, description = "Candidate EVM config, in YAML or JSON", content_type = "application/yaml"),
+    responses(
+        (status = 200, description = "Diff the candidate EVM config against the running one, without applying it", body = ConfigDeploymentResponse),
+        (status = 400, description = "Invalid EVM config", body = ProblemDetails),
+        (status = 401, description = "Missing or invalid admin API key", body = ProblemDetails),
+        (status = 422, description = "The validators of a chain could not be resolved", body = ProblemDetails)
+    ),
+)]
+pub async fn validate_config(
+    State(state): State<AppState>,
+    body: String,
+) -> Result<Json<ConfigDeploymentResponse>, TheorosError> {
+    let candidate = parse_candidate(&body)?;
+    let diff = state.evm_config.validate(&state, candidate).await?;
+    Ok(Json(ConfigDeploymentResponse { applied: false, diff }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/admin/config/apply",
+    request_body(content = String, description = "Candidate EVM config, in YAML or JSON", content_type = "application/yaml"),
+    responses(
+        (status = 200, description = "Apply the candidate EVM config, replacing the running one at once", body = ConfigDeploymentResponse),
+        (status = 400, description = "Invalid EVM config", body = ProblemDetails),
+        (status = 401, description = "Missing or invalid admin API key", body = ProblemDetails),
+        (status = 422, description = "The validators of a chain could not be resolved, nothing was applied", body = ProblemDetails)
+    ),
+)]
+pub async fn apply_config(
+    State(state): State<AppState>,
+    body: String,
+) -> Result<Json<ConfigDeploymentResponse>, TheorosError> {
+    let candidate = parse_candidate(&body)?;
+    let diff = state.evm_config.apply(&state, candidate).await?;
+    tracing::info!("🛠️ [Admin] Applied a new EVM config: {:?}", diff);
+    Ok(Json(ConfigDeploymentResponse { applied: true, diff }))
+}
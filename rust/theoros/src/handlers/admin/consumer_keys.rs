@@ -0,0 +1,108 @@
+use axum::{extract::State, Json};
+use serde::{Deserialize, Serialize};
+use utoipa::{ToResponse, ToSchema};
+
+use crate::{
+    errors::TheorosError,
+    extractors::{JsonExtractor, PathExtractor},
+    storage::SettingKind,
+    types::encryption::ConsumerPublicKey,
+    AppState,
+};
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct RegisterConsumerKeyRequest {
+    /// Hex-encoded X25519 public key of the consumer.
+    pub public_key: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToResponse, ToSchema)]
+pub struct ConsumerKeyResponse {
+    pub consumer_id: String,
+    /// Identifier of the key used to encrypt the calldata served to the consumer.
+    pub key_id: String,
+}
+
+impl ConsumerKeyResponse {
+    fn new(consumer_id: String, key: &ConsumerPublicKey) -> Self {
+        Self { consumer_id, key_id: key.key_id() }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, ToResponse, ToSchema)]
+pub struct ConsumerKeysResponse(pub Vec<ConsumerKeyResponse>);
+
+#[utoipa::path(
+    get,
+    path = "/v1/admin/consumers",
+    responses(
+        (status = 200, description = "Get the consumers & the id of their current encryption key", body = ConsumerKeysResponse),
+        (status = 401, description = "Missing or invalid admin API key", body = ProblemDetails)
+    ),
+)]
+pub async fn get_consumer_keys(State(state): State<AppState>) -> Result<Json<ConsumerKeysResponse>, TheorosError> {
+    let mut consumers: Vec<_> = state
+        .storage
+        .consumer_keys()
+        .all()
+        .into_iter()
+        .map(|(consumer_id, key)| ConsumerKeyResponse::new(consumer_id, &key))
+        .collect();
+    consumers.sort_by(|a, b| a.consumer_id.cmp(&b.consumer_id));
+    Ok(Json(ConsumerKeysResponse(consumers)))
+}
+
+#[utoipa::path(
+    put,
+    path = "/v1/admin/consumers/{consumer_id}/key",
+    params(
+        ("consumer_id" = String, Path, description = "The consumer registering its key")
+    ),
+    request_body = RegisterConsumerKeyRequest,
+    responses(
+        (status = 200, description = "Register or rotate the encryption key of a consumer", body = ConsumerKeyResponse),
+        (status = 400, description = "Invalid public key", body = ProblemDetails),
+        (status = 401, description = "Missing or invalid admin API key", body = ProblemDetails)
+    ),
+)]
+pub async fn register_consumer_key(
+    State(state): State<AppState>,
+    PathExtractor(consumer_id): PathExtractor<String>,
+    JsonExtractor(request): JsonExtractor<RegisterConsumerKeyRequest>,
+) -> Result<Json<ConsumerKeyResponse>, TheorosError> {
+    let key =
+        ConsumerPublicKey::from_hex(&request.public_key).map_err(|e| TheorosError::InvalidPublicKey(e.to_string()))?;
+    let response = ConsumerKeyResponse::new(consumer_id.clone(), &key);
+
+    state.storage.save_setting(SettingKind::ConsumerKey, &consumer_id, key.to_hex()).await;
+    state.storage.consumer_keys().set(consumer_id, key);
+    tracing::info!("🛠️ [Admin] Registered key {} for consumer {}", response.key_id, response.consumer_id);
+
+    Ok(Json(response))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/v1/admin/consumers/{consumer_id}/key",
+    params(
+        ("consumer_id" = String, Path, description = "The consumer whose key is revoked")
+    ),
+    responses(
+        (status = 200, description = "Revoke the encryption key of a consumer", body = ConsumerKeyResponse),
+        (status = 401, description = "Missing or invalid admin API key", body = ProblemDetails),
+        (status = 404, description = "Unknown consumer", body = ProblemDetails)
+    ),
+)]
+pub async fn revoke_consumer_key(
+    State(state): State<AppState>,
+    PathExtractor(consumer_id): PathExtractor<String>,
+) -> Result<Json<ConsumerKeyResponse>, TheorosError> {
+    let key = state
+        .storage
+        .consumer_keys()
+        .remove(&consumer_id)
+        .ok_or(TheorosError::ConsumerNotFound(consumer_id.clone()))?;
+    state.storage.remove_setting(SettingKind::ConsumerKey, &consumer_id).await;
+    tracing::info!("🛠️ [Admin] Revoked key {} of consumer {}", key.key_id(), consumer_id);
+    Ok(Json(ConsumerKeyResponse::new(consumer_id, &key)))
+}
@@ -0,0 +1,100 @@
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToResponse, ToSchema};
+
+use crate::{
+    errors::TheorosError,
+    extractors::PathExtractor,
+    services::indexer::store_dispatch,
+    storage::{DeadLetter, DeadLetterEvent, RawDispatchEvent},
+    types::hyperlane::{DispatchEvent, FromStarknetEventData, ValidatorAnnouncementEvent},
+    AppState,
+};
+
+#[derive(Debug, Deserialize, IntoParams, ToSchema)]
+pub struct GetDeadLettersQuery {
+    /// Only return the dead letters of this event.
+    pub event: Option<DeadLetterEvent>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, ToResponse, ToSchema)]
+pub struct GetDeadLettersResponse(pub Vec<DeadLetter>);
+
+#[derive(Debug, Serialize, Deserialize, ToResponse, ToSchema)]
+pub struct ReplayDeadLetterResponse {
+    pub id: u64,
+    pub event: DeadLetterEvent,
+    /// Nonce of the replayed dispatch.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<u32>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/admin/dead_letters",
+    params(
+        GetDeadLettersQuery
+    ),
+    responses(
+        (status = 200, description = "Get the indexed events that could not be decoded, most recent first", body = GetDeadLettersResponse),
+        (status = 401, description = "Missing or invalid admin API key", body = ProblemDetails)
+    ),
+)]
+pub async fn get_dead_letters(
+    State(state): State<AppState>,
+    Query(params): Query<GetDeadLettersQuery>,
+) -> Result<Json<GetDeadLettersResponse>, TheorosError> {
+    let mut dead_letters = state.storage.dead_letters().all().await;
+    if let Some(event) = params.event {
+        dead_letters.retain(|dead_letter| dead_letter.event == event);
+    }
+    Ok(Json(GetDeadLettersResponse(dead_letters)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/admin/dead_letters/{id}/replay",
+    params(
+        ("id" = u64, Path, description = "The dead letter to replay")
+    ),
+    responses(
+        (status = 200, description = "Decode the dead letter with the current parser & index it as if it was just emitted, then remove it", body = ReplayDeadLetterResponse),
+        (status = 401, description = "Missing or invalid admin API key", body = ProblemDetails),
+        (status = 404, description = "Unknown or already replayed dead letter", body = ProblemDetails),
+        (status = 422, description = "The event still can't be decoded", body = ProblemDetails)
+    ),
+)]
+pub async fn replay_dead_letter(
+    State(state): State<AppState>,
+    PathExtractor(id): PathExtractor<u64>,
+) -> Result<Json<ReplayDeadLetterResponse>, TheorosError> {
+    let dead_letter = state.storage.dead_letters().get(id).await.ok_or(TheorosError::DeadLetterNotFound(id))?;
+    let not_decoded = |e: anyhow::Error| TheorosError::DeadLetterNotDecoded(format!("{e:#}"));
+    let data = dead_letter.data_felts().map_err(not_decoded)?;
+
+    // Replayed dispatches aren't rolled back by reorgs: their block is usually final by then.
+    let nonce = match dead_letter.event {
+        DeadLetterEvent::Dispatch => {
+            let event = DispatchEvent::from_starknet_event_data(data.clone()).map_err(not_decoded)?;
+            let nonce = event.message.header.nonce;
+            let keys = dead_letter.key_felts().map_err(not_decoded)?;
+            let transaction_hash = dead_letter.transaction_hash_felt().map_err(not_decoded)?;
+            let raw_event = RawDispatchEvent::new(nonce, dead_letter.block_number, transaction_hash, &keys, &data);
+            store_dispatch(&state, &event, raw_event).await;
+            Some(nonce)
+        }
+        DeadLetterEvent::ValidatorAnnouncement => {
+            let event = ValidatorAnnouncementEvent::from_starknet_event_data(data).map_err(not_decoded)?;
+            state.storage.validators_fetchers().add_from_announcement_event(event).await?;
+            None
+        }
+    };
+    state.storage.remove_dead_letter(id).await;
+    state.metrics.events_indexed.with_label_values(&[dead_letter.event.as_str()]).inc();
+    tracing::info!("🛠️ [Admin] Replayed the dead letter #{} ({})", id, dead_letter.event.as_str());
+
+    Ok(Json(ReplayDeadLetterResponse { id, event: dead_letter.event, nonce }))
+}
@@ -0,0 +1,25 @@
+use axum::{extract::State, Json};
+
+use crate::{
+    diagnostics::{self, DiagnosticBundle},
+    errors::TheorosError,
+    AppState,
+};
+
+#[utoipa::path(
+    get,
+    path = "/v1/admin/diagnostics",
+    responses(
+        (
+            status = 200,
+            description = "Get the diagnostic bundle of the instance, also logged when receiving a SIGQUIT",
+            body = DiagnosticBundle
+        ),
+        (status = 401, description = "Missing or invalid admin API key", body = ProblemDetails)
+    ),
+)]
+pub async fn get_diagnostics(State(state): State<AppState>) -> Result<Json<DiagnosticBundle>, TheorosError> {
+    let bundle = diagnostics::collect(&state).await;
+    tracing::info!("🛠️ [Admin] Diagnostic bundle collected");
+    Ok(Json(bundle))
+}
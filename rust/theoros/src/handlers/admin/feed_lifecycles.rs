@@ -0,0 +1,79 @@
+use axum::{extract::State, Json};
+use serde::{Deserialize, Serialize};
+use utoipa::{ToResponse, ToSchema};
+
+use crate::{
+    configs::feed_lifecycle::FeedLifecycle,
+    errors::TheorosError,
+    extractors::{JsonExtractor, PathExtractor},
+    storage::SettingKind,
+    AppState,
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToResponse, ToSchema)]
+pub struct FeedLifecycleResponse {
+    pub feed_id: String,
+    /// The lifecycle as configured, its `state` taking the scheduled deprecation & retirement into account.
+    #[serde(flatten)]
+    pub lifecycle: FeedLifecycle,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, ToResponse, ToSchema)]
+pub struct GetFeedLifecyclesResponse(pub Vec<FeedLifecycleResponse>);
+
+#[utoipa::path(
+    get,
+    path = "/v1/admin/feeds/lifecycles",
+    responses(
+        (status = 200, description = "Get the lifecycle of the feeds that aren't simply active", body = GetFeedLifecyclesResponse),
+        (status = 401, description = "Missing or invalid admin API key", body = ProblemDetails)
+    ),
+)]
+pub async fn get_feed_lifecycles(
+    State(state): State<AppState>,
+) -> Result<Json<GetFeedLifecyclesResponse>, TheorosError> {
+    let mut lifecycles: Vec<_> = state
+        .feed_lifecycles
+        .all()
+        .into_iter()
+        .map(|(feed_id, lifecycle)| FeedLifecycleResponse { feed_id, lifecycle: lifecycle.current() })
+        .collect();
+    lifecycles.sort_by(|a, b| a.feed_id.cmp(&b.feed_id));
+    Ok(Json(GetFeedLifecyclesResponse(lifecycles)))
+}
+
+#[utoipa::path(
+    put,
+    path = "/v1/admin/feeds/{feed_id}/lifecycle",
+    params(
+        ("feed_id" = String, Path, description = "The feed to update")
+    ),
+    request_body = FeedLifecycle,
+    responses(
+        (status = 200, description = "Update the lifecycle of the feed", body = FeedLifecycleResponse),
+        (status = 400, description = "Invalid feed lifecycle", body = ProblemDetails),
+        (status = 401, description = "Missing or invalid admin API key", body = ProblemDetails),
+        (status = 404, description = "Unknown feed", body = ProblemDetails)
+    ),
+)]
+pub async fn update_feed_lifecycle(
+    State(state): State<AppState>,
+    PathExtractor(feed_id): PathExtractor<String>,
+    JsonExtractor(lifecycle): JsonExtractor<FeedLifecycle>,
+) -> Result<Json<FeedLifecycleResponse>, TheorosError> {
+    if !state.storage.feed_ids().contains(&feed_id) {
+        return Err(TheorosError::FeedNotFound(feed_id));
+    }
+    lifecycle.validate(&feed_id).map_err(TheorosError::InvalidFeedLifecycle)?;
+    if let Some(replaced_by) = &lifecycle.replaced_by {
+        if !state.storage.feed_ids().contains(replaced_by) {
+            return Err(TheorosError::InvalidFeedLifecycle(format!("replacement feed {replaced_by} is not supported")));
+        }
+    }
+
+    state.storage.save_setting(SettingKind::FeedLifecycle, &feed_id, &lifecycle).await;
+    state.feed_lifecycles.set(feed_id.clone(), lifecycle.clone());
+    tracing::info!("🛠️ [Admin] Updated the lifecycle of feed {}: {:?}", feed_id, lifecycle);
+
+    Ok(Json(FeedLifecycleResponse { feed_id, lifecycle: lifecycle.current() }))
+}
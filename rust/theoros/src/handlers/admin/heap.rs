@@ -0,0 +1,83 @@
+use axum::{
+    extract::State,
+    http::header,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::{
+    errors::TheorosError,
+    extractors::JsonExtractor,
+    types::heap::{self, HeapStats},
+    AppState,
+};
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct HeapProfilingRequest {
+    /// Whether the allocations should be sampled for the heap profiles.
+    pub active: bool,
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/admin/heap/stats",
+    responses(
+        (status = 200, description = "Get the allocator memory statistics", body = HeapStats),
+        (status = 401, description = "Missing or invalid admin API key", body = ProblemDetails),
+        (status = 501, description = "Theoros was not built with jemalloc", body = ProblemDetails)
+    ),
+)]
+pub async fn get_heap_stats(State(_state): State<AppState>) -> Result<Json<HeapStats>, TheorosError> {
+    ensure_heap_profiling_available()?;
+    let stats = heap::heap_stats().map_err(|e| TheorosError::HeapProfiling(e.to_string()))?;
+    Ok(Json(stats))
+}
+
+#[utoipa::path(
+    put,
+    path = "/v1/admin/heap/profiling",
+    request_body = HeapProfilingRequest,
+    responses(
+        (status = 200, description = "Start or stop sampling the allocations", body = HeapProfilingRequest),
+        (status = 401, description = "Missing or invalid admin API key", body = ProblemDetails),
+        (status = 501, description = "Theoros was not built with jemalloc", body = ProblemDetails)
+    ),
+)]
+pub async fn update_heap_profiling(
+    State(_state): State<AppState>,
+    JsonExtractor(request): JsonExtractor<HeapProfilingRequest>,
+) -> Result<Json<HeapProfilingRequest>, TheorosError> {
+    ensure_heap_profiling_available()?;
+    heap::set_profiling_active(request.active).map_err(|e| TheorosError::HeapProfiling(e.to_string()))?;
+    tracing::info!("🛠️ [Admin] Heap profiling active: {}", request.active);
+    Ok(Json(request))
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/admin/heap/dump",
+    responses(
+        (status = 200, description = "Dump a heap profile, to be analyzed with `jeprof`", content_type = "application/octet-stream", body = Vec<u8>),
+        (status = 401, description = "Missing or invalid admin API key", body = ProblemDetails),
+        (status = 501, description = "Theoros was not built with jemalloc", body = ProblemDetails)
+    ),
+)]
+pub async fn dump_heap_profile(State(_state): State<AppState>) -> Result<Response, TheorosError> {
+    ensure_heap_profiling_available()?;
+    let profile = tokio::task::spawn_blocking(heap::dump_heap_profile)
+        .await
+        .map_err(|_| TheorosError::InternalServerError)?
+        .map_err(|e| TheorosError::HeapProfiling(e.to_string()))?;
+    tracing::info!("🛠️ [Admin] Dumped a heap profile of {} bytes", profile.len());
+    Ok(([(header::CONTENT_TYPE, "application/octet-stream")], profile).into_response())
+}
+
+fn ensure_heap_profiling_available() -> Result<(), TheorosError> {
+    if heap::is_available() {
+        Ok(())
+    } else {
+        Err(TheorosError::HeapProfilingUnavailable)
+    }
+}
@@ -0,0 +1,61 @@
+use axum::{extract::State, Json};
+use serde::{Deserialize, Serialize};
+use utoipa::{ToResponse, ToSchema};
+
+use crate::{errors::TheorosError, AppState};
+
+#[derive(Debug, Default, Serialize, Deserialize, ToResponse, ToSchema)]
+pub struct IndexerStatusResponse {
+    pub paused: bool,
+    /// Nonce of the latest indexed dispatch.
+    pub latest_nonce: Option<u32>,
+}
+
+impl IndexerStatusResponse {
+    async fn current(state: &AppState) -> Self {
+        let paused = *state.indexer_paused.borrow();
+        Self { paused, latest_nonce: state.storage.raw_dispatch_events().latest_nonce().await }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/admin/indexer",
+    responses(
+        (status = 200, description = "Get whether the indexer is paused", body = IndexerStatusResponse),
+        (status = 401, description = "Missing or invalid admin API key", body = ProblemDetails)
+    ),
+)]
+pub async fn get_indexer_status(State(state): State<AppState>) -> Result<Json<IndexerStatusResponse>, TheorosError> {
+    Ok(Json(IndexerStatusResponse::current(&state).await))
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/admin/indexer/pause",
+    responses(
+        (status = 200, description = "Pause the indexer once done with its current batch, disconnecting from Apibara until resumed", body = IndexerStatusResponse),
+        (status = 401, description = "Missing or invalid admin API key", body = ProblemDetails)
+    ),
+)]
+pub async fn pause_indexer(State(state): State<AppState>) -> Result<Json<IndexerStatusResponse>, TheorosError> {
+    if !state.indexer_paused.send_replace(true) {
+        tracing::info!("🛠️ [Admin] Pausing the indexer");
+    }
+    Ok(Json(IndexerStatusResponse::current(&state).await))
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/admin/indexer/resume",
+    responses(
+        (status = 200, description = "Resume the indexer after the last processed block", body = IndexerStatusResponse),
+        (status = 401, description = "Missing or invalid admin API key", body = ProblemDetails)
+    ),
+)]
+pub async fn resume_indexer(State(state): State<AppState>) -> Result<Json<IndexerStatusResponse>, TheorosError> {
+    if state.indexer_paused.send_replace(false) {
+        tracing::info!("🛠️ [Admin] Resuming the indexer");
+    }
+    Ok(Json(IndexerStatusResponse::current(&state).await))
+}
@@ -0,0 +1,17 @@
+pub mod auth;
+pub mod caches;
+pub mod chains;
+pub mod checkpoints;
+pub mod config;
+pub mod consumer_keys;
+pub mod dead_letters;
+pub mod diagnostics;
+pub mod feed_lifecycles;
+pub mod heap;
+pub mod indexer;
+pub mod parse_failures;
+pub mod private_feeds;
+pub mod quarantine;
+pub mod state;
+pub mod tracing_sampling;
+pub mod validators;
@@ -0,0 +1,60 @@
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToResponse, ToSchema};
+
+use crate::{errors::TheorosError, storage::DispatchParseFailure, AppState};
+
+#[derive(Debug, Deserialize, IntoParams, ToSchema)]
+pub struct GetParseFailuresQuery {
+    /// Only return the failures of the dispatch with this nonce.
+    pub nonce: Option<u32>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, ToResponse, ToSchema)]
+pub struct GetParseFailuresResponse(pub Vec<DispatchParseFailure>);
+
+#[derive(Debug, Default, Serialize, Deserialize, ToResponse, ToSchema)]
+pub struct ClearParseFailuresResponse {
+    pub removed: usize,
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/admin/parse_failures",
+    params(
+        GetParseFailuresQuery
+    ),
+    responses(
+        (status = 200, description = "Get the indexed updates that could not be parsed, most recent first", body = GetParseFailuresResponse),
+        (status = 401, description = "Missing or invalid admin API key", body = ProblemDetails)
+    ),
+)]
+pub async fn get_parse_failures(
+    State(state): State<AppState>,
+    Query(params): Query<GetParseFailuresQuery>,
+) -> Result<Json<GetParseFailuresResponse>, TheorosError> {
+    let mut failures = state.storage.parse_failures().all().await;
+    if let Some(nonce) = params.nonce {
+        failures.retain(|failure| failure.nonce == nonce);
+    }
+    Ok(Json(GetParseFailuresResponse(failures)))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/v1/admin/parse_failures",
+    responses(
+        (status = 200, description = "Remove all the parse failures", body = ClearParseFailuresResponse),
+        (status = 401, description = "Missing or invalid admin API key", body = ProblemDetails)
+    ),
+)]
+pub async fn clear_parse_failures(
+    State(state): State<AppState>,
+) -> Result<Json<ClearParseFailuresResponse>, TheorosError> {
+    let removed = state.storage.parse_failures().clear().await;
+    tracing::info!("🛠️ [Admin] Removed {} parse failures", removed);
+    Ok(Json(ClearParseFailuresResponse { removed }))
+}
@@ -0,0 +1,66 @@
+use axum::{extract::State, Json};
+use serde::{Deserialize, Serialize};
+use utoipa::{ToResponse, ToSchema};
+
+use crate::{
+    errors::TheorosError,
+    extractors::{JsonExtractor, PathExtractor},
+    storage::SettingKind,
+    AppState,
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct UpdateFeedPrivacyRequest {
+    /// Whether the calldata of the feed is only served encrypted for a registered consumer.
+    pub private: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToResponse, ToSchema)]
+pub struct FeedPrivacyResponse {
+    pub feed_id: String,
+    pub private: bool,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, ToResponse, ToSchema)]
+pub struct GetPrivateFeedsResponse(pub Vec<String>);
+
+#[utoipa::path(
+    get,
+    path = "/v1/admin/feeds/private",
+    responses(
+        (status = 200, description = "Get the feeds whose calldata is only served encrypted for a registered consumer", body = GetPrivateFeedsResponse),
+        (status = 401, description = "Missing or invalid admin API key", body = ProblemDetails)
+    ),
+)]
+pub async fn get_private_feeds(State(state): State<AppState>) -> Result<Json<GetPrivateFeedsResponse>, TheorosError> {
+    Ok(Json(GetPrivateFeedsResponse(state.private_feeds.all())))
+}
+
+#[utoipa::path(
+    put,
+    path = "/v1/admin/feeds/{feed_id}/private",
+    params(
+        ("feed_id" = String, Path, description = "The feed to update")
+    ),
+    request_body = UpdateFeedPrivacyRequest,
+    responses(
+        (status = 200, description = "Make the feed private or public", body = FeedPrivacyResponse),
+        (status = 401, description = "Missing or invalid admin API key", body = ProblemDetails),
+        (status = 404, description = "Unknown feed", body = ProblemDetails)
+    ),
+)]
+pub async fn update_feed_privacy(
+    State(state): State<AppState>,
+    PathExtractor(feed_id): PathExtractor<String>,
+    JsonExtractor(request): JsonExtractor<UpdateFeedPrivacyRequest>,
+) -> Result<Json<FeedPrivacyResponse>, TheorosError> {
+    if !state.storage.feed_ids().contains(&feed_id) {
+        return Err(TheorosError::FeedNotFound(feed_id));
+    }
+
+    state.storage.save_setting(SettingKind::PrivateFeed, &feed_id, request.private).await;
+    state.private_feeds.set(feed_id.clone(), request.private);
+    tracing::info!("🛠️ [Admin] Made feed {} {}", feed_id, if request.private { "private" } else { "public" });
+
+    Ok(Json(FeedPrivacyResponse { feed_id, private: request.private }))
+}
@@ -0,0 +1,58 @@
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToResponse, ToSchema};
+
+use crate::{errors::TheorosError, storage::QuarantinedCheckpoint, AppState};
+
+#[derive(Debug, Deserialize, IntoParams, ToSchema)]
+pub struct GetQuarantineQuery {
+    /// Only return the checkpoints of this validator.
+    pub validator: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, ToResponse, ToSchema)]
+pub struct GetQuarantineResponse(pub Vec<QuarantinedCheckpoint>);
+
+#[derive(Debug, Default, Serialize, Deserialize, ToResponse, ToSchema)]
+pub struct ClearQuarantineResponse {
+    pub removed: usize,
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/admin/quarantine",
+    params(
+        GetQuarantineQuery
+    ),
+    responses(
+        (status = 200, description = "Get the fetched checkpoints that failed validation, most recent first", body = GetQuarantineResponse),
+        (status = 401, description = "Missing or invalid admin API key", body = ProblemDetails)
+    ),
+)]
+pub async fn get_quarantine(
+    State(state): State<AppState>,
+    Query(params): Query<GetQuarantineQuery>,
+) -> Result<Json<GetQuarantineResponse>, TheorosError> {
+    let mut quarantined = state.storage.quarantine().all().await;
+    if let Some(validator) = params.validator {
+        quarantined.retain(|q| q.validator.eq_ignore_ascii_case(&validator));
+    }
+    Ok(Json(GetQuarantineResponse(quarantined)))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/v1/admin/quarantine",
+    responses(
+        (status = 200, description = "Empty the quarantine", body = ClearQuarantineResponse),
+        (status = 401, description = "Missing or invalid admin API key", body = ProblemDetails)
+    ),
+)]
+pub async fn clear_quarantine(State(state): State<AppState>) -> Result<Json<ClearQuarantineResponse>, TheorosError> {
+    let removed = state.storage.quarantine().clear().await;
+    tracing::info!("🛠️ [Admin] Removed {} checkpoints from the quarantine", removed);
+    Ok(Json(ClearQuarantineResponse { removed }))
+}
@@ -0,0 +1,127 @@
+use axum::{extract::State, Json};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use starknet::core::types::Felt;
+use utoipa::{ToResponse, ToSchema};
+
+use crate::{configs::evm_config::EvmChainName, errors::TheorosError, AppState};
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PendingDispatchDump {
+    pub nonce: u32,
+    pub indexed_at: Option<DateTime<Utc>>,
+    /// Validators whose checkpoint of the nonce is stored.
+    pub signed_by: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct LatestUpdateDump {
+    pub feed_id: String,
+    /// Nonce of the dispatch the feed was last updated by.
+    pub nonce: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ValidatorDump {
+    pub validator: String,
+    /// Storage location the checkpoints of the validator are fetched from first.
+    pub location: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ValidatorSetDump {
+    pub chain: EvmChainName,
+    pub validators: Vec<String>,
+    pub threshold: usize,
+}
+
+/// The internal state of the instance, in more detail than the diagnostic bundle.
+#[derive(Debug, Clone, Serialize, Deserialize, ToResponse, ToSchema)]
+pub struct StateDump {
+    pub generated_at: DateTime<Utc>,
+    pub indexer_paused: bool,
+    /// Nonce of the latest indexed dispatch.
+    pub latest_indexed_nonce: Option<u32>,
+    /// Dispatches whose checkpoints are still being collected, by nonce.
+    pub pending_dispatches: Vec<PendingDispatchDump>,
+    /// Latest update of each feed, by feed id.
+    pub latest_updates: Vec<LatestUpdateDump>,
+    /// Validators checkpoints are fetched from, by address.
+    pub validators: Vec<ValidatorDump>,
+    /// Validators & threshold of each chain, by chain name.
+    pub validator_sets: Vec<ValidatorSetDump>,
+    /// Checkpoints in the cache of the fetched checkpoints, if enabled.
+    pub cached_checkpoints: Option<usize>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/admin/state",
+    responses(
+        (status = 200, description = "Dump the internal state of the instance: pending dispatches & their signatures, latest updates, validators & caches", body = StateDump),
+        (status = 401, description = "Missing or invalid admin API key", body = ProblemDetails)
+    ),
+)]
+pub async fn get_state_dump(State(state): State<AppState>) -> Result<Json<StateDump>, TheorosError> {
+    let storage = &state.storage;
+    let fetchers = storage.validators_fetchers().all();
+    let validator_addresses: Vec<Felt> = fetchers.keys().copied().collect();
+
+    let mut pending_dispatches = Vec::new();
+    for nonce in storage.unsigned_checkpoints().nonces().await {
+        let mut signed_by: Vec<String> = storage
+            .signed_checkpoints()
+            .get(&validator_addresses, nonce)
+            .into_iter()
+            .map(|(validator, _)| format!("{:#x}", validator))
+            .collect();
+        signed_by.sort();
+        let indexed_at = storage.raw_dispatch_events().get(nonce).await.map(|raw_event| raw_event.indexed_at);
+        pending_dispatches.push(PendingDispatchDump { nonce, indexed_at, signed_by });
+    }
+
+    let mut latest_updates: Vec<LatestUpdateDump> = storage
+        .latest_update_per_feed()
+        .latest_nonces()
+        .into_iter()
+        .map(|(feed_id, nonce)| LatestUpdateDump { feed_id: format!("{:#x}", feed_id), nonce })
+        .collect();
+    latest_updates.sort_by(|a, b| a.feed_id.cmp(&b.feed_id));
+
+    let mut validators: Vec<ValidatorDump> = fetchers
+        .iter()
+        .map(|(validator, fetcher)| ValidatorDump {
+            validator: format!("{:#x}", validator),
+            location: fetcher.announcement_location(),
+        })
+        .collect();
+    validators.sort_by(|a, b| a.validator.cmp(&b.validator));
+
+    let mut validator_sets: Vec<ValidatorSetDump> = state
+        .hyperlane_validators_mapping
+        .chain_names()
+        .into_iter()
+        .filter_map(|chain| {
+            let validator_set = state.hyperlane_validators_mapping.get_validator_set(&chain)?;
+            let mut validators: Vec<String> =
+                validator_set.validators.keys().map(|validator| format!("{:#x}", validator)).collect();
+            validators.sort();
+            Some(ValidatorSetDump { chain, validators, threshold: validator_set.threshold })
+        })
+        .collect();
+    validator_sets.sort_by_key(|validator_set| validator_set.chain.to_string());
+
+    let indexer_paused = *state.indexer_paused.borrow();
+    let dump = StateDump {
+        generated_at: Utc::now(),
+        indexer_paused,
+        latest_indexed_nonce: storage.raw_dispatch_events().latest_nonce().await,
+        pending_dispatches,
+        latest_updates,
+        validators,
+        validator_sets,
+        cached_checkpoints: storage.validators_fetchers().checkpoint_cache().map(|cache| cache.len()),
+    };
+    tracing::info!("🛠️ [Admin] Internal state dumped");
+    Ok(Json(dump))
+}
@@ -0,0 +1,70 @@
+use axum::{extract::State, Json};
+use serde::{Deserialize, Serialize};
+use utoipa::{ToResponse, ToSchema};
+
+use pragma_utils::tracing::SamplingRule;
+
+use crate::{errors::TheorosError, extractors::JsonExtractor, storage::SettingKind, AppState};
+
+/// Id of the persisted tracing sampling rules, which are replaced all at once.
+const TRACING_SAMPLING_SETTING: &str = "rules";
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToResponse, ToSchema)]
+pub struct TracingSamplingRule {
+    /// Prefix of the tracing targets matched by this rule, e.g. `theoros::handlers`.
+    pub target: String,
+    /// Ratio of the matching spans & events that are kept, between 0 and 1.
+    pub rate: f64,
+}
+
+impl From<SamplingRule> for TracingSamplingRule {
+    fn from(rule: SamplingRule) -> Self {
+        Self { target: rule.target, rate: rule.rate }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, ToResponse, ToSchema)]
+pub struct TracingSamplingResponse(pub Vec<TracingSamplingRule>);
+
+#[utoipa::path(
+    get,
+    path = "/v1/admin/tracing/sampling",
+    responses(
+        (status = 200, description = "Get the tracing sampling rules currently applied", body = TracingSamplingResponse),
+        (status = 401, description = "Missing or invalid admin API key", body = ProblemDetails)
+    ),
+)]
+pub async fn get_tracing_sampling(
+    State(state): State<AppState>,
+) -> Result<Json<TracingSamplingResponse>, TheorosError> {
+    let rules = state.tracing_sampler.rules().into_iter().map(TracingSamplingRule::from).collect();
+    Ok(Json(TracingSamplingResponse(rules)))
+}
+
+#[utoipa::path(
+    put,
+    path = "/v1/admin/tracing/sampling",
+    request_body = Vec<TracingSamplingRule>,
+    responses(
+        (status = 200, description = "Replace the tracing sampling rules", body = TracingSamplingResponse),
+        (status = 400, description = "Invalid sampling rule", body = ProblemDetails),
+        (status = 401, description = "Missing or invalid admin API key", body = ProblemDetails)
+    ),
+)]
+pub async fn update_tracing_sampling(
+    State(state): State<AppState>,
+    JsonExtractor(rules): JsonExtractor<Vec<TracingSamplingRule>>,
+) -> Result<Json<TracingSamplingResponse>, TheorosError> {
+    let rules = rules
+        .into_iter()
+        .map(|rule| SamplingRule::new(rule.target, rule.rate))
+        .collect::<anyhow::Result<Vec<_>>>()
+        .map_err(|e| TheorosError::InvalidSamplingRule(e.to_string()))?;
+
+    state.tracing_sampler.set_rules(rules);
+    tracing::info!("🛠️ [Admin] Updated tracing sampling rules: {:?}", state.tracing_sampler.rules());
+
+    let rules: Vec<_> = state.tracing_sampler.rules().into_iter().map(TracingSamplingRule::from).collect();
+    state.storage.save_setting(SettingKind::TracingSampling, TRACING_SAMPLING_SETTING, &rules).await;
+    Ok(Json(TracingSamplingResponse(rules)))
+}
@@ -0,0 +1,48 @@
+use axum::{extract::State, Json};
+use serde::{Deserialize, Serialize};
+use utoipa::{ToResponse, ToSchema};
+
+use crate::{
+    configs::evm_config::EvmChainName, errors::TheorosError, services::validators_refresh::refresh_validators,
+    types::config_deployment::ValidatorsChange, AppState,
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ValidatorsRefreshFailure {
+    pub chain: EvmChainName,
+    pub error: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, ToResponse, ToSchema)]
+pub struct RefreshValidatorsResponse {
+    /// Chains whose validators changed, now swapped in.
+    pub changed: Vec<ValidatorsChange>,
+    /// Chains whose validators couldn't be fetched, keeping the current ones.
+    pub failed: Vec<ValidatorsRefreshFailure>,
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/admin/validators/refresh",
+    responses(
+        (status = 200, description = "Fetch again the validators & threshold of each enabled chain from its ISM without waiting for the next refresh, & swap in the ones that changed", body = RefreshValidatorsResponse),
+        (status = 401, description = "Missing or invalid admin API key", body = ProblemDetails)
+    ),
+)]
+pub async fn refresh_validator_sets(
+    State(state): State<AppState>,
+) -> Result<Json<RefreshValidatorsResponse>, TheorosError> {
+    let mut response = RefreshValidatorsResponse::default();
+    for (chain, change) in refresh_validators(&state).await {
+        match change {
+            Ok(change) => response.changed.push(change),
+            Err(e) => response.failed.push(ValidatorsRefreshFailure { chain, error: format!("{e:#}") }),
+        }
+    }
+    tracing::info!(
+        "🛠️ [Admin] Refreshed the validators: {} chains changed, {} failed",
+        response.changed.len(),
+        response.failed.len()
+    );
+    Ok(Json(response))
+}
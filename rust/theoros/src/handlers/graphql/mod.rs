@@ -0,0 +1,35 @@
+pub mod schema;
+
+use async_graphql::http::GraphiQLSource;
+use async_graphql_axum::{GraphQLRequest, GraphQLResponse};
+use axum::{response::Html, Extension};
+
+use schema::TheorosSchema;
+
+/// Executes a GraphQL query over the feeds, their updates, the checkpoints & the chains.
+#[utoipa::path(
+    post,
+    path = "/v1/graphql",
+    request_body(content = String, description = "GraphQL request", content_type = "application/json"),
+    responses(
+        (status = 200, description = "GraphQL response, with the errors of the query if any", body = String)
+    ),
+)]
+pub async fn graphql_handler(Extension(schema): Extension<TheorosSchema>, request: GraphQLRequest) -> GraphQLResponse {
+    let started_at = std::time::Instant::now();
+    let response = schema.execute(request.into_inner()).await;
+    tracing::info!("🌐 graphql - {:?}", started_at.elapsed());
+    response.into()
+}
+
+/// Serves GraphiQL, to explore the GraphQL schema from a browser.
+#[utoipa::path(
+    get,
+    path = "/v1/graphql",
+    responses(
+        (status = 200, description = "GraphiQL page", content_type = "text/html", body = String)
+    ),
+)]
+pub async fn graphiql() -> Html<String> {
+    Html(GraphiQLSource::build().endpoint("/v1/graphql").finish())
+}
@@ -0,0 +1,229 @@
+use async_graphql::{Context, EmptyMutation, EmptySubscription, Json, Object, Result, Schema, SimpleObject};
+use starknet::core::types::Felt;
+
+use pragma_feeds::Feed;
+use pragma_utils::conversions::alloy::hex_str_to_u256;
+
+use crate::{
+    constants::{GRAPHQL_MAX_COMPLEXITY, GRAPHQL_MAX_DEPTH},
+    handlers::rest::get_calldata::ensure_public,
+    types::{hyperlane::DispatchUpdateInfos, update_view::UpdateView},
+    AppState,
+};
+
+pub type TheorosSchema = Schema<QueryRoot, EmptyMutation, EmptySubscription>;
+
+/// Builds the GraphQL schema, resolving the queries against the state of the application.
+pub fn build_schema(state: AppState) -> TheorosSchema {
+    Schema::build(QueryRoot, EmptyMutation, EmptySubscription)
+        .data(state)
+        .limit_depth(GRAPHQL_MAX_DEPTH)
+        .limit_complexity(GRAPHQL_MAX_COMPLEXITY)
+        .finish()
+}
+
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    /// All the available feeds.
+    async fn feeds(&self, ctx: &Context<'_>) -> Vec<FeedNode> {
+        let state = ctx.data_unchecked::<AppState>();
+        state.storage.feed_ids().iter().map(FeedNode::new).collect()
+    }
+
+    /// A single feed, if it exists.
+    async fn feed(&self, ctx: &Context<'_>, id: String) -> Option<FeedNode> {
+        let state = ctx.data_unchecked::<AppState>();
+        state.storage.feed_ids().contains(&id).then(|| FeedNode::new(id))
+    }
+
+    /// The configured destination chains & their status.
+    async fn chains(&self, ctx: &Context<'_>) -> Vec<ChainNode> {
+        let state = ctx.data_unchecked::<AppState>();
+        let mut chains: Vec<ChainNode> = state
+            .chain_statuses
+            .all()
+            .into_iter()
+            .map(|(chain_name, status)| ChainNode {
+                name: chain_name.to_string(),
+                enabled: status.enabled,
+                indexed: status.indexes(),
+                served: status.serves(),
+                validators: state.hyperlane_validators_mapping.get_validators(&chain_name).map_or(0, |v| v.len()),
+                threshold: state.hyperlane_validators_mapping.threshold(&chain_name),
+            })
+            .collect();
+        chains.sort_by(|a, b| a.name.cmp(&b.name));
+        chains
+    }
+
+    /// The checkpoints signed by the validators for a message nonce.
+    async fn checkpoints(&self, ctx: &Context<'_>, nonce: u32) -> Vec<SignedCheckpointNode> {
+        let state = ctx.data_unchecked::<AppState>();
+        let validators: Vec<Felt> = state.storage.validators_fetchers().all().into_keys().collect();
+        state
+            .storage
+            .signed_checkpoints()
+            .get(&validators, nonce)
+            .into_iter()
+            .map(|(validator, checkpoint)| SignedCheckpointNode {
+                validator: format!("{:#x}", validator),
+                index: checkpoint.value.checkpoint.index,
+                root: checkpoint.value.checkpoint.root.clone(),
+                message_id: format!("{:#x}", checkpoint.value.message_id),
+                signature: format!("0x{}", alloy::hex::encode(<[u8; 65]>::from(checkpoint.signature))),
+            })
+            .collect()
+    }
+}
+
+/// A data feed.
+pub struct FeedNode {
+    id: String,
+    feed: Option<Feed>,
+}
+
+impl FeedNode {
+    fn new(id: String) -> Self {
+        let feed = id.parse().ok();
+        Self { id, feed }
+    }
+
+    fn latest_updates(&self, state: &AppState, limit: usize) -> Vec<DispatchUpdateInfos> {
+        let Ok(feed_id) = hex_str_to_u256(&self.id) else {
+            return Vec::new();
+        };
+        let history = state.storage.feed_history().get(&feed_id);
+        history.into_iter().rev().take(limit).collect()
+    }
+}
+
+#[Object(name = "Feed")]
+impl FeedNode {
+    async fn id(&self) -> &str {
+        &self.id
+    }
+
+    async fn pair_id(&self) -> Option<&str> {
+        self.feed.as_ref().map(|feed| feed.pair_id.as_str())
+    }
+
+    async fn asset_class(&self) -> Option<String> {
+        self.feed.as_ref().map(|feed| feed.asset_class.to_string())
+    }
+
+    async fn feed_type(&self) -> Option<String> {
+        self.feed.as_ref().map(|feed| feed.feed_type.to_string())
+    }
+
+    /// The latest signed update of the feed. Refused for a private feed.
+    async fn latest_update(&self, ctx: &Context<'_>) -> Result<Option<UpdateNode>> {
+        let state = ctx.data_unchecked::<AppState>();
+        ensure_public(state, &self.id)?;
+        let Ok(feed_id) = hex_str_to_u256(&self.id) else {
+            return Ok(None);
+        };
+        Ok(state.storage.latest_update_per_feed().get(&feed_id).map(UpdateNode))
+    }
+
+    /// The most recent signed updates of the feed, latest first. Refused for a private feed.
+    async fn updates(&self, ctx: &Context<'_>, #[graphql(default = 10)] limit: usize) -> Result<Vec<UpdateNode>> {
+        let state = ctx.data_unchecked::<AppState>();
+        ensure_public(state, &self.id)?;
+        Ok(self.latest_updates(state, limit).into_iter().map(UpdateNode).collect())
+    }
+}
+
+/// A signed update of a feed.
+pub struct UpdateNode(DispatchUpdateInfos);
+
+#[Object(name = "Update")]
+impl UpdateNode {
+    /// Nonce of the Hyperlane message carrying the update.
+    async fn nonce(&self) -> u32 {
+        self.0.nonce
+    }
+
+    async fn emitter_chain_id(&self) -> u32 {
+        self.0.emitter_chain_id
+    }
+
+    async fn emitter_address(&self) -> String {
+        format!("{:#x}", self.0.emitter_address)
+    }
+
+    /// The update, tagged by the `type` of its feed.
+    async fn data(&self) -> Json<UpdateView> {
+        Json(UpdateView::from(&self.0.update))
+    }
+
+    /// How many of the validators of each served chain signed the checkpoint of the update.
+    async fn checkpoint_status(&self, ctx: &Context<'_>) -> Vec<CheckpointStatusNode> {
+        let state = ctx.data_unchecked::<AppState>();
+        let mut statuses: Vec<CheckpointStatusNode> = state
+            .hyperlane_validators_mapping
+            .chain_names()
+            .into_iter()
+            .filter(|chain_name| state.chain_statuses.is_served(chain_name))
+            .filter_map(|chain_name| {
+                let validators: Vec<Felt> =
+                    state.hyperlane_validators_mapping.get_validators(&chain_name)?.keys().copied().collect();
+                let signers: Vec<String> = state
+                    .storage
+                    .signed_checkpoints()
+                    .get(&validators, self.0.nonce)
+                    .into_iter()
+                    .map(|(validator, _)| format!("{:#x}", validator))
+                    .collect();
+                Some(CheckpointStatusNode {
+                    chain: chain_name.to_string(),
+                    signed: signers.len(),
+                    required: validators.len(),
+                    fully_signed: signers.len() == validators.len(),
+                    signers,
+                })
+            })
+            .collect();
+        statuses.sort_by(|a, b| a.chain.cmp(&b.chain));
+        statuses
+    }
+}
+
+/// A destination chain.
+#[derive(SimpleObject)]
+#[graphql(name = "Chain")]
+pub struct ChainNode {
+    name: String,
+    enabled: bool,
+    /// Whether the state of the chain is kept up to date.
+    indexed: bool,
+    /// Whether calldata is served for the chain.
+    served: bool,
+    /// Number of validators of the ISM of the chain.
+    validators: usize,
+    /// Number of signatures required by the ISM of the chain.
+    threshold: Option<usize>,
+}
+
+/// The signatures collected for a checkpoint, from the validators of a chain.
+#[derive(SimpleObject)]
+#[graphql(name = "CheckpointStatus")]
+pub struct CheckpointStatusNode {
+    chain: String,
+    signed: usize,
+    required: usize,
+    fully_signed: bool,
+    signers: Vec<String>,
+}
+
+/// A checkpoint signed by a validator.
+#[derive(SimpleObject)]
+#[graphql(name = "SignedCheckpoint")]
+pub struct SignedCheckpointNode {
+    validator: String,
+    index: u32,
+    root: String,
+    message_id: String,
+    signature: String,
+}
@@ -0,0 +1,337 @@
+pub mod proto {
+    tonic::include_proto!("theoros.v1");
+}
+
+use std::{str::FromStr, time::Instant};
+
+use alloy::hex;
+use tokio::sync::{broadcast::error::RecvError, mpsc};
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::{Request, Response, Status};
+
+use pragma_feeds::Feed;
+use pragma_utils::conversions::alloy::hex_str_to_u256;
+
+use crate::{
+    configs::{evm_config::EvmChainName, feed_lifecycle::FeedLifecycleState},
+    constants::GRPC_STREAM_BUFFER_SIZE,
+    errors::TheorosError,
+    handlers::{
+        rest::{
+            get_calldata::{ensure_private_feeds_encrypted, ensure_public, serve_calldata, GetCalldataQuery},
+            get_data_feeds::{feed_freshness, unix_now},
+        },
+        websocket::fanout::{FanoutBatch, FanoutSubscriptions, SubscriptionKind},
+    },
+    types::{
+        api::v1::{CalldataResponse, LatestFeedUpdate, RpcDataFeed},
+        update_view::UpdateView,
+    },
+    AppState,
+};
+use proto::theoros_server::Theoros;
+
+/// Serves the gRPC API, mirroring the REST API over the same state.
+pub struct TheorosGrpc {
+    state: AppState,
+}
+
+impl TheorosGrpc {
+    pub fn new(state: AppState) -> Self {
+        Self { state }
+    }
+}
+
+#[tonic::async_trait]
+impl Theoros for TheorosGrpc {
+    async fn list_feeds(
+        &self,
+        _request: Request<proto::ListFeedsRequest>,
+    ) -> Result<Response<proto::ListFeedsResponse>, Status> {
+        let started_at = Instant::now();
+
+        let feed_ids = self.state.storage.feed_ids();
+        let now = unix_now();
+        let mut feeds = Vec::with_capacity(feed_ids.len());
+        for feed_id in feed_ids.iter() {
+            let feed: Feed = feed_id.parse().map_err(|_| TheorosError::InvalidFeedId(feed_id.clone()))?;
+            let lifecycle = self.state.feed_lifecycles.get(&feed_id).current();
+            let freshness = feed_freshness(&self.state, &feed_id, now);
+            feeds.push(proto::Feed {
+                feed_id: feed.feed_id,
+                pair_id: feed.pair_id,
+                asset_class: feed.asset_class.to_string(),
+                feed_type: feed.feed_type.to_string(),
+                lifecycle: lifecycle_name(lifecycle.state).to_owned(),
+                replaced_by: lifecycle.replaced_by,
+                status: freshness.status.as_str().to_owned(),
+                last_update_timestamp: freshness.last_update_timestamp,
+            });
+        }
+
+        tracing::info!("🌐 grpc list_feeds - {:?}", started_at.elapsed());
+        Ok(Response::new(proto::ListFeedsResponse { feeds }))
+    }
+
+    async fn get_latest_update(
+        &self,
+        request: Request<proto::GetLatestUpdateRequest>,
+    ) -> Result<Response<proto::LatestUpdate>, Status> {
+        let started_at = Instant::now();
+        let feed_id = request.into_inner().feed_id;
+
+        if !self.state.storage.feed_ids().contains(&feed_id) {
+            return Err(TheorosError::FeedNotFound(feed_id).into());
+        }
+        ensure_public(&self.state, &feed_id)?;
+        let feed_id_u256 = hex_str_to_u256(&feed_id).map_err(|_| TheorosError::InvalidFeedId(feed_id.clone()))?;
+        let update = self
+            .state
+            .storage
+            .latest_update_per_feed()
+            .get(&feed_id_u256)
+            .ok_or_else(|| Status::not_found(format!("No update of the feed {feed_id} indexed yet")))?;
+        let latest = LatestFeedUpdate::new(&self.state, &update);
+
+        tracing::info!("🌐 grpc get_latest_update - {:?}", started_at.elapsed());
+        Ok(Response::new(proto::LatestUpdate {
+            feed_id,
+            nonce: latest.nonce,
+            emitter_chain_id: latest.emitter_chain_id,
+            emitter_address: latest.emitter_address,
+            update: Some(latest.update.into()),
+            checkpoints: latest
+                .checkpoints
+                .into_iter()
+                .map(|status| proto::ChainCheckpointStatus {
+                    chain: status.chain.to_string(),
+                    signed: status.signed as u32,
+                    validators: status.validators as u32,
+                    fully_signed: status.fully_signed,
+                })
+                .collect(),
+        }))
+    }
+
+    async fn get_calldata(
+        &self,
+        request: Request<proto::GetCalldataRequest>,
+    ) -> Result<Response<proto::GetCalldataResponse>, Status> {
+        let started_at = Instant::now();
+        let request = request.into_inner();
+
+        let params = GetCalldataQuery {
+            chain: request.chain,
+            feed_ids: request.feed_ids,
+            order: Default::default(),
+            consumer: request.consumer,
+        };
+        let calldata = serve_calldata(&self.state, params, started_at).await?;
+
+        tracing::info!("🌐 grpc get_calldata - {:?}", started_at.elapsed());
+        Ok(Response::new(proto::GetCalldataResponse {
+            calldata: calldata.into_iter().map(proto::Calldata::from).collect(),
+        }))
+    }
+
+    type StreamUpdatesStream = ReceiverStream<Result<proto::FeedUpdate, Status>>;
+
+    async fn stream_updates(
+        &self,
+        request: Request<proto::StreamUpdatesRequest>,
+    ) -> Result<Response<Self::StreamUpdatesStream>, Status> {
+        let request = request.into_inner();
+        let chain = EvmChainName::from_str(&request.chain)
+            .map_err(|_| TheorosError::ChainNotSupported(request.chain.clone()))?;
+        if !self.state.hyperlane_validators_mapping.is_supported_chain(&chain) {
+            return Err(TheorosError::ChainNotSupported(request.chain).into());
+        }
+        if !self.state.chain_statuses.is_served(&chain) {
+            return Err(TheorosError::ChainDisabled(request.chain).into());
+        }
+        if let Some(missing_id) = self.state.storage.feed_ids().contains_vec(&request.feed_ids) {
+            return Err(TheorosError::FeedNotFound(missing_id).into());
+        }
+        if let Some(feed_id) = self.state.feed_lifecycles.first_retired(&request.feed_ids) {
+            let replaced_by = self.state.feed_lifecycles.get(feed_id).replaced_by;
+            return Err(TheorosError::FeedRetired { feed_id: feed_id.clone(), replaced_by }.into());
+        }
+        // The calldata pushed is built once for all the subscribers, so it can't be encrypted for each one.
+        ensure_private_feeds_encrypted(&self.state, &request.feed_ids, None)?;
+        if request.feed_ids.len() > self.state.max_batch_feeds {
+            let (requested, max) = (request.feed_ids.len(), self.state.max_batch_feeds);
+            return Err(TheorosError::TooManyFeeds { requested, max }.into());
+        }
+
+        let mut subscriptions = FanoutSubscriptions::new(self.state.ws.clone(), SubscriptionKind::FeedUpdates);
+        for feed_id in request.feed_ids {
+            subscriptions.insert(feed_id, chain);
+        }
+        let (sender, receiver) = mpsc::channel(GRPC_STREAM_BUFFER_SIZE);
+        tokio::spawn(push_updates(self.state.clone(), subscriptions, sender));
+        Ok(Response::new(ReceiverStream::new(receiver)))
+    }
+}
+
+/// Pushes the updates of the subscribed feeds until the client disconnects or Theoros shuts down. A client
+/// too slow to keep up is sent a `DATA_LOSS` status ending the stream, so it can resubscribe.
+async fn push_updates(
+    state: AppState,
+    subscriptions: FanoutSubscriptions,
+    sender: mpsc::Sender<Result<proto::FeedUpdate, Status>>,
+) {
+    let mut batches = state.ws.fanout.receiver();
+    loop {
+        let batch = tokio::select! {
+            batch = batches.recv() => batch,
+            _ = sender.closed() => return,
+            _ = state.shutdown.cancelled() => {
+                let _ = sender.send(Err(Status::unavailable("Theoros is shutting down"))).await;
+                return;
+            }
+        };
+        let batch = match batch {
+            Ok(batch) => batch,
+            Err(RecvError::Lagged(skipped)) => {
+                let message = format!("Too slow to keep up, the updates of {skipped} dispatches were not pushed");
+                let _ = sender.send(Err(Status::data_loss(message))).await;
+                return;
+            }
+            Err(RecvError::Closed) => return,
+        };
+        for update in feed_updates(&batch, &subscriptions) {
+            state.metrics.calldata_served.inc();
+            if sender.send(Ok(update)).await.is_err() {
+                return;
+            }
+        }
+    }
+}
+
+/// Updates of the subscribed feeds in the batch, leaving out the ones whose calldata could not be built.
+fn feed_updates(batch: &FanoutBatch, subscriptions: &FanoutSubscriptions) -> Vec<proto::FeedUpdate> {
+    batch
+        .frames_for(subscriptions)
+        .filter_map(|frame| {
+            let (nonce, data_feed) = frame.served.clone()?;
+            let RpcDataFeed { feed_id, calldata_id, encoded_calldata, update } = data_feed;
+            Some(proto::FeedUpdate {
+                nonce,
+                chain: frame.chain.to_string(),
+                feed_id,
+                calldata_id,
+                encoded_calldata,
+                update: update.map(Into::into),
+            })
+        })
+        .collect()
+}
+
+fn lifecycle_name(state: FeedLifecycleState) -> &'static str {
+    match state {
+        FeedLifecycleState::Active => "active",
+        FeedLifecycleState::Deprecated => "deprecated",
+        FeedLifecycleState::Retired => "retired",
+    }
+}
+
+impl From<CalldataResponse> for proto::Calldata {
+    fn from(calldata: CalldataResponse) -> Self {
+        Self {
+            feed_id: calldata.feed_id,
+            calldata_id: calldata.calldata_id,
+            encoded_calldata: calldata.encoded_calldata,
+            key_id: calldata.key_id,
+        }
+    }
+}
+
+impl From<UpdateView> for proto::Update {
+    fn from(update: UpdateView) -> Self {
+        let update = match update {
+            UpdateView::SpotMedian { timestamp, num_sources_aggregated, decimals, price, volume } => {
+                proto::update::Update::SpotMedian(proto::SpotMedianUpdate {
+                    timestamp,
+                    num_sources_aggregated: num_sources_aggregated.into(),
+                    decimals: decimals.into(),
+                    price,
+                    volume,
+                })
+            }
+            UpdateView::Perp {
+                timestamp,
+                num_sources_aggregated,
+                decimals,
+                mark_price,
+                funding_rate,
+                open_interest,
+                volume,
+            } => proto::update::Update::Perp(proto::PerpUpdate {
+                timestamp,
+                num_sources_aggregated: num_sources_aggregated.into(),
+                decimals: decimals.into(),
+                mark_price,
+                funding_rate,
+                open_interest,
+                volume,
+            }),
+            UpdateView::Opaque { feed_type, data } => proto::update::Update::Opaque(proto::OpaqueUpdate {
+                feed_type: feed_type.into(),
+                // The view is built from the raw bytes, so it is always valid hex.
+                data: hex::decode(data).unwrap_or_default(),
+            }),
+        };
+        Self { update: Some(update) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::types::state::WsState;
+
+    #[test]
+    fn test_errors_map_to_grpc_statuses() {
+        let status = Status::from(TheorosError::FeedNotFound("0x42".into()));
+        assert_eq!(status.code(), tonic::Code::NotFound);
+        assert_eq!(status.metadata().get("theoros-error-code").unwrap(), "feed_not_found");
+        assert_eq!(Status::from(TheorosError::MissingChain).code(), tonic::Code::InvalidArgument);
+        assert_eq!(Status::from(TheorosError::PartialQuorum("2/3".into())).code(), tonic::Code::DeadlineExceeded);
+    }
+
+    #[test]
+    fn test_only_built_updates_of_subscribed_feeds_are_streamed() {
+        let frame = |feed_id: &str, served: bool| crate::handlers::websocket::fanout::FeedFrame {
+            chain: EvmChainName::Sepolia,
+            feed_id: feed_id.to_owned(),
+            updated: true,
+            data_feed: Err(String::new()),
+            served: served.then(|| {
+                let data_feed = RpcDataFeed {
+                    feed_id: feed_id.to_owned(),
+                    calldata_id: String::from("0x01"),
+                    encoded_calldata: String::from("0x02"),
+                    update: Some(UpdateView::Opaque { feed_type: 7, data: String::from("0xabcd") }),
+                };
+                (42, data_feed)
+            }),
+            message: Default::default(),
+            message_name: "feed_update",
+        };
+        let batch =
+            FanoutBatch { nonce: 42, frames: vec![frame("0x1", true), frame("0x2", true), frame("0x3", false)] };
+        let mut subscriptions = FanoutSubscriptions::new(Arc::new(WsState::new()), SubscriptionKind::FeedUpdates);
+        subscriptions.insert(String::from("0x1"), EvmChainName::Sepolia);
+        subscriptions.insert(String::from("0x3"), EvmChainName::Sepolia);
+
+        let updates = feed_updates(&batch, &subscriptions);
+        assert_eq!(updates.len(), 1);
+        assert_eq!((updates[0].feed_id.as_str(), updates[0].nonce), ("0x1", 42));
+        let opaque = proto::Update {
+            update: Some(proto::update::Update::Opaque(proto::OpaqueUpdate { feed_type: 7, data: vec![0xab, 0xcd] })),
+        };
+        assert_eq!(updates[0].update, Some(opaque));
+    }
+}
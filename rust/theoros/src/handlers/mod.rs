@@ -1,2 +1,6 @@
+pub mod admin;
+pub mod graphql;
+#[cfg(feature = "grpc")]
+pub mod grpc;
 pub mod rest;
 pub mod websocket;
@@ -0,0 +1,32 @@
+use axum::extract::State;
+use axum::Json;
+use serde::{Deserialize, Serialize};
+use utoipa::{ToResponse, ToSchema};
+
+use crate::errors::TheorosError;
+use crate::types::hyperlane::CheckpointAnomaly;
+use crate::AppState;
+
+#[derive(Debug, Default, Serialize, Deserialize, ToResponse, ToSchema)]
+pub struct GetAnomaliesResponse(pub Vec<CheckpointAnomaly>);
+
+#[utoipa::path(
+    get,
+    path = "/v1/anomalies",
+    responses(
+        (
+            status = 200,
+            description = "Get the most recent validator checkpoint anomalies (equivocations & diverging roots), most recent first",
+            body = GetAnomaliesResponse
+        )
+    ),
+)]
+pub async fn get_anomalies(State(state): State<AppState>) -> Result<Json<GetAnomaliesResponse>, TheorosError> {
+    let started_at = std::time::Instant::now();
+
+    let anomalies = state.storage.checkpoint_anomalies().all().await;
+    let response = GetAnomaliesResponse(anomalies);
+
+    tracing::info!("🌐 get_anomalies - {:?}", started_at.elapsed());
+    Ok(Json(response))
+}
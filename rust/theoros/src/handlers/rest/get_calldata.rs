@@ -1,31 +1,70 @@
-use std::str::FromStr;
+use std::{str::FromStr, time::Instant};
 
-use alloy::hex;
+use alloy::{hex, primitives::B256};
 use axum::{
     extract::{Query, State},
     Json,
 };
-use serde::{Deserialize, Serialize};
-use utoipa::{IntoParams, ToResponse, ToSchema};
+use serde::Deserialize;
+use utoipa::{IntoParams, ToSchema};
 
 use crate::{
     configs::evm_config::EvmChainName,
-    errors::GetCalldataError,
-    types::calldata::{AsCalldata, Calldata},
+    errors::TheorosError,
+    storage::StoredCalldata,
+    types::{
+        api::v1::CalldataResponse,
+        calldata::{Calldata, CalldataOrdering},
+        encryption::ConsumerPublicKey,
+    },
     AppState,
 };
 
 #[derive(Deserialize, IntoParams, ToSchema)]
 pub struct GetCalldataQuery {
-    pub chain: String,
+    /// The destination chain. Falls back to the default chain when omitted.
+    pub chain: Option<String>,
     #[serde(deserialize_with = "deserialize_feed_ids")]
     pub feed_ids: Vec<String>,
+    /// Order of the returned calldata. Defaults to the requested order.
+    #[serde(default)]
+    pub order: CalldataOrdering,
+    /// Consumer whose registered key is used to encrypt the calldata. Served in cleartext when omitted, which
+    /// private feeds refuse.
+    pub consumer: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, ToResponse, ToSchema)]
-pub struct CalldataResponse {
-    pub feed_id: String,
-    pub encoded_calldata: String,
+impl CalldataResponse {
+    /// Builds the response, encrypting the calldata with the consumer key if provided.
+    pub fn new(
+        feed_id: String,
+        calldata_id: B256,
+        calldata: &[u8],
+        consumer_key: Option<&ConsumerPublicKey>,
+    ) -> Result<Self, TheorosError> {
+        let calldata_id = calldata_id.to_string();
+        let Some(key) = consumer_key else {
+            return Ok(Self { feed_id, calldata_id, encoded_calldata: hex::encode(calldata), key_id: None });
+        };
+        let encrypted = key.encrypt(calldata).map_err(|e| TheorosError::Internal(e.to_string()))?;
+        Ok(Self { feed_id, calldata_id, encoded_calldata: hex::encode(encrypted), key_id: Some(key.key_id()) })
+    }
+
+    /// Stores the calldata so it can be retrieved by id, then builds the response. The calldata of a private
+    /// feed is refused without a consumer to encrypt it for.
+    pub fn serve(
+        state: &AppState,
+        feed_id: String,
+        chain: EvmChainName,
+        calldata: Vec<u8>,
+        consumer: Option<&(String, ConsumerPublicKey)>,
+    ) -> Result<Self, TheorosError> {
+        ensure_private_feeds_encrypted(state, std::slice::from_ref(&feed_id), consumer)?;
+        let stored =
+            StoredCalldata::new(feed_id.clone(), chain, calldata, consumer.map(|(consumer_id, _)| consumer_id.clone()));
+        let calldata_id = state.storage.calldata_blobs().add(stored.clone());
+        Self::new(feed_id, calldata_id, &stored.calldata, consumer.map(|(_, key)| key))
+    }
 }
 
 pub type GetCalldataResponse = Vec<CalldataResponse>;
@@ -39,50 +78,170 @@ pub type GetCalldataResponse = Vec<CalldataResponse>;
     responses(
         (
             status = 200,
-            description = "Constructs the calldata used to update the specified feed IDs",
-            body = [GetCalldataResponse]
+            description = "Constructs the calldata used to update the specified feed IDs, sorted according to `order`",
+            body = [CalldataResponse]
+        ),
+        (
+            status = 403,
+            description = "A private feed is requested without a consumer",
+            body = ProblemDetails
         ),
         (
             status = 404,
-            description = "Unknown Feed ID",
-            body = GetCalldataError
+            description = "Unknown Feed ID or consumer",
+            body = ProblemDetails
+        ),
+        (
+            status = 410,
+            description = "The feed is retired",
+            body = ProblemDetails
+        ),
+        (
+            status = 413,
+            description = "More feeds requested than allowed at once",
+            body = ProblemDetails
+        ),
+        (
+            status = 503,
+            description = "Serving calldata for the chain is disabled",
+            body = ProblemDetails
+        ),
+        (
+            status = 504,
+            description = "Some validators could not be fetched before the deadline",
+            body = ProblemDetails
         )
     ),
 )]
 pub async fn get_calldata(
     State(state): State<AppState>,
     Query(params): Query<GetCalldataQuery>,
-) -> Result<Json<GetCalldataResponse>, GetCalldataError> {
+) -> Result<Json<GetCalldataResponse>, TheorosError> {
     let started_at = std::time::Instant::now();
+    let responses = serve_calldata(&state, params, started_at).await?;
+    tracing::info!("🌐 get_calldata - {:?}", started_at.elapsed());
+    Ok(Json(responses))
+}
 
-    let chain_name =
-        EvmChainName::from_str(&params.chain).map_err(|_| GetCalldataError::ChainNotSupported(params.chain.clone()))?;
+/// Builds the calldata of the requested feeds, within the deadline of the request received at `started_at`.
+pub(crate) async fn serve_calldata(
+    state: &AppState,
+    params: GetCalldataQuery,
+    started_at: Instant,
+) -> Result<GetCalldataResponse, TheorosError> {
+    let chain_name = resolve_chain(state, params.chain.as_deref())?;
+    let consumer = resolve_consumer(state, params.consumer.as_deref())?;
+    ensure_batch_size(state, params.feed_ids.len())?;
 
     let stored_feed_ids = state.storage.feed_ids();
 
     // Check if all requested feed IDs are supported.
     if let Some(missing_id) = stored_feed_ids.contains_vec(&params.feed_ids) {
-        return Err(GetCalldataError::FeedNotFound(missing_id));
+        return Err(TheorosError::FeedNotFound(missing_id));
     }
+    ensure_not_retired(state, &params.feed_ids)?;
+    ensure_private_feeds_encrypted(state, &params.feed_ids, consumer.as_ref())?;
+
+    let mut feed_ids = params.feed_ids;
+    params.order.apply(&mut feed_ids);
 
-    // Build calldata for each feed ID.
-    let mut responses: GetCalldataResponse = Vec::with_capacity(params.feed_ids.len());
-    for feed_id in &params.feed_ids {
-        let calldata = Calldata::build_from(&state, chain_name, feed_id.clone())
-            .await
-            .map_err(|e| GetCalldataError::CalldataError(e.to_string()))?;
+    // Build calldata for each feed ID, all sharing the deadline of the request.
+    let deadline = started_at + state.calldata_deadline;
+    let mut responses: GetCalldataResponse = Vec::with_capacity(feed_ids.len());
+    for feed_id in &feed_ids {
+        let calldata =
+            Calldata::build_from(state, chain_name, feed_id.clone(), deadline).await.map_err(TheorosError::from)?;
 
-        let response =
-            CalldataResponse { feed_id: feed_id.clone(), encoded_calldata: hex::encode(calldata.as_bytes()) };
-        responses.push(response);
+        state.storage.feed_timelines().record_calldata_served(feed_id, calldata.hyperlane_msg.nonce, chain_name);
+        let encoded_calldata = calldata.encode_for_chain(state, &chain_name);
+        responses.push(CalldataResponse::serve(
+            state,
+            feed_id.clone(),
+            chain_name,
+            encoded_calldata,
+            consumer.as_ref(),
+        )?);
     }
 
-    tracing::info!("🌐 get_calldata - {:?}", started_at.elapsed());
-    Ok(Json(responses))
+    state.metrics.calldata_served.inc_by(responses.len() as u64);
+    Ok(responses)
+}
+
+/// Returns the requested chain, or the configured default chain if none was provided.
+pub(crate) fn resolve_chain(state: &AppState, chain: Option<&str>) -> Result<EvmChainName, TheorosError> {
+    let chain_name = match chain {
+        Some(chain) => EvmChainName::from_str(chain).map_err(|_| TheorosError::ChainNotSupported(chain.to_owned()))?,
+        None => state.default_chain.ok_or(TheorosError::MissingChain)?,
+    };
+    ensure_chain_served(state, chain_name)?;
+    Ok(chain_name)
+}
+
+/// Fails if serving calldata for the chain is currently disabled.
+pub(crate) fn ensure_chain_served(state: &AppState, chain_name: EvmChainName) -> Result<(), TheorosError> {
+    if !state.chain_statuses.is_served(&chain_name) {
+        return Err(TheorosError::ChainDisabled(chain_name.to_string()));
+    }
+    Ok(())
+}
+
+/// Fails if one of the feeds is retired.
+pub(crate) fn ensure_not_retired(state: &AppState, feed_ids: &[String]) -> Result<(), TheorosError> {
+    match state.feed_lifecycles.first_retired(feed_ids) {
+        Some(feed_id) => Err(TheorosError::FeedRetired {
+            feed_id: feed_id.clone(),
+            replaced_by: state.feed_lifecycles.get(feed_id).replaced_by,
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Fails if one of the feeds is private & there is no consumer to encrypt its calldata for.
+pub(crate) fn ensure_private_feeds_encrypted(
+    state: &AppState,
+    feed_ids: &[String],
+    consumer: Option<&(String, ConsumerPublicKey)>,
+) -> Result<(), TheorosError> {
+    match state.private_feeds.first_private(feed_ids) {
+        Some(feed_id) if consumer.is_none() => Err(TheorosError::PrivateFeed(feed_id.clone())),
+        _ => Ok(()),
+    }
+}
+
+/// Fails if the feed is private, its updates being only served in its calldata, encrypted for a consumer.
+pub(crate) fn ensure_public(state: &AppState, feed_id: &str) -> Result<(), TheorosError> {
+    if state.private_feeds.is_private(feed_id) {
+        return Err(TheorosError::PrivateFeed(feed_id.to_owned()));
+    }
+    Ok(())
+}
+
+/// Fails if more feeds are requested at once than allowed.
+pub(crate) fn ensure_batch_size(state: &AppState, num_feeds: usize) -> Result<(), TheorosError> {
+    if num_feeds > state.max_batch_feeds {
+        return Err(TheorosError::TooManyFeeds { requested: num_feeds, max: state.max_batch_feeds });
+    }
+    Ok(())
+}
+
+/// Returns the consumer with its registered key, if one was provided.
+pub(crate) fn resolve_consumer(
+    state: &AppState,
+    consumer: Option<&str>,
+) -> Result<Option<(String, ConsumerPublicKey)>, TheorosError> {
+    match consumer {
+        Some(consumer) => state
+            .storage
+            .consumer_keys()
+            .get(consumer)
+            .map(|key| Some((consumer.to_owned(), key)))
+            .ok_or_else(|| TheorosError::ConsumerNotFound(consumer.to_owned())),
+        None => Ok(None),
+    }
 }
 
 /// Deserialize a list of feed ids "A, B, C" into a Vec<String> = [A, B, C].
-fn deserialize_feed_ids<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+pub(crate) fn deserialize_feed_ids<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
 where
     D: serde::Deserializer<'de>,
 {
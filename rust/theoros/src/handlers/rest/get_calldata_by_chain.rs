@@ -0,0 +1,114 @@
+use std::str::FromStr;
+
+use alloy::{hex, primitives::Bytes};
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use serde::Deserialize;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::{
+    configs::evm_config::EvmChainName,
+    errors::TheorosError,
+    extractors::PathExtractor,
+    handlers::rest::get_calldata::{
+        ensure_chain_served, ensure_not_retired, ensure_private_feeds_encrypted, resolve_consumer,
+    },
+    rpc::evm::calldata::encode_update_data_feeds,
+    types::{
+        api::v1::{CalldataResponse, ChainCalldataResponse},
+        calldata::Calldata,
+    },
+    AppState,
+};
+
+#[derive(Deserialize, IntoParams, ToSchema)]
+pub struct GetCalldataByChainQuery {
+    /// Consumer whose registered key is used to encrypt the calldata. Served in cleartext when omitted, which
+    /// private feeds refuse.
+    pub consumer: Option<String>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/calldata/{chain_name}/{feed_id}",
+    params(
+        ("chain_name" = String, Path, description = "The destination chain"),
+        ("feed_id" = String, Path, description = "The feed ID to build the calldata for"),
+        GetCalldataByChainQuery
+    ),
+    responses(
+        (
+            status = 200,
+            description = "Constructs the calldata used to update the feed ID on the chain & the call submitting it",
+            body = ChainCalldataResponse
+        ),
+        (
+            status = 403,
+            description = "The feed is private & no consumer is provided",
+            body = ProblemDetails
+        ),
+        (
+            status = 404,
+            description = "Unknown Feed ID or consumer",
+            body = ProblemDetails
+        ),
+        (
+            status = 410,
+            description = "The feed is retired",
+            body = ProblemDetails
+        ),
+        (
+            status = 503,
+            description = "Serving calldata for the chain is disabled",
+            body = ProblemDetails
+        ),
+        (
+            status = 504,
+            description = "Some validators could not be fetched before the deadline",
+            body = ProblemDetails
+        )
+    ),
+)]
+pub async fn get_calldata_by_chain(
+    State(state): State<AppState>,
+    PathExtractor((chain_name, feed_id)): PathExtractor<(String, String)>,
+    Query(params): Query<GetCalldataByChainQuery>,
+) -> Result<Json<ChainCalldataResponse>, TheorosError> {
+    let started_at = std::time::Instant::now();
+
+    let chain_name = EvmChainName::from_str(&chain_name).map_err(|_| TheorosError::ChainNotSupported(chain_name))?;
+    ensure_chain_served(&state, chain_name)?;
+    let consumer = resolve_consumer(&state, params.consumer.as_deref())?;
+
+    if !state.storage.feed_ids().contains(&feed_id) {
+        return Err(TheorosError::FeedNotFound(feed_id));
+    }
+    ensure_not_retired(&state, std::slice::from_ref(&feed_id))?;
+    ensure_private_feeds_encrypted(&state, std::slice::from_ref(&feed_id), consumer.as_ref())?;
+
+    let calldata = Calldata::build_from(&state, chain_name, feed_id.clone(), started_at + state.calldata_deadline)
+        .await
+        .map_err(TheorosError::from)?;
+
+    let nonce = calldata.hyperlane_msg.nonce;
+    state.storage.feed_timelines().record_calldata_served(&feed_id, nonce, chain_name);
+    let encoded_calldata = calldata.encode_for_chain(&state, &chain_name);
+    let transaction_data = match consumer {
+        Some(_) => None,
+        None => Some(hex::encode_prefixed(encode_update_data_feeds(vec![Bytes::from(encoded_calldata.clone())]))),
+    };
+    let response = ChainCalldataResponse {
+        calldata: CalldataResponse::serve(&state, feed_id, chain_name, encoded_calldata, consumer.as_ref())?,
+        chain: chain_name,
+        nonce,
+        num_signatures: calldata.hyperlane_msg.signers_len,
+        contract_address: state.pragma_contracts.get(&chain_name).map(|contract| contract.address.to_string()),
+        transaction_data,
+    };
+
+    state.metrics.calldata_served.inc();
+    tracing::info!("🌐 get_calldata_by_chain - {:?}", started_at.elapsed());
+    Ok(Json(response))
+}
@@ -0,0 +1,99 @@
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use serde::Deserialize;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::{
+    errors::TheorosError,
+    extractors::PathExtractor,
+    handlers::rest::get_calldata::{
+        ensure_not_retired, ensure_private_feeds_encrypted, resolve_chain, resolve_consumer,
+    },
+    types::{api::v1::CalldataResponse, calldata::Calldata},
+    AppState,
+};
+
+#[derive(Deserialize, IntoParams, ToSchema)]
+pub struct GetCalldataByFeedIdQuery {
+    /// The destination chain. Falls back to the default chain when omitted.
+    pub chain: Option<String>,
+    /// Consumer whose registered key is used to encrypt the calldata. Served in cleartext when omitted, which
+    /// private feeds refuse.
+    pub consumer: Option<String>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/calldata/{feed_id}",
+    params(
+        ("feed_id" = String, Path, description = "The feed ID to build the calldata for"),
+        GetCalldataByFeedIdQuery
+    ),
+    responses(
+        (
+            status = 200,
+            description = "Constructs the calldata used to update the feed ID on the requested (or default) chain",
+            body = CalldataResponse
+        ),
+        (
+            status = 400,
+            description = "No chain provided and no default chain configured",
+            body = ProblemDetails
+        ),
+        (
+            status = 403,
+            description = "The feed is private & no consumer is provided",
+            body = ProblemDetails
+        ),
+        (
+            status = 404,
+            description = "Unknown Feed ID or consumer",
+            body = ProblemDetails
+        ),
+        (
+            status = 410,
+            description = "The feed is retired",
+            body = ProblemDetails
+        ),
+        (
+            status = 503,
+            description = "Serving calldata for the chain is disabled",
+            body = ProblemDetails
+        ),
+        (
+            status = 504,
+            description = "Some validators could not be fetched before the deadline",
+            body = ProblemDetails
+        )
+    ),
+)]
+pub async fn get_calldata_by_feed_id(
+    State(state): State<AppState>,
+    PathExtractor(feed_id): PathExtractor<String>,
+    Query(params): Query<GetCalldataByFeedIdQuery>,
+) -> Result<Json<CalldataResponse>, TheorosError> {
+    let started_at = std::time::Instant::now();
+
+    let chain_name = resolve_chain(&state, params.chain.as_deref())?;
+    let consumer = resolve_consumer(&state, params.consumer.as_deref())?;
+
+    if !state.storage.feed_ids().contains(&feed_id) {
+        return Err(TheorosError::FeedNotFound(feed_id));
+    }
+    ensure_not_retired(&state, std::slice::from_ref(&feed_id))?;
+    ensure_private_feeds_encrypted(&state, std::slice::from_ref(&feed_id), consumer.as_ref())?;
+
+    let calldata = Calldata::build_from(&state, chain_name, feed_id.clone(), started_at + state.calldata_deadline)
+        .await
+        .map_err(TheorosError::from)?;
+
+    state.storage.feed_timelines().record_calldata_served(&feed_id, calldata.hyperlane_msg.nonce, chain_name);
+    let encoded_calldata = calldata.encode_for_chain(&state, &chain_name);
+    let response = CalldataResponse::serve(&state, feed_id, chain_name, encoded_calldata, consumer.as_ref())?;
+
+    state.metrics.calldata_served.inc();
+    tracing::info!("🌐 get_calldata_by_feed_id - {:?}", started_at.elapsed());
+    Ok(Json(response))
+}
@@ -0,0 +1,87 @@
+use std::str::FromStr;
+
+use crate::{
+    configs::evm_config::EvmChainName,
+    errors::TheorosError,
+    extractors::PathExtractor,
+    handlers::rest::get_calldata::ensure_chain_served,
+    types::{
+        api::v1::{CalldataResponse, GetCalldataByIdResponse},
+        build_info::EncoderVersion,
+    },
+    AppState,
+};
+use alloy::primitives::B256;
+use axum::{extract::State, Json};
+
+#[utoipa::path(
+    get,
+    path = "/v1/calldata/by-id/{calldata_id}",
+    params(
+        ("calldata_id" = String, Path, description = "The id of a previously served calldata")
+    ),
+    responses(
+        (
+            status = 200,
+            description = "Retrieves a previously served calldata. Calldata served encrypted is encrypted again for the same consumer",
+            body = GetCalldataByIdResponse
+        ),
+        (
+            status = 400,
+            description = "Invalid calldata ID",
+            body = ProblemDetails
+        ),
+        (
+            status = 403,
+            description = "The calldata of a private feed was served in cleartext before the feed became private",
+            body = ProblemDetails
+        ),
+        (
+            status = 404,
+            description = "Unknown calldata ID",
+            body = ProblemDetails
+        ),
+        (
+            status = 503,
+            description = "Serving calldata for the chain is disabled",
+            body = ProblemDetails
+        )
+    ),
+)]
+pub async fn get_calldata_by_id(
+    State(state): State<AppState>,
+    PathExtractor(calldata_id): PathExtractor<String>,
+) -> Result<Json<GetCalldataByIdResponse>, TheorosError> {
+    let started_at = std::time::Instant::now();
+
+    let id = B256::from_str(&calldata_id).map_err(|_| TheorosError::InvalidCalldataId(calldata_id.clone()))?;
+    let stored = state.storage.calldata_blobs().get(&id).ok_or(TheorosError::CalldataNotFound(calldata_id))?;
+    ensure_chain_served(&state, stored.chain)?;
+
+    // Never serve in cleartext a calldata that was encrypted for a consumer.
+    let consumer_key = match &stored.consumer {
+        Some(consumer) => Some(
+            state
+                .storage
+                .consumer_keys()
+                .get(consumer)
+                .ok_or_else(|| TheorosError::ConsumerNotFound(consumer.clone()))?,
+        ),
+        // Stored before the feed became private, e.g. pushed to the subscribers.
+        None if state.private_feeds.is_private(&stored.feed_id) => {
+            return Err(TheorosError::PrivateFeed(stored.feed_id));
+        }
+        None => None,
+    };
+
+    let calldata = CalldataResponse::new(stored.feed_id, id, &stored.calldata, consumer_key.as_ref())?;
+    let response = GetCalldataByIdResponse {
+        chain: stored.chain,
+        encoder: stored.encoder,
+        git_commit: stored.git_commit.to_owned(),
+        calldata,
+    };
+
+    tracing::info!("🌐 get_calldata_by_id - {:?}", started_at.elapsed());
+    Ok(Json(response))
+}
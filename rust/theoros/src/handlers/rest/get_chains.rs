@@ -1,26 +1,40 @@
 use axum::extract::State;
 use axum::Json;
-use serde::{Deserialize, Serialize};
-use utoipa::{ToResponse, ToSchema};
 
-use crate::configs::evm_config::EvmChainName;
-use crate::errors::GetChainsError;
+use crate::errors::TheorosError;
+use crate::types::api::v1::{ChainInfo, GetChainsResponse};
 use crate::AppState;
 
-#[derive(Debug, Default, Serialize, Deserialize, ToResponse, ToSchema)]
-pub struct GetChainsResponse(pub Vec<EvmChainName>);
-
 #[utoipa::path(
     get,
     path = "/v1/chains",
     responses(
-        (status = 200, description = "Get all the supported chains", body = [GetDataFeedsResponse])
+        (status = 200, description = "Get all the supported chains", body = GetChainsResponse)
     ),
 )]
-pub async fn get_chains(State(state): State<AppState>) -> Result<Json<GetChainsResponse>, GetChainsError> {
+pub async fn get_chains(State(state): State<AppState>) -> Result<Json<GetChainsResponse>, TheorosError> {
     let started_at = std::time::Instant::now();
 
-    let chains = state.hyperlane_validators_mapping.chain_names();
+    let config = state.evm_config.current();
+    let mut chains: Vec<ChainInfo> = state
+        .hyperlane_validators_mapping
+        .chain_names()
+        .into_iter()
+        .filter(|chain_name| state.chain_statuses.is_served(chain_name))
+        .filter_map(|chain_name| {
+            let validator_set = state.hyperlane_validators_mapping.get_validator_set(&chain_name)?;
+            let chain_config = config.chains().get(&chain_name)?;
+            Some(ChainInfo {
+                name: chain_name,
+                chain_id: chain_name.chain_id(),
+                ism_address: chain_config.hyperlane_address.clone(),
+                validators: validator_set.validators.len(),
+                threshold: validator_set.threshold,
+                last_served_nonce: state.quorum_tracker.last_served(&chain_name),
+            })
+        })
+        .collect();
+    chains.sort_by_key(|chain| chain.name.to_string());
     let response = GetChainsResponse(chains);
 
     tracing::info!("🌐 get_chains - {:?}", started_at.elapsed());
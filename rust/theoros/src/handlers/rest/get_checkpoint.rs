@@ -0,0 +1,47 @@
+use axum::{extract::State, Json};
+use starknet::core::types::Felt;
+
+use crate::{
+    errors::TheorosError, extractors::PathExtractor, types::hyperlane::SignedCheckpointWithMessageId, AppState,
+};
+
+/// Parses the index of a checkpoint object, named as in the validators storage.
+fn checkpoint_index(object: &str) -> Option<u32> {
+    object.strip_prefix("checkpoint_")?.strip_suffix("_with_id.json")?.parse().ok()
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/checkpoints/{validator}/{object}",
+    params(
+        ("validator" = String, Path, description = "Address of the validator"),
+        ("object" = String, Path, description = "The checkpoint, named as in the validators storage, e.g. `checkpoint_42_with_id.json`")
+    ),
+    responses(
+        (
+            status = 200,
+            description = "Get a checkpoint fetched by this instance, in the format of the validators storage, so the instances sharding the fetches can share them",
+            body = Object
+        ),
+        (status = 400, description = "Invalid validator address", body = ProblemDetails),
+        (status = 404, description = "The checkpoint is not stored", body = ProblemDetails)
+    ),
+)]
+pub async fn get_checkpoint(
+    State(state): State<AppState>,
+    PathExtractor((validator, object)): PathExtractor<(String, String)>,
+) -> Result<Json<SignedCheckpointWithMessageId>, TheorosError> {
+    let started_at = std::time::Instant::now();
+
+    let validator_address =
+        Felt::from_hex(&validator).map_err(|_| TheorosError::InvalidValidator(validator.clone()))?;
+    let index = checkpoint_index(&object).ok_or(TheorosError::UnknownObject(object))?;
+    let checkpoint = state
+        .storage
+        .signed_checkpoints()
+        .get_for_validator(validator_address, index)
+        .ok_or(TheorosError::CheckpointNotFound(index))?;
+
+    tracing::info!("🌐 get_checkpoint - {:?}", started_at.elapsed());
+    Ok(Json(checkpoint))
+}
@@ -0,0 +1,110 @@
+use axum::extract::{Query, State};
+use axum::Json;
+use serde::Deserialize;
+use utoipa::{IntoParams, ToSchema};
+
+use pragma_feeds::Feed;
+use pragma_utils::conversions::alloy::hex_str_to_u256;
+
+use crate::{
+    errors::TheorosError,
+    extractors::PathExtractor,
+    handlers::rest::{get_calldata::ensure_public, get_data_feeds::unix_now},
+    types::{
+        api::v1::{ChainCheckpointStatus, GetDataFeedResponse, LatestFeedUpdate},
+        hyperlane::DispatchUpdateInfos,
+        quorum::agreeing_checkpoints,
+        update_view::UpdateView,
+    },
+    AppState,
+};
+
+#[derive(Debug, Default, Deserialize, IntoParams, ToSchema)]
+pub struct GetDataFeedQuery {
+    /// Name of the emitter whose latest update is returned, e.g. `staging`. Defaults to the served one.
+    pub emitter: Option<String>,
+}
+
+impl LatestFeedUpdate {
+    pub(crate) fn new(state: &AppState, update: &DispatchUpdateInfos) -> Self {
+        let mut checkpoints: Vec<_> = state
+            .hyperlane_validators_mapping
+            .chain_names()
+            .into_iter()
+            .filter(|chain_name| state.chain_statuses.is_served(chain_name))
+            .filter_map(|chain| {
+                let validators: Vec<_> =
+                    state.hyperlane_validators_mapping.get_validators(&chain)?.keys().copied().collect();
+                let signed =
+                    agreeing_checkpoints(state.storage.signed_checkpoints().get(&validators, update.nonce)).len();
+                Some(ChainCheckpointStatus {
+                    chain,
+                    signed,
+                    validators: validators.len(),
+                    fully_signed: signed == validators.len(),
+                })
+            })
+            .collect();
+        checkpoints.sort_by_key(|status| status.chain.to_string());
+
+        Self {
+            nonce: update.nonce,
+            emitter_chain_id: update.emitter_chain_id,
+            emitter_address: format!("{:#x}", update.emitter_address),
+            emitter: state.storage.emitters().get(&update.emitter_address).map(|emitter| emitter.name.clone()),
+            update: UpdateView::from(&update.update),
+            checkpoints,
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/data_feeds/{feed_id}",
+    params(
+        ("feed_id" = String, Path, description = "The feed ID to get"),
+        GetDataFeedQuery
+    ),
+    responses(
+        (
+            status = 200,
+            description = "The feed, its latest update & the signing status of the update on each chain",
+            body = GetDataFeedResponse
+        ),
+        (status = 403, description = "The feed is private", body = ProblemDetails),
+        (status = 404, description = "Unknown Feed ID or emitter", body = ProblemDetails)
+    ),
+)]
+pub async fn get_data_feed(
+    State(state): State<AppState>,
+    PathExtractor(feed_id): PathExtractor<String>,
+    Query(params): Query<GetDataFeedQuery>,
+) -> Result<Json<GetDataFeedResponse>, TheorosError> {
+    let started_at = std::time::Instant::now();
+
+    if !state.storage.feed_ids().contains(&feed_id) {
+        return Err(TheorosError::FeedNotFound(feed_id));
+    }
+    ensure_public(&state, &feed_id)?;
+    let feed: Feed = feed_id.parse().map_err(|_| TheorosError::InvalidFeedId(feed_id.clone()))?;
+    let feed_id_u256 = hex_str_to_u256(&feed_id).map_err(|_| TheorosError::InvalidFeedId(feed_id.clone()))?;
+
+    let latest = match params.emitter {
+        Some(name) => {
+            let emitter = state.storage.emitters().by_name(&name).ok_or(TheorosError::EmitterNotFound(name))?;
+            state.storage.latest_update_per_emitter().get(&emitter.address, &feed_id_u256)
+        }
+        None => state.storage.latest_update_per_feed().get(&feed_id_u256),
+    };
+    let freshness = state.staleness_policy.freshness(
+        &feed_id,
+        latest.as_ref().and_then(|latest| latest.update.timestamp()),
+        unix_now(),
+    );
+    let latest_update = latest.map(|update| LatestFeedUpdate::new(&state, &update));
+
+    let lifecycle = state.feed_lifecycles.get(&feed_id).current();
+
+    tracing::info!("🌐 get_data_feed - {:?}", started_at.elapsed());
+    Ok(Json(GetDataFeedResponse { feed, lifecycle, freshness, latest_update }))
+}
@@ -1,35 +1,214 @@
-use axum::extract::State;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use axum::extract::{Query, State};
 use axum::Json;
-use serde::{Deserialize, Serialize};
-use utoipa::{ToResponse, ToSchema};
+use serde::Deserialize;
+use utoipa::{IntoParams, ToSchema};
 
-use pragma_feeds::Feed;
+use pragma_feeds::{AssetClass, Feed, FeedType};
+use pragma_utils::conversions::alloy::hex_str_to_u256;
 
-use crate::errors::GetDataFeedsError;
+use crate::constants::{DEFAULT_DATA_FEEDS_LIMIT, MAX_DATA_FEEDS_LIMIT};
+use crate::errors::TheorosError;
+use crate::types::api::v1::{DataFeed, GetDataFeedsResponse};
+use crate::types::staleness::{FeedFreshness, FeedStatus};
 use crate::AppState;
 
-#[derive(Debug, Default, Serialize, Deserialize, ToResponse, ToSchema)]
-pub struct GetDataFeedsResponse(pub Vec<Feed>);
+#[derive(Debug, Default, Deserialize, IntoParams, ToSchema)]
+pub struct GetDataFeedsQuery {
+    /// Only the feeds of this asset class.
+    pub asset_class: Option<AssetClass>,
+    /// Only the feeds of this type.
+    pub feed_type: Option<FeedType>,
+    /// Only the feeds with this status, e.g. `stale` to find the feeds which stopped updating.
+    pub status: Option<FeedStatus>,
+    /// Only the feeds whose pair or ID contains this text, case-insensitive, e.g. `btc`.
+    pub search: Option<String>,
+    /// Maximum number of feeds returned. Defaults to 100, at most 1000.
+    pub limit: Option<usize>,
+    /// The `next_cursor` of the previous page, to get the next one.
+    pub cursor: Option<String>,
+}
+
+impl GetDataFeedsQuery {
+    fn matches(&self, feed: &Feed, freshness: &FeedFreshness) -> bool {
+        let search = self.search.as_deref().map(str::to_lowercase);
+        self.status.map_or(true, |status| freshness.status == status)
+            && self.asset_class.as_ref().map_or(true, |asset_class| feed.asset_class == *asset_class)
+            && self.feed_type.as_ref().map_or(true, |feed_type| feed.feed_type == *feed_type)
+            && search.map_or(true, |search| {
+                feed.pair_id.to_lowercase().contains(&search) || feed.feed_id.to_lowercase().contains(&search)
+            })
+    }
+}
+
+/// A page of the feeds matching a query.
+#[derive(Debug, PartialEq)]
+struct FeedsPage {
+    feeds: Vec<(Feed, FeedFreshness)>,
+    total: usize,
+    next_cursor: Option<String>,
+}
 
 #[utoipa::path(
     get,
     path = "/v1/data_feeds",
+    params(GetDataFeedsQuery),
     responses(
-        (status = 200, description = "Get all the available feed ids", body = [GetDataFeedsResponse])
+        (status = 200, description = "A page of the available feeds matching the filters", body = GetDataFeedsResponse),
+        (status = 400, description = "Invalid filter or limit", body = ProblemDetails)
     ),
 )]
-pub async fn get_data_feeds(State(state): State<AppState>) -> Result<Json<GetDataFeedsResponse>, GetDataFeedsError> {
+pub async fn get_data_feeds(
+    State(state): State<AppState>,
+    Query(params): Query<GetDataFeedsQuery>,
+) -> Result<Json<GetDataFeedsResponse>, TheorosError> {
     let started_at = std::time::Instant::now();
 
-    let feed_ids = state.storage.feed_ids();
-
-    let mut feeds = Vec::with_capacity(feed_ids.len());
-    for feed_id in feed_ids.iter() {
-        let feed = feed_id.parse().map_err(|_| GetDataFeedsError::ParsingFeedId(feed_id.clone()))?;
-        feeds.push(feed);
+    let limit = params.limit.unwrap_or(DEFAULT_DATA_FEEDS_LIMIT);
+    if limit == 0 || limit > MAX_DATA_FEEDS_LIMIT {
+        return Err(TheorosError::InvalidRange(format!("`limit` must be between 1 & {MAX_DATA_FEEDS_LIMIT}")));
     }
 
-    let response = GetDataFeedsResponse(feeds);
+    let feed_ids: Vec<String> = state.storage.feed_ids().iter().collect();
+    let now = unix_now();
+    let page = select_feeds(feed_ids, &params, limit, |feed_id| feed_freshness(&state, feed_id, now))?;
+    let data_feeds = page
+        .feeds
+        .into_iter()
+        .map(|(feed, freshness)| {
+            let lifecycle = state.feed_lifecycles.get(&feed.feed_id).current();
+            let emitters = feed_emitters(&state, &feed.feed_id);
+            DataFeed { feed, lifecycle, freshness, emitters }
+        })
+        .collect();
+
+    let response = GetDataFeedsResponse { data_feeds, total: page.total, next_cursor: page.next_cursor };
     tracing::info!("🌐 get_data_feeds - {:?}", started_at.elapsed());
     Ok(Json(response))
 }
+
+/// Current Unix timestamp, in seconds.
+pub(crate) fn unix_now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|elapsed| elapsed.as_secs()).unwrap_or_default()
+}
+
+/// Freshness of the feed at `now`, from the timestamp of its latest indexed update.
+pub(crate) fn feed_freshness(state: &AppState, feed_id: &str, now: u64) -> FeedFreshness {
+    let last_update_timestamp = hex_str_to_u256(feed_id)
+        .ok()
+        .and_then(|feed_id| state.storage.latest_update_per_feed().get(&feed_id))
+        .and_then(|latest| latest.update.timestamp());
+    state.staleness_policy.freshness(feed_id, last_update_timestamp, now)
+}
+
+/// Names of the configured emitters which dispatched an update of the feed, in the configured order.
+fn feed_emitters(state: &AppState, feed_id: &str) -> Vec<String> {
+    let Ok(feed_id) = hex_str_to_u256(feed_id) else {
+        return Vec::new();
+    };
+    let dispatched_by = state.storage.latest_update_per_emitter().emitters_of(&feed_id);
+    state
+        .storage
+        .emitters()
+        .iter()
+        .filter(|emitter| dispatched_by.contains(&emitter.address))
+        .map(|emitter| emitter.name.clone())
+        .collect()
+}
+
+/// Selects the page of the feeds matching the query, after its cursor. The feeds are sorted by ID, so the
+/// pages stay stable as feeds are registered: the cursor is the ID of the last feed of the previous page.
+fn select_feeds(
+    mut feed_ids: Vec<String>,
+    query: &GetDataFeedsQuery,
+    limit: usize,
+    freshness: impl Fn(&str) -> FeedFreshness,
+) -> Result<FeedsPage, TheorosError> {
+    feed_ids.sort_unstable();
+    let mut matching = Vec::new();
+    for feed_id in feed_ids {
+        let feed: Feed = feed_id.parse().map_err(|_| TheorosError::InvalidFeedId(feed_id.clone()))?;
+        let freshness = freshness(&feed_id);
+        if query.matches(&feed, &freshness) {
+            matching.push((feed, freshness));
+        }
+    }
+
+    let total = matching.len();
+    let mut feeds: Vec<(Feed, FeedFreshness)> = match &query.cursor {
+        Some(cursor) => matching.into_iter().filter(|(feed, _)| feed.feed_id.as_str() > cursor.as_str()).collect(),
+        None => matching,
+    };
+    let next_cursor = (feeds.len() > limit).then(|| feeds[limit - 1].0.feed_id.clone());
+    feeds.truncate(limit);
+    Ok(FeedsPage { feeds, total, next_cursor })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BTC_USD: &str = "0x4254432f555344";
+    const ETH_USD: &str = "0x4554482f555344";
+    const SOL_USD: &str = "0x534f4c2f555344";
+
+    fn feed_ids(page: &FeedsPage) -> Vec<&str> {
+        page.feeds.iter().map(|(feed, _)| feed.feed_id.as_str()).collect()
+    }
+
+    fn unknown(_feed_id: &str) -> FeedFreshness {
+        FeedFreshness { status: FeedStatus::Unknown, last_update_timestamp: None }
+    }
+
+    #[test]
+    fn test_feeds_are_paginated_in_a_stable_order() {
+        let all = vec![SOL_USD.to_owned(), BTC_USD.to_owned(), ETH_USD.to_owned()];
+
+        let first = select_feeds(all.clone(), &GetDataFeedsQuery::default(), 2, unknown).unwrap();
+        assert_eq!(feed_ids(&first), vec![BTC_USD, ETH_USD]);
+        assert_eq!((first.total, first.next_cursor.as_deref()), (3, Some(ETH_USD)));
+
+        let query = GetDataFeedsQuery { cursor: first.next_cursor, ..Default::default() };
+        let last = select_feeds(all.clone(), &query, 2, unknown).unwrap();
+        assert_eq!(feed_ids(&last), vec![SOL_USD]);
+        assert_eq!(last.next_cursor, None);
+
+        // A full last page has no next one.
+        assert_eq!(select_feeds(all, &GetDataFeedsQuery::default(), 3, unknown).unwrap().next_cursor, None);
+    }
+
+    #[test]
+    fn test_feeds_are_filtered() {
+        let all = vec![SOL_USD.to_owned(), BTC_USD.to_owned(), ETH_USD.to_owned()];
+
+        let query = GetDataFeedsQuery { search: Some(String::from("eth")), ..Default::default() };
+        let page = select_feeds(all.clone(), &query, 10, unknown).unwrap();
+        assert_eq!((feed_ids(&page), page.total), (vec![ETH_USD], 1));
+
+        let query = GetDataFeedsQuery { asset_class: Some(AssetClass::Crypto), ..Default::default() };
+        assert_eq!(select_feeds(all.clone(), &query, 10, unknown).unwrap().total, 3);
+
+        let query = GetDataFeedsQuery { feed_type: Some(FeedType::UniquePerpMedian), ..Default::default() };
+        assert_eq!(select_feeds(all, &query, 10, unknown).unwrap().total, 0);
+    }
+
+    #[test]
+    fn test_feeds_are_filtered_by_status() {
+        let all = vec![SOL_USD.to_owned(), BTC_USD.to_owned(), ETH_USD.to_owned()];
+        let freshness = |feed_id: &str| {
+            let status = if feed_id == ETH_USD { FeedStatus::Stale } else { FeedStatus::Fresh };
+            FeedFreshness { status, last_update_timestamp: Some(1_700_000_000) }
+        };
+
+        let query = GetDataFeedsQuery { status: Some(FeedStatus::Stale), ..Default::default() };
+        let page = select_feeds(all.clone(), &query, 10, freshness).unwrap();
+        assert_eq!((feed_ids(&page), page.total), (vec![ETH_USD], 1));
+
+        let query = GetDataFeedsQuery { status: Some(FeedStatus::Fresh), ..Default::default() };
+        assert_eq!(select_feeds(all.clone(), &query, 10, freshness).unwrap().total, 2);
+
+        let query = GetDataFeedsQuery { status: Some(FeedStatus::Unknown), ..Default::default() };
+        assert_eq!(select_feeds(all, &query, 10, freshness).unwrap().total, 0);
+    }
+}
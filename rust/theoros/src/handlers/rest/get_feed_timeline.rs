@@ -0,0 +1,47 @@
+use axum::{extract::State, Json};
+use serde::{Deserialize, Serialize};
+use utoipa::{ToResponse, ToSchema};
+
+use pragma_utils::conversions::alloy::hex_str_to_u256;
+
+use crate::{errors::TheorosError, extractors::PathExtractor, types::timeline::FeedTimelineEvent, AppState};
+
+#[derive(Debug, Serialize, Deserialize, ToResponse, ToSchema)]
+pub struct GetFeedTimelineResponse {
+    pub feed_id: String,
+    /// The most recent lifecycle events of the feed, in chronological order.
+    pub events: Vec<FeedTimelineEvent>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/debug/feeds/{feed_id}/timeline",
+    params(
+        ("feed_id" = String, Path, description = "The feed ID to debug")
+    ),
+    responses(
+        (
+            status = 200,
+            description = "Reconstructs the lifecycle of the feed updates: dispatch indexed, checkpoints fetched, update stored & calldata first served",
+            body = GetFeedTimelineResponse
+        ),
+        (status = 404, description = "Unknown Feed ID", body = ProblemDetails)
+    ),
+)]
+pub async fn get_feed_timeline(
+    State(state): State<AppState>,
+    PathExtractor(feed_id): PathExtractor<String>,
+) -> Result<Json<GetFeedTimelineResponse>, TheorosError> {
+    let started_at = std::time::Instant::now();
+
+    if !state.storage.feed_ids().contains(&feed_id) {
+        return Err(TheorosError::FeedNotFound(feed_id));
+    }
+    let feed_id_u256 = hex_str_to_u256(&feed_id).map_err(|_| TheorosError::InvalidFeedId(feed_id.clone()))?;
+
+    let events = state.storage.feed_timelines().get(&feed_id_u256);
+    let response = GetFeedTimelineResponse { feed_id, events };
+
+    tracing::info!("🌐 get_feed_timeline - {:?}", started_at.elapsed());
+    Ok(Json(response))
+}
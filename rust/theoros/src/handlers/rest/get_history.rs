@@ -0,0 +1,80 @@
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToResponse, ToSchema};
+
+use pragma_utils::conversions::alloy::hex_str_to_u256;
+
+use crate::{
+    constants::{DEFAULT_HISTORY_LIMIT, MAX_HISTORY_LIMIT},
+    errors::TheorosError,
+    extractors::PathExtractor,
+    handlers::rest::get_calldata::ensure_public,
+    types::history::{HistoryPoint, HistoryRange},
+    AppState,
+};
+
+#[derive(Deserialize, IntoParams, ToSchema)]
+pub struct GetHistoryQuery {
+    /// Unix timestamp in seconds of the oldest update returned, inclusive.
+    pub from: Option<u64>,
+    /// Unix timestamp in seconds of the most recent update returned, inclusive.
+    pub to: Option<u64>,
+    /// Maximum number of updates returned, keeping the most recent ones in range. Defaults to 100, at most 1000.
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToResponse, ToSchema)]
+pub struct GetHistoryResponse {
+    pub feed_id: String,
+    /// Updates of the feed in range, sorted by timestamp.
+    pub updates: Vec<HistoryPoint>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/data_feeds/{feed_id}/history",
+    params(
+        ("feed_id" = String, Path, description = "The feed ID to get the history of"),
+        GetHistoryQuery
+    ),
+    responses(
+        (status = 200, description = "Price, timestamp & nonce of the updates of the feed", body = GetHistoryResponse),
+        (status = 400, description = "Invalid range or limit", body = ProblemDetails),
+        (status = 403, description = "The feed is private", body = ProblemDetails),
+        (status = 404, description = "Unknown Feed ID", body = ProblemDetails)
+    ),
+)]
+pub async fn get_history(
+    State(state): State<AppState>,
+    PathExtractor(feed_id): PathExtractor<String>,
+    Query(params): Query<GetHistoryQuery>,
+) -> Result<Json<GetHistoryResponse>, TheorosError> {
+    let started_at = std::time::Instant::now();
+
+    let range = HistoryRange { from: params.from, to: params.to, limit: params.limit.unwrap_or(DEFAULT_HISTORY_LIMIT) };
+    if let (Some(from), Some(to)) = (range.from, range.to) {
+        if from > to {
+            return Err(TheorosError::InvalidRange(format!("`from` ({from}) is after `to` ({to})")));
+        }
+    }
+    if range.limit == 0 || range.limit > MAX_HISTORY_LIMIT {
+        return Err(TheorosError::InvalidRange(format!("`limit` must be between 1 & {MAX_HISTORY_LIMIT}")));
+    }
+
+    if !state.storage.feed_ids().contains(&feed_id) {
+        return Err(TheorosError::FeedNotFound(feed_id));
+    }
+    ensure_public(&state, &feed_id)?;
+    let feed_id_u256 = hex_str_to_u256(&feed_id).map_err(|_| TheorosError::InvalidFeedId(feed_id.clone()))?;
+
+    let updates = state.storage.feed_history_in_range(feed_id_u256, &range).await.map_err(|e| {
+        tracing::error!("🌐 get_history - Failed to read the history of {}: {:?}", feed_id, e);
+        TheorosError::Storage
+    })?;
+
+    tracing::info!("🌐 get_history - {:?}", started_at.elapsed());
+    Ok(Json(GetHistoryResponse { feed_id, updates }))
+}
@@ -0,0 +1,115 @@
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast::error::RecvError;
+use utoipa::{IntoParams, ToResponse, ToSchema};
+
+use pragma_utils::conversions::alloy::hex_str_to_u256;
+
+use crate::{
+    configs::indexer_start::parse_duration,
+    constants::{DEFAULT_LONG_POLL_TIMEOUT, MAX_LONG_POLL_TIMEOUT},
+    errors::TheorosError,
+    extractors::PathExtractor,
+    handlers::rest::get_calldata::ensure_public,
+    types::{hyperlane::DispatchUpdateInfos, update_view::UpdateView},
+    AppState,
+};
+
+#[derive(Deserialize, IntoParams, ToSchema)]
+pub struct GetNextUpdateQuery {
+    /// Only updates dispatched with a nonce strictly greater than this one are returned.
+    pub after_nonce: u32,
+    /// How long to wait for a new update, e.g. `30s`. Defaults to 30 seconds, at most 60 seconds.
+    pub timeout: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToResponse, ToSchema)]
+pub struct GetNextUpdateResponse {
+    pub feed_id: String,
+    /// Nonce of the Dispatch message containing the update.
+    pub nonce: u32,
+    pub emitter_chain_id: u32,
+    pub emitter_address: String,
+    pub update: UpdateView,
+}
+
+impl GetNextUpdateResponse {
+    fn new(feed_id: String, update: &DispatchUpdateInfos) -> Self {
+        Self {
+            feed_id,
+            nonce: update.nonce,
+            emitter_chain_id: update.emitter_chain_id,
+            emitter_address: format!("{:#x}", update.emitter_address),
+            update: UpdateView::from(&update.update),
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/data_feeds/{feed_id}/next",
+    params(
+        ("feed_id" = String, Path, description = "The feed ID to wait an update for"),
+        GetNextUpdateQuery
+    ),
+    responses(
+        (
+            status = 200,
+            description = "A signed update with a nonce greater than `after_nonce` is available",
+            body = GetNextUpdateResponse
+        ),
+        (status = 204, description = "No newer update was available before the timeout elapsed"),
+        (status = 400, description = "Invalid timeout", body = ProblemDetails),
+        (status = 403, description = "The feed is private", body = ProblemDetails),
+        (status = 404, description = "Unknown Feed ID", body = ProblemDetails)
+    ),
+)]
+pub async fn get_next_update(
+    State(state): State<AppState>,
+    PathExtractor(feed_id): PathExtractor<String>,
+    Query(params): Query<GetNextUpdateQuery>,
+) -> Result<Response, TheorosError> {
+    let started_at = std::time::Instant::now();
+
+    let timeout = match params.timeout {
+        Some(timeout) => parse_duration(&timeout).map_err(|e| TheorosError::InvalidTimeout(e.to_string()))?,
+        None => DEFAULT_LONG_POLL_TIMEOUT,
+    };
+    if timeout > MAX_LONG_POLL_TIMEOUT {
+        return Err(TheorosError::InvalidTimeout(format!("must be at most {}s", MAX_LONG_POLL_TIMEOUT.as_secs())));
+    }
+
+    if !state.storage.feed_ids().contains(&feed_id) {
+        return Err(TheorosError::FeedNotFound(feed_id));
+    }
+    ensure_public(&state, &feed_id)?;
+    let feed_id_u256 = hex_str_to_u256(&feed_id).map_err(|_| TheorosError::InvalidFeedId(feed_id.clone()))?;
+
+    // Subscribe before checking the latest update so no update can be missed in between.
+    let mut updates_rx = state.storage.feeds_updated_tx().subscribe();
+    let deadline = tokio::time::sleep(timeout);
+    tokio::pin!(deadline);
+
+    let response = loop {
+        if let Some(update) = state.storage.latest_update_per_feed().get(&feed_id_u256) {
+            if update.nonce > params.after_nonce {
+                break Json(GetNextUpdateResponse::new(feed_id, &update)).into_response();
+            }
+        }
+        tokio::select! {
+            _ = &mut deadline => break StatusCode::NO_CONTENT.into_response(),
+            received = updates_rx.recv() => match received {
+                Ok(_) | Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => return Err(TheorosError::InternalServerError),
+            },
+        }
+    };
+
+    tracing::info!("🌐 get_next_update - {:?}", started_at.elapsed());
+    Ok(response)
+}
@@ -0,0 +1,99 @@
+use std::time::Duration;
+
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToResponse, ToSchema};
+
+use pragma_utils::conversions::alloy::hex_str_to_u256;
+
+use crate::{
+    configs::indexer_start::parse_duration,
+    constants::DEFAULT_OHLC_INTERVAL,
+    errors::TheorosError,
+    extractors::PathExtractor,
+    handlers::rest::get_calldata::ensure_public,
+    types::ohlc::{aggregate_candles, Candle},
+    AppState,
+};
+
+#[derive(Deserialize, IntoParams, ToSchema)]
+pub struct GetOhlcQuery {
+    /// Duration of each candle, e.g. `1m`, `15m` or `1h`. Defaults to one minute.
+    pub interval: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToResponse, ToSchema)]
+pub struct GetOhlcResponse {
+    pub feed_id: String,
+    /// Duration of each candle, in seconds.
+    pub interval: u64,
+    /// Candles computed from the stored updates, sorted by open time.
+    pub candles: Vec<Candle>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/data_feeds/{feed_id}/ohlc",
+    params(
+        ("feed_id" = String, Path, description = "The feed ID to aggregate"),
+        GetOhlcQuery
+    ),
+    responses(
+        (status = 200, description = "OHLC candles of the stored updates of the feed", body = GetOhlcResponse),
+        (status = 400, description = "Invalid interval", body = ProblemDetails),
+        (status = 403, description = "The feed is private", body = ProblemDetails),
+        (status = 404, description = "Unknown Feed ID", body = ProblemDetails)
+    ),
+)]
+pub async fn get_ohlc(
+    State(state): State<AppState>,
+    PathExtractor(feed_id): PathExtractor<String>,
+    Query(params): Query<GetOhlcQuery>,
+) -> Result<Json<GetOhlcResponse>, TheorosError> {
+    let started_at = std::time::Instant::now();
+
+    let interval = parse_interval(params.interval.as_deref())?;
+
+    if !state.storage.feed_ids().contains(&feed_id) {
+        return Err(TheorosError::FeedNotFound(feed_id));
+    }
+    ensure_public(&state, &feed_id)?;
+    let feed_id_u256 = hex_str_to_u256(&feed_id).map_err(|_| TheorosError::InvalidFeedId(feed_id.clone()))?;
+
+    let history = state.storage.feed_history().get(&feed_id_u256);
+    let candles = aggregate_candles(&history, interval);
+
+    tracing::info!("🌐 get_ohlc - {:?}", started_at.elapsed());
+    Ok(Json(GetOhlcResponse { feed_id, interval: interval.as_secs(), candles }))
+}
+
+/// Parses the duration of the candles, aligned on whole seconds: the resolution of the timestamps of the updates.
+fn parse_interval(interval: Option<&str>) -> Result<Duration, TheorosError> {
+    let interval = match interval {
+        Some(interval) => parse_duration(interval).map_err(|e| TheorosError::InvalidInterval(e.to_string()))?,
+        None => DEFAULT_OHLC_INTERVAL,
+    };
+    if interval < Duration::from_secs(1) || interval.subsec_nanos() != 0 {
+        return Err(TheorosError::InvalidInterval(String::from("must be a whole number of seconds, at least 1s")));
+    }
+    Ok(interval)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interval_is_whole_seconds() {
+        assert_eq!(parse_interval(None).unwrap(), DEFAULT_OHLC_INTERVAL);
+        assert_eq!(parse_interval(Some("15m")).unwrap(), Duration::from_secs(15 * 60));
+        assert_eq!(parse_interval(Some("2000ms")).unwrap(), Duration::from_secs(2));
+
+        for interval in ["0s", "500ms", "1500ms", "999999999999999999d"] {
+            assert!(matches!(parse_interval(Some(interval)), Err(TheorosError::InvalidInterval(_))), "{interval}");
+        }
+    }
+}
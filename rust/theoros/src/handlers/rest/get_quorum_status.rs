@@ -0,0 +1,42 @@
+use axum::{extract::State, Json};
+use serde::{Deserialize, Serialize};
+use utoipa::{ToResponse, ToSchema};
+
+use crate::{errors::TheorosError, extractors::PathExtractor, types::quorum::QuorumStatus, AppState};
+
+#[derive(Debug, Serialize, Deserialize, ToResponse, ToSchema)]
+pub struct GetQuorumStatusResponse {
+    pub nonce: u32,
+    /// Signatures collected against the validators of each chain, sorted by chain name.
+    pub chains: Vec<QuorumStatus>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/debug/dispatches/{nonce}/quorum",
+    params(
+        ("nonce" = u32, Path, description = "Nonce of the indexed dispatch")
+    ),
+    responses(
+        (
+            status = 200,
+            description = "Validators which signed the dispatch & whether they reach the threshold of each chain",
+            body = GetQuorumStatusResponse
+        ),
+        (status = 404, description = "No raw event stored for the nonce", body = ProblemDetails)
+    ),
+)]
+pub async fn get_quorum_status(
+    State(state): State<AppState>,
+    PathExtractor(nonce): PathExtractor<u32>,
+) -> Result<Json<GetQuorumStatusResponse>, TheorosError> {
+    let started_at = std::time::Instant::now();
+
+    if state.storage.raw_dispatch_events().get(nonce).await.is_none() {
+        return Err(TheorosError::RawDispatchNotFound(nonce));
+    }
+    let chains = state.quorum_tracker.statuses(nonce);
+
+    tracing::info!("🌐 get_quorum_status - {:?}", started_at.elapsed());
+    Ok(Json(GetQuorumStatusResponse { nonce, chains }))
+}
@@ -0,0 +1,113 @@
+use axum::{extract::State, Json};
+use serde::{Deserialize, Serialize};
+use utoipa::{ToResponse, ToSchema};
+
+use crate::{
+    errors::TheorosError,
+    extractors::PathExtractor,
+    storage::RawDispatchEvent,
+    types::{
+        hyperlane::{DispatchEvent, FromStarknetEventData},
+        update_view::UpdateView,
+    },
+    AppState,
+};
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct RedecodedUpdate {
+    pub feed_id: String,
+    pub update: UpdateView,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct RedecodedParseFailure {
+    /// Position of the update in the dispatch.
+    pub update_index: u8,
+    /// Offset of the update in the message body, in bytes.
+    pub offset: usize,
+    pub error: String,
+}
+
+/// The raw event decoded again by this version of Theoros.
+#[derive(Debug, Default, Serialize, Deserialize, ToSchema)]
+pub struct RedecodedDispatch {
+    pub updates: Vec<RedecodedUpdate>,
+    pub parse_failures: Vec<RedecodedParseFailure>,
+    /// Set when the event could not be decoded at all.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToResponse, ToSchema)]
+pub struct GetRawDispatchResponse {
+    pub raw_event: RawDispatchEvent,
+    pub decoded: RedecodedDispatch,
+    /// Set when the dispatch may carry updates of private feeds: their updates are left out, along with the data
+    /// of the event.
+    pub redacted: bool,
+}
+
+impl RedecodedDispatch {
+    fn decode(raw_event: &RawDispatchEvent) -> Self {
+        let event = raw_event.data_felts().and_then(DispatchEvent::from_starknet_event_data);
+        let body = match event {
+            Ok(event) => event.message.body,
+            Err(e) => return Self { error: Some(format!("{e:#}")), ..Default::default() },
+        };
+        Self {
+            updates: body
+                .updates
+                .iter()
+                .map(|update| RedecodedUpdate { feed_id: update.feed_id(), update: UpdateView::from(update) })
+                .collect(),
+            parse_failures: body
+                .parse_failures
+                .into_iter()
+                .map(|failure| RedecodedParseFailure {
+                    update_index: failure.update_index,
+                    offset: failure.offset,
+                    error: failure.error,
+                })
+                .collect(),
+            error: None,
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/debug/dispatches/{nonce}",
+    params(
+        ("nonce" = u32, Path, description = "Nonce of the indexed dispatch")
+    ),
+    responses(
+        (
+            status = 200,
+            description = "The Starknet event of the dispatch as indexed, & its decoding by this version of Theoros",
+            body = GetRawDispatchResponse
+        ),
+        (status = 404, description = "No raw event stored for the nonce", body = ProblemDetails)
+    ),
+)]
+pub async fn get_raw_dispatch(
+    State(state): State<AppState>,
+    PathExtractor(nonce): PathExtractor<u32>,
+) -> Result<Json<GetRawDispatchResponse>, TheorosError> {
+    let started_at = std::time::Instant::now();
+
+    let mut raw_event =
+        state.storage.raw_dispatch_events().get(nonce).await.ok_or(TheorosError::RawDispatchNotFound(nonce))?;
+    let mut decoded = RedecodedDispatch::decode(&raw_event);
+
+    // The updates of the private feeds are only served encrypted for a registered consumer. Events which can't be
+    // decoded may carry some.
+    let num_updates = decoded.updates.len();
+    decoded.updates.retain(|update| !state.private_feeds.is_private(&update.feed_id));
+    let redacted = decoded.updates.len() < num_updates || (decoded.error.is_some() && !state.private_feeds.is_empty());
+    if redacted {
+        raw_event.data.clear();
+    }
+
+    tracing::info!("🌐 get_raw_dispatch - {:?}", started_at.elapsed());
+    Ok(Json(GetRawDispatchResponse { raw_event, decoded, redacted }))
+}
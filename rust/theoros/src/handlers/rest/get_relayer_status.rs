@@ -0,0 +1,37 @@
+use axum::extract::State;
+use axum::Json;
+use serde::{Deserialize, Serialize};
+use utoipa::{ToResponse, ToSchema};
+
+use crate::errors::TheorosError;
+use crate::types::relayer::ChainRelayerStatus;
+use crate::AppState;
+
+#[derive(Debug, Default, Serialize, Deserialize, ToResponse, ToSchema)]
+pub struct GetRelayerStatusResponse {
+    /// Whether the relayer sends the updates of feeds to at least one chain.
+    pub enabled: bool,
+    /// The relayer of each chain, sorted by chain name.
+    pub chains: Vec<ChainRelayerStatus>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/relayer/status",
+    responses(
+        (
+            status = 200,
+            description = "The nonces, counters & latest transactions of the relayer of each chain",
+            body = GetRelayerStatusResponse
+        )
+    ),
+)]
+pub async fn get_relayer_status(State(state): State<AppState>) -> Result<Json<GetRelayerStatusResponse>, TheorosError> {
+    let started_at = std::time::Instant::now();
+
+    let chains = state.relayer_statuses.all();
+    let response = GetRelayerStatusResponse { enabled: !chains.is_empty(), chains };
+
+    tracing::info!("🌐 get_relayer_status - {:?}", started_at.elapsed());
+    Ok(Json(response))
+}
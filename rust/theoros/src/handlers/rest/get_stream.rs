@@ -0,0 +1,131 @@
+use std::convert::Infallible;
+
+use axum::{
+    extract::{Query, State},
+    response::sse::{Event, KeepAlive, Sse},
+};
+use futures::{Stream, StreamExt};
+use serde::Deserialize;
+use tokio::sync::broadcast::error::RecvError;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::{
+    errors::TheorosError,
+    handlers::{
+        rest::get_calldata::{
+            deserialize_feed_ids, ensure_batch_size, ensure_not_retired, ensure_private_feeds_encrypted, resolve_chain,
+        },
+        websocket::{
+            fanout::{FanoutSubscriptions, SubscriptionKind},
+            subscribe_to_feed_updates::ServerMessage,
+        },
+    },
+    AppState,
+};
+
+#[derive(Debug, Deserialize, IntoParams, ToSchema)]
+pub struct GetStreamQuery {
+    /// Comma-separated feed IDs to stream the updates of.
+    #[serde(deserialize_with = "deserialize_feed_ids")]
+    pub feed_ids: Vec<String>,
+    /// The destination chain. Falls back to the default chain when omitted.
+    pub chain: Option<String>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/stream",
+    params(GetStreamQuery),
+    responses(
+        (
+            status = 200,
+            description = "Server-Sent Events stream of the updates of the feeds, with the payloads of the `/v1/ws` messages. \
+                Each event is named after the `type` of its message: `feed_update`, `error` or `lagged`",
+            content_type = "text/event-stream",
+            body = String
+        ),
+        (
+            status = 403,
+            description = "A private feed is requested, its calldata is only served by the calldata endpoints",
+            body = ProblemDetails
+        ),
+        (
+            status = 404,
+            description = "Unknown Feed ID",
+            body = ProblemDetails
+        ),
+        (
+            status = 410,
+            description = "The feed is retired",
+            body = ProblemDetails
+        ),
+        (
+            status = 413,
+            description = "More feeds requested than allowed at once",
+            body = ProblemDetails
+        ),
+        (
+            status = 503,
+            description = "Serving calldata for the chain is disabled",
+            body = ProblemDetails
+        )
+    ),
+)]
+pub async fn get_stream(
+    State(state): State<AppState>,
+    Query(params): Query<GetStreamQuery>,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, TheorosError> {
+    let started_at = std::time::Instant::now();
+
+    let chain_name = resolve_chain(&state, params.chain.as_deref())?;
+    ensure_batch_size(&state, params.feed_ids.len())?;
+    if let Some(missing_id) = state.storage.feed_ids().contains_vec(&params.feed_ids) {
+        return Err(TheorosError::FeedNotFound(missing_id));
+    }
+    ensure_not_retired(&state, &params.feed_ids)?;
+    // The calldata pushed is built once for all the subscribers, so it can't be encrypted for each one.
+    ensure_private_feeds_encrypted(&state, &params.feed_ids, None)?;
+
+    // Unregistered from the fanout once the client disconnects & the stream is dropped.
+    let mut subscriptions = FanoutSubscriptions::new(state.ws.clone(), SubscriptionKind::FeedUpdates);
+    for feed_id in params.feed_ids {
+        subscriptions.insert(feed_id, chain_name);
+    }
+    let updates_receiver = state.ws.fanout.receiver();
+    let events = futures::stream::unfold(
+        (state, updates_receiver, subscriptions),
+        |(state, mut updates_receiver, subscriptions)| async move {
+            let events = loop {
+                match updates_receiver.recv().await {
+                    Ok(batch) => {
+                        let events: Vec<_> = batch
+                            .frames_for(&subscriptions)
+                            .map(|frame| {
+                                if frame.data_feed.is_ok() {
+                                    state.metrics.calldata_served.inc();
+                                }
+                                Event::default().event(frame.message_name).data(frame.message_text())
+                            })
+                            .collect();
+                        if !events.is_empty() {
+                            break events;
+                        }
+                    }
+                    Err(RecvError::Lagged(skipped)) => {
+                        let lagged = ServerMessage::Lagged { skipped };
+                        match Event::default().event(lagged.name()).json_data(&lagged) {
+                            Ok(event) => break vec![event],
+                            Err(e) => tracing::error!("😱 Failed to serialize the stream event {:?}: {:?}", lagged, e),
+                        }
+                    }
+                    Err(RecvError::Closed) => return None,
+                }
+            };
+            Some((futures::stream::iter(events.into_iter().map(Ok)), (state, updates_receiver, subscriptions)))
+        },
+    )
+    .flatten();
+
+    tracing::info!("🌐 get_stream - {:?}", started_at.elapsed());
+    Ok(Sse::new(events).keep_alive(KeepAlive::default()))
+}
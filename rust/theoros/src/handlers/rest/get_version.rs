@@ -0,0 +1,17 @@
+use axum::Json;
+
+use crate::types::build_info::BuildInfo;
+
+#[utoipa::path(
+    get,
+    path = "/v1/version",
+    responses(
+        (status = 200, description = "Get the version of the running build & of its calldata encoder", body = BuildInfo)
+    ),
+)]
+pub async fn get_version() -> Json<BuildInfo> {
+    let started_at = std::time::Instant::now();
+    let response = BuildInfo::current();
+    tracing::info!("🌐 get_version - {:?}", started_at.elapsed());
+    Json(response)
+}
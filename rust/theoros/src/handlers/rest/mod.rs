@@ -1,3 +1,21 @@
+pub mod get_anomalies;
 pub mod get_calldata;
+pub mod get_calldata_by_chain;
+pub mod get_calldata_by_feed_id;
+pub mod get_calldata_by_id;
 pub mod get_chains;
+pub mod get_checkpoint;
+pub mod get_data_feed;
 pub mod get_data_feeds;
+pub mod get_feed_timeline;
+pub mod get_history;
+pub mod get_next_update;
+pub mod get_ohlc;
+pub mod get_quorum_status;
+pub mod get_raw_dispatch;
+pub mod get_relayer_status;
+pub mod get_stream;
+pub mod get_version;
+pub mod post_calldata_batch;
+pub mod simulate_calldata;
+pub mod simulate_update;
@@ -0,0 +1,164 @@
+use std::collections::HashSet;
+use std::str::FromStr;
+
+use alloy::{hex, primitives::Bytes};
+use axum::extract::State;
+use axum::Json;
+use serde::{Deserialize, Serialize};
+use utoipa::{ToResponse, ToSchema};
+
+use crate::{
+    configs::evm_config::EvmChainName,
+    errors::TheorosError,
+    extractors::{JsonExtractor, PathExtractor},
+    handlers::rest::get_calldata::{
+        ensure_batch_size, ensure_chain_served, ensure_not_retired, ensure_private_feeds_encrypted, resolve_consumer,
+    },
+    rpc::evm::calldata::encode_update_data_feeds,
+    types::{
+        api::v1::CalldataResponse,
+        calldata::{Calldata, CalldataOrdering},
+    },
+    AppState,
+};
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CalldataBatchRequest {
+    pub feed_ids: Vec<String>,
+    /// Order of the returned calldata, also followed by the packed call. Defaults to the requested order.
+    #[serde(default)]
+    pub order: CalldataOrdering,
+    /// Consumer whose registered key is used to encrypt the calldata. Served in cleartext when omitted, which
+    /// private feeds refuse.
+    pub consumer: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToResponse, ToSchema)]
+pub struct CalldataBatchResponse {
+    pub chain: EvmChainName,
+    /// The calldata of each feed, sorted according to `order`.
+    pub calldata: Vec<CalldataResponse>,
+    /// Pragma contract to send the update to, if configured for the chain.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contract_address: Option<String>,
+    /// ABI-encoded `updateDataFeeds` call updating all the feeds in one transaction, with the calldata in
+    /// the order of `calldata`, as a hex string. Only served for cleartext calldata.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transaction_data: Option<String>,
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/calldata/batch/{chain_name}",
+    params(
+        ("chain_name" = String, Path, description = "The destination chain")
+    ),
+    request_body = CalldataBatchRequest,
+    responses(
+        (
+            status = 200,
+            description = "Constructs the calldata of the feeds, sorted according to `order`, & the call submitting them all at once",
+            body = CalldataBatchResponse
+        ),
+        (
+            status = 400,
+            description = "Empty batch or duplicated Feed ID",
+            body = ProblemDetails
+        ),
+        (
+            status = 403,
+            description = "A private feed is requested without a consumer",
+            body = ProblemDetails
+        ),
+        (
+            status = 404,
+            description = "Unknown Feed ID or consumer",
+            body = ProblemDetails
+        ),
+        (
+            status = 410,
+            description = "The feed is retired",
+            body = ProblemDetails
+        ),
+        (
+            status = 413,
+            description = "Body too large, or more feeds requested than allowed at once",
+            body = ProblemDetails
+        ),
+        (
+            status = 503,
+            description = "Serving calldata for the chain is disabled",
+            body = ProblemDetails
+        ),
+        (
+            status = 504,
+            description = "Some validators could not be fetched before the deadline",
+            body = ProblemDetails
+        )
+    ),
+)]
+pub async fn post_calldata_batch(
+    State(state): State<AppState>,
+    PathExtractor(chain_name): PathExtractor<String>,
+    JsonExtractor(request): JsonExtractor<CalldataBatchRequest>,
+) -> Result<Json<CalldataBatchResponse>, TheorosError> {
+    let started_at = std::time::Instant::now();
+
+    let chain_name = EvmChainName::from_str(&chain_name).map_err(|_| TheorosError::ChainNotSupported(chain_name))?;
+    ensure_chain_served(&state, chain_name)?;
+    let consumer = resolve_consumer(&state, request.consumer.as_deref())?;
+
+    if request.feed_ids.is_empty() {
+        return Err(TheorosError::InvalidBatch(String::from("no feed ids provided")));
+    }
+    ensure_batch_size(&state, request.feed_ids.len())?;
+    let mut unique_ids = HashSet::with_capacity(request.feed_ids.len());
+    if let Some(duplicate) = request.feed_ids.iter().find(|feed_id| !unique_ids.insert(*feed_id)) {
+        return Err(TheorosError::InvalidBatch(format!("feed id {duplicate} is duplicated")));
+    }
+    if let Some(missing_id) = state.storage.feed_ids().contains_vec(&request.feed_ids) {
+        return Err(TheorosError::FeedNotFound(missing_id));
+    }
+    ensure_not_retired(&state, &request.feed_ids)?;
+    ensure_private_feeds_encrypted(&state, &request.feed_ids, consumer.as_ref())?;
+
+    let mut feed_ids = request.feed_ids;
+    request.order.apply(&mut feed_ids);
+
+    // Build calldata for each feed ID, all sharing the deadline of the request.
+    let deadline = started_at + state.calldata_deadline;
+    let mut calldata = Vec::with_capacity(feed_ids.len());
+    let mut update_data = Vec::with_capacity(feed_ids.len());
+    for feed_id in &feed_ids {
+        let feed_calldata =
+            Calldata::build_from(&state, chain_name, feed_id.clone(), deadline).await.map_err(TheorosError::from)?;
+
+        state.storage.feed_timelines().record_calldata_served(feed_id, feed_calldata.hyperlane_msg.nonce, chain_name);
+        let encoded_calldata = feed_calldata.encode_for_chain(&state, &chain_name);
+        if consumer.is_none() {
+            update_data.push(Bytes::from(encoded_calldata.clone()));
+        }
+        calldata.push(CalldataResponse::serve(
+            &state,
+            feed_id.clone(),
+            chain_name,
+            encoded_calldata,
+            consumer.as_ref(),
+        )?);
+    }
+
+    let transaction_data = match consumer {
+        Some(_) => None,
+        None => Some(hex::encode_prefixed(encode_update_data_feeds(update_data))),
+    };
+    let response = CalldataBatchResponse {
+        chain: chain_name,
+        contract_address: state.pragma_contracts.get(&chain_name).map(|contract| contract.address.to_string()),
+        transaction_data,
+        calldata,
+    };
+
+    state.metrics.calldata_served.inc_by(response.calldata.len() as u64);
+    tracing::info!("🌐 post_calldata_batch - {:?}", started_at.elapsed());
+    Ok(Json(response))
+}
@@ -0,0 +1,101 @@
+use std::str::FromStr;
+
+use axum::{extract::State, Json};
+use serde::Deserialize;
+use utoipa::ToSchema;
+
+use crate::{
+    configs::evm_config::EvmChainName,
+    errors::TheorosError,
+    extractors::{JsonExtractor, PathExtractor},
+    handlers::rest::{
+        get_calldata::ensure_chain_served,
+        simulate_update::{parse_address, parse_value, simulate_feeds_update, SimulateUpdateResponse, SimulatedCall},
+    },
+    AppState,
+};
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SimulateCalldataRequest {
+    pub feed_ids: Vec<String>,
+    /// Sender of the simulated call.
+    pub from: Option<String>,
+    /// Value, in wei, sent along the update to pay its fee. Defaults to 0.
+    pub value: Option<String>,
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/calldata/{chain_name}/simulate",
+    params(
+        ("chain_name" = String, Path, description = "The destination chain"),
+    ),
+    request_body = SimulateCalldataRequest,
+    responses(
+        (
+            status = 200,
+            description = "Simulates, through the chain RPC, the update of the feeds on the Pragma contract of the chain & returns its revert reason or gas estimate",
+            body = SimulateUpdateResponse
+        ),
+        (
+            status = 400,
+            description = "Invalid address or value",
+            body = ProblemDetails
+        ),
+        (
+            status = 403,
+            description = "A private feed is requested, its calldata can't be simulated",
+            body = ProblemDetails
+        ),
+        (
+            status = 404,
+            description = "Unknown Feed ID or no Pragma contract for the chain",
+            body = ProblemDetails
+        ),
+        (
+            status = 410,
+            description = "The feed is retired",
+            body = ProblemDetails
+        ),
+        (
+            status = 413,
+            description = "Body too large, or more feeds requested than allowed at once",
+            body = ProblemDetails
+        ),
+        (
+            status = 502,
+            description = "The RPC could not run the simulation",
+            body = ProblemDetails
+        ),
+        (
+            status = 503,
+            description = "Serving calldata for the chain is disabled",
+            body = ProblemDetails
+        )
+    ),
+)]
+pub async fn simulate_calldata(
+    State(state): State<AppState>,
+    PathExtractor(chain_name): PathExtractor<String>,
+    JsonExtractor(request): JsonExtractor<SimulateCalldataRequest>,
+) -> Result<Json<SimulateUpdateResponse>, TheorosError> {
+    let started_at = std::time::Instant::now();
+
+    let chain_name = EvmChainName::from_str(&chain_name).map_err(|_| TheorosError::ChainNotSupported(chain_name))?;
+    ensure_chain_served(&state, chain_name)?;
+    let contract = state
+        .pragma_contracts
+        .get(&chain_name)
+        .ok_or_else(|| TheorosError::PragmaContractNotConfigured(chain_name.to_string()))?;
+
+    let call = SimulatedCall {
+        rpc_url: contract.rpc_url.clone(),
+        contract_address: contract.address,
+        from: request.from.as_deref().map(parse_address).transpose()?,
+        value: parse_value(request.value.as_deref())?,
+    };
+
+    let response = simulate_feeds_update(&state, chain_name, request.feed_ids, call, started_at).await?;
+    tracing::info!("🌐 simulate_calldata - {:?}", started_at.elapsed());
+    Ok(Json(response))
+}
@@ -0,0 +1,213 @@
+use std::str::FromStr;
+use std::time::Instant;
+
+use alloy::{
+    hex::{self, FromHex},
+    primitives::{Address, Bytes, U256},
+};
+use axum::{extract::State, Json};
+use serde::{Deserialize, Serialize};
+use url::Url;
+use utoipa::{ToResponse, ToSchema};
+
+use crate::{
+    configs::evm_config::EvmChainName,
+    errors::TheorosError,
+    extractors::JsonExtractor,
+    handlers::rest::get_calldata::{
+        ensure_batch_size, ensure_not_retired, ensure_private_feeds_encrypted, resolve_chain,
+    },
+    rpc::evm::pragma::{simulate_update_data_feeds, SimulationOutcome},
+    types::{calldata::Calldata, units::NativeAmount},
+    AppState,
+};
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SimulateUpdateRequest {
+    /// The destination chain. Falls back to the default chain when omitted.
+    pub chain: Option<String>,
+    pub feed_ids: Vec<String>,
+    /// HTTPS RPC of a fork of the chain (e.g. a Tenderly fork) to simulate against instead of the chain RPC.
+    pub fork_url: Option<String>,
+    /// Pragma contract to call. Defaults to the contract configured for the chain.
+    pub contract_address: Option<String>,
+    /// Sender of the simulated call.
+    pub from: Option<String>,
+    /// Value, in wei, sent along the update to pay its fee. Defaults to 0.
+    pub value: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct SimulatedRevert {
+    /// Name of the error, e.g. `InvalidHyperlaneSignatures`, `Error` or `Panic`.
+    pub error: String,
+    /// Human readable reason of the revert.
+    pub reason: String,
+    /// Raw revert data, as a hex string.
+    pub data: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToResponse, ToSchema)]
+pub struct SimulateUpdateResponse {
+    pub chain: String,
+    pub contract_address: String,
+    pub feed_ids: Vec<String>,
+    /// Value sent along the update, in the units of the chain native token.
+    pub value: NativeAmount,
+    /// Whether the update would be applied without reverting.
+    pub success: bool,
+    /// Gas used by the update, estimated through `eth_estimateGas`, if it would be applied.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gas_estimate: Option<u64>,
+    /// The decoded revert, if the update reverted.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub revert: Option<SimulatedRevert>,
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/simulate/update",
+    request_body = SimulateUpdateRequest,
+    responses(
+        (
+            status = 200,
+            description = "Simulates, through an `eth_call`, the update of the feeds on the Pragma contract & decodes its revert reason or estimates its gas",
+            body = SimulateUpdateResponse
+        ),
+        (
+            status = 400,
+            description = "Invalid fork URL, address or value",
+            body = ProblemDetails
+        ),
+        (
+            status = 403,
+            description = "A private feed is requested, its calldata can't be simulated",
+            body = ProblemDetails
+        ),
+        (
+            status = 404,
+            description = "Unknown Feed ID or no Pragma contract for the chain",
+            body = ProblemDetails
+        ),
+        (
+            status = 410,
+            description = "The feed is retired",
+            body = ProblemDetails
+        ),
+        (
+            status = 413,
+            description = "Body too large, or more feeds requested than allowed at once",
+            body = ProblemDetails
+        ),
+        (
+            status = 502,
+            description = "The RPC could not run the simulation",
+            body = ProblemDetails
+        )
+    ),
+)]
+pub async fn simulate_update(
+    State(state): State<AppState>,
+    JsonExtractor(request): JsonExtractor<SimulateUpdateRequest>,
+) -> Result<Json<SimulateUpdateResponse>, TheorosError> {
+    let started_at = std::time::Instant::now();
+
+    let chain_name = resolve_chain(&state, request.chain.as_deref())?;
+
+    let configured_contract = state.pragma_contracts.get(&chain_name);
+    let contract_address = match (&request.contract_address, &configured_contract) {
+        (Some(address), _) => parse_address(address)?,
+        (None, Some(contract)) => contract.address,
+        (None, None) => return Err(TheorosError::PragmaContractNotConfigured(chain_name.to_string())),
+    };
+    let rpc_url = match (&request.fork_url, &configured_contract) {
+        (Some(fork_url), _) => parse_fork_url(fork_url)?,
+        (None, Some(contract)) => contract.rpc_url.clone(),
+        (None, None) => return Err(TheorosError::PragmaContractNotConfigured(chain_name.to_string())),
+    };
+    let call = SimulatedCall {
+        rpc_url,
+        contract_address,
+        from: request.from.as_deref().map(parse_address).transpose()?,
+        value: parse_value(request.value.as_deref())?,
+    };
+
+    let response = simulate_feeds_update(&state, chain_name, request.feed_ids, call, started_at).await?;
+    tracing::info!("🌐 simulate_update - {:?}", started_at.elapsed());
+    Ok(Json(response))
+}
+
+/// Call of the Pragma contract simulated with the update of the feeds.
+pub(crate) struct SimulatedCall {
+    pub rpc_url: Url,
+    pub contract_address: Address,
+    pub from: Option<Address>,
+    pub value: U256,
+}
+
+/// Builds the calldata of the feeds, sharing the deadline of the request, & simulates their update.
+pub(crate) async fn simulate_feeds_update(
+    state: &AppState,
+    chain_name: EvmChainName,
+    feed_ids: Vec<String>,
+    call: SimulatedCall,
+    started_at: Instant,
+) -> Result<SimulateUpdateResponse, TheorosError> {
+    ensure_batch_size(state, feed_ids.len())?;
+    if let Some(missing_id) = state.storage.feed_ids().contains_vec(&feed_ids) {
+        return Err(TheorosError::FeedNotFound(missing_id));
+    }
+    ensure_not_retired(state, &feed_ids)?;
+    // The calldata is sent in cleartext to the RPC simulating the update.
+    ensure_private_feeds_encrypted(state, &feed_ids, None)?;
+
+    let deadline = started_at + state.calldata_deadline;
+    let mut update_data = Vec::with_capacity(feed_ids.len());
+    for feed_id in &feed_ids {
+        let calldata =
+            Calldata::build_from(state, chain_name, feed_id.clone(), deadline).await.map_err(TheorosError::from)?;
+        update_data.push(Bytes::from(calldata.encode_for_chain(state, &chain_name)));
+    }
+
+    let outcome = simulate_update_data_feeds(call.rpc_url, call.contract_address, update_data, call.from, call.value)
+        .await
+        .map_err(|e| TheorosError::RpcError(e.to_string()))?;
+    let (gas_estimate, revert) = match outcome {
+        SimulationOutcome::Success { gas_estimate } => (Some(gas_estimate), None),
+        SimulationOutcome::Reverted(revert) => {
+            let data = hex::encode_prefixed(&revert.data);
+            (None, Some(SimulatedRevert { error: revert.error, reason: revert.reason, data }))
+        }
+    };
+
+    Ok(SimulateUpdateResponse {
+        chain: chain_name.to_string(),
+        contract_address: call.contract_address.to_string(),
+        feed_ids,
+        value: NativeAmount::new(chain_name, call.value),
+        success: revert.is_none(),
+        gas_estimate,
+        revert,
+    })
+}
+
+/// Parses the value sent along the update, in wei. Defaults to 0.
+pub(crate) fn parse_value(value: Option<&str>) -> Result<U256, TheorosError> {
+    match value {
+        Some(value) => U256::from_str(value).map_err(|e| TheorosError::InvalidValue(e.to_string())),
+        None => Ok(U256::ZERO),
+    }
+}
+
+pub(crate) fn parse_address(address: &str) -> Result<Address, TheorosError> {
+    Address::from_hex(address).map_err(|e| TheorosError::InvalidAddress(format!("{address}: {e}")))
+}
+
+/// Only HTTPS fork URLs are accepted, so the simulation can't be used to reach internal plain HTTP services.
+fn parse_fork_url(fork_url: &str) -> Result<Url, TheorosError> {
+    let url = Url::parse(fork_url).map_err(|e| TheorosError::InvalidForkUrl(e.to_string()))?;
+    if url.scheme() != "https" {
+        return Err(TheorosError::InvalidForkUrl(String::from("only https URLs are supported")));
+    }
+    Ok(url)
+}
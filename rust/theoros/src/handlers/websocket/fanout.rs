@@ -0,0 +1,278 @@
+use std::{collections::BTreeMap, sync::Arc, time::Instant};
+
+use anyhow::Result;
+use axum::body::Bytes;
+use dashmap::DashMap;
+use tokio::sync::broadcast::{self, error::RecvError, Receiver, Sender};
+
+use crate::{
+    configs::evm_config::EvmChainName,
+    constants::FEED_UPDATED_CHANNEL_CAPACITY,
+    errors::TheorosError,
+    handlers::websocket::subscribe_to_feed_updates::ServerMessage,
+    types::{api::v1::RpcDataFeed, hyperlane::NewUpdatesAvailableEvent, state::WsState},
+    AppState,
+};
+
+/// When a subscriber wants the calldata of a feed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SubscriptionKind {
+    /// At every dispatch, whether it updated the feed or not, like `/v1/data_feeds/subscribe`.
+    EveryDispatch,
+    /// Only at the dispatches updating the feed, like `/v1/ws` & `/v1/stream`.
+    FeedUpdates,
+}
+
+/// The calldata of a feed for a chain at a dispatch, serialized once for all the subscribers.
+#[derive(Debug)]
+pub struct FeedFrame {
+    pub chain: EvmChainName,
+    pub feed_id: String,
+    /// Whether the dispatch updated the feed.
+    pub updated: bool,
+    /// The serialized [RpcDataFeed], or why it could not be built.
+    pub data_feed: Result<Bytes, String>,
+    /// The built [RpcDataFeed] & the nonce of the dispatch of its update, for the subscribers not served JSON.
+    pub served: Option<(u32, RpcDataFeed)>,
+    /// The serialized [ServerMessage] pushing the update, a `feed_update` or an `error`.
+    pub message: Bytes,
+    pub message_name: &'static str,
+}
+
+impl FeedFrame {
+    fn build(
+        chain: EvmChainName,
+        feed_id: String,
+        updated: bool,
+        nonce: u32,
+        built: Result<(RpcDataFeed, u32)>,
+    ) -> Result<Self> {
+        let (data_feed, served, message) = match built {
+            // The feed may have been updated again since, its latest update is served.
+            Ok((data_feed, latest_nonce)) => (
+                Ok(Bytes::from(serde_json::to_vec(&data_feed)?)),
+                Some((latest_nonce, data_feed.clone())),
+                ServerMessage::FeedUpdate { nonce: latest_nonce, chain, data_feed },
+            ),
+            Err(e) => (
+                Err(e.to_string()),
+                None,
+                ServerMessage::Error {
+                    error: format!("Error building calldata of dispatch #{} for {}: {}", nonce, feed_id, e),
+                },
+            ),
+        };
+        Ok(Self {
+            chain,
+            feed_id,
+            updated,
+            data_feed,
+            served,
+            message: Bytes::from(serde_json::to_vec(&message)?),
+            message_name: message.name(),
+        })
+    }
+
+    /// The serialized [ServerMessage], as the text of a WebSocket message or of a Server-Sent Event.
+    pub fn message_text(&self) -> &str {
+        std::str::from_utf8(&self.message).expect("Frames are serialized JSON")
+    }
+}
+
+/// The frames of the feeds subscribed to, built for a dispatch.
+#[derive(Debug)]
+pub struct FanoutBatch {
+    pub nonce: u32,
+    pub frames: Vec<FeedFrame>,
+}
+
+impl FanoutBatch {
+    /// Frames of the subscribed feeds, for the chain each of them is subscribed for. Frames of the feeds
+    /// not updated by the dispatch are left out, unless subscribed to at every dispatch.
+    pub(crate) fn frames_for<'a>(
+        &'a self,
+        subscriptions: &'a FanoutSubscriptions,
+    ) -> impl Iterator<Item = &'a FeedFrame> + 'a {
+        self.frames.iter().filter(|frame| {
+            (frame.updated || subscriptions.kind == SubscriptionKind::EveryDispatch)
+                && subscriptions.get(&frame.feed_id) == Some(frame.chain)
+        })
+    }
+}
+
+/// Builds the calldata of the subscribed feeds once per dispatch & broadcasts it serialized, so the cost
+/// of an update doesn't grow with the number of subscribers.
+pub struct FeedUpdatesFanout {
+    /// Number of subscribers of each feed for a chain.
+    subscriptions: DashMap<(EvmChainName, String, SubscriptionKind), usize>,
+    batches_tx: Sender<Arc<FanoutBatch>>,
+}
+
+#[allow(clippy::new_without_default)]
+impl FeedUpdatesFanout {
+    pub fn new() -> Self {
+        Self { subscriptions: DashMap::new(), batches_tx: broadcast::channel(FEED_UPDATED_CHANNEL_CAPACITY).0 }
+    }
+
+    pub fn receiver(&self) -> Receiver<Arc<FanoutBatch>> {
+        self.batches_tx.subscribe()
+    }
+
+    fn register(&self, chain: EvmChainName, feed_id: &str, kind: SubscriptionKind) {
+        *self.subscriptions.entry((chain, feed_id.to_owned(), kind)).or_default() += 1;
+    }
+
+    fn unregister(&self, chain: EvmChainName, feed_id: &str, kind: SubscriptionKind) {
+        let key = (chain, feed_id.to_owned(), kind);
+        self.subscriptions.remove_if_mut(&key, |_, subscribers| {
+            *subscribers -= 1;
+            *subscribers == 0
+        });
+    }
+
+    /// The feeds to build for a dispatch updating `updated_feed_ids`, & whether it updated them.
+    fn targets(&self, updated_feed_ids: &[String]) -> Vec<(EvmChainName, String, bool)> {
+        let mut targets: Vec<_> = self
+            .subscriptions
+            .iter()
+            .filter_map(|entry| {
+                let (chain, feed_id, kind) = entry.key();
+                let updated = updated_feed_ids.contains(feed_id);
+                (updated || *kind == SubscriptionKind::EveryDispatch).then(|| (*chain, feed_id.clone(), updated))
+            })
+            .collect();
+        targets.sort_by(|a, b| (a.0.to_string(), &a.1).cmp(&(b.0.to_string(), &b.1)));
+        targets.dedup();
+        targets
+    }
+
+    /// Broadcasts the frames of the subscribed feeds each time a dispatch reaches quorum.
+    pub async fn run(state: AppState) -> Result<()> {
+        let mut updates_receiver = state.storage.feeds_updated_tx().subscribe();
+        loop {
+            let update = tokio::select! {
+                update = updates_receiver.recv() => update,
+                _ = state.shutdown.cancelled() => return Ok(()),
+            };
+            let (nonce, feed_ids) = match update {
+                Ok(NewUpdatesAvailableEvent::New { nonce, feed_ids }) => (nonce, feed_ids),
+                Err(RecvError::Lagged(skipped)) => {
+                    tracing::warn!("🕸️ [Websocket] Fanout lagged, skipped {} dispatches", skipped);
+                    continue;
+                }
+                Err(RecvError::Closed) => return Ok(()),
+            };
+            let fanout = &state.ws.fanout;
+            let targets: Vec<_> = fanout
+                .targets(&feed_ids)
+                .into_iter()
+                .filter(|(chain, _, _)| state.chain_statuses.is_served(chain))
+                .collect();
+            if targets.is_empty() {
+                continue;
+            }
+
+            let deadline = Instant::now() + state.calldata_deadline;
+            let state = &state;
+            let built = futures::future::join_all(targets.iter().map(|(chain, feed_id, _)| async move {
+                // Made private since subscribed to, its subscribers are told its calldata isn't pushed anymore.
+                if state.private_feeds.is_private(feed_id) {
+                    anyhow::bail!(TheorosError::PrivateFeed(feed_id.clone()));
+                }
+                RpcDataFeed::build(state, *chain, feed_id.clone(), deadline).await
+            }))
+            .await;
+            let mut frames = Vec::with_capacity(targets.len());
+            for ((chain, feed_id, updated), built) in targets.into_iter().zip(built) {
+                frames.push(FeedFrame::build(chain, feed_id, updated, nonce, built)?);
+            }
+            // Only fails when no one is subscribed anymore.
+            let _ = fanout.batches_tx.send(Arc::new(FanoutBatch { nonce, frames }));
+        }
+    }
+}
+
+/// The feeds a subscriber registered to the fanout, with the chain of each one. Unregistered on drop.
+pub(crate) struct FanoutSubscriptions {
+    ws: Arc<WsState>,
+    kind: SubscriptionKind,
+    feeds: BTreeMap<String, EvmChainName>,
+}
+
+impl FanoutSubscriptions {
+    pub fn new(ws: Arc<WsState>, kind: SubscriptionKind) -> Self {
+        Self { ws, kind, feeds: BTreeMap::new() }
+    }
+
+    /// Subscribes to the feed for the chain, replacing the chain it was subscribed for.
+    pub fn insert(&mut self, feed_id: String, chain: EvmChainName) {
+        if self.get(&feed_id) == Some(chain) {
+            return;
+        }
+        self.remove(&feed_id);
+        self.ws.fanout.register(chain, &feed_id, self.kind);
+        self.feeds.insert(feed_id, chain);
+    }
+
+    pub fn remove(&mut self, feed_id: &str) {
+        if let Some(chain) = self.feeds.remove(feed_id) {
+            self.ws.fanout.unregister(chain, feed_id, self.kind);
+        }
+    }
+
+    pub fn get(&self, feed_id: &str) -> Option<EvmChainName> {
+        self.feeds.get(feed_id).copied()
+    }
+
+    pub fn contains(&self, feed_id: &str) -> bool {
+        self.feeds.contains_key(feed_id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.feeds.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.feeds.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &EvmChainName)> {
+        self.feeds.iter()
+    }
+}
+
+impl Drop for FanoutSubscriptions {
+    fn drop(&mut self) {
+        for (feed_id, chain) in &self.feeds {
+            self.ws.fanout.unregister(*chain, feed_id, self.kind);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subscriptions_are_built_once() {
+        let ws = Arc::new(WsState::new());
+        let mut first = FanoutSubscriptions::new(ws.clone(), SubscriptionKind::FeedUpdates);
+        let mut second = FanoutSubscriptions::new(ws.clone(), SubscriptionKind::FeedUpdates);
+        first.insert("0x1".into(), EvmChainName::Sepolia);
+        second.insert("0x1".into(), EvmChainName::Sepolia);
+        second.insert("0x2".into(), EvmChainName::Sepolia);
+
+        let updated = vec!["0x1".to_owned()];
+        assert_eq!(ws.fanout.targets(&updated), vec![(EvmChainName::Sepolia, "0x1".to_owned(), true)]);
+
+        let mut legacy = FanoutSubscriptions::new(ws.clone(), SubscriptionKind::EveryDispatch);
+        legacy.insert("0x2".into(), EvmChainName::Sepolia);
+        assert_eq!(ws.fanout.targets(&updated).len(), 2);
+
+        drop(legacy);
+        drop(second);
+        first.remove("0x1");
+        assert!(ws.fanout.targets(&updated).is_empty());
+        assert!(ws.fanout.subscriptions.is_empty());
+    }
+}
@@ -1 +1,11 @@
+pub mod fanout;
+pub mod subscribe_to_anomalies;
 pub mod subscribe_to_calldata;
+pub mod subscribe_to_feed_updates;
+
+use axum::extract::ws::{close_code, CloseFrame, Message};
+
+/// Sent to the clients when Theoros shuts down, so they reconnect to another instance.
+pub(crate) fn shutdown_close_frame() -> Message {
+    Message::Close(Some(CloseFrame { code: close_code::AWAY, reason: "Theoros is shutting down".into() }))
+}
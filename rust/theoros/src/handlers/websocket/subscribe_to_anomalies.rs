@@ -0,0 +1,72 @@
+use std::net::SocketAddr;
+
+use anyhow::Result;
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        ConnectInfo, State as AxumState,
+    },
+    response::IntoResponse,
+};
+use futures::{SinkExt, StreamExt};
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::{constants::PING_INTERVAL_DURATION, handlers::websocket::shutdown_close_frame, AppState};
+
+/// WebSocket route handler streaming the validator checkpoint anomalies as they are detected.
+#[utoipa::path(
+    get,
+    path = "/v1/ws/anomalies",
+    responses(
+        (status = 101, description = "Upgrades to a WebSocket pushing the checkpoint anomalies as they are detected")
+    ),
+)]
+pub async fn ws_anomalies_route_handler(
+    ws: WebSocketUpgrade,
+    AxumState(state): AxumState<AppState>,
+    _connect_info: ConnectInfo<SocketAddr>,
+) -> impl IntoResponse {
+    let connections = state.ws.connections.clone();
+    ws.on_upgrade(move |socket| {
+        connections.track_future(async move {
+            let _connection = state.metrics.track_ws_connection("anomalies");
+            if let Err(e) = anomalies_websocket_handler(socket, state).await {
+                tracing::debug!("🕸️ [Websocket] Anomalies subscriber disconnected: {:?}", e);
+            }
+        })
+    })
+}
+
+/// Sends every new anomaly to the client until it disconnects.
+async fn anomalies_websocket_handler(stream: WebSocket, state: AppState) -> Result<()> {
+    let (mut sender, mut receiver) = stream.split();
+    let mut anomalies_receiver = state.storage.checkpoint_anomalies().anomalies_tx().subscribe();
+    let mut ping_interval = tokio::time::interval(PING_INTERVAL_DURATION);
+
+    loop {
+        tokio::select! {
+            anomaly = anomalies_receiver.recv() => {
+                match anomaly {
+                    Ok(anomaly) => sender.send(Message::Text(serde_json::to_string(&anomaly)?)).await?,
+                    // The client was too slow, the oldest anomalies are still available through the REST API.
+                    Err(RecvError::Lagged(_)) => continue,
+                    Err(RecvError::Closed) => return Ok(()),
+                }
+            },
+            message = receiver.next() => {
+                match message {
+                    Some(Ok(Message::Close(_))) | None => return Ok(()),
+                    Some(Ok(_)) => {}
+                    Some(Err(e)) => anyhow::bail!("WebSocket error: {:?}", e),
+                }
+            },
+            _ = ping_interval.tick() => {
+                sender.send(Message::Ping(vec![])).await?;
+            }
+            _ = state.shutdown.cancelled() => {
+                sender.send(shutdown_close_frame()).await?;
+                return Ok(());
+            }
+        }
+    }
+}
@@ -2,6 +2,7 @@ use std::{
     collections::HashMap,
     net::SocketAddr,
     sync::{atomic::Ordering, Arc},
+    time::Instant,
 };
 
 use alloy::hex;
@@ -18,22 +19,27 @@ use futures::{
     SinkExt, StreamExt,
 };
 use serde::{Deserialize, Serialize};
-use tokio::sync::broadcast::Receiver;
-use utoipa::ToSchema;
+use tokio::sync::broadcast::{error::RecvError, Receiver};
+
+use pragma_utils::conversions::alloy::hex_str_to_u256;
 
 use crate::{
     configs::evm_config::EvmChainName,
     constants::{MAX_CLIENT_MESSAGE_SIZE, PING_INTERVAL_DURATION},
+    errors::TheorosError,
+    handlers::websocket::{
+        fanout::{FanoutBatch, FanoutSubscriptions, SubscriptionKind},
+        shutdown_close_frame,
+    },
+    storage::StoredCalldata,
     types::{
-        calldata::{AsCalldata, Calldata},
-        hyperlane::NewUpdatesAvailableEvent,
+        api::v1::RpcDataFeed,
+        calldata::{Calldata, CalldataOrdering},
+        update_view::UpdateView,
     },
     AppState,
 };
 
-#[derive(Clone)]
-pub struct DataFeedClientConfig {}
-
 #[derive(Deserialize, Debug, Clone)]
 #[serde(tag = "type")]
 enum ClientMessage {
@@ -43,11 +49,37 @@ enum ClientMessage {
     Unsubscribe { feed_ids: Vec<String> },
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
-pub struct RpcDataFeed {
-    pub feed_id: String,
-    /// The calldata binary represented as a hex string.
-    pub encoded_calldata: String,
+impl RpcDataFeed {
+    /// Builds the calldata of the latest update of the feed for the chain & stores it, so it can be
+    /// fetched back by id. Also returns the nonce of the dispatch of the update.
+    pub(crate) async fn build(
+        state: &AppState,
+        chain_name: EvmChainName,
+        feed_id: String,
+        deadline: Instant,
+    ) -> Result<(Self, u32)> {
+        let calldata = Calldata::build_from(state, chain_name, feed_id.clone(), deadline).await?;
+        let nonce = calldata.hyperlane_msg.nonce;
+        state.storage.feed_timelines().record_calldata_served(&feed_id, nonce, chain_name);
+        let stored =
+            StoredCalldata::new(feed_id.clone(), chain_name, calldata.encode_for_chain(state, &chain_name), None);
+        let calldata_id = state.storage.calldata_blobs().add(stored.clone());
+        let update = update_view(state, &feed_id, nonce);
+        let data_feed = Self {
+            feed_id,
+            calldata_id: calldata_id.to_string(),
+            encoded_calldata: hex::encode(stored.calldata),
+            update,
+        };
+        Ok((data_feed, nonce))
+    }
+}
+
+/// Returns the view of the update dispatched with `nonce` for the feed, if it is still the latest one.
+fn update_view(state: &AppState, feed_id: &str, nonce: u32) -> Option<UpdateView> {
+    let feed_id = hex_str_to_u256(feed_id).ok()?;
+    let update = state.storage.latest_update_per_feed().get(&feed_id)?;
+    (update.nonce == nonce).then(|| UpdateView::from(&update.update))
 }
 
 #[derive(Serialize, Debug, Clone)]
@@ -55,8 +87,20 @@ pub struct RpcDataFeed {
 enum ServerMessage {
     #[serde(rename = "response")]
     Response(ServerResponseMessage),
-    #[serde(rename = "data_feed_update")]
-    DataFeedUpdate { data_feeds: Vec<RpcDataFeed> },
+}
+
+/// Builds the `data_feed_update` message from the serialized [RpcDataFeed]s, without serializing them again:
+/// `{"type":"data_feed_update","data_feeds":[...]}`.
+fn data_feed_update_message(data_feeds: &[&str]) -> String {
+    let mut message = String::from(r#"{"type":"data_feed_update","data_feeds":["#);
+    for (i, data_feed) in data_feeds.iter().enumerate() {
+        if i > 0 {
+            message.push(',');
+        }
+        message.push_str(data_feed);
+    }
+    message.push_str("]}");
+    message
 }
 
 #[derive(Serialize, Debug, Clone)]
@@ -72,21 +116,33 @@ enum ServerResponseMessage {
 ///
 /// Upgrades the HTTP connection to a WebSocket connection and spawns a new
 /// subscriber to handle incoming and outgoing messages.
+#[utoipa::path(
+    get,
+    path = "/v1/ws/calldata",
+    responses(
+        (
+            status = 101,
+            description = "Upgrades to a WebSocket pushing the calldata of the subscribed feeds at every dispatch"
+        )
+    ),
+)]
 pub async fn ws_route_handler(
     ws: WebSocketUpgrade,
     AxumState(state): AxumState<AppState>,
-    ConnectInfo(_): ConnectInfo<SocketAddr>,
+    _connect_info: ConnectInfo<SocketAddr>,
 ) -> impl IntoResponse {
-    ws.max_message_size(MAX_CLIENT_MESSAGE_SIZE).on_upgrade(move |socket| websocket_handler(socket, state))
+    ws.max_message_size(MAX_CLIENT_MESSAGE_SIZE)
+        .on_upgrade(move |socket| state.ws.connections.clone().track_future(websocket_handler(socket, state)))
 }
 
 /// Handles the WebSocket connection for a single client.
 #[tracing::instrument(skip(stream, state))]
 async fn websocket_handler(stream: WebSocket, state: AppState) {
     let ws_state = state.ws.clone();
+    let _connection = state.metrics.track_ws_connection("calldata");
 
     let (sender, receiver) = stream.split();
-    let feeds_receiver = state.storage.feeds_updated_tx().subscribe();
+    let feeds_receiver = state.ws.fanout.receiver();
     let id = ws_state.subscriber_counter.fetch_add(1, Ordering::SeqCst);
     let mut subscriber = Subscriber::new(id, Arc::new(state), feeds_receiver, receiver, sender);
 
@@ -103,10 +159,11 @@ pub struct Subscriber {
     id: SubscriberId,
     closed: bool,
     state: Arc<AppState>,
-    feeds_receiver: Receiver<NewUpdatesAvailableEvent>,
+    feeds_receiver: Receiver<Arc<FanoutBatch>>,
     receiver: SplitStream<WebSocket>,
     sender: SplitSink<WebSocket, Message>,
-    data_feeds_with_config: HashMap<String, DataFeedClientConfig>,
+    /// The feeds subscribed to, all for the active chain.
+    subscriptions: FanoutSubscriptions,
     active_chain: Option<EvmChainName>,
     ping_interval: tokio::time::Interval,
     responded_to_ping: bool,
@@ -117,10 +174,11 @@ impl Subscriber {
     pub fn new(
         id: SubscriberId,
         state: Arc<AppState>,
-        feeds_receiver: Receiver<NewUpdatesAvailableEvent>,
+        feeds_receiver: Receiver<Arc<FanoutBatch>>,
         receiver: SplitStream<WebSocket>,
         sender: SplitSink<WebSocket, Message>,
     ) -> Self {
+        let subscriptions = FanoutSubscriptions::new(state.ws.clone(), SubscriptionKind::EveryDispatch);
         Self {
             id,
             closed: false,
@@ -128,7 +186,7 @@ impl Subscriber {
             feeds_receiver,
             receiver,
             sender,
-            data_feeds_with_config: HashMap::new(),
+            subscriptions,
             active_chain: None,
             ping_interval: tokio::time::interval(PING_INTERVAL_DURATION),
             responded_to_ping: true,
@@ -150,7 +208,12 @@ impl Subscriber {
         tokio::select! {
             maybe_update = self.feeds_receiver.recv() => {
                 match maybe_update {
-                    Ok(_) => self.handle_data_feeds_update().await,
+                    Ok(batch) => self.handle_data_feeds_update(&batch).await,
+                    // The next update sends the latest calldata of every feed anyway.
+                    Err(RecvError::Lagged(skipped)) => {
+                        tracing::debug!(subscriber = self.id, "Skipped {} data feeds updates.", skipped);
+                        Ok(())
+                    }
                     Err(e) => anyhow::bail!("Failed to receive update from store: {:?}", e),
                 }
             },
@@ -170,40 +233,52 @@ impl Subscriber {
                 self.sender.send(Message::Ping(vec![])).await?;
                 Ok(())
             }
+            _ = self.state.shutdown.cancelled() => {
+                self.closed = true;
+                self.sender.send(shutdown_close_frame()).await?;
+                Ok(())
+            }
         }
     }
 
     /// Handles data feed updates by sending new data to the client for all subscribed feeds.
-    async fn handle_data_feeds_update(&mut self) -> Result<()> {
-        if self.active_chain.is_none() || self.data_feeds_with_config.is_empty() {
+    /// The calldata is built & serialized once for all the subscribers by the fanout.
+    async fn handle_data_feeds_update(&mut self, batch: &FanoutBatch) -> Result<()> {
+        if self.active_chain.is_none() || self.subscriptions.is_empty() {
             return Ok(());
         }
 
-        tracing::debug!(subscriber = self.id, "Handling data feeds update.");
+        tracing::debug!(subscriber = self.id, "Handling data feeds update of dispatch #{}.", batch.nonce);
 
-        // Retrieve the list of subscribed feed IDs.
-        let feed_ids: Vec<String> = self.data_feeds_with_config.keys().cloned().collect();
+        // Retrieve the list of subscribed feed IDs, sorted so updates are always sent in the same order.
+        let mut feed_ids: Vec<String> = self.subscriptions.iter().map(|(feed_id, _)| feed_id.clone()).collect();
+        CalldataOrdering::FeedId.apply(&mut feed_ids);
 
+        let chain_name = self.active_chain.unwrap();
+        if !self.state.chain_statuses.is_served(&chain_name) {
+            tracing::debug!(subscriber = self.id, "Serving calldata for {} is disabled, skipping update.", chain_name);
+            return Ok(());
+        }
+        let frames: HashMap<&str, _> =
+            batch.frames_for(&self.subscriptions).map(|frame| (frame.feed_id.as_str(), frame)).collect();
         let mut data_feeds = Vec::with_capacity(feed_ids.len());
-        // Build calldata for each subscribed feed and collect them.
-        for feed_id in feed_ids {
-            match Calldata::build_from(self.state.as_ref(), self.active_chain.unwrap(), feed_id.clone()).await {
-                Ok(calldata) => {
-                    data_feeds.push(RpcDataFeed {
-                        feed_id: feed_id.clone(),
-                        encoded_calldata: hex::encode(calldata.as_bytes()),
-                    });
-                }
-                Err(e) => {
-                    self.send_error_to_client(format!("Error building calldata for {}: {}", feed_id, e)).await?;
-                }
+        let mut errors = Vec::new();
+        for feed_id in &feed_ids {
+            match frames.get(feed_id.as_str()).map(|frame| &frame.data_feed) {
+                Some(Ok(data_feed)) => data_feeds.push(std::str::from_utf8(data_feed)?),
+                Some(Err(e)) => errors.push(format!("Error building calldata for {}: {}", feed_id, e)),
+                None => {}
             }
         }
+        let message = (!data_feeds.is_empty()).then(|| data_feed_update_message(&data_feeds));
+        let num_data_feeds = data_feeds.len() as u64;
 
+        for error in errors {
+            self.send_error_to_client(error).await?;
+        }
         // Send a single update containing all data feeds.
-        if !data_feeds.is_empty() {
-            let update = ServerMessage::DataFeedUpdate { data_feeds };
-            let message = serde_json::to_string(&update)?;
+        if let Some(message) = message {
+            self.state.metrics.calldata_served.inc_by(num_data_feeds);
             self.sender.send(Message::Text(message)).await?;
         }
 
@@ -246,7 +321,9 @@ impl Subscriber {
         match client_message {
             ClientMessage::Subscribe { feed_ids, chain } => {
                 // Check if the chain is supported
-                if !self.state.hyperlane_validators_mapping.is_supported_chain(&chain) {
+                if !self.state.hyperlane_validators_mapping.is_supported_chain(&chain)
+                    || !self.state.chain_statuses.is_served(&chain)
+                {
                     self.send_error_to_client(format!(
                         "The chain {} is not supported. Call /v1/chains to know the chains supported by Theoros.",
                         chain,
@@ -261,16 +338,22 @@ impl Subscriber {
                         .await?;
                     return Ok(());
                 }
+                if let Some(private_id) = self.state.private_feeds.first_private(&feed_ids) {
+                    let error = TheorosError::PrivateFeed(private_id.clone());
+                    self.send_error_to_client(format!("Can't subscribe: {}", error)).await?;
+                    return Ok(());
+                }
 
-                // Subscribe to the requested feed IDs.
+                // Subscribe to the requested feed IDs, the feeds already subscribed to switching to the chain.
                 self.active_chain = Some(chain);
-                for feed_id in feed_ids {
-                    self.data_feeds_with_config.insert(feed_id, DataFeedClientConfig {});
+                let subscribed: Vec<String> = self.subscriptions.iter().map(|(feed_id, _)| feed_id.clone()).collect();
+                for feed_id in subscribed.into_iter().chain(feed_ids) {
+                    self.subscriptions.insert(feed_id, chain);
                 }
             }
             ClientMessage::Unsubscribe { feed_ids } => {
                 for feed_id in feed_ids {
-                    self.data_feeds_with_config.remove(&feed_id);
+                    self.subscriptions.remove(&feed_id);
                 }
             }
         }
@@ -288,3 +371,23 @@ impl Subscriber {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_data_feed_update_message() {
+        let data_feed = RpcDataFeed {
+            feed_id: "0x1".into(),
+            calldata_id: "0x2".into(),
+            encoded_calldata: "0x3".into(),
+            update: None,
+        };
+        let serialized = serde_json::to_string(&data_feed).unwrap();
+        let message: serde_json::Value =
+            serde_json::from_str(&data_feed_update_message(&[&serialized, &serialized])).unwrap();
+        let data_feed = serde_json::to_value(&data_feed).unwrap();
+        assert_eq!(message, serde_json::json!({"type": "data_feed_update", "data_feeds": [data_feed, data_feed]}));
+    }
+}
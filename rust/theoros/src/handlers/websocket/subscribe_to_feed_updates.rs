@@ -0,0 +1,273 @@
+use std::{net::SocketAddr, sync::atomic::Ordering, sync::Arc};
+
+use anyhow::Result;
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        ConnectInfo, State as AxumState,
+    },
+    response::IntoResponse,
+};
+use futures::{
+    stream::{SplitSink, SplitStream},
+    SinkExt, StreamExt,
+};
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast::{error::RecvError, Receiver};
+
+use crate::{
+    configs::evm_config::EvmChainName,
+    constants::{MAX_CLIENT_MESSAGE_SIZE, PING_INTERVAL_DURATION},
+    errors::TheorosError,
+    handlers::websocket::{
+        fanout::{FanoutBatch, FanoutSubscriptions, SubscriptionKind},
+        shutdown_close_frame,
+    },
+    types::api::v1::RpcDataFeed,
+    AppState,
+};
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ClientMessage {
+    /// Subscribes to the feeds, replacing the chain of the feeds already subscribed to.
+    Subscribe {
+        feed_ids: Vec<String>,
+        chain: EvmChainName,
+    },
+    Unsubscribe {
+        feed_ids: Vec<String>,
+    },
+    /// Lists the feeds subscribed to.
+    Subscriptions,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub(crate) struct Subscription {
+    feed_id: String,
+    chain: EvmChainName,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub(crate) enum ServerMessage {
+    /// Acknowledges a client message with the feeds subscribed to.
+    Subscriptions {
+        subscriptions: Vec<Subscription>,
+    },
+    Error {
+        error: String,
+    },
+    /// A dispatch updating the feed reached quorum.
+    FeedUpdate {
+        nonce: u32,
+        chain: EvmChainName,
+        #[serde(flatten)]
+        data_feed: RpcDataFeed,
+    },
+    /// The connection was too slow to keep up: the updates of `skipped` dispatches were not pushed.
+    Lagged {
+        skipped: u64,
+    },
+}
+
+impl ServerMessage {
+    /// Name of the message, as its `type`.
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            Self::Subscriptions { .. } => "subscriptions",
+            Self::Error { .. } => "error",
+            Self::FeedUpdate { .. } => "feed_update",
+            Self::Lagged { .. } => "lagged",
+        }
+    }
+}
+
+/// WebSocket route handler pushing the update & the calldata of the subscribed feeds every time one of
+/// their dispatches reaches quorum.
+#[utoipa::path(
+    get,
+    path = "/v1/ws",
+    responses(
+        (
+            status = 101,
+            description = "Upgrades to a WebSocket pushing the update & the calldata of the subscribed feeds when updated"
+        )
+    ),
+)]
+pub async fn ws_feed_updates_route_handler(
+    ws: WebSocketUpgrade,
+    AxumState(state): AxumState<AppState>,
+    _connect_info: ConnectInfo<SocketAddr>,
+) -> impl IntoResponse {
+    let connections = state.ws.connections.clone();
+    ws.max_message_size(MAX_CLIENT_MESSAGE_SIZE).on_upgrade(move |socket| {
+        connections.track_future(async move {
+            let _connection = state.metrics.track_ws_connection("feed_updates");
+            let id = state.ws.subscriber_counter.fetch_add(1, Ordering::SeqCst);
+            if let Err(e) = FeedUpdatesSubscriber::new(id, state, socket).run().await {
+                tracing::debug!("🕸️ [Websocket] Feed updates subscriber {} disconnected: {:?}", id, e);
+            }
+        })
+    })
+}
+
+/// A client connected to the feed updates WebSocket & the feeds it subscribed to.
+struct FeedUpdatesSubscriber {
+    id: usize,
+    state: AppState,
+    updates_receiver: Receiver<Arc<FanoutBatch>>,
+    receiver: SplitStream<WebSocket>,
+    sender: SplitSink<WebSocket, Message>,
+    subscriptions: FanoutSubscriptions,
+    responded_to_ping: bool,
+}
+
+impl FeedUpdatesSubscriber {
+    fn new(id: usize, state: AppState, stream: WebSocket) -> Self {
+        let (sender, receiver) = stream.split();
+        let updates_receiver = state.ws.fanout.receiver();
+        let subscriptions = FanoutSubscriptions::new(state.ws.clone(), SubscriptionKind::FeedUpdates);
+        Self { id, state, updates_receiver, receiver, sender, subscriptions, responded_to_ping: true }
+    }
+
+    /// Handles the messages of the client & pushes the updates until it disconnects.
+    async fn run(mut self) -> Result<()> {
+        let mut ping_interval = tokio::time::interval(PING_INTERVAL_DURATION);
+        loop {
+            tokio::select! {
+                event = self.updates_receiver.recv() => {
+                    match event {
+                        Ok(batch) => self.push_updates(&batch).await?,
+                        Err(RecvError::Lagged(skipped)) => self.send(&ServerMessage::Lagged { skipped }).await?,
+                        Err(RecvError::Closed) => return Ok(()),
+                    }
+                },
+                message = self.receiver.next() => {
+                    match message {
+                        Some(Ok(Message::Close(_))) | None => return Ok(()),
+                        Some(Ok(message)) => self.handle_client_message(message).await?,
+                        Some(Err(e)) => anyhow::bail!("WebSocket error: {:?}", e),
+                    }
+                },
+                _ = ping_interval.tick() => {
+                    anyhow::ensure!(self.responded_to_ping, "Subscriber did not respond to ping. Closing connection.");
+                    self.responded_to_ping = false;
+                    self.sender.send(Message::Ping(vec![])).await?;
+                }
+                _ = self.state.shutdown.cancelled() => {
+                    self.sender.send(shutdown_close_frame()).await?;
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    /// Pushes the update & the calldata of the subscribed feeds updated by the dispatch.
+    async fn push_updates(&mut self, batch: &FanoutBatch) -> Result<()> {
+        for frame in batch.frames_for(&self.subscriptions) {
+            if frame.data_feed.is_ok() {
+                self.state.metrics.calldata_served.inc();
+            }
+            self.sender.send(Message::Text(frame.message_text().to_owned())).await?;
+        }
+        Ok(())
+    }
+
+    async fn handle_client_message(&mut self, message: Message) -> Result<()> {
+        let text = match message {
+            Message::Text(text) => text,
+            Message::Binary(data) => String::from_utf8(data)?,
+            Message::Pong(_) => {
+                self.responded_to_ping = true;
+                return Ok(());
+            }
+            // Axum handles PONG responses automatically.
+            Message::Ping(_) | Message::Close(_) => return Ok(()),
+        };
+        let response = match serde_json::from_str(&text) {
+            Ok(message) => self.process_client_message(message),
+            Err(e) => Err(e.to_string()),
+        };
+        let message = match response {
+            Ok(()) => self.subscriptions_message(),
+            Err(error) => ServerMessage::Error { error },
+        };
+        self.send(&message).await
+    }
+
+    /// Updates the subscriptions of the client, or returns why they can't be updated.
+    fn process_client_message(&mut self, message: ClientMessage) -> Result<(), String> {
+        match message {
+            ClientMessage::Subscribe { feed_ids, chain } => {
+                if !self.state.hyperlane_validators_mapping.is_supported_chain(&chain)
+                    || !self.state.chain_statuses.is_served(&chain)
+                {
+                    return Err(format!(
+                        "The chain {} is not supported. Call /v1/chains to know the chains supported by Theoros.",
+                        chain
+                    ));
+                }
+                if let Some(missing_id) = self.state.storage.feed_ids().contains_vec(&feed_ids) {
+                    return Err(format!("Can't subscribe: feed ID not supported {}", missing_id));
+                }
+                if let Some(retired_id) = self.state.feed_lifecycles.first_retired(&feed_ids) {
+                    return Err(format!("Can't subscribe: feed ID {} is retired", retired_id));
+                }
+                // The calldata pushed is built once for all the subscribers, so it can't be encrypted for each one.
+                if let Some(private_id) = self.state.private_feeds.first_private(&feed_ids) {
+                    return Err(format!("Can't subscribe: {}", TheorosError::PrivateFeed(private_id.clone())));
+                }
+                let new_feeds = feed_ids.iter().filter(|feed_id| !self.subscriptions.contains(feed_id)).count();
+                if self.subscriptions.len() + new_feeds > self.state.max_batch_feeds {
+                    return Err(format!(
+                        "Can't subscribe: at most {} feeds can be subscribed to at once",
+                        self.state.max_batch_feeds
+                    ));
+                }
+                for feed_id in feed_ids {
+                    self.subscriptions.insert(feed_id, chain);
+                }
+            }
+            ClientMessage::Unsubscribe { feed_ids } => {
+                for feed_id in feed_ids {
+                    self.subscriptions.remove(&feed_id);
+                }
+            }
+            ClientMessage::Subscriptions => {}
+        }
+        tracing::debug!(subscriber = self.id, "🕸️ [Websocket] {} feeds subscribed to", self.subscriptions.len());
+        Ok(())
+    }
+
+    fn subscriptions_message(&self) -> ServerMessage {
+        let subscriptions = self
+            .subscriptions
+            .iter()
+            .map(|(feed_id, chain)| Subscription { feed_id: feed_id.clone(), chain: *chain })
+            .collect();
+        ServerMessage::Subscriptions { subscriptions }
+    }
+
+    async fn send(&mut self, message: &ServerMessage) -> Result<()> {
+        self.sender.send(Message::Text(serde_json::to_string(message)?)).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_client_messages_format() {
+        let subscribe: ClientMessage =
+            serde_json::from_str(r#"{"type":"subscribe","feed_ids":["0x1"],"chain":"sepolia"}"#).unwrap();
+        assert!(matches!(subscribe, ClientMessage::Subscribe { chain: EvmChainName::Sepolia, .. }));
+        let subscriptions: ClientMessage = serde_json::from_str(r#"{"type":"subscriptions"}"#).unwrap();
+        assert!(matches!(subscriptions, ClientMessage::Subscriptions));
+
+        let lagged = serde_json::to_value(ServerMessage::Lagged { skipped: 3 }).unwrap();
+        assert_eq!(lagged, serde_json::json!({"type": "lagged", "skipped": 3}));
+    }
+}
@@ -0,0 +1,425 @@
+pub mod cli;
+pub mod configs;
+pub mod constants;
+pub mod diagnostics;
+pub mod errors;
+pub mod extractors;
+pub mod handlers;
+pub mod replay;
+pub mod rpc;
+pub mod selftest;
+pub mod services;
+pub mod shutdown;
+pub mod storage;
+pub mod types;
+
+pub use types::state::AppState;
+
+#[cfg(all(feature = "jemalloc", feature = "mimalloc"))]
+compile_error!("The `jemalloc` and `mimalloc` features are mutually exclusive");
+
+// Set by the library, so every binary (`theoros`, `theoros-api` & `theoros-indexer`) uses the same allocator.
+#[cfg(feature = "jemalloc")]
+#[global_allocator]
+static GLOBAL: tikv_jemallocator::Jemalloc = tikv_jemallocator::Jemalloc;
+
+// Heap profiling is enabled but inactive by default: allocations are only sampled once it is
+// activated through the admin API.
+#[cfg(feature = "jemalloc")]
+#[allow(non_upper_case_globals)]
+#[export_name = "_rjem_malloc_conf"]
+#[used]
+pub static malloc_conf: &[u8] = b"prof:true,prof_active:false,lg_prof_sample:19\0";
+
+#[cfg(feature = "mimalloc")]
+#[global_allocator]
+static GLOBAL: mimalloc::MiMalloc = mimalloc::MiMalloc;
+
+use std::num::NonZeroUsize;
+use std::sync::Arc;
+
+use alloy::primitives::keccak256;
+use anyhow::{bail, Context, Result};
+use prometheus::Registry;
+
+use pragma_utils::{
+    redaction::redactor,
+    services::{Service, ServiceGroup},
+    tracing::{init_tracing, LogLevel, TracingSampler},
+};
+
+use cli::TheorosCli;
+use configs::{emitters::Emitters, runtime_settings::RuntimeSettings};
+use rpc::{
+    evm::{
+        pragma::PragmaContractsMapping, validator_announce::discover_announced_locations, HyperlaneValidatorsMapping,
+    },
+    starknet::StarknetRpc,
+};
+use services::indexer::cursor_store::CursorStoreConfig;
+use services::{
+    api::{cors::CorsConfig, priority_lanes::PriorityLanes, rate_limit::RateLimiter},
+    metrics::TheorosMetrics,
+    ApiService, BackendSyncService, ConfigWatcherService, HyperlaneService, IndexerService, MetricsService,
+    RelayerService, RetentionService, SyntheticFeedService, ValidatorsRefreshService,
+};
+use storage::{StorageBackendConfig, TheorosStorage, ValidatorsFetchersStorage};
+use types::{
+    chain_statuses::ChainStatuses,
+    feed_lifecycles::FeedLifecycles,
+    hyperlane::{caching::CheckpointCache, retrying::RetryPolicy, sharded::FetchSharding},
+    post_processors::PostProcessorsMapping,
+    private_feeds::PrivateFeeds,
+    retention::RetentionPolicy,
+    staleness::StalenessPolicy,
+};
+
+/// Components of Theoros run by a process.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Components {
+    /// The indexing & the API, sharing the in-memory storages: `theoros`.
+    All,
+    /// The API, following the state persisted by the indexer into the storage backend: `theoros-api`.
+    Api,
+    /// The indexing & the collection of the signatures, persisted into the storage backend: `theoros-indexer`.
+    Indexer,
+}
+
+impl Components {
+    fn runs_api(self) -> bool {
+        matches!(self, Self::All | Self::Api)
+    }
+
+    fn runs_indexer(self) -> bool {
+        matches!(self, Self::All | Self::Indexer)
+    }
+}
+
+/// Runs the components until they stop, e.g. on a shutdown signal, then closes the storage.
+pub async fn run(config: &TheorosCli, components: Components) -> Result<()> {
+    // The other backends can't be shared: RocksDB is locked by the process opening it.
+    if components != Components::All && !matches!(config.storage_backend, StorageBackendConfig::Postgres(_)) {
+        bail!(
+            "The API & the indexer run by distinct processes share their state through the storage backend, which \
+             must be Postgres: set `--storage-backend postgres://...`"
+        );
+    }
+
+    let tracing_sampler = TracingSampler::new(config.tracing_sampling.clone());
+    // The level of the runtime settings file, if any, is applied once the state is built.
+    let log_level = LogLevel::new(config.log_level);
+    init_tracing(&config.app_name, log_level.clone(), tracing_sampler.clone())?;
+
+    let metrics_service = MetricsService::new(config.prometheus_external, config.metrics_port)?;
+    let state = build_state(config, metrics_service.registry(), tracing_sampler, log_level).await?;
+    let metrics_service = metrics_service
+        .with_persisted_metrics(&state, config.metrics_instance.clone())
+        .with_shutdown(state.shutdown.clone());
+    diagnostics::dump_on_sigquit(state.clone())?;
+    shutdown::cancel_on_signal(state.shutdown.clone())?;
+
+    let mut services = ServiceGroup::default().with(metrics_service).with(validators_refresh_service(&state));
+    if components.runs_indexer() {
+        services.push(indexer_service(&state, config).await?);
+        services.push(hyperlane_service(&state, config));
+        if let Some(synthetic_feed_service) = synthetic_feed_service(&state, config)? {
+            services.push(synthetic_feed_service);
+        }
+        // Relays the updates as they reach quorum, which only the indexing process is notified of right away.
+        if let Some(relayer_service) = relayer_service(&state, config)? {
+            services.push(relayer_service);
+        }
+    }
+    if components.runs_api() {
+        services.push(api_service(&state, config));
+        #[cfg(feature = "grpc")]
+        if let Some(grpc_service) = grpc_service(&state, config) {
+            services.push(grpc_service);
+        }
+    }
+    if components != Components::All {
+        services.push(backend_sync_service(&state, config, components));
+    }
+    if let Some(config_watcher_service) = config_watcher_service(&state, config) {
+        services.push(config_watcher_service);
+    }
+    // Also run by the API alone, whose in-memory storages follow the backend but aren't pruned with it, to prune them.
+    if let Some(retention_service) = retention_service(&state, config, components) {
+        services.push(retention_service);
+    }
+
+    let result = tokio::select! {
+        result = services.start_and_drive_to_end() => result,
+        _ = shutdown::deadline(&state.shutdown, config.shutdown_timeout) => {
+            tracing::warn!("🛑 Services still running {:?} after the shutdown, exiting anyway", config.shutdown_timeout);
+            Ok(())
+        }
+    };
+    // Stops the services left, if one of them failed, before closing the storage they write to.
+    state.shutdown.cancel();
+    if let Some(instance) = &config.metrics_instance {
+        if let Err(e) = state.metrics.persist(&state.storage, instance).await {
+            tracing::error!("😱 Failed to persist metrics: {:?}", e);
+        }
+    }
+    state.storage.close().await;
+    tracing::info!("🛑 Theoros stopped");
+
+    // Ensure that the tracing provider is shutdown correctly
+    opentelemetry::global::shutdown_tracer_provider();
+
+    result
+}
+
+/// Registers the secrets of the configuration, so they are redacted from the logs & the API errors.
+pub fn register_secrets(config: &TheorosCli) {
+    let redactor = redactor();
+    for rpc_url in std::iter::once(&config.madara_rpc_url).chain(&config.madara_fallback_rpc_urls) {
+        redactor.add_url_secrets(rpc_url.as_str());
+    }
+    if let Some(CursorStoreConfig::Postgres(url)) = &config.indexer_cursor_store {
+        redactor.add_url_secrets(url);
+    }
+    if let StorageBackendConfig::Postgres(url) = &config.storage_backend {
+        redactor.add_url_secrets(url);
+    }
+    for rpc_url in config.evm_config.chains().values().flat_map(|chain_config| chain_config.rpc_urls()) {
+        redactor.add_url_secrets(rpc_url);
+    }
+    for relayer in config.evm_config.chains().values().filter_map(|chain_config| chain_config.relayer.as_ref()) {
+        redactor.add_secret(relayer.private_key.trim().to_owned());
+    }
+    let api_keys = config
+        .apibara_api_key
+        .iter()
+        .chain(config.admin_api_key.iter())
+        .chain(&config.premium_api_keys)
+        .chain(&config.rate_limited_api_keys);
+    for api_key in api_keys {
+        redactor.add_secret(api_key.clone());
+    }
+    for env_var in &config.redacted_env_vars {
+        if let Ok(value) = std::env::var(env_var) {
+            redactor.add_secret(value);
+        }
+    }
+}
+
+/// Hash of the configuration, with its secrets redacted.
+pub fn config_fingerprint(config: &TheorosCli) -> String {
+    let config = format!("{config:?}");
+    keccak256(redactor().redact(&config).as_bytes()).to_string()
+}
+
+/// Builds the state shared by all the Theoros components.
+pub async fn build_state(
+    config: &TheorosCli,
+    metrics_registry: Registry,
+    tracing_sampler: TracingSampler,
+    log_level: LogLevel,
+) -> Result<AppState> {
+    let starknet_rpc =
+        StarknetRpc::new(config.madara_rpc_url.clone()).with_fallbacks(config.madara_fallback_rpc_urls.clone());
+    let hyperlane_validators_mapping = HyperlaneValidatorsMapping::from_config(&config.evm_config).await?;
+
+    let metrics = Arc::new(TheorosMetrics::register(&metrics_registry)?);
+
+    let theoros_storage = TheorosStorage::from_rpc_state(
+        &starknet_rpc,
+        &config.pragma_feeds_registry_address,
+        &config.hyperlane_validator_announce_address,
+        validators_fetchers(config, &metrics),
+    )
+    .await?
+    .with_backend(config.storage_backend.build(config.storage_max_connections).await?)
+    .with_emitters(Emitters::new(config.pragma_dispatchers.clone())?);
+    theoros_storage.restore().await?;
+    if let Some(instance) = &config.metrics_instance {
+        metrics.restore(&theoros_storage, instance).await.context("Restoring the metrics counters")?;
+    }
+    let discovered = discover_announced_locations(
+        &config.evm_config,
+        &hyperlane_validators_mapping,
+        theoros_storage.validators_fetchers(),
+    )
+    .await;
+    if discovered > 0 {
+        tracing::info!("🔎 Discovered {} validators from the ValidatorAnnounce contracts of the chains", discovered);
+    }
+
+    let state = AppState::builder()
+        .with_starknet_rpc(Arc::new(starknet_rpc))
+        .with_hyperlane_validators_mapping(hyperlane_validators_mapping)
+        .with_chain_statuses(ChainStatuses::from_config(&config.evm_config))
+        .with_feed_lifecycles(
+            config.feed_lifecycle_config.as_ref().map(FeedLifecycles::from_config).unwrap_or_default(),
+        )
+        .with_private_feeds(config.feed_lifecycle_config.as_ref().map(PrivateFeeds::from_config).unwrap_or_default())
+        .with_staleness_policy(StalenessPolicy::new(config.feed_max_age, config.feed_max_ages.clone()))
+        .with_post_processors(PostProcessorsMapping::from_config(&config.evm_config))
+        .with_pragma_contracts(PragmaContractsMapping::from_config(&config.evm_config)?)
+        .with_evm_config(config.evm_config.config.clone())
+        .with_filter_dispatch_destinations(config.filter_dispatch_destinations)
+        .with_storage(theoros_storage)
+        .with_metrics_registry(metrics_registry)
+        .with_metrics(metrics.clone())
+        .with_default_chain(config.default_chain)
+        .with_tracing_sampler(tracing_sampler)
+        .with_log_level(log_level)
+        .with_runtime_settings(RuntimeSettings::load(config)?)
+        .with_admin_api_key(config.admin_api_key.clone())
+        .with_calldata_deadline(config.calldata_deadline)
+        .with_validator_fetch_timeout(config.validator_fetch_timeout)
+        .with_max_request_body_size(config.max_request_body_size)
+        .with_max_batch_feeds(config.max_batch_feeds)
+        .with_rate_limiter(rate_limiter(config, &metrics))
+        .with_config_fingerprint(config_fingerprint(config))
+        .build()?;
+    let num_settings = state.sync_settings().await.context("Restoring the persisted settings")?;
+    if num_settings > 0 {
+        tracing::info!("💾 Restored {} settings changed through the admin API", num_settings);
+    }
+    Ok(state)
+}
+
+/// Fetchers of the checkpoints of the validators, retrying the transient failures & caching the fetched checkpoints.
+fn validators_fetchers(config: &TheorosCli, metrics: &Arc<TheorosMetrics>) -> ValidatorsFetchersStorage {
+    let checkpoint_cache = NonZeroUsize::new(config.checkpoint_cache_capacity)
+        .map(|capacity| Arc::new(CheckpointCache::new(capacity).with_metrics(metrics)));
+    ValidatorsFetchersStorage::default().with_retry_policy(retry_policy(config)).with_checkpoint_cache(checkpoint_cache)
+}
+
+/// Retries of the checkpoint fetches failing with a transient error.
+pub fn retry_policy(config: &TheorosCli) -> RetryPolicy {
+    RetryPolicy {
+        max_attempts: config.checkpoint_fetch_max_attempts,
+        base_delay: config.checkpoint_fetch_retry_delay,
+        jitter: config.checkpoint_fetch_retry_jitter,
+    }
+}
+
+/// Rate limiter of the expensive API endpoints, recognizing the premium & rate limited API keys.
+fn rate_limiter(config: &TheorosCli, metrics: &Arc<TheorosMetrics>) -> RateLimiter {
+    RateLimiter::new(metrics.clone())
+        .with_api_keys(config.premium_api_keys.iter().chain(&config.rate_limited_api_keys).cloned())
+}
+
+/// Indexes the Pragma chain events into the storage.
+pub async fn indexer_service(state: &AppState, config: &TheorosCli) -> Result<IndexerService> {
+    let starting_block = match config.backfill_from_block {
+        Some(from_block) => from_block,
+        None => IndexerService::starting_block(state.starknet_rpc.as_ref(), config.indexer_start).await?,
+    };
+    let mut indexer = IndexerService::new(
+        state.clone(),
+        config.apibara_dna_uri.clone(),
+        config.hyperlane_mailbox_address,
+        config.hyperlane_validator_announce_address,
+        config.pragma_feeds_registry_address,
+        starting_block,
+    )?
+    .with_fallback_uris(config.apibara_dna_fallback_uris.clone())
+    .with_liveness_timeout(config.indexer_liveness_timeout);
+    if config.backfill_from_block.is_some() {
+        indexer = indexer.with_backfill(state.starknet_rpc.block_number().await?);
+    }
+    match &config.indexer_cursor_store {
+        // Replicas index the same events but at their own pace, each one keeps its cursor.
+        Some(cursor_store) => {
+            let indexer_name = config.instance_id.as_deref().unwrap_or(&config.app_name);
+            indexer.with_cursor_store(cursor_store.build(indexer_name).await?).await
+        }
+        None => Ok(indexer),
+    }
+}
+
+/// Collects the validators signatures of the indexed messages.
+pub fn hyperlane_service(state: &AppState, config: &TheorosCli) -> HyperlaneService {
+    let sharding = match (&config.instance_id, config.peer_instances.is_empty()) {
+        (Some(instance_id), false) => Some(FetchSharding::new(instance_id.clone(), config.peer_instances.clone())),
+        _ => None,
+    };
+    HyperlaneService::new(state.storage.clone(), state.metrics.clone())
+        .with_fetch_timeout(state.validator_fetch_timeout)
+        .with_sharding(sharding)
+        .with_quorum_tracker(state.quorum_tracker.clone())
+        .with_runtime_settings(state.runtime_settings.subscribe())
+        .with_poll_interval(config.checkpoint_poll_interval)
+        .with_shutdown(state.shutdown.clone())
+}
+
+/// Injects the synthetic feed through the pipeline, if enabled.
+pub fn synthetic_feed_service(state: &AppState, config: &TheorosCli) -> Result<Option<SyntheticFeedService>> {
+    if !config.synthetic_feed {
+        return Ok(None);
+    }
+    Ok(Some(SyntheticFeedService::new(state)?))
+}
+
+/// Sends the updates of the subscribed feeds to the chains configuring a relayer, if enabled.
+pub fn relayer_service(state: &AppState, config: &TheorosCli) -> Result<Option<RelayerService>> {
+    if !config.relayer {
+        return Ok(None);
+    }
+    Ok(Some(RelayerService::new(state, &config.evm_config, config.relayer_receipt_timeout)?))
+}
+
+/// Refreshes the validators of the chains from their ISM, while the refresh interval isn't zero.
+pub fn validators_refresh_service(state: &AppState) -> ValidatorsRefreshService {
+    ValidatorsRefreshService::new(state.clone())
+}
+
+/// Follows the settings changed through the admin API of the other processes sharing the storage backend &, when
+/// the API runs in its own process, the state persisted by the indexer.
+pub fn backend_sync_service(state: &AppState, config: &TheorosCli, components: Components) -> BackendSyncService {
+    BackendSyncService::new(state.clone(), config.backend_sync_interval).with_dispatches(components == Components::Api)
+}
+
+/// Prunes what the retention policy doesn't keep anymore, if any limit is set. The API running in its own process
+/// only prunes its in-memory storages: the backend is pruned by the indexer, whose state is up to date.
+pub fn retention_service(state: &AppState, config: &TheorosCli, components: Components) -> Option<RetentionService> {
+    let policy = RetentionPolicy { keep_updates: config.retention_updates, keep_for: config.retention_period };
+    if !policy.is_enabled() {
+        return None;
+    }
+    Some(
+        RetentionService::new(state.clone(), policy, config.compaction_interval)
+            .with_backend_pruning(components != Components::Api),
+    )
+}
+
+/// Applies the changes of the config files without a restart, if enabled.
+pub fn config_watcher_service(state: &AppState, config: &TheorosCli) -> Option<ConfigWatcherService> {
+    if !config.watch_config {
+        return None;
+    }
+    let service =
+        ConfigWatcherService::new(state.clone(), config.evm_config.path.clone(), RuntimeSettings::from_cli(config))
+            .with_runtime_settings_path(config.runtime_settings_path.clone());
+    Some(service)
+}
+
+/// Serves the gRPC API, if built with the `grpc` feature & given a port.
+#[cfg(feature = "grpc")]
+pub fn grpc_service(state: &AppState, config: &TheorosCli) -> Option<services::GrpcService> {
+    let port = config.grpc_port?;
+    Some(services::GrpcService::new(state.clone(), config.server_host, port))
+}
+
+/// Serves the REST & WebSocket API.
+pub fn api_service(state: &AppState, config: &TheorosCli) -> ApiService {
+    ApiService::new(state.clone(), config.server_host, config.server_port)
+        .with_proxy_protocol(config.proxy_protocol)
+        .with_trusted_proxies(config.trusted_proxies.clone())
+        .with_priority_lanes(config.max_concurrent_requests.map(|max_concurrent| {
+            PriorityLanes::new(max_concurrent, state.metrics.clone())
+                .with_premium_api_keys(config.premium_api_keys.clone())
+                .with_max_queued(config.max_queued_requests)
+                .with_queue_timeout(config.request_queue_timeout)
+        }))
+        .with_cors(CorsConfig {
+            allowed_origins: config.cors_allowed_origins.clone(),
+            allowed_methods: config.cors_allowed_methods.clone(),
+            max_age: config.cors_max_age,
+        })
+}
@@ -0,0 +1,385 @@
+use std::{collections::BTreeSet, path::Path, sync::Arc};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use starknet::core::{types::Felt, utils::get_selector_from_name};
+
+use pragma_utils::conversions::alloy::hex_str_to_u256;
+
+use crate::{
+    cli::TheorosCli,
+    configs::emitters::Emitters,
+    rpc::starknet::StarknetRpc,
+    services::{hyperlane::store_updates, indexer::store_dispatch},
+    storage::{FeedIdsStorage, RawDispatchEvent, TheorosStorage, ValidatorsFetchersStorage},
+    types::{
+        hyperlane::{DispatchEvent, FromStarknetEventData},
+        update_view::UpdateView,
+    },
+    AppState,
+};
+
+/// A Dispatch event of a fixture, with where it was emitted when known.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FixtureEvent {
+    pub keys: Vec<Felt>,
+    pub data: Vec<Felt>,
+    pub block_number: Option<u64>,
+    pub transaction_hash: Option<Felt>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReplayStatus {
+    /// The event was decoded & its updates stored.
+    Stored,
+    /// The event was decoded but sent by an emitter which isn't indexed.
+    Skipped,
+    /// The event, or some of its updates, failed to decode.
+    Failed,
+}
+
+/// Outcome of the replay of a single event.
+#[derive(Debug, Clone, Serialize)]
+pub struct ReplayedEvent {
+    /// Position of the event in the fixture.
+    pub index: usize,
+    pub nonce: Option<u32>,
+    pub status: ReplayStatus,
+    /// Ids of the feeds updated by the event.
+    pub feed_ids: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+/// Latest update of a feed once the fixture is replayed, for each emitter which dispatched one.
+#[derive(Debug, Clone, Serialize)]
+pub struct ReplayedFeed {
+    pub feed_id: String,
+    pub nonce: u32,
+    pub emitter_address: String,
+    /// Name of the Pragma dispatcher of the update, when the emitters are configured.
+    pub emitter: Option<String>,
+    pub update: UpdateView,
+}
+
+/// Machine-readable report of `theoros replay`.
+#[derive(Debug, Clone, Serialize)]
+pub struct ReplayReport {
+    /// Whether every event & all their updates were decoded.
+    pub passed: bool,
+    pub events: Vec<ReplayedEvent>,
+    pub feeds: Vec<ReplayedFeed>,
+}
+
+impl ReplayReport {
+    /// Human-readable summary of the report, one line per event & per feed.
+    pub fn summary(&self) -> String {
+        let mut lines: Vec<String> = self
+            .events
+            .iter()
+            .map(|event| {
+                let status = match event.status {
+                    ReplayStatus::Stored => "✅ STORED",
+                    ReplayStatus::Skipped => "⏭️ SKIPPED",
+                    ReplayStatus::Failed => "❌ FAILED",
+                };
+                let nonce = event.nonce.map(|nonce| format!(" nonce #{nonce}")).unwrap_or_default();
+                let updated = format!("{} feeds updated", event.feed_ids.len());
+                match &event.detail {
+                    Some(detail) => format!("{status} event {}{nonce} ({updated}): {detail}", event.index),
+                    None => format!("{status} event {}{nonce} ({updated})", event.index),
+                }
+            })
+            .collect();
+        for feed in self.feeds.iter() {
+            let emitter = feed.emitter.clone().unwrap_or_else(|| feed.emitter_address.clone());
+            let update = serde_json::to_string(&feed.update).unwrap_or_default();
+            lines.push(format!("📈 {} from {emitter} at nonce #{}: {update}", feed.feed_id, feed.nonce));
+        }
+        lines.push(if self.passed { "Replay passed".to_owned() } else { "Replay failed".to_owned() });
+        lines.join("\n")
+    }
+}
+
+/// Replays the Dispatch events of the fixture into an in-memory storage, indexing only the configured
+/// emitters. Nothing is fetched: the updates are stored as if the validators had signed them.
+pub async fn run(config: &TheorosCli, fixture: &Path) -> Result<ReplayReport> {
+    let contents =
+        tokio::fs::read_to_string(fixture).await.with_context(|| format!("Reading {}", fixture.display()))?;
+    let events = parse_fixture(&contents).with_context(|| format!("Parsing {}", fixture.display()))?;
+
+    let storage = TheorosStorage::new(FeedIdsStorage::default(), ValidatorsFetchersStorage::default())
+        .with_emitters(Emitters::new(config.pragma_dispatchers.clone())?);
+    // Never called: the state only needs a Starknet RPC to be built.
+    let starknet_rpc = StarknetRpc::new(config.madara_rpc_url.clone());
+    let state = AppState::builder().with_starknet_rpc(Arc::new(starknet_rpc)).with_storage(storage).build()?;
+    Ok(replay(&state, events).await)
+}
+
+/// Runs each event through the decoding & the storage of the indexer, then reads the latest update of the
+/// feeds they updated.
+pub async fn replay(state: &AppState, events: Vec<FixtureEvent>) -> ReplayReport {
+    let mut replayed = Vec::with_capacity(events.len());
+    for (index, event) in events.into_iter().enumerate() {
+        replayed.push(replay_event(state, index, event).await);
+    }
+
+    let feed_ids: BTreeSet<&String> = replayed.iter().flat_map(|event| event.feed_ids.iter()).collect();
+    let mut feeds = Vec::new();
+    for feed_id in feed_ids {
+        let Ok(feed_id_u256) = hex_str_to_u256(feed_id) else {
+            continue;
+        };
+        let mut emitters = state.storage.latest_update_per_emitter().emitters_of(&feed_id_u256);
+        emitters.sort();
+        for emitter in emitters {
+            let Some(update) = state.storage.latest_update_per_emitter().get(&emitter, &feed_id_u256) else {
+                continue;
+            };
+            feeds.push(ReplayedFeed {
+                feed_id: feed_id.clone(),
+                nonce: update.nonce,
+                emitter_address: format!("{:#x}", emitter),
+                emitter: state.storage.emitters().get(&emitter).map(|emitter| emitter.name.clone()),
+                update: UpdateView::from(&update.update),
+            });
+        }
+    }
+
+    let passed = replayed.iter().all(|event| event.status != ReplayStatus::Failed);
+    ReplayReport { passed, events: replayed, feeds }
+}
+
+async fn replay_event(state: &AppState, index: usize, event: FixtureEvent) -> ReplayedEvent {
+    let dispatch_event = match DispatchEvent::from_starknet_event_data(event.data.clone()) {
+        Ok(dispatch_event) => dispatch_event,
+        Err(e) => {
+            return ReplayedEvent {
+                index,
+                nonce: None,
+                status: ReplayStatus::Failed,
+                feed_ids: vec![],
+                detail: Some(format!("{e:#}")),
+            }
+        }
+    };
+    let nonce = dispatch_event.message.header.nonce;
+    let emitter_address = dispatch_event.emitter_address();
+    if !state.storage.emitters().is_indexed(&emitter_address) {
+        return ReplayedEvent {
+            index,
+            nonce: Some(nonce),
+            status: ReplayStatus::Skipped,
+            feed_ids: vec![],
+            detail: Some(format!("Sent by the unknown emitter {:#x}", emitter_address)),
+        };
+    }
+
+    let raw_event = RawDispatchEvent::new(nonce, event.block_number, event.transaction_hash, &event.keys, &event.data);
+    store_dispatch(state, &dispatch_event, raw_event).await;
+    let stored = store_updates(&state.storage, &dispatch_event).await;
+    state.storage.remove_pending_dispatch(nonce).await;
+
+    let mut errors: Vec<String> = dispatch_event
+        .message
+        .body
+        .parse_failures
+        .iter()
+        .map(|failure| format!("update #{} at offset {}: {}", failure.update_index, failure.offset, failure.error))
+        .collect();
+    let feed_ids = match stored {
+        Ok(feed_ids) => feed_ids,
+        Err(e) => {
+            errors.push(format!("{e:#}"));
+            vec![]
+        }
+    };
+    ReplayedEvent {
+        index,
+        nonce: Some(nonce),
+        status: if errors.is_empty() { ReplayStatus::Stored } else { ReplayStatus::Failed },
+        feed_ids,
+        detail: (!errors.is_empty()).then(|| errors.join(", ")),
+    }
+}
+
+/// Reads the Dispatch events of a fixture, in any of the formats:
+/// * the data of a single event, as an array of hex felts,
+/// * an event or an array of events, with their `data` & optionally their `keys`, `block_number` &
+///   `transaction_hash`, like the bundled `fixtures/dispatch_event.json`,
+/// * a block or an array of blocks exported from the Apibara Starknet stream, whose field elements are hex
+///   strings.
+///
+/// The events whose keys are known but aren't the ones of a Dispatch event are left out.
+pub fn parse_fixture(contents: &str) -> Result<Vec<FixtureEvent>> {
+    let events = match serde_json::from_str(contents)? {
+        Fixture::Felts(data) => vec![JsonEvent { data, ..Default::default() }],
+        Fixture::Event(event) => vec![event],
+        Fixture::Events(events) => events,
+        Fixture::Block(block) => block.into_events(),
+        Fixture::Blocks(blocks) => blocks.into_iter().flat_map(ApibaraBlock::into_events).collect(),
+    };
+
+    let dispatch_selector = get_selector_from_name("Dispatch")?;
+    let mut parsed = Vec::with_capacity(events.len());
+    for event in events {
+        let event = event.parse()?;
+        if event.keys.first().is_some_and(|selector| *selector != dispatch_selector) {
+            continue;
+        }
+        parsed.push(event);
+    }
+    Ok(parsed)
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Fixture {
+    Felts(Vec<String>),
+    Event(JsonEvent),
+    Events(Vec<JsonEvent>),
+    Block(ApibaraBlock),
+    Blocks(Vec<ApibaraBlock>),
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct JsonEvent {
+    #[serde(default)]
+    keys: Vec<String>,
+    data: Vec<String>,
+    #[serde(default, alias = "blockNumber")]
+    block_number: Option<u64>,
+    #[serde(default, alias = "transactionHash")]
+    transaction_hash: Option<String>,
+}
+
+impl JsonEvent {
+    fn parse(self) -> Result<FixtureEvent> {
+        let felts = |felts: Vec<String>| -> Result<Vec<Felt>> {
+            felts.iter().map(|felt| Felt::from_hex(felt).with_context(|| format!("Invalid felt: {felt}"))).collect()
+        };
+        Ok(FixtureEvent {
+            keys: felts(self.keys)?,
+            data: felts(self.data)?,
+            block_number: self.block_number,
+            transaction_hash: self.transaction_hash.as_deref().map(Felt::from_hex).transpose()?,
+        })
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ApibaraBlock {
+    #[serde(default)]
+    header: Option<ApibaraBlockHeader>,
+    events: Vec<ApibaraEventWithTransaction>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ApibaraBlockHeader {
+    block_number: u64,
+}
+
+#[derive(Deserialize)]
+struct ApibaraEventWithTransaction {
+    event: Option<ApibaraEvent>,
+    #[serde(default)]
+    transaction: Option<ApibaraTransaction>,
+}
+
+#[derive(Deserialize)]
+struct ApibaraEvent {
+    #[serde(default)]
+    keys: Vec<String>,
+    #[serde(default)]
+    data: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct ApibaraTransaction {
+    meta: Option<ApibaraTransactionMeta>,
+}
+
+#[derive(Deserialize)]
+struct ApibaraTransactionMeta {
+    hash: Option<String>,
+}
+
+impl ApibaraBlock {
+    fn into_events(self) -> Vec<JsonEvent> {
+        let block_number = self.header.map(|header| header.block_number);
+        self.events
+            .into_iter()
+            .filter_map(|event_with_tx| {
+                let event = event_with_tx.event?;
+                let transaction_hash = event_with_tx.transaction.and_then(|tx| tx.meta).and_then(|meta| meta.hash);
+                Some(JsonEvent { keys: event.keys, data: event.data, block_number, transaction_hash })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DISPATCH_EVENT_FIXTURE: &str = include_str!("../fixtures/dispatch_event.json");
+
+    fn state() -> AppState {
+        let storage = TheorosStorage::new(FeedIdsStorage::default(), ValidatorsFetchersStorage::default());
+        let starknet_rpc = StarknetRpc::new("http://localhost:9944".parse().unwrap());
+        AppState::builder().with_starknet_rpc(Arc::new(starknet_rpc)).with_storage(storage).build().unwrap()
+    }
+
+    #[test]
+    fn test_parse_fixture_formats() {
+        let event = parse_fixture(DISPATCH_EVENT_FIXTURE).unwrap();
+        assert_eq!(event.len(), 1);
+        assert_eq!(event[0].data.len(), 22);
+
+        let felts = parse_fixture(r#"["0x1", "0x2"]"#).unwrap();
+        assert_eq!(felts, vec![FixtureEvent { data: vec![Felt::ONE, Felt::TWO], ..Default::default() }]);
+
+        let dispatch_selector = get_selector_from_name("Dispatch").unwrap().to_hex_string();
+        let blocks = format!(
+            r#"[{{
+                "header": {{ "blockNumber": 42 }},
+                "events": [
+                    {{ "event": {{ "keys": ["{dispatch_selector}"], "data": ["0x1"] }},
+                       "transaction": {{ "meta": {{ "hash": "0xabc" }} }} }},
+                    {{ "event": {{ "keys": ["0x123"], "data": ["0x2"] }} }}
+                ]
+            }}]"#
+        );
+        let events = parse_fixture(&blocks).unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].block_number, Some(42));
+        assert_eq!(events[0].transaction_hash, Some(Felt::from_hex("0xabc").unwrap()));
+
+        assert!(parse_fixture(r#"["not a felt"]"#).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_replay_stores_the_updates() {
+        let events = parse_fixture(DISPATCH_EVENT_FIXTURE).unwrap();
+        let report = replay(&state(), events).await;
+
+        assert!(report.passed, "{}", report.summary());
+        assert_eq!(report.events[0].status, ReplayStatus::Stored);
+        assert_eq!(report.feeds.len(), 1);
+        assert_eq!(Some(report.feeds[0].nonce), report.events[0].nonce);
+        assert_eq!(report.feeds[0].feed_id, report.events[0].feed_ids[0]);
+    }
+
+    #[tokio::test]
+    async fn test_replay_fails_on_undecodable_events() {
+        let events = vec![FixtureEvent { data: vec![Felt::ONE], ..Default::default() }];
+        let report = replay(&state(), events).await;
+
+        assert!(!report.passed);
+        assert_eq!(report.events[0].status, ReplayStatus::Failed);
+        assert!(report.feeds.is_empty());
+    }
+}
@@ -0,0 +1,159 @@
+use alloy::primitives::{Bytes, B256, U256};
+use alloy::sol;
+use alloy::sol_types::{SolCall, SolValue};
+use starknet::core::types::U256 as StarknetU256;
+
+use crate::rpc::evm::pragma::IPragma;
+use crate::types::hyperlane::{MetadataUpdate, PerpUpdate, SpotMedianUpdate};
+
+sol! {
+    /// Header of every update, see `PragmaStructs.sol`. The update data is packed, as parsed by `DataParser.sol`.
+    struct Metadata {
+        bytes32 feedId;
+        uint64 timestamp;
+        uint16 numberOfSources;
+        uint8 decimals;
+    }
+
+    struct SpotMedian {
+        Metadata metadata;
+        uint256 price;
+        uint256 volume;
+    }
+
+    struct Perp {
+        Metadata metadata;
+        uint256 markPrice;
+        uint256 fundingRate;
+        uint256 openInterest;
+        uint256 volume;
+    }
+}
+
+/// Converts a Starknet U256 into its EVM counterpart.
+fn to_evm_u256(value: &StarknetU256) -> U256 {
+    (U256::from(value.high()) << 128) | U256::from(value.low())
+}
+
+impl Metadata {
+    /// The feed id word starts with the low half of the pair id, followed by its high half, as emitted by
+    /// the Pragma chain.
+    fn new(pair_id: &StarknetU256, metadata: &MetadataUpdate) -> Self {
+        let mut feed_id = [0u8; 32];
+        feed_id[..16].copy_from_slice(&pair_id.low().to_be_bytes());
+        feed_id[16..].copy_from_slice(&pair_id.high().to_be_bytes());
+        Self {
+            feedId: B256::from(feed_id),
+            timestamp: metadata.timestamp,
+            numberOfSources: metadata.num_sources_aggregated,
+            decimals: metadata.decimals,
+        }
+    }
+}
+
+impl From<&SpotMedianUpdate> for SpotMedian {
+    fn from(update: &SpotMedianUpdate) -> Self {
+        Self {
+            metadata: Metadata::new(&update.pair_id, &update.metadata),
+            price: to_evm_u256(&update.price),
+            volume: to_evm_u256(&update.volume),
+        }
+    }
+}
+
+impl From<&PerpUpdate> for Perp {
+    fn from(update: &PerpUpdate) -> Self {
+        Self {
+            metadata: Metadata::new(&update.pair_id, &update.metadata),
+            markPrice: to_evm_u256(&update.mark_price),
+            fundingRate: to_evm_u256(&update.funding_rate),
+            openInterest: to_evm_u256(&update.open_interest),
+            volume: to_evm_u256(&update.volume),
+        }
+    }
+}
+
+/// Packs the update data of a spot median update, as parsed by `DataParser.parseSpotData`.
+pub fn encode_spot_median(update: &SpotMedianUpdate) -> Vec<u8> {
+    SpotMedian::from(update).abi_encode_packed()
+}
+
+/// Packs the update data of a perp update, as parsed by `DataParser.parsePerpData`.
+pub fn encode_perp(update: &PerpUpdate) -> Vec<u8> {
+    Perp::from(update).abi_encode_packed()
+}
+
+/// ABI-encodes the `updateDataFeeds` call of the Pragma contract, ready to be sent in a transaction.
+pub fn encode_update_data_feeds(update_data: Vec<Bytes>) -> Bytes {
+    IPragma::updateDataFeedsCall { updateData: update_data }.abi_encode().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::hex;
+
+    use super::*;
+
+    /// Update data of `testParseSpotMedianEntry` in `DataParser.t.sol`, checked there as `SPOT_MEDIAN_GOLDEN_VECTOR`.
+    const SPOT_MEDIAN_GOLDEN_VECTOR: &str = "0x000000004254432f555344000000000000000000000000000000000000000000\
+        0000000060dd05800003080000000000000000000000000000000000000000000007695a92c20d6fe00000000000000000000000\
+        0000000000000000000000000000056bc75e2d63100000";
+    /// Update data of `testParsePerpEntry` in `DataParser.t.sol`, checked there as `PERP_GOLDEN_VECTOR`.
+    const PERP_GOLDEN_VECTOR: &str = "0x000004004254432f555344000000000000000000000000000000000000000000\
+        0000000060dd05800003080000000000000000000000000000000000000000000007695a92c20d6fe00000000000000000000000\
+        00000000000000000000000000000000038d7ea4c6800000000000000000000000000000000000000000000000003635c9adc5de\
+        a0000000000000000000000000000000000000000000000000001b1ae4d6e2ef500000";
+
+    const ETHER: u128 = 1_000_000_000_000_000_000;
+
+    fn ether(amount: u128) -> U256 {
+        U256::from(amount * ETHER)
+    }
+
+    /// Metadata of the BTC/USD feed of the given type in `DataParser.t.sol`.
+    fn metadata(feed_type: u8) -> Metadata {
+        let mut feed_id = [0u8; 32];
+        feed_id[2] = feed_type;
+        feed_id[4..11].copy_from_slice(b"BTC/USD");
+        Metadata { feedId: B256::from(feed_id), timestamp: 1_625_097_600, numberOfSources: 3, decimals: 8 }
+    }
+
+    #[test]
+    fn test_spot_median_golden_vector() {
+        let spot = SpotMedian { metadata: metadata(0), price: ether(35_000), volume: ether(100) };
+        assert_eq!(hex::encode_prefixed(spot.abi_encode_packed()), SPOT_MEDIAN_GOLDEN_VECTOR);
+    }
+
+    #[test]
+    fn test_perp_golden_vector() {
+        let perp = Perp {
+            metadata: metadata(4),
+            markPrice: ether(35_000),
+            fundingRate: ether(1) / U256::from(1_000),
+            openInterest: ether(1_000),
+            volume: ether(500),
+        };
+        assert_eq!(hex::encode_prefixed(perp.abi_encode_packed()), PERP_GOLDEN_VECTOR);
+    }
+
+    #[test]
+    fn test_update_is_encoded_from_its_pair_id() {
+        let pair_id = StarknetU256::from_words(0x4254432f555344 << 40, 0);
+        let update = SpotMedianUpdate {
+            pair_id,
+            metadata: MetadataUpdate { timestamp: 1_625_097_600, num_sources_aggregated: 3, decimals: 8 },
+            price: StarknetU256::from(35_000 * ETHER),
+            volume: StarknetU256::from(100 * ETHER),
+        };
+        assert_eq!(hex::encode_prefixed(encode_spot_median(&update)), SPOT_MEDIAN_GOLDEN_VECTOR);
+    }
+
+    #[test]
+    fn test_encode_update_data_feeds() {
+        let update_data = vec![Bytes::from(vec![0x01, 0x02])];
+        let encoded = encode_update_data_feeds(update_data.clone());
+        assert_eq!(encoded[..4], IPragma::updateDataFeedsCall::SELECTOR);
+        let decoded = IPragma::updateDataFeedsCall::abi_decode(&encoded, true).unwrap();
+        assert_eq!(decoded.updateData, update_data);
+    }
+}
@@ -0,0 +1,48 @@
+use std::{future::Future, time::Instant};
+
+use url::Url;
+
+use crate::{
+    configs::evm_config::{EvmChainConfig, EvmChainName},
+    rpc::failover::RpcEndpointsHealth,
+};
+
+lazy_static::lazy_static! {
+    static ref RPC_ENDPOINTS_HEALTH: RpcEndpointsHealth<EvmChainName> = RpcEndpointsHealth::default();
+}
+
+/// Runs the call against the RPC endpoints of the chain in turn, until one of them succeeds. Endpoints which
+/// fail are skipped for a while, unless all the endpoints of the chain are failing.
+pub async fn with_failover<T, F, Fut>(
+    chain_name: &EvmChainName,
+    chain_config: &EvmChainConfig,
+    call: F,
+) -> anyhow::Result<T>
+where
+    F: Fn(Url) -> Fut,
+    Fut: Future<Output = anyhow::Result<T>>,
+{
+    let rpc_urls: Vec<&str> = chain_config.rpc_urls().collect();
+    let mut last_error = None;
+    for rpc_url in RPC_ENDPOINTS_HEALTH.order(*chain_name, &rpc_urls, Instant::now()) {
+        let result = match Url::parse(rpc_url) {
+            Ok(url) => call(url).await,
+            Err(e) => Err(e.into()),
+        };
+        match result {
+            Ok(value) => {
+                RPC_ENDPOINTS_HEALTH.mark_healthy(rpc_url);
+                return Ok(value);
+            }
+            Err(e) => {
+                if rpc_urls.len() > 1 {
+                    tracing::warn!("⚠️ RPC endpoint of {} failed, trying the next one: {:?}", chain_name, e);
+                }
+                RPC_ENDPOINTS_HEALTH.mark_failed(rpc_url, Instant::now());
+                last_error = Some(e);
+            }
+        }
+    }
+    let error = last_error.unwrap_or_else(|| anyhow::anyhow!("No RPC endpoint configured"));
+    Err(error.context(format!("All the {} RPC endpoints of {} failed", rpc_urls.len(), chain_name)))
+}
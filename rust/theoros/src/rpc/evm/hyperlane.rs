@@ -5,6 +5,7 @@ use alloy::primitives::Address;
 use alloy::providers::fillers::{ChainIdFiller, FillProvider, GasFiller, JoinFill, NonceFiller};
 use alloy::providers::{Identity, ProviderBuilder, RootProvider};
 use alloy::transports::http::{Client, Http};
+use alloy::transports::RpcError;
 use alloy::{providers::fillers::BlobGasFiller, sol};
 use anyhow::Result;
 use pragma_utils::bytes::pad_left_to_32_bytes;
@@ -15,6 +16,7 @@ sol! {
     #[sol(rpc)]
     interface IHyperlane {
         function _validators(uint256) external view returns (address);
+        function threshold() external view returns (uint8);
     }
 }
 
@@ -42,7 +44,13 @@ impl HyperlaneClient {
         let mut validators = HashMap::new();
         let mut index = 0;
 
-        while let Ok(address) = self.0._validators(index.try_into()?).call().await {
+        loop {
+            let address = match self.0._validators(index.try_into()?).call().await {
+                Ok(address) => address,
+                // Reading past the end of the validators reverts.
+                Err(e) if is_revert(&e) => break,
+                Err(e) => return Err(e.into()),
+            };
             if address._0 == Address::ZERO {
                 break;
             }
@@ -53,4 +61,21 @@ impl HyperlaneClient {
 
         Ok(validators)
     }
+
+    /// Number of signatures required by the ISM. Not every Hyperlane contract exposes it.
+    pub async fn get_threshold(&self) -> Result<Option<u8>> {
+        match self.0.threshold().call().await {
+            Ok(threshold) => Ok(Some(threshold._0)),
+            Err(e) if is_revert(&e) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+/// Whether the call was rejected by the contract, as opposed to failing to reach the RPC.
+fn is_revert(error: &alloy::contract::Error) -> bool {
+    matches!(
+        error,
+        alloy::contract::Error::AbiError(_) | alloy::contract::Error::TransportError(RpcError::ErrorResp(_))
+    )
 }
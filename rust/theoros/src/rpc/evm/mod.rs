@@ -1,44 +1,127 @@
+pub mod calldata;
+pub mod failover;
 pub mod hyperlane;
+pub mod pragma;
+pub mod validator_announce;
 
 pub use hyperlane::*;
 use starknet::core::types::Felt;
 
-use std::collections::HashMap;
+use std::{collections::HashMap, sync::Arc};
 
 use alloy::hex::FromHex;
 use alloy::primitives::Address;
-use url::Url;
+use dashmap::DashMap;
 
-use crate::configs::evm_config::{EvmChainName, EvmConfig};
+use crate::{
+    configs::evm_config::{EvmChainConfig, EvmChainName, EvmConfig},
+    types::quorum::quorum_threshold,
+};
 
-#[derive(Debug, Default, Clone)]
-pub struct HyperlaneValidatorsMapping(HashMap<EvmChainName, HashMap<Felt, u8>>);
+/// Validators of a chain with their indexes, & the number of signatures its Hyperlane contract requires.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidatorSet {
+    pub validators: Arc<HashMap<Felt, u8>>,
+    pub threshold: usize,
+}
+
+impl ValidatorSet {
+    /// Requires the quorum of the Pragma Hyperlane contract, see [quorum_threshold].
+    pub fn new(validators: HashMap<Felt, u8>) -> Self {
+        let threshold = quorum_threshold(validators.len());
+        Self { validators: Arc::new(validators), threshold }
+    }
+
+    pub fn with_threshold(mut self, threshold: usize) -> Self {
+        self.threshold = threshold;
+        self
+    }
+}
+
+/// Validators of the chains & their indexes, which can be replaced at runtime when a new EVM config is applied.
+#[derive(Debug, Default)]
+pub struct HyperlaneValidatorsMapping(DashMap<EvmChainName, ValidatorSet>);
 
 impl HyperlaneValidatorsMapping {
     pub async fn from_config(config: &EvmConfig) -> anyhow::Result<Self> {
-        let mut contracts = HashMap::new();
+        let contracts = DashMap::new();
 
         for (chain_name, chain_config) in config.chains() {
-            let rpc_url: Url = chain_config.rpc_url.parse()?;
-            let address = Address::from_hex(&chain_config.hyperlane_address)
-                .map_err(|e| anyhow::anyhow!("Invalid hyperlane address for {chain_name:?}: {e}"))?;
-            let rpc_client = HyperlaneClient::new(rpc_url, address).await;
-
-            let validators = rpc_client.get_validators_with_index().await?;
+            if !chain_config.status.enabled {
+                tracing::info!("⏸️ Chain {chain_name} is disabled, not loading its validators");
+                continue;
+            }
+            let validators = Self::fetch_validators(chain_name, chain_config).await?;
             contracts.insert(*chain_name, validators);
         }
 
         Ok(Self(contracts))
     }
 
+    /// Fetches the validators of the chain, their indexes & the threshold from its Hyperlane contract. Contracts
+    /// without a `threshold()` require the quorum of the Pragma Hyperlane contract.
+    pub async fn fetch_validators(
+        chain_name: &EvmChainName,
+        chain_config: &EvmChainConfig,
+    ) -> anyhow::Result<ValidatorSet> {
+        let address = Address::from_hex(&chain_config.hyperlane_address)
+            .map_err(|e| anyhow::anyhow!("Invalid hyperlane address for {chain_name:?}: {e}"))?;
+        let (validators, threshold) = failover::with_failover(chain_name, chain_config, |rpc_url| async move {
+            let rpc_client = HyperlaneClient::new(rpc_url, address).await;
+            Ok((rpc_client.get_validators_with_index().await?, rpc_client.get_threshold().await?))
+        })
+        .await?;
+        let validator_set = ValidatorSet::new(validators);
+
+        let threshold = match threshold {
+            Some(0) => {
+                tracing::warn!("⚠️ The ISM of {chain_name} returned a threshold of 0, using the default quorum");
+                return Ok(validator_set);
+            }
+            Some(threshold) => usize::from(threshold),
+            None => {
+                tracing::debug!("The ISM of {chain_name} has no threshold, using the default quorum");
+                return Ok(validator_set);
+            }
+        };
+        if threshold > validator_set.validators.len() {
+            tracing::warn!(
+                "⚠️ The ISM of {chain_name} requires {threshold} signatures but only has {} validators",
+                validator_set.validators.len()
+            );
+        }
+        Ok(validator_set.with_threshold(threshold))
+    }
+
     /// Get the available validators for a chain & their indexes
-    pub fn get_validators(&self, chain_name: &EvmChainName) -> Option<&HashMap<Felt, u8>> {
-        self.0.get(chain_name)
+    pub fn get_validators(&self, chain_name: &EvmChainName) -> Option<Arc<HashMap<Felt, u8>>> {
+        self.0.get(chain_name).map(|validator_set| validator_set.validators.clone())
+    }
+
+    pub fn get_validator_set(&self, chain_name: &EvmChainName) -> Option<ValidatorSet> {
+        self.0.get(chain_name).map(|validator_set| validator_set.clone())
+    }
+
+    /// Number of signatures required by the chain.
+    pub fn threshold(&self, chain_name: &EvmChainName) -> Option<usize> {
+        self.0.get(chain_name).map(|validator_set| validator_set.threshold)
+    }
+
+    pub fn insert(&self, chain_name: EvmChainName, validator_set: ValidatorSet) {
+        self.0.insert(chain_name, validator_set);
+    }
+
+    /// Replaces the validators of all the chains by the ones of `other`.
+    pub fn replace(&self, other: Self) {
+        self.0.retain(|chain_name, _| other.0.contains_key(chain_name));
+        for (chain_name, validators) in other.0 {
+            self.0.insert(chain_name, validators);
+        }
     }
 
     /// Get all configured chains names
     pub fn chain_names(&self) -> Vec<EvmChainName> {
-        self.0.keys().cloned().collect()
+        self.0.iter().map(|entry| *entry.key()).collect()
     }
 
     /// Check if the provided chain is supported
@@ -0,0 +1,190 @@
+use alloy::hex::{self, FromHex};
+use alloy::primitives::{Address, Bytes, U256};
+use alloy::providers::ProviderBuilder;
+use alloy::sol;
+use alloy::sol_types::{decode_revert_reason, Panic, SolError, SolInterface};
+use anyhow::Result;
+use dashmap::DashMap;
+use url::Url;
+
+use crate::configs::evm_config::{EvmChainName, EvmConfig};
+
+sol! {
+    #[sol(rpc)]
+    interface IPragma {
+        error InsufficientFee();
+        error InvalidUpdateDataSource();
+        error InvalidVersion();
+        error InvalidHyperlaneCheckpointRoot();
+        error InvalidHyperlaneSignatures(string);
+        error InvalidUpdateData();
+        error InvalidDataFeedType();
+        error DataNotFound();
+        error DataStale();
+
+        function updateDataFeeds(bytes[] calldata updateData) external payable;
+        function getUpdateFee(bytes[] calldata updateData) external view returns (uint256 feeAmount);
+    }
+}
+
+/// Location of the Pragma contract deployed on a chain.
+#[derive(Debug, Clone)]
+pub struct PragmaContract {
+    pub rpc_url: Url,
+    pub address: Address,
+}
+
+/// Mapping between the chains and their Pragma contract, for the chains configuring one.
+#[derive(Debug, Default)]
+pub struct PragmaContractsMapping(DashMap<EvmChainName, PragmaContract>);
+
+impl PragmaContractsMapping {
+    pub fn from_config(config: &EvmConfig) -> Result<Self> {
+        let contracts = DashMap::new();
+        for (chain_name, chain_config) in config.chains() {
+            let Some(pragma_address) = &chain_config.pragma_address else {
+                continue;
+            };
+            let address = Address::from_hex(pragma_address)
+                .map_err(|e| anyhow::anyhow!("Invalid pragma address for {chain_name:?}: {e}"))?;
+            contracts.insert(*chain_name, PragmaContract { rpc_url: chain_config.rpc_url.parse()?, address });
+        }
+        Ok(Self(contracts))
+    }
+
+    pub fn get(&self, chain_name: &EvmChainName) -> Option<PragmaContract> {
+        self.0.get(chain_name).map(|contract| contract.clone())
+    }
+
+    /// Replaces the contracts of all the chains by the ones of `other`.
+    pub fn replace(&self, other: Self) {
+        self.0.retain(|chain_name, _| other.0.contains_key(chain_name));
+        for (chain_name, contract) in other.0 {
+            self.0.insert(chain_name, contract);
+        }
+    }
+}
+
+/// Revert returned by the Pragma contract, decoded when possible.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodedRevert {
+    /// Name of the error, e.g. `InvalidHyperlaneSignatures`, `Error` or `Panic`.
+    pub error: String,
+    /// Human readable reason of the revert.
+    pub reason: String,
+    /// Raw revert data.
+    pub data: Bytes,
+}
+
+impl DecodedRevert {
+    pub fn decode(data: Bytes) -> Self {
+        let (error, reason) = match IPragma::IPragmaErrors::abi_decode(&data, true) {
+            Ok(error) => describe_pragma_error(error),
+            Err(_) => match decode_revert_reason(&data) {
+                Some(reason) if data.len() >= 4 && data[..4] == Panic::SELECTOR => (String::from("Panic"), reason),
+                Some(reason) => (String::from("Error"), reason),
+                None => (String::from("Unknown"), format!("Undecodable revert data 0x{}", hex::encode(&data))),
+            },
+        };
+        Self { error, reason, data }
+    }
+}
+
+fn describe_pragma_error(error: IPragma::IPragmaErrors) -> (String, String) {
+    use IPragma::IPragmaErrors as E;
+    let (name, reason) = match error {
+        E::InsufficientFee(_) => ("InsufficientFee", String::from("The value sent doesn't cover the update fee")),
+        E::InvalidUpdateDataSource(_) => (
+            "InvalidUpdateDataSource",
+            String::from("The emitter of the update is not registered as a data source of the Pragma contract"),
+        ),
+        E::InvalidVersion(_) => {
+            ("InvalidVersion", String::from("The Hyperlane or Pragma version of the update is not supported"))
+        }
+        E::InvalidHyperlaneCheckpointRoot(_) => {
+            ("InvalidHyperlaneCheckpointRoot", String::from("The checkpoint root doesn't match the message"))
+        }
+        E::InvalidHyperlaneSignatures(e) => (
+            "InvalidHyperlaneSignatures",
+            format!("The validators signatures were rejected, check the ISM validators & threshold: {}", e._0),
+        ),
+        E::InvalidUpdateData(_) => ("InvalidUpdateData", String::from("The update data could not be deserialized")),
+        E::InvalidDataFeedType(_) => ("InvalidDataFeedType", String::from("The data feed type is not supported")),
+        E::DataNotFound(_) => ("DataNotFound", String::from("The data feed was not found")),
+        E::DataStale(_) => ("DataStale", String::from("The data feed is stale")),
+    };
+    (name.to_owned(), reason)
+}
+
+/// Outcome of an `eth_call` of `updateDataFeeds`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SimulationOutcome {
+    /// The update would be applied, using about `gas_estimate` gas.
+    Success {
+        gas_estimate: u64,
+    },
+    Reverted(DecodedRevert),
+}
+
+/// Simulates the update of the data feeds through an `eth_call` against the provided RPC,
+/// which can be the RPC of the chain or of a fork of it, & estimates its gas when it succeeds.
+pub async fn simulate_update_data_feeds(
+    rpc_url: Url,
+    contract_address: Address,
+    update_data: Vec<Bytes>,
+    from: Option<Address>,
+    value: U256,
+) -> Result<SimulationOutcome> {
+    let provider = ProviderBuilder::new().on_http(rpc_url);
+    let pragma = IPragma::new(contract_address, provider);
+
+    let mut call = pragma.updateDataFeeds(update_data).value(value);
+    if let Some(from) = from {
+        call = call.from(from);
+    }
+
+    match call.call().await {
+        Ok(_) => Ok(SimulationOutcome::Success { gas_estimate: call.estimate_gas().await? }),
+        Err(alloy::contract::Error::TransportError(e)) => {
+            match e.as_error_resp().and_then(|payload| payload.as_revert_data()) {
+                Some(data) => Ok(SimulationOutcome::Reverted(DecodedRevert::decode(data))),
+                None => Err(e.into()),
+            }
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::sol_types::Revert;
+
+    use super::*;
+
+    #[test]
+    fn test_decode_pragma_errors() {
+        let data: Bytes = IPragma::InsufficientFee {}.abi_encode().into();
+        let revert = DecodedRevert::decode(data.clone());
+        assert_eq!(revert.error, "InsufficientFee");
+        assert_eq!(revert.data, data);
+
+        let data = IPragma::InvalidHyperlaneSignatures { _0: String::from("threshold not reached") }.abi_encode();
+        let revert = DecodedRevert::decode(data.into());
+        assert_eq!(revert.error, "InvalidHyperlaneSignatures");
+        assert!(revert.reason.ends_with("threshold not reached"));
+    }
+
+    #[test]
+    fn test_decode_generic_reverts() {
+        let revert = DecodedRevert::decode(Revert::from("slice_outOfBounds").abi_encode().into());
+        assert_eq!(revert.error, "Error");
+        assert!(revert.reason.contains("slice_outOfBounds"));
+
+        let revert = DecodedRevert::decode(Panic::from(0x11).abi_encode().into());
+        assert_eq!(revert.error, "Panic");
+
+        let revert = DecodedRevert::decode(Bytes::from(vec![0xde, 0xad, 0xbe, 0xef]));
+        assert_eq!(revert.error, "Unknown");
+        assert!(revert.reason.ends_with("deadbeef"));
+    }
+}
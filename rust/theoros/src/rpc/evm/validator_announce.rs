@@ -0,0 +1,100 @@
+use std::str::FromStr;
+
+use alloy::network::Ethereum;
+use alloy::primitives::Address;
+use alloy::providers::fillers::{ChainIdFiller, FillProvider, GasFiller, JoinFill, NonceFiller};
+use alloy::providers::{Identity, ProviderBuilder, RootProvider};
+use alloy::transports::http::{Client, Http};
+use alloy::{providers::fillers::BlobGasFiller, sol};
+use anyhow::Result;
+use starknet::core::types::Felt;
+use url::Url;
+
+use crate::{
+    configs::evm_config::EvmConfig,
+    rpc::evm::{failover, HyperlaneValidatorsMapping},
+    storage::ValidatorsFetchersStorage,
+};
+
+sol! {
+    #[sol(rpc)]
+    interface IValidatorAnnounce {
+        function getAnnouncedStorageLocations(address[] calldata validators) external view returns (string[][] memory);
+    }
+}
+
+pub type ValidatorAnnounceContract = IValidatorAnnounce::IValidatorAnnounceInstance<
+    Http<Client>,
+    FillProvider<
+        JoinFill<Identity, JoinFill<GasFiller, JoinFill<BlobGasFiller, JoinFill<NonceFiller, ChainIdFiller>>>>,
+        RootProvider<Http<Client>>,
+        Http<Client>,
+        Ethereum,
+    >,
+>;
+
+/// Client of the Hyperlane `ValidatorAnnounce` contract of an EVM chain, where validators announce the
+/// locations of their signed checkpoints.
+#[derive(Debug, Clone)]
+pub struct ValidatorAnnounceClient(ValidatorAnnounceContract);
+
+impl ValidatorAnnounceClient {
+    pub async fn new(rpc_url: Url, contract_address: Address) -> Self {
+        let provider = ProviderBuilder::new().with_recommended_fillers().on_http(rpc_url);
+        Self(IValidatorAnnounce::new(contract_address, provider))
+    }
+
+    /// Returns the storage locations announced by each validator, in the order of the validators & from the
+    /// oldest announced.
+    pub async fn get_announced_storage_locations(&self, validators: Vec<Address>) -> Result<Vec<Vec<String>>> {
+        Ok(self.0.getAnnouncedStorageLocations(validators).call().await?._0)
+    }
+}
+
+/// Discovers the storage locations of the validators of the chains from their `ValidatorAnnounce` contract, for
+/// the validators whose locations aren't known yet. Returns the number of validators discovered.
+pub async fn discover_announced_locations(
+    config: &EvmConfig,
+    validators: &HyperlaneValidatorsMapping,
+    fetchers: &ValidatorsFetchersStorage,
+) -> usize {
+    let mut discovered = 0;
+    for (chain_name, chain_config) in config.chains() {
+        let (Some(validator_announce_address), Some(chain_validators)) =
+            (&chain_config.validator_announce_address, validators.get_validators(chain_name))
+        else {
+            continue;
+        };
+        let unknown: Vec<Felt> =
+            chain_validators.keys().filter(|validator| !fetchers.contains(validator)).copied().collect();
+        if unknown.is_empty() {
+            continue;
+        }
+
+        let locations = failover::with_failover(chain_name, chain_config, |rpc_url| {
+            announced_locations(rpc_url, validator_announce_address, &unknown)
+        });
+        let locations = match locations.await {
+            Ok(locations) => locations,
+            Err(e) => {
+                tracing::warn!("⚠️ Failed to read the validators announced on {}: {:?}", chain_name, e);
+                continue;
+            }
+        };
+        for (validator, locations) in unknown.iter().zip(locations) {
+            if fetchers.add_announced_locations(*validator, &locations).await {
+                tracing::info!("🔎 Discovered the storage of validator {:#x} on {}", validator, chain_name);
+                discovered += 1;
+            }
+        }
+    }
+    discovered
+}
+
+async fn announced_locations(rpc_url: Url, address: &str, validators: &[Felt]) -> Result<Vec<Vec<String>>> {
+    let client = ValidatorAnnounceClient::new(rpc_url, Address::from_str(address)?).await;
+    let addresses = validators.iter().map(|validator| Address::from_slice(&validator.to_bytes_be()[12..])).collect();
+    let locations = client.get_announced_storage_locations(addresses).await?;
+    anyhow::ensure!(locations.len() == validators.len(), "Expected the locations of {} validators", validators.len());
+    Ok(locations)
+}
@@ -0,0 +1,85 @@
+use std::{hash::Hash, time::Instant};
+
+use dashmap::DashMap;
+
+use crate::constants::RPC_ENDPOINT_COOLDOWN;
+
+/// Health of groups of interchangeable RPC endpoints, e.g. the endpoints of a chain, shared by all the calls
+/// made to them.
+#[derive(Debug)]
+pub struct RpcEndpointsHealth<K: Eq + Hash> {
+    /// Time until which each endpoint which failed is skipped.
+    unhealthy_until: DashMap<String, Instant>,
+    /// Index of the endpoint the next call of each group starts from.
+    next: DashMap<K, usize>,
+}
+
+impl<K: Eq + Hash> Default for RpcEndpointsHealth<K> {
+    fn default() -> Self {
+        Self { unhealthy_until: DashMap::new(), next: DashMap::new() }
+    }
+}
+
+impl<K: Eq + Hash> RpcEndpointsHealth<K> {
+    /// Order in which the endpoints of the group are tried: round-robin over the healthy endpoints, then the
+    /// unhealthy ones from the one which recovers first, so a call is still attempted when all of them failed
+    /// recently.
+    pub fn order<'a>(&self, group: K, rpc_urls: &[&'a str], now: Instant) -> Vec<&'a str> {
+        if rpc_urls.is_empty() {
+            return Vec::new();
+        }
+        let start = {
+            let mut next = self.next.entry(group).or_default();
+            let start = *next % rpc_urls.len();
+            *next = start + 1;
+            start
+        };
+        let rotated = rpc_urls.iter().cycle().skip(start).take(rpc_urls.len()).copied();
+        let (mut healthy, mut unhealthy): (Vec<&str>, Vec<&str>) =
+            rotated.partition(|rpc_url| self.unhealthy_until(rpc_url).map_or(true, |until| until <= now));
+        unhealthy.sort_by_key(|rpc_url| self.unhealthy_until(rpc_url));
+        healthy.append(&mut unhealthy);
+        healthy
+    }
+
+    /// Skips the endpoint for [RPC_ENDPOINT_COOLDOWN].
+    pub fn mark_failed(&self, rpc_url: &str, now: Instant) {
+        self.unhealthy_until.insert(rpc_url.to_owned(), now + RPC_ENDPOINT_COOLDOWN);
+    }
+
+    pub fn mark_healthy(&self, rpc_url: &str) {
+        self.unhealthy_until.remove(rpc_url);
+    }
+
+    fn unhealthy_until(&self, rpc_url: &str) -> Option<Instant> {
+        self.unhealthy_until.get(rpc_url).map(|until| *until)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_endpoints_order() {
+        let health = RpcEndpointsHealth::default();
+        let rpc_urls = ["http://a", "http://b", "http://c"];
+        let now = Instant::now();
+
+        assert_eq!(health.order("sepolia", &rpc_urls, now), vec!["http://a", "http://b", "http://c"]);
+        assert_eq!(health.order("sepolia", &rpc_urls, now), vec!["http://b", "http://c", "http://a"]);
+        // Each group has its own round-robin.
+        assert_eq!(health.order("holesky", &rpc_urls, now), vec!["http://a", "http://b", "http://c"]);
+
+        health.mark_failed("http://a", now);
+        health.mark_failed("http://c", now - RPC_ENDPOINT_COOLDOWN / 2);
+        assert_eq!(health.order("sepolia", &rpc_urls, now), vec!["http://b", "http://c", "http://a"]);
+
+        // Endpoints are tried again once their cooldown is over.
+        let later = now + RPC_ENDPOINT_COOLDOWN;
+        assert_eq!(health.order("sepolia", &rpc_urls, later), vec!["http://a", "http://b", "http://c"]);
+
+        health.mark_healthy("http://a");
+        assert_eq!(health.order("sepolia", &rpc_urls, now), vec!["http://b", "http://a", "http://c"]);
+    }
+}
@@ -1,2 +1,3 @@
 pub mod evm;
+pub mod failover;
 pub mod starknet;
@@ -47,7 +47,7 @@ impl HyperlaneCalls for StarknetRpc {
             calldata,
         };
 
-        let response = self.0.call(call, BlockId::Tag(BlockTag::Pending)).await?;
+        let response = self.with_failover(|client| client.call(&call, BlockId::Tag(BlockTag::Pending))).await?;
         let storage_locations = process_nested_felt_array(&response)?;
 
         Ok(storage_locations)
@@ -59,7 +59,7 @@ impl HyperlaneCalls for StarknetRpc {
             entry_point_selector: selector!("get_announced_validators"),
             calldata: vec![],
         };
-        let mut response = self.0.call(call, BlockId::Tag(BlockTag::Pending)).await?;
+        let mut response = self.with_failover(|client| client.call(&call, BlockId::Tag(BlockTag::Pending))).await?;
         response.remove(0); // We remove the first element because it is the size of the response.
         Ok(response)
     }
@@ -70,7 +70,7 @@ impl HyperlaneCalls for StarknetRpc {
             entry_point_selector: selector!("latest_checkpoint"),
             calldata: vec![],
         };
-        let response = self.0.call(call, BlockId::Tag(BlockTag::Pending)).await?;
+        let response = self.with_failover(|client| client.call(&call, BlockId::Tag(BlockTag::Pending))).await?;
         Ok(response)
     }
 }
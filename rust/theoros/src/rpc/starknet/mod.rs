@@ -4,18 +4,146 @@ pub mod pragma_feeds_registry;
 pub use hyperlane::*;
 pub use pragma_feeds_registry::*;
 
+use std::{future::Future, time::Instant};
+
 use anyhow::Context;
-use starknet::providers::{jsonrpc::HttpTransport, JsonRpcClient, Provider};
+use starknet::core::types::{BlockId, MaybePendingBlockWithTxHashes};
+use starknet::providers::{jsonrpc::HttpTransport, JsonRpcClient, Provider, ProviderError};
 use url::Url;
 
-pub struct StarknetRpc(JsonRpcClient<HttpTransport>);
+use crate::rpc::failover::RpcEndpointsHealth;
+
+/// All the calls made by Theoros to the Pragma chain.
+/// Allows to provide alternate implementations of the RPC, e.g. in tests.
+pub trait StarknetCalls: BlockCalls + HyperlaneCalls + PragmaFeedsRegistryCalls + Send + Sync {}
+
+impl<T: BlockCalls + HyperlaneCalls + PragmaFeedsRegistryCalls + Send + Sync> StarknetCalls for T {}
+
+#[async_trait::async_trait]
+pub trait BlockCalls: Send + Sync {
+    /// Retrieves the current block number.
+    async fn block_number(&self) -> anyhow::Result<u64>;
+
+    /// Returns the timestamp of the provided block.
+    async fn block_timestamp(&self, block_number: u64) -> anyhow::Result<u64>;
+
+    /// Returns the first block produced at or after the provided timestamp, using a binary search
+    /// over the blocks timestamps.
+    /// If the timestamp is in the future, returns the current block.
+    async fn first_block_after(&self, timestamp: u64) -> anyhow::Result<u64> {
+        let (mut low, mut high) = (0, self.block_number().await?);
+        while low < high {
+            let middle = low + (high - low) / 2;
+            if self.block_timestamp(middle).await? < timestamp {
+                low = middle + 1;
+            } else {
+                high = middle;
+            }
+        }
+        Ok(low)
+    }
+}
+
+/// Client of the Pragma chain, failing over between its RPC endpoints.
+pub struct StarknetRpc {
+    endpoints: Vec<(String, JsonRpcClient<HttpTransport>)>,
+    health: RpcEndpointsHealth<()>,
+}
 
 impl StarknetRpc {
     pub fn new(rpc_url: Url) -> Self {
-        Self(JsonRpcClient::new(HttpTransport::new(rpc_url)))
+        Self { endpoints: Vec::new(), health: RpcEndpointsHealth::default() }.with_fallbacks([rpc_url])
+    }
+
+    /// Endpoints called along with the first one, round-robin, skipping the ones which failed recently.
+    pub fn with_fallbacks(mut self, rpc_urls: impl IntoIterator<Item = Url>) -> Self {
+        for rpc_url in rpc_urls {
+            self.endpoints.push((rpc_url.to_string(), JsonRpcClient::new(HttpTransport::new(rpc_url))));
+        }
+        self
+    }
+
+    /// Runs the call against the endpoints in turn, until one of them answers. Errors returned by the chain
+    /// itself, e.g. a reverted contract call, are returned without trying the other endpoints.
+    async fn with_failover<'a, T, F, Fut>(&'a self, call: F) -> Result<T, ProviderError>
+    where
+        F: Fn(&'a JsonRpcClient<HttpTransport>) -> Fut,
+        Fut: Future<Output = Result<T, ProviderError>>,
+    {
+        let mut last_error = None;
+        for (rpc_url, client) in self.endpoints_in_order() {
+            match call(client).await {
+                Err(e) if !matches!(e, ProviderError::StarknetError(_)) => {
+                    if self.endpoints.len() > 1 {
+                        tracing::warn!("⚠️ Starknet RPC endpoint failed, trying the next one: {:?}", e);
+                    }
+                    self.health.mark_failed(rpc_url, Instant::now());
+                    last_error = Some(e);
+                }
+                result => {
+                    self.health.mark_healthy(rpc_url);
+                    return result;
+                }
+            }
+        }
+        Err(last_error.expect("There is at least one endpoint"))
+    }
+
+    fn endpoints_in_order(&self) -> Vec<(&str, &JsonRpcClient<HttpTransport>)> {
+        let rpc_urls: Vec<&str> = self.endpoints.iter().map(|(rpc_url, _)| rpc_url.as_str()).collect();
+        self.health
+            .order((), &rpc_urls, Instant::now())
+            .into_iter()
+            .filter_map(|rpc_url| self.endpoints.iter().find(|(url, _)| url == rpc_url))
+            .map(|(rpc_url, client)| (rpc_url.as_str(), client))
+            .collect()
+    }
+}
+
+#[async_trait::async_trait]
+impl BlockCalls for StarknetRpc {
+    async fn block_number(&self) -> anyhow::Result<u64> {
+        self.with_failover(|client| client.block_number()).await.context("Fetching block number")
+    }
+
+    async fn block_timestamp(&self, block_number: u64) -> anyhow::Result<u64> {
+        let block = self
+            .with_failover(|client| client.get_block_with_tx_hashes(BlockId::Number(block_number)))
+            .await
+            .with_context(|| format!("Fetching block #{block_number}"))?;
+        match block {
+            MaybePendingBlockWithTxHashes::Block(block) => Ok(block.timestamp),
+            MaybePendingBlockWithTxHashes::PendingBlock(block) => Ok(block.timestamp),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Chain producing a block every 10 seconds.
+    struct TenSecondsBlocks {
+        current_block: u64,
+    }
+
+    #[async_trait::async_trait]
+    impl BlockCalls for TenSecondsBlocks {
+        async fn block_number(&self) -> anyhow::Result<u64> {
+            Ok(self.current_block)
+        }
+
+        async fn block_timestamp(&self, block_number: u64) -> anyhow::Result<u64> {
+            Ok(block_number * 10)
+        }
     }
 
-    pub async fn block_number(&self) -> anyhow::Result<u64> {
-        self.0.block_number().await.context("Fetching block number")
+    #[tokio::test]
+    async fn test_first_block_after() {
+        let chain = TenSecondsBlocks { current_block: 100 };
+        assert_eq!(chain.first_block_after(0).await.unwrap(), 0);
+        assert_eq!(chain.first_block_after(25).await.unwrap(), 3);
+        assert_eq!(chain.first_block_after(30).await.unwrap(), 3);
+        assert_eq!(chain.first_block_after(5_000).await.unwrap(), 100);
     }
 }
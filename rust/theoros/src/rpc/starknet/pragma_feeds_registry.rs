@@ -23,7 +23,7 @@ impl PragmaFeedsRegistryCalls for StarknetRpc {
             calldata: vec![],
         };
 
-        let raw_response = self.0.call(call, PENDING_BLOCK).await?;
+        let raw_response = self.with_failover(|client| client.call(&call, PENDING_BLOCK)).await?;
         Ok(raw_response.iter().skip(1).map(|x| x.to_hex_string()).collect())
     }
 }
@@ -0,0 +1,270 @@
+use std::{
+    future::Future,
+    time::{Duration, Instant},
+};
+
+use alloy::{hex::FromHex, primitives::Address};
+use anyhow::{Context, Result};
+use futures::future::join_all;
+use prometheus::Registry;
+use serde::{Deserialize, Serialize};
+use starknet::core::types::Felt;
+
+use pragma_utils::conversions::alloy::hex_str_to_u256;
+
+use crate::{
+    cli::TheorosCli,
+    rpc::{
+        evm::{failover, HyperlaneClient},
+        starknet::StarknetRpc,
+    },
+    services::metrics::TheorosMetrics,
+    storage::{FeedIdsStorage, TheorosStorage, ValidatorsFetchersStorage},
+    types::hyperlane::{DispatchEvent, DispatchUpdateInfos, FetchFromStorage, FromStarknetEventData},
+};
+
+/// Dispatch event decoded by the self-test, bundled in the binary.
+const DISPATCH_EVENT_FIXTURE: &str = include_str!("../fixtures/dispatch_event.json");
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Pass,
+    Fail,
+    /// The check doesn't apply to the configuration, e.g. no persisted state.
+    Skip,
+}
+
+/// Outcome of a single check of the self-test.
+#[derive(Debug, Clone, Serialize)]
+pub struct CheckResult {
+    pub name: String,
+    pub status: CheckStatus,
+    pub duration_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+/// Machine-readable report of `theoros selftest`.
+#[derive(Debug, Clone, Serialize)]
+pub struct SelftestReport {
+    /// Whether none of the checks failed.
+    pub passed: bool,
+    pub checks: Vec<CheckResult>,
+}
+
+impl SelftestReport {
+    fn new(checks: Vec<CheckResult>) -> Self {
+        let passed = checks.iter().all(|check| check.status != CheckStatus::Fail);
+        Self { passed, checks }
+    }
+
+    /// Human-readable summary of the report, one line per check.
+    pub fn summary(&self) -> String {
+        let mut lines: Vec<String> = self
+            .checks
+            .iter()
+            .map(|check| {
+                let status = match check.status {
+                    CheckStatus::Pass => "✅ PASS",
+                    CheckStatus::Fail => "❌ FAIL",
+                    CheckStatus::Skip => "⏭️ SKIP",
+                };
+                match &check.detail {
+                    Some(detail) => format!("{status} {} ({}ms): {detail}", check.name, check.duration_ms),
+                    None => format!("{status} {} ({}ms)", check.name, check.duration_ms),
+                }
+            })
+            .collect();
+        lines.push(if self.passed { "Self-test passed".to_owned() } else { "Self-test failed".to_owned() });
+        lines.join("\n")
+    }
+}
+
+/// Exercises each subsystem of Theoros against the configured environment:
+/// * decodes the bundled dispatch event fixture,
+/// * fetches the latest checkpoint of every announced validator,
+/// * calls the ISM of every enabled destination chain,
+/// * writes & reads the storage, and reads the persisted metrics counters if configured.
+pub async fn run(config: &TheorosCli) -> SelftestReport {
+    let mut checks = vec![check("decode_fixture", decode_fixture()).await];
+
+    let starknet_rpc = StarknetRpc::new(config.madara_rpc_url.clone());
+    let storage = TheorosStorage::from_rpc_state(
+        &starknet_rpc,
+        &config.pragma_feeds_registry_address,
+        &config.hyperlane_validator_announce_address,
+        ValidatorsFetchersStorage::default().with_retry_policy(crate::retry_policy(config)),
+    );
+    match timed(storage).await {
+        (Ok(storage), elapsed) => {
+            checks.push(passed("starknet_rpc", elapsed, None));
+            checks.extend(check_validators(&storage, config.validator_fetch_timeout).await);
+            checks.push(check("storage", storage_roundtrip(&storage)).await);
+        }
+        (Err(e), elapsed) => checks.push(failed("starknet_rpc", elapsed, e)),
+    }
+
+    checks.extend(check_isms(config).await);
+
+    let persisted_state = match &config.metrics_instance {
+        Some(instance) => check("persisted_state", persisted_metrics(config, instance)).await,
+        None => skipped("persisted_state", "METRICS_INSTANCE is not set"),
+    };
+    checks.push(persisted_state);
+
+    SelftestReport::new(checks)
+}
+
+/// Decodes the bundled fixture & ensures all its updates are understood.
+async fn decode_fixture() -> Result<Option<String>> {
+    let event = fixture_event()?;
+    let body = &event.message.body;
+    anyhow::ensure!(body.parse_failures.is_empty(), "Failed to parse updates: {:?}", body.parse_failures);
+    anyhow::ensure!(body.updates.len() == body.nb_updated as usize, "Missing updates");
+    anyhow::ensure!(body.updates.iter().all(|update| !update.is_opaque()), "Unknown feed types");
+    Ok(Some(format!("{} updates decoded", body.updates.len())))
+}
+
+fn fixture_event() -> Result<DispatchEvent> {
+    #[derive(Deserialize)]
+    struct Fixture {
+        data: Vec<String>,
+    }
+    let fixture: Fixture = serde_json::from_str(DISPATCH_EVENT_FIXTURE)?;
+    let data = fixture.data.iter().map(|felt| Felt::from_hex(felt)).collect::<Result<Vec<Felt>, _>>()?;
+    DispatchEvent::from_starknet_event_data(data)
+}
+
+/// Fetches the latest checkpoint of every validator, one check per validator.
+async fn check_validators(storage: &TheorosStorage, fetch_timeout: Duration) -> Vec<CheckResult> {
+    let mut fetchers: Vec<_> = storage.validators_fetchers().all().into_iter().collect();
+    if fetchers.is_empty() {
+        return vec![failed("validator_checkpoints", Duration::ZERO, anyhow::anyhow!("No validator announced"))];
+    }
+    fetchers.sort_by_key(|(validator, _)| *validator);
+
+    join_all(fetchers.into_iter().map(|(validator, fetcher)| async move {
+        let name = format!("validator_checkpoint:{:#x}", validator);
+        check(&name, async {
+            tokio::time::timeout(fetch_timeout, fetch_latest_checkpoint(fetcher.as_ref()))
+                .await
+                .with_context(|| format!("Timed out after {:?}", fetch_timeout))?
+        })
+        .await
+    }))
+    .await
+}
+
+async fn fetch_latest_checkpoint(fetcher: &(dyn FetchFromStorage + Send + Sync)) -> Result<Option<String>> {
+    let index = fetcher.fetch_latest_index().await?.context("No latest checkpoint index")?;
+    let checkpoint = fetcher.fetch(index).await?.with_context(|| format!("Checkpoint {index} not found"))?;
+    Ok(Some(format!("checkpoint {} of message {:#x}", index, checkpoint.value.message_id)))
+}
+
+/// Calls the ISM of every enabled chain, one check per chain.
+async fn check_isms(config: &TheorosCli) -> Vec<CheckResult> {
+    let mut chains: Vec<_> =
+        config.evm_config.chains().iter().filter(|(_, chain_config)| chain_config.status.enabled).collect();
+    chains.sort_by_key(|(chain_name, _)| chain_name.to_string());
+
+    join_all(chains.into_iter().map(|(chain_name, chain_config)| async move {
+        check(&format!("ism:{chain_name}"), async {
+            let address = Address::from_hex(&chain_config.hyperlane_address)?;
+            let validators = failover::with_failover(chain_name, chain_config, |rpc_url| async move {
+                HyperlaneClient::new(rpc_url, address).await.get_validators_with_index().await
+            })
+            .await?;
+            anyhow::ensure!(!validators.is_empty(), "No validators returned by the ISM");
+            Ok(Some(format!("{} validators", validators.len())))
+        })
+        .await
+    }))
+    .await
+}
+
+/// Writes the fixture update into the storage & reads it back. The storage is the one built for the
+/// self-test, so nothing is served from it.
+async fn storage_roundtrip(storage: &TheorosStorage) -> Result<Option<String>> {
+    let event = fixture_event()?;
+    let update = event.message.body.updates.first().context("Empty fixture")?;
+    let infos = DispatchUpdateInfos::new(&event, update);
+    let feed_id = hex_str_to_u256(&update.feed_id())?;
+
+    storage.latest_update_per_feed().add(feed_id, infos);
+    let read = storage.latest_update_per_feed().get(&feed_id).context("Update not found after being written")?;
+    anyhow::ensure!(read.nonce == event.message.header.nonce, "Read a different update than the written one");
+    Ok(None)
+}
+
+/// Reads the metrics counters persisted by the instance from the storage backend, ensuring they can be restored.
+async fn persisted_metrics(config: &TheorosCli, instance: &str) -> Result<Option<String>> {
+    let backend = config.storage_backend.build(1).await?;
+    let storage =
+        TheorosStorage::new(FeedIdsStorage::default(), ValidatorsFetchersStorage::default()).with_backend(backend);
+    let metrics = TheorosMetrics::register(&Registry::new())?;
+    metrics.restore(&storage, instance).await?;
+    storage.close().await;
+    Ok(Some(format!("{} calldata served", metrics.calldata_served.get())))
+}
+
+/// Runs a check, timing it. The check returns an optional detail on success.
+async fn check(name: &str, check: impl Future<Output = Result<Option<String>>>) -> CheckResult {
+    match timed(check).await {
+        (Ok(detail), elapsed) => passed(name, elapsed, detail),
+        (Err(e), elapsed) => failed(name, elapsed, e),
+    }
+}
+
+async fn timed<T>(future: impl Future<Output = T>) -> (T, Duration) {
+    let started_at = Instant::now();
+    let output = future.await;
+    (output, started_at.elapsed())
+}
+
+fn passed(name: &str, elapsed: Duration, detail: Option<String>) -> CheckResult {
+    CheckResult { name: name.to_owned(), status: CheckStatus::Pass, duration_ms: elapsed.as_millis() as u64, detail }
+}
+
+fn failed(name: &str, elapsed: Duration, error: anyhow::Error) -> CheckResult {
+    CheckResult {
+        name: name.to_owned(),
+        status: CheckStatus::Fail,
+        duration_ms: elapsed.as_millis() as u64,
+        detail: Some(format!("{error:#}")),
+    }
+}
+
+fn skipped(name: &str, reason: &str) -> CheckResult {
+    CheckResult { name: name.to_owned(), status: CheckStatus::Skip, duration_ms: 0, detail: Some(reason.to_owned()) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{FeedIdsStorage, ValidatorsFetchersStorage};
+
+    #[tokio::test]
+    async fn test_bundled_fixture_decodes() {
+        let detail = decode_fixture().await.unwrap();
+        assert_eq!(detail.as_deref(), Some("1 updates decoded"));
+    }
+
+    #[tokio::test]
+    async fn test_storage_roundtrip() {
+        let storage = TheorosStorage::new(FeedIdsStorage::default(), ValidatorsFetchersStorage::default());
+        storage_roundtrip(&storage).await.unwrap();
+    }
+
+    #[test]
+    fn test_report_fails_on_any_failed_check() {
+        let report = SelftestReport::new(vec![passed("a", Duration::ZERO, None), skipped("b", "not configured")]);
+        assert!(report.passed);
+        let report = SelftestReport::new(vec![
+            passed("a", Duration::ZERO, None),
+            failed("b", Duration::ZERO, anyhow::anyhow!("boom")),
+        ]);
+        assert!(!report.passed);
+        assert_eq!(serde_json::to_value(&report).unwrap()["checks"][1]["status"], "fail");
+    }
+}
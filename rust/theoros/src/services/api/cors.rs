@@ -0,0 +1,110 @@
+use std::time::Duration;
+
+use axum::{
+    extract::Request,
+    http::{
+        header::{self, HeaderName, RETRY_AFTER},
+        HeaderValue, Method,
+    },
+    middleware::Next,
+    response::Response,
+};
+use tower_http::cors::{AllowOrigin, Any, CorsLayer};
+
+use crate::constants::DEFAULT_CORS_MAX_AGE;
+use crate::services::api::request_id::REQUEST_ID_HEADER;
+
+/// Security headers set on every response, unless the handler already set them.
+const SECURITY_HEADERS: [(HeaderName, &str); 3] = [
+    (header::X_CONTENT_TYPE_OPTIONS, "nosniff"),
+    (header::X_FRAME_OPTIONS, "DENY"),
+    (header::REFERRER_POLICY, "no-referrer"),
+];
+
+/// Origins, methods & preflight caching allowed to the browsers calling the API.
+#[derive(Debug, Clone)]
+pub struct CorsConfig {
+    /// Origins allowed to call the API. `*` allows any origin.
+    pub allowed_origins: Vec<HeaderValue>,
+    pub allowed_methods: Vec<Method>,
+    /// How long browsers may cache the preflight responses.
+    pub max_age: Duration,
+}
+
+impl Default for CorsConfig {
+    fn default() -> Self {
+        Self {
+            allowed_origins: vec![HeaderValue::from_static("*")],
+            allowed_methods: vec![Method::GET, Method::POST],
+            max_age: DEFAULT_CORS_MAX_AGE,
+        }
+    }
+}
+
+impl CorsConfig {
+    pub fn layer(&self) -> CorsLayer {
+        let allow_origin = if self.allowed_origins.iter().any(|origin| origin == "*") {
+            AllowOrigin::any()
+        } else {
+            AllowOrigin::list(self.allowed_origins.iter().cloned())
+        };
+        CorsLayer::new()
+            .allow_origin(allow_origin)
+            .allow_methods(self.allowed_methods.clone())
+            .allow_headers(Any)
+            // Lets the rate limited & shed clients read when to retry, and any client the ID of its request.
+            .expose_headers([RETRY_AFTER, REQUEST_ID_HEADER])
+            .max_age(self.max_age)
+    }
+}
+
+/// Sets the standard security headers on the responses.
+pub async fn set_security_headers(request: Request, next: Next) -> Response {
+    let mut response = next.run(request).await;
+    let headers = response.headers_mut();
+    for (name, value) in SECURITY_HEADERS {
+        headers.entry(name).or_insert(HeaderValue::from_static(value));
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{body::Body, routing::get, Router};
+    use tower::ServiceExt;
+
+    use super::*;
+
+    fn app(cors: CorsConfig) -> Router {
+        Router::new().route("/v1/chains", get(|| async { "ok" })).layer(cors.layer())
+    }
+
+    fn preflight(origin: &str) -> Request {
+        Request::builder()
+            .method(Method::OPTIONS)
+            .uri("/v1/chains")
+            .header(header::ORIGIN, origin)
+            .header(header::ACCESS_CONTROL_REQUEST_METHOD, "GET")
+            .body(Body::empty())
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_cors_allowed_origins() {
+        let cors = CorsConfig {
+            allowed_origins: vec![HeaderValue::from_static("https://dashboard.pragma.build")],
+            ..CorsConfig::default()
+        };
+
+        let response = app(cors.clone()).oneshot(preflight("https://dashboard.pragma.build")).await.unwrap();
+        let headers = response.headers();
+        assert_eq!(headers[header::ACCESS_CONTROL_ALLOW_ORIGIN], "https://dashboard.pragma.build");
+        assert_eq!(headers[header::ACCESS_CONTROL_MAX_AGE], DEFAULT_CORS_MAX_AGE.as_secs().to_string());
+
+        let response = app(cors).oneshot(preflight("https://evil.example")).await.unwrap();
+        assert!(response.headers().get(header::ACCESS_CONTROL_ALLOW_ORIGIN).is_none());
+
+        let response = app(CorsConfig::default()).oneshot(preflight("https://evil.example")).await.unwrap();
+        assert_eq!(response.headers()[header::ACCESS_CONTROL_ALLOW_ORIGIN], "*");
+    }
+}
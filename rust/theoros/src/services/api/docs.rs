@@ -1,10 +1,13 @@
 use anyhow::Result;
 use serde_json::to_string_pretty;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use utoipa::OpenApi;
 use utoipauto::utoipauto;
 
-#[utoipauto(paths = "./theoros/src, ./pragma-feeds/src")]
+/// Name of the snapshot of the spec, at the root of the crate.
+pub const OPENAPI_SNAPSHOT: &str = "openapi.json";
+
+#[utoipauto(paths = "./theoros/src, ./pragma-feeds/src from pragma_feeds")]
 #[derive(OpenApi)]
 #[openapi(
     tags(
@@ -14,18 +17,47 @@ use utoipauto::utoipauto;
 pub struct ApiDoc;
 
 impl ApiDoc {
-    #[allow(dead_code)]
-    pub fn generate_openapi_json(output_path: PathBuf) -> Result<()> {
-        let openapi = ApiDoc::openapi();
-        let json = to_string_pretty(&openapi)?;
+    /// The spec served on `/v1/openapi.json`, as written in the snapshot.
+    pub fn openapi_json() -> Result<String> {
+        let mut json = to_string_pretty(&ApiDoc::openapi())?;
+        json.push('\n');
+        Ok(json)
+    }
 
-        if let Some(parent) = output_path.parent() {
-            std::fs::create_dir_all(parent)?;
-        }
-        let file_path = output_path.join("openapi.json");
+    /// Writes the snapshot of the spec into the directory.
+    pub fn generate_openapi_json(output_path: PathBuf) -> Result<()> {
+        std::fs::create_dir_all(&output_path)?;
+        let file_path = output_path.join(OPENAPI_SNAPSHOT);
         tracing::info!("Saving OpenAPI specs to {} ....", file_path.as_path().display());
-        std::fs::write(file_path, json)?;
+        std::fs::write(file_path, Self::openapi_json()?)?;
         tracing::info!("OpenAPI specs saved!");
         Ok(())
     }
+
+    /// Whether the snapshot in the directory is the spec of the API, i.e. the snapshot is up to date.
+    pub fn is_snapshot_up_to_date(output_path: &Path) -> Result<bool> {
+        let snapshot = std::fs::read_to_string(output_path.join(OPENAPI_SNAPSHOT))?;
+        Ok(snapshot == Self::openapi_json()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_openapi_snapshot_is_up_to_date() {
+        let crate_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+        assert!(
+            ApiDoc::is_snapshot_up_to_date(crate_dir).unwrap(),
+            "The API changed, update the spec with `cargo run -p theoros --bin openapi`"
+        );
+
+        let spec: serde_json::Value = serde_json::from_str(&ApiDoc::openapi_json().unwrap()).unwrap();
+        for path in ["/v1/calldata", "/v1/data_feeds/{feed_id}", "/v1/ws", "/v1/graphql", "/v1/admin/chains", "/health"]
+        {
+            assert!(spec["paths"].get(path).is_some(), "{path} is missing from the spec");
+        }
+        assert!(spec["components"]["schemas"].get("ProblemDetails").is_some());
+    }
 }
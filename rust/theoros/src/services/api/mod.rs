@@ -1,57 +1,268 @@
+pub mod cors;
 pub mod docs;
+pub mod priority_lanes;
+pub mod proxy_protocol;
+pub mod rate_limit;
+pub mod redaction;
+pub mod request_id;
+pub mod request_metrics;
 pub mod router;
+pub mod versioning;
 
-use std::net::SocketAddr;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
 
 use anyhow::{Context, Result};
+use axum::{
+    extract::{ConnectInfo, Request},
+    middleware, Router,
+};
+use cors::{set_security_headers, CorsConfig};
 use docs::ApiDoc;
+use hyper_util::{
+    rt::{TokioExecutor, TokioIo},
+    server::conn::auto,
+    service::TowerToHyperService,
+};
+use priority_lanes::{prioritize_requests, PriorityLanes};
+use redaction::redact_error_responses;
+use request_id::{assign_request_id, RequestId};
 use router::api_router;
-use tokio::{net::TcpListener, task::JoinSet};
-use tower_http::{
-    cors::CorsLayer,
-    trace::{DefaultMakeSpan, TraceLayer},
+use socket2::{Domain, Protocol, Socket, Type};
+use tokio::{
+    net::{TcpListener, TcpStream},
+    task::JoinSet,
 };
+use tokio_util::{sync::CancellationToken, task::TaskTracker};
+use tower::ServiceExt;
+use tower_http::trace::TraceLayer;
 
 use pragma_utils::services::Service;
 
-use crate::AppState;
+use crate::{
+    constants::{PROXY_HEADER_TIMEOUT, TCP_LISTEN_BACKLOG},
+    extractors::client_ip::{resolve_client_ip, ClientIp, TrustedProxies},
+    handlers::websocket::fanout::FeedUpdatesFanout,
+    AppState,
+};
 
 pub struct ApiService {
     state: AppState,
-    host: String,
-    port: u16,
+    address: SocketAddr,
+    proxy_protocol: bool,
+    trusted_proxies: TrustedProxies,
+    priority_lanes: Option<Arc<PriorityLanes>>,
+    cors: CorsConfig,
 }
 
 impl ApiService {
-    pub fn new(state: AppState, host: &str, port: u16) -> Self {
-        Self { state, host: host.to_owned(), port }
+    pub fn new(state: AppState, host: IpAddr, port: u16) -> Self {
+        Self {
+            state,
+            address: SocketAddr::new(host, port),
+            proxy_protocol: false,
+            trusted_proxies: Vec::new().into(),
+            priority_lanes: None,
+            cors: CorsConfig::default(),
+        }
+    }
+
+    /// Expects every connection to start with a PROXY protocol header, announcing the client address.
+    pub fn with_proxy_protocol(mut self, proxy_protocol: bool) -> Self {
+        self.proxy_protocol = proxy_protocol;
+        self
+    }
+
+    /// Proxies allowed to set the client address through the `X-Forwarded-For` header.
+    pub fn with_trusted_proxies(mut self, trusted_proxies: Vec<IpAddr>) -> Self {
+        self.trusted_proxies = trusted_proxies.into();
+        self
+    }
+
+    /// Limits the concurrent standard requests, premium requests bypassing the limit.
+    pub fn with_priority_lanes(mut self, priority_lanes: Option<PriorityLanes>) -> Self {
+        self.priority_lanes = priority_lanes.map(Arc::new);
+        self
+    }
+
+    /// Origins & methods allowed to the browsers calling the API. Defaults to any origin.
+    pub fn with_cors(mut self, cors: CorsConfig) -> Self {
+        self.cors = cors;
+        self
     }
 }
 
 #[async_trait::async_trait]
 impl Service for ApiService {
     async fn start(&mut self, join_set: &mut JoinSet<Result<()>>) -> anyhow::Result<()> {
-        // ApiDoc::generate_openapi_json("./theoros".into())?;
-
-        let host = self.host.to_owned();
-        let port = self.port;
+        let address = self.address;
+        let proxy_protocol = self.proxy_protocol;
+        let trusted_proxies = self.trusted_proxies.clone();
+        let priority_lanes = self.priority_lanes.clone();
+        let cors = self.cors.layer();
         let state = self.state.clone();
+        let shutdown = self.state.shutdown.clone();
+        let ws_connections = self.state.ws.connections.clone();
 
+        join_set.spawn(FeedUpdatesFanout::run(state.clone()));
         join_set.spawn(async move {
-            let address = format!("{}:{}", host, port);
-            let socket_addr: SocketAddr = address.parse()?;
-            let listener = TcpListener::bind(socket_addr).await?;
-
-            let app = api_router::<ApiDoc>(state.clone())
-                .with_state(state)
-                .layer(TraceLayer::new_for_http().make_span_with(DefaultMakeSpan::default().include_headers(true)))
-                .layer(CorsLayer::permissive());
-
-            tracing::info!("🧩 API server started at http://{}", socket_addr);
-            axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
-                .await
-                .context("😱 API server stopped!")
+            let listener = bind_listener(address)?;
+
+            let mut app = api_router::<ApiDoc>(state.clone()).with_state(state);
+            if let Some(priority_lanes) = priority_lanes {
+                app = app.layer(middleware::from_fn_with_state(priority_lanes, prioritize_requests));
+            }
+            let app = app
+                .layer(middleware::from_fn(redact_error_responses))
+                .layer(TraceLayer::new_for_http().make_span_with(make_request_span))
+                .layer(middleware::from_fn(assign_request_id))
+                .layer(cors)
+                .layer(middleware::from_fn(set_security_headers))
+                .layer(middleware::from_fn_with_state(trusted_proxies, resolve_client_ip));
+
+            tracing::info!("🧩 API server started at http://{}", address);
+            if proxy_protocol {
+                serve_with_proxy_protocol(listener, app, shutdown).await;
+            } else {
+                axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
+                    .with_graceful_shutdown(shutdown.cancelled_owned())
+                    .await
+                    .context("😱 API server stopped!")?;
+            }
+            // The WebSocket connections were upgraded out of the server, they end once sent a close frame.
+            ws_connections.close();
+            ws_connections.wait().await;
+            tracing::info!("🧩 API server stopped, in-flight requests answered");
+            Ok(())
         });
         Ok(())
     }
 }
+
+/// Binds the API listener. Binding an IPv6 address also accepts IPv4 connections, so `::`
+/// listens on both stacks.
+fn bind_listener(address: SocketAddr) -> Result<TcpListener> {
+    let socket = Socket::new(Domain::for_address(address), Type::STREAM, Some(Protocol::TCP))?;
+    if address.is_ipv6() {
+        socket.set_only_v6(false)?;
+    }
+    socket.set_reuse_address(true)?;
+    socket.set_nonblocking(true)?;
+    socket.bind(&address.into()).with_context(|| format!("Binding API server on {address}"))?;
+    socket.listen(TCP_LISTEN_BACKLOG)?;
+    Ok(TcpListener::from_std(socket.into())?)
+}
+
+/// Same as [`DefaultMakeSpan`](tower_http::trace::DefaultMakeSpan) including the headers, with the client IP
+/// & the request ID.
+fn make_request_span(request: &Request) -> tracing::Span {
+    let client_ip = request.extensions().get::<ClientIp>().map(|ClientIp(ip)| ip.to_string());
+    let request_id = request.extensions().get::<RequestId>().map(|RequestId(id)| id.as_str());
+    tracing::debug_span!(
+        "request",
+        method = %request.method(),
+        uri = %request.uri(),
+        version = ?request.version(),
+        headers = ?request.headers(),
+        client_ip = client_ip.as_deref().unwrap_or_default(),
+        request_id = request_id.unwrap_or_default(),
+    )
+}
+
+/// Serves the connections of the listener, reading the client address from their PROXY protocol header.
+/// On shutdown, stops accepting connections & returns once the in-flight requests are answered.
+async fn serve_with_proxy_protocol(listener: TcpListener, app: Router, shutdown: CancellationToken) {
+    let connections = TaskTracker::new();
+    loop {
+        let accepted = tokio::select! {
+            accepted = listener.accept() => accepted,
+            _ = shutdown.cancelled() => break,
+        };
+        let (stream, peer) = match accepted {
+            Ok(connection) => connection,
+            Err(e) => {
+                tracing::error!("😱 Failed to accept connection: {:?}", e);
+                continue;
+            }
+        };
+        connections.spawn(serve_proxied_connection(stream, peer, app.clone(), shutdown.clone()));
+    }
+    drop(listener);
+    connections.close();
+    connections.wait().await;
+}
+
+async fn serve_proxied_connection(mut stream: TcpStream, peer: SocketAddr, app: Router, shutdown: CancellationToken) {
+    let source = match tokio::time::timeout(PROXY_HEADER_TIMEOUT, proxy_protocol::read_proxy_header(&mut stream)).await
+    {
+        Ok(Ok(source)) => source.unwrap_or(peer),
+        Ok(Err(e)) => {
+            tracing::debug!("Rejected connection from {}: {:?}", peer, e);
+            return;
+        }
+        Err(_) => {
+            tracing::debug!("Rejected connection from {}: PROXY header timed out", peer);
+            return;
+        }
+    };
+
+    let service = tower::service_fn(move |mut request: Request<_>| {
+        request.extensions_mut().insert(ConnectInfo(source));
+        app.clone().oneshot(request)
+    });
+    let builder = auto::Builder::new(TokioExecutor::new());
+    let connection = builder.serve_connection_with_upgrades(TokioIo::new(stream), TowerToHyperService::new(service));
+    tokio::pin!(connection);
+    let served = tokio::select! {
+        served = connection.as_mut() => served,
+        _ = shutdown.cancelled() => {
+            // Answers the request in flight, if any, then closes the connection.
+            connection.as_mut().graceful_shutdown();
+            connection.await
+        }
+    };
+    if let Err(e) = served {
+        tracing::debug!("Connection from {} closed with error: {:?}", source, e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use axum::routing::get;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_in_flight_requests_are_answered_on_shutdown() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let app = Router::new().route(
+            "/slow",
+            get(|| async {
+                tokio::time::sleep(Duration::from_millis(200)).await;
+                "done"
+            }),
+        );
+        let shutdown = CancellationToken::new();
+        let server = tokio::spawn(serve_with_proxy_protocol(listener, app, shutdown.clone()));
+
+        let mut stream = TcpStream::connect(address).await.unwrap();
+        stream
+            .write_all(b"PROXY TCP4 203.0.113.7 127.0.0.1 4000 80\r\nGET /slow HTTP/1.1\r\nHost: theoros\r\n\r\n")
+            .await
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        shutdown.cancel();
+
+        // The request is answered, then the connection closed.
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 200"), "{response}");
+        assert!(response.ends_with("done"), "{response}");
+        tokio::time::timeout(Duration::from_secs(1), server).await.unwrap().unwrap();
+        assert!(TcpStream::connect(address).await.is_err());
+    }
+}
@@ -0,0 +1,206 @@
+use std::collections::HashSet;
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+use std::time::{Duration, Instant};
+
+use axum::{
+    extract::{Request, State},
+    http::HeaderName,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use prometheus::IntGauge;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::{
+    constants::{DEFAULT_MAX_QUEUED_REQUESTS, DEFAULT_REQUEST_QUEUE_TIMEOUT},
+    errors::TheorosError,
+    services::metrics::TheorosMetrics,
+};
+
+/// Header carrying the API key of the integrator.
+pub const API_KEY_HEADER: HeaderName = HeaderName::from_static("x-api-key");
+
+/// Lane a request is served through.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lane {
+    /// Requests authenticated with a premium API key. They bypass the concurrency limit & are never shed.
+    Premium,
+    /// Every other request, subject to the concurrency limit & shed first under load.
+    Standard,
+}
+
+impl Lane {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Lane::Premium => "premium",
+            Lane::Standard => "standard",
+        }
+    }
+}
+
+/// Two-tier admission of the API requests.
+///
+/// Standard requests are limited to `max_concurrent` in flight. Above it, they wait in a
+/// queue of at most `max_queued` requests for up to `queue_timeout` and are shed past that.
+/// Premium requests skip the queue altogether, so they keep being served while the standard
+/// lane is saturated.
+pub struct PriorityLanes {
+    premium_api_keys: HashSet<String>,
+    standard_permits: Arc<Semaphore>,
+    max_queued: usize,
+    queued: AtomicUsize,
+    queue_timeout: Duration,
+    metrics: Arc<TheorosMetrics>,
+}
+
+impl PriorityLanes {
+    pub fn new(max_concurrent: usize, metrics: Arc<TheorosMetrics>) -> Self {
+        Self {
+            premium_api_keys: HashSet::new(),
+            standard_permits: Arc::new(Semaphore::new(max_concurrent)),
+            max_queued: DEFAULT_MAX_QUEUED_REQUESTS,
+            queued: AtomicUsize::new(0),
+            queue_timeout: DEFAULT_REQUEST_QUEUE_TIMEOUT,
+            metrics,
+        }
+    }
+
+    pub fn with_premium_api_keys(mut self, premium_api_keys: impl IntoIterator<Item = String>) -> Self {
+        self.premium_api_keys = premium_api_keys.into_iter().collect();
+        self
+    }
+
+    pub fn with_max_queued(mut self, max_queued: usize) -> Self {
+        self.max_queued = max_queued;
+        self
+    }
+
+    pub fn with_queue_timeout(mut self, queue_timeout: Duration) -> Self {
+        self.queue_timeout = queue_timeout;
+        self
+    }
+
+    /// Returns the lane of the request, based on its API key.
+    pub fn lane_of(&self, request: &Request) -> Lane {
+        let api_key = request.headers().get(API_KEY_HEADER).and_then(|value| value.to_str().ok());
+        match api_key {
+            Some(key) if self.premium_api_keys.contains(key) => Lane::Premium,
+            _ => Lane::Standard,
+        }
+    }
+
+    /// Waits for a slot of the standard lane. Returns `None` if the request must be shed.
+    async fn admit_standard(&self) -> Option<OwnedSemaphorePermit> {
+        if let Ok(permit) = self.standard_permits.clone().try_acquire_owned() {
+            return Some(permit);
+        }
+
+        // Reserve a place in the queue, shedding the request if it is full.
+        let reserved = self.queued.fetch_update(Ordering::AcqRel, Ordering::Acquire, |queued| {
+            (queued < self.max_queued).then_some(queued + 1)
+        });
+        if reserved.is_err() {
+            return None;
+        }
+
+        let permit = tokio::time::timeout(self.queue_timeout, self.standard_permits.clone().acquire_owned()).await;
+        self.queued.fetch_sub(1, Ordering::AcqRel);
+        permit.ok().and_then(Result::ok)
+    }
+}
+
+/// Admits the request through its lane, shedding standard requests when the server is saturated.
+pub async fn prioritize_requests(State(lanes): State<Arc<PriorityLanes>>, request: Request, next: Next) -> Response {
+    // Health checks must keep answering under load.
+    if request.uri().path() == "/health" {
+        return next.run(request).await;
+    }
+
+    let lane = lanes.lane_of(&request);
+    let metrics = &lanes.metrics;
+    metrics.api_requests.with_label_values(&[lane.as_str()]).inc();
+
+    let queued_at = Instant::now();
+    let _permit = match lane {
+        Lane::Premium => None,
+        Lane::Standard => match lanes.admit_standard().await {
+            Some(permit) => Some(permit),
+            None => {
+                metrics.api_requests_shed.with_label_values(&[lane.as_str()]).inc();
+                return TheorosError::Overloaded.into_response();
+            }
+        },
+    };
+    metrics.api_queue_wait_seconds.with_label_values(&[lane.as_str()]).observe(queued_at.elapsed().as_secs_f64());
+
+    let _in_flight = InFlightGuard::new(metrics.api_requests_in_flight.with_label_values(&[lane.as_str()]));
+    next.run(request).await
+}
+
+/// Counts a request as in flight until dropped, including when the client disconnects early.
+struct InFlightGuard(IntGauge);
+
+impl InFlightGuard {
+    fn new(gauge: IntGauge) -> Self {
+        gauge.inc();
+        Self(gauge)
+    }
+}
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        self.0.dec();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use prometheus::Registry;
+
+    use super::*;
+
+    fn lanes(max_concurrent: usize, max_queued: usize) -> PriorityLanes {
+        let metrics = Arc::new(TheorosMetrics::register(&Registry::new()).unwrap());
+        PriorityLanes::new(max_concurrent, metrics)
+            .with_premium_api_keys([String::from("premium-key")])
+            .with_max_queued(max_queued)
+            .with_queue_timeout(Duration::from_millis(50))
+    }
+
+    #[test]
+    fn test_lane_of_request() {
+        let lanes = lanes(1, 1);
+        let request = |key: Option<&str>| {
+            let mut builder = Request::builder().uri("/v1/calldata");
+            if let Some(key) = key {
+                builder = builder.header(API_KEY_HEADER, key);
+            }
+            builder.body(axum::body::Body::empty()).unwrap()
+        };
+        assert_eq!(lanes.lane_of(&request(Some("premium-key"))), Lane::Premium);
+        assert_eq!(lanes.lane_of(&request(Some("other-key"))), Lane::Standard);
+        assert_eq!(lanes.lane_of(&request(None)), Lane::Standard);
+    }
+
+    #[tokio::test]
+    async fn test_standard_requests_are_queued_then_shed() {
+        let lanes = lanes(1, 1);
+        let in_flight = lanes.admit_standard().await.expect("a slot is free");
+
+        // The queue is empty: the request waits for a slot until the queue timeout.
+        assert!(lanes.admit_standard().await.is_none());
+
+        // The queue is full: the request is shed right away.
+        lanes.queued.store(1, Ordering::Release);
+        let started_at = Instant::now();
+        assert!(lanes.admit_standard().await.is_none());
+        assert!(started_at.elapsed() < Duration::from_millis(50));
+        lanes.queued.store(0, Ordering::Release);
+
+        drop(in_flight);
+        assert!(lanes.admit_standard().await.is_some());
+    }
+}
@@ -0,0 +1,116 @@
+//! Parsing of the PROXY protocol header sent by L4 load balancers before the proxied connection,
+//! see https://www.haproxy.org/download/2.9/doc/proxy-protocol.txt
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+
+use anyhow::{bail, ensure, Context, Result};
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+const V1_PREFIX: &[u8] = b"PROXY ";
+const V1_MAX_LENGTH: usize = 107;
+const V2_SIGNATURE: [u8; 12] = [0x0D, 0x0A, 0x0D, 0x0A, 0x00, 0x0D, 0x0A, 0x51, 0x55, 0x49, 0x54, 0x0A];
+
+const V2_COMMAND_LOCAL: u8 = 0x0;
+const V2_COMMAND_PROXY: u8 = 0x1;
+const V2_FAMILY_TCP4: u8 = 0x11;
+const V2_FAMILY_TCP6: u8 = 0x21;
+
+/// Reads the PROXY protocol (v1 or v2) header at the start of the stream.
+///
+/// Returns the source address of the proxied connection, or `None` when the proxy doesn't
+/// forward any (health checks, unknown protocols...).
+pub async fn read_proxy_header<S: AsyncRead + Unpin>(stream: &mut S) -> Result<Option<SocketAddr>> {
+    let mut signature = [0u8; V2_SIGNATURE.len()];
+    stream.read_exact(&mut signature).await.context("Reading PROXY header")?;
+
+    if signature == V2_SIGNATURE {
+        read_v2_header(stream).await
+    } else if signature.starts_with(V1_PREFIX) {
+        read_v1_header(stream, signature.to_vec()).await
+    } else {
+        bail!("Missing PROXY header")
+    }
+}
+
+async fn read_v1_header<S: AsyncRead + Unpin>(stream: &mut S, mut header: Vec<u8>) -> Result<Option<SocketAddr>> {
+    // The header is short & only sent once per connection: reading it byte per byte avoids
+    // consuming the beginning of the proxied payload.
+    while !header.ends_with(b"\r\n") {
+        ensure!(header.len() < V1_MAX_LENGTH, "PROXY v1 header is too long");
+        header.push(stream.read_u8().await.context("Reading PROXY v1 header")?);
+    }
+    parse_v1_header(&header[..header.len() - 2])
+}
+
+fn parse_v1_header(header: &[u8]) -> Result<Option<SocketAddr>> {
+    let header = std::str::from_utf8(header).context("PROXY v1 header is not valid UTF-8")?;
+    let mut parts = header.split(' ').skip(1);
+    match parts.next() {
+        Some("TCP4") | Some("TCP6") => {
+            let source: IpAddr = parts.next().context("Missing source address")?.parse()?;
+            let _destination: IpAddr = parts.next().context("Missing destination address")?.parse()?;
+            let source_port: u16 = parts.next().context("Missing source port")?.parse()?;
+            Ok(Some(SocketAddr::new(source, source_port)))
+        }
+        Some("UNKNOWN") => Ok(None),
+        protocol => bail!("Unsupported PROXY v1 protocol: {:?}", protocol),
+    }
+}
+
+async fn read_v2_header<S: AsyncRead + Unpin>(stream: &mut S) -> Result<Option<SocketAddr>> {
+    let version_command = stream.read_u8().await?;
+    let family = stream.read_u8().await?;
+    let length = stream.read_u16().await? as usize;
+    let mut addresses = vec![0u8; length];
+    stream.read_exact(&mut addresses).await.context("Reading PROXY v2 addresses")?;
+
+    ensure!(version_command >> 4 == 2, "Unsupported PROXY protocol version: {}", version_command >> 4);
+    match version_command & 0x0F {
+        V2_COMMAND_LOCAL => Ok(None),
+        V2_COMMAND_PROXY => parse_v2_addresses(family, &addresses),
+        command => bail!("Unsupported PROXY v2 command: {}", command),
+    }
+}
+
+fn parse_v2_addresses(family: u8, addresses: &[u8]) -> Result<Option<SocketAddr>> {
+    let (source, ports): (IpAddr, &[u8]) = match family {
+        V2_FAMILY_TCP4 => {
+            ensure!(addresses.len() >= 12, "Truncated PROXY v2 IPv4 addresses");
+            let source: [u8; 4] = addresses[..4].try_into()?;
+            (Ipv4Addr::from(source).into(), &addresses[8..])
+        }
+        V2_FAMILY_TCP6 => {
+            ensure!(addresses.len() >= 36, "Truncated PROXY v2 IPv6 addresses");
+            let source: [u8; 16] = addresses[..16].try_into()?;
+            (Ipv6Addr::from(source).into(), &addresses[32..])
+        }
+        _ => return Ok(None),
+    };
+    let source_port = u16::from_be_bytes([ports[0], ports[1]]);
+    Ok(Some(SocketAddr::new(source, source_port)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_read_proxy_header() {
+        let mut v1: &[u8] = b"PROXY TCP6 2001:db8::1 2001:db8::2 51000 443\r\nGET /";
+        let source = read_proxy_header(&mut v1).await.unwrap();
+        assert_eq!(source, Some("[2001:db8::1]:51000".parse().unwrap()));
+        assert_eq!(v1, b"GET /");
+
+        let mut v2 = V2_SIGNATURE.to_vec();
+        v2.extend_from_slice(&[0x21, V2_FAMILY_TCP4, 0, 12, 203, 0, 113, 7, 10, 0, 0, 1, 0xC3, 0x50, 0x01, 0xBB]);
+        v2.extend_from_slice(b"GET /");
+        let mut v2 = v2.as_slice();
+        let source = read_proxy_header(&mut v2).await.unwrap();
+        assert_eq!(source, Some("203.0.113.7:50000".parse().unwrap()));
+        assert_eq!(v2, b"GET /");
+
+        let mut local: &[u8] = b"PROXY UNKNOWN\r\n";
+        assert_eq!(read_proxy_header(&mut local).await.unwrap(), None);
+        let mut missing: &[u8] = b"GET / HTTP/1.1\r\n";
+        assert!(read_proxy_header(&mut missing).await.is_err());
+    }
+}
@@ -0,0 +1,219 @@
+use std::collections::HashSet;
+use std::net::IpAddr;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use dashmap::DashMap;
+
+use crate::{
+    constants::{DEFAULT_RATE_LIMIT_WINDOW, RATE_LIMIT_MAX_TRACKED_CLIENTS},
+    errors::TheorosError,
+    extractors::client_ip::ClientIp,
+    services::{api::priority_lanes::API_KEY_HEADER, metrics::TheorosMetrics},
+};
+
+/// Client a request is accounted to.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum RateLimitKey {
+    ApiKey(String),
+    Ip(IpAddr),
+}
+
+impl RateLimitKey {
+    fn kind(&self) -> &'static str {
+        match self {
+            RateLimitKey::ApiKey(_) => "api_key",
+            RateLimitKey::Ip(_) => "ip",
+        }
+    }
+}
+
+/// Requests left to a client, refilled continuously over the window.
+#[derive(Debug, Clone, Copy)]
+struct TokenBucket {
+    tokens: f64,
+    updated_at: Instant,
+}
+
+/// Requests allowed to each client over a window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimits {
+    /// Requests allowed per window to each client IP. Unlimited when `None`.
+    pub per_ip: Option<u32>,
+    /// Requests allowed per window to each known API key. Unlimited when `None`.
+    pub per_api_key: Option<u32>,
+    pub window: Duration,
+}
+
+impl Default for RateLimits {
+    fn default() -> Self {
+        Self { per_ip: None, per_api_key: None, window: DEFAULT_RATE_LIMIT_WINDOW }
+    }
+}
+
+impl RateLimits {
+    /// Whether any limit is configured.
+    pub fn is_enabled(&self) -> bool {
+        self.per_ip.is_some() || self.per_api_key.is_some()
+    }
+}
+
+/// Limits the requests of each client to a number per window, with bursts up to that number.
+///
+/// Requests carrying one of the known API keys are accounted to their key, every other request to its
+/// client IP. Unknown keys are ignored, so a client can't escape its IP limit by sending random keys.
+/// The limits can be changed at runtime, the clients keeping the requests left to them.
+pub struct RateLimiter {
+    limits: RwLock<RateLimits>,
+    api_keys: HashSet<String>,
+    buckets: DashMap<RateLimitKey, TokenBucket>,
+    metrics: Arc<TheorosMetrics>,
+}
+
+impl RateLimiter {
+    /// A rate limiter without any limit.
+    pub fn new(metrics: Arc<TheorosMetrics>) -> Self {
+        Self { limits: RwLock::new(RateLimits::default()), api_keys: HashSet::new(), buckets: DashMap::new(), metrics }
+    }
+
+    pub fn with_limits(self, limits: RateLimits) -> Self {
+        self.set_limits(limits);
+        self
+    }
+
+    pub fn with_api_keys(mut self, api_keys: impl IntoIterator<Item = String>) -> Self {
+        self.api_keys = api_keys.into_iter().collect();
+        self
+    }
+
+    pub fn limits(&self) -> RateLimits {
+        *self.limits.read().expect("Poisoned rate limits")
+    }
+
+    pub fn set_limits(&self, limits: RateLimits) {
+        *self.limits.write().expect("Poisoned rate limits") = limits;
+    }
+
+    fn key_of(&self, request: &Request) -> Option<RateLimitKey> {
+        let api_key = request.headers().get(API_KEY_HEADER).and_then(|value| value.to_str().ok());
+        match api_key {
+            Some(key) if self.api_keys.contains(key) => Some(RateLimitKey::ApiKey(key.to_owned())),
+            _ => request.extensions().get::<ClientIp>().map(|ClientIp(ip)| RateLimitKey::Ip(*ip)),
+        }
+    }
+
+    /// Takes a request from the bucket of the client. Returns the time to wait for the next request
+    /// to be allowed if the client exhausted its limit.
+    fn acquire(&self, key: RateLimitKey, now: Instant) -> Result<(), Duration> {
+        let limits = self.limits();
+        let limit = match key {
+            RateLimitKey::ApiKey(_) => limits.per_api_key,
+            RateLimitKey::Ip(_) => limits.per_ip,
+        };
+        let Some(limit) = limit else {
+            return Ok(());
+        };
+        let capacity = f64::from(limit);
+        let refill_per_sec = capacity / limits.window.as_secs_f64();
+
+        if self.buckets.len() >= RATE_LIMIT_MAX_TRACKED_CLIENTS {
+            self.prune(now, limits.window);
+        }
+        let mut bucket = self.buckets.entry(key).or_insert(TokenBucket { tokens: capacity, updated_at: now });
+        let elapsed = now.saturating_duration_since(bucket.updated_at).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * refill_per_sec).min(capacity);
+        bucket.updated_at = now;
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - bucket.tokens) / refill_per_sec))
+        }
+    }
+
+    /// Forgets the clients whose bucket refilled since their last request: they'd start full anyway.
+    fn prune(&self, now: Instant, window: Duration) {
+        self.buckets.retain(|_, bucket| now.saturating_duration_since(bucket.updated_at) < window);
+    }
+}
+
+/// Rejects the requests of the clients over their limit with a `429 Too Many Requests`, telling them
+/// when to retry through the `Retry-After` header.
+pub async fn rate_limit_requests(State(limiter): State<Arc<RateLimiter>>, request: Request, next: Next) -> Response {
+    if !limiter.limits().is_enabled() {
+        return next.run(request).await;
+    }
+    let Some(key) = limiter.key_of(&request) else {
+        return next.run(request).await;
+    };
+    let kind = key.kind();
+    if let Err(retry_after) = limiter.acquire(key, Instant::now()) {
+        limiter.metrics.api_requests_rate_limited.with_label_values(&[kind]).inc();
+        return TheorosError::RateLimited(retry_after).into_response();
+    }
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv4Addr;
+
+    use prometheus::Registry;
+
+    use super::*;
+
+    fn limiter() -> RateLimiter {
+        let metrics = Arc::new(TheorosMetrics::register(&Registry::new()).unwrap());
+        RateLimiter::new(metrics)
+            .with_limits(RateLimits { per_ip: Some(2), per_api_key: Some(4), window: Duration::from_secs(10) })
+            .with_api_keys([String::from("known-key")])
+    }
+
+    fn request(api_key: Option<&str>) -> Request {
+        let mut builder = Request::builder().uri("/v1/calldata");
+        if let Some(key) = api_key {
+            builder = builder.header(API_KEY_HEADER, key);
+        }
+        let mut request = builder.body(axum::body::Body::empty()).unwrap();
+        request.extensions_mut().insert(ClientIp(IpAddr::V4(Ipv4Addr::LOCALHOST)));
+        request
+    }
+
+    #[test]
+    fn test_key_of_request() {
+        let limiter = limiter();
+        let ip = RateLimitKey::Ip(IpAddr::V4(Ipv4Addr::LOCALHOST));
+        assert_eq!(limiter.key_of(&request(Some("known-key"))), Some(RateLimitKey::ApiKey("known-key".into())));
+        assert_eq!(limiter.key_of(&request(Some("random-key"))), Some(ip.clone()));
+        assert_eq!(limiter.key_of(&request(None)), Some(ip));
+    }
+
+    #[test]
+    fn test_clients_are_limited_then_refilled() {
+        let limiter = limiter();
+        let ip = RateLimitKey::Ip(IpAddr::V4(Ipv4Addr::LOCALHOST));
+        let now = Instant::now();
+
+        assert!(limiter.acquire(ip.clone(), now).is_ok());
+        assert!(limiter.acquire(ip.clone(), now).is_ok());
+        // One request is refilled every 5 seconds.
+        assert_eq!(limiter.acquire(ip.clone(), now), Err(Duration::from_secs(5)));
+
+        // The API key has its own bucket.
+        let api_key = RateLimitKey::ApiKey("known-key".into());
+        assert!((0..4).all(|_| limiter.acquire(api_key.clone(), now).is_ok()));
+        assert!(limiter.acquire(api_key, now).is_err());
+
+        assert!(limiter.acquire(ip.clone(), now + Duration::from_secs(5)).is_ok());
+        assert!(limiter.acquire(ip.clone(), now + Duration::from_secs(5)).is_err());
+
+        // Lifting the limit of the IPs lets them through right away.
+        limiter.set_limits(RateLimits { per_ip: None, ..limiter.limits() });
+        assert!(limiter.acquire(ip, now + Duration::from_secs(5)).is_ok());
+    }
+}
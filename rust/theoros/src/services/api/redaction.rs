@@ -0,0 +1,34 @@
+use axum::{
+    body::{to_bytes, Body},
+    extract::Request,
+    http::header::CONTENT_LENGTH,
+    middleware::Next,
+    response::Response,
+};
+
+use pragma_utils::redaction::redactor;
+
+/// Redacts the secrets from the bodies of the error responses, as they may include the
+/// messages of upstream errors (e.g. an RPC error echoing its URL).
+pub async fn redact_error_responses(request: Request, next: Next) -> Response {
+    let response = next.run(request).await;
+    if !(response.status().is_client_error() || response.status().is_server_error()) {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let bytes = match to_bytes(body, usize::MAX).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            tracing::error!("😱 Failed to read the error response body: {:?}", e);
+            return Response::from_parts(parts, Body::empty());
+        }
+    };
+    let text = String::from_utf8_lossy(&bytes);
+    let redacted = redactor().redact(&text);
+    if redacted == text {
+        return Response::from_parts(parts, Body::from(bytes));
+    }
+    parts.headers.remove(CONTENT_LENGTH);
+    Response::from_parts(parts, Body::from(redacted.into_owned()))
+}
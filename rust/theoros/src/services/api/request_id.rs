@@ -0,0 +1,85 @@
+use axum::{
+    extract::Request,
+    http::{HeaderName, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+use tracing::Instrument;
+
+pub const REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+/// Longest request ID accepted from the clients, longer ones being replaced.
+const MAX_REQUEST_ID_LENGTH: usize = 128;
+
+tokio::task_local! {
+    /// ID of the request being served by the task.
+    static CURRENT_REQUEST_ID: RequestId;
+}
+
+/// ID of a request, to correlate its logs & its response, e.g. when a user reports an incident.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestId(pub String);
+
+impl RequestId {
+    /// The ID sent by the client or a proxy if any & well formed, else a new one.
+    fn from_request(request: &Request) -> Self {
+        let received = request.headers().get(REQUEST_ID_HEADER).and_then(|value| value.to_str().ok()).filter(|id| {
+            !id.is_empty() && id.len() <= MAX_REQUEST_ID_LENGTH && id.chars().all(|c| c.is_ascii_graphic())
+        });
+        match received {
+            Some(id) => Self(id.to_owned()),
+            None => Self(uuid::Uuid::new_v4().to_string()),
+        }
+    }
+
+    /// ID of the request being served, `None` outside of a request.
+    pub fn current() -> Option<Self> {
+        CURRENT_REQUEST_ID.try_with(Clone::clone).ok()
+    }
+}
+
+/// Assigns a [RequestId] to every request, available to the handlers through the request extensions &
+/// [RequestId::current]. Its logs are recorded within a span holding the ID, which is returned in the
+/// `X-Request-Id` header of the response.
+pub async fn assign_request_id(mut request: Request, next: Next) -> Response {
+    let request_id = RequestId::from_request(&request);
+    request.extensions_mut().insert(request_id.clone());
+    let span = tracing::info_span!("request", request_id = %request_id.0);
+
+    let mut response = CURRENT_REQUEST_ID.scope(request_id.clone(), next.run(request)).instrument(span).await;
+    if let Ok(value) = HeaderValue::from_str(&request_id.0) {
+        response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{body::Body, middleware, routing::get, Router};
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::errors::TheorosError;
+
+    fn app() -> Router {
+        Router::new()
+            .route("/v1/data_feeds/:feed_id", get(|| async { Err::<(), _>(TheorosError::FeedNotFound("0x42".into())) }))
+            .layer(middleware::from_fn(assign_request_id))
+    }
+
+    #[tokio::test]
+    async fn test_request_id_is_propagated() {
+        let request =
+            Request::get("/v1/data_feeds/0x42").header(REQUEST_ID_HEADER, "incident-1234").body(Body::empty());
+        let response = app().oneshot(request.unwrap()).await.unwrap();
+        assert_eq!(response.headers()[REQUEST_ID_HEADER], "incident-1234");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let problem: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(problem["request_id"], "incident-1234");
+
+        // Missing or malformed IDs are replaced by a new one.
+        let request = Request::get("/v1/data_feeds/0x42").header(REQUEST_ID_HEADER, "not an id").body(Body::empty());
+        let response = app().oneshot(request.unwrap()).await.unwrap();
+        let request_id = response.headers()[REQUEST_ID_HEADER].to_str().unwrap();
+        assert!(uuid::Uuid::parse_str(request_id).is_ok());
+    }
+}
@@ -0,0 +1,29 @@
+use std::sync::Arc;
+use std::time::Instant;
+
+use axum::{
+    extract::{MatchedPath, Request, State},
+    middleware::Next,
+    response::Response,
+};
+
+use crate::services::metrics::TheorosMetrics;
+
+/// Records the time taken to serve the request, labelled by its route rather than its path so the number of
+/// series stays bounded. Applied after routing: requests matching no route aren't recorded.
+pub async fn record_request_metrics(
+    State(metrics): State<Arc<TheorosMetrics>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let route = request.extensions().get::<MatchedPath>().map(|path| path.as_str().to_owned()).unwrap_or_default();
+    let method = request.method().clone();
+    let started_at = Instant::now();
+
+    let response = next.run(request).await;
+    metrics
+        .http_request_duration_seconds
+        .with_label_values(&[method.as_str(), &route, response.status().as_str()])
+        .observe(started_at.elapsed().as_secs_f64());
+    response
+}
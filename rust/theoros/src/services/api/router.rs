@@ -1,53 +1,240 @@
-use axum::http::StatusCode;
+use axum::extract::{DefaultBodyLimit, State};
+use axum::http::{header, StatusCode};
+use axum::middleware;
 use axum::response::IntoResponse;
-use axum::routing::get;
-use axum::Router;
+use axum::routing::{delete, get, post, put};
+use axum::{Extension, Router};
+use prometheus::{Encoder, TextEncoder};
 
 use utoipa::OpenApi as OpenApiT;
 use utoipa_swagger_ui::SwaggerUi;
 
+use crate::handlers::admin::auth::require_admin_key;
+use crate::handlers::admin::caches::{evict_calldata_blobs_cache, evict_checkpoints_cache};
+use crate::handlers::admin::chains::{get_chain_statuses, update_chain_status};
+use crate::handlers::admin::checkpoints::refetch_checkpoint;
+use crate::handlers::admin::config::{apply_config, validate_config};
+use crate::handlers::admin::consumer_keys::{get_consumer_keys, register_consumer_key, revoke_consumer_key};
+use crate::handlers::admin::dead_letters::{get_dead_letters, replay_dead_letter};
+use crate::handlers::admin::diagnostics::get_diagnostics;
+use crate::handlers::admin::feed_lifecycles::{get_feed_lifecycles, update_feed_lifecycle};
+use crate::handlers::admin::heap::{dump_heap_profile, get_heap_stats, update_heap_profiling};
+use crate::handlers::admin::indexer::{get_indexer_status, pause_indexer, resume_indexer};
+use crate::handlers::admin::parse_failures::{clear_parse_failures, get_parse_failures};
+use crate::handlers::admin::private_feeds::{get_private_feeds, update_feed_privacy};
+use crate::handlers::admin::quarantine::{clear_quarantine, get_quarantine};
+use crate::handlers::admin::state::get_state_dump;
+use crate::handlers::admin::tracing_sampling::{get_tracing_sampling, update_tracing_sampling};
+use crate::handlers::admin::validators::refresh_validator_sets;
+use crate::handlers::graphql::{graphiql, graphql_handler, schema::build_schema};
+use crate::handlers::rest::get_anomalies::get_anomalies;
 use crate::handlers::rest::get_calldata::get_calldata;
+use crate::handlers::rest::get_calldata_by_chain::get_calldata_by_chain;
+use crate::handlers::rest::get_calldata_by_feed_id::get_calldata_by_feed_id;
+use crate::handlers::rest::get_calldata_by_id::get_calldata_by_id;
 use crate::handlers::rest::get_chains::get_chains;
+use crate::handlers::rest::get_checkpoint::get_checkpoint;
+use crate::handlers::rest::get_data_feed::get_data_feed;
 use crate::handlers::rest::get_data_feeds::get_data_feeds;
+use crate::handlers::rest::get_feed_timeline::get_feed_timeline;
+use crate::handlers::rest::get_history::get_history;
+use crate::handlers::rest::get_next_update::get_next_update;
+use crate::handlers::rest::get_ohlc::get_ohlc;
+use crate::handlers::rest::get_quorum_status::get_quorum_status;
+use crate::handlers::rest::get_raw_dispatch::get_raw_dispatch;
+use crate::handlers::rest::get_relayer_status::get_relayer_status;
+use crate::handlers::rest::get_stream::get_stream;
+use crate::handlers::rest::get_version::get_version;
+use crate::handlers::rest::post_calldata_batch::post_calldata_batch;
+use crate::handlers::rest::simulate_calldata::simulate_calldata;
+use crate::handlers::rest::simulate_update::simulate_update;
+use crate::handlers::websocket::subscribe_to_anomalies::ws_anomalies_route_handler;
 use crate::handlers::websocket::subscribe_to_calldata::ws_route_handler;
+use crate::handlers::websocket::subscribe_to_feed_updates::ws_feed_updates_route_handler;
+use crate::services::api::rate_limit::rate_limit_requests;
+use crate::services::api::request_metrics::record_request_metrics;
+use crate::services::api::versioning::{negotiate_api_version, ApiVersion};
 use crate::AppState;
 
 pub fn api_router<T: OpenApiT>(state: AppState) -> Router<AppState> {
     let open_api = T::openapi();
+
+    let mut v1_routes = Router::new()
+        .merge(calldata_routes(state.clone()))
+        .merge(data_feeds_routes(state.clone()))
+        .merge(chains_routes(state.clone()))
+        .merge(checkpoints_routes(state.clone()))
+        .merge(anomalies_routes(state.clone()))
+        .merge(debug_routes(state.clone()))
+        .merge(simulate_routes(state.clone()))
+        .merge(relayer_routes(state.clone()))
+        .merge(graphql_routes(state.clone()))
+        .route("/version", get(get_version))
+        .merge(ws_route(state.clone()));
+    if state.admin_api_key.is_some() {
+        v1_routes = v1_routes.nest("/admin", admin_routes(state.clone()));
+    }
+    // The routes v2 doesn't override are served as in v1.
+    let v2_routes = v2_routes(state.clone())
+        .fallback_service(v1_routes.clone().fallback(handler_404).with_state(state.clone()))
+        .layer(Extension(ApiVersion::V2));
+    let v1_routes = v1_routes
+        .layer(middleware::from_fn_with_state(v2_routes.clone().with_state(state.clone()), negotiate_api_version))
+        .layer(Extension(ApiVersion::V1));
+
     Router::new()
         .route("/health", get(health))
-        .merge(SwaggerUi::new("/v1/docs").url("/v1/docs/openapi.json", open_api))
-        .nest(
-            "/v1",
-            Router::new()
-                .merge(calldata_routes(state.clone()))
-                .merge(data_feeds_routes(state.clone()))
-                .merge(chains_routes(state.clone()))
-                .merge(ws_route(state.clone())),
-        )
+        .route("/metrics", get(metrics))
+        .merge(SwaggerUi::new("/v1/docs").url("/v1/openapi.json", open_api))
+        .nest(ApiVersion::V1.prefix(), v1_routes)
+        .nest(ApiVersion::V2.prefix(), v2_routes)
+        .route_layer(middleware::from_fn_with_state(state.metrics.clone(), record_request_metrics))
         .fallback(handler_404)
+        .layer(DefaultBodyLimit::max(state.max_request_body_size))
 }
 
-async fn health() -> StatusCode {
+#[utoipa::path(
+    get,
+    path = "/health",
+    responses(
+        (status = 200, description = "The API is up")
+    ),
+)]
+pub async fn health() -> StatusCode {
     StatusCode::OK
 }
 
+/// Same metrics as the ones served on the metrics port, for deployments scraping the API.
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    responses(
+        (status = 200, description = "Prometheus metrics", content_type = "text/plain", body = String)
+    ),
+)]
+pub async fn metrics(State(state): State<AppState>) -> impl IntoResponse {
+    let encoder = TextEncoder::new();
+    let mut buffer = Vec::new();
+    match encoder.encode(&state.metrics_registry.gather(), &mut buffer) {
+        Ok(()) => ([(header::CONTENT_TYPE, encoder.format_type().to_owned())], buffer).into_response(),
+        Err(e) => {
+            tracing::error!("😱 Failed to encode the metrics: {:?}", e);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
 async fn handler_404() -> impl IntoResponse {
     (StatusCode::NOT_FOUND, "The requested resource was not found")
 }
 
+/// Routes breaking the v1 ones, e.g. because their payloads changed. Their handlers are mounted here instead of
+/// changing the v1 ones, which must stay stable.
+fn v2_routes(state: AppState) -> Router<AppState> {
+    Router::new().with_state(state)
+}
+
 fn ws_route(state: AppState) -> Router<AppState> {
-    Router::new().route("/ws/calldata", get(ws_route_handler)).with_state(state)
+    Router::new()
+        .route("/ws", get(ws_feed_updates_route_handler))
+        .route("/stream", get(get_stream))
+        .route("/ws/calldata", get(ws_route_handler))
+        .with_state(state)
+}
+
+/// Limits the requests of each client to the routes, when rate limits are configured.
+fn rate_limited(routes: Router<AppState>, state: &AppState) -> Router<AppState> {
+    routes.route_layer(middleware::from_fn_with_state(state.rate_limiter.clone(), rate_limit_requests))
 }
 
 fn calldata_routes(state: AppState) -> Router<AppState> {
-    Router::new().route("/calldata", get(get_calldata)).with_state(state)
+    let routes = Router::new()
+        .route("/calldata", get(get_calldata))
+        .route("/calldata/:feed_id", get(get_calldata_by_feed_id))
+        .route("/calldata/batch/:chain_name", post(post_calldata_batch))
+        .route("/calldata/by-id/:calldata_id", get(get_calldata_by_id))
+        .route("/calldata/:chain_name/:feed_id", get(get_calldata_by_chain))
+        .route("/calldata/:chain_name/simulate", post(simulate_calldata));
+    rate_limited(routes, &state).with_state(state)
 }
 
 fn data_feeds_routes(state: AppState) -> Router<AppState> {
-    Router::new().route("/data_feeds", get(get_data_feeds).with_state(state))
+    Router::new()
+        .route("/data_feeds", get(get_data_feeds))
+        .route("/data_feeds/:feed_id", get(get_data_feed))
+        .route("/data_feeds/:feed_id/next", get(get_next_update))
+        .route("/data_feeds/:feed_id/ohlc", get(get_ohlc))
+        .merge(rate_limited(Router::new().route("/data_feeds/:feed_id/history", get(get_history)), &state))
+        .with_state(state)
 }
 
 fn chains_routes(state: AppState) -> Router<AppState> {
     Router::new().route("/chains", get(get_chains).with_state(state))
 }
+
+fn checkpoints_routes(state: AppState) -> Router<AppState> {
+    Router::new().route("/checkpoints/:validator/:object", get(get_checkpoint)).with_state(state)
+}
+
+fn anomalies_routes(state: AppState) -> Router<AppState> {
+    Router::new()
+        .route("/anomalies", get(get_anomalies))
+        .route("/ws/anomalies", get(ws_anomalies_route_handler))
+        .with_state(state)
+}
+
+fn simulate_routes(state: AppState) -> Router<AppState> {
+    Router::new().route("/simulate/update", post(simulate_update)).with_state(state)
+}
+
+fn relayer_routes(state: AppState) -> Router<AppState> {
+    Router::new().route("/relayer/status", get(get_relayer_status)).with_state(state)
+}
+
+fn graphql_routes(state: AppState) -> Router<AppState> {
+    Router::new()
+        .route("/graphql", get(graphiql).post(graphql_handler))
+        .layer(Extension(build_schema(state.clone())))
+        .with_state(state)
+}
+
+fn debug_routes(state: AppState) -> Router<AppState> {
+    Router::new()
+        .route("/debug/feeds/:feed_id/timeline", get(get_feed_timeline))
+        .route("/debug/dispatches/:nonce", get(get_raw_dispatch))
+        .route("/debug/dispatches/:nonce/quorum", get(get_quorum_status))
+        .with_state(state)
+}
+
+fn admin_routes(state: AppState) -> Router<AppState> {
+    Router::new()
+        .route("/tracing/sampling", get(get_tracing_sampling).put(update_tracing_sampling))
+        .route("/diagnostics", get(get_diagnostics))
+        .route("/state", get(get_state_dump))
+        .route("/heap/stats", get(get_heap_stats))
+        .route("/heap/profiling", put(update_heap_profiling))
+        .route("/heap/dump", post(dump_heap_profile))
+        .route("/quarantine", get(get_quarantine).delete(clear_quarantine))
+        .route("/parse_failures", get(get_parse_failures).delete(clear_parse_failures))
+        .route("/dead_letters", get(get_dead_letters))
+        .route("/dead_letters/:id/replay", post(replay_dead_letter))
+        .route("/indexer", get(get_indexer_status))
+        .route("/indexer/pause", post(pause_indexer))
+        .route("/indexer/resume", post(resume_indexer))
+        .route("/validators/refresh", post(refresh_validator_sets))
+        .route("/checkpoints/:nonce/refetch", post(refetch_checkpoint))
+        .route("/caches/checkpoints", delete(evict_checkpoints_cache))
+        .route("/caches/calldata_blobs", delete(evict_calldata_blobs_cache))
+        .route("/chains", get(get_chain_statuses))
+        .route("/chains/:chain_name", put(update_chain_status))
+        .route("/config/validate", post(validate_config))
+        .route("/config/apply", post(apply_config))
+        .route("/feeds/lifecycles", get(get_feed_lifecycles))
+        .route("/feeds/:feed_id/lifecycle", put(update_feed_lifecycle))
+        .route("/feeds/private", get(get_private_feeds))
+        .route("/feeds/:feed_id/private", put(update_feed_privacy))
+        .route("/consumers", get(get_consumer_keys))
+        .route("/consumers/:consumer_id/key", put(register_consumer_key).delete(revoke_consumer_key))
+        .route_layer(middleware::from_fn_with_state(state.clone(), require_admin_key))
+        .with_state(state)
+}
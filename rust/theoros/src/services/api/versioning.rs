@@ -0,0 +1,233 @@
+use std::convert::Infallible;
+use std::fmt;
+
+use axum::{
+    async_trait,
+    extract::{FromRequestParts, Request, State},
+    http::{
+        header::{ACCEPT, VARY},
+        request::Parts,
+        HeaderMap, HeaderValue,
+    },
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Router,
+};
+use tower::ServiceExt;
+
+use crate::errors::TheorosError;
+
+/// Prefix of the vendor media types naming a version of the API, e.g. `application/vnd.theoros.v2+json`.
+const MEDIA_TYPE_PREFIX: &str = "application/vnd.theoros.";
+const MEDIA_TYPE_SUFFIX: &str = "+json";
+
+/// Version of the API, each one mounted under its own prefix, e.g. `/v1`.
+///
+/// A version only changes what it breaks: the routes it doesn't override are served by the previous version, so
+/// a breaking change, e.g. of the calldata format, ships under the next version while the existing consumers keep
+/// the stable one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ApiVersion {
+    V1,
+    V2,
+}
+
+impl ApiVersion {
+    /// Every version served, from the oldest.
+    pub const ALL: [Self; 2] = [Self::V1, Self::V2];
+    /// Version served when the client doesn't ask for one.
+    pub const STABLE: Self = Self::V1;
+
+    /// Prefix of the routes of the version, e.g. `/v1`.
+    pub const fn prefix(self) -> &'static str {
+        match self {
+            Self::V1 => "/v1",
+            Self::V2 => "/v2",
+        }
+    }
+
+    /// Vendor media type asking for the version through the `Accept` header.
+    pub const fn media_type(self) -> &'static str {
+        match self {
+            Self::V1 => "application/vnd.theoros.v1+json",
+            Self::V2 => "application/vnd.theoros.v2+json",
+        }
+    }
+
+    /// The version named by a vendor media type, e.g. `application/vnd.theoros.v2+json`, `None` if the media type
+    /// isn't a Theoros one & an error if it names an unknown version.
+    fn from_media_type(media_type: &str) -> Result<Option<Self>, TheorosError> {
+        let essence = media_type.split(';').next().unwrap_or_default().trim().to_ascii_lowercase();
+        let Some(version) =
+            essence.strip_prefix(MEDIA_TYPE_PREFIX).and_then(|rest| rest.strip_suffix(MEDIA_TYPE_SUFFIX))
+        else {
+            return Ok(None);
+        };
+        Self::ALL
+            .into_iter()
+            .find(|candidate| candidate.to_string() == version)
+            .map(Some)
+            .ok_or_else(|| TheorosError::UnsupportedApiVersion(version.to_owned()))
+    }
+
+    /// The version asked for through the `Accept` header, the first Theoros media type listed winning. `None` if
+    /// the client doesn't name any, e.g. `Accept: application/json`.
+    pub fn negotiate(headers: &HeaderMap) -> Result<Option<Self>, TheorosError> {
+        for value in headers.get_all(ACCEPT) {
+            let Ok(value) = value.to_str() else { continue };
+            for media_type in value.split(',') {
+                if let Some(version) = Self::from_media_type(media_type)? {
+                    return Ok(Some(version));
+                }
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl fmt::Display for ApiVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.prefix()[1..])
+    }
+}
+
+/// Version of the API serving the request, the stable one outside of the versioned routes.
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for ApiVersion {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(parts.extensions.get::<ApiVersion>().copied().unwrap_or(Self::STABLE))
+    }
+}
+
+/// Serves the requests of the stable routes asking for a later version through the `Accept` header with the
+/// routes of that version, given as state, so clients can opt into it without changing their URLs.
+///
+/// The paths of both routers must be relative to their prefix, as they are once nested.
+pub async fn negotiate_api_version(State(next_version): State<Router>, request: Request, next: Next) -> Response {
+    let mut response = match ApiVersion::negotiate(request.headers()) {
+        Ok(Some(version)) if version > ApiVersion::STABLE => match next_version.oneshot(request).await {
+            Ok(response) => response,
+            Err(infallible) => match infallible {},
+        },
+        Ok(_) => next.run(request).await,
+        Err(e) => e.into_response(),
+    };
+    // The same URL is answered differently depending on the `Accept` header, which caches must account for.
+    response.headers_mut().append(VARY, HeaderValue::from_static("accept"));
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        body::Body,
+        http::{HeaderName, StatusCode},
+        middleware,
+        routing::get,
+        Extension,
+    };
+
+    use super::*;
+
+    fn accept(value: &str) -> HeaderMap {
+        HeaderMap::from_iter([(ACCEPT, HeaderValue::from_str(value).unwrap())])
+    }
+
+    #[test]
+    fn test_negotiate() {
+        assert_eq!(ApiVersion::negotiate(&HeaderMap::new()).unwrap(), None);
+        assert_eq!(ApiVersion::negotiate(&accept("application/json")).unwrap(), None);
+        assert_eq!(ApiVersion::negotiate(&accept("*/*")).unwrap(), None);
+        assert_eq!(ApiVersion::negotiate(&accept("application/vnd.theoros.v1+json")).unwrap(), Some(ApiVersion::V1));
+        assert_eq!(
+            ApiVersion::negotiate(&accept("application/json;q=0.5, Application/Vnd.Theoros.V2+JSON; q=1")).unwrap(),
+            Some(ApiVersion::V2)
+        );
+        assert!(matches!(
+            ApiVersion::negotiate(&accept("application/vnd.theoros.v3+json")),
+            Err(TheorosError::UnsupportedApiVersion(version)) if version == "v3"
+        ));
+    }
+
+    #[test]
+    fn test_media_types_name_their_version() {
+        for version in ApiVersion::ALL {
+            assert_eq!(ApiVersion::from_media_type(version.media_type()).unwrap(), Some(version));
+            assert_eq!(version.prefix(), format!("/{version}"));
+        }
+    }
+
+    const SERVED_BY: HeaderName = HeaderName::from_static("x-served-by");
+
+    /// Stable routes answering `v1`, the next version overriding `/calldata` only & falling back to them.
+    fn app() -> Router {
+        async fn served_by(version: ApiVersion, name: &'static str) -> impl IntoResponse {
+            ([(SERVED_BY, name)], version.to_string())
+        }
+        let v1 = Router::new()
+            .route("/calldata", get(|version: ApiVersion| served_by(version, "v1")))
+            .route("/chains", get(|version: ApiVersion| served_by(version, "v1")));
+        let v2 = Router::new()
+            .route("/calldata", get(|version: ApiVersion| served_by(version, "v2")))
+            .fallback_service(v1.clone())
+            .layer(Extension(ApiVersion::V2));
+        Router::new()
+            .nest(
+                ApiVersion::V1.prefix(),
+                v1.layer(middleware::from_fn_with_state(v2.clone(), negotiate_api_version))
+                    .layer(Extension(ApiVersion::V1)),
+            )
+            .nest(ApiVersion::V2.prefix(), v2)
+    }
+
+    async fn get_with_accept(uri: &str, accept: Option<&str>) -> Response {
+        let mut request = Request::get(uri);
+        if let Some(accept) = accept {
+            request = request.header(ACCEPT, accept);
+        }
+        app().oneshot(request.body(Body::empty()).unwrap()).await.unwrap()
+    }
+
+    async fn body(response: Response) -> String {
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        String::from_utf8(body.to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_stable_version_by_default() {
+        let response = get_with_accept("/v1/calldata", Some("application/json")).await;
+        assert_eq!(response.headers()[SERVED_BY], "v1");
+        assert_eq!(response.headers()[VARY], "accept");
+        assert_eq!(body(response).await, "v1");
+    }
+
+    #[tokio::test]
+    async fn test_accept_header_selects_the_version() {
+        let response = get_with_accept("/v1/calldata", Some(ApiVersion::V2.media_type())).await;
+        assert_eq!(response.headers()[SERVED_BY], "v2");
+        assert_eq!(body(response).await, "v2");
+
+        // Routes the next version doesn't override are still served, as part of it.
+        let response = get_with_accept("/v1/chains", Some(ApiVersion::V2.media_type())).await;
+        assert_eq!(response.headers()[SERVED_BY], "v1");
+        assert_eq!(body(response).await, "v2");
+
+        let response = get_with_accept("/v1/calldata", Some("application/vnd.theoros.v3+json")).await;
+        assert_eq!(response.status(), StatusCode::NOT_ACCEPTABLE);
+    }
+
+    #[tokio::test]
+    async fn test_prefix_selects_the_version() {
+        let response = get_with_accept("/v2/calldata", None).await;
+        assert_eq!(response.headers()[SERVED_BY], "v2");
+
+        let response = get_with_accept("/v2/chains", None).await;
+        assert_eq!(response.headers()[SERVED_BY], "v1");
+        assert_eq!(body(response).await, "v2");
+
+        let response = get_with_accept("/v2/unknown", None).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
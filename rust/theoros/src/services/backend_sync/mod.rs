@@ -0,0 +1,70 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use tokio::task::JoinSet;
+
+use pragma_utils::services::Service;
+
+use crate::AppState;
+
+/// Follows the settings changed through the admin API of the other processes sharing the storage backend at
+/// every interval & for `theoros-api`, the dispatches, checkpoints & updates persisted by `theoros-indexer`.
+#[derive(Clone)]
+pub struct BackendSyncService {
+    state: AppState,
+    interval: Duration,
+    /// Whether the dispatches, checkpoints & updates are followed too.
+    follows_dispatches: bool,
+}
+
+#[async_trait]
+impl Service for BackendSyncService {
+    async fn start(&mut self, join_set: &mut JoinSet<anyhow::Result<()>>) -> anyhow::Result<()> {
+        let service = self.clone();
+        join_set.spawn(async move {
+            tracing::info!("🧩 Backend sync service started, syncing every {:?}", service.interval);
+            service.run_forever().await;
+            Ok(())
+        });
+        Ok(())
+    }
+}
+
+impl BackendSyncService {
+    pub fn new(state: AppState, interval: Duration) -> Self {
+        Self { state, interval, follows_dispatches: true }
+    }
+
+    /// Follows the dispatches, checkpoints & updates too, which `theoros-indexer` persists. Enabled by default.
+    pub fn with_dispatches(mut self, follows_dispatches: bool) -> Self {
+        self.follows_dispatches = follows_dispatches;
+        self
+    }
+
+    async fn run_forever(&self) {
+        let mut interval = tokio::time::interval(self.interval);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            tokio::select! {
+                _ = interval.tick() => self.sync().await,
+                _ = self.state.shutdown.cancelled() => return,
+            }
+        }
+    }
+
+    async fn sync(&self) {
+        if self.follows_dispatches {
+            match self.state.storage.sync_from_backend().await {
+                Ok(0) => {}
+                Ok(updated) => {
+                    tracing::debug!("💾 [Sync] Synced the updates of {} dispatches from the backend", updated)
+                }
+                // Keeps serving the state synced so far, the next sync catching up.
+                Err(e) => tracing::error!("💾 [Sync] Failed to sync from the storage backend: {:?}", e),
+            }
+        }
+        if let Err(e) = self.state.sync_settings().await {
+            tracing::error!("💾 [Sync] Failed to sync the settings from the storage backend: {:?}", e);
+        }
+    }
+}
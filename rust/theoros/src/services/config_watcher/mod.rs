@@ -0,0 +1,210 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+use async_trait::async_trait;
+use notify::{RecursiveMode, Watcher};
+use tokio::{sync::mpsc, task::JoinSet};
+
+use pragma_utils::services::Service;
+
+use crate::{
+    configs::{
+        evm_config::EvmConfig,
+        runtime_settings::{RuntimeSettings, RuntimeSettingsOverrides},
+    },
+    constants::CONFIG_RELOAD_DEBOUNCE,
+    AppState,
+};
+
+/// Watches the EVM config & runtime settings files, applying their changes without a restart.
+///
+/// The runtime settings are applied in place. A changed EVM config goes through the same path as the one
+/// applied through the admin API: the components of the chains are rebuilt, and swapped in once all of them
+/// could be. Invalid files are rejected & the running config kept, until the files change again.
+#[derive(Clone)]
+pub struct ConfigWatcherService {
+    state: AppState,
+    evm_config_path: PathBuf,
+    runtime_settings_path: Option<PathBuf>,
+    /// Settings of the command line, overridden by the runtime settings file.
+    cli_settings: RuntimeSettings,
+}
+
+#[async_trait]
+impl Service for ConfigWatcherService {
+    async fn start(&mut self, join_set: &mut JoinSet<anyhow::Result<()>>) -> anyhow::Result<()> {
+        let service = self.clone();
+        join_set.spawn(async move {
+            tracing::info!("🧩 Config watcher service started");
+            service.run_forever().await
+        });
+        Ok(())
+    }
+}
+
+impl ConfigWatcherService {
+    pub fn new(state: AppState, evm_config_path: PathBuf, cli_settings: RuntimeSettings) -> Self {
+        Self { state, evm_config_path, runtime_settings_path: None, cli_settings }
+    }
+
+    pub fn with_runtime_settings_path(mut self, runtime_settings_path: Option<PathBuf>) -> Self {
+        self.runtime_settings_path = runtime_settings_path;
+        self
+    }
+
+    async fn run_forever(self) -> anyhow::Result<()> {
+        let (sender, mut events) = mpsc::unbounded_channel();
+        let mut watcher = notify::recommended_watcher(move |event| {
+            let _ = sender.send(event);
+        })?;
+        // The directories are watched rather than the files, as editors & Kubernetes replace the files.
+        let mut directories: Vec<&Path> = std::iter::once(&self.evm_config_path)
+            .chain(&self.runtime_settings_path)
+            .map(|path| directory_of(path))
+            .collect();
+        directories.dedup();
+        for directory in directories {
+            watcher
+                .watch(directory, RecursiveMode::NonRecursive)
+                .with_context(|| format!("Watching the config directory {}", directory.display()))?;
+        }
+
+        let mut evm_config = fs::read_to_string(&self.evm_config_path).ok();
+        let mut runtime_settings = self.runtime_settings_path.as_ref().and_then(|path| fs::read_to_string(path).ok());
+        loop {
+            let event = tokio::select! {
+                event = events.recv() => event,
+                _ = self.state.shutdown.cancelled() => return Ok(()),
+            };
+            let Some(event) = event else {
+                break;
+            };
+            if let Err(e) = event {
+                tracing::warn!("⚠️ [Config] Failed to watch the config files: {:?}", e);
+                continue;
+            }
+            tokio::time::sleep(CONFIG_RELOAD_DEBOUNCE).await;
+            while events.try_recv().is_ok() {}
+
+            self.reload_evm_config(&mut evm_config).await;
+            self.reload_runtime_settings(&mut runtime_settings);
+        }
+        anyhow::bail!("😱 Config watcher stopped!")
+    }
+
+    /// Applies the EVM config if it changed since `last_read`.
+    async fn reload_evm_config(&self, last_read: &mut Option<String>) {
+        let Some(contents) = read_if_changed(&self.evm_config_path, last_read) else {
+            return;
+        };
+        let applied = match EvmConfig::from_yaml(&contents) {
+            Ok(candidate) => self.state.evm_config.apply(&self.state, candidate).await.map_err(anyhow::Error::from),
+            Err(e) => Err(e.into()),
+        };
+        match applied {
+            Ok(diff) => {
+                self.record_reload("evm_config", "applied");
+                tracing::info!("🔁 [Config] Applied the changes of the EVM config: {:?}", diff);
+            }
+            Err(e) => {
+                self.record_reload("evm_config", "rejected");
+                tracing::error!("🔁 [Config] Rejected the EVM config, keeping the running one: {:?}", e);
+            }
+        }
+    }
+
+    /// Applies the runtime settings if they changed since `last_read`.
+    fn reload_runtime_settings(&self, last_read: &mut Option<String>) {
+        let Some(path) = &self.runtime_settings_path else {
+            return;
+        };
+        let Some(contents) = read_if_changed(path, last_read) else {
+            return;
+        };
+        let settings = RuntimeSettingsOverrides::from_yaml(&contents)
+            .and_then(|overrides| self.cli_settings.with_overrides(&overrides));
+        match settings {
+            Ok(settings) => {
+                settings.apply(&self.state);
+                self.record_reload("runtime_settings", "applied");
+                tracing::info!("🔁 [Config] Applied the runtime settings: {:?}", settings);
+            }
+            Err(e) => {
+                self.record_reload("runtime_settings", "rejected");
+                tracing::error!("🔁 [Config] Rejected the runtime settings, keeping the running ones: {:?}", e);
+            }
+        }
+    }
+
+    fn record_reload(&self, file: &str, outcome: &str) {
+        self.state.metrics.config_reloads.with_label_values(&[file, outcome]).inc();
+    }
+}
+
+fn directory_of(path: &Path) -> &Path {
+    match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    }
+}
+
+/// Reads the file, returning its contents if they differ from the ones read last. A file missing for a moment
+/// while being replaced is skipped.
+fn read_if_changed(path: &Path, last_read: &mut Option<String>) -> Option<String> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) => {
+            tracing::debug!("Skipped the reload of {}: {:?}", path.display(), e);
+            return None;
+        }
+    };
+    if last_read.as_ref() == Some(&contents) {
+        return None;
+    }
+    *last_read = Some(contents.clone());
+    Some(contents)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use tracing::Level;
+
+    use super::*;
+    use crate::storage::{FeedIdsStorage, TheorosStorage, ValidatorsFetchersStorage};
+
+    #[test]
+    fn test_runtime_settings_are_reloaded() {
+        let rpc = crate::rpc::starknet::StarknetRpc::new(url::Url::parse("http://localhost:1").unwrap());
+        let storage = TheorosStorage::new(FeedIdsStorage::default(), ValidatorsFetchersStorage::default());
+        let state = AppState::builder().with_starknet_rpc(Arc::new(rpc)).with_storage(storage).build().unwrap();
+        let path = std::env::temp_dir().join(format!("theoros-runtime-settings-{}.yaml", std::process::id()));
+        let service =
+            ConfigWatcherService::new(state.clone(), PathBuf::from("evm_config.yaml"), RuntimeSettings::default())
+                .with_runtime_settings_path(Some(path.clone()));
+        let mut last_read = None;
+
+        fs::write(&path, "log_level: debug\nrate_limit_per_ip: 10\ncheckpoints_fetch_interval: 5s\n").unwrap();
+        service.reload_runtime_settings(&mut last_read);
+        assert_eq!(state.log_level.get(), Level::DEBUG);
+        assert_eq!(state.rate_limiter.limits().per_ip, Some(10));
+        assert_eq!(state.runtime_settings.borrow().checkpoints_fetch_interval, Duration::from_secs(5));
+
+        // Invalid settings are rejected, the running ones being kept.
+        fs::write(&path, "log_level: debug\nrate_limit_window: 0s\n").unwrap();
+        service.reload_runtime_settings(&mut last_read);
+        assert_eq!(state.rate_limiter.limits().per_ip, Some(10));
+
+        // Settings left out are back to their command line value.
+        fs::write(&path, "log_level: warn\n").unwrap();
+        service.reload_runtime_settings(&mut last_read);
+        assert_eq!(state.log_level.get(), Level::WARN);
+        assert_eq!(state.rate_limiter.limits().per_ip, None);
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,46 @@
+use std::net::{IpAddr, SocketAddr};
+
+use anyhow::Context;
+use async_trait::async_trait;
+use tokio::task::JoinSet;
+use tonic::transport::Server;
+
+use pragma_utils::services::Service;
+
+use crate::{
+    handlers::grpc::{proto::theoros_server::TheorosServer, TheorosGrpc},
+    AppState,
+};
+
+/// Serves the gRPC API on its own port, next to the REST API whose fanout streams the updates.
+pub struct GrpcService {
+    state: AppState,
+    address: SocketAddr,
+}
+
+impl GrpcService {
+    pub fn new(state: AppState, host: IpAddr, port: u16) -> Self {
+        Self { state, address: SocketAddr::new(host, port) }
+    }
+}
+
+#[async_trait]
+impl Service for GrpcService {
+    async fn start(&mut self, join_set: &mut JoinSet<anyhow::Result<()>>) -> anyhow::Result<()> {
+        let address = self.address;
+        let state = self.state.clone();
+        join_set.spawn(async move {
+            let shutdown = state.shutdown.clone();
+            tracing::info!("🧩 gRPC server started at {}", address);
+            // The streams end on shutdown, so the server stops once the in-flight calls are answered.
+            Server::builder()
+                .add_service(TheorosServer::new(TheorosGrpc::new(state)))
+                .serve_with_shutdown(address, shutdown.cancelled_owned())
+                .await
+                .context("😱 gRPC server stopped!")?;
+            tracing::info!("🧩 gRPC server stopped, in-flight calls answered");
+            Ok(())
+        });
+        Ok(())
+    }
+}
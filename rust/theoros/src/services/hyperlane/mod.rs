@@ -1,21 +1,46 @@
-use std::{sync::Arc, time::Duration};
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
+use alloy::primitives::Address;
+use futures::stream::{FuturesUnordered, StreamExt};
 use starknet::core::types::Felt;
-use tokio::task::JoinSet;
+use tokio::{sync::watch, task::JoinSet};
+use tokio_util::sync::CancellationToken;
 
 use pragma_utils::{conversions::alloy::hex_str_to_u256, services::Service};
 
-use crate::storage::TheorosStorage;
+use crate::configs::runtime_settings::RuntimeSettings;
+use crate::constants::{
+    DEFAULT_CHECKPOINTS_FETCH_INTERVAL, DEFAULT_VALIDATOR_FETCH_TIMEOUT, MAX_PREFETCHED_CHECKPOINTS,
+    MIN_CHECKPOINTS_FOR_RANGE_FETCH, VALIDATOR_LAG_WARNING_THRESHOLD, VALIDATOR_RANGE_FETCH_TIMEOUT,
+};
+use crate::services::metrics::TheorosMetrics;
+use crate::storage::{QuarantineReason, TheorosStorage};
 use crate::types::hyperlane::{
-    DispatchUpdateInfos, FetchFromStorage, NewUpdatesAvailableEvent, SignedCheckpointWithMessageId,
+    sharded::FetchSharding, CheckpointAnomaly, CheckpointAnomalyKind, DispatchEvent, DispatchUpdateInfos,
+    FetchFromStorage, NewUpdatesAvailableEvent, SignedCheckpointWithMessageId, SignedValue,
 };
+use crate::types::quorum::QuorumTracker;
+use crate::types::timeline::FeedTimelineEventKind;
 
-/// Every [FETCH_INTERVAL] seconds, we check the pending checkpoints for all validators.
-const FETCH_INTERVAL: Duration = Duration::from_secs(1);
+/// Fetchers of the validators a checkpoint is collected from.
+type ValidatorFetchers = Vec<(Felt, Arc<dyn FetchFromStorage + Send + Sync>)>;
+/// Fetchers of all the validators, by validator.
+type ValidatorsFetchersMap = HashMap<Felt, Arc<dyn FetchFromStorage + Send + Sync>>;
 
 #[derive(Clone)]
 pub struct HyperlaneService {
     storage: Arc<TheorosStorage>,
+    metrics: Arc<TheorosMetrics>,
+    fetch_timeout: Duration,
+    sharding: Option<FetchSharding>,
+    quorum_tracker: Option<Arc<QuorumTracker>>,
+    runtime_settings: Option<watch::Receiver<RuntimeSettings>>,
+    poll_interval: Duration,
+    shutdown: CancellationToken,
 }
 
 #[async_trait::async_trait]
@@ -27,22 +52,97 @@ impl Service for HyperlaneService {
             service.run_forever().await?;
             Ok(())
         });
+        if !self.poll_interval.is_zero() {
+            let poller = self.clone();
+            join_set.spawn(async move {
+                tracing::info!("🧩 Checkpoints poller started, every {:?}", poller.poll_interval);
+                poller.poll_forever().await;
+                Ok(())
+            });
+        }
         Ok(())
     }
 }
 
 impl HyperlaneService {
-    pub fn new(storage: Arc<TheorosStorage>) -> Self {
-        Self { storage }
+    pub fn new(storage: Arc<TheorosStorage>, metrics: Arc<TheorosMetrics>) -> Self {
+        Self {
+            storage,
+            metrics,
+            fetch_timeout: DEFAULT_VALIDATOR_FETCH_TIMEOUT,
+            sharding: None,
+            quorum_tracker: None,
+            runtime_settings: None,
+            poll_interval: Duration::ZERO,
+            shutdown: CancellationToken::new(),
+        }
+    }
+
+    /// Maximum time spent fetching a checkpoint from a single validator.
+    pub fn with_fetch_timeout(mut self, fetch_timeout: Duration) -> Self {
+        self.fetch_timeout = fetch_timeout;
+        self
+    }
+
+    /// Only fetches from the validators the checkpoints owned by this instance, reading the others from
+    /// the other replicas.
+    pub fn with_sharding(mut self, sharding: Option<FetchSharding>) -> Self {
+        self.sharding = sharding;
+        self
+    }
+
+    /// Stores the updates of a nonce once its signatures reach the threshold of a destination chain, instead of
+    /// waiting for all the validators.
+    pub fn with_quorum_tracker(mut self, quorum_tracker: Arc<QuorumTracker>) -> Self {
+        self.quorum_tracker = Some(quorum_tracker);
+        self
+    }
+
+    /// Checks the pending checkpoints at the interval of the runtime settings, instead of every second.
+    pub fn with_runtime_settings(mut self, runtime_settings: watch::Receiver<RuntimeSettings>) -> Self {
+        self.runtime_settings = Some(runtime_settings);
+        self
+    }
+
+    /// Polls the latest checkpoint of each validator at this interval, prefetching the checkpoints signed before
+    /// their dispatch is indexed. `0s` disables the polling: the checkpoints are only fetched once indexed.
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// Stops between two rounds of fetches once the token is cancelled, so the checkpoints of a round are stored.
+    pub fn with_shutdown(mut self, shutdown: CancellationToken) -> Self {
+        self.shutdown = shutdown;
+        self
     }
 
     pub async fn run_forever(&self) -> anyhow::Result<()> {
         loop {
             self.process_validator_checkpoints().await;
-            tokio::time::sleep(FETCH_INTERVAL).await;
+            tokio::select! {
+                _ = tokio::time::sleep(self.fetch_interval()) => {}
+                _ = self.shutdown.cancelled() => return Ok(()),
+            }
         }
     }
 
+    async fn poll_forever(&self) {
+        loop {
+            self.poll_latest_checkpoints().await;
+            tokio::select! {
+                _ = tokio::time::sleep(self.poll_interval) => {}
+                _ = self.shutdown.cancelled() => return,
+            }
+        }
+    }
+
+    fn fetch_interval(&self) -> Duration {
+        self.runtime_settings
+            .as_ref()
+            .map_or(DEFAULT_CHECKPOINTS_FETCH_INTERVAL, |settings| settings.borrow().checkpoints_fetch_interval)
+    }
+
     /// Processes validator checkpoints by fetching signed checkpoints from all validators for each unsigned nonce.
     ///
     /// This function performs the following steps:
@@ -53,48 +153,275 @@ impl HyperlaneService {
     /// 2. **Retrieve Validators and Fetchers**:
     ///    - Gets all registered validators and their corresponding fetchers from the `ValidatorsFetchersStorage`.
     ///
-    /// 3. **Fetch Signed Checkpoints**:
+    /// 3. **Fetch Latest Indexes**:
+    ///    - Fetches the index of the latest checkpoint signed by each validator, to report the lagging validators.
+    ///
+    /// 4. **Fetch Signed Checkpoints**:
     ///    - Attempts to fetch the signed checkpoint all unsigned nonce from each validator's fetcher (in parallel),
+    ///    - Stops fetching a nonce once it reaches quorum, cancelling the fetches still in flight,
+    ///    - Skips the nonces above the latest index of the validator, as it didn't sign them yet.
+    ///    - Fetches the checkpoints as a range for the validators missing many of them, e.g. after a downtime.
     ///
-    /// 4. **Process Completed Nonces**:
+    /// 5. **Process Completed Nonces**:
     ///    - After all fetches are completed, iterates over the unsigned nonces again.
-    ///    - Checks if all validators have signed the nonce using the `all_validators_signed_nonce` method.
-    ///    - **Note**: Currently, the function only proceeds if **all** validators have signed the nonce.
-    ///      - There's a `TODO` to modify this behavior to use a quorum method (e.g., consider a nonce as valid if 66% of validators have signed it).
-    ///    - If all validators have signed the nonce:
+    ///    - Checks if the nonce reached quorum on at least one destination chain, according to the [QuorumTracker].
+    ///    - Without a quorum tracker, the function only proceeds if **all** validators have signed the nonce.
+    ///    - If the nonce reached quorum:
     ///        - Calls `store_event_updates(nonce)` to process and store the updates associated with that nonce.
     ///        - Removes the nonce from the `UnsignedCheckpointsStorage`, as it has been fully processed.
     ///
-    async fn process_validator_checkpoints(&self) {
+    pub(crate) async fn process_validator_checkpoints(&self) {
         let unsigned_nonces = self.storage.unsigned_checkpoints().nonces().await;
         if unsigned_nonces.is_empty() {
             return;
         }
 
-        let validators_fetchers = self.storage.validators_fetchers().all();
-        let mut futures = Vec::with_capacity(unsigned_nonces.len());
-        for &nonce in &unsigned_nonces {
-            for (validator, fetcher) in &validators_fetchers {
-                let fut = self.fetch_checkpoint_for_validator(*validator, fetcher.clone(), nonce);
-                futures.push(fut);
+        let validators_fetchers = self.sharded_validators_fetchers();
+        let latest_indexes = self.fetch_latest_indexes(&validators_fetchers, &unsigned_nonces).await;
+        let validator_addresses: Vec<Felt> = validators_fetchers.keys().cloned().collect();
+        let mut fetchers_per_nonce: BTreeMap<u32, ValidatorFetchers> = BTreeMap::new();
+        let mut range_futures = Vec::new();
+        for (validator, fetcher) in &validators_fetchers {
+            let latest_index = latest_indexes.get(validator).copied().unwrap_or(u32::MAX);
+            let pending_nonces: Vec<u32> = unsigned_nonces
+                .iter()
+                .copied()
+                .filter(|&nonce| {
+                    nonce <= latest_index
+                        && !self.storage.signed_checkpoints().validator_signed_nonce(*validator, nonce)
+                })
+                .collect();
+            if pending_nonces.len() >= MIN_CHECKPOINTS_FOR_RANGE_FETCH {
+                range_futures.push(self.fetch_checkpoints_range_for_validator(
+                    *validator,
+                    fetcher.clone(),
+                    pending_nonces,
+                ));
+                continue;
+            }
+            for nonce in pending_nonces {
+                fetchers_per_nonce.entry(nonce).or_default().push((*validator, fetcher.clone()));
             }
         }
-        futures::future::join_all(futures).await;
+        let collections = fetchers_per_nonce.into_iter().map(|(nonce, fetchers)| {
+            let validator_addresses = &validator_addresses;
+            self.collect_checkpoints(fetchers, nonce, self.fetch_timeout, move || {
+                self.quorum_reached(validator_addresses, nonce)
+            })
+        });
+        futures::future::join(futures::future::join_all(collections), futures::future::join_all(range_futures)).await;
 
-        // NOTE: At the moment, we only process updates when ALL validators have signed a message.
-        // TODO: We should instead use a quorum method - if 66% have signed, consider it ok.
-        let validator_addresses: Vec<Felt> = validators_fetchers.keys().cloned().collect();
         for &nonce in &unsigned_nonces {
-            if !self.all_validators_signed_nonce(&validator_addresses, nonce) {
+            self.detect_diverging_checkpoints(&validator_addresses, nonce).await;
+        }
+
+        for &nonce in &unsigned_nonces {
+            if !self.quorum_reached(&validator_addresses, nonce) {
                 continue;
             }
             // TODO: If the nonce n+1 is fully signed, shall we ignore every nonces before..? Or raise an alert?
-            tracing::info!("🌉 [Hyperlane] ✅ Nonce #{} is fully signed by all validators! Storing updates...", nonce);
-            if let Err(e) = self.store_dispatch_updates(nonce).await {
-                tracing::error!("😱 Failed to store event updates for nonce {}: {:?}", nonce, e);
+            tracing::info!("🌉 [Hyperlane] ✅ Nonce #{} reached quorum! Storing updates...", nonce);
+            self.observe_quorum_latency(nonce).await;
+            match self.store_dispatch_updates(nonce).await {
+                Ok(feed_ids) => self.send_websocket_notification(nonce, feed_ids).await,
+                Err(e) => tracing::error!("😱 Failed to store event updates for nonce {}: {:?}", nonce, e),
+            }
+            self.storage.remove_pending_dispatch(nonce).await;
+        }
+    }
+
+    /// Fetchers of all the validators, fetching only the checkpoints owned by this instance when sharded.
+    fn sharded_validators_fetchers(&self) -> ValidatorsFetchersMap {
+        let validators_fetchers = self.storage.validators_fetchers().all();
+        match &self.sharding {
+            Some(sharding) => validators_fetchers
+                .into_iter()
+                .map(|(validator, fetcher)| (validator, sharding.shard(validator, fetcher)))
+                .collect(),
+            None => validators_fetchers,
+        }
+    }
+
+    /// Polls the index of the latest checkpoint signed by each validator, reporting how far ahead of the latest
+    /// indexed dispatch it is, & prefetches the checkpoints it signed past that dispatch. Their dispatches then
+    /// reach quorum as soon as indexed, without waiting for the checkpoints to be fetched.
+    pub(crate) async fn poll_latest_checkpoints(&self) {
+        let Some(mailbox_nonce) = self.storage.raw_dispatch_events().latest_nonce().await else {
+            return;
+        };
+        let polls = self
+            .sharded_validators_fetchers()
+            .into_iter()
+            .map(|(validator, fetcher)| self.poll_validator(validator, fetcher, mailbox_nonce));
+        futures::future::join_all(polls).await;
+    }
+
+    async fn poll_validator(
+        &self,
+        validator: Felt,
+        fetcher: Arc<dyn FetchFromStorage + Send + Sync>,
+        mailbox_nonce: u32,
+    ) {
+        let latest_index = match tokio::time::timeout(self.fetch_timeout, fetcher.fetch_latest_index()).await {
+            Ok(Ok(Some(latest_index))) => latest_index,
+            Ok(Ok(None)) | Err(_) => return,
+            Ok(Err(e)) => {
+                tracing::debug!(
+                    "🌉 [Hyperlane] Failed to poll the latest checkpoint index of validator {:#x}: {:?}",
+                    validator,
+                    e
+                );
+                return;
+            }
+        };
+        let lag = i64::from(latest_index) - i64::from(mailbox_nonce);
+        self.metrics.validator_mailbox_lag.with_label_values(&[&format!("{:#x}", validator)]).set(lag);
+
+        let from = mailbox_nonce.saturating_add(1).max(latest_index.saturating_sub(MAX_PREFETCHED_CHECKPOINTS - 1));
+        let nonces: Vec<u32> = (from..=latest_index)
+            .filter(|&nonce| !self.storage.signed_checkpoints().validator_signed_nonce(validator, nonce))
+            .collect();
+        if nonces.is_empty() {
+            return;
+        }
+        tracing::debug!("🌉 [Hyperlane] Prefetching {} checkpoints of validator {:#x}", nonces.len(), validator);
+        if nonces.len() >= MIN_CHECKPOINTS_FOR_RANGE_FETCH {
+            self.fetch_checkpoints_range_for_validator(validator, fetcher, nonces).await;
+            return;
+        }
+        let fetches = nonces
+            .into_iter()
+            .map(|nonce| self.fetch_checkpoint_for_validator(validator, fetcher.clone(), nonce, self.fetch_timeout));
+        futures::future::join_all(fetches).await;
+    }
+
+    /// Records the time the nonce took to reach quorum since it was indexed.
+    async fn observe_quorum_latency(&self, nonce: u32) {
+        let Some(raw_event) = self.storage.raw_dispatch_events().get(nonce).await else {
+            return;
+        };
+        if let Ok(latency) = (chrono::Utc::now() - raw_event.indexed_at).to_std() {
+            self.metrics.quorum_latency_seconds.observe(latency.as_secs_f64());
+        }
+    }
+
+    /// Fetches the index of the latest checkpoint signed by each validator & reports how far behind the
+    /// latest unsigned nonce they are. Validators whose latest index is unknown are left out.
+    async fn fetch_latest_indexes(
+        &self,
+        validators_fetchers: &ValidatorsFetchersMap,
+        unsigned_nonces: &[u32],
+    ) -> HashMap<Felt, u32> {
+        let Some(&latest_nonce) = unsigned_nonces.iter().max() else {
+            return HashMap::new();
+        };
+
+        let futures = validators_fetchers.iter().map(|(validator, fetcher)| async move {
+            let fetched = tokio::time::timeout(self.fetch_timeout, fetcher.fetch_latest_index()).await;
+            (*validator, fetched)
+        });
+        let mut latest_indexes = HashMap::with_capacity(validators_fetchers.len());
+        for (validator, fetched) in futures::future::join_all(futures).await {
+            let latest_index = match fetched {
+                Ok(Ok(Some(latest_index))) => latest_index,
+                Ok(Ok(None)) => continue,
+                Ok(Err(e)) => {
+                    tracing::debug!(
+                        "🌉 [Hyperlane] Failed to fetch the latest checkpoint index of validator {:#x}: {:?}",
+                        validator,
+                        e
+                    );
+                    continue;
+                }
+                Err(_) => continue,
+            };
+
+            let lag = latest_nonce.saturating_sub(latest_index);
+            self.metrics.validator_checkpoint_lag.with_label_values(&[&format!("{:#x}", validator)]).set(lag.into());
+            if lag > VALIDATOR_LAG_WARNING_THRESHOLD {
+                tracing::warn!(
+                    "🌉 [Hyperlane] Validator {:#x} is {} checkpoints behind (latest signed: #{}, latest dispatched: #{})",
+                    validator,
+                    lag,
+                    latest_index,
+                    latest_nonce
+                );
+            }
+            latest_indexes.insert(validator, latest_index);
+        }
+        latest_indexes
+    }
+
+    /// Reports the validators that signed a checkpoint whose message id isn't the one of the indexed dispatch,
+    /// computed locally: the validators diverging together are reported too, even when they are the majority.
+    async fn detect_diverging_checkpoints(&self, validators_addresses: &[Felt], nonce: u32) {
+        let Some(raw_event) = self.storage.raw_dispatch_events().get(nonce).await else {
+            return;
+        };
+        let expected_message_id = match raw_event.message_id() {
+            Ok(message_id) => message_id,
+            Err(e) => {
+                tracing::warn!("🌉 [Hyperlane] Could not compute the message id of dispatch #{}: {:?}", nonce, e);
+                return;
+            }
+        };
+
+        let checkpoints = self.storage.signed_checkpoints().get(validators_addresses, nonce);
+        // The root can't be computed locally: the expected one is signed along with the expected message id.
+        let expected_root = checkpoints
+            .iter()
+            .find(|(_, checkpoint)| checkpoint.value.message_id == expected_message_id)
+            .map(|(_, checkpoint)| checkpoint.value.checkpoint.root.clone());
+        for (validator, checkpoint) in &checkpoints {
+            if checkpoint.value.message_id == expected_message_id {
+                continue;
+            }
+            let signed = SignedValue::from(&checkpoint.value);
+            let expected = SignedValue {
+                root: expected_root.clone().unwrap_or_else(|| signed.root.clone()),
+                message_id: format!("{:#x}", expected_message_id),
+            };
+            let kind = CheckpointAnomalyKind::RootDivergence { expected, signed };
+            self.report_anomaly(CheckpointAnomaly::new(format!("{:#x}", validator), nonce, kind)).await;
+            self.quarantine(*validator, nonce, QuarantineReason::RootDivergence, checkpoint).await;
+        }
+    }
+
+    /// Stores the anomaly, raises an alert & notifies the subscribers.
+    async fn report_anomaly(&self, anomaly: CheckpointAnomaly) {
+        if !self.storage.checkpoint_anomalies().add(anomaly.clone()).await {
+            return;
+        }
+        self.metrics.checkpoint_anomalies.with_label_values(&[anomaly.kind_name()]).inc();
+        tracing::error!(
+            "🌉 [Hyperlane] 🚨 Validator {} checkpoint #{} anomaly: {:?}",
+            anomaly.validator,
+            anomaly.index,
+            anomaly.kind
+        );
+    }
+
+    /// Keeps a checkpoint that failed validation so it can be shared with the validator operator.
+    async fn quarantine(
+        &self,
+        validator: Felt,
+        nonce: u32,
+        reason: QuarantineReason,
+        checkpoint: &SignedCheckpointWithMessageId,
+    ) {
+        match self.storage.quarantine().add(format!("{:#x}", validator), nonce, reason.clone(), checkpoint).await {
+            Ok(true) => {
+                tracing::warn!(
+                    "🌉 [Hyperlane] Quarantined checkpoint #{} of validator {:#x}: {:?}",
+                    nonce,
+                    validator,
+                    reason
+                );
+            }
+            Ok(false) => {}
+            Err(e) => {
+                tracing::error!("😱 Failed to quarantine checkpoint #{} of validator {:#x}: {:?}", nonce, validator, e);
             }
-            self.send_websocket_notification().await;
-            self.storage.unsigned_checkpoints().remove(nonce).await;
         }
     }
 
@@ -103,22 +430,133 @@ impl HyperlaneService {
         self.storage.signed_checkpoints().all_validators_signed_nonce(validators_addresses, nonce)
     }
 
+    /// Whether the nonce reached quorum on at least one destination chain, according to the [QuorumTracker].
+    /// Without a quorum tracker, whether all the validators signed it.
+    fn quorum_reached(&self, validators_addresses: &[Felt], nonce: u32) -> bool {
+        match &self.quorum_tracker {
+            Some(quorum_tracker) => quorum_tracker.reached_on_any_chain(nonce),
+            None => self.all_validators_signed_nonce(validators_addresses, nonce),
+        }
+    }
+
+    /// Fetches the checkpoints of the validators that didn't sign the nonce yet, sharing the time left
+    /// before the deadline, until `quorum_reached`. Returns the validators whose fetch timed out.
+    pub async fn fetch_missing_checkpoints(
+        &self,
+        validators: &[Felt],
+        nonce: u32,
+        deadline: Instant,
+        quorum_reached: impl Fn() -> bool,
+    ) -> Vec<Felt> {
+        let validators_fetchers = self.storage.validators_fetchers().all();
+        let budget = self.fetch_timeout.min(deadline.saturating_duration_since(Instant::now()));
+        let fetchers = validators
+            .iter()
+            .filter_map(|validator| Some((*validator, validators_fetchers.get(validator)?.clone())))
+            .collect();
+        self.collect_checkpoints(fetchers, nonce, budget, quorum_reached).await
+    }
+
+    /// Fetches again the checkpoint of the nonce from the validators that didn't sign it, bypassing the
+    /// checkpoint cache, e.g. once a validator fixed the checkpoint it published. Returns the validators whose
+    /// fetch timed out.
+    pub async fn refetch_checkpoint(&self, nonce: u32) -> Vec<Felt> {
+        if let Some(cache) = self.storage.validators_fetchers().checkpoint_cache() {
+            cache.evict(Some(nonce));
+        }
+        let fetchers = self.storage.validators_fetchers().all().into_iter().collect();
+        self.collect_checkpoints(fetchers, nonce, self.fetch_timeout, || false).await
+    }
+
+    /// Fetches the checkpoint of the nonce from all the validators at once, as signatures come in no particular
+    /// order. Returns as soon as `quorum_reached`, cancelling the fetches still in flight: their signatures aren't
+    /// needed anymore. Returns the validators whose fetch timed out.
+    async fn collect_checkpoints(
+        &self,
+        fetchers: ValidatorFetchers,
+        nonce: u32,
+        timeout: Duration,
+        quorum_reached: impl Fn() -> bool,
+    ) -> Vec<Felt> {
+        let mut pending: HashMap<Felt, String> =
+            fetchers.iter().map(|(validator, fetcher)| (*validator, fetcher.backend_name())).collect();
+        let mut fetches: FuturesUnordered<_> = fetchers
+            .into_iter()
+            .map(|(validator, fetcher)| async move {
+                (validator, self.fetch_checkpoint_for_validator(validator, fetcher, nonce, timeout).await)
+            })
+            .collect();
+
+        let mut timed_out = Vec::new();
+        while !quorum_reached() {
+            let Some((validator, completed)) = fetches.next().await else {
+                break;
+            };
+            pending.remove(&validator);
+            if !completed {
+                timed_out.push(validator);
+            }
+        }
+        drop(fetches);
+
+        if !pending.is_empty() {
+            tracing::debug!(
+                "🌉 [Hyperlane] Nonce #{} reached quorum, cancelled the fetches of {} validators",
+                nonce,
+                pending.len()
+            );
+        }
+        for (validator, backend) in pending {
+            self.metrics
+                .checkpoints_fetched
+                .with_label_values(&[&format!("{:#x}", validator), &backend, "cancelled"])
+                .inc();
+        }
+        timed_out
+    }
+
     /// Given a validator & a nonce, query the fetcher to try to get the signed checkpoint.
     /// If it exists, it will get stored in the Signed Checkpoints storage.
+    /// Returns `false` if the fetch didn't complete before the timeout.
     async fn fetch_checkpoint_for_validator(
         &self,
         validator: Felt,
         fetcher: Arc<dyn FetchFromStorage + Send + Sync>,
         nonce: u32,
-    ) {
+        timeout: Duration,
+    ) -> bool {
         // If the validator already signed this nonce, ignore
         if self.storage.signed_checkpoints().validator_signed_nonce(validator, nonce) {
-            return;
+            return true;
         }
 
-        match fetcher.fetch(nonce).await {
+        let backend = fetcher.backend_name();
+        let started_at = Instant::now();
+        let fetched = tokio::time::timeout(timeout, fetcher.fetch(nonce)).await;
+        self.metrics
+            .checkpoint_fetch_seconds
+            .with_label_values(&[&backend])
+            .observe(started_at.elapsed().as_secs_f64());
+        let outcome = match &fetched {
+            Ok(Ok(Some(_))) => "signed",
+            Ok(Ok(None)) => "not_signed",
+            Ok(Err(_)) => "failed",
+            Err(_) => "timeout",
+        };
+        self.metrics.checkpoints_fetched.with_label_values(&[&format!("{:#x}", validator), &backend, outcome]).inc();
+
+        let Ok(fetched) = fetched else {
+            tracing::warn!(
+                "🌉 [Hyperlane] Fetching checkpoint #{} of validator {:#x} timed out after {:?}",
+                nonce,
+                validator,
+                timeout
+            );
+            return false;
+        };
+        match fetched {
             Ok(Some(checkpoint)) => {
-                self.store_signed_checkpoint(validator, checkpoint);
+                self.process_fetched_checkpoint(validator, nonce, checkpoint).await;
             }
             Ok(None) => {
                 tracing::debug!("🌉 [Hyperlane] Validator {:#x} has not yet signed nonce {}", validator, nonce);
@@ -132,42 +570,135 @@ impl HyperlaneService {
                 );
             }
         }
+        true
+    }
+
+    /// Fetches at once the checkpoints of a validator missing many nonces, e.g. to catch up after a downtime.
+    async fn fetch_checkpoints_range_for_validator(
+        &self,
+        validator: Felt,
+        fetcher: Arc<dyn FetchFromStorage + Send + Sync>,
+        nonces: Vec<u32>,
+    ) {
+        let (Some(&from), Some(&to)) = (nonces.iter().min(), nonces.iter().max()) else {
+            return;
+        };
+        tracing::info!(
+            "🌉 [Hyperlane] Catching up {} checkpoints (#{} to #{}) of validator {:#x}",
+            nonces.len(),
+            from,
+            to,
+            validator
+        );
+
+        let mut checkpoints =
+            match tokio::time::timeout(VALIDATOR_RANGE_FETCH_TIMEOUT, fetcher.fetch_range(from, to)).await {
+                Ok(Ok(checkpoints)) => checkpoints,
+                Ok(Err(e)) => {
+                    tracing::error!(
+                        "🌉 [Hyperlane] Failed to fetch checkpoints #{} to #{} for validator {:#x}: {:?}",
+                        from,
+                        to,
+                        validator,
+                        e
+                    );
+                    return;
+                }
+                Err(_) => {
+                    tracing::warn!(
+                        "🌉 [Hyperlane] Fetching checkpoints #{} to #{} of validator {:#x} timed out after {:?}",
+                        from,
+                        to,
+                        validator,
+                        VALIDATOR_RANGE_FETCH_TIMEOUT
+                    );
+                    return;
+                }
+            };
+        for nonce in nonces {
+            if let Some(checkpoint) = checkpoints.remove(&nonce) {
+                self.process_fetched_checkpoint(validator, nonce, checkpoint).await;
+            }
+        }
+    }
+
+    /// Stores the checkpoint fetched for the nonce, or quarantines it if it was signed for another index
+    /// or not signed by the validator.
+    async fn process_fetched_checkpoint(&self, validator: Felt, nonce: u32, checkpoint: SignedCheckpointWithMessageId) {
+        if checkpoint.value.checkpoint.index != nonce {
+            let reason = QuarantineReason::IndexMismatch { fetched_index: checkpoint.value.checkpoint.index };
+            self.quarantine(validator, nonce, reason, &checkpoint).await;
+            return;
+        }
+        if let Err(recovered_signer) = Self::verify_signature(validator, &checkpoint) {
+            tracing::error!(
+                "🌉 [Hyperlane] Dropping checkpoint #{} of validator {:#x}: invalid signature (recovered signer: {:?})",
+                nonce,
+                validator,
+                recovered_signer
+            );
+            self.metrics.invalid_checkpoint_signatures.with_label_values(&[&format!("{:#x}", validator)]).inc();
+            self.quarantine(validator, nonce, QuarantineReason::InvalidSignature { recovered_signer }, &checkpoint)
+                .await;
+            return;
+        }
+        self.store_signed_checkpoint(validator, checkpoint).await;
+    }
+
+    /// Checks that the checkpoint was signed by the validator, i.e. the address registered in the ISM
+    /// of the destination chains. On failure, returns the address recovered from the signature, if any.
+    fn verify_signature(validator: Felt, checkpoint: &SignedCheckpointWithMessageId) -> Result<(), Option<String>> {
+        let expected = Address::from_slice(&validator.to_bytes_be()[12..]);
+        match checkpoint.recover_signer() {
+            Ok(signer) if signer == expected => Ok(()),
+            Ok(signer) => Err(Some(signer.to_string())),
+            Err(_) => Err(None),
+        }
     }
 
     /// Store the signed checkpoint for the (validator;nonce) couple.
-    fn store_signed_checkpoint(&self, validator: Felt, checkpoint: SignedCheckpointWithMessageId) {
+    /// If the validator already signed a different checkpoint for this nonce, an equivocation is reported.
+    async fn store_signed_checkpoint(&self, validator: Felt, checkpoint: SignedCheckpointWithMessageId) {
         let nonce = checkpoint.value.checkpoint.index;
 
-        if self.storage.signed_checkpoints().validator_signed_nonce(validator, nonce) {
+        if let Some(existing) = self.storage.signed_checkpoints().get_for_validator(validator, nonce) {
+            if existing.value != checkpoint.value {
+                let kind = CheckpointAnomalyKind::Equivocation {
+                    first: SignedValue::from(&existing.value),
+                    second: SignedValue::from(&checkpoint.value),
+                };
+                self.report_anomaly(CheckpointAnomaly::new(format!("{:#x}", validator), nonce, kind)).await;
+                self.quarantine(validator, nonce, QuarantineReason::Equivocation, &checkpoint).await;
+                return;
+            }
             tracing::debug!("🌉 [Hyperlane] Skipping duplicate checkpoint for validator {:#x}: #{}", validator, nonce);
             return;
         }
 
-        self.storage.signed_checkpoints().add(validator, nonce, checkpoint);
+        self.storage.add_signed_checkpoint(validator, checkpoint).await;
+        if let Some(event) = self.storage.unsigned_checkpoints().get(nonce).await {
+            for update in event.message.body.updates.iter() {
+                let timeline_event =
+                    FeedTimelineEventKind::CheckpointFetched { nonce, validator: format!("{:#x}", validator) };
+                self.storage.feed_timelines().record(&update.feed_id(), timeline_event);
+            }
+        }
         tracing::info!("🌉 [Hyperlane] Validator {:#x} signed checkpoint #{}", validator, nonce);
     }
 
-    /// Stores the updates once it has been signed.
-    /// Also sends an update to the websocket channel that an update has been stored.
-    async fn store_dispatch_updates(&self, nonce: u32) -> anyhow::Result<()> {
+    /// Stores the updates once it has been signed & returns the ids of the updated feeds.
+    async fn store_dispatch_updates(&self, nonce: u32) -> anyhow::Result<Vec<String>> {
         let event = match self.storage.unsigned_checkpoints().get(nonce).await {
             Some(e) => e,
             None => unreachable!(),
         };
-
-        for update in event.message.body.updates.iter() {
-            let dispatch_update_infos = DispatchUpdateInfos::new(&event, update);
-
-            let feed_id = hex_str_to_u256(&update.feed_id())?;
-            self.storage.latest_update_per_feed().add(feed_id, dispatch_update_infos);
-        }
-        Ok(())
+        store_updates(&self.storage, &event).await
     }
 
     /// Sends a websocket notification to any client that *might* be listening.
     /// Allows them to retrieve the latest update instantly after it is stored.
-    async fn send_websocket_notification(&self) {
-        match self.storage.feeds_updated_tx().send(NewUpdatesAvailableEvent::New) {
+    async fn send_websocket_notification(&self, nonce: u32, feed_ids: Vec<String>) {
+        match self.storage.feeds_updated_tx().send(NewUpdatesAvailableEvent::New { nonce, feed_ids }) {
             Ok(_) => {
                 tracing::debug!("🕸️ [Websocket] 🔔 Successfully sent websocket notification");
             }
@@ -178,3 +709,187 @@ impl HyperlaneService {
         }
     }
 }
+
+/// Stores the updates of a dispatch signed by the validators & returns the ids of the updated feeds.
+/// Also used to replay the events of a fixture.
+pub async fn store_updates(storage: &TheorosStorage, event: &DispatchEvent) -> anyhow::Result<Vec<String>> {
+    let nonce = event.message.header.nonce;
+    let mut feed_ids = Vec::with_capacity(event.message.body.updates.len());
+    for update in event.message.body.updates.iter() {
+        let dispatch_update_infos = DispatchUpdateInfos::new(event, update);
+
+        let feed_id = hex_str_to_u256(&update.feed_id())?;
+        storage.add_update(feed_id, dispatch_update_infos).await;
+        storage.feed_timelines().record(&update.feed_id(), FeedTimelineEventKind::UpdateStored { nonce });
+        feed_ids.push(update.feed_id());
+    }
+    Ok(feed_ids)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicU32, Ordering},
+        Mutex,
+    };
+
+    use alloy::primitives::{Parity, U256};
+    use alloy::signers::Signature;
+    use anyhow::Result;
+    use prometheus::Registry;
+
+    use super::*;
+    use crate::services::synthetic::dispatch_event_data;
+    use crate::storage::{FeedIdsStorage, RawDispatchEvent, ValidatorsFetchersStorage};
+    use crate::types::hyperlane::{Checkpoint, CheckpointWithMessageId};
+
+    /// Answers after `delay` that the nonce isn't signed yet, counting its answers.
+    #[derive(Debug)]
+    struct DelayedBackend {
+        delay: Duration,
+        answered: Arc<AtomicU32>,
+    }
+
+    #[async_trait::async_trait]
+    impl FetchFromStorage for DelayedBackend {
+        async fn fetch(&self, _index: u32) -> Result<Option<SignedCheckpointWithMessageId>> {
+            tokio::time::sleep(self.delay).await;
+            self.answered.fetch_add(1, Ordering::SeqCst);
+            Ok(None)
+        }
+
+        async fn fetch_latest_index(&self) -> Result<Option<u32>> {
+            Ok(None)
+        }
+
+        fn announcement_location(&self) -> String {
+            String::from("mock://delayed")
+        }
+    }
+
+    /// Signed up to `latest_index`, recording the fetched indexes. Its checkpoints aren't available yet.
+    #[derive(Debug, Default)]
+    struct AheadBackend {
+        latest_index: u32,
+        fetched: Arc<Mutex<Vec<u32>>>,
+    }
+
+    #[async_trait::async_trait]
+    impl FetchFromStorage for AheadBackend {
+        async fn fetch(&self, index: u32) -> Result<Option<SignedCheckpointWithMessageId>> {
+            self.fetched.lock().unwrap().push(index);
+            Ok(None)
+        }
+
+        async fn fetch_latest_index(&self) -> Result<Option<u32>> {
+            Ok(Some(self.latest_index))
+        }
+
+        fn announcement_location(&self) -> String {
+            String::from("mock://ahead")
+        }
+    }
+
+    #[tokio::test]
+    async fn test_poller_prefetches_the_checkpoints_signed_ahead_of_the_mailbox() {
+        let storage = TheorosStorage::new(FeedIdsStorage::default(), ValidatorsFetchersStorage::default());
+        let fetched = Arc::new(Mutex::new(Vec::new()));
+        let backend = AheadBackend { latest_index: 12, fetched: fetched.clone() };
+        storage.validators_fetchers().add(Felt::ONE, Arc::new(backend));
+        let metrics = Arc::new(TheorosMetrics::register(&Registry::new()).unwrap());
+        let service = HyperlaneService::new(Arc::new(storage), metrics.clone());
+
+        // Nothing is prefetched until a dispatch is indexed.
+        service.poll_latest_checkpoints().await;
+        assert!(fetched.lock().unwrap().is_empty());
+
+        service.storage.raw_dispatch_events().add(RawDispatchEvent::new(10, None, None, &[], &[])).await;
+        service.poll_latest_checkpoints().await;
+        let mut prefetched = fetched.lock().unwrap().clone();
+        prefetched.sort_unstable();
+        assert_eq!(prefetched, vec![11, 12]);
+        assert_eq!(metrics.validator_mailbox_lag.with_label_values(&["0x1"]).get(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_collection_stops_once_quorum_is_reached() {
+        let storage = TheorosStorage::new(FeedIdsStorage::default(), ValidatorsFetchersStorage::default());
+        let metrics = Arc::new(TheorosMetrics::register(&Registry::new()).unwrap());
+        let service = HyperlaneService::new(Arc::new(storage), metrics.clone());
+
+        let answered = Arc::new(AtomicU32::new(0));
+        let fetchers: ValidatorFetchers = [10, 20, 3_600_000]
+            .into_iter()
+            .enumerate()
+            .map(|(i, delay)| {
+                let backend = DelayedBackend { delay: Duration::from_millis(delay), answered: answered.clone() };
+                (Felt::from(i), Arc::new(backend) as Arc<dyn FetchFromStorage + Send + Sync>)
+            })
+            .collect();
+
+        // The slowest validator isn't waited for once two of them answered.
+        let collection = service
+            .collect_checkpoints(fetchers, 1, Duration::from_secs(3600), || answered.load(Ordering::SeqCst) >= 2);
+        let timed_out = tokio::time::timeout(Duration::from_secs(5), collection).await.unwrap();
+        assert!(timed_out.is_empty());
+        assert_eq!(answered.load(Ordering::SeqCst), 2);
+        let cancelled = metrics.checkpoints_fetched.with_label_values(&["0x2", "mock", "cancelled"]).get();
+        assert_eq!(cancelled, 1);
+    }
+
+    fn signed_message_id(nonce: u32, message_id: U256) -> SignedCheckpointWithMessageId {
+        SignedCheckpointWithMessageId {
+            value: CheckpointWithMessageId {
+                checkpoint: Checkpoint {
+                    merkle_tree_hook_address: U256::ZERO,
+                    mailbox_domain: 0,
+                    root: format!("{:#x}", message_id),
+                    index: nonce,
+                },
+                message_id,
+            },
+            signature: Signature::new(U256::from(1), U256::from(2), Parity::Parity(false)),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_checkpoints_diverging_from_the_indexed_message_are_reported() {
+        let storage = TheorosStorage::new(FeedIdsStorage::default(), ValidatorsFetchersStorage::default());
+        let metrics = Arc::new(TheorosMetrics::register(&Registry::new()).unwrap());
+        let service = HyperlaneService::new(Arc::new(storage), metrics);
+
+        let data = dispatch_event_data(7, 1_700_000_000);
+        service.storage.raw_dispatch_events().add(RawDispatchEvent::new(7, None, None, &[], &data)).await;
+        let message_id = U256::from_be_bytes(DispatchEvent::message_id_of(&data).unwrap().0);
+
+        // Even split: a majority can't tell which validator diverges, the indexed message can.
+        let validators = [Felt::ONE, Felt::TWO];
+        service.storage.signed_checkpoints().add(Felt::ONE, 7, signed_message_id(7, message_id));
+        service.storage.signed_checkpoints().add(Felt::TWO, 7, signed_message_id(7, U256::from(42)));
+        service.detect_diverging_checkpoints(&validators, 7).await;
+
+        let anomalies = service.storage.checkpoint_anomalies().all().await;
+        assert_eq!(anomalies.len(), 1);
+        assert_eq!(anomalies[0].validator, "0x2");
+        let CheckpointAnomalyKind::RootDivergence { expected, signed } = &anomalies[0].kind else {
+            panic!("Unexpected anomaly: {:?}", anomalies[0].kind);
+        };
+        assert_eq!(expected.message_id, format!("{:#x}", message_id));
+        assert_eq!(expected.root, format!("{:#x}", message_id));
+        assert_eq!(signed.message_id, "0x2a");
+
+        // Validators agreeing on a wrong message id are all reported, even as the majority.
+        let validators = [Felt::ONE, Felt::TWO, Felt::THREE];
+        service.storage.signed_checkpoints().add(Felt::ONE, 8, signed_message_id(8, U256::from(42)));
+        service.storage.signed_checkpoints().add(Felt::TWO, 8, signed_message_id(8, U256::from(42)));
+        service.storage.signed_checkpoints().add(Felt::THREE, 8, signed_message_id(8, U256::from(42)));
+        service.detect_diverging_checkpoints(&validators, 8).await;
+        // The dispatch #8 isn't indexed: nothing to judge the checkpoints against.
+        assert_eq!(service.storage.checkpoint_anomalies().all().await.len(), 1);
+
+        let data = dispatch_event_data(8, 1_700_000_000);
+        service.storage.raw_dispatch_events().add(RawDispatchEvent::new(8, None, None, &[], &data)).await;
+        service.detect_diverging_checkpoints(&validators, 8).await;
+        assert_eq!(service.storage.checkpoint_anomalies().all().await.len(), 4);
+    }
+}
@@ -1,12 +1,16 @@
-use std::cmp::max;
+pub mod cursor_store;
+pub mod reorg;
+
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use anyhow::{anyhow, bail, Context, Result};
 use apibara_core::{
-    node::v1alpha2::DataFinality,
+    node::v1alpha2::{Cursor, DataFinality},
     starknet::v1alpha2::{Block, Event, FieldElement, Filter, HeaderFilter},
 };
 use apibara_sdk::{configuration, ClientBuilder, Configuration, DataMessage, Uri};
-use futures_util::TryStreamExt;
+use futures_util::{Stream, TryStreamExt};
 use starknet::core::types::Felt;
 use starknet::core::utils::get_selector_from_name;
 use tokio::task::JoinSet;
@@ -16,13 +20,26 @@ use pragma_utils::{
     services::Service,
 };
 
-use crate::types::hyperlane::{DispatchEvent, FromStarknetEventData, ValidatorAnnouncementEvent};
+use crate::configs::indexer_start::IndexerStart;
+use crate::constants::INDEXER_RECONNECT_DELAY;
+use crate::rpc::starknet::BlockCalls;
+use crate::services::indexer::cursor_store::{CursorStore, IndexerCursor};
+use crate::services::indexer::reorg::{IndexedBlock, IndexedBlocks};
+use crate::storage::{DeadLetter, DeadLetterEvent, DispatchParseFailure, RawDispatchEvent};
+use crate::types::hyperlane::{DispatchEvent, FromStarknetEventData};
 use crate::types::state::AppState;
+use crate::types::timeline::FeedTimelineEventKind;
 
 const INDEXING_STREAM_CHUNK_SIZE: usize = 1;
 
 const START_INDEXER_DELTA: u64 = 5;
 
+/// Number of blocks per batch when backfilling.
+const BACKFILL_BATCH_SIZE: u64 = 100;
+
+/// Default time without any message from the stream after which the indexer reconnects.
+const DEFAULT_LIVENESS_TIMEOUT: Duration = Duration::from_secs(60);
+
 lazy_static::lazy_static! {
     // Pragma Dispatcher
     pub static ref DISPATCH_EVENT_SELECTOR: FieldElement = felt_as_apibara_field(&get_selector_from_name("Dispatch").unwrap());
@@ -33,11 +50,52 @@ lazy_static::lazy_static! {
     pub static ref REMOVED_FEED_ID_EVENT_SELECTOR: FieldElement = felt_as_apibara_field(&get_selector_from_name("RemovedFeedId").unwrap());
 }
 
+/// Why the live stream of an Apibara endpoint stopped.
+enum StreamInterruption {
+    Failed(anyhow::Error),
+    /// No message, not even a heartbeat, was received for the liveness timeout.
+    Stalled,
+    Ended,
+    /// The indexer was paused through the admin API.
+    Paused,
+    /// Theoros is shutting down.
+    Shutdown,
+}
+
+impl StreamInterruption {
+    fn reason(&self) -> &'static str {
+        match self {
+            Self::Failed(_) => "failed",
+            Self::Stalled => "stalled",
+            Self::Ended => "ended",
+            Self::Paused => "paused",
+            Self::Shutdown => "shutdown",
+        }
+    }
+}
+
+/// A Starknet event, as emitted, & where it was emitted.
+struct RawEvent<'a> {
+    keys: &'a [Felt],
+    data: &'a [Felt],
+    transaction_hash: Option<Felt>,
+    block_number: Option<u64>,
+}
+
 #[derive(Clone)]
 pub struct IndexerService {
     state: AppState,
-    uri: Uri,
+    /// Apibara DNA endpoints, the first one is streamed from first.
+    uris: Vec<Uri>,
     stream_config: Configuration<Filter>,
+    cursor_store: Option<Arc<dyn CursorStore>>,
+    /// Cursor of the last processed block, the stream resumes from it when reconnecting.
+    last_cursor: Option<Cursor>,
+    liveness_timeout: Duration,
+    /// Block up to which the indexer backfills before switching to live mode.
+    backfill_until: Option<u64>,
+    /// Recently indexed blocks, rolled back when orphaned by a reorg.
+    indexed_blocks: IndexedBlocks,
 }
 
 #[async_trait::async_trait]
@@ -60,10 +118,10 @@ impl IndexerService {
         hyperlane_mailbox_address: Felt,
         hyperlane_validator_announce_address: Felt,
         pragma_feeds_registry_address: Felt,
-        current_block: u64,
+        starting_block: u64,
     ) -> Result<Self> {
         let stream_config = Configuration::<Filter>::default()
-            .with_starting_block(max(0, current_block.saturating_sub(START_INDEXER_DELTA)))
+            .with_starting_block(starting_block)
             .with_filter(|mut filter| {
                 filter
                     .with_header(HeaderFilter::weak())
@@ -86,120 +144,522 @@ impl IndexerService {
             })
             .with_finality(DataFinality::DataStatusPending);
 
-        let indexer_service = Self { state, uri: apibara_uri, stream_config };
+        let indexer_service = Self {
+            state,
+            uris: vec![apibara_uri],
+            stream_config,
+            cursor_store: None,
+            last_cursor: None,
+            liveness_timeout: DEFAULT_LIVENESS_TIMEOUT,
+            backfill_until: None,
+            indexed_blocks: IndexedBlocks::default(),
+        };
         Ok(indexer_service)
     }
 
-    /// Runs the indexer forever.
+    /// Resolves the block from which the indexer should start.
+    /// Defaults to [START_INDEXER_DELTA] blocks before the current one.
+    pub async fn starting_block(starknet_rpc: &(impl BlockCalls + ?Sized), start: Option<IndexerStart>) -> Result<u64> {
+        let starting_block = match start {
+            None => starknet_rpc.block_number().await?.saturating_sub(START_INDEXER_DELTA),
+            Some(IndexerStart::Block(block)) => block,
+            Some(IndexerStart::Timestamp(timestamp)) => starknet_rpc.first_block_after(timestamp).await?,
+            Some(IndexerStart::Ago(duration)) => {
+                let now = SystemTime::now().duration_since(UNIX_EPOCH)?;
+                let timestamp = now.saturating_sub(duration).as_secs();
+                starknet_rpc.first_block_after(timestamp).await?
+            }
+        };
+        tracing::info!("📨 [Indexer] Starting to index from block #{}", starting_block);
+        Ok(starting_block)
+    }
+
+    /// Replays the events from the starting block up to `until_block`, usually the current one, by
+    /// batches of accepted blocks before switching to live mode.
+    pub fn with_backfill(mut self, until_block: u64) -> Self {
+        self.backfill_until = Some(until_block);
+        self
+    }
+
+    /// Apibara DNA endpoints streamed from, in turn, when the stream of the current one fails or stalls.
+    pub fn with_fallback_uris(mut self, uris: Vec<Uri>) -> Self {
+        self.uris.extend(uris);
+        self
+    }
+
+    /// Time without any message from the stream, heartbeats included, after which the indexer reconnects.
+    pub fn with_liveness_timeout(mut self, liveness_timeout: Duration) -> Self {
+        self.liveness_timeout = liveness_timeout;
+        self
+    }
+
+    /// Saves the cursor of the indexer in the store after each accepted block, & resumes from the cursor
+    /// saved by the previous run, if any, instead of the starting block.
+    pub async fn with_cursor_store(mut self, cursor_store: Arc<dyn CursorStore>) -> Result<Self> {
+        if let Some(cursor) = cursor_store.load().await.context("Loading the indexer cursor")? {
+            tracing::info!("📨 [Indexer] Resuming after block #{} from the saved cursor", cursor.block_number);
+            let cursor = Cursor::try_from(&cursor)?;
+            self.stream_config = self.stream_config.with_starting_cursor(cursor.clone());
+            self.last_cursor = Some(cursor);
+        }
+        self.cursor_store = Some(cursor_store);
+        Ok(self)
+    }
+
+    /// Runs the indexer until Theoros shuts down. When the stream fails, ends or stalls, reconnects to the next
+    /// Apibara endpoint & resumes from the last processed block. Once paused through the admin API, disconnects
+    /// until resumed: the backfill isn't paused, the indexer pausing once it switches to live mode.
     pub async fn run_forever(mut self) -> Result<()> {
+        if let Some(until_block) = self.backfill_until {
+            self.backfill(until_block).await?;
+        }
+
+        let mut endpoint = 0;
+        while !self.state.shutdown.is_cancelled() {
+            let uri = self.uris[endpoint].clone();
+            let interruption = self.stream_live(uri.clone()).await?;
+            match interruption {
+                StreamInterruption::Shutdown => break,
+                // Resumes from the same endpoint, after the last processed block.
+                StreamInterruption::Paused => {
+                    self.wait_until_resumed().await;
+                    continue;
+                }
+                _ => {}
+            }
+            self.state.metrics.indexer_reconnections.with_label_values(&[interruption.reason()]).inc();
+            endpoint = (endpoint + 1) % self.uris.len();
+            let next_uri = &self.uris[endpoint];
+            match interruption {
+                StreamInterruption::Failed(e) => {
+                    tracing::error!("📨 [Indexer] The stream of {} failed, reconnecting to {}: {:?}", uri, next_uri, e)
+                }
+                StreamInterruption::Stalled => tracing::warn!(
+                    "📨 [Indexer] No message from {} for {:?}, reconnecting to {}",
+                    uri,
+                    self.liveness_timeout,
+                    next_uri
+                ),
+                StreamInterruption::Ended => {
+                    tracing::warn!("📨 [Indexer] The stream of {} ended, reconnecting to {}", uri, next_uri)
+                }
+                StreamInterruption::Paused | StreamInterruption::Shutdown => {
+                    unreachable!("The indexer waits until resumed or stops")
+                }
+            }
+            // All the endpoints were tried, give them some time to recover.
+            if endpoint == 0 {
+                tokio::select! {
+                    _ = tokio::time::sleep(INDEXER_RECONNECT_DELAY) => {}
+                    _ = self.state.shutdown.cancelled() => {}
+                }
+            }
+        }
+        self.shut_down().await;
+        Ok(())
+    }
+
+    /// Waits until the indexer is resumed through the admin API, or Theoros shuts down.
+    async fn wait_until_resumed(&self) {
+        let mut paused = self.state.indexer_paused.subscribe();
+        match &self.last_cursor {
+            Some(cursor) => tracing::warn!("📨 [Indexer] Paused after block #{}", cursor.order_key),
+            None => tracing::warn!("📨 [Indexer] Paused"),
+        }
+        tokio::select! {
+            _ = paused.wait_for(|paused| !*paused) => {
+                tracing::info!("📨 [Indexer] Resumed");
+            }
+            _ = self.state.shutdown.cancelled() => {}
+        }
+    }
+
+    /// Closes the cursor store. The indexer only stops between two batches, so the saved cursor is the one
+    /// of the last processed block.
+    async fn shut_down(&self) {
+        if let Some(cursor_store) = &self.cursor_store {
+            if let Err(e) = cursor_store.close().await {
+                tracing::warn!("📨 [Indexer] Could not close the cursor store: {:?}", e);
+            }
+        }
+        match &self.last_cursor {
+            Some(cursor) => tracing::info!("📨 [Indexer] Stopped after block #{}", cursor.order_key),
+            None => tracing::info!("📨 [Indexer] Stopped"),
+        }
+    }
+
+    /// Streams the live blocks from the endpoint, from the last processed block, until the stream is interrupted.
+    /// Only fails if a block can't be processed.
+    async fn stream_live(&mut self, uri: Uri) -> Result<StreamInterruption> {
+        let stream_config = match &self.last_cursor {
+            Some(cursor) => self.stream_config.clone().with_starting_cursor(cursor.clone()),
+            None => self.stream_config.clone(),
+        };
+        let mut stream = match self.start_stream(uri, stream_config).await {
+            Ok(stream) => stream,
+            Err(e) => return Ok(StreamInterruption::Failed(e)),
+        };
+        let shutdown = self.state.shutdown.clone();
+        let mut paused = self.state.indexer_paused.subscribe();
+        loop {
+            // Only between two batches, so the paused indexer resumes after the last processed block.
+            let next = tokio::select! {
+                next = tokio::time::timeout(self.liveness_timeout, stream.try_next()) => next,
+                _ = paused.wait_for(|paused| *paused) => return Ok(StreamInterruption::Paused),
+                _ = shutdown.cancelled() => return Ok(StreamInterruption::Shutdown),
+            };
+            match next {
+                Ok(Ok(Some(response))) => self.process_batch(response).await?,
+                Ok(Ok(None)) => return Ok(StreamInterruption::Ended),
+                Ok(Err(e)) => return Ok(StreamInterruption::Failed(e)),
+                Err(_) => return Ok(StreamInterruption::Stalled),
+            }
+        }
+    }
+
+    /// Streams the accepted blocks by batches until `until_block` is processed, then makes the live stream
+    /// start from the last processed block.
+    async fn backfill(&mut self, until_block: u64) -> Result<()> {
+        tracing::info!("📨 [Indexer] Backfilling up to block #{}", until_block);
+        let config = self
+            .stream_config
+            .clone()
+            .with_finality(DataFinality::DataStatusAccepted)
+            .with_batch_size(BACKFILL_BATCH_SIZE);
+        let mut stream = self.start_stream(self.uris[0].clone(), config).await?;
+
+        let shutdown = self.state.shutdown.clone();
+        loop {
+            let response = tokio::select! {
+                response = stream.try_next() => response?,
+                _ = shutdown.cancelled() => return Ok(()),
+            };
+            let Some(response) = response else {
+                break;
+            };
+            let end_cursor = match &response {
+                DataMessage::Data { end_cursor, .. } => Some(end_cursor.clone()),
+                _ => None,
+            };
+            self.process_batch(response).await?;
+            let Some(end_cursor) = end_cursor else {
+                continue;
+            };
+            if end_cursor.order_key >= until_block {
+                tracing::info!("📨 [Indexer] Backfilled up to block #{}, switching to live mode", end_cursor.order_key);
+                self.stream_config = self.stream_config.clone().with_starting_cursor(end_cursor);
+                return Ok(());
+            }
+            tracing::debug!("📨 [Indexer] Backfilled up to block #{}/{}", end_cursor.order_key, until_block);
+        }
+        bail!("Indexing stream ended while backfilling")
+    }
+
+    async fn start_stream(
+        &self,
+        uri: Uri,
+        stream_config: Configuration<Filter>,
+    ) -> Result<impl Stream<Item = Result<DataMessage<Block>>> + Unpin> {
         let (config_client, config_stream) = configuration::channel(INDEXING_STREAM_CHUNK_SIZE);
 
-        config_client.send(self.stream_config.clone()).await.context("Sending indexing stream configuration")?;
+        config_client.send(stream_config).await.context("Sending indexing stream configuration")?;
 
-        let mut stream = ClientBuilder::default()
-            .connect(self.uri.clone())
+        ClientBuilder::default()
+            .connect(uri)
             .await
             .map_err(|e| anyhow!("Error while connecting to Apibara DNA: {}", e))?
             .start_stream::<Filter, Block, _>(config_stream)
             .await
-            .map_err(|e| anyhow!("Error while starting indexing stream: {}", e))?;
-
-        loop {
-            match stream.try_next().await {
-                Ok(Some(response)) => {
-                    self.process_batch(response).await?;
-                }
-                Ok(None) => continue,
-                Err(e) => bail!("Error while streaming indexed batch: {}", e),
-            }
-        }
+            .map(|stream| stream.map_err(|e| anyhow!("Error while streaming indexed batch: {}", e)))
+            .map_err(|e| anyhow!("Error while starting indexing stream: {}", e))
     }
 
     /// Process a batch of blocks indexed by Apibara DNA
     async fn process_batch(&mut self, batch: DataMessage<Block>) -> Result<()> {
         match batch {
-            DataMessage::Data { cursor: _, end_cursor: _, finality: _, batch } => {
+            DataMessage::Data { cursor: _, end_cursor, finality, batch } => {
                 for block in batch {
-                    for event in block.clone().events.into_iter().filter_map(|e| e.event) {
+                    let block_number = block.header.as_ref().map(|header| header.block_number);
+                    let block_hash = block
+                        .header
+                        .as_ref()
+                        .and_then(|header| header.block_hash.as_ref())
+                        .map(apibara_field_as_felt)
+                        .filter(|hash| *hash != Felt::ZERO);
+                    if let Some(block_number) = block_number {
+                        if self.indexed_blocks.is_orphaned_by(block_number, block_hash) {
+                            let orphaned = self.indexed_blocks.orphan_from(block_number);
+                            self.roll_back(orphaned, &format!("block #{block_number} was replaced")).await;
+                        }
+                    }
+
+                    let mut indexed = IndexedBlock::new(block_hash);
+                    for event_with_tx in block.clone().events {
+                        let Some(event) = event_with_tx.event else {
+                            continue;
+                        };
                         if event.from_address.is_none() {
                             continue;
                         }
-                        self.process_event(event, &block).await?;
+                        let transaction_hash = event_with_tx
+                            .transaction
+                            .and_then(|tx| tx.meta)
+                            .and_then(|meta| meta.hash)
+                            .map(|hash| apibara_field_as_felt(&hash));
+                        self.process_event(event, transaction_hash, &block, &mut indexed).await?;
                     }
+                    let Some(block_number) = block_number else {
+                        continue;
+                    };
+                    if let Some(dropped) = self.indexed_blocks.record(block_number, indexed) {
+                        let reason = format!("events of the pending block #{block_number} were dropped");
+                        self.roll_back(vec![(block_number, dropped)], &reason).await;
+                    }
+                }
+                // Pending blocks are sent again once accepted, resuming after them would skip their events.
+                if finality != DataFinality::DataStatusPending {
+                    self.save_cursor(&end_cursor).await;
                 }
             }
-            DataMessage::Invalidate { cursor } => match cursor {
-                Some(c) => bail!("Indexed an invalidate request data at {}", &c.order_key),
-                None => bail!("Invalidate request without cursor provided"),
-            },
+            DataMessage::Invalidate { cursor: Some(cursor) } => {
+                let orphaned = self.indexed_blocks.orphan_from(cursor.order_key + 1);
+                self.roll_back(orphaned, &format!("blocks after #{} were invalidated", cursor.order_key)).await;
+                self.save_cursor(&cursor).await;
+            }
+            DataMessage::Invalidate { cursor: None } => bail!("Invalidate request without cursor provided"),
             DataMessage::Heartbeat => {}
         }
         Ok(())
     }
 
+    /// Rolls back, newest first, what the blocks orphaned by a reorg brought: their dispatches & the feed ids
+    /// they registered or removed. Validator announcements are kept, they only add storage locations.
+    async fn roll_back(&self, orphaned: Vec<(u64, IndexedBlock)>, reason: &str) {
+        let num_dispatches: usize = orphaned.iter().map(|(_, block)| block.dispatches.len()).sum();
+        tracing::warn!(
+            "📨 [Indexer] Reorg detected, {}: rolling back {} blocks & {} dispatches",
+            reason,
+            orphaned.len(),
+            num_dispatches
+        );
+        self.state.metrics.reorgs.inc();
+
+        for (block_number, block) in orphaned.into_iter().rev() {
+            for (nonce, feed_ids) in block.dispatches.into_iter().rev() {
+                tracing::warn!(
+                    "📨 [Indexer] Rolling back the Dispatch event with nonce #{} of the orphaned block #{}",
+                    nonce,
+                    block_number
+                );
+                self.state.storage.roll_back_dispatch(nonce).await;
+                for feed_id in feed_ids {
+                    let event = FeedTimelineEventKind::DispatchOrphaned { nonce, block_number };
+                    self.state.storage.feed_timelines().record(&feed_id, event);
+                }
+                self.state.metrics.dispatches_rolled_back.inc();
+            }
+            for feed_id in block.new_feed_ids {
+                self.state.storage.feed_ids().remove(&feed_id);
+            }
+            for feed_id in block.removed_feed_ids {
+                self.state.storage.feed_ids().add(feed_id);
+            }
+        }
+    }
+
+    /// Persists the cursor of the last processed block. Failing to do so only means more blocks are
+    /// processed again after a restart, so the indexer keeps running.
+    async fn save_cursor(&mut self, end_cursor: &Cursor) {
+        self.last_cursor = Some(end_cursor.clone());
+        let Some(cursor_store) = &self.cursor_store else {
+            return;
+        };
+        let cursor = IndexerCursor::from(end_cursor);
+        if let Err(e) = cursor_store.save(&cursor).await {
+            tracing::warn!("📨 [Indexer] Could not save the cursor at block #{}: {:?}", cursor.block_number, e);
+        }
+    }
+
     /// Decodes a starknet [Event].
-    async fn process_event(&self, event: Event, block: &Block) -> Result<()> {
+    async fn process_event(
+        &self,
+        event: Event,
+        transaction_hash: Option<Felt>,
+        block: &Block,
+        indexed: &mut IndexedBlock,
+    ) -> Result<()> {
         let event_selector = event.keys.first().context("No event selector")?;
         let event_data: Vec<Felt> = event.data.iter().map(apibara_field_as_felt).collect();
-        match event_selector {
+        let event_keys: Vec<Felt> = event.keys.iter().map(apibara_field_as_felt).collect();
+        let block_number = block.header.as_ref().map(|header| header.block_number);
+        let raw_event = RawEvent { keys: &event_keys, data: &event_data, transaction_hash, block_number };
+        let event_name = match event_selector {
             selector if selector == &*DISPATCH_EVENT_SELECTOR => {
-                self.decode_dispatch_event(event_data, block).await?;
+                if self.is_skipped_destination(&event_data) {
+                    return Ok(());
+                }
+                let Some(dispatch_event) = self.decode_or_dead_letter(DeadLetterEvent::Dispatch, &raw_event).await
+                else {
+                    return Ok(());
+                };
+                if self.is_unknown_emitter(&dispatch_event) {
+                    return Ok(());
+                }
+                self.index_dispatch_event(dispatch_event, &raw_event, indexed).await;
+                "dispatch"
             }
             selector if selector == &*VALIDATOR_ANNOUNCEMENT_SELECTOR => {
-                self.decode_validator_announce_event(event_data).await?;
+                let Some(announcement) =
+                    self.decode_or_dead_letter(DeadLetterEvent::ValidatorAnnouncement, &raw_event).await
+                else {
+                    return Ok(());
+                };
+                tracing::info!("📨 [Indexer] Indexed a ValidatorAnnouncement event");
+                self.state.storage.validators_fetchers().add_from_announcement_event(announcement).await?;
+                "validator_announcement"
             }
             selector if selector == &*NEW_FEED_ID_EVENT_SELECTOR => {
-                self.decode_new_feed_id_event(event_data);
+                indexed.new_feed_ids.push(self.decode_new_feed_id_event(event_data));
+                "new_feed_id"
             }
             selector if selector == &*REMOVED_FEED_ID_EVENT_SELECTOR => {
-                self.decode_removed_feed_id_event(event_data);
+                indexed.removed_feed_ids.push(self.decode_removed_feed_id_event(event_data));
+                "removed_feed_id"
             }
             _ => unreachable!(),
-        }
+        };
+        self.state.metrics.events_indexed.with_label_values(&[event_name]).inc();
         Ok(())
     }
 
-    /// Decodes a DispatchEvent from the Starknet event data.
-    async fn decode_dispatch_event(&self, event_data: Vec<Felt>, block: &Block) -> anyhow::Result<()> {
-        let dispatch_event = DispatchEvent::from_starknet_event_data(event_data).context("Parsing DispatchEvent")?;
+    /// Whether the dispatch is sent to a domain missing from the EVM config, when filtering the destinations.
+    /// Dispatches whose destination can't be read aren't skipped, to be dead-lettered.
+    fn is_skipped_destination(&self, event_data: &[Felt]) -> bool {
+        if !self.state.filter_dispatch_destinations {
+            return false;
+        }
+        let Some(destination_domain) = DispatchEvent::destination_domain_of(event_data) else {
+            return false;
+        };
+        if self.state.evm_config.current().destination_domains().contains(&destination_domain) {
+            return false;
+        }
+        tracing::debug!("📨 [Indexer] Skipped a Dispatch event sent to the domain {}", destination_domain);
+        self.state.metrics.dispatches_skipped.with_label_values(&[&destination_domain.to_string()]).inc();
+        true
+    }
+
+    /// Whether the dispatch was sent by a Pragma dispatcher which isn't indexed, when some are configured.
+    fn is_unknown_emitter(&self, dispatch_event: &DispatchEvent) -> bool {
+        let emitter_address = dispatch_event.emitter_address();
+        if self.state.storage.emitters().is_indexed(&emitter_address) {
+            return false;
+        }
+        tracing::debug!("📨 [Indexer] Skipped a Dispatch event sent by the unknown emitter {:#x}", emitter_address);
+        self.state.metrics.dispatches_unknown_emitter.inc();
+        true
+    }
+
+    /// Decodes the event from its Starknet event data. Events that can't be decoded are dead-lettered, to be
+    /// replayed through the admin API once the parser is fixed, & skipped.
+    async fn decode_or_dead_letter<T: FromStarknetEventData>(
+        &self,
+        kind: DeadLetterEvent,
+        raw_event: &RawEvent<'_>,
+    ) -> Option<T> {
+        let error = match T::from_starknet_event_data(raw_event.data.to_vec()) {
+            Ok(event) => return Some(event),
+            Err(e) => e,
+        };
+        let dead_letter = DeadLetter::new(
+            kind,
+            raw_event.block_number,
+            raw_event.transaction_hash,
+            raw_event.keys,
+            raw_event.data,
+            &error,
+        );
+        let dead_letter = self.state.storage.add_dead_letter(dead_letter).await;
+        tracing::error!(
+            "📨 [Indexer] Failed to decode a {} event, dead-lettered as #{}: {:?}",
+            kind.as_str(),
+            dead_letter.id,
+            error
+        );
+        self.state.metrics.dead_letters.with_label_values(&[kind.as_str()]).inc();
+        None
+    }
+
+    /// Stores a decoded DispatchEvent & keeps the raw event.
+    async fn index_dispatch_event(
+        &self,
+        dispatch_event: DispatchEvent,
+        raw_event: &RawEvent<'_>,
+        indexed: &mut IndexedBlock,
+    ) {
         let nonce = dispatch_event.message.header.nonce;
-        match &block.header {
-            Some(h) => {
-                tracing::info!(
-                    "📨 [Indexer] [Block {}] Indexed a Dispatch event with nonce #{}",
-                    h.block_number,
-                    nonce,
-                );
+        match raw_event.block_number {
+            Some(block_number) => {
+                tracing::info!("📨 [Indexer] [Block {}] Indexed a Dispatch event with nonce #{}", block_number, nonce);
             }
             None => {
                 tracing::info!("📨 [Indexer] Indexed a Dispatch event with nonce #{}", nonce);
             }
         };
-        self.state.storage.unsigned_checkpoints().add(nonce, &dispatch_event).await;
-        Ok(())
+        let raw_event = RawDispatchEvent::new(
+            nonce,
+            raw_event.block_number,
+            raw_event.transaction_hash,
+            raw_event.keys,
+            raw_event.data,
+        );
+        let feed_ids = store_dispatch(&self.state, &dispatch_event, raw_event).await;
+        indexed.dispatches.insert(nonce, feed_ids);
     }
 
-    /// Decodes a ValidatorAnnouncementEvent from the Starknet event data.
-    async fn decode_validator_announce_event(&self, event_data: Vec<Felt>) -> anyhow::Result<()> {
-        tracing::info!("📨 [Indexer] Indexed a ValidatorAnnouncement event");
-        let validator_announcement_event = ValidatorAnnouncementEvent::from_starknet_event_data(event_data)
-            .context("Failed to parse ValidatorAnnouncement")?;
-        let validators = &mut self.state.storage.validators_fetchers();
-        validators.add_from_announcement_event(validator_announcement_event).await?;
-        Ok(())
-    }
-
-    /// Decodes a NewFeedId event from the Starknet event data.
-    fn decode_new_feed_id_event(&self, event_data: Vec<Felt>) {
+    /// Decodes a NewFeedId event from the Starknet event data & returns the new feed id.
+    fn decode_new_feed_id_event(&self, event_data: Vec<Felt>) -> String {
         let feed_id = event_data[1].to_hex_string();
         tracing::info!("📨 [Indexer] Indexed a NewFeedId event for: {}", feed_id);
-        self.state.storage.feed_ids().add(feed_id);
+        self.state.storage.feed_ids().add(feed_id.clone());
+        feed_id
     }
 
-    /// Decodes a RemovedFeedId event from the Starknet event data.
-    fn decode_removed_feed_id_event(&self, event_data: Vec<Felt>) {
+    /// Decodes a RemovedFeedId event from the Starknet event data & returns the removed feed id.
+    fn decode_removed_feed_id_event(&self, event_data: Vec<Felt>) -> String {
         let feed_id = event_data[1].to_hex_string();
         tracing::info!("📨 [Indexer] Indexed a RemovedFeedId event for: {}", feed_id);
         self.state.storage.feed_ids().remove(&feed_id);
+        feed_id
+    }
+}
+
+/// Stores a decoded dispatch, waiting for the signatures of the validators, & returns the feeds it updates.
+/// Also used to replay the dead-lettered dispatches.
+pub async fn store_dispatch(
+    state: &AppState,
+    dispatch_event: &DispatchEvent,
+    raw_event: RawDispatchEvent,
+) -> Vec<String> {
+    let nonce = dispatch_event.message.header.nonce;
+    let block_number = raw_event.block_number;
+    for failure in dispatch_event.message.body.parse_failures.iter() {
+        tracing::error!(
+            "📨 [Indexer] Failed to parse update #{} of the Dispatch event with nonce #{} at offset {}: {}",
+            failure.update_index,
+            nonce,
+            failure.offset,
+            failure.error
+        );
+        state.storage.parse_failures().add(DispatchParseFailure::new(nonce, block_number, failure)).await;
+        state.metrics.update_parse_failures.inc();
+    }
+    state.storage.add_dispatch(raw_event, dispatch_event).await;
+    let feed_ids: Vec<String> = dispatch_event.message.body.updates.iter().map(|update| update.feed_id()).collect();
+    for feed_id in feed_ids.iter() {
+        let event = FeedTimelineEventKind::DispatchIndexed { nonce, block_number };
+        state.storage.feed_timelines().record(feed_id, event);
     }
+    state.metrics.dispatches_indexed.inc();
+    feed_ids
 }
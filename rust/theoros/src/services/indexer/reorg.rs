@@ -0,0 +1,112 @@
+use std::collections::BTreeMap;
+
+use starknet::core::types::Felt;
+
+/// Number of blocks tracked below the latest indexed one. Older blocks are considered final.
+const TRACKED_BLOCKS: u64 = 256;
+
+/// What an indexed block brought, so it can be rolled back if the block is orphaned.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IndexedBlock {
+    /// Hash of the block. Unknown for pending blocks.
+    pub hash: Option<Felt>,
+    /// Nonces of the indexed Dispatch events, with the feeds they update.
+    pub dispatches: BTreeMap<u32, Vec<String>>,
+    pub new_feed_ids: Vec<String>,
+    pub removed_feed_ids: Vec<String>,
+}
+
+impl IndexedBlock {
+    pub fn new(hash: Option<Felt>) -> Self {
+        Self { hash, ..Default::default() }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.dispatches.is_empty() && self.new_feed_ids.is_empty() && self.removed_feed_ids.is_empty()
+    }
+}
+
+/// The recently indexed blocks, by number, to detect the ones orphaned by a reorg.
+#[derive(Debug, Clone, Default)]
+pub struct IndexedBlocks(BTreeMap<u64, IndexedBlock>);
+
+impl IndexedBlocks {
+    /// Whether a block with another hash was indexed at the same height, i.e. it was orphaned.
+    pub fn is_orphaned_by(&self, block_number: u64, hash: Option<Felt>) -> bool {
+        match (self.0.get(&block_number).and_then(|block| block.hash), hash) {
+            (Some(indexed), Some(hash)) => indexed != hash,
+            _ => false,
+        }
+    }
+
+    /// Records an indexed block. When the block was already indexed, e.g. while pending, returns what the
+    /// previous version brought that this one doesn't.
+    pub fn record(&mut self, block_number: u64, block: IndexedBlock) -> Option<IndexedBlock> {
+        let previous = self.0.insert(block_number, block.clone());
+        let oldest_tracked = block_number.saturating_sub(TRACKED_BLOCKS);
+        self.0 = self.0.split_off(&oldest_tracked);
+
+        let previous = previous?;
+        let dropped = IndexedBlock {
+            hash: previous.hash,
+            dispatches: previous
+                .dispatches
+                .into_iter()
+                .filter(|(nonce, _)| !block.dispatches.contains_key(nonce))
+                .collect(),
+            new_feed_ids: previous
+                .new_feed_ids
+                .into_iter()
+                .filter(|feed_id| !block.new_feed_ids.contains(feed_id))
+                .collect(),
+            removed_feed_ids: previous
+                .removed_feed_ids
+                .into_iter()
+                .filter(|feed_id| !block.removed_feed_ids.contains(feed_id))
+                .collect(),
+        };
+        (!dropped.is_empty()).then_some(dropped)
+    }
+
+    /// Forgets the blocks from `block_number` on, returning them in ascending order.
+    pub fn orphan_from(&mut self, block_number: u64) -> Vec<(u64, IndexedBlock)> {
+        self.0.split_off(&block_number).into_iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block(hash: u64, nonces: &[u32]) -> IndexedBlock {
+        IndexedBlock {
+            hash: Some(Felt::from(hash)),
+            dispatches: nonces.iter().map(|nonce| (*nonce, vec![])).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_orphaned_blocks() {
+        let mut blocks = IndexedBlocks::default();
+        assert!(blocks.record(10, block(0xa, &[1])).is_none());
+        assert!(blocks.record(11, block(0xb, &[2, 3])).is_none());
+
+        assert!(!blocks.is_orphaned_by(11, Some(Felt::from(0xb_u64))));
+        assert!(!blocks.is_orphaned_by(12, Some(Felt::from(0xc_u64))));
+        assert!(blocks.is_orphaned_by(11, Some(Felt::from(0xbb_u64))));
+
+        // A pending block accepted without one of its events.
+        let pending = IndexedBlock { hash: None, ..block(0, &[4, 5]) };
+        assert!(blocks.record(12, pending).is_none());
+        let dropped = blocks.record(12, block(0xc, &[4])).unwrap();
+        assert_eq!(dropped.dispatches.keys().copied().collect::<Vec<_>>(), vec![5]);
+
+        let orphaned = blocks.orphan_from(11);
+        assert_eq!(orphaned.iter().map(|(number, _)| *number).collect::<Vec<_>>(), vec![11, 12]);
+        assert!(!blocks.is_orphaned_by(11, Some(Felt::from(0xbb_u64))));
+
+        blocks.record(10 + TRACKED_BLOCKS + 1, block(0xd, &[]));
+        assert!(!blocks.is_orphaned_by(10, Some(Felt::from(0xaa_u64))));
+    }
+}
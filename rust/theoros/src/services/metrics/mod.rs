@@ -1,7 +1,12 @@
 // Source:
 // https://github.com/madara-alliance/madara/blob/main/crates/client/metrics/src/lib.rs#L66
+pub mod theoros_metrics;
+
+pub use theoros_metrics::TheorosMetrics;
 
 use std::net::{Ipv4Addr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
 
 use anyhow::{Context, Result};
 use hyper::{
@@ -22,9 +27,15 @@ use tokio::{
     net::TcpListener,
     task::{JoinHandle, JoinSet},
 };
+use tokio_util::sync::CancellationToken;
 
 use pragma_utils::services::Service;
 
+use crate::{storage::TheorosStorage, AppState};
+
+/// Every [METRICS_PERSISTENCE_INTERVAL], the persisted counters are written to the storage backend.
+const METRICS_PERSISTENCE_INTERVAL: Duration = Duration::from_secs(15);
+
 #[derive(thiserror::Error, Debug)]
 #[error("error while handling request in prometheus endpoint: {0}")]
 enum MetricsError {
@@ -38,12 +49,37 @@ pub struct MetricsService {
     prometheus_external: bool,
     prometheus_port: u16,
     registry: Registry,
+    persisted_metrics: Option<PersistedMetrics>,
+    shutdown: CancellationToken,
+}
+
+/// Metrics whose counters are persisted into the storage backend, under the name of the process.
+#[derive(Clone)]
+struct PersistedMetrics {
+    metrics: Arc<TheorosMetrics>,
+    storage: Arc<TheorosStorage>,
+    instance: String,
 }
 
 #[async_trait::async_trait]
 impl Service for MetricsService {
     async fn start(&mut self, join_set: &mut JoinSet<anyhow::Result<()>>) -> anyhow::Result<()> {
         let service = self.clone();
+        if let Some(persisted) = self.persisted_metrics.clone() {
+            let shutdown = self.shutdown.clone();
+            join_set.spawn(async move {
+                loop {
+                    tokio::select! {
+                        _ = tokio::time::sleep(METRICS_PERSISTENCE_INTERVAL) => {}
+                        // Flushed one last time by the shutdown, before the storage is closed.
+                        _ = shutdown.cancelled() => return Ok(()),
+                    };
+                    if let Err(e) = persisted.metrics.persist(&persisted.storage, &persisted.instance).await {
+                        tracing::error!("😱 Failed to persist metrics: {:?}", e);
+                    }
+                }
+            });
+        }
         join_set.spawn(async move {
             service.run_forever()?;
             Ok(())
@@ -54,10 +90,33 @@ impl Service for MetricsService {
 
 impl MetricsService {
     pub fn new(prometheus_external: bool, prometheus_port: u16) -> Result<Self> {
-        let service = Self { prometheus_external, prometheus_port, registry: Default::default() };
+        let service = Self {
+            prometheus_external,
+            prometheus_port,
+            registry: Default::default(),
+            persisted_metrics: None,
+            shutdown: CancellationToken::new(),
+        };
         Ok(service)
     }
 
+    /// Periodically persists the counters of the metrics of the state into its storage backend, under the name of
+    /// the process, if any.
+    pub fn with_persisted_metrics(mut self, state: &AppState, instance: Option<String>) -> Self {
+        self.persisted_metrics = instance.map(|instance| PersistedMetrics {
+            metrics: state.metrics.clone(),
+            storage: state.storage.clone(),
+            instance,
+        });
+        self
+    }
+
+    /// Stops persisting the counters once the token is cancelled.
+    pub fn with_shutdown(mut self, shutdown: CancellationToken) -> Self {
+        self.shutdown = shutdown;
+        self
+    }
+
     pub fn registry(&self) -> Registry {
         self.registry.clone()
     }
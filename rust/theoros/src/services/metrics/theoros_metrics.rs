@@ -0,0 +1,444 @@
+use anyhow::Result;
+use prometheus::{
+    Histogram, HistogramOpts, HistogramVec, IntCounter, IntCounterVec, IntGauge, IntGaugeVec, Opts, Registry,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::storage::{decode_versioned, encode_versioned, TheorosStorage, VersionedState};
+
+/// Values of the monotonic counters, persisted so they survive restarts.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct PersistedCounters {
+    dispatches_indexed: u64,
+    calldata_served: u64,
+}
+
+impl VersionedState for PersistedCounters {
+    const NAME: &'static str = "metrics";
+    const SCHEMA_VERSION: u32 = 1;
+
+    fn migrate(version: u32, state: serde_json::Value) -> Result<serde_json::Value> {
+        match version {
+            // v0 -> v1: the state file is versioned, the counters are unchanged.
+            0 => Ok(state),
+            _ => anyhow::bail!("No migration from schema version {version}"),
+        }
+    }
+}
+
+/// Metrics exposed by Theoros.
+pub struct TheorosMetrics {
+    /// Number of Dispatch events indexed
+    pub dispatches_indexed: IntCounter,
+    /// Number of reorgs detected by the indexer
+    pub reorgs: IntCounter,
+    /// Number of Dispatch events rolled back because their block was orphaned
+    pub dispatches_rolled_back: IntCounter,
+    /// Number of calldata served through the REST & WebSocket endpoints
+    pub calldata_served: IntCounter,
+    /// Number of checkpoint anomalies detected, per kind
+    pub checkpoint_anomalies: IntCounterVec,
+    /// Number of fetched checkpoints dropped because of an invalid signature, per validator
+    pub invalid_checkpoint_signatures: IntCounterVec,
+    /// Number of API requests received, per priority lane
+    pub api_requests: IntCounterVec,
+    /// Number of API requests shed under load, per priority lane
+    pub api_requests_shed: IntCounterVec,
+    /// Number of API requests rejected by the rate limits, per kind of client (IP or API key)
+    pub api_requests_rate_limited: IntCounterVec,
+    /// Number of API requests being served, per priority lane
+    pub api_requests_in_flight: IntGaugeVec,
+    /// Time spent by the API requests waiting to be admitted, per priority lane
+    pub api_queue_wait_seconds: HistogramVec,
+    /// Number of checkpoints a validator is behind the latest dispatched message, per validator
+    pub validator_checkpoint_lag: IntGaugeVec,
+    /// Latest checkpoint index signed by a validator minus the nonce of the latest dispatched message, per
+    /// validator. Negative when the validator is behind the mailbox.
+    pub validator_mailbox_lag: IntGaugeVec,
+    /// Time taken by a dispatch of the synthetic feed to go through the whole pipeline
+    pub synthetic_feed_latency_seconds: Histogram,
+    /// Number of dispatches of the synthetic feed that failed to go through the pipeline
+    pub synthetic_feed_failures: IntCounter,
+    /// Unix timestamp of the last dispatch of the synthetic feed that went through the pipeline
+    pub synthetic_feed_last_success: IntGauge,
+    /// Validator sets changed on-chain & swapped in by the periodic refresh, by chain.
+    pub validator_set_changes: IntCounterVec,
+    /// Failed refreshes of the validator set, by chain.
+    pub validator_set_refresh_failures: IntCounterVec,
+    /// Reconnections of the indexer to an Apibara endpoint, by reason the previous stream stopped.
+    pub indexer_reconnections: IntCounterVec,
+    /// Reloads of the watched config files, by file & outcome (applied or rejected).
+    pub config_reloads: IntCounterVec,
+    /// Events indexed from the Pragma chain, by event.
+    pub events_indexed: IntCounterVec,
+    /// Updates of the indexed dispatches that could not be parsed.
+    pub update_parse_failures: IntCounter,
+    /// Indexed events that could not be decoded & were dead-lettered, by event.
+    pub dead_letters: IntCounterVec,
+    /// Dispatches skipped as sent to a domain not configured, by destination domain.
+    pub dispatches_skipped: IntCounterVec,
+    /// Dispatches skipped as sent by a Pragma dispatcher which isn't configured.
+    pub dispatches_unknown_emitter: IntCounter,
+    /// Entries pruned by the compactions enforcing the retention policy, by kind & storage.
+    pub pruned: IntCounterVec,
+    /// Attempts to fetch a checkpoint, by validator, storage backend & outcome.
+    pub checkpoints_fetched: IntCounterVec,
+    /// Time taken to fetch a checkpoint from a validator, by storage backend.
+    pub checkpoint_fetch_seconds: HistogramVec,
+    /// Lookups of the fetched checkpoints cache, by outcome (hit or miss).
+    pub checkpoint_cache_lookups: IntCounterVec,
+    /// Time between the indexing of a dispatch & its quorum.
+    pub quorum_latency_seconds: Histogram,
+    /// Time taken to serve the API requests, by method, route & status.
+    pub http_request_duration_seconds: HistogramVec,
+    /// Open WebSocket connections, by endpoint.
+    pub ws_connections: IntGaugeVec,
+    /// Transactions of the relayer, by chain & outcome (sent, failed, confirmed, reverted or timed out).
+    pub relayer_transactions: IntCounterVec,
+}
+
+impl TheorosMetrics {
+    /// Registers the metrics into the registry.
+    pub fn register(registry: &Registry) -> Result<Self> {
+        let dispatches_indexed =
+            IntCounter::new("theoros_dispatches_indexed_total", "Number of Dispatch events indexed")?;
+        registry.register(Box::new(dispatches_indexed.clone()))?;
+
+        let reorgs = IntCounter::new("theoros_reorgs_total", "Number of reorgs detected by the indexer")?;
+        registry.register(Box::new(reorgs.clone()))?;
+
+        let dispatches_rolled_back = IntCounter::new(
+            "theoros_dispatches_rolled_back_total",
+            "Number of Dispatch events rolled back because their block was orphaned",
+        )?;
+        registry.register(Box::new(dispatches_rolled_back.clone()))?;
+
+        let calldata_served = IntCounter::new("theoros_calldata_served_total", "Number of calldata served")?;
+        registry.register(Box::new(calldata_served.clone()))?;
+
+        let checkpoint_anomalies = IntCounterVec::new(
+            Opts::new("theoros_checkpoint_anomalies_total", "Number of validator checkpoint anomalies detected"),
+            &["kind"],
+        )?;
+        registry.register(Box::new(checkpoint_anomalies.clone()))?;
+
+        let invalid_checkpoint_signatures = IntCounterVec::new(
+            Opts::new(
+                "theoros_invalid_checkpoint_signatures_total",
+                "Number of fetched checkpoints dropped because of an invalid signature",
+            ),
+            &["validator"],
+        )?;
+        registry.register(Box::new(invalid_checkpoint_signatures.clone()))?;
+
+        let api_requests =
+            IntCounterVec::new(Opts::new("theoros_api_requests_total", "Number of API requests received"), &["lane"])?;
+        registry.register(Box::new(api_requests.clone()))?;
+
+        let api_requests_shed = IntCounterVec::new(
+            Opts::new("theoros_api_requests_shed_total", "Number of API requests shed under load"),
+            &["lane"],
+        )?;
+        registry.register(Box::new(api_requests_shed.clone()))?;
+
+        let api_requests_rate_limited = IntCounterVec::new(
+            Opts::new("theoros_api_requests_rate_limited_total", "Number of API requests rejected by the rate limits"),
+            &["client"],
+        )?;
+        registry.register(Box::new(api_requests_rate_limited.clone()))?;
+
+        let api_requests_in_flight = IntGaugeVec::new(
+            Opts::new("theoros_api_requests_in_flight", "Number of API requests being served"),
+            &["lane"],
+        )?;
+        registry.register(Box::new(api_requests_in_flight.clone()))?;
+
+        let api_queue_wait_seconds = HistogramVec::new(
+            HistogramOpts::new(
+                "theoros_api_queue_wait_seconds",
+                "Time spent by the API requests waiting to be admitted",
+            )
+            .buckets(vec![0.001, 0.005, 0.01, 0.05, 0.1, 0.5, 1.0, 5.0]),
+            &["lane"],
+        )?;
+        registry.register(Box::new(api_queue_wait_seconds.clone()))?;
+
+        let validator_checkpoint_lag = IntGaugeVec::new(
+            Opts::new(
+                "theoros_validator_checkpoint_lag",
+                "Number of checkpoints a validator is behind the latest dispatched message",
+            ),
+            &["validator"],
+        )?;
+        registry.register(Box::new(validator_checkpoint_lag.clone()))?;
+
+        let validator_mailbox_lag = IntGaugeVec::new(
+            Opts::new(
+                "theoros_validator_mailbox_lag",
+                "Latest checkpoint index signed by a validator minus the nonce of the latest dispatched message",
+            ),
+            &["validator"],
+        )?;
+        registry.register(Box::new(validator_mailbox_lag.clone()))?;
+
+        let synthetic_feed_latency_seconds = Histogram::with_opts(
+            HistogramOpts::new(
+                "theoros_synthetic_feed_latency_seconds",
+                "Time taken by a dispatch of the synthetic feed to go through the whole pipeline",
+            )
+            .buckets(vec![0.001, 0.005, 0.01, 0.05, 0.1, 0.5, 1.0, 5.0, 10.0]),
+        )?;
+        registry.register(Box::new(synthetic_feed_latency_seconds.clone()))?;
+
+        let synthetic_feed_failures = IntCounter::new(
+            "theoros_synthetic_feed_failures_total",
+            "Number of dispatches of the synthetic feed that failed to go through the pipeline",
+        )?;
+        registry.register(Box::new(synthetic_feed_failures.clone()))?;
+
+        let synthetic_feed_last_success = IntGauge::new(
+            "theoros_synthetic_feed_last_success_timestamp_seconds",
+            "Unix timestamp of the last dispatch of the synthetic feed that went through the pipeline",
+        )?;
+        registry.register(Box::new(synthetic_feed_last_success.clone()))?;
+
+        let validator_set_changes = IntCounterVec::new(
+            Opts::new(
+                "theoros_validator_set_changes_total",
+                "Number of changes of the validators or threshold of a chain found by the periodic refresh",
+            ),
+            &["chain"],
+        )?;
+        registry.register(Box::new(validator_set_changes.clone()))?;
+
+        let validator_set_refresh_failures = IntCounterVec::new(
+            Opts::new(
+                "theoros_validator_set_refresh_failures_total",
+                "Number of periodic refreshes of the validators of a chain that failed",
+            ),
+            &["chain"],
+        )?;
+        registry.register(Box::new(validator_set_refresh_failures.clone()))?;
+
+        let config_reloads = IntCounterVec::new(
+            Opts::new("theoros_config_reloads_total", "Number of reloads of the watched config files, by outcome"),
+            &["file", "outcome"],
+        )?;
+        registry.register(Box::new(config_reloads.clone()))?;
+
+        let indexer_reconnections = IntCounterVec::new(
+            Opts::new(
+                "theoros_indexer_reconnections_total",
+                "Number of reconnections of the indexer to an Apibara endpoint, by reason the previous stream stopped",
+            ),
+            &["reason"],
+        )?;
+        registry.register(Box::new(indexer_reconnections.clone()))?;
+
+        let events_indexed = IntCounterVec::new(
+            Opts::new("theoros_events_indexed_total", "Number of events indexed from the Pragma chain"),
+            &["event"],
+        )?;
+        registry.register(Box::new(events_indexed.clone()))?;
+
+        let update_parse_failures = IntCounter::new(
+            "theoros_update_parse_failures_total",
+            "Number of updates of the indexed dispatches that could not be parsed",
+        )?;
+        registry.register(Box::new(update_parse_failures.clone()))?;
+
+        let dead_letters = IntCounterVec::new(
+            Opts::new("theoros_dead_letters_total", "Number of indexed events that could not be decoded"),
+            &["event"],
+        )?;
+        registry.register(Box::new(dead_letters.clone()))?;
+
+        let dispatches_skipped = IntCounterVec::new(
+            Opts::new(
+                "theoros_dispatches_skipped_total",
+                "Number of dispatches skipped as sent to a domain not configured",
+            ),
+            &["destination_domain"],
+        )?;
+        registry.register(Box::new(dispatches_skipped.clone()))?;
+
+        let dispatches_unknown_emitter = IntCounter::new(
+            "theoros_dispatches_unknown_emitter_total",
+            "Number of dispatches skipped as sent by a Pragma dispatcher not configured",
+        )?;
+        registry.register(Box::new(dispatches_unknown_emitter.clone()))?;
+
+        let pruned = IntCounterVec::new(
+            Opts::new("theoros_pruned_total", "Number of entries pruned by the retention policy"),
+            &["kind", "storage"],
+        )?;
+        registry.register(Box::new(pruned.clone()))?;
+
+        let checkpoints_fetched = IntCounterVec::new(
+            Opts::new("theoros_checkpoints_fetched_total", "Number of attempts to fetch a checkpoint from a validator"),
+            &["validator", "backend", "outcome"],
+        )?;
+        registry.register(Box::new(checkpoints_fetched.clone()))?;
+
+        let checkpoint_cache_lookups = IntCounterVec::new(
+            Opts::new("theoros_checkpoint_cache_lookups_total", "Number of lookups of the fetched checkpoints cache"),
+            &["outcome"],
+        )?;
+        registry.register(Box::new(checkpoint_cache_lookups.clone()))?;
+
+        let checkpoint_fetch_seconds = HistogramVec::new(
+            HistogramOpts::new("theoros_checkpoint_fetch_seconds", "Time taken to fetch a checkpoint from a validator")
+                .buckets(vec![0.01, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0]),
+            &["backend"],
+        )?;
+        registry.register(Box::new(checkpoint_fetch_seconds.clone()))?;
+
+        let quorum_latency_seconds = Histogram::with_opts(
+            HistogramOpts::new(
+                "theoros_quorum_latency_seconds",
+                "Time between the indexing of a dispatch & the quorum of its signatures",
+            )
+            .buckets(vec![0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0, 300.0]),
+        )?;
+        registry.register(Box::new(quorum_latency_seconds.clone()))?;
+
+        let http_request_duration_seconds = HistogramVec::new(
+            HistogramOpts::new("theoros_http_request_duration_seconds", "Time taken to serve the API requests")
+                .buckets(vec![0.001, 0.005, 0.01, 0.05, 0.1, 0.5, 1.0, 5.0, 10.0]),
+            &["method", "route", "status"],
+        )?;
+        registry.register(Box::new(http_request_duration_seconds.clone()))?;
+
+        let ws_connections = IntGaugeVec::new(
+            Opts::new("theoros_ws_connections", "Number of open WebSocket connections"),
+            &["endpoint"],
+        )?;
+        registry.register(Box::new(ws_connections.clone()))?;
+
+        let relayer_transactions = IntCounterVec::new(
+            Opts::new("theoros_relayer_transactions_total", "Number of transactions of the relayer, by outcome"),
+            &["chain", "outcome"],
+        )?;
+        registry.register(Box::new(relayer_transactions.clone()))?;
+
+        Ok(Self {
+            dispatches_indexed,
+            reorgs,
+            dispatches_rolled_back,
+            calldata_served,
+            checkpoint_anomalies,
+            invalid_checkpoint_signatures,
+            api_requests,
+            api_requests_shed,
+            api_requests_rate_limited,
+            api_requests_in_flight,
+            api_queue_wait_seconds,
+            validator_checkpoint_lag,
+            validator_mailbox_lag,
+            synthetic_feed_latency_seconds,
+            synthetic_feed_failures,
+            synthetic_feed_last_success,
+            validator_set_changes,
+            validator_set_refresh_failures,
+            indexer_reconnections,
+            config_reloads,
+            events_indexed,
+            update_parse_failures,
+            dead_letters,
+            dispatches_skipped,
+            dispatches_unknown_emitter,
+            pruned,
+            checkpoints_fetched,
+            checkpoint_fetch_seconds,
+            checkpoint_cache_lookups,
+            quorum_latency_seconds,
+            http_request_duration_seconds,
+            ws_connections,
+            relayer_transactions,
+        })
+    }
+
+    /// Counts the WebSocket connection to the endpoint as open until the returned guard is dropped.
+    pub fn track_ws_connection(&self, endpoint: &str) -> WsConnectionGuard {
+        let gauge = self.ws_connections.with_label_values(&[endpoint]);
+        gauge.inc();
+        WsConnectionGuard(gauge)
+    }
+
+    /// Writes the counters values into the storage backend, under the name of the process.
+    pub async fn persist(&self, storage: &TheorosStorage, instance: &str) -> Result<()> {
+        storage.save_metrics(instance, &self.encode_counters()?).await
+    }
+
+    /// Adds the counters values last persisted by the process, if any, so they don't reset on restart.
+    pub async fn restore(&self, storage: &TheorosStorage, instance: &str) -> Result<()> {
+        let Some(counters) = storage.load_metrics(instance).await? else {
+            return Ok(());
+        };
+        self.restore_counters(&counters)?;
+        tracing::info!("🧩 Restored the metrics counters of {}", instance);
+        Ok(())
+    }
+
+    fn encode_counters(&self) -> Result<Vec<u8>> {
+        let counters = PersistedCounters {
+            dispatches_indexed: self.dispatches_indexed.get(),
+            calldata_served: self.calldata_served.get(),
+        };
+        encode_versioned(&counters)
+    }
+
+    fn restore_counters(&self, counters: &[u8]) -> Result<()> {
+        let counters: PersistedCounters = decode_versioned(counters)?;
+        self.dispatches_indexed.inc_by(counters.dispatches_indexed);
+        self.calldata_served.inc_by(counters.calldata_served);
+        Ok(())
+    }
+}
+
+/// Open WebSocket connection, see [TheorosMetrics::track_ws_connection].
+pub struct WsConnectionGuard(IntGauge);
+
+impl Drop for WsConnectionGuard {
+    fn drop(&mut self) {
+        self.0.dec();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counters_survive_restart() {
+        let metrics = TheorosMetrics::register(&Registry::new()).unwrap();
+        metrics.dispatches_indexed.inc_by(3);
+        metrics.calldata_served.inc_by(42);
+        let counters = metrics.encode_counters().unwrap();
+
+        let restarted = TheorosMetrics::register(&Registry::new()).unwrap();
+        restarted.restore_counters(&counters).unwrap();
+        assert_eq!(restarted.dispatches_indexed.get(), 3);
+        assert_eq!(restarted.calldata_served.get(), 42);
+    }
+
+    #[test]
+    fn test_unversioned_state_is_migrated() {
+        let metrics = TheorosMetrics::register(&Registry::new()).unwrap();
+        metrics.restore_counters(br#"{"dispatches_indexed": 1, "calldata_served": 2}"#).unwrap();
+        assert_eq!(metrics.calldata_served.get(), 2);
+
+        let newer = br#"{"dispatches_indexed": 1, "calldata_served": 2, "schema_version": 99}"#;
+        assert!(TheorosMetrics::register(&Registry::new()).unwrap().restore_counters(newer).is_err());
+    }
+
+    #[test]
+    fn test_ws_connections_are_closed_on_drop() {
+        let metrics = TheorosMetrics::register(&Registry::new()).unwrap();
+        let first = metrics.track_ws_connection("calldata");
+        let _second = metrics.track_ws_connection("calldata");
+        assert_eq!(metrics.ws_connections.with_label_values(&["calldata"]).get(), 2);
+        drop(first);
+        assert_eq!(metrics.ws_connections.with_label_values(&["calldata"]).get(), 1);
+    }
+}
@@ -1,9 +1,25 @@
 pub mod api;
+pub mod backend_sync;
+pub mod config_watcher;
+#[cfg(feature = "grpc")]
+pub mod grpc;
 pub mod hyperlane;
 pub mod indexer;
 pub mod metrics;
+pub mod relayer;
+pub mod retention;
+pub mod synthetic;
+pub mod validators_refresh;
 
 pub use api::ApiService;
+pub use backend_sync::BackendSyncService;
+pub use config_watcher::ConfigWatcherService;
+#[cfg(feature = "grpc")]
+pub use grpc::GrpcService;
 pub use hyperlane::HyperlaneService;
 pub use indexer::IndexerService;
 pub use metrics::MetricsService;
+pub use relayer::RelayerService;
+pub use retention::RetentionService;
+pub use synthetic::SyntheticFeedService;
+pub use validators_refresh::ValidatorsRefreshService;
@@ -0,0 +1,48 @@
+use alloy::consensus::TxEnvelope;
+use alloy::primitives::{Address, TxHash};
+use alloy::providers::{Provider, RootProvider};
+use alloy::transports::http::{Client, Http};
+use anyhow::Result;
+use async_trait::async_trait;
+
+use crate::types::relayer::{RelayedReceipt, RelayedTransactionStatus};
+
+/// The transactions of the relayer on a chain, as seen by its RPC.
+#[async_trait]
+pub trait RelayerChain: Send + Sync {
+    /// Number of transactions sent from the address, mined only or including the pending ones.
+    async fn transaction_count(&self, address: Address, include_pending: bool) -> Result<u64>;
+
+    async fn send_transaction(&self, envelope: TxEnvelope) -> Result<TxHash>;
+
+    /// Receipt of the transaction, `None` until it is mined.
+    async fn receipt(&self, hash: TxHash) -> Result<Option<RelayedReceipt>>;
+}
+
+#[async_trait]
+impl RelayerChain for RootProvider<Http<Client>> {
+    async fn transaction_count(&self, address: Address, include_pending: bool) -> Result<u64> {
+        let count = self.get_transaction_count(address);
+        Ok(if include_pending { count.pending().await? } else { count.latest().await? })
+    }
+
+    async fn send_transaction(&self, envelope: TxEnvelope) -> Result<TxHash> {
+        Ok(*self.send_tx_envelope(envelope).await?.tx_hash())
+    }
+
+    async fn receipt(&self, hash: TxHash) -> Result<Option<RelayedReceipt>> {
+        let Some(receipt) = self.get_transaction_receipt(hash).await? else {
+            return Ok(None);
+        };
+        Ok(Some(RelayedReceipt {
+            status: if receipt.status() {
+                RelayedTransactionStatus::Confirmed
+            } else {
+                RelayedTransactionStatus::Reverted
+            },
+            block_number: receipt.block_number,
+            gas_used: u64::try_from(receipt.gas_used).ok(),
+            effective_gas_price: u64::try_from(receipt.effective_gas_price).ok(),
+        }))
+    }
+}
@@ -0,0 +1,244 @@
+use alloy::eips::BlockNumberOrTag;
+use alloy::network::TransactionBuilder;
+use alloy::providers::{Provider, RootProvider};
+use alloy::rpc::types::TransactionRequest;
+use alloy::transports::http::{Client, Http};
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+
+use crate::{
+    configs::evm_config::GasPriceConfig,
+    constants::{RELAYER_FEE_HISTORY_BLOCKS, RELAYER_REPLACEMENT_FEE_BUMP_PERCENT},
+};
+
+/// Fees paid per gas by a transaction, in wei.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GasFees {
+    Eip1559 { base_fee_per_gas: u128, max_fee_per_gas: u128, max_priority_fee_per_gas: u128 },
+    Legacy { gas_price: u128 },
+}
+
+impl GasFees {
+    /// Price expected to be paid per gas if the transaction is mined in the next block.
+    pub fn expected_price(&self) -> u128 {
+        match self {
+            GasFees::Eip1559 { base_fee_per_gas, max_priority_fee_per_gas, .. } => {
+                base_fee_per_gas + max_priority_fee_per_gas
+            }
+            GasFees::Legacy { gas_price } => *gas_price,
+        }
+    }
+
+    /// Lowers the fees to the cap. Fails if the expected price is above it: the transaction would be stuck,
+    /// blocking the next ones.
+    pub fn capped(self, max_fee_per_gas: Option<u128>) -> Result<Self> {
+        let Some(cap) = max_fee_per_gas else {
+            return Ok(self);
+        };
+        anyhow::ensure!(
+            self.expected_price() <= cap,
+            "The gas would cost {} wei, above the cap of {} wei",
+            self.expected_price(),
+            cap
+        );
+        Ok(match self {
+            GasFees::Eip1559 { base_fee_per_gas, max_fee_per_gas, max_priority_fee_per_gas } => GasFees::Eip1559 {
+                base_fee_per_gas,
+                max_fee_per_gas: max_fee_per_gas.min(cap),
+                max_priority_fee_per_gas,
+            },
+            GasFees::Legacy { gas_price } => GasFees::Legacy { gas_price },
+        })
+    }
+
+    /// Fees of a transaction replacing one paying these. `None` if they would go above the cap: the transaction
+    /// can't be replaced anymore.
+    pub fn bumped(self, max_fee_per_gas: Option<u128>) -> Option<Self> {
+        let bump = |fee: u128| fee + (fee * RELAYER_REPLACEMENT_FEE_BUMP_PERCENT).div_ceil(100).max(1);
+        let (bumped, highest_fee) = match self {
+            GasFees::Eip1559 { base_fee_per_gas, max_fee_per_gas, max_priority_fee_per_gas } => {
+                let bumped = GasFees::Eip1559 {
+                    base_fee_per_gas,
+                    max_fee_per_gas: bump(max_fee_per_gas),
+                    max_priority_fee_per_gas: bump(max_priority_fee_per_gas),
+                };
+                (bumped, bump(max_fee_per_gas))
+            }
+            GasFees::Legacy { gas_price } => (GasFees::Legacy { gas_price: bump(gas_price) }, bump(gas_price)),
+        };
+        match max_fee_per_gas {
+            Some(cap) if highest_fee > cap => None,
+            _ => Some(bumped),
+        }
+    }
+
+    pub fn apply(self, tx: TransactionRequest) -> TransactionRequest {
+        match self {
+            GasFees::Eip1559 { max_fee_per_gas, max_priority_fee_per_gas, .. } => {
+                tx.with_max_fee_per_gas(max_fee_per_gas).with_max_priority_fee_per_gas(max_priority_fee_per_gas)
+            }
+            GasFees::Legacy { gas_price } => tx.with_gas_price(gas_price),
+        }
+    }
+}
+
+/// Activity of the gas market of a chain, as seen by its RPC.
+#[async_trait]
+pub trait GasMarket: Send + Sync {
+    /// Base fee of the next block, & the priority fee paid at the percentile in each of the latest blocks.
+    async fn fee_history(&self, reward_percentile: f64) -> Result<(u128, Vec<u128>)>;
+
+    async fn gas_price(&self) -> Result<u128>;
+}
+
+#[async_trait]
+impl GasMarket for RootProvider<Http<Client>> {
+    async fn fee_history(&self, reward_percentile: f64) -> Result<(u128, Vec<u128>)> {
+        let history =
+            self.get_fee_history(RELAYER_FEE_HISTORY_BLOCKS, BlockNumberOrTag::Latest, &[reward_percentile]).await?;
+        let base_fee_per_gas = history
+            .next_block_base_fee()
+            .filter(|base_fee| *base_fee != 0)
+            .context("The chain doesn't support EIP-1559, use the legacy gas price strategy")?;
+        let rewards =
+            history.reward.unwrap_or_default().iter().filter_map(|rewards| rewards.first().copied()).collect();
+        Ok((base_fee_per_gas, rewards))
+    }
+
+    async fn gas_price(&self) -> Result<u128> {
+        Ok(self.get_gas_price().await?)
+    }
+}
+
+/// Prices the gas of the transactions sent on a chain.
+#[async_trait]
+pub trait GasPriceStrategy: Send + Sync {
+    async fn gas_fees(&self, market: &dyn GasMarket) -> Result<GasFees>;
+}
+
+/// See [GasPriceConfig::Eip1559].
+pub struct Eip1559GasPrice {
+    pub priority_fee_percentile: f64,
+    pub base_fee_multiplier: u128,
+}
+
+#[async_trait]
+impl GasPriceStrategy for Eip1559GasPrice {
+    async fn gas_fees(&self, market: &dyn GasMarket) -> Result<GasFees> {
+        let (base_fee_per_gas, mut rewards) = market.fee_history(self.priority_fee_percentile).await?;
+        rewards.sort_unstable();
+        // The median of the latest blocks, so a single block with outlier fees doesn't move the price.
+        let max_priority_fee_per_gas = rewards.get(rewards.len() / 2).copied().unwrap_or_default().max(1);
+        Ok(GasFees::Eip1559 {
+            base_fee_per_gas,
+            max_fee_per_gas: base_fee_per_gas * self.base_fee_multiplier + max_priority_fee_per_gas,
+            max_priority_fee_per_gas,
+        })
+    }
+}
+
+/// See [GasPriceConfig::Legacy].
+pub struct LegacyGasPrice;
+
+#[async_trait]
+impl GasPriceStrategy for LegacyGasPrice {
+    async fn gas_fees(&self, market: &dyn GasMarket) -> Result<GasFees> {
+        Ok(GasFees::Legacy { gas_price: market.gas_price().await? })
+    }
+}
+
+/// See [GasPriceConfig::Fixed].
+pub struct FixedGasPrice(pub u128);
+
+#[async_trait]
+impl GasPriceStrategy for FixedGasPrice {
+    async fn gas_fees(&self, _market: &dyn GasMarket) -> Result<GasFees> {
+        Ok(GasFees::Legacy { gas_price: self.0 })
+    }
+}
+
+/// The strategy selected for a chain by its relayer config.
+pub fn gas_price_strategy(config: &GasPriceConfig) -> Box<dyn GasPriceStrategy> {
+    match config {
+        GasPriceConfig::Eip1559 { priority_fee_percentile, base_fee_multiplier } => Box::new(Eip1559GasPrice {
+            priority_fee_percentile: *priority_fee_percentile,
+            base_fee_multiplier: u128::from(*base_fee_multiplier),
+        }),
+        GasPriceConfig::Legacy => Box::new(LegacyGasPrice),
+        GasPriceConfig::Fixed { gas_price } => Box::new(FixedGasPrice(u128::from(*gas_price))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GWEI: u128 = 1_000_000_000;
+
+    struct StaticMarket;
+
+    #[async_trait]
+    impl GasMarket for StaticMarket {
+        async fn fee_history(&self, _reward_percentile: f64) -> Result<(u128, Vec<u128>)> {
+            Ok((10 * GWEI, vec![3 * GWEI, GWEI, 50 * GWEI, 2 * GWEI, 2 * GWEI]))
+        }
+
+        async fn gas_price(&self) -> Result<u128> {
+            Ok(12 * GWEI)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_gas_price_strategies() {
+        let eip1559 = gas_price_strategy(&GasPriceConfig::default()).gas_fees(&StaticMarket).await.unwrap();
+        let expected = GasFees::Eip1559 {
+            base_fee_per_gas: 10 * GWEI,
+            max_fee_per_gas: 22 * GWEI,
+            max_priority_fee_per_gas: 2 * GWEI,
+        };
+        assert_eq!(eip1559, expected);
+
+        let legacy = gas_price_strategy(&GasPriceConfig::Legacy).gas_fees(&StaticMarket).await.unwrap();
+        assert_eq!(legacy, GasFees::Legacy { gas_price: 12 * GWEI });
+
+        let fixed = GasPriceConfig::Fixed { gas_price: 5_000_000_000 };
+        let fixed = gas_price_strategy(&fixed).gas_fees(&StaticMarket).await.unwrap();
+        assert_eq!(fixed, GasFees::Legacy { gas_price: 5 * GWEI });
+    }
+
+    #[test]
+    fn test_gas_fees_are_capped() {
+        let fees = GasFees::Eip1559 {
+            base_fee_per_gas: 10 * GWEI,
+            max_fee_per_gas: 22 * GWEI,
+            max_priority_fee_per_gas: GWEI,
+        };
+
+        let capped = fees.capped(Some(15 * GWEI)).unwrap();
+        assert!(matches!(capped, GasFees::Eip1559 { max_fee_per_gas, .. } if max_fee_per_gas == 15 * GWEI));
+        assert_eq!(fees.capped(None).unwrap(), fees);
+        // The transaction wouldn't be mined under the cap.
+        assert!(fees.capped(Some(10 * GWEI)).is_err());
+        assert!(GasFees::Legacy { gas_price: 12 * GWEI }.capped(Some(10 * GWEI)).is_err());
+    }
+
+    #[test]
+    fn test_gas_fees_are_bumped_under_the_cap() {
+        let fees = GasFees::Eip1559 {
+            base_fee_per_gas: 10 * GWEI,
+            max_fee_per_gas: 20 * GWEI,
+            max_priority_fee_per_gas: GWEI,
+        };
+        let expected = GasFees::Eip1559 {
+            base_fee_per_gas: 10 * GWEI,
+            max_fee_per_gas: 22_400_000_000,
+            max_priority_fee_per_gas: 1_120_000_000,
+        };
+        assert_eq!(fees.bumped(None), Some(expected));
+        assert_eq!(fees.bumped(Some(23 * GWEI)), Some(expected));
+        assert_eq!(fees.bumped(Some(22 * GWEI)), None);
+
+        // Even the lowest fees are raised.
+        assert_eq!(GasFees::Legacy { gas_price: 1 }.bumped(None), Some(GasFees::Legacy { gas_price: 2 }));
+    }
+}
@@ -0,0 +1,575 @@
+pub mod chain;
+pub mod gas_price;
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use alloy::network::{EthereumWallet, TransactionBuilder};
+use alloy::primitives::{Address, Bytes, TxHash};
+use alloy::providers::{Provider, ProviderBuilder, RootProvider};
+use alloy::rpc::types::TransactionRequest;
+use alloy::transports::http::{Client, Http};
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use tokio::task::JoinSet;
+
+use pragma_utils::services::Service;
+
+use crate::{
+    configs::evm_config::{EvmChainName, EvmConfig},
+    constants::{RELAYER_GAS_LIMIT_MARGIN_PERCENT, RELAYER_RECEIPT_POLL_INTERVAL},
+    handlers::rest::get_data_feeds::unix_now,
+    rpc::evm::{
+        calldata::encode_update_data_feeds,
+        pragma::{IPragma, PragmaContract},
+    },
+    services::relayer::{
+        chain::RelayerChain,
+        gas_price::{gas_price_strategy, GasFees, GasPriceStrategy},
+    },
+    types::{
+        calldata::Calldata,
+        hyperlane::NewUpdatesAvailableEvent,
+        relayer::{RelayedReceipt, RelayedTransaction, RelayedTransactionStatus},
+    },
+    AppState,
+};
+
+/// Sends the updates of the subscribed feeds to the Pragma contract of each chain configuring a relayer, as soon
+/// as they reach quorum, from the funded account of the chain. The status of the transactions is exposed on
+/// `/v1/relayer/status`.
+///
+/// The relayers are built from the EVM config on startup: changing them requires a restart.
+pub struct RelayerService {
+    relayers: Vec<ChainRelayer>,
+}
+
+#[async_trait]
+impl Service for RelayerService {
+    async fn start(&mut self, join_set: &mut JoinSet<anyhow::Result<()>>) -> anyhow::Result<()> {
+        for relayer in self.relayers.drain(..) {
+            join_set.spawn(async move {
+                tracing::info!("🧩 Relayer of {} started, sending from {}", relayer.chain_name, relayer.address);
+                relayer.run_forever().await;
+                Ok(())
+            });
+        }
+        Ok(())
+    }
+}
+
+impl RelayerService {
+    pub fn new(state: &AppState, config: &EvmConfig, receipt_timeout: Duration) -> Result<Self> {
+        let mut relayers = Vec::new();
+        for (chain_name, chain_config) in config.chains() {
+            let Some(relayer_config) = &chain_config.relayer else {
+                continue;
+            };
+            let signer = relayer_config.signer().map_err(|e| anyhow::anyhow!("Relayer of {chain_name}: {e}"))?;
+            let contract = state
+                .pragma_contracts
+                .get(chain_name)
+                .with_context(|| format!("Relayer of {chain_name}: no Pragma contract configured"))?;
+            let address = signer.address();
+            state.relayer_statuses.register(*chain_name, address.to_string(), relayer_config.feeds.clone());
+            let provider = ProviderBuilder::new().on_http(contract.rpc_url.clone());
+            let (timed_out_sender, timed_out_receiver) = mpsc::unbounded_channel();
+            relayers.push(ChainRelayer {
+                state: state.clone(),
+                chain_name: *chain_name,
+                feed_ids: relayer_config.feeds.clone(),
+                chain: Arc::new(provider.clone()),
+                provider,
+                contract,
+                wallet: EthereumWallet::from(signer),
+                address,
+                gas_price: gas_price_strategy(&relayer_config.gas_price),
+                max_fee_per_gas: relayer_config.max_fee_per_gas.map(u128::from),
+                next_nonce: None,
+                stuck_nonce: None,
+                receipt_timeout,
+                timed_out_sender,
+                timed_out_receiver,
+            });
+        }
+        if relayers.is_empty() {
+            tracing::warn!("🚚 [Relayer] Enabled, but no chain configures a relayer");
+        }
+        Ok(Self { relayers })
+    }
+}
+
+/// Sends the updates of the feeds on a chain, one transaction at a time so their nonces follow each other.
+struct ChainRelayer {
+    state: AppState,
+    chain_name: EvmChainName,
+    feed_ids: Vec<String>,
+    contract: PragmaContract,
+    /// Calls the Pragma contract & estimates the gas of the updates.
+    provider: RootProvider<Http<Client>>,
+    /// Sends the transactions & tracks them.
+    chain: Arc<dyn RelayerChain>,
+    wallet: EthereumWallet,
+    address: Address,
+    gas_price: Box<dyn GasPriceStrategy>,
+    /// Highest fee paid per gas, in wei.
+    max_fee_per_gas: Option<u128>,
+    /// Nonce of the next transaction. Fetched from the chain on the first one & after a failed send, which may
+    /// or may not have consumed it.
+    next_nonce: Option<u64>,
+    /// Nonce of the transaction stuck with fees which can't be bumped under the cap. The next updates are held
+    /// back until it is mined or replaced.
+    stuck_nonce: Option<u64>,
+    /// Time waited for the receipt of a transaction before replacing it.
+    receipt_timeout: Duration,
+    /// The transactions without receipt before the timeout, handed back by their tracking to be replaced.
+    timed_out_sender: UnboundedSender<SentTransaction>,
+    timed_out_receiver: UnboundedReceiver<SentTransaction>,
+}
+
+/// A transaction sent by the relayer, as signed.
+#[derive(Debug, Clone)]
+struct SentTransaction {
+    hash: TxHash,
+    /// The request signed, with its nonce & fees.
+    tx: TransactionRequest,
+    fees: GasFees,
+    dispatch_nonce: u32,
+    feed_ids: Vec<String>,
+}
+
+/// What wakes a relayer up.
+enum Wakeup {
+    Update(Result<NewUpdatesAvailableEvent, RecvError>),
+    TimedOut(SentTransaction),
+}
+
+impl ChainRelayer {
+    async fn run_forever(mut self) {
+        let mut updates_receiver = self.state.storage.feeds_updated_tx().subscribe();
+        loop {
+            let wakeup = tokio::select! {
+                update = updates_receiver.recv() => Wakeup::Update(update),
+                Some(timed_out) = self.timed_out_receiver.recv() => Wakeup::TimedOut(timed_out),
+                _ = self.state.shutdown.cancelled() => return,
+            };
+            let update = match wakeup {
+                Wakeup::Update(update) => update,
+                Wakeup::TimedOut(timed_out) => {
+                    let hash = timed_out.hash;
+                    if let Err(e) = self.replace(timed_out).await {
+                        tracing::error!("🚚 [Relayer] Failed to replace {} on {}: {:?}", hash, self.chain_name, e);
+                        count(&self.state, self.chain_name, "failed");
+                        self.state.relayer_statuses.record_failure(self.chain_name, format!("{e:#}"));
+                    }
+                    continue;
+                }
+            };
+            let (nonce, updated_feed_ids) = match update {
+                Ok(NewUpdatesAvailableEvent::New { nonce, feed_ids }) => (nonce, feed_ids),
+                // The next dispatch updating the skipped feeds sends their latest update anyway.
+                Err(RecvError::Lagged(skipped)) => {
+                    tracing::warn!("🚚 [Relayer] {} lagged, skipped {} dispatches", self.chain_name, skipped);
+                    continue;
+                }
+                Err(RecvError::Closed) => return,
+            };
+            let feed_ids: Vec<String> =
+                self.feed_ids.iter().filter(|feed_id| updated_feed_ids.contains(feed_id)).cloned().collect();
+            if feed_ids.is_empty() || !self.state.chain_statuses.is_served(&self.chain_name) {
+                continue;
+            }
+            // The next dispatch updating the feeds once the nonce is unstuck sends their latest update anyway.
+            if let Some(stuck_nonce) = self.stuck_nonce {
+                tracing::debug!(
+                    "🚚 [Relayer] Holding back the update {} to {}, stuck at the nonce {}",
+                    nonce,
+                    self.chain_name,
+                    stuck_nonce
+                );
+                continue;
+            }
+            if let Err(e) = self.relay(nonce, feed_ids).await {
+                tracing::error!("🚚 [Relayer] Failed to send the update {} to {}: {:?}", nonce, self.chain_name, e);
+                count(&self.state, self.chain_name, "failed");
+                self.state.relayer_statuses.record_failure(self.chain_name, format!("{e:#}"));
+            }
+        }
+    }
+
+    /// Sends the updates of the feeds in the dispatch, paying the update fee, & tracks the receipt of the
+    /// transaction in the background.
+    async fn relay(&mut self, dispatch_nonce: u32, feed_ids: Vec<String>) -> Result<()> {
+        let deadline = Instant::now() + self.state.calldata_deadline;
+        let mut update_data = Vec::with_capacity(feed_ids.len());
+        for feed_id in &feed_ids {
+            let calldata = Calldata::build_from(&self.state, self.chain_name, feed_id.clone(), deadline).await?;
+            update_data.push(Bytes::from(calldata.encode_for_chain(&self.state, &self.chain_name)));
+        }
+
+        let pragma = IPragma::new(self.contract.address, &self.provider);
+        let fee = pragma.getUpdateFee(update_data.clone()).call().await.context("Failed to get the update fee")?;
+
+        let tx = TransactionRequest::default()
+            .with_from(self.address)
+            .with_to(self.contract.address)
+            .with_input(encode_update_data_feeds(update_data))
+            .with_value(fee.feeAmount)
+            .with_chain_id(self.chain_name.chain_id());
+        let gas_estimate = self.provider.estimate_gas(&tx).await.context("Failed to estimate the gas")?;
+        let fees = self.gas_price.gas_fees(&self.provider).await.context("Failed to price the gas")?;
+        let fees = fees.capped(self.max_fee_per_gas)?;
+        let tx = tx.with_gas_limit(gas_estimate * RELAYER_GAS_LIMIT_MARGIN_PERCENT / 100);
+        self.send(tx, fees, dispatch_nonce, feed_ids).await
+    }
+
+    /// Replaces a transaction without receipt before the timeout, which holds back the next ones: sends it again
+    /// with bumped fees. Once the cap leaves no room to bump them, the transaction keeps its nonce & the chain is
+    /// marked as stuck: it is checked again after the timeout, until mined, or sent again at the current price
+    /// once dropped from the mempool & the price is back under the cap.
+    async fn replace(&mut self, timed_out: SentTransaction) -> Result<()> {
+        let nonce = timed_out.tx.nonce.context("The timed out transaction has no nonce")?;
+        let mined = self.chain.transaction_count(self.address, false).await.context("Failed to get the nonce")?;
+        // Mined after all, or replaced by a transaction which was.
+        if mined > nonce {
+            self.set_stuck_nonce(None);
+            return Ok(());
+        }
+        if let Some(fees) = timed_out.fees.bumped(self.max_fee_per_gas) {
+            tracing::info!(
+                "🚚 [Relayer] Replacing {} on {}, stuck at the nonce {}",
+                timed_out.hash,
+                self.chain_name,
+                nonce
+            );
+            self.set_stuck_nonce(None);
+            return self.send(timed_out.tx, fees, timed_out.dispatch_nonce, timed_out.feed_ids).await;
+        }
+
+        // Still in the mempool, any transaction with the nonce would be rejected as underpriced.
+        let pending = self.chain.transaction_count(self.address, true).await.context("Failed to get the nonce")?;
+        if pending <= nonce {
+            let fees = self.gas_price.gas_fees(&self.provider).await.context("Failed to price the gas")?;
+            if let Ok(fees) = fees.capped(self.max_fee_per_gas) {
+                tracing::info!(
+                    "🚚 [Relayer] {} was dropped from the mempool of {}, sending it again at the nonce {}",
+                    timed_out.hash,
+                    self.chain_name,
+                    nonce
+                );
+                self.set_stuck_nonce(None);
+                return self.send(timed_out.tx, fees, timed_out.dispatch_nonce, timed_out.feed_ids).await;
+            }
+        }
+
+        if self.stuck_nonce != Some(nonce) {
+            tracing::warn!(
+                "🚚 [Relayer] The fees of {} on {} can't be bumped under the cap, stuck at the nonce {}",
+                timed_out.hash,
+                self.chain_name,
+                nonce
+            );
+            self.set_stuck_nonce(Some(nonce));
+        }
+        self.retry_later(timed_out);
+        Ok(())
+    }
+
+    fn set_stuck_nonce(&mut self, stuck_nonce: Option<u64>) {
+        self.stuck_nonce = stuck_nonce;
+        self.state.relayer_statuses.set_stuck_nonce(self.chain_name, stuck_nonce);
+    }
+
+    /// Hands the stuck transaction back to be replaced after the receipt timeout.
+    fn retry_later(&self, stuck: SentTransaction) {
+        let (shutdown, timed_out_sender, timeout) =
+            (self.state.shutdown.clone(), self.timed_out_sender.clone(), self.receipt_timeout);
+        tokio::spawn(async move {
+            tokio::select! {
+                _ = tokio::time::sleep(timeout) => {
+                    // Fails only once the relayer stopped.
+                    let _ = timed_out_sender.send(stuck);
+                }
+                _ = shutdown.cancelled() => {}
+            }
+        });
+    }
+
+    /// Signs & sends the transaction with the fees, from the next nonce unless it replaces a sent one, & tracks
+    /// its receipt in the background.
+    async fn send(
+        &mut self,
+        tx: TransactionRequest,
+        fees: GasFees,
+        dispatch_nonce: u32,
+        feed_ids: Vec<String>,
+    ) -> Result<()> {
+        let replacement = tx.nonce.is_some();
+        let nonce = match tx.nonce.or(self.next_nonce) {
+            Some(nonce) => nonce,
+            None => self.chain.transaction_count(self.address, true).await.context("Failed to get the nonce")?,
+        };
+        let tx = fees.apply(tx.with_nonce(nonce));
+        let envelope = tx.clone().build(&self.wallet).await?;
+
+        let hash = match self.chain.send_transaction(envelope).await {
+            Ok(hash) => hash,
+            Err(e) => {
+                self.next_nonce = None;
+                self.state.relayer_statuses.set_next_nonce(self.chain_name, None);
+                return Err(e).context("Failed to send the transaction");
+            }
+        };
+        if !replacement {
+            self.next_nonce = Some(nonce + 1);
+            self.state.relayer_statuses.set_next_nonce(self.chain_name, self.next_nonce);
+        }
+
+        tracing::info!(
+            "🚚 [Relayer] Sent the update {} of {} to {}: {}",
+            dispatch_nonce,
+            feed_ids.join(", "),
+            self.chain_name,
+            hash
+        );
+        count(&self.state, self.chain_name, "sent");
+        self.state.relayer_statuses.record_sent(
+            self.chain_name,
+            RelayedTransaction {
+                hash: hash.to_string(),
+                nonce,
+                dispatch_nonce,
+                feed_ids: feed_ids.clone(),
+                status: RelayedTransactionStatus::Pending,
+                sent_at: unix_now(),
+                block_number: None,
+                gas_used: None,
+                effective_gas_price: None,
+            },
+        );
+        tokio::spawn(track_receipt(
+            self.state.clone(),
+            self.chain_name,
+            self.chain.clone(),
+            SentTransaction { hash, tx, fees, dispatch_nonce, feed_ids },
+            self.receipt_timeout,
+            self.timed_out_sender.clone(),
+        ));
+        Ok(())
+    }
+}
+
+/// Polls the receipt of the transaction until it is mined or the timeout elapses, handing it back to the relayer
+/// to be replaced in that case.
+async fn track_receipt(
+    state: AppState,
+    chain_name: EvmChainName,
+    chain: Arc<dyn RelayerChain>,
+    sent: SentTransaction,
+    timeout: Duration,
+    timed_out_sender: UnboundedSender<SentTransaction>,
+) {
+    let lookups = async {
+        loop {
+            tokio::time::sleep(RELAYER_RECEIPT_POLL_INTERVAL).await;
+            match chain.receipt(sent.hash).await {
+                Ok(Some(receipt)) => return receipt,
+                Ok(None) => {}
+                Err(e) => tracing::debug!("🚚 [Relayer] Failed to get the receipt of {}: {:?}", sent.hash, e),
+            }
+        }
+    };
+    let receipt = tokio::select! {
+        receipt = tokio::time::timeout(timeout, lookups) => receipt,
+        _ = state.shutdown.cancelled() => return,
+    };
+    let receipt = match receipt {
+        Ok(receipt) => receipt,
+        Err(_) => {
+            tracing::warn!("🚚 [Relayer] No receipt for {} on {} after {:?}", sent.hash, chain_name, timeout);
+            RelayedReceipt {
+                status: RelayedTransactionStatus::TimedOut,
+                block_number: None,
+                gas_used: None,
+                effective_gas_price: None,
+            }
+        }
+    };
+    count(&state, chain_name, receipt.status.as_str());
+    state.relayer_statuses.record_receipt(chain_name, &sent.hash.to_string(), receipt);
+    if receipt.status == RelayedTransactionStatus::TimedOut {
+        // Fails only once the relayer stopped.
+        let _ = timed_out_sender.send(sent);
+    }
+}
+
+fn count(state: &AppState, chain_name: EvmChainName, outcome: &str) {
+    state.metrics.relayer_transactions.with_label_values(&[&chain_name.to_string(), outcome]).inc();
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+    use std::sync::Mutex;
+
+    use alloy::consensus::TxEnvelope;
+    use alloy::primitives::B256;
+    use alloy::signers::local::PrivateKeySigner;
+
+    use super::*;
+    use crate::rpc::starknet::StarknetRpc;
+    use crate::services::relayer::gas_price::FixedGasPrice;
+    use crate::storage::{FeedIdsStorage, TheorosStorage, ValidatorsFetchersStorage};
+
+    const GWEI: u128 = 1_000_000_000;
+
+    /// Never mines the transactions sent, recording their nonce & gas price.
+    #[derive(Debug, Default)]
+    struct MockChain {
+        mined: AtomicU64,
+        pending: AtomicU64,
+        rejects: AtomicBool,
+        sent: Mutex<Vec<(u64, u128)>>,
+    }
+
+    #[async_trait]
+    impl RelayerChain for MockChain {
+        async fn transaction_count(&self, _address: Address, include_pending: bool) -> Result<u64> {
+            let count = if include_pending { &self.pending } else { &self.mined };
+            Ok(count.load(Ordering::SeqCst))
+        }
+
+        async fn send_transaction(&self, envelope: TxEnvelope) -> Result<TxHash> {
+            anyhow::ensure!(!self.rejects.load(Ordering::SeqCst), "nonce too low");
+            let TxEnvelope::Legacy(signed) = envelope else {
+                anyhow::bail!("Unexpected transaction type");
+            };
+            self.sent.lock().unwrap().push((signed.tx().nonce, signed.tx().gas_price));
+            Ok(*signed.hash())
+        }
+
+        async fn receipt(&self, _hash: TxHash) -> Result<Option<RelayedReceipt>> {
+            Ok(None)
+        }
+    }
+
+    fn relayer(chain: Arc<MockChain>) -> ChainRelayer {
+        let storage = TheorosStorage::new(FeedIdsStorage::default(), ValidatorsFetchersStorage::default());
+        let starknet_rpc = StarknetRpc::new("http://localhost:9944".parse().unwrap());
+        let state =
+            AppState::builder().with_starknet_rpc(Arc::new(starknet_rpc)).with_storage(storage).build().unwrap();
+        let signer = PrivateKeySigner::from_bytes(&B256::repeat_byte(7)).unwrap();
+        let address = signer.address();
+        state.relayer_statuses.register(EvmChainName::Sepolia, address.to_string(), Vec::new());
+        let contract = PragmaContract { rpc_url: "http://localhost:8545".parse().unwrap(), address: Address::ZERO };
+        let (timed_out_sender, timed_out_receiver) = mpsc::unbounded_channel();
+        ChainRelayer {
+            state,
+            chain_name: EvmChainName::Sepolia,
+            feed_ids: Vec::new(),
+            provider: ProviderBuilder::new().on_http(contract.rpc_url.clone()),
+            contract,
+            chain,
+            wallet: EthereumWallet::from(signer),
+            address,
+            gas_price: Box::new(FixedGasPrice(10 * GWEI)),
+            max_fee_per_gas: None,
+            next_nonce: None,
+            stuck_nonce: None,
+            receipt_timeout: Duration::from_millis(100),
+            timed_out_sender,
+            timed_out_receiver,
+        }
+    }
+
+    fn update_tx(relayer: &ChainRelayer) -> TransactionRequest {
+        TransactionRequest::default()
+            .with_from(relayer.address)
+            .with_to(Address::ZERO)
+            .with_chain_id(EvmChainName::Sepolia.chain_id())
+            .with_gas_limit(100_000)
+    }
+
+    async fn next_timed_out(relayer: &mut ChainRelayer) -> SentTransaction {
+        tokio::time::timeout(Duration::from_secs(5), relayer.timed_out_receiver.recv()).await.unwrap().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_nonce_is_fetched_again_after_a_failed_send() {
+        let chain = Arc::new(MockChain { pending: AtomicU64::new(3), ..Default::default() });
+        let mut relayer = relayer(chain.clone());
+        let fees = GasFees::Legacy { gas_price: 10 * GWEI };
+
+        relayer.send(update_tx(&relayer), fees, 7, Vec::new()).await.unwrap();
+        assert_eq!(relayer.next_nonce, Some(4));
+
+        chain.rejects.store(true, Ordering::SeqCst);
+        assert!(relayer.send(update_tx(&relayer), fees, 8, Vec::new()).await.is_err());
+        assert_eq!(relayer.next_nonce, None);
+        assert_eq!(relayer.state.relayer_statuses.all()[0].next_nonce, None);
+
+        // The rejected transaction may have consumed its nonce or not: the chain tells.
+        chain.rejects.store(false, Ordering::SeqCst);
+        chain.pending.store(5, Ordering::SeqCst);
+        relayer.send(update_tx(&relayer), fees, 9, Vec::new()).await.unwrap();
+        assert_eq!(*chain.sent.lock().unwrap(), vec![(3, 10 * GWEI), (5, 10 * GWEI)]);
+        assert_eq!(relayer.next_nonce, Some(6));
+    }
+
+    #[tokio::test]
+    async fn test_timed_out_transaction_is_replaced() {
+        let chain = Arc::new(MockChain { mined: AtomicU64::new(3), pending: AtomicU64::new(3), ..Default::default() });
+        let mut relayer = relayer(chain.clone());
+        relayer.max_fee_per_gas = Some(12 * GWEI);
+
+        relayer.send(update_tx(&relayer), GasFees::Legacy { gas_price: 10 * GWEI }, 7, Vec::new()).await.unwrap();
+        let timed_out = next_timed_out(&mut relayer).await;
+        relayer.replace(timed_out).await.unwrap();
+        // The same nonce is sent again, paying more.
+        assert_eq!(*chain.sent.lock().unwrap(), vec![(3, 10 * GWEI), (3, 11_200_000_000)]);
+        assert_eq!(relayer.next_nonce, Some(4));
+        let status = &relayer.state.relayer_statuses.all()[0];
+        assert_eq!((status.sent, status.timed_out), (2, 1));
+        assert_eq!(status.transactions[1].status, RelayedTransactionStatus::TimedOut);
+
+        // The cap leaves no room for another bump: the transaction keeps its nonce, checked again later.
+        chain.pending.store(4, Ordering::SeqCst);
+        let timed_out = next_timed_out(&mut relayer).await;
+        relayer.replace(timed_out).await.unwrap();
+        assert_eq!(chain.sent.lock().unwrap().len(), 2);
+        assert_eq!((relayer.next_nonce, relayer.stuck_nonce), (Some(4), Some(3)));
+        assert_eq!(relayer.state.relayer_statuses.all()[0].stuck_nonce, Some(3));
+
+        // Nothing to replace once the nonce is mined.
+        chain.mined.store(4, Ordering::SeqCst);
+        let stuck = next_timed_out(&mut relayer).await;
+        assert_eq!(stuck.tx.nonce, Some(3));
+        relayer.replace(stuck).await.unwrap();
+        assert_eq!(chain.sent.lock().unwrap().len(), 2);
+        assert_eq!((relayer.next_nonce, relayer.stuck_nonce), (Some(4), None));
+        assert_eq!(relayer.state.relayer_statuses.all()[0].stuck_nonce, None);
+    }
+
+    #[tokio::test]
+    async fn test_stuck_transaction_dropped_from_the_mempool_is_sent_again() {
+        let chain = Arc::new(MockChain { mined: AtomicU64::new(3), pending: AtomicU64::new(3), ..Default::default() });
+        let mut relayer = relayer(chain.clone());
+        relayer.max_fee_per_gas = Some(12 * GWEI);
+
+        relayer.send(update_tx(&relayer), GasFees::Legacy { gas_price: 12 * GWEI }, 7, Vec::new()).await.unwrap();
+        // Still in the mempool: the fees at the cap can't be bumped.
+        chain.pending.store(4, Ordering::SeqCst);
+        let timed_out = next_timed_out(&mut relayer).await;
+        relayer.replace(timed_out).await.unwrap();
+        assert_eq!(relayer.stuck_nonce, Some(3));
+
+        // Dropped: sent again at the current price, under the cap.
+        chain.pending.store(3, Ordering::SeqCst);
+        let stuck = next_timed_out(&mut relayer).await;
+        relayer.replace(stuck).await.unwrap();
+        assert_eq!(*chain.sent.lock().unwrap(), vec![(3, 12 * GWEI), (3, 10 * GWEI)]);
+        assert_eq!((relayer.next_nonce, relayer.stuck_nonce), (Some(4), None));
+    }
+}
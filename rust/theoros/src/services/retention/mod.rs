@@ -0,0 +1,90 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use async_trait::async_trait;
+use tokio::task::JoinSet;
+
+use pragma_utils::services::Service;
+
+use crate::{
+    types::retention::{PrunedCounts, RetentionPolicy},
+    AppState,
+};
+
+/// Compacts the storage at every interval, pruning the updates, dispatches & signed checkpoints the retention
+/// policy doesn't keep anymore from the in-memory storages & the storage backend.
+#[derive(Clone)]
+pub struct RetentionService {
+    state: AppState,
+    policy: RetentionPolicy,
+    interval: Duration,
+    /// Whether the storage backend is pruned along with the in-memory storages.
+    prunes_backend: bool,
+}
+
+#[async_trait]
+impl Service for RetentionService {
+    async fn start(&mut self, join_set: &mut JoinSet<anyhow::Result<()>>) -> anyhow::Result<()> {
+        let service = self.clone();
+        join_set.spawn(async move {
+            tracing::info!("🧩 Retention service started, compacting every {:?}", service.interval);
+            service.run_forever().await;
+            Ok(())
+        });
+        Ok(())
+    }
+}
+
+impl RetentionService {
+    pub fn new(state: AppState, policy: RetentionPolicy, interval: Duration) -> Self {
+        Self { state, policy, interval, prunes_backend: true }
+    }
+
+    /// Prunes the storage backend too. Enabled by default.
+    pub fn with_backend_pruning(mut self, prunes_backend: bool) -> Self {
+        self.prunes_backend = prunes_backend;
+        self
+    }
+
+    async fn run_forever(&self) {
+        let mut interval = tokio::time::interval(self.interval);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            tokio::select! {
+                _ = interval.tick() => self.compact().await,
+                _ = self.state.shutdown.cancelled() => return,
+            }
+        }
+    }
+
+    async fn compact(&self) {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).map(|elapsed| elapsed.as_secs()).unwrap_or_default();
+        if !self.prunes_backend {
+            let (memory, _) = self.state.storage.prune_memory(&self.policy, now).await;
+            self.record("memory", &memory);
+            tracing::info!("🧹 [Retention] Pruned {:?} from memory", memory);
+            return;
+        }
+        let (memory, backend) = self.state.storage.prune(&self.policy, now).await;
+        self.record("memory", &memory);
+        match backend {
+            Ok(backend) => {
+                self.record("backend", &backend);
+                tracing::info!(
+                    "🧹 [Retention] Pruned {:?} from memory & {:?} from the storage backend",
+                    memory,
+                    backend
+                );
+            }
+            Err(e) => {
+                tracing::info!("🧹 [Retention] Pruned {:?} from memory", memory);
+                tracing::error!("🧹 [Retention] Failed to prune the storage backend: {:?}", e);
+            }
+        }
+    }
+
+    fn record(&self, storage: &str, pruned: &PrunedCounts) {
+        for (kind, count) in pruned.by_kind() {
+            self.state.metrics.pruned.with_label_values(&[kind, storage]).inc_by(count);
+        }
+    }
+}
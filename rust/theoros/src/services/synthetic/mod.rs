@@ -0,0 +1,288 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use alloy::primitives::{keccak256, U256};
+use alloy::signers::{local::PrivateKeySigner, SignerSync};
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use dashmap::DashMap;
+use pragma_feeds::{AssetClass, FeedType};
+use starknet::core::types::Felt;
+use tokio::task::JoinSet;
+
+use pragma_utils::services::Service;
+
+use crate::{
+    configs::evm_config::EvmChainName,
+    constants::{HYPERLANE_VERSION, SYNTHETIC_FEED_INTERVAL},
+    rpc::evm::{HyperlaneValidatorsMapping, ValidatorSet},
+    services::{metrics::TheorosMetrics, HyperlaneService},
+    storage::{FeedIdsStorage, RawDispatchEvent, TheorosStorage, ValidatorsFetchersStorage},
+    types::{
+        calldata::{AsCalldata, Calldata},
+        hyperlane::{
+            Checkpoint, CheckpointWithMessageId, DispatchEvent, DispatchMessage, FetchFromStorage,
+            FromStarknetEventData, SignedCheckpointWithMessageId,
+        },
+    },
+    AppState,
+};
+
+/// Pair of the synthetic feed, which is never registered on Pragma chain.
+const SYNTHETIC_PAIR_ID: &[u8; 12] = b"HEARTBEAT\0\0\0";
+/// Chain the calldata of the synthetic feed is built for. The synthetic pipeline has its own state, so any works.
+const SYNTHETIC_CHAIN: EvmChainName = EvmChainName::Sepolia;
+
+/// Injects a synthetic "heartbeat" dispatch through the whole pipeline at every [SYNTHETIC_FEED_INTERVAL]: the
+/// dispatch is decoded like an indexed one, signed by a test validator, collected by the Hyperlane service &
+/// assembled into calldata. The end-to-end latency is the black-box health metric of Theoros.
+///
+/// The pipeline runs on a state of its own, so the synthetic feed is never served nor mixed with the real ones.
+#[derive(Clone)]
+pub struct SyntheticFeedService {
+    state: AppState,
+    /// Metrics of Theoros, where the latency is reported.
+    metrics: Arc<TheorosMetrics>,
+    validator: PrivateKeySigner,
+    checkpoints: Arc<SyntheticCheckpoints>,
+    nonce: u32,
+}
+
+#[async_trait]
+impl Service for SyntheticFeedService {
+    async fn start(&mut self, join_set: &mut JoinSet<anyhow::Result<()>>) -> anyhow::Result<()> {
+        let mut service = self.clone();
+        join_set.spawn(async move {
+            tracing::info!("🧩 Synthetic feed service started");
+            service.run_forever().await;
+            Ok(())
+        });
+        Ok(())
+    }
+}
+
+impl SyntheticFeedService {
+    /// Builds the state of the synthetic pipeline, trusting a test validator generated on startup.
+    pub fn new(main_state: &AppState) -> Result<Self> {
+        let validator = PrivateKeySigner::random();
+        let validator_felt = Felt::from_bytes_be_slice(validator.address().as_slice());
+        let checkpoints = Arc::new(SyntheticCheckpoints::default());
+
+        let validators_fetchers = ValidatorsFetchersStorage::default();
+        validators_fetchers.add(validator_felt, checkpoints.clone());
+        let storage =
+            TheorosStorage::new(FeedIdsStorage::from_rpc_response(vec![synthetic_feed_id()]), validators_fetchers);
+
+        let validators = HyperlaneValidatorsMapping::default();
+        validators.insert(SYNTHETIC_CHAIN, ValidatorSet::new(HashMap::from([(validator_felt, 0)])));
+
+        let state = AppState::builder()
+            .with_starknet_rpc(main_state.starknet_rpc.clone())
+            .with_storage(storage)
+            .with_hyperlane_validators_mapping(validators)
+            .with_calldata_deadline(main_state.calldata_deadline)
+            .with_shutdown(main_state.shutdown.clone())
+            .build()?;
+        Ok(Self { state, metrics: main_state.metrics.clone(), validator, checkpoints, nonce: 0 })
+    }
+
+    async fn run_forever(&mut self) {
+        let mut interval = tokio::time::interval(SYNTHETIC_FEED_INTERVAL);
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {}
+                _ = self.state.shutdown.cancelled() => return,
+            }
+            let started_at = Instant::now();
+            match self.run_once().await {
+                Ok(()) => {
+                    let latency = started_at.elapsed();
+                    self.metrics.synthetic_feed_latency_seconds.observe(latency.as_secs_f64());
+                    self.metrics.synthetic_feed_last_success.set(unix_timestamp() as i64);
+                    tracing::debug!(
+                        "💓 [Synthetic] Dispatch #{} went through the pipeline in {:?}",
+                        self.nonce,
+                        latency
+                    );
+                }
+                Err(e) => {
+                    self.metrics.synthetic_feed_failures.inc();
+                    tracing::error!(
+                        "💓 [Synthetic] Dispatch #{} failed to go through the pipeline: {:?}",
+                        self.nonce,
+                        e
+                    );
+                }
+            }
+        }
+    }
+
+    /// Dispatches a new update of the synthetic feed & waits until its calldata is built.
+    async fn run_once(&mut self) -> Result<()> {
+        // Only the latest dispatch is kept, whether it went through or not.
+        let storage = &self.state.storage;
+        storage.unsigned_checkpoints().remove(self.nonce).await;
+        storage.signed_checkpoints().remove_nonce(self.nonce);
+        self.checkpoints.0.clear();
+
+        self.nonce += 1;
+        let nonce = self.nonce;
+        let timestamp = unix_timestamp();
+
+        let event_data = dispatch_event_data(nonce, timestamp);
+        let dispatch = DispatchEvent::from_starknet_event_data(event_data.clone()).context("Decoding the dispatch")?;
+        anyhow::ensure!(dispatch.message.body.parse_failures.is_empty(), "The synthetic update could not be parsed");
+        let raw_event = RawDispatchEvent::new(nonce, None, None, &[], &event_data);
+        storage.add_dispatch(raw_event, &dispatch).await;
+
+        let value = checkpoint(nonce, &dispatch.message);
+        let signature = self.validator.sign_message_sync(value.signing_hash()?.as_slice())?;
+        self.checkpoints.0.insert(nonce, SignedCheckpointWithMessageId { value, signature });
+
+        HyperlaneService::new(storage.clone(), self.state.metrics.clone()).process_validator_checkpoints().await;
+        anyhow::ensure!(
+            storage.unsigned_checkpoints().get(nonce).await.is_none(),
+            "The checkpoint of the test validator was not collected"
+        );
+
+        let deadline = Instant::now() + self.state.calldata_deadline;
+        let calldata = Calldata::build_from(&self.state, SYNTHETIC_CHAIN, synthetic_feed_id(), deadline).await?;
+        anyhow::ensure!(calldata.hyperlane_msg.nonce == nonce, "The calldata is not built from the latest dispatch");
+        anyhow::ensure!(!calldata.as_bytes().is_empty(), "Empty calldata");
+        Ok(())
+    }
+}
+
+/// Checkpoints signed by the test validator, fetched as from the storage of a real validator.
+#[derive(Debug, Default)]
+struct SyntheticCheckpoints(DashMap<u32, SignedCheckpointWithMessageId>);
+
+#[async_trait]
+impl FetchFromStorage for SyntheticCheckpoints {
+    async fn fetch(&self, index: u32) -> Result<Option<SignedCheckpointWithMessageId>> {
+        Ok(self.0.get(&index).map(|checkpoint| checkpoint.clone()))
+    }
+
+    async fn fetch_latest_index(&self) -> Result<Option<u32>> {
+        Ok(self.0.iter().map(|entry| *entry.key()).max())
+    }
+
+    fn announcement_location(&self) -> String {
+        String::from("synthetic://")
+    }
+}
+
+fn unix_timestamp() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|elapsed| elapsed.as_secs()).unwrap_or_default()
+}
+
+/// Asset class, feed type & pair id starting the updates of the synthetic feed.
+fn synthetic_feed_header() -> Vec<u8> {
+    [
+        (AssetClass::Crypto as u16).to_be_bytes().as_slice(),
+        &(FeedType::UniqueSpotMedian as u16).to_be_bytes(),
+        &[0u8; 16],
+        SYNTHETIC_PAIR_ID,
+    ]
+    .concat()
+}
+
+/// Id of the synthetic feed, as decoded from its updates: the low part of the pair id is padded to 16 bytes.
+pub fn synthetic_feed_id() -> String {
+    let header = synthetic_feed_header();
+    let feed_id = [&header[..20], &[0u8; 4], &header[20..]].concat();
+    format!("0x{}", alloy::hex::encode(feed_id))
+}
+
+/// Spot median update of the synthetic feed, whose price is the timestamp of the dispatch.
+fn synthetic_update(timestamp: u64) -> Vec<u8> {
+    [
+        synthetic_feed_header().as_slice(),
+        &timestamp.to_be_bytes(),
+        &1_u16.to_be_bytes(),
+        &[0],
+        &[0u8; 16],
+        &u128::from(timestamp).to_be_bytes(),
+        &[0u8; 32],
+    ]
+    .concat()
+}
+
+/// Data of the Starknet Dispatch event of the synthetic update, as emitted by the Hyperlane mailbox.
+pub(crate) fn dispatch_event_data(nonce: u32, timestamp: u64) -> Vec<Felt> {
+    let body = [[1u8].as_slice(), &synthetic_update(timestamp)].concat();
+    // The body is packed into words of 16 bytes, the last one holding the remaining bytes in its low bytes.
+    let body_felts: Vec<Felt> = body
+        .chunks(16)
+        .map(|chunk| {
+            let mut padded = [0u8; 32];
+            padded[32 - chunk.len()..].copy_from_slice(chunk);
+            Felt::from_bytes_be(&padded)
+        })
+        .collect();
+
+    // Sender, destination & recipient of the dispatch.
+    let mut data = vec![Felt::ZERO; 5];
+    // Header: version, nonce, origin, sender, destination & recipient, then the size of the body in bytes & felts.
+    data.extend([Felt::from(HYPERLANE_VERSION), Felt::from(nonce)]);
+    data.extend([Felt::ZERO; 6]);
+    data.extend([Felt::from(body.len()), Felt::from(body_felts.len())]);
+    data.extend(body_felts);
+    data
+}
+
+/// Checkpoint of the synthetic dispatch, signed by the test validator.
+fn checkpoint(nonce: u32, message: &DispatchMessage) -> CheckpointWithMessageId {
+    let message_id = message.id();
+    CheckpointWithMessageId {
+        checkpoint: Checkpoint {
+            merkle_tree_hook_address: U256::ZERO,
+            mailbox_domain: 0,
+            root: format!("{:#x}", keccak256(message_id)),
+            index: nonce,
+        },
+        message_id: U256::from_be_bytes(message_id.0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pragma_utils::conversions::alloy::hex_str_to_u256;
+
+    use crate::types::hyperlane::DispatchUpdate;
+
+    #[test]
+    fn test_synthetic_dispatch_is_decoded() {
+        let dispatch = DispatchEvent::from_starknet_event_data(dispatch_event_data(7, 1_700_000_000)).unwrap();
+        assert_eq!(dispatch.message.header.nonce, 7);
+        assert_eq!(dispatch.message.body.updates.len(), 1);
+        match &dispatch.message.body.updates[0] {
+            DispatchUpdate::SpotMedian { feed_id, update } => {
+                assert_eq!(feed_id, &synthetic_feed_id());
+                assert_eq!(update.metadata.timestamp, 1_700_000_000);
+                assert_eq!(update.price, starknet::core::types::U256::from(1_700_000_000_u64));
+            }
+            update => panic!("Unexpected update {update:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_synthetic_dispatch_goes_through_the_pipeline() {
+        // The pipeline never calls the Starknet RPC.
+        let rpc = crate::rpc::starknet::StarknetRpc::new(url::Url::parse("http://localhost:1").unwrap());
+        let storage = TheorosStorage::new(FeedIdsStorage::default(), ValidatorsFetchersStorage::default());
+        let main_state = AppState::builder().with_starknet_rpc(Arc::new(rpc)).with_storage(storage).build().unwrap();
+
+        let mut service = SyntheticFeedService::new(&main_state).unwrap();
+        service.run_once().await.unwrap();
+        service.run_once().await.unwrap();
+        assert_eq!(service.state.storage.signed_checkpoints().len(), 1);
+        assert!(main_state
+            .storage
+            .latest_update_per_feed()
+            .get(&hex_str_to_u256(&synthetic_feed_id()).unwrap())
+            .is_none());
+    }
+}
@@ -0,0 +1,87 @@
+use async_trait::async_trait;
+use tokio::task::JoinSet;
+
+use pragma_utils::services::Service;
+
+use crate::{configs::evm_config::EvmChainName, types::config_deployment::ValidatorsChange, AppState};
+
+/// Fetches again the validators & threshold of each enabled chain from its ISM at every interval & swaps in the
+/// ones that changed, so validators rotated on-chain are picked up without a restart. The interval is the one of
+/// the runtime settings, the refresh being paused while it is zero.
+#[derive(Clone)]
+pub struct ValidatorsRefreshService {
+    state: AppState,
+}
+
+#[async_trait]
+impl Service for ValidatorsRefreshService {
+    async fn start(&mut self, join_set: &mut JoinSet<anyhow::Result<()>>) -> anyhow::Result<()> {
+        let service = self.clone();
+        join_set.spawn(async move {
+            tracing::info!("🧩 Validators refresh service started");
+            service.run_forever().await;
+            Ok(())
+        });
+        Ok(())
+    }
+}
+
+impl ValidatorsRefreshService {
+    pub fn new(state: AppState) -> Self {
+        Self { state }
+    }
+
+    async fn run_forever(&self) {
+        let mut settings = self.state.runtime_settings.subscribe();
+        loop {
+            let interval = settings.borrow_and_update().validators_refresh_interval;
+            // The validators were just loaded, on startup or by the previous refresh. A new interval restarts
+            // the wait.
+            let wait = async {
+                if interval.is_zero() {
+                    std::future::pending::<()>().await;
+                }
+                tokio::time::sleep(interval).await;
+            };
+            tokio::select! {
+                _ = wait => {
+                    refresh_validators(&self.state).await;
+                }
+                changed = settings.changed() => {
+                    if changed.is_err() {
+                        return;
+                    }
+                }
+                _ = self.state.shutdown.cancelled() => return,
+            }
+        }
+    }
+}
+
+/// Fetches again the validators & threshold of each enabled chain & swaps in the ones that changed. Returns the
+/// chains whose validators changed or couldn't be fetched. Also run on demand through the admin API.
+pub async fn refresh_validators(state: &AppState) -> Vec<(EvmChainName, anyhow::Result<ValidatorsChange>)> {
+    let changes = state.evm_config.refresh_validators(state).await;
+    for (chain_name, change) in &changes {
+        let chain_label = chain_name.to_string();
+        match change {
+            Ok(change) => {
+                state.metrics.validator_set_changes.with_label_values(&[&chain_label]).inc();
+                tracing::warn!(
+                    "🔄 [Validators] The validator set of {} changed: added [{}], removed [{}], now {} validators \
+                     with a threshold of {}",
+                    chain_name,
+                    change.added.join(", "),
+                    change.removed.join(", "),
+                    change.validators,
+                    change.threshold
+                );
+            }
+            Err(e) => {
+                state.metrics.validator_set_refresh_failures.with_label_values(&[&chain_label]).inc();
+                tracing::error!("🔄 [Validators] Failed to refresh the validators of {}: {:?}", chain_name, e);
+            }
+        }
+    }
+    changes
+}
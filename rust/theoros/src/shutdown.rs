@@ -0,0 +1,47 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use tokio_util::sync::CancellationToken;
+
+/// Cancels the token on the first SIGTERM or SIGINT, so the services stop once done with their current work.
+/// A second signal exits right away.
+#[cfg(unix)]
+pub fn cancel_on_signal(shutdown: CancellationToken) -> Result<()> {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut sigterm = signal(SignalKind::terminate())?;
+    let mut sigint = signal(SignalKind::interrupt())?;
+    tokio::spawn(async move {
+        loop {
+            let signal = tokio::select! {
+                _ = sigterm.recv() => "SIGTERM",
+                _ = sigint.recv() => "SIGINT",
+            };
+            if shutdown.is_cancelled() {
+                tracing::warn!("🛑 {} received again, exiting right away", signal);
+                std::process::exit(1);
+            }
+            tracing::info!("🛑 {} received, shutting down gracefully", signal);
+            shutdown.cancel();
+        }
+    });
+    Ok(())
+}
+
+/// Only Ctrl-C is supported out of unix.
+#[cfg(not(unix))]
+pub fn cancel_on_signal(shutdown: CancellationToken) -> Result<()> {
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            tracing::info!("🛑 Ctrl-C received, shutting down gracefully");
+            shutdown.cancel();
+        }
+    });
+    Ok(())
+}
+
+/// Completes `timeout` after the shutdown started, when the services still running are given up on.
+pub async fn deadline(shutdown: &CancellationToken, timeout: Duration) {
+    shutdown.cancelled().await;
+    tokio::time::sleep(timeout).await;
+}
@@ -0,0 +1,81 @@
+use std::collections::VecDeque;
+
+use tokio::sync::{broadcast::Sender, RwLock};
+
+use crate::{
+    constants::{ANOMALIES_CHANNEL_CAPACITY, MAX_STORED_ANOMALIES},
+    types::hyperlane::CheckpointAnomaly,
+};
+
+/// Contains the most recent checkpoint anomalies & notifies subscribers of new ones.
+#[derive(Debug)]
+pub struct CheckpointAnomaliesStorage {
+    anomalies: RwLock<VecDeque<CheckpointAnomaly>>,
+    anomalies_tx: Sender<CheckpointAnomaly>,
+}
+
+impl Default for CheckpointAnomaliesStorage {
+    fn default() -> Self {
+        Self {
+            anomalies: RwLock::new(VecDeque::new()),
+            anomalies_tx: tokio::sync::broadcast::channel(ANOMALIES_CHANNEL_CAPACITY).0,
+        }
+    }
+}
+
+impl CheckpointAnomaliesStorage {
+    /// Stores the anomaly, unless the same one was already reported, & notifies the subscribers.
+    /// Returns whether the anomaly was new.
+    pub async fn add(&self, anomaly: CheckpointAnomaly) -> bool {
+        let mut anomalies = self.anomalies.write().await;
+        let already_reported = anomalies
+            .iter()
+            .any(|a| a.validator == anomaly.validator && a.index == anomaly.index && a.kind == anomaly.kind);
+        if already_reported {
+            return false;
+        }
+        anomalies.push_back(anomaly.clone());
+        if anomalies.len() > MAX_STORED_ANOMALIES {
+            anomalies.pop_front();
+        }
+        // Fails only when there are no subscribers
+        let _ = self.anomalies_tx.send(anomaly);
+        true
+    }
+
+    /// Returns the stored anomalies, most recent first.
+    pub async fn all(&self) -> Vec<CheckpointAnomaly> {
+        self.anomalies.read().await.iter().rev().cloned().collect()
+    }
+
+    pub fn anomalies_tx(&self) -> &Sender<CheckpointAnomaly> {
+        &self.anomalies_tx
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::hyperlane::{CheckpointAnomalyKind, SignedValue};
+
+    fn divergence(index: u32) -> CheckpointAnomaly {
+        let value = |root: &str| SignedValue { root: root.into(), message_id: "0x1".into() };
+        let kind = CheckpointAnomalyKind::RootDivergence { expected: value("0xa"), signed: value("0xb") };
+        CheckpointAnomaly::new("0x1".into(), index, kind)
+    }
+
+    #[tokio::test]
+    async fn test_anomalies_are_reported_once() {
+        let storage = CheckpointAnomaliesStorage::default();
+        let mut rx = storage.anomalies_tx().subscribe();
+
+        assert!(storage.add(divergence(1)).await);
+        assert!(!storage.add(divergence(1)).await);
+        assert!(storage.add(divergence(2)).await);
+
+        let indexes: Vec<u32> = storage.all().await.iter().map(|a| a.index).collect();
+        assert_eq!(indexes, vec![2, 1]);
+        assert_eq!(rx.recv().await.unwrap().index, 1);
+        assert_eq!(rx.recv().await.unwrap().index, 2);
+    }
+}
@@ -0,0 +1,77 @@
+use alloy::primitives::U256;
+use anyhow::Result;
+use async_trait::async_trait;
+use starknet::core::types::Felt;
+
+use super::{PersistedSettings, PersistedState, SettingKind, Storage};
+use crate::{
+    storage::{DeadLetter, RawDispatchEvent},
+    types::hyperlane::SignedCheckpointWithMessageId,
+};
+
+/// Keeps the state in the in-memory storages only: it is lost on restart.
+#[derive(Debug, Default)]
+pub struct InMemoryStorage;
+
+#[async_trait]
+impl Storage for InMemoryStorage {
+    async fn load(&self) -> Result<PersistedState> {
+        Ok(PersistedState::default())
+    }
+
+    async fn save_dispatch(&self, _event: &RawDispatchEvent) -> Result<()> {
+        Ok(())
+    }
+
+    async fn remove_pending_nonce(&self, _nonce: u32) -> Result<()> {
+        Ok(())
+    }
+
+    async fn remove_dispatch(&self, _nonce: u32) -> Result<()> {
+        Ok(())
+    }
+
+    async fn save_signed_checkpoint(
+        &self,
+        _validator: Felt,
+        _checkpoint: &SignedCheckpointWithMessageId,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    async fn save_latest_update(&self, _feed_id: U256, _nonce: u32) -> Result<()> {
+        Ok(())
+    }
+
+    async fn remove_latest_update(&self, _feed_id: U256) -> Result<()> {
+        Ok(())
+    }
+
+    async fn save_dead_letter(&self, _dead_letter: &DeadLetter) -> Result<()> {
+        Ok(())
+    }
+
+    async fn remove_dead_letter(&self, _id: u64) -> Result<()> {
+        Ok(())
+    }
+
+    async fn save_setting(&self, _kind: SettingKind, _id: &str, _value: &serde_json::Value) -> Result<()> {
+        Ok(())
+    }
+
+    async fn remove_setting(&self, _kind: SettingKind, _id: &str) -> Result<()> {
+        Ok(())
+    }
+
+    async fn load_settings(&self) -> Result<PersistedSettings> {
+        Ok(PersistedSettings::default())
+    }
+
+    async fn save_metrics(&self, _instance: &str, _counters: &[u8]) -> Result<()> {
+        Ok(())
+    }
+
+    async fn load_metrics(&self, _instance: &str) -> Result<Option<Vec<u8>>> {
+        Ok(None)
+    }
+}
@@ -0,0 +1,466 @@
+use std::str::FromStr;
+
+use alloy::primitives::U256;
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use sqlx::{postgres::PgPoolOptions, PgPool, Row};
+use starknet::core::types::Felt;
+
+use super::{PersistedSettings, PersistedState, SettingKind, Storage};
+use crate::{
+    storage::{DeadLetter, RawDispatchEvent},
+    types::{
+        history::{HistoryPoint, HistoryRange},
+        hyperlane::{DispatchUpdateInfos, SignedCheckpointWithMessageId},
+        retention::{PrunedCounts, RetentionPolicy},
+        update_view::UpdateView,
+    },
+};
+
+/// Key of the advisory lock held while migrating, so concurrent instances don't migrate at once.
+const MIGRATIONS_LOCK: i64 = 0x0074_6865_6f72_6f73; // "theoros"
+
+/// Migrations of the schema, applied in order. The version of the schema is the number of migrations applied.
+const MIGRATIONS: &[&[&str]] = &[
+    // v1: dispatches, signed checkpoints & updates, appended as they are stored.
+    &[
+        "CREATE TABLE theoros_dispatches (
+            nonce BIGINT PRIMARY KEY,
+            block_number BIGINT,
+            raw_event JSONB NOT NULL,
+            pending BOOLEAN NOT NULL DEFAULT TRUE,
+            indexed_at TIMESTAMPTZ NOT NULL DEFAULT now()
+        )",
+        "CREATE INDEX theoros_dispatches_pending ON theoros_dispatches (nonce) WHERE pending",
+        "CREATE TABLE theoros_signed_checkpoints (
+            nonce BIGINT NOT NULL,
+            validator TEXT NOT NULL,
+            checkpoint JSONB NOT NULL,
+            stored_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+            PRIMARY KEY (nonce, validator)
+        )",
+        "CREATE TABLE theoros_feed_updates (
+            feed_id TEXT NOT NULL,
+            nonce BIGINT NOT NULL,
+            price NUMERIC(78, 0),
+            decimals SMALLINT,
+            published_at TIMESTAMPTZ,
+            payload JSONB NOT NULL,
+            stored_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+            PRIMARY KEY (feed_id, nonce)
+        )",
+        "CREATE INDEX theoros_feed_updates_published_at ON theoros_feed_updates (feed_id, published_at)",
+        "CREATE TABLE theoros_latest_updates (
+            feed_id TEXT PRIMARY KEY,
+            nonce BIGINT NOT NULL
+        )",
+    ],
+    // v2: events that could not be decoded, until replayed.
+    &["CREATE TABLE theoros_dead_letters (
+            id BIGINT PRIMARY KEY,
+            dead_letter JSONB NOT NULL,
+            stored_at TIMESTAMPTZ NOT NULL DEFAULT now()
+        )"],
+    // v3: settings changed through the admin API, followed by every process sharing the database.
+    &["CREATE TABLE theoros_settings (
+            kind TEXT NOT NULL,
+            id TEXT NOT NULL,
+            value JSONB NOT NULL,
+            updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+            PRIMARY KEY (kind, id)
+        )"],
+    // v4: metrics counters of each process, so they don't reset on restart.
+    &["CREATE TABLE theoros_metrics (
+            instance TEXT PRIMARY KEY,
+            counters JSONB NOT NULL,
+            updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+        )"],
+];
+
+/// Persists the state of Theoros in Postgres & keeps the history of the updates & signed checkpoints,
+/// with the time they were stored at.
+#[derive(Debug)]
+pub struct PostgresStorage {
+    pool: PgPool,
+}
+
+impl PostgresStorage {
+    /// Connects to the database & migrates its schema. Databases migrated by a newer version of Theoros
+    /// are refused.
+    pub async fn connect(url: &str, max_connections: u32) -> Result<Self> {
+        let pool = PgPoolOptions::new()
+            .max_connections(max_connections)
+            .connect(url)
+            .await
+            .context("Connecting to Postgres")?;
+        migrate(&pool).await.context("Migrating the Postgres storage")?;
+        Ok(Self { pool })
+    }
+
+    pub fn pool(&self) -> &PgPool {
+        &self.pool
+    }
+}
+
+async fn migrate(pool: &PgPool) -> Result<()> {
+    let mut tx = pool.begin().await?;
+    sqlx::query("SELECT pg_advisory_xact_lock($1)").bind(MIGRATIONS_LOCK).execute(&mut *tx).await?;
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS theoros_migrations (
+            version INT PRIMARY KEY,
+            applied_at TIMESTAMPTZ NOT NULL DEFAULT now()
+        )",
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    let version: i32 = sqlx::query("SELECT COALESCE(MAX(version), 0) AS version FROM theoros_migrations")
+        .fetch_one(&mut *tx)
+        .await?
+        .try_get("version")?;
+    let version = usize::try_from(version).context("Negative schema version")?;
+    anyhow::ensure!(
+        version <= MIGRATIONS.len(),
+        "The Postgres storage has schema version {}, newer than the supported version {}. \
+         It was migrated by a newer version of Theoros.",
+        version,
+        MIGRATIONS.len()
+    );
+
+    for (index, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+        for statement in migration.iter() {
+            sqlx::query(statement).execute(&mut *tx).await?;
+        }
+        sqlx::query("INSERT INTO theoros_migrations (version) VALUES ($1)")
+            .bind(i32::try_from(index + 1)?)
+            .execute(&mut *tx)
+            .await?;
+        tracing::info!("💾 Migrated the Postgres storage to schema version {}", index + 1);
+    }
+    tx.commit().await?;
+    Ok(())
+}
+
+fn nonce_from_row(row: &sqlx::postgres::PgRow) -> Result<u32> {
+    let nonce: i64 = row.try_get("nonce")?;
+    Ok(u32::try_from(nonce)?)
+}
+
+#[async_trait]
+impl Storage for PostgresStorage {
+    /// Only loads the dispatches still needed: the ones pending quorum & the ones feeds were last updated by.
+    async fn load(&self) -> Result<PersistedState> {
+        let mut state = PersistedState::default();
+
+        let latest_updates =
+            sqlx::query("SELECT feed_id, nonce FROM theoros_latest_updates").fetch_all(&self.pool).await?;
+        for row in latest_updates {
+            let feed_id: String = row.try_get("feed_id")?;
+            state.latest_updates.insert(U256::from_str(&feed_id)?, nonce_from_row(&row)?);
+        }
+
+        let dispatches = sqlx::query(
+            "SELECT nonce, pending, raw_event::TEXT AS raw_event FROM theoros_dispatches
+            WHERE pending OR nonce IN (SELECT nonce FROM theoros_latest_updates)",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        for row in dispatches {
+            let nonce = nonce_from_row(&row)?;
+            if row.try_get("pending")? {
+                state.pending_nonces.insert(nonce);
+            }
+            let raw_event: String = row.try_get("raw_event")?;
+            state.dispatches.insert(nonce, serde_json::from_str(&raw_event)?);
+        }
+
+        let checkpoints = sqlx::query(
+            "SELECT validator, checkpoint::TEXT AS checkpoint FROM theoros_signed_checkpoints
+            WHERE nonce IN (
+                SELECT nonce FROM theoros_dispatches WHERE pending
+                UNION SELECT nonce FROM theoros_latest_updates
+            )",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        for row in checkpoints {
+            let validator: String = row.try_get("validator")?;
+            let checkpoint: String = row.try_get("checkpoint")?;
+            state.signed_checkpoints.push((Felt::from_hex(&validator)?, serde_json::from_str(&checkpoint)?));
+        }
+
+        let dead_letters = sqlx::query("SELECT dead_letter::TEXT AS dead_letter FROM theoros_dead_letters ORDER BY id")
+            .fetch_all(&self.pool)
+            .await?;
+        for row in dead_letters {
+            let dead_letter: String = row.try_get("dead_letter")?;
+            state.dead_letters.push(serde_json::from_str(&dead_letter)?);
+        }
+        Ok(state)
+    }
+
+    async fn save_dispatch(&self, event: &RawDispatchEvent) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO theoros_dispatches (nonce, block_number, raw_event, indexed_at)
+            VALUES ($1, $2, $3::JSONB, to_timestamp($4::BIGINT))
+            ON CONFLICT (nonce) DO UPDATE
+            SET block_number = EXCLUDED.block_number, raw_event = EXCLUDED.raw_event, pending = TRUE,
+                indexed_at = EXCLUDED.indexed_at",
+        )
+        .bind(i64::from(event.nonce))
+        .bind(event.block_number.map(i64::try_from).transpose()?)
+        .bind(serde_json::to_string(event)?)
+        // Retained for the retention period from when it was indexed, as in memory.
+        .bind(event.indexed_at.timestamp())
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn remove_pending_nonce(&self, nonce: u32) -> Result<()> {
+        sqlx::query("UPDATE theoros_dispatches SET pending = FALSE WHERE nonce = $1")
+            .bind(i64::from(nonce))
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn remove_dispatch(&self, nonce: u32) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        for table in ["theoros_dispatches", "theoros_signed_checkpoints", "theoros_feed_updates"] {
+            sqlx::query(&format!("DELETE FROM {table} WHERE nonce = $1"))
+                .bind(i64::from(nonce))
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn save_signed_checkpoint(&self, validator: Felt, checkpoint: &SignedCheckpointWithMessageId) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO theoros_signed_checkpoints (nonce, validator, checkpoint) VALUES ($1, $2, $3::JSONB)
+            ON CONFLICT (nonce, validator) DO NOTHING",
+        )
+        .bind(i64::from(checkpoint.value.checkpoint.index))
+        .bind(format!("{:#x}", validator))
+        .bind(serde_json::to_string(checkpoint)?)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn save_latest_update(&self, feed_id: U256, nonce: u32) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO theoros_latest_updates (feed_id, nonce) VALUES ($1, $2)
+            ON CONFLICT (feed_id) DO UPDATE SET nonce = EXCLUDED.nonce",
+        )
+        .bind(format!("{:#x}", feed_id))
+        .bind(i64::from(nonce))
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn remove_latest_update(&self, feed_id: U256) -> Result<()> {
+        sqlx::query("DELETE FROM theoros_latest_updates WHERE feed_id = $1")
+            .bind(format!("{:#x}", feed_id))
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn save_dead_letter(&self, dead_letter: &DeadLetter) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO theoros_dead_letters (id, dead_letter) VALUES ($1, $2::JSONB)
+            ON CONFLICT (id) DO UPDATE SET dead_letter = EXCLUDED.dead_letter",
+        )
+        .bind(i64::try_from(dead_letter.id)?)
+        .bind(serde_json::to_string(dead_letter)?)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn remove_dead_letter(&self, id: u64) -> Result<()> {
+        sqlx::query("DELETE FROM theoros_dead_letters WHERE id = $1")
+            .bind(i64::try_from(id)?)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn save_setting(&self, kind: SettingKind, id: &str, value: &serde_json::Value) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO theoros_settings (kind, id, value) VALUES ($1, $2, $3::JSONB)
+            ON CONFLICT (kind, id) DO UPDATE SET value = EXCLUDED.value, updated_at = now()",
+        )
+        .bind(kind.to_string())
+        .bind(id)
+        .bind(serde_json::to_string(value)?)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn remove_setting(&self, kind: SettingKind, id: &str) -> Result<()> {
+        sqlx::query("DELETE FROM theoros_settings WHERE kind = $1 AND id = $2")
+            .bind(kind.to_string())
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn load_settings(&self) -> Result<PersistedSettings> {
+        let rows =
+            sqlx::query("SELECT kind, id, value::TEXT AS value FROM theoros_settings").fetch_all(&self.pool).await?;
+        let mut settings = PersistedSettings::default();
+        for row in rows {
+            let kind: String = row.try_get("kind")?;
+            let value: String = row.try_get("value")?;
+            settings.insert(kind.parse()?, row.try_get("id")?, serde_json::from_str(&value)?);
+        }
+        Ok(settings)
+    }
+
+    async fn save_metrics(&self, instance: &str, counters: &[u8]) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO theoros_metrics (instance, counters) VALUES ($1, $2::JSONB)
+            ON CONFLICT (instance) DO UPDATE SET counters = EXCLUDED.counters, updated_at = now()",
+        )
+        .bind(instance)
+        .bind(std::str::from_utf8(counters)?)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn load_metrics(&self, instance: &str) -> Result<Option<Vec<u8>>> {
+        let counters: Option<String> =
+            sqlx::query_scalar("SELECT counters::TEXT FROM theoros_metrics WHERE instance = $1")
+                .bind(instance)
+                .fetch_optional(&self.pool)
+                .await?;
+        Ok(counters.map(String::into_bytes))
+    }
+
+    async fn append_update(&self, feed_id: U256, update: &DispatchUpdateInfos) -> Result<()> {
+        // The price, decimals & publication time the history is queried on. Opaque updates have none.
+        let point = HistoryPoint::from_update(update);
+        sqlx::query(
+            "INSERT INTO theoros_feed_updates (feed_id, nonce, price, decimals, published_at, payload)
+            VALUES ($1, $2, $3::NUMERIC, $4, to_timestamp($5), $6::JSONB)
+            ON CONFLICT (feed_id, nonce) DO NOTHING",
+        )
+        .bind(format!("{:#x}", feed_id))
+        .bind(i64::from(update.nonce))
+        .bind(point.as_ref().map(|point| point.price.clone()))
+        .bind(point.as_ref().map(|point| i16::from(point.decimals)))
+        .bind(point.as_ref().and_then(|point| i64::try_from(point.timestamp).ok()))
+        .bind(serde_json::to_string(&UpdateView::from(&update.update))?)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn history(&self, feed_id: U256, range: &HistoryRange) -> Result<Option<Vec<HistoryPoint>>> {
+        let timestamp = |bound: Option<u64>| bound.map(i64::try_from).transpose();
+        let rows = sqlx::query(
+            "SELECT nonce, price::TEXT AS price, decimals, EXTRACT(EPOCH FROM published_at)::BIGINT AS timestamp
+            FROM theoros_feed_updates
+            WHERE feed_id = $1 AND price IS NOT NULL
+                AND ($2::BIGINT IS NULL OR published_at >= to_timestamp($2))
+                AND ($3::BIGINT IS NULL OR published_at <= to_timestamp($3))
+            ORDER BY published_at DESC, nonce DESC
+            LIMIT $4",
+        )
+        .bind(format!("{:#x}", feed_id))
+        .bind(timestamp(range.from)?)
+        .bind(timestamp(range.to)?)
+        .bind(i64::try_from(range.limit)?)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut points = rows
+            .iter()
+            .map(|row| {
+                let timestamp: i64 = row.try_get("timestamp")?;
+                let decimals: i16 = row.try_get("decimals")?;
+                Ok(HistoryPoint {
+                    nonce: nonce_from_row(row)?,
+                    timestamp: u64::try_from(timestamp)?,
+                    price: row.try_get("price")?,
+                    decimals: u8::try_from(decimals)?,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        points.reverse();
+        Ok(Some(points))
+    }
+
+    /// Prunes the updates of the history beyond the policy, except the latest one of each feed, then the
+    /// dispatches left without any update & their signed checkpoints. The checkpoints of the nonces not
+    /// indexed yet, e.g. prefetched, are kept.
+    async fn prune(&self, _nonces: &[u32], policy: &RetentionPolicy) -> Result<PrunedCounts> {
+        let keep_updates = policy.keep_updates.map(i64::try_from).transpose()?;
+        let keep_for = policy.keep_for.map(|keep_for| i64::try_from(keep_for.as_secs())).transpose()?;
+        let mut tx = self.pool.begin().await?;
+
+        let updates = sqlx::query(
+            "DELETE FROM theoros_feed_updates u
+            USING (
+                SELECT feed_id, nonce, ROW_NUMBER() OVER (PARTITION BY feed_id ORDER BY nonce DESC) AS rank
+                FROM theoros_feed_updates
+            ) ranked
+            WHERE u.feed_id = ranked.feed_id AND u.nonce = ranked.nonce
+                AND NOT EXISTS (
+                    SELECT 1 FROM theoros_latest_updates l WHERE l.feed_id = u.feed_id AND l.nonce = u.nonce
+                )
+                AND (
+                    ($1::BIGINT IS NOT NULL AND ranked.rank > $1)
+                    OR (
+                        $2::BIGINT IS NOT NULL
+                        AND COALESCE(u.published_at, u.stored_at) < now() - $2 * INTERVAL '1 second'
+                    )
+                )",
+        )
+        .bind(keep_updates)
+        .bind(keep_for)
+        .execute(&mut *tx)
+        .await?;
+
+        let dispatches = sqlx::query(
+            "DELETE FROM theoros_dispatches d
+            WHERE NOT d.pending
+                AND d.nonce < (SELECT MAX(nonce) FROM theoros_dispatches)
+                AND NOT EXISTS (SELECT 1 FROM theoros_feed_updates u WHERE u.nonce = d.nonce)
+                AND NOT EXISTS (SELECT 1 FROM theoros_latest_updates l WHERE l.nonce = d.nonce)
+                AND ($1::BIGINT IS NULL OR d.indexed_at < now() - $1 * INTERVAL '1 second')",
+        )
+        .bind(keep_for)
+        .execute(&mut *tx)
+        .await?;
+
+        let checkpoints = sqlx::query(
+            "DELETE FROM theoros_signed_checkpoints c
+            WHERE c.nonce < (SELECT MAX(nonce) FROM theoros_dispatches)
+                AND NOT EXISTS (SELECT 1 FROM theoros_dispatches d WHERE d.nonce = c.nonce)",
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(PrunedCounts {
+            updates: updates.rows_affected(),
+            dispatches: dispatches.rows_affected(),
+            checkpoints: checkpoints.rows_affected(),
+        })
+    }
+
+    /// Waits for the writes in flight to complete, then closes the connections.
+    async fn close(&self) -> Result<()> {
+        self.pool.close().await;
+        Ok(())
+    }
+}
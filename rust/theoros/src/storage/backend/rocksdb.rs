@@ -0,0 +1,303 @@
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+};
+
+use alloy::primitives::U256;
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use rocksdb::{ColumnFamily, ColumnFamilyDescriptor, Direction, IteratorMode, Options, WriteBatch, DB};
+use starknet::core::types::Felt;
+
+use super::{PersistedSettings, PersistedState, SettingKind, Storage};
+use crate::{
+    storage::{DeadLetter, RawDispatchEvent},
+    types::{
+        hyperlane::SignedCheckpointWithMessageId,
+        retention::{PrunedCounts, RetentionPolicy},
+    },
+};
+
+/// Version of the layout of the database written by this version of Theoros.
+/// - v2: dead letters.
+/// - v3: settings changed through the admin API.
+/// - v4: metrics counters.
+const SCHEMA_VERSION: u32 = 4;
+const SCHEMA_VERSION_KEY: &[u8] = b"schema_version";
+
+/// Raw events of the dispatches, by nonce.
+const DISPATCHES: &str = "dispatches";
+/// Nonces of the dispatches waiting for quorum.
+const PENDING_NONCES: &str = "pending_nonces";
+/// Signed checkpoints, by nonce then validator.
+const SIGNED_CHECKPOINTS: &str = "signed_checkpoints";
+/// Nonce of the dispatch each feed was last updated by, by feed id.
+const LATEST_UPDATES: &str = "latest_updates";
+/// Events that could not be decoded, by id.
+const DEAD_LETTERS: &str = "dead_letters";
+/// Settings changed through the admin API, by `<kind>/<id>`.
+const SETTINGS: &str = "settings";
+/// Metrics counters, by instance.
+const METRICS: &str = "metrics";
+
+const COLUMN_FAMILIES: [&str; 7] =
+    [DISPATCHES, PENDING_NONCES, SIGNED_CHECKPOINTS, LATEST_UPDATES, DEAD_LETTERS, SETTINGS, METRICS];
+
+/// Persists the state of Theoros in a RocksDB database. Nonces are keyed big-endian, so they are
+/// iterated in ascending order.
+pub struct RocksDbStorage {
+    db: DB,
+    path: PathBuf,
+}
+
+impl fmt::Debug for RocksDbStorage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RocksDbStorage").field("path", &self.path).finish()
+    }
+}
+
+impl RocksDbStorage {
+    /// Opens the database, creating it if needed. Databases written by a newer version of Theoros are refused.
+    pub fn open(path: &Path) -> Result<Self> {
+        let mut options = Options::default();
+        options.create_if_missing(true);
+        options.create_missing_column_families(true);
+        let column_families = COLUMN_FAMILIES.iter().map(|name| ColumnFamilyDescriptor::new(*name, Options::default()));
+        let db = DB::open_cf_descriptors(&options, path, column_families)
+            .with_context(|| format!("Opening the RocksDB database at {}", path.display()))?;
+
+        match db.get(SCHEMA_VERSION_KEY)? {
+            None => db.put(SCHEMA_VERSION_KEY, SCHEMA_VERSION.to_be_bytes())?,
+            Some(version) => {
+                let version = u32::from_be_bytes(version.as_slice().try_into().context("Invalid schema version")?);
+                anyhow::ensure!(
+                    version <= SCHEMA_VERSION,
+                    "The RocksDB database has schema version {version}, newer than the supported version \
+                     {SCHEMA_VERSION}. It was written by a newer version of Theoros."
+                );
+                // The column families added since are created on open.
+                if version < SCHEMA_VERSION {
+                    db.put(SCHEMA_VERSION_KEY, SCHEMA_VERSION.to_be_bytes())?;
+                }
+            }
+        }
+        tracing::info!("💾 Opened the RocksDB storage at {}", path.display());
+        Ok(Self { db, path: path.to_owned() })
+    }
+
+    fn cf(&self, name: &str) -> Result<&ColumnFamily> {
+        self.db.cf_handle(name).with_context(|| format!("Missing column family {name}"))
+    }
+
+    /// Iterates over all the entries of a column family, in key order.
+    fn entries(&self, name: &str) -> Result<Vec<(Box<[u8]>, Box<[u8]>)>> {
+        self.db.iterator_cf(self.cf(name)?, IteratorMode::Start).map(|entry| Ok(entry?)).collect()
+    }
+
+    /// Adds the deletion of the checkpoints signed for the nonce to the batch & returns how many there are.
+    fn delete_checkpoints(&self, batch: &mut WriteBatch, nonce: u32) -> Result<u64> {
+        let key = nonce.to_be_bytes();
+        let checkpoints = self.cf(SIGNED_CHECKPOINTS)?;
+        let mut deleted = 0;
+        for entry in self.db.iterator_cf(checkpoints, IteratorMode::From(&key, Direction::Forward)) {
+            let (checkpoint_key, _) = entry?;
+            if !checkpoint_key.starts_with(&key) {
+                break;
+            }
+            batch.delete_cf(checkpoints, checkpoint_key);
+            deleted += 1;
+        }
+        Ok(deleted)
+    }
+}
+
+fn nonce_from_key(key: &[u8]) -> Result<u32> {
+    Ok(u32::from_be_bytes(key.get(..4).context("Invalid nonce key")?.try_into()?))
+}
+
+fn checkpoint_key(nonce: u32, validator: Felt) -> Vec<u8> {
+    [nonce.to_be_bytes().as_slice(), &validator.to_bytes_be()].concat()
+}
+
+fn setting_key(kind: SettingKind, id: &str) -> String {
+    format!("{kind}/{id}")
+}
+
+#[async_trait]
+impl Storage for RocksDbStorage {
+    async fn load(&self) -> Result<PersistedState> {
+        let mut state = PersistedState::default();
+        for (key, value) in self.entries(DISPATCHES)? {
+            state.dispatches.insert(nonce_from_key(&key)?, serde_json::from_slice(&value)?);
+        }
+        for (key, _) in self.entries(PENDING_NONCES)? {
+            state.pending_nonces.insert(nonce_from_key(&key)?);
+        }
+        for (key, value) in self.entries(SIGNED_CHECKPOINTS)? {
+            let validator = Felt::from_bytes_be_slice(key.get(4..).context("Invalid checkpoint key")?);
+            state.signed_checkpoints.push((validator, serde_json::from_slice(&value)?));
+        }
+        for (key, value) in self.entries(LATEST_UPDATES)? {
+            let feed_id = U256::try_from_be_slice(&key).context("Invalid feed id key")?;
+            state.latest_updates.insert(feed_id, nonce_from_key(&value)?);
+        }
+        for (_, value) in self.entries(DEAD_LETTERS)? {
+            state.dead_letters.push(serde_json::from_slice(&value)?);
+        }
+        Ok(state)
+    }
+
+    async fn save_dispatch(&self, event: &RawDispatchEvent) -> Result<()> {
+        let nonce = event.nonce.to_be_bytes();
+        let mut batch = WriteBatch::default();
+        batch.put_cf(self.cf(DISPATCHES)?, nonce, serde_json::to_vec(event)?);
+        batch.put_cf(self.cf(PENDING_NONCES)?, nonce, b"");
+        Ok(self.db.write(batch)?)
+    }
+
+    async fn remove_pending_nonce(&self, nonce: u32) -> Result<()> {
+        Ok(self.db.delete_cf(self.cf(PENDING_NONCES)?, nonce.to_be_bytes())?)
+    }
+
+    async fn remove_dispatch(&self, nonce: u32) -> Result<()> {
+        let key = nonce.to_be_bytes();
+        let mut batch = WriteBatch::default();
+        batch.delete_cf(self.cf(DISPATCHES)?, key);
+        batch.delete_cf(self.cf(PENDING_NONCES)?, key);
+        self.delete_checkpoints(&mut batch, nonce)?;
+        Ok(self.db.write(batch)?)
+    }
+
+    async fn save_signed_checkpoint(&self, validator: Felt, checkpoint: &SignedCheckpointWithMessageId) -> Result<()> {
+        let key = checkpoint_key(checkpoint.value.checkpoint.index, validator);
+        Ok(self.db.put_cf(self.cf(SIGNED_CHECKPOINTS)?, key, serde_json::to_vec(checkpoint)?)?)
+    }
+
+    async fn save_latest_update(&self, feed_id: U256, nonce: u32) -> Result<()> {
+        Ok(self.db.put_cf(self.cf(LATEST_UPDATES)?, feed_id.to_be_bytes::<32>(), nonce.to_be_bytes())?)
+    }
+
+    async fn remove_latest_update(&self, feed_id: U256) -> Result<()> {
+        Ok(self.db.delete_cf(self.cf(LATEST_UPDATES)?, feed_id.to_be_bytes::<32>())?)
+    }
+
+    async fn save_dead_letter(&self, dead_letter: &DeadLetter) -> Result<()> {
+        Ok(self.db.put_cf(self.cf(DEAD_LETTERS)?, dead_letter.id.to_be_bytes(), serde_json::to_vec(dead_letter)?)?)
+    }
+
+    async fn remove_dead_letter(&self, id: u64) -> Result<()> {
+        Ok(self.db.delete_cf(self.cf(DEAD_LETTERS)?, id.to_be_bytes())?)
+    }
+
+    async fn save_setting(&self, kind: SettingKind, id: &str, value: &serde_json::Value) -> Result<()> {
+        Ok(self.db.put_cf(self.cf(SETTINGS)?, setting_key(kind, id), serde_json::to_vec(value)?)?)
+    }
+
+    async fn remove_setting(&self, kind: SettingKind, id: &str) -> Result<()> {
+        Ok(self.db.delete_cf(self.cf(SETTINGS)?, setting_key(kind, id))?)
+    }
+
+    async fn load_settings(&self) -> Result<PersistedSettings> {
+        let mut settings = PersistedSettings::default();
+        for (key, value) in self.entries(SETTINGS)? {
+            let key = std::str::from_utf8(&key).context("Invalid setting key")?;
+            let (kind, id) = key.split_once('/').context("Invalid setting key")?;
+            settings.insert(kind.parse()?, id.to_owned(), serde_json::from_slice(&value)?);
+        }
+        Ok(settings)
+    }
+
+    async fn save_metrics(&self, instance: &str, counters: &[u8]) -> Result<()> {
+        Ok(self.db.put_cf(self.cf(METRICS)?, instance, counters)?)
+    }
+
+    async fn load_metrics(&self, instance: &str) -> Result<Option<Vec<u8>>> {
+        Ok(self.db.get_cf(self.cf(METRICS)?, instance)?)
+    }
+
+    async fn prune(&self, nonces: &[u32], _policy: &RetentionPolicy) -> Result<PrunedCounts> {
+        let mut pruned = PrunedCounts::default();
+        let mut batch = WriteBatch::default();
+        for nonce in nonces {
+            let key = nonce.to_be_bytes();
+            if self.db.get_cf(self.cf(PENDING_NONCES)?, key)?.is_some() {
+                continue;
+            }
+            if self.db.get_cf(self.cf(DISPATCHES)?, key)?.is_some() {
+                batch.delete_cf(self.cf(DISPATCHES)?, key);
+                pruned.dispatches += 1;
+            }
+            pruned.checkpoints += self.delete_checkpoints(&mut batch, *nonce)?;
+        }
+        self.db.write(batch)?;
+        Ok(pruned)
+    }
+
+    /// Syncs the write-ahead log & flushes the memtables, so the next start doesn't replay the log.
+    async fn close(&self) -> Result<()> {
+        self.db.flush_wal(true)?;
+        self.db.flush()?;
+        for name in COLUMN_FAMILIES {
+            self.db.flush_cf(self.cf(name)?)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::DeadLetterEvent;
+
+    #[tokio::test]
+    async fn test_rocksdb_storage_survives_reopening() {
+        let path = std::env::temp_dir().join(format!("theoros-rocksdb-{}", std::process::id()));
+        let event = RawDispatchEvent::new(7, Some(100), None, &[], &[Felt::ONE]);
+        {
+            let storage = RocksDbStorage::open(&path).unwrap();
+            storage.save_dispatch(&event).await.unwrap();
+            storage.save_dispatch(&RawDispatchEvent::new(8, Some(101), None, &[], &[])).await.unwrap();
+            storage.remove_pending_nonce(7).await.unwrap();
+            storage.save_latest_update(U256::from(1), 7).await.unwrap();
+            storage.remove_dispatch(8).await.unwrap();
+            let error = anyhow::anyhow!("Missing nonce");
+            let dead_letter = DeadLetter::new(DeadLetterEvent::Dispatch, Some(100), None, &[], &[Felt::ONE], &error);
+            storage.save_dead_letter(&DeadLetter { id: 3, ..dead_letter }).await.unwrap();
+            storage.save_setting(SettingKind::PrivateFeed, "0x01", &serde_json::json!(true)).await.unwrap();
+            storage.save_setting(SettingKind::PrivateFeed, "0x02", &serde_json::json!(true)).await.unwrap();
+            storage.remove_setting(SettingKind::PrivateFeed, "0x02").await.unwrap();
+            storage.save_metrics("theoros-0", br#"{"calldata_served":2}"#).await.unwrap();
+        }
+
+        let state = RocksDbStorage::open(&path).unwrap().load().await.unwrap();
+        assert_eq!(state.dispatches.into_values().collect::<Vec<_>>(), vec![event]);
+        assert!(state.pending_nonces.is_empty());
+        assert_eq!(state.latest_updates.get(&U256::from(1)), Some(&7));
+        assert_eq!(state.dead_letters.iter().map(|dead_letter| dead_letter.id).collect::<Vec<_>>(), vec![3]);
+        let storage = RocksDbStorage::open(&path).unwrap();
+        let settings = storage.load_settings().await.unwrap();
+        assert_eq!(settings.of::<bool>(SettingKind::PrivateFeed).unwrap(), vec![("0x01".to_string(), true)]);
+        assert_eq!(storage.load_metrics("theoros-0").await.unwrap(), Some(br#"{"calldata_served":2}"#.to_vec()));
+        assert_eq!(storage.load_metrics("theoros-1").await.unwrap(), None);
+        drop(storage);
+        std::fs::remove_dir_all(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_rocksdb_storage_prunes_the_dispatches_not_pending() {
+        let path = std::env::temp_dir().join(format!("theoros-rocksdb-prune-{}", std::process::id()));
+        let storage = RocksDbStorage::open(&path).unwrap();
+        for nonce in [1, 2, 3] {
+            storage.save_dispatch(&RawDispatchEvent::new(nonce, Some(100), None, &[], &[])).await.unwrap();
+        }
+        storage.remove_pending_nonce(1).await.unwrap();
+        storage.remove_pending_nonce(2).await.unwrap();
+
+        let pruned = storage.prune(&[1, 3, 4], &RetentionPolicy::default()).await.unwrap();
+        assert_eq!(pruned, PrunedCounts { updates: 0, dispatches: 1, checkpoints: 0 });
+        let state = storage.load().await.unwrap();
+        assert_eq!(state.dispatches.into_keys().collect::<Vec<_>>(), vec![2, 3]);
+        drop(storage);
+        std::fs::remove_dir_all(path).unwrap();
+    }
+}
@@ -0,0 +1,125 @@
+use std::{collections::VecDeque, sync::Mutex};
+
+use alloy::primitives::{keccak256, B256};
+use dashmap::DashMap;
+
+use crate::{
+    configs::evm_config::EvmChainName,
+    constants::MAX_STORED_CALLDATA_BLOBS,
+    types::build_info::{EncoderVersion, GIT_COMMIT},
+};
+
+/// An assembled calldata blob, as served to a client.
+#[derive(Debug, Clone)]
+pub struct StoredCalldata {
+    pub feed_id: String,
+    pub chain: EvmChainName,
+    pub calldata: Vec<u8>,
+    /// Consumer the calldata was encrypted for, if it was served encrypted.
+    pub consumer: Option<String>,
+    /// Version of the encoder that produced the calldata layout.
+    pub encoder: EncoderVersion,
+    /// Commit of the Theoros build that encoded the calldata.
+    pub git_commit: &'static str,
+}
+
+impl StoredCalldata {
+    /// Creates a blob stamped with the encoder version of the running build.
+    pub fn new(feed_id: String, chain: EvmChainName, calldata: Vec<u8>, consumer: Option<String>) -> Self {
+        Self { feed_id, chain, calldata, consumer, encoder: EncoderVersion::CURRENT, git_commit: GIT_COMMIT }
+    }
+
+    /// Deterministic id of the calldata blob: the keccak256 hash of its bytes.
+    pub fn id(&self) -> B256 {
+        keccak256(&self.calldata)
+    }
+}
+
+/// Contains the most recently served calldata blobs, indexed by their id.
+/// Once [MAX_STORED_CALLDATA_BLOBS] is reached, the oldest blobs are evicted.
+#[derive(Debug, Default)]
+pub struct CalldataBlobsStorage {
+    blobs: DashMap<B256, StoredCalldata>,
+    insertion_order: Mutex<VecDeque<B256>>,
+}
+
+impl CalldataBlobsStorage {
+    /// Stores the calldata blob & returns its id.
+    pub fn add(&self, blob: StoredCalldata) -> B256 {
+        let id = blob.id();
+        let mut insertion_order = self.insertion_order.lock().expect("Poisoned lock");
+        match self.blobs.get_mut(&id) {
+            // Once served in cleartext, a blob is not tied to a consumer anymore.
+            Some(mut stored) => {
+                if blob.consumer.is_none() {
+                    stored.consumer = None;
+                }
+            }
+            None => {
+                self.blobs.insert(id, blob);
+                insertion_order.push_back(id);
+                while insertion_order.len() > MAX_STORED_CALLDATA_BLOBS {
+                    if let Some(evicted) = insertion_order.pop_front() {
+                        self.blobs.remove(&evicted);
+                    }
+                }
+            }
+        }
+        id
+    }
+
+    pub fn get(&self, id: &B256) -> Option<StoredCalldata> {
+        self.blobs.get(id).map(|blob| blob.clone())
+    }
+
+    pub fn num_blobs(&self) -> usize {
+        self.blobs.len()
+    }
+
+    /// Removes all the blobs & returns how many were removed.
+    pub fn clear(&self) -> usize {
+        let mut insertion_order = self.insertion_order.lock().expect("Poisoned lock");
+        let removed = self.blobs.len();
+        self.blobs.clear();
+        insertion_order.clear();
+        removed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn blob(calldata: Vec<u8>, consumer: Option<&str>) -> StoredCalldata {
+        StoredCalldata::new("0x1".into(), EvmChainName::Mainnet, calldata, consumer.map(String::from))
+    }
+
+    #[test]
+    fn test_calldata_ids_are_deterministic() {
+        let storage = CalldataBlobsStorage::default();
+        let id = storage.add(blob(vec![1, 2, 3], None));
+        assert_eq!(id, storage.add(blob(vec![1, 2, 3], None)));
+        assert_ne!(id, storage.add(blob(vec![3, 2, 1], None)));
+        assert_eq!(storage.get(&id).unwrap().calldata, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_oldest_blobs_are_evicted() {
+        let storage = CalldataBlobsStorage::default();
+        let first_id = storage.add(blob(0_u64.to_be_bytes().to_vec(), None));
+        for i in 1..=MAX_STORED_CALLDATA_BLOBS as u64 {
+            storage.add(blob(i.to_be_bytes().to_vec(), None));
+        }
+        assert!(storage.get(&first_id).is_none());
+        assert_eq!(storage.blobs.len(), MAX_STORED_CALLDATA_BLOBS);
+    }
+
+    #[test]
+    fn test_cleartext_blob_is_not_tied_to_consumer() {
+        let storage = CalldataBlobsStorage::default();
+        let id = storage.add(blob(vec![1], Some("consumer")));
+        assert_eq!(storage.get(&id).unwrap().consumer.as_deref(), Some("consumer"));
+        storage.add(blob(vec![1], None));
+        assert!(storage.get(&id).unwrap().consumer.is_none());
+    }
+}
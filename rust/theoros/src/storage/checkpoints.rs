@@ -1,4 +1,4 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
 use std::sync::Arc;
 
 use dashmap::DashMap;
@@ -46,6 +46,10 @@ impl SignedCheckpointsStorage {
         self.0.len()
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
     /// Adds or updates the [SignedCheckpointWithMessageId] for the given validator
     pub fn add(&self, validator: Felt, nonce: u32, checkpoint: SignedCheckpointWithMessageId) {
         self.0.insert((validator, nonce), checkpoint);
@@ -66,11 +70,33 @@ impl SignedCheckpointsStorage {
         checkpoints
     }
 
+    /// Returns the checkpoint signed by the validator for the given nonce, if any.
+    pub fn get_for_validator(&self, validator: Felt, nonce: u32) -> Option<SignedCheckpointWithMessageId> {
+        self.0.get(&(validator, nonce)).map(|checkpoint| checkpoint.clone())
+    }
+
     // Check if the given validator has a checkpoint for the given nonce.
     pub fn validator_signed_nonce(&self, validator: Felt, nonce: u32) -> bool {
         self.0.contains_key(&(validator, nonce))
     }
 
+    /// Removes the checkpoints signed by all the validators for a nonce.
+    pub fn remove_nonce(&self, nonce: u32) {
+        self.0.retain(|(_, signed_nonce), _| *signed_nonce != nonce);
+    }
+
+    /// Nonces with a checkpoint signed by any validator.
+    pub fn nonces(&self) -> BTreeSet<u32> {
+        self.0.iter().map(|entry| entry.key().1).collect()
+    }
+
+    /// Removes the checkpoints signed by all the validators for these nonces & returns how many were removed.
+    pub fn remove_nonces(&self, nonces: &HashSet<u32>) -> u64 {
+        let before = self.0.len();
+        self.0.retain(|(_, signed_nonce), _| !nonces.contains(signed_nonce));
+        before.saturating_sub(self.0.len()) as u64
+    }
+
     /// Checks if all validators have signed a nonce.
     pub fn all_validators_signed_nonce(&self, validators: &[Felt], nonce: u32) -> bool {
         validators.iter().all(|validator| self.0.contains_key(&(*validator, nonce)))
@@ -0,0 +1,38 @@
+use std::{collections::HashMap, sync::Arc};
+
+use dashmap::DashMap;
+
+use crate::types::encryption::ConsumerPublicKey;
+
+/// Contains the public keys registered by the consumers of private feeds.
+#[derive(Debug, Default, Clone)]
+pub struct ConsumerKeysStorage(Arc<DashMap<String, ConsumerPublicKey>>);
+
+impl ConsumerKeysStorage {
+    /// Registers the key of a consumer, replacing (rotating) the previous one if any.
+    pub fn set(&self, consumer_id: String, key: ConsumerPublicKey) {
+        self.0.insert(consumer_id, key);
+    }
+
+    /// Removes the key of a consumer. Returns the removed key, if any.
+    pub fn remove(&self, consumer_id: &str) -> Option<ConsumerPublicKey> {
+        self.0.remove(consumer_id).map(|(_, key)| key)
+    }
+
+    pub fn get(&self, consumer_id: &str) -> Option<ConsumerPublicKey> {
+        self.0.get(consumer_id).map(|key| key.clone())
+    }
+
+    /// Replaces the keys of all the consumers by `keys`, e.g. persisted by another process.
+    pub fn replace(&self, keys: HashMap<String, ConsumerPublicKey>) {
+        self.0.retain(|consumer_id, _| keys.contains_key(consumer_id));
+        for (consumer_id, key) in keys {
+            self.0.insert(consumer_id, key);
+        }
+    }
+
+    /// Returns all the consumers with their current key.
+    pub fn all(&self) -> Vec<(String, ConsumerPublicKey)> {
+        self.0.iter().map(|entry| (entry.key().clone(), entry.value().clone())).collect()
+    }
+}
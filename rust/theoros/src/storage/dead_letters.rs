@@ -0,0 +1,149 @@
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use starknet::core::types::Felt;
+use tokio::sync::RwLock;
+use utoipa::ToSchema;
+
+use crate::constants::MAX_STORED_DEAD_LETTERS;
+
+/// The indexed events that can be dead-lettered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DeadLetterEvent {
+    Dispatch,
+    ValidatorAnnouncement,
+}
+
+impl DeadLetterEvent {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DeadLetterEvent::Dispatch => "dispatch",
+            DeadLetterEvent::ValidatorAnnouncement => "validator_announcement",
+        }
+    }
+}
+
+/// A Starknet event the indexer could not decode, kept as emitted so it can be replayed once the parser is fixed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct DeadLetter {
+    pub id: u64,
+    pub event: DeadLetterEvent,
+    pub block_number: Option<u64>,
+    /// Hash of the transaction emitting the event, as a hex string.
+    pub transaction_hash: Option<String>,
+    /// Keys of the event, as hex strings.
+    pub keys: Vec<String>,
+    /// Data of the event, as hex strings.
+    pub data: Vec<String>,
+    pub error: String,
+    pub failed_at: DateTime<Utc>,
+}
+
+impl DeadLetter {
+    /// A dead letter without id, assigned once stored.
+    pub fn new(
+        event: DeadLetterEvent,
+        block_number: Option<u64>,
+        transaction_hash: Option<Felt>,
+        keys: &[Felt],
+        data: &[Felt],
+        error: &anyhow::Error,
+    ) -> Self {
+        Self {
+            id: 0,
+            event,
+            block_number,
+            transaction_hash: transaction_hash.map(|hash| hash.to_hex_string()),
+            keys: keys.iter().map(Felt::to_hex_string).collect(),
+            data: data.iter().map(Felt::to_hex_string).collect(),
+            error: format!("{error:#}"),
+            failed_at: Utc::now(),
+        }
+    }
+
+    /// Parses the keys of the event back into felts, to decode it again.
+    pub fn key_felts(&self) -> anyhow::Result<Vec<Felt>> {
+        self.keys.iter().map(|felt| Felt::from_hex(felt).map_err(anyhow::Error::from)).collect()
+    }
+
+    /// Parses the data of the event back into felts, to decode it again.
+    pub fn data_felts(&self) -> anyhow::Result<Vec<Felt>> {
+        self.data.iter().map(|felt| Felt::from_hex(felt).map_err(anyhow::Error::from)).collect()
+    }
+
+    pub fn transaction_hash_felt(&self) -> anyhow::Result<Option<Felt>> {
+        self.transaction_hash.as_deref().map(Felt::from_hex).transpose().map_err(anyhow::Error::from)
+    }
+}
+
+/// Contains the most recent dead letters, by id. Ids are never reused, even once replayed.
+#[derive(Debug, Default)]
+pub struct DeadLettersStorage {
+    dead_letters: RwLock<BTreeMap<u64, DeadLetter>>,
+    next_id: AtomicU64,
+}
+
+impl DeadLettersStorage {
+    /// Stores a dead letter under the next id, evicting the oldest ones when full. Returns the stored dead
+    /// letter & the ids of the evicted ones.
+    pub async fn add(&self, mut dead_letter: DeadLetter) -> (DeadLetter, Vec<u64>) {
+        let mut dead_letters = self.dead_letters.write().await;
+        dead_letter.id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        dead_letters.insert(dead_letter.id, dead_letter.clone());
+        let mut evicted = Vec::new();
+        while dead_letters.len() > MAX_STORED_DEAD_LETTERS {
+            evicted.extend(dead_letters.pop_first().map(|(id, _)| id));
+        }
+        (dead_letter, evicted)
+    }
+
+    /// Restores a persisted dead letter, keeping its id.
+    pub async fn restore(&self, dead_letter: DeadLetter) {
+        self.next_id.fetch_max(dead_letter.id + 1, Ordering::Relaxed);
+        self.dead_letters.write().await.insert(dead_letter.id, dead_letter);
+    }
+
+    pub async fn get(&self, id: u64) -> Option<DeadLetter> {
+        self.dead_letters.read().await.get(&id).cloned()
+    }
+
+    pub async fn remove(&self, id: u64) -> Option<DeadLetter> {
+        self.dead_letters.write().await.remove(&id)
+    }
+
+    /// Returns the dead letters, most recent first.
+    pub async fn all(&self) -> Vec<DeadLetter> {
+        self.dead_letters.read().await.values().rev().cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_dead_letters_roundtrip_and_eviction() {
+        let storage = DeadLettersStorage::default();
+        let data = [Felt::from(3_u8), Felt::from_hex("0xdeadbeef").unwrap()];
+        let error = anyhow::anyhow!("Missing nonce");
+        for _ in 0..=MAX_STORED_DEAD_LETTERS {
+            let dead_letter =
+                DeadLetter::new(DeadLetterEvent::Dispatch, Some(12), Some(Felt::from(42_u8)), &[], &data, &error);
+            storage.add(dead_letter).await;
+        }
+
+        let dead_letter = DeadLetter::new(DeadLetterEvent::Dispatch, None, None, &[], &[], &error);
+        let (stored, evicted) = storage.add(dead_letter).await;
+        assert_eq!((stored.id, evicted), (MAX_STORED_DEAD_LETTERS as u64 + 1, vec![1]));
+        assert!(storage.get(1).await.is_none());
+
+        let dead_letter = storage.remove(2).await.unwrap();
+        assert_eq!(dead_letter.transaction_hash_felt().unwrap(), Some(Felt::from(42_u8)));
+        assert_eq!(dead_letter.data_felts().unwrap(), data);
+        assert_eq!(dead_letter.error, "Missing nonce");
+        assert_eq!(storage.all().await.first().map(|dead_letter| dead_letter.id), Some(stored.id));
+    }
+}
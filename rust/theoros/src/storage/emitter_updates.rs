@@ -0,0 +1,87 @@
+use std::sync::Arc;
+
+use alloy::primitives::U256;
+use dashmap::DashMap;
+use starknet::core::types::Felt;
+
+use crate::storage::LatestUpdatePerFeedStorage;
+use crate::types::hyperlane::DispatchUpdateInfos;
+
+/// Contains the latest update of each feed dispatched by each emitter, served or not.
+///
+/// Only kept in memory: the updates of the served emitter are also persisted through the backend.
+#[derive(Debug, Default)]
+pub struct LatestUpdatePerEmitterStorage(Arc<DashMap<Felt, LatestUpdatePerFeedStorage>>);
+
+impl LatestUpdatePerEmitterStorage {
+    /// Insert the latest [`DispatchUpdateInfos`] of a feed, under the namespace of its emitter.
+    pub fn add(&self, feed_id: U256, update: DispatchUpdateInfos) {
+        self.0.entry(update.emitter_address).or_default().add(feed_id, update);
+    }
+
+    /// Retrieves the latest [`DispatchUpdateInfos`] of a feed dispatched by the emitter.
+    pub fn get(&self, emitter: &Felt, feed_id: &U256) -> Option<DispatchUpdateInfos> {
+        self.0.get(emitter).and_then(|updates| updates.get(feed_id))
+    }
+
+    /// Emitters which dispatched an update of the feed.
+    pub fn emitters_of(&self, feed_id: &U256) -> Vec<Felt> {
+        self.0.iter().filter(|updates| updates.get(feed_id).is_some()).map(|updates| *updates.key()).collect()
+    }
+
+    /// Nonces of the dispatches the feeds were last updated by, all emitters included.
+    pub fn latest_nonces(&self) -> Vec<u32> {
+        self.0.iter().flat_map(|updates| updates.latest_nonces().into_iter().map(|(_, nonce)| nonce)).collect()
+    }
+
+    /// Removes the updates coming from a dispatch orphaned by a reorg. Only the latest update of each feed is
+    /// kept, so the feeds it updated have none until their next dispatch.
+    pub fn roll_back(&self, nonce: u32) {
+        for updates in self.0.iter() {
+            updates.roll_back(nonce, |_| None);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use starknet::core::types::U256 as FeltU256;
+
+    use super::*;
+    use crate::types::hyperlane::{DispatchUpdate, MetadataUpdate, SpotMedianUpdate};
+
+    fn update(emitter: Felt, nonce: u32) -> DispatchUpdateInfos {
+        DispatchUpdateInfos {
+            nonce,
+            emitter_chain_id: 0,
+            emitter_address: emitter,
+            update: DispatchUpdate::SpotMedian {
+                update: SpotMedianUpdate {
+                    pair_id: FeltU256::from(0_u8),
+                    metadata: MetadataUpdate { timestamp: 0, num_sources_aggregated: 1, decimals: 8 },
+                    price: FeltU256::from(1_u8),
+                    volume: FeltU256::from(0_u8),
+                },
+                feed_id: "0x1".to_string(),
+            },
+        }
+    }
+
+    #[test]
+    fn test_updates_are_namespaced_by_emitter() {
+        let storage = LatestUpdatePerEmitterStorage::default();
+        let feed_id = U256::from(1);
+        storage.add(feed_id, update(Felt::ONE, 1));
+        storage.add(feed_id, update(Felt::TWO, 2));
+
+        assert_eq!(storage.get(&Felt::ONE, &feed_id).map(|update| update.nonce), Some(1));
+        assert_eq!(storage.get(&Felt::TWO, &feed_id).map(|update| update.nonce), Some(2));
+        let mut emitters = storage.emitters_of(&feed_id);
+        emitters.sort();
+        assert_eq!(emitters, vec![Felt::ONE, Felt::TWO]);
+
+        storage.roll_back(2);
+        assert!(storage.get(&Felt::TWO, &feed_id).is_none());
+        assert_eq!(storage.emitters_of(&feed_id), vec![Felt::ONE]);
+    }
+}
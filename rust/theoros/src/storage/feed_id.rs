@@ -22,6 +22,11 @@ impl FeedIdsStorage {
         self.0.remove(feed_id);
     }
 
+    /// Checks if the feed ID is present in the storage.
+    pub fn contains(&self, feed_id: &str) -> bool {
+        self.0.contains(feed_id)
+    }
+
     /// Checks if all feed IDs in the given vector are present in the storage.
     /// Returns None if all IDs are present, or Some(id) with the first missing ID.
     pub fn contains_vec(&self, feed_ids: &[String]) -> Option<String> {
@@ -33,6 +38,11 @@ impl FeedIdsStorage {
         self.0.len()
     }
 
+    /// Returns true if the storage contains no feed ID.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
     /// Returns an iterator over the feed IDs.
     pub fn iter(&self) -> impl Iterator<Item = String> {
         self.0.iter().map(|ref_multi| ref_multi.key().clone()).collect::<Vec<_>>().into_iter()
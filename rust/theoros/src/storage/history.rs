@@ -0,0 +1,71 @@
+use std::collections::{HashSet, VecDeque};
+use std::sync::Arc;
+
+use alloy::primitives::U256;
+use dashmap::DashMap;
+
+use crate::{
+    constants::MAX_HISTORY_UPDATES_PER_FEED,
+    types::{hyperlane::DispatchUpdateInfos, retention::RetentionPolicy},
+};
+
+/// Contains the most recent signed updates of each feed, in dispatch order.
+#[derive(Debug, Default)]
+pub struct FeedHistoryStorage(Arc<DashMap<U256, VecDeque<DispatchUpdateInfos>>>);
+
+impl FeedHistoryStorage {
+    /// Appends an update to the history of the feed. Updates already stored are ignored.
+    pub fn add(&self, feed_id: U256, update: DispatchUpdateInfos) {
+        let mut history = self.0.entry(feed_id).or_default();
+        if history.iter().rev().any(|stored| stored.nonce == update.nonce) {
+            return;
+        }
+        history.push_back(update);
+        if history.len() > MAX_HISTORY_UPDATES_PER_FEED {
+            history.pop_front();
+        }
+    }
+
+    /// Removes the updates of a dispatch from the history of all the feeds.
+    pub fn remove_nonce(&self, nonce: u32) {
+        for mut history in self.0.iter_mut() {
+            history.retain(|update| update.nonce != nonce);
+        }
+    }
+
+    /// Removes the updates the retention policy doesn't keep anymore at `now` & returns how many were removed.
+    pub fn prune(&self, policy: &RetentionPolicy, now: u64) -> u64 {
+        let mut pruned = 0;
+        for mut history in self.0.iter_mut() {
+            // Ranked from the latest update, at the back.
+            let mut rank = history.len();
+            history.retain(|update| {
+                rank -= 1;
+                let kept = policy.keeps_update(rank, update.update.timestamp(), now);
+                pruned += u64::from(!kept);
+                kept
+            });
+        }
+        pruned
+    }
+
+    /// Nonces of the dispatches with an update in the history of a feed.
+    pub fn nonces(&self) -> HashSet<u32> {
+        self.0.iter().flat_map(|history| history.iter().map(|update| update.nonce).collect::<Vec<_>>()).collect()
+    }
+
+    /// Returns the latest stored update of the feed.
+    pub fn latest(&self, feed_id: &U256) -> Option<DispatchUpdateInfos> {
+        self.0.get(feed_id).and_then(|history| history.back().cloned())
+    }
+
+    /// Returns the stored updates of the feed, in dispatch order.
+    pub fn get(&self, feed_id: &U256) -> Vec<DispatchUpdateInfos> {
+        self.0.get(feed_id).map(|history| history.iter().cloned().collect()).unwrap_or_default()
+    }
+
+    /// Number of updates stored, all feeds included.
+    pub fn num_updates(&self) -> usize {
+        self.0.iter().map(|history| history.len()).sum()
+    }
+}
@@ -1,20 +1,56 @@
+pub mod anomalies;
+pub mod backend;
+pub mod calldata_blobs;
 pub mod checkpoints;
+pub mod consumer_keys;
+pub mod dead_letters;
+pub mod emitter_updates;
 pub mod feed_id;
+pub mod history;
+pub mod parse_failures;
+pub mod quarantine;
+pub mod raw_events;
+pub mod schema;
+pub mod timeline;
 pub mod updates;
 pub mod validator;
 
+pub use anomalies::*;
+pub use backend::{InMemoryStorage, PersistedSettings, PersistedState, SettingKind, Storage, StorageBackendConfig};
+pub use calldata_blobs::*;
 pub use checkpoints::*;
+pub use consumer_keys::*;
+pub use dead_letters::*;
+pub use emitter_updates::*;
 pub use feed_id::*;
+pub use history::*;
+pub use parse_failures::*;
+pub use quarantine::*;
+pub use raw_events::*;
+pub use schema::*;
+pub use timeline::*;
 pub use updates::*;
 pub use validator::*;
 
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
+
+use alloy::primitives::U256;
+use anyhow::Context;
+use pragma_utils::conversions::alloy::hex_str_to_u256;
 use starknet::core::types::Felt;
 use tokio::sync::broadcast::Sender;
 
 use crate::{
+    configs::emitters::Emitters,
     constants::FEED_UPDATED_CHANNEL_CAPACITY,
-    rpc::starknet::{HyperlaneCalls, PragmaFeedsRegistryCalls, StarknetRpc},
-    types::hyperlane::NewUpdatesAvailableEvent,
+    rpc::starknet::StarknetCalls,
+    types::history::{HistoryPoint, HistoryRange},
+    types::hyperlane::{
+        DispatchEvent, DispatchUpdateInfos, FromStarknetEventData, NewUpdatesAvailableEvent,
+        SignedCheckpointWithMessageId,
+    },
+    types::retention::{PrunedCounts, RetentionPolicy},
 };
 
 pub struct TheorosStorage {
@@ -23,35 +59,80 @@ pub struct TheorosStorage {
     signed_checkpoints: SignedCheckpointsStorage,
     unsigned_checkpoints: UnsignedCheckpointsStorage,
     latest_update_per_feed: LatestUpdatePerFeedStorage,
+    /// Latest updates of every indexed emitter, the served one included.
+    latest_update_per_emitter: LatestUpdatePerEmitterStorage,
+    /// Emitters whose dispatches are indexed, the updates of the served one backing the calldata.
+    emitters: Emitters,
+    feed_history: FeedHistoryStorage,
+    consumer_keys: ConsumerKeysStorage,
+    calldata_blobs: CalldataBlobsStorage,
+    checkpoint_anomalies: CheckpointAnomaliesStorage,
+    feed_timelines: FeedTimelinesStorage,
+    quarantine: QuarantineStorage,
+    parse_failures: ParseFailuresStorage,
+    raw_dispatch_events: RawDispatchEventsStorage,
+    dead_letters: DeadLettersStorage,
+    /// Where the dispatches, checkpoints & latest updates are persisted across restarts.
+    backend: Arc<dyn Storage>,
     // websocket notifications
     feeds_updated_tx: Sender<NewUpdatesAvailableEvent>,
 }
 
 impl TheorosStorage {
+    /// Fills the `validators_fetchers` with the storage locations announced by the validators on-chain.
     pub async fn from_rpc_state(
-        rpc_client: &StarknetRpc,
+        rpc_client: &dyn StarknetCalls,
         pragma_feeds_registry_address: &Felt,
         hyperlane_validator_announce_address: &Felt,
+        mut validators_fetchers: ValidatorsFetchersStorage,
     ) -> anyhow::Result<Self> {
         let initial_validators = rpc_client.get_announced_validators(hyperlane_validator_announce_address).await?;
         let initial_locations = rpc_client
             .get_announced_storage_locations(hyperlane_validator_announce_address, &initial_validators)
             .await?;
 
-        let mut validators_fetchers = ValidatorsFetchersStorage::default();
         validators_fetchers.fill_with_initial_state(initial_validators, initial_locations).await?;
 
         let supported_feed_ids = rpc_client.get_feed_ids(pragma_feeds_registry_address).await?;
         let feed_ids = FeedIdsStorage::from_rpc_response(supported_feed_ids);
 
-        Ok(Self {
+        Ok(Self::new(feed_ids, validators_fetchers))
+    }
+
+    /// Creates a storage with the provided feed ids & validators fetchers, everything else being empty.
+    pub fn new(feed_ids: FeedIdsStorage, validators_fetchers: ValidatorsFetchersStorage) -> Self {
+        Self {
             feed_ids,
             validators_fetchers,
             signed_checkpoints: SignedCheckpointsStorage::default(),
             unsigned_checkpoints: UnsignedCheckpointsStorage::default(),
             latest_update_per_feed: LatestUpdatePerFeedStorage::default(),
+            latest_update_per_emitter: LatestUpdatePerEmitterStorage::default(),
+            emitters: Emitters::default(),
+            feed_history: FeedHistoryStorage::default(),
+            consumer_keys: ConsumerKeysStorage::default(),
+            calldata_blobs: CalldataBlobsStorage::default(),
+            checkpoint_anomalies: CheckpointAnomaliesStorage::default(),
+            feed_timelines: FeedTimelinesStorage::default(),
+            quarantine: QuarantineStorage::default(),
+            parse_failures: ParseFailuresStorage::default(),
+            raw_dispatch_events: RawDispatchEventsStorage::default(),
+            dead_letters: DeadLettersStorage::default(),
+            backend: Arc::new(InMemoryStorage),
             feeds_updated_tx: tokio::sync::broadcast::channel(FEED_UPDATED_CHANNEL_CAPACITY).0,
-        })
+        }
+    }
+
+    /// Persists the state into the backend, instead of keeping it in memory only.
+    pub fn with_backend(mut self, backend: Arc<dyn Storage>) -> Self {
+        self.backend = backend;
+        self
+    }
+
+    /// Indexes the dispatches of these emitters only, serving the updates of the first one.
+    pub fn with_emitters(mut self, emitters: Emitters) -> Self {
+        self.emitters = emitters;
+        self
     }
 
     pub fn feed_ids(&self) -> &FeedIdsStorage {
@@ -70,11 +151,665 @@ impl TheorosStorage {
         &self.latest_update_per_feed
     }
 
+    pub fn latest_update_per_emitter(&self) -> &LatestUpdatePerEmitterStorage {
+        &self.latest_update_per_emitter
+    }
+
+    pub fn emitters(&self) -> &Emitters {
+        &self.emitters
+    }
+
+    pub fn feed_history(&self) -> &FeedHistoryStorage {
+        &self.feed_history
+    }
+
     pub fn unsigned_checkpoints(&self) -> &UnsignedCheckpointsStorage {
         &self.unsigned_checkpoints
     }
 
+    pub fn consumer_keys(&self) -> &ConsumerKeysStorage {
+        &self.consumer_keys
+    }
+
+    pub fn calldata_blobs(&self) -> &CalldataBlobsStorage {
+        &self.calldata_blobs
+    }
+
+    pub fn checkpoint_anomalies(&self) -> &CheckpointAnomaliesStorage {
+        &self.checkpoint_anomalies
+    }
+
+    pub fn feed_timelines(&self) -> &FeedTimelinesStorage {
+        &self.feed_timelines
+    }
+
+    pub fn quarantine(&self) -> &QuarantineStorage {
+        &self.quarantine
+    }
+
+    pub fn parse_failures(&self) -> &ParseFailuresStorage {
+        &self.parse_failures
+    }
+
+    pub fn raw_dispatch_events(&self) -> &RawDispatchEventsStorage {
+        &self.raw_dispatch_events
+    }
+
+    pub fn dead_letters(&self) -> &DeadLettersStorage {
+        &self.dead_letters
+    }
+
     pub fn feeds_updated_tx(&self) -> &Sender<NewUpdatesAvailableEvent> {
         &self.feeds_updated_tx
     }
+
+    /// Stores an indexed dispatch, waiting for the signatures of the validators.
+    pub async fn add_dispatch(&self, raw_event: RawDispatchEvent, event: &DispatchEvent) {
+        let nonce = event.message.header.nonce;
+        self.persisted("dispatch", nonce, self.backend.save_dispatch(&raw_event).await);
+        self.raw_dispatch_events.add(raw_event).await;
+        self.unsigned_checkpoints.add(nonce, event).await;
+    }
+
+    /// Removes a dispatch from the ones waiting for quorum, once its updates are stored.
+    pub async fn remove_pending_dispatch(&self, nonce: u32) {
+        self.unsigned_checkpoints.remove(nonce).await;
+        self.persisted("pending nonce", nonce, self.backend.remove_pending_nonce(nonce).await);
+    }
+
+    pub async fn add_signed_checkpoint(&self, validator: Felt, checkpoint: SignedCheckpointWithMessageId) {
+        let nonce = checkpoint.value.checkpoint.index;
+        self.persisted("signed checkpoint", nonce, self.backend.save_signed_checkpoint(validator, &checkpoint).await);
+        self.signed_checkpoints.add(validator, nonce, checkpoint);
+    }
+
+    /// Stores the update of a feed signed by the validators, in its history & as its latest update. Backends
+    /// keeping a history append every update, even when older than the latest one. The updates of the
+    /// emitters which aren't served are only kept as the latest update of their emitter.
+    pub async fn add_update(&self, feed_id: U256, update: DispatchUpdateInfos) {
+        self.latest_update_per_emitter.add(feed_id, update.clone());
+        if !self.emitters.is_served(&update.emitter_address) {
+            return;
+        }
+        let nonce = update.nonce;
+        self.persisted("update", nonce, self.backend.append_update(feed_id, &update).await);
+        self.feed_history.add(feed_id, update.clone());
+        if self.latest_update_per_feed.add(feed_id, update) {
+            self.persisted("latest update", nonce, self.backend.save_latest_update(feed_id, nonce).await);
+        }
+    }
+
+    /// Returns the updates of the feed in range, from the backend when it keeps a history, otherwise from the
+    /// updates kept in memory.
+    pub async fn feed_history_in_range(
+        &self,
+        feed_id: U256,
+        range: &HistoryRange,
+    ) -> anyhow::Result<Vec<HistoryPoint>> {
+        if let Some(points) = self.backend.history(feed_id, range).await? {
+            return Ok(points);
+        }
+        Ok(range.select(self.feed_history.get(&feed_id).iter().filter_map(HistoryPoint::from_update)))
+    }
+
+    /// Removes everything stored for a dispatch orphaned by a reorg. The feeds it updated fall back to
+    /// their previous update, when still in their history.
+    pub async fn roll_back_dispatch(&self, nonce: u32) {
+        self.unsigned_checkpoints.remove(nonce).await;
+        self.signed_checkpoints.remove_nonce(nonce);
+        self.raw_dispatch_events.remove(nonce).await;
+        self.feed_history.remove_nonce(nonce);
+        self.latest_update_per_emitter.roll_back(nonce);
+        let rolled_back = self.latest_update_per_feed.roll_back(nonce, |feed_id| self.feed_history.latest(feed_id));
+
+        self.persisted("rolled back dispatch", nonce, self.backend.remove_dispatch(nonce).await);
+        for (feed_id, latest_nonce) in rolled_back {
+            let persisted = match latest_nonce {
+                Some(latest_nonce) => self.backend.save_latest_update(feed_id, latest_nonce).await,
+                None => self.backend.remove_latest_update(feed_id).await,
+            };
+            self.persisted("rolled back latest update", nonce, persisted);
+        }
+    }
+
+    /// Prunes what the retention policy doesn't keep anymore at `now`, from the in-memory storages & the backend,
+    /// & returns what was pruned from each. A dispatch is kept with its signed checkpoints while pending, while
+    /// a feed was last updated by it, while one of its updates is kept or, with a retention period, while it was
+    /// indexed within the period. The checkpoints of the nonces not indexed yet, e.g. prefetched, are kept.
+    pub async fn prune(&self, policy: &RetentionPolicy, now: u64) -> (PrunedCounts, anyhow::Result<PrunedCounts>) {
+        let (pruned, nonces) = self.prune_memory(policy, now).await;
+        (pruned, self.backend.prune(&nonces, policy).await)
+    }
+
+    /// Prunes the in-memory storages only, e.g. of `theoros-api` whose backend is pruned by `theoros-indexer`, &
+    /// returns what was pruned along with the nonces of the pruned dispatches & signed checkpoints.
+    pub async fn prune_memory(&self, policy: &RetentionPolicy, now: u64) -> (PrunedCounts, Vec<u32>) {
+        let mut pruned = PrunedCounts { updates: self.feed_history.prune(policy, now), ..Default::default() };
+
+        let indexed_at = self.raw_dispatch_events.indexed_at().await;
+        let mut kept: HashSet<u32> = self.feed_history.nonces();
+        kept.extend(self.unsigned_checkpoints.nonces().await);
+        kept.extend(self.latest_update_per_feed.latest_nonces().into_iter().map(|(_, nonce)| nonce));
+        kept.extend(self.latest_update_per_emitter.latest_nonces());
+        if policy.keep_for.is_some() {
+            let recent = indexed_at
+                .iter()
+                .filter(|(_, indexed_at)| !policy.is_expired(u64::try_from(indexed_at.timestamp()).ok(), now));
+            kept.extend(recent.map(|(nonce, _)| *nonce));
+        }
+
+        if let Some(latest_nonce) = self.raw_dispatch_events.latest_nonce().await {
+            let pruned_nonces: HashSet<u32> = self
+                .signed_checkpoints
+                .nonces()
+                .into_iter()
+                .chain(indexed_at.iter().map(|(nonce, _)| *nonce))
+                .filter(|nonce| *nonce < latest_nonce && !kept.contains(nonce))
+                .collect();
+            pruned.checkpoints = self.signed_checkpoints.remove_nonces(&pruned_nonces);
+            pruned.dispatches = self.raw_dispatch_events.remove_nonces(&pruned_nonces).await;
+            return (pruned, pruned_nonces.into_iter().collect());
+        }
+        (pruned, Vec::new())
+    }
+
+    /// Stores an event that could not be decoded, until it is replayed, & returns it with its id.
+    pub async fn add_dead_letter(&self, dead_letter: DeadLetter) -> DeadLetter {
+        let (dead_letter, evicted) = self.dead_letters.add(dead_letter).await;
+        self.persisted_dead_letter(dead_letter.id, self.backend.save_dead_letter(&dead_letter).await);
+        for id in evicted {
+            self.persisted_dead_letter(id, self.backend.remove_dead_letter(id).await);
+        }
+        dead_letter
+    }
+
+    /// Removes a dead letter, e.g. once replayed.
+    pub async fn remove_dead_letter(&self, id: u64) -> Option<DeadLetter> {
+        let dead_letter = self.dead_letters.remove(id).await?;
+        self.persisted_dead_letter(id, self.backend.remove_dead_letter(id).await);
+        Some(dead_letter)
+    }
+
+    /// Persists a setting changed through the admin API. It is kept in memory only when the backend fails.
+    pub async fn save_setting(&self, kind: SettingKind, id: &str, value: impl serde::Serialize) {
+        let result = match serde_json::to_value(value) {
+            Ok(value) => self.backend.save_setting(kind, id, &value).await,
+            Err(e) => Err(e.into()),
+        };
+        self.persisted_setting(kind, id, result);
+    }
+
+    pub async fn remove_setting(&self, kind: SettingKind, id: &str) {
+        self.persisted_setting(kind, id, self.backend.remove_setting(kind, id).await);
+    }
+
+    /// Returns the settings changed through the admin API & persisted, possibly by another process sharing the
+    /// backend.
+    pub async fn load_settings(&self) -> anyhow::Result<PersistedSettings> {
+        self.backend.load_settings().await.context("Loading the persisted settings")
+    }
+
+    /// Persists the metrics counters of the process, as versioned JSON.
+    pub async fn save_metrics(&self, instance: &str, counters: &[u8]) -> anyhow::Result<()> {
+        self.backend.save_metrics(instance, counters).await
+    }
+
+    /// Returns the metrics counters last persisted by the process, if any.
+    pub async fn load_metrics(&self, instance: &str) -> anyhow::Result<Option<Vec<u8>>> {
+        self.backend.load_metrics(instance).await.context("Loading the persisted metrics counters")
+    }
+
+    /// Restores the state persisted in the backend, e.g. before a restart.
+    pub async fn restore(&self) -> anyhow::Result<()> {
+        let mut state = self.backend.load().await.context("Loading the persisted state")?;
+        let num_pending = state.pending_nonces.len();
+
+        let num_dead_letters = state.dead_letters.len();
+        for dead_letter in std::mem::take(&mut state.dead_letters) {
+            self.dead_letters.restore(dead_letter).await;
+        }
+        self.apply_persisted(state).await?;
+
+        tracing::info!(
+            "💾 Restored {} dispatches pending quorum, {} signed checkpoints, the latest update of {} feeds & {} dead \
+             letters",
+            num_pending,
+            self.signed_checkpoints.len(),
+            self.latest_update_per_feed.num_feeds(),
+            num_dead_letters
+        );
+        Ok(())
+    }
+
+    /// Catches up with the state persisted by another process sharing the backend, e.g. by `theoros-indexer`
+    /// for `theoros-api`, & notifies the subscribers of the feeds updated since the last sync. Returns the number
+    /// of dispatches which updated feeds.
+    pub async fn sync_from_backend(&self) -> anyhow::Result<usize> {
+        let state = self.backend.load().await.context("Loading the persisted state")?;
+        let updated = self.apply_persisted(state).await?;
+        let num_updated = updated.len();
+        for (nonce, feed_ids) in updated {
+            // Fails when nobody is subscribed, which is expected.
+            let _ = self.feeds_updated_tx.send(NewUpdatesAvailableEvent::New { nonce, feed_ids });
+        }
+        Ok(num_updated)
+    }
+
+    /// Applies a persisted state onto the in-memory storages, only decoding the dispatches that changed, & returns
+    /// the ids of the feeds whose latest update changed, by nonce. The dead letters aren't applied.
+    async fn apply_persisted(&self, state: PersistedState) -> anyhow::Result<BTreeMap<u32, Vec<String>>> {
+        let pending_in_memory: HashSet<u32> = self.unsigned_checkpoints.nonces().await.into_iter().collect();
+        for nonce in pending_in_memory.iter().filter(|nonce| !state.pending_nonces.contains(nonce)) {
+            self.unsigned_checkpoints.remove(*nonce).await;
+        }
+
+        let mut events: HashMap<u32, DispatchEvent> = HashMap::new();
+        for nonce in state.pending_nonces.iter().filter(|nonce| !pending_in_memory.contains(nonce)) {
+            let Some(raw_event) = state.dispatches.get(nonce) else { continue };
+            let event = decode_persisted(raw_event)?;
+            self.unsigned_checkpoints.add(*nonce, &event).await;
+            events.insert(*nonce, event);
+        }
+
+        for (validator, checkpoint) in state.signed_checkpoints {
+            self.signed_checkpoints.add(validator, checkpoint.value.checkpoint.index, checkpoint);
+        }
+
+        let mut updated: BTreeMap<u32, Vec<String>> = BTreeMap::new();
+        let latest_in_memory: HashMap<U256, u32> = self.latest_update_per_feed.latest_nonces().into_iter().collect();
+        for (feed_id, nonce) in &state.latest_updates {
+            if latest_in_memory.get(feed_id) == Some(nonce) {
+                continue;
+            }
+            if !events.contains_key(nonce) {
+                let Some(raw_event) = state.dispatches.get(nonce) else {
+                    tracing::warn!("💾 The persisted latest update of feed {:#x} (#{}) is missing", feed_id, nonce);
+                    continue;
+                };
+                events.insert(*nonce, decode_persisted(raw_event)?);
+            }
+            let event = &events[nonce];
+            let update = event.message.body.updates.iter().find(|update| {
+                hex_str_to_u256(&update.feed_id()).is_ok_and(|update_feed_id| update_feed_id == *feed_id)
+            });
+            let Some(update) = update else {
+                tracing::warn!("💾 The persisted latest update of feed {:#x} (#{}) is missing", feed_id, nonce);
+                continue;
+            };
+            let feed_id_str = update.feed_id();
+            let update = DispatchUpdateInfos::new(event, update);
+            self.feed_history.add(*feed_id, update.clone());
+            self.latest_update_per_emitter.add(*feed_id, update.clone());
+            if self.latest_update_per_feed.add(*feed_id, update) {
+                updated.entry(*nonce).or_default().push(feed_id_str);
+            }
+        }
+
+        for (nonce, raw_event) in state.dispatches {
+            if self.raw_dispatch_events.get(nonce).await.is_none() {
+                self.raw_dispatch_events.add(raw_event).await;
+            }
+        }
+        Ok(updated)
+    }
+
+    /// Closes the backend on shutdown, once nothing writes to the storage anymore.
+    pub async fn close(&self) {
+        if let Err(e) = self.backend.close().await {
+            tracing::error!("💾 Failed to close the storage backend: {:?}", e);
+        }
+    }
+
+    /// The in-memory storages keep serving when the backend fails, the state is only lost on restart.
+    fn persisted(&self, what: &str, nonce: u32, result: anyhow::Result<()>) {
+        if let Err(e) = result {
+            tracing::error!("💾 Failed to persist the {} of dispatch #{}: {:?}", what, nonce, e);
+        }
+    }
+
+    fn persisted_dead_letter(&self, id: u64, result: anyhow::Result<()>) {
+        if let Err(e) = result {
+            tracing::error!("💾 Failed to persist the dead letter #{}: {:?}", id, e);
+        }
+    }
+
+    fn persisted_setting(&self, kind: SettingKind, id: &str, result: anyhow::Result<()>) {
+        if let Err(e) = result {
+            tracing::error!("💾 Failed to persist the {} setting {}: {:?}", kind, id, e);
+        }
+    }
+}
+
+fn decode_persisted(raw_event: &RawDispatchEvent) -> anyhow::Result<DispatchEvent> {
+    DispatchEvent::from_starknet_event_data(raw_event.data_felts()?)
+        .with_context(|| format!("Decoding the persisted dispatch #{}", raw_event.nonce))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    use alloy::primitives::Parity;
+    use alloy::signers::Signature;
+    use async_trait::async_trait;
+    use chrono::{DateTime, Utc};
+    use pragma_utils::tracing::SamplingRule;
+
+    use super::*;
+    use crate::configs::emitters::Emitter;
+    use crate::handlers::admin::tracing_sampling::TracingSamplingRule;
+    use crate::services::{
+        hyperlane::store_updates,
+        synthetic::{dispatch_event_data, synthetic_feed_id},
+    };
+    use crate::types::{
+        encryption::ConsumerPublicKey,
+        hyperlane::{Checkpoint, CheckpointWithMessageId},
+    };
+    use crate::AppState;
+
+    /// Backend shared by the storages of distinct processes, e.g. of `theoros-indexer` & `theoros-api`.
+    #[derive(Debug, Default)]
+    struct SharedBackend(Mutex<SharedState>);
+
+    #[derive(Debug, Default)]
+    struct SharedState {
+        dispatches: BTreeMap<u32, RawDispatchEvent>,
+        pending_nonces: BTreeSet<u32>,
+        signed_checkpoints: Vec<(Felt, SignedCheckpointWithMessageId)>,
+        latest_updates: HashMap<U256, u32>,
+        settings: BTreeMap<(SettingKind, String), serde_json::Value>,
+        metrics: HashMap<String, Vec<u8>>,
+    }
+
+    #[async_trait]
+    impl Storage for SharedBackend {
+        async fn load(&self) -> anyhow::Result<PersistedState> {
+            let state = self.0.lock().unwrap();
+            Ok(PersistedState {
+                dispatches: state.dispatches.clone(),
+                pending_nonces: state.pending_nonces.clone(),
+                signed_checkpoints: state.signed_checkpoints.clone(),
+                latest_updates: state.latest_updates.clone(),
+                dead_letters: Vec::new(),
+            })
+        }
+
+        async fn save_dispatch(&self, event: &RawDispatchEvent) -> anyhow::Result<()> {
+            let mut state = self.0.lock().unwrap();
+            state.pending_nonces.insert(event.nonce);
+            state.dispatches.insert(event.nonce, event.clone());
+            Ok(())
+        }
+
+        async fn remove_pending_nonce(&self, nonce: u32) -> anyhow::Result<()> {
+            self.0.lock().unwrap().pending_nonces.remove(&nonce);
+            Ok(())
+        }
+
+        async fn remove_dispatch(&self, nonce: u32) -> anyhow::Result<()> {
+            let mut state = self.0.lock().unwrap();
+            state.dispatches.remove(&nonce);
+            state.pending_nonces.remove(&nonce);
+            state.signed_checkpoints.retain(|(_, checkpoint)| checkpoint.value.checkpoint.index != nonce);
+            Ok(())
+        }
+
+        async fn save_signed_checkpoint(
+            &self,
+            validator: Felt,
+            checkpoint: &SignedCheckpointWithMessageId,
+        ) -> anyhow::Result<()> {
+            self.0.lock().unwrap().signed_checkpoints.push((validator, checkpoint.clone()));
+            Ok(())
+        }
+
+        async fn save_latest_update(&self, feed_id: U256, nonce: u32) -> anyhow::Result<()> {
+            self.0.lock().unwrap().latest_updates.insert(feed_id, nonce);
+            Ok(())
+        }
+
+        async fn remove_latest_update(&self, feed_id: U256) -> anyhow::Result<()> {
+            self.0.lock().unwrap().latest_updates.remove(&feed_id);
+            Ok(())
+        }
+
+        async fn save_dead_letter(&self, _dead_letter: &DeadLetter) -> anyhow::Result<()> {
+            Ok(())
+        }
+
+        async fn remove_dead_letter(&self, _id: u64) -> anyhow::Result<()> {
+            Ok(())
+        }
+
+        async fn save_setting(&self, kind: SettingKind, id: &str, value: &serde_json::Value) -> anyhow::Result<()> {
+            self.0.lock().unwrap().settings.insert((kind, id.to_owned()), value.clone());
+            Ok(())
+        }
+
+        async fn remove_setting(&self, kind: SettingKind, id: &str) -> anyhow::Result<()> {
+            self.0.lock().unwrap().settings.remove(&(kind, id.to_owned()));
+            Ok(())
+        }
+
+        async fn load_settings(&self) -> anyhow::Result<PersistedSettings> {
+            let mut settings = PersistedSettings::default();
+            for ((kind, id), value) in &self.0.lock().unwrap().settings {
+                settings.insert(*kind, id.clone(), value.clone());
+            }
+            Ok(settings)
+        }
+
+        async fn save_metrics(&self, instance: &str, counters: &[u8]) -> anyhow::Result<()> {
+            self.0.lock().unwrap().metrics.insert(instance.to_owned(), counters.to_vec());
+            Ok(())
+        }
+
+        async fn load_metrics(&self, instance: &str) -> anyhow::Result<Option<Vec<u8>>> {
+            Ok(self.0.lock().unwrap().metrics.get(instance).cloned())
+        }
+    }
+
+    fn storage(backend: &Arc<SharedBackend>) -> TheorosStorage {
+        TheorosStorage::new(FeedIdsStorage::default(), ValidatorsFetchersStorage::default())
+            .with_backend(backend.clone())
+    }
+
+    #[tokio::test]
+    async fn test_sync_follows_the_state_persisted_by_another_process() {
+        let backend = Arc::new(SharedBackend::default());
+        let indexer = storage(&backend);
+        let api = storage(&backend);
+        let mut updates_rx = api.feeds_updated_tx().subscribe();
+
+        let data = dispatch_event_data(5, 1_700_000_000);
+        let event = DispatchEvent::from_starknet_event_data(data.clone()).unwrap();
+        indexer.add_dispatch(RawDispatchEvent::new(5, Some(100), None, &[], &data), &event).await;
+        assert_eq!(api.sync_from_backend().await.unwrap(), 0);
+        assert!(api.unsigned_checkpoints().get(5).await.is_some());
+        assert!(api.raw_dispatch_events().get(5).await.is_some());
+
+        // The dispatch reaches quorum in the indexer.
+        store_updates(&indexer, &event).await.unwrap();
+        indexer.remove_pending_dispatch(5).await;
+        assert_eq!(api.sync_from_backend().await.unwrap(), 1);
+        assert!(api.unsigned_checkpoints().get(5).await.is_none());
+        let feed_id = hex_str_to_u256(&synthetic_feed_id()).unwrap();
+        assert_eq!(api.latest_update_per_feed().get(&feed_id).map(|update| update.nonce), Some(5));
+        let NewUpdatesAvailableEvent::New { nonce, feed_ids } = updates_rx.try_recv().unwrap();
+        assert_eq!((nonce, feed_ids), (5, vec![synthetic_feed_id()]));
+
+        // Nothing changed since the last sync.
+        assert_eq!(api.sync_from_backend().await.unwrap(), 0);
+        assert!(updates_rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_sync_follows_the_settings_changed_by_another_process() {
+        let backend = Arc::new(SharedBackend::default());
+        let app_state = |backend: &Arc<SharedBackend>| {
+            let rpc = crate::rpc::starknet::StarknetRpc::new(url::Url::parse("http://localhost:1").unwrap());
+            AppState::builder().with_starknet_rpc(Arc::new(rpc)).with_storage(storage(backend)).build().unwrap()
+        };
+        let (replica_a, replica_b) = (app_state(&backend), app_state(&backend));
+        let key = ConsumerPublicKey::from_hex(&"ab".repeat(32)).unwrap();
+
+        replica_a.storage.save_setting(SettingKind::ConsumerKey, "consumer", key.to_hex()).await;
+        replica_a.storage.save_setting(SettingKind::PrivateFeed, "0x01", true).await;
+        assert_eq!(replica_b.sync_settings().await.unwrap(), 2);
+        assert_eq!(replica_b.storage.consumer_keys().get("consumer"), Some(key));
+        assert!(replica_b.private_feeds.is_private("0x01"));
+
+        replica_a.storage.remove_setting(SettingKind::ConsumerKey, "consumer").await;
+        replica_a.storage.save_setting(SettingKind::PrivateFeed, "0x01", false).await;
+        let rules = vec![TracingSamplingRule { target: "theoros::handlers".into(), rate: 0.5 }];
+        replica_a.storage.save_setting(SettingKind::TracingSampling, "rules", &rules).await;
+        replica_b.sync_settings().await.unwrap();
+        assert_eq!(replica_b.storage.consumer_keys().get("consumer"), None);
+        assert!(!replica_b.private_feeds.is_private("0x01"));
+        assert_eq!(replica_b.tracing_sampler.rules(), vec![SamplingRule::new("theoros::handlers", 0.5).unwrap()]);
+    }
+
+    const FEED_A: U256 = U256::from_limbs([0xa, 0, 0, 0]);
+    const FEED_B: U256 = U256::from_limbs([0xb, 0, 0, 0]);
+    const FEED_C: U256 = U256::from_limbs([0xc, 0, 0, 0]);
+
+    /// Age of the dispatches indexed before the retention period.
+    const ONE_HOUR: i64 = 3_600;
+
+    const RETENTION: RetentionPolicy =
+        RetentionPolicy { keep_updates: Some(1), keep_for: Some(Duration::from_secs(60)) };
+
+    fn signed_checkpoint(nonce: u32) -> SignedCheckpointWithMessageId {
+        SignedCheckpointWithMessageId {
+            value: CheckpointWithMessageId {
+                checkpoint: Checkpoint {
+                    merkle_tree_hook_address: U256::ZERO,
+                    mailbox_domain: 0,
+                    root: String::new(),
+                    index: nonce,
+                },
+                message_id: U256::from(nonce),
+            },
+            signature: Signature::new(U256::from(1), U256::from(2), Parity::Parity(false)),
+        }
+    }
+
+    /// Seeds a dispatch for each case of the retention, the served emitter being the synthetic one:
+    /// - #1, indexed an hour ago, whose update of the feed A is superseded, is the only one pruned.
+    /// - #2, indexed an hour ago, is pending quorum.
+    /// - #3, indexed an hour ago, holds the latest update of the feed B by another emitter.
+    /// - #4, indexed an hour ago, holds the latest update of the feed C.
+    /// - #5, indexed within the retention period, holds an update of the feed A beyond `keep_updates`.
+    /// - #6, the latest dispatch, holds the latest update of the feed A.
+    /// - #7 isn't indexed yet, its checkpoint was prefetched.
+    ///
+    /// Every dispatch is signed by a validator.
+    async fn seed_retention_cases(storage: &TheorosStorage, now: i64) {
+        let cases = [
+            (1, Some(FEED_A), ONE_HOUR),
+            (2, None, ONE_HOUR),
+            (3, Some(FEED_B), ONE_HOUR),
+            (4, Some(FEED_C), ONE_HOUR),
+            (5, Some(FEED_A), 0),
+            (6, Some(FEED_A), 0),
+        ];
+        for (nonce, feed_id, age) in cases {
+            let mut data = dispatch_event_data(nonce, u64::try_from(now).unwrap());
+            if feed_id == Some(FEED_B) {
+                // Dispatched by another emitter than the served one.
+                data[8] = Felt::ONE;
+            }
+            let event = DispatchEvent::from_starknet_event_data(data.clone()).unwrap();
+            let raw_event = RawDispatchEvent {
+                indexed_at: DateTime::from_timestamp(now - age, 0).unwrap(),
+                ..RawDispatchEvent::new(nonce, Some(100 + u64::from(nonce)), None, &[], &data)
+            };
+            storage.add_dispatch(raw_event, &event).await;
+            storage.add_signed_checkpoint(Felt::ONE, signed_checkpoint(nonce)).await;
+            let Some(feed_id) = feed_id else { continue };
+            storage.add_update(feed_id, DispatchUpdateInfos::new(&event, &event.message.body.updates[0])).await;
+            storage.remove_pending_dispatch(nonce).await;
+        }
+        storage.add_signed_checkpoint(Felt::ONE, signed_checkpoint(7)).await;
+    }
+
+    /// Prunes the seeded retention cases, checks what is left in memory & returns what the backend pruned.
+    async fn prune_retention_cases(backend: Arc<dyn Storage>) -> PrunedCounts {
+        let storage = TheorosStorage::new(FeedIdsStorage::default(), ValidatorsFetchersStorage::default())
+            .with_backend(backend)
+            .with_emitters(Emitters::new(vec![Emitter { name: String::from("pragma"), address: Felt::ZERO }]).unwrap());
+        let now = Utc::now().timestamp();
+        seed_retention_cases(&storage, now).await;
+
+        let (pruned, backend_pruned) = storage.prune(&RETENTION, u64::try_from(now).unwrap()).await;
+        // The updates of the feed A by #1 & #5.
+        assert_eq!(pruned, PrunedCounts { updates: 2, dispatches: 1, checkpoints: 1 });
+        let indexed: Vec<u32> = storage.raw_dispatch_events().indexed_at().await.into_iter().map(|(n, _)| n).collect();
+        assert_eq!(indexed, vec![2, 3, 4, 5, 6]);
+        assert_eq!(storage.signed_checkpoints().nonces(), BTreeSet::from([2, 3, 4, 5, 6, 7]));
+        assert_eq!(storage.unsigned_checkpoints().nonces().await, vec![2]);
+        assert_eq!(storage.feed_history().nonces(), HashSet::from([4, 6]));
+        assert_eq!(storage.latest_update_per_feed().get(&FEED_A).map(|update| update.nonce), Some(6));
+        backend_pruned.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_prune_keeps_what_the_retention_policy_keeps() {
+        let pruned = prune_retention_cases(Arc::new(InMemoryStorage)).await;
+        assert_eq!(pruned, PrunedCounts::default());
+    }
+
+    #[cfg(feature = "rocksdb")]
+    #[tokio::test]
+    async fn test_prune_removes_the_same_nonces_from_rocksdb() {
+        let path = std::env::temp_dir().join(format!("theoros-rocksdb-retention-{}", std::process::id()));
+        let backend = Arc::new(backend::RocksDbStorage::open(&path).unwrap());
+        let pruned = prune_retention_cases(backend.clone()).await;
+        assert_eq!(pruned, PrunedCounts { updates: 0, dispatches: 1, checkpoints: 1 });
+
+        let state = backend.load().await.unwrap();
+        assert_eq!(state.dispatches.into_keys().collect::<Vec<_>>(), vec![2, 3, 4, 5, 6]);
+        assert_eq!(state.pending_nonces, BTreeSet::from([2]));
+        let checkpoints: BTreeSet<u32> =
+            state.signed_checkpoints.iter().map(|(_, checkpoint)| checkpoint.value.checkpoint.index).collect();
+        assert_eq!(checkpoints, BTreeSet::from([2, 3, 4, 5, 6, 7]));
+        drop(backend);
+        std::fs::remove_dir_all(path).unwrap();
+    }
+
+    /// Runs against the database of `THEOROS_TEST_DATABASE_URL`, in a schema of its own, & is skipped without it.
+    #[tokio::test]
+    async fn test_prune_removes_the_same_nonces_from_postgres() {
+        let Ok(url) = std::env::var("THEOROS_TEST_DATABASE_URL") else {
+            eprintln!("THEOROS_TEST_DATABASE_URL isn't set, skipping the Postgres retention test");
+            return;
+        };
+        let schema = format!("theoros_retention_{}", std::process::id());
+        let admin = sqlx::PgPool::connect(&url).await.unwrap();
+        sqlx::query(&format!("CREATE SCHEMA {schema}")).execute(&admin).await.unwrap();
+        let separator = if url.contains('?') { '&' } else { '?' };
+        let schema_url = format!("{url}{separator}options=-csearch_path%3D{schema}");
+        let backend = Arc::new(backend::PostgresStorage::connect(&schema_url, 2).await.unwrap());
+
+        let pruned = prune_retention_cases(backend.clone()).await;
+        let nonces = |table: &'static str| {
+            let query = format!("SELECT nonce FROM {table} ORDER BY nonce");
+            let pool = backend.pool().clone();
+            async move { sqlx::query_scalar::<_, i64>(&query).fetch_all(&pool).await.unwrap() }
+        };
+        let dispatches = nonces("theoros_dispatches").await;
+        let checkpoints = nonces("theoros_signed_checkpoints").await;
+        let updates = nonces("theoros_feed_updates").await;
+        backend.close().await.unwrap();
+        sqlx::query(&format!("DROP SCHEMA {schema} CASCADE")).execute(&admin).await.unwrap();
+
+        // Postgres only keeps the latest updates of the served emitter, which it restores, so #3 is pruned too.
+        assert_eq!(pruned, PrunedCounts { updates: 2, dispatches: 2, checkpoints: 2 });
+        assert_eq!(dispatches, vec![2, 4, 5, 6]);
+        assert_eq!(checkpoints, vec![2, 4, 5, 6, 7]);
+        assert_eq!(updates, vec![4, 6]);
+    }
 }
@@ -0,0 +1,67 @@
+use std::collections::VecDeque;
+
+use alloy::hex;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+use utoipa::ToSchema;
+
+use crate::{constants::MAX_STORED_PARSE_FAILURES, types::hyperlane::UpdateParseFailure};
+
+/// An update of an indexed dispatch that could not be parsed.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct DispatchParseFailure {
+    /// Nonce of the dispatch containing the update.
+    pub nonce: u32,
+    pub block_number: Option<u64>,
+    /// Position of the update in the dispatch.
+    pub update_index: u8,
+    /// Offset of the update in the message body, in bytes.
+    pub offset: usize,
+    /// Bytes of the update as a hex string. When the end of the update is unknown, all the remaining
+    /// bytes of the message body.
+    pub raw: String,
+    pub error: String,
+    pub happened_at: DateTime<Utc>,
+}
+
+impl DispatchParseFailure {
+    pub fn new(nonce: u32, block_number: Option<u64>, failure: &UpdateParseFailure) -> Self {
+        Self {
+            nonce,
+            block_number,
+            update_index: failure.update_index,
+            offset: failure.offset,
+            raw: hex::encode_prefixed(&failure.raw),
+            error: failure.error.clone(),
+            happened_at: Utc::now(),
+        }
+    }
+}
+
+/// Contains the most recent updates that could not be parsed.
+#[derive(Debug, Default)]
+pub struct ParseFailuresStorage(RwLock<VecDeque<DispatchParseFailure>>);
+
+impl ParseFailuresStorage {
+    pub async fn add(&self, failure: DispatchParseFailure) {
+        let mut failures = self.0.write().await;
+        failures.push_back(failure);
+        if failures.len() > MAX_STORED_PARSE_FAILURES {
+            failures.pop_front();
+        }
+    }
+
+    /// Returns the parse failures, most recent first.
+    pub async fn all(&self) -> Vec<DispatchParseFailure> {
+        self.0.read().await.iter().rev().cloned().collect()
+    }
+
+    /// Removes all the parse failures & returns how many were removed.
+    pub async fn clear(&self) -> usize {
+        let mut failures = self.0.write().await;
+        let removed = failures.len();
+        failures.clear();
+        removed
+    }
+}
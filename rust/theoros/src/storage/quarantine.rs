@@ -0,0 +1,86 @@
+use std::collections::VecDeque;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+use utoipa::ToSchema;
+
+use crate::{constants::MAX_QUARANTINED_CHECKPOINTS, types::hyperlane::SignedCheckpointWithMessageId};
+
+/// Why a fetched checkpoint was quarantined.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum QuarantineReason {
+    /// The checkpoint fetched for a nonce is for another index.
+    IndexMismatch { fetched_index: u32 },
+    /// The validator already signed a different checkpoint for this index.
+    Equivocation,
+    /// The message id of the checkpoint diverges from the one computed from the indexed dispatch.
+    RootDivergence,
+    /// The signature doesn't recover to the address of the validator.
+    InvalidSignature {
+        /// Address recovered from the signature, if it could be recovered.
+        recovered_signer: Option<String>,
+    },
+}
+
+/// A fetched checkpoint that failed validation, kept as evidence for the validator operator.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct QuarantinedCheckpoint {
+    pub validator: String,
+    pub nonce: u32,
+    pub reason: QuarantineReason,
+    /// The signed checkpoint, as JSON.
+    #[schema(value_type = Object)]
+    pub checkpoint: serde_json::Value,
+    pub quarantined_at: DateTime<Utc>,
+}
+
+/// Contains the most recent checkpoints that failed validation.
+#[derive(Debug, Default)]
+pub struct QuarantineStorage(RwLock<VecDeque<QuarantinedCheckpoint>>);
+
+impl QuarantineStorage {
+    /// Quarantines the checkpoint, unless it was already quarantined for the same reason.
+    /// Returns whether the checkpoint was newly quarantined.
+    pub async fn add(
+        &self,
+        validator: String,
+        nonce: u32,
+        reason: QuarantineReason,
+        checkpoint: &SignedCheckpointWithMessageId,
+    ) -> anyhow::Result<bool> {
+        let checkpoint = serde_json::to_value(checkpoint)?;
+        let mut quarantine = self.0.write().await;
+        let already_quarantined = quarantine
+            .iter()
+            .any(|q| q.validator == validator && q.nonce == nonce && q.reason == reason && q.checkpoint == checkpoint);
+        if already_quarantined {
+            return Ok(false);
+        }
+        quarantine.push_back(QuarantinedCheckpoint {
+            validator,
+            nonce,
+            reason,
+            checkpoint,
+            quarantined_at: Utc::now(),
+        });
+        if quarantine.len() > MAX_QUARANTINED_CHECKPOINTS {
+            quarantine.pop_front();
+        }
+        Ok(true)
+    }
+
+    /// Returns the quarantined checkpoints, most recent first.
+    pub async fn all(&self) -> Vec<QuarantinedCheckpoint> {
+        self.0.read().await.iter().rev().cloned().collect()
+    }
+
+    /// Empties the quarantine & returns the number of removed checkpoints.
+    pub async fn clear(&self) -> usize {
+        let mut quarantine = self.0.write().await;
+        let removed = quarantine.len();
+        quarantine.clear();
+        removed
+    }
+}
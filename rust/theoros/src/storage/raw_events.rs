@@ -0,0 +1,118 @@
+use std::collections::{BTreeMap, HashSet};
+
+use alloy::primitives::U256;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use starknet::core::types::Felt;
+use tokio::sync::RwLock;
+use utoipa::ToSchema;
+
+use crate::constants::MAX_STORED_RAW_DISPATCHES;
+use crate::types::hyperlane::DispatchEvent;
+
+/// The Starknet event of an indexed dispatch, as emitted, so its decoding can be reproduced.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct RawDispatchEvent {
+    pub nonce: u32,
+    pub block_number: Option<u64>,
+    /// Hash of the transaction emitting the event, as a hex string.
+    pub transaction_hash: Option<String>,
+    /// Keys of the event, as hex strings.
+    pub keys: Vec<String>,
+    /// Data of the event, as hex strings.
+    pub data: Vec<String>,
+    pub indexed_at: DateTime<Utc>,
+}
+
+impl RawDispatchEvent {
+    pub fn new(
+        nonce: u32,
+        block_number: Option<u64>,
+        transaction_hash: Option<Felt>,
+        keys: &[Felt],
+        data: &[Felt],
+    ) -> Self {
+        Self {
+            nonce,
+            block_number,
+            transaction_hash: transaction_hash.map(|hash| hash.to_hex_string()),
+            keys: keys.iter().map(Felt::to_hex_string).collect(),
+            data: data.iter().map(Felt::to_hex_string).collect(),
+            indexed_at: Utc::now(),
+        }
+    }
+
+    /// Parses the data of the event back into felts, to decode it again.
+    pub fn data_felts(&self) -> anyhow::Result<Vec<Felt>> {
+        self.data.iter().map(|felt| Felt::from_hex(felt).map_err(anyhow::Error::from)).collect()
+    }
+
+    /// Id of the dispatched message, computed locally to check the one signed by the validators.
+    pub fn message_id(&self) -> anyhow::Result<U256> {
+        Ok(U256::from_be_bytes(DispatchEvent::message_id_of(&self.data_felts()?)?.0))
+    }
+}
+
+/// Contains the raw events of the most recent dispatches, by nonce.
+#[derive(Debug, Default)]
+pub struct RawDispatchEventsStorage(RwLock<BTreeMap<u32, RawDispatchEvent>>);
+
+impl RawDispatchEventsStorage {
+    /// Stores the raw event of a dispatch, evicting the lowest nonces when full.
+    pub async fn add(&self, event: RawDispatchEvent) {
+        let mut events = self.0.write().await;
+        events.insert(event.nonce, event);
+        while events.len() > MAX_STORED_RAW_DISPATCHES {
+            events.pop_first();
+        }
+    }
+
+    pub async fn remove(&self, nonce: u32) {
+        self.0.write().await.remove(&nonce);
+    }
+
+    pub async fn get(&self, nonce: u32) -> Option<RawDispatchEvent> {
+        self.0.read().await.get(&nonce).cloned()
+    }
+
+    /// Nonce of the latest indexed dispatch, i.e. the nonce of the mailbox as far as the indexer knows.
+    pub async fn latest_nonce(&self) -> Option<u32> {
+        self.0.read().await.last_key_value().map(|(nonce, _)| *nonce)
+    }
+
+    /// When the stored dispatches were indexed, by nonce.
+    pub async fn indexed_at(&self) -> Vec<(u32, DateTime<Utc>)> {
+        self.0.read().await.values().map(|event| (event.nonce, event.indexed_at)).collect()
+    }
+
+    /// Removes the raw events of these nonces & returns how many were removed.
+    pub async fn remove_nonces(&self, nonces: &HashSet<u32>) -> u64 {
+        let mut events = self.0.write().await;
+        let before = events.len();
+        events.retain(|nonce, _| !nonces.contains(nonce));
+        (before - events.len()) as u64
+    }
+
+    pub async fn num_events(&self) -> usize {
+        self.0.read().await.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_raw_events_roundtrip_and_eviction() {
+        let storage = RawDispatchEventsStorage::default();
+        let data = [Felt::from(1_u8), Felt::from_hex("0xdeadbeef").unwrap()];
+        for nonce in 0..=MAX_STORED_RAW_DISPATCHES as u32 {
+            storage.add(RawDispatchEvent::new(nonce, Some(12), Some(Felt::from(42_u8)), &[], &data)).await;
+        }
+
+        assert!(storage.get(0).await.is_none());
+        let event = storage.get(1).await.unwrap();
+        assert_eq!(event.transaction_hash.as_deref(), Some("0x2a"));
+        assert_eq!(event.data_felts().unwrap(), data);
+    }
+}
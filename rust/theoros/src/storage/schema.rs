@@ -0,0 +1,113 @@
+use anyhow::{bail, Context, Result};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+
+/// Field of the persisted states holding the version of their schema.
+pub const SCHEMA_VERSION_FIELD: &str = "schema_version";
+
+/// A state persisted across restarts, whose schema is versioned.
+///
+/// States written by an older version of Theoros are migrated step by step up to the
+/// current schema. States written by a newer version are refused, so rolling back a
+/// deploy never reads - and then overwrites - a state it doesn't understand.
+pub trait VersionedState: Serialize + DeserializeOwned {
+    /// Name of the state, used in the errors.
+    const NAME: &'static str;
+    /// Version of the schema written by this version of Theoros.
+    const SCHEMA_VERSION: u32;
+
+    /// Migrates the state from the schema `version` to `version + 1`.
+    /// States persisted before the schema was versioned are of version 0.
+    fn migrate(version: u32, state: Value) -> Result<Value>;
+}
+
+/// Serializes the state, tagged with its current schema version.
+pub fn encode_versioned<T: VersionedState>(state: &T) -> Result<Vec<u8>> {
+    let mut value = serde_json::to_value(state)?;
+    let Value::Object(fields) = &mut value else {
+        bail!("The {} state must be serialized as an object to be versioned", T::NAME);
+    };
+    fields.insert(SCHEMA_VERSION_FIELD.to_owned(), Value::from(T::SCHEMA_VERSION));
+    Ok(serde_json::to_vec(&value)?)
+}
+
+/// Deserializes the state, migrating it to the current schema version if needed.
+pub fn decode_versioned<T: VersionedState>(bytes: &[u8]) -> Result<T> {
+    let mut value: Value = serde_json::from_slice(bytes).with_context(|| format!("Parsing the {} state", T::NAME))?;
+    let Value::Object(fields) = &mut value else {
+        bail!("The {} state is not an object", T::NAME);
+    };
+    let version = match fields.remove(SCHEMA_VERSION_FIELD) {
+        None => 0,
+        Some(version) => version
+            .as_u64()
+            .and_then(|version| u32::try_from(version).ok())
+            .with_context(|| format!("Invalid schema version of the {} state: {}", T::NAME, version))?,
+    };
+    if version > T::SCHEMA_VERSION {
+        bail!(
+            "The {} state has schema version {}, newer than the supported version {}. \
+             It was written by a newer version of Theoros.",
+            T::NAME,
+            version,
+            T::SCHEMA_VERSION
+        );
+    }
+
+    for from in version..T::SCHEMA_VERSION {
+        value = T::migrate(from, value)
+            .with_context(|| format!("Migrating the {} state from schema version {}", T::NAME, from))?;
+        tracing::info!("🧩 Migrated the {} state from schema version {} to {}", T::NAME, from, from + 1);
+    }
+    serde_json::from_value(value).with_context(|| format!("Parsing the {} state", T::NAME))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+    use serde_json::json;
+
+    use super::*;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct State {
+        served: u64,
+    }
+
+    impl VersionedState for State {
+        const NAME: &'static str = "test";
+        const SCHEMA_VERSION: u32 = 2;
+
+        fn migrate(version: u32, mut state: Value) -> Result<Value> {
+            match version {
+                // v0 -> v1: `count` renamed to `served`
+                0 => {
+                    let count = state["count"].take();
+                    Ok(json!({ "served": count }))
+                }
+                // v1 -> v2: no change to the fields
+                1 => Ok(state),
+                _ => bail!("Unknown version {version}"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_versioned_state_roundtrip() {
+        let encoded = encode_versioned(&State { served: 3 }).unwrap();
+        assert_eq!(serde_json::from_slice::<Value>(&encoded).unwrap(), json!({ "served": 3, "schema_version": 2 }));
+        assert_eq!(decode_versioned::<State>(&encoded).unwrap(), State { served: 3 });
+    }
+
+    #[test]
+    fn test_older_states_are_migrated() {
+        assert_eq!(decode_versioned::<State>(br#"{"count": 5}"#).unwrap(), State { served: 5 });
+        assert_eq!(decode_versioned::<State>(br#"{"served": 6, "schema_version": 1}"#).unwrap(), State { served: 6 });
+    }
+
+    #[test]
+    fn test_newer_states_are_refused() {
+        let err = decode_versioned::<State>(br#"{"served": 6, "schema_version": 3}"#).unwrap_err();
+        assert!(err.to_string().contains("newer than the supported version"));
+    }
+}
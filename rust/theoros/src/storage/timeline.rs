@@ -0,0 +1,72 @@
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+use alloy::primitives::U256;
+use dashmap::DashMap;
+
+use pragma_utils::conversions::alloy::hex_str_to_u256;
+
+use crate::{
+    configs::evm_config::EvmChainName,
+    constants::MAX_TIMELINE_EVENTS_PER_FEED,
+    types::timeline::{FeedTimelineEvent, FeedTimelineEventKind},
+};
+
+/// Contains the most recent lifecycle events of each feed, in chronological order.
+#[derive(Debug, Default)]
+pub struct FeedTimelinesStorage(Arc<DashMap<U256, VecDeque<FeedTimelineEvent>>>);
+
+impl FeedTimelinesStorage {
+    /// Records an event for the feed. Invalid feed ids are ignored.
+    pub fn record(&self, feed_id: &str, kind: FeedTimelineEventKind) {
+        let Ok(feed_id) = hex_str_to_u256(feed_id) else {
+            return;
+        };
+        let mut timeline = self.0.entry(feed_id).or_default();
+        timeline.push_back(FeedTimelineEvent::now(kind));
+        if timeline.len() > MAX_TIMELINE_EVENTS_PER_FEED {
+            timeline.pop_front();
+        }
+    }
+
+    /// Records that the calldata of an update was served, only the first time for each chain.
+    pub fn record_calldata_served(&self, feed_id: &str, nonce: u32, chain: EvmChainName) {
+        let kind = FeedTimelineEventKind::CalldataFirstServed { nonce, chain };
+        let already_served = hex_str_to_u256(feed_id)
+            .ok()
+            .and_then(|feed_id| self.0.get(&feed_id).map(|timeline| timeline.iter().any(|e| e.kind == kind)))
+            .unwrap_or(false);
+        if !already_served {
+            self.record(feed_id, kind);
+        }
+    }
+
+    /// Returns the events of the feed, in chronological order.
+    pub fn get(&self, feed_id: &U256) -> Vec<FeedTimelineEvent> {
+        self.0.get(feed_id).map(|timeline| timeline.iter().cloned().collect()).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_calldata_served_is_recorded_once_per_chain() {
+        let storage = FeedTimelinesStorage::default();
+        storage.record("0x1", FeedTimelineEventKind::UpdateStored { nonce: 1 });
+        storage.record_calldata_served("0x1", 1, EvmChainName::Mainnet);
+        storage.record_calldata_served("0x01", 1, EvmChainName::Mainnet);
+        storage.record_calldata_served("0x1", 1, EvmChainName::Base);
+
+        let kinds: Vec<_> = storage.get(&U256::from(1)).into_iter().map(|e| e.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                FeedTimelineEventKind::UpdateStored { nonce: 1 },
+                FeedTimelineEventKind::CalldataFirstServed { nonce: 1, chain: EvmChainName::Mainnet },
+                FeedTimelineEventKind::CalldataFirstServed { nonce: 1, chain: EvmChainName::Base },
+            ]
+        );
+    }
+}
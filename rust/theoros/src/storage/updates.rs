@@ -10,13 +10,54 @@ use crate::types::hyperlane::DispatchUpdateInfos;
 pub struct LatestUpdatePerFeedStorage(Arc<DashMap<U256, DispatchUpdateInfos>>);
 
 impl LatestUpdatePerFeedStorage {
-    /// Insert the latest [`DispatchUpdateInfos`] for a feed id.
-    pub fn add(&self, feed_id: U256, event: DispatchUpdateInfos) {
-        self.0.insert(feed_id, event);
+    /// Insert the latest [`DispatchUpdateInfos`] for a feed id & returns whether it is now the latest one.
+    /// Updates from an older dispatch, e.g. signed late or replayed by a backfill, are ignored.
+    pub fn add(&self, feed_id: U256, event: DispatchUpdateInfos) -> bool {
+        let mut latest = self.0.entry(feed_id).or_insert_with(|| event.clone());
+        if event.nonce < latest.nonce {
+            return false;
+        }
+        *latest = event;
+        true
     }
 
     /// Retrieves the latest [`DispatchUpdateInfos`] for a feed id.
     pub fn get(&self, feed_id: &U256) -> Option<DispatchUpdateInfos> {
         self.0.get(feed_id).map(|r| r.value().clone())
     }
+
+    /// Replaces the updates coming from a dispatch by the previous update of their feed, if any, or removes them.
+    /// Returns the feeds rolled back, with the nonce of their new latest update.
+    pub fn roll_back(
+        &self,
+        nonce: u32,
+        previous: impl Fn(&U256) -> Option<DispatchUpdateInfos>,
+    ) -> Vec<(U256, Option<u32>)> {
+        let feed_ids: Vec<U256> =
+            self.0.iter().filter(|entry| entry.nonce == nonce).map(|entry| *entry.key()).collect();
+        let mut rolled_back = Vec::with_capacity(feed_ids.len());
+        for feed_id in feed_ids {
+            match previous(&feed_id) {
+                Some(update) => {
+                    rolled_back.push((feed_id, Some(update.nonce)));
+                    self.0.insert(feed_id, update);
+                }
+                None => {
+                    rolled_back.push((feed_id, None));
+                    self.0.remove(&feed_id);
+                }
+            }
+        }
+        rolled_back
+    }
+
+    /// Nonce of the dispatch each feed was last updated by.
+    pub fn latest_nonces(&self) -> Vec<(U256, u32)> {
+        self.0.iter().map(|entry| (*entry.key(), entry.nonce)).collect()
+    }
+
+    /// Number of feeds with an update.
+    pub fn num_feeds(&self) -> usize {
+        self.0.len()
+    }
 }
@@ -5,14 +5,43 @@ use anyhow::bail;
 use dashmap::DashMap;
 use starknet::core::types::Felt;
 
-use crate::types::hyperlane::{CheckpointStorage, FetchFromStorage, ValidatorAnnouncementEvent};
+use crate::constants::STORAGE_BACKEND_FETCH_TIMEOUT;
+use crate::types::hyperlane::{
+    caching::{CachingFetcher, CheckpointCache},
+    multi::MultiStorageFetcher,
+    retrying::{RetryPolicy, RetryingFetcher},
+    CheckpointStorage, FetchFromStorage, ValidatorAnnouncementEvent,
+};
 
 /// Mapping between the validators and their fetcher used to
 /// retrieve signed checkpoints.
+/// Each validator can announce several storage locations, tried from the latest announced.
 #[derive(Debug, Default)]
-pub struct ValidatorsFetchersStorage(Arc<DashMap<Felt, Arc<dyn FetchFromStorage + Send + Sync>>>);
+pub struct ValidatorsFetchersStorage {
+    fetchers: Arc<DashMap<Felt, Arc<MultiStorageFetcher>>>,
+    /// Retries of the fetches from the announced storage locations.
+    retry_policy: RetryPolicy,
+    /// Checkpoints already fetched, shared by all the validators. Disabled when `None`.
+    checkpoint_cache: Option<Arc<CheckpointCache>>,
+}
 
 impl ValidatorsFetchersStorage {
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Serves the checkpoints already fetched from the cache, instead of fetching them again.
+    pub fn with_checkpoint_cache(mut self, checkpoint_cache: Option<Arc<CheckpointCache>>) -> Self {
+        self.checkpoint_cache = checkpoint_cache;
+        self
+    }
+
+    /// The cache of the fetched checkpoints, if enabled.
+    pub fn checkpoint_cache(&self) -> Option<&Arc<CheckpointCache>> {
+        self.checkpoint_cache.as_ref()
+    }
+
     /// Fills the [DashMap] with the initial state fetched from the RPC.
     pub async fn fill_with_initial_state(
         &mut self,
@@ -23,32 +52,78 @@ impl ValidatorsFetchersStorage {
             bail!("⛔ Validators and locations vectors must have the same length");
         }
 
-        for (validator, location) in validators.into_iter().zip(locations.into_iter()) {
-            // TODO: This should be a feature. We sometime want to have a local storage.
-            if location[location.len() - 1].starts_with("file") {
+        for (validator, locations) in validators.into_iter().zip(locations.into_iter()) {
+            self.add_announced_locations(validator, &locations).await;
+        }
+
+        Ok(())
+    }
+
+    /// Registers the storage locations announced by the validator, tried from the latest announced. Returns
+    /// `false` if none of them could be built.
+    pub async fn add_announced_locations(&self, validator: Felt, locations: &[String]) -> bool {
+        let mut fetchers = Vec::with_capacity(locations.len());
+        for location in locations.iter().rev() {
+            if !Self::is_supported_location(location) {
                 continue;
             }
-            let storage = CheckpointStorage::from_str(&location[location.len() - 1])?;
-            let storage_fetcher = storage.build().await?;
-            self.0.insert(validator, storage_fetcher);
+            match self.build(location).await {
+                Ok(fetcher) => fetchers.push(fetcher),
+                Err(e) => {
+                    tracing::warn!("⚠️ Skipping storage location {} of validator {:#x}: {:?}", location, validator, e)
+                }
+            }
+        }
+        if fetchers.is_empty() {
+            return false;
         }
+        self.fetchers.insert(validator, Arc::new(MultiStorageFetcher::new(fetchers, STORAGE_BACKEND_FETCH_TIMEOUT)));
+        true
+    }
 
-        Ok(())
+    /// Local storages are only read when built with the `local-checkpoint-storage` feature.
+    fn is_supported_location(location: &str) -> bool {
+        cfg!(feature = "local-checkpoint-storage") || !location.starts_with("file")
+    }
+
+    pub fn contains(&self, validator: &Felt) -> bool {
+        self.fetchers.contains_key(validator)
     }
 
-    /// Adds or updates the [CheckpointStorage] for the given validator
+    async fn build(&self, location: &str) -> anyhow::Result<Arc<dyn FetchFromStorage + Send + Sync>> {
+        self.build_storage(&CheckpointStorage::from_str(location)?).await
+    }
+
+    /// Builds the fetcher of the storage, retrying its failed fetches.
+    async fn build_storage(
+        &self,
+        storage: &CheckpointStorage,
+    ) -> anyhow::Result<Arc<dyn FetchFromStorage + Send + Sync>> {
+        Ok(Arc::new(RetryingFetcher::new(storage.build().await?, self.retry_policy)))
+    }
+
+    /// Adds the [CheckpointStorage] for the given validator, tried before its previously known locations.
     pub async fn build_and_add(&self, validator: Felt, storage: CheckpointStorage) -> anyhow::Result<()> {
-        let storage_fetcher = storage.build().await?;
-        self.0.insert(validator, storage_fetcher);
+        let storage_fetcher = self.build_storage(&storage).await?;
+        let fetcher = match self.fetchers.get(&validator) {
+            Some(existing) => existing.with_first(storage_fetcher),
+            None => MultiStorageFetcher::new(vec![storage_fetcher], STORAGE_BACKEND_FETCH_TIMEOUT),
+        };
+        self.fetchers.insert(validator, Arc::new(fetcher));
         Ok(())
     }
 
+    /// Registers an already built fetcher for the given validator, replacing its known locations.
+    pub fn add(&self, validator: Felt, fetcher: Arc<dyn FetchFromStorage + Send + Sync>) {
+        self.fetchers
+            .insert(validator, Arc::new(MultiStorageFetcher::new(vec![fetcher], STORAGE_BACKEND_FETCH_TIMEOUT)));
+    }
+
     /// Adds or updates the [CheckpointStorage] for the given validator from a [ValidatorAnnouncementEvent]
-    /// NOTE: This won't work with local storage.
-    /// TODO: This should be a feature. We sometime want to have a local storage.
+    /// NOTE: Local storages are ignored unless built with the `local-checkpoint-storage` feature.
     pub async fn add_from_announcement_event(&self, event: ValidatorAnnouncementEvent) -> anyhow::Result<()> {
         let validator: Felt = event.validator.into();
-        if event.storage_location.starts_with("file") {
+        if !Self::is_supported_location(&event.storage_location) {
             return Ok(());
         }
         let storage = CheckpointStorage::from_str(&event.storage_location)?;
@@ -56,8 +131,19 @@ impl ValidatorsFetchersStorage {
         Ok(())
     }
 
-    /// Returns all registered mappings between validators & their location storage.
+    /// Returns all registered mappings between validators & their location storage, reading the checkpoint
+    /// cache first if any.
     pub fn all(&self) -> HashMap<Felt, Arc<dyn FetchFromStorage + Send + Sync>> {
-        self.0.iter().map(|entry| (*entry.key(), entry.value().clone())).collect()
+        self.fetchers
+            .iter()
+            .map(|entry| {
+                let fetcher = entry.value().clone() as Arc<dyn FetchFromStorage + Send + Sync>;
+                let fetcher = match &self.checkpoint_cache {
+                    Some(cache) => Arc::new(CachingFetcher::new(*entry.key(), fetcher, cache.clone())),
+                    None => fetcher,
+                };
+                (*entry.key(), fetcher)
+            })
+            .collect()
     }
 }
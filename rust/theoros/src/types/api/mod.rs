@@ -0,0 +1,7 @@
+//! Payloads of the public API, one module per version of the API.
+//!
+//! They are what the clients parse, e.g. the TypeScript SDK or `theoros-client`, so their JSON shape is locked by
+//! the fixtures of `fixtures/api`, against which each payload is round-tripped. A refactoring of the internal
+//! types they embed failing those tests is a breaking change of the API: the payload must then be kept as is &
+//! the new shape served under a new version, see [ApiVersion](crate::services::api::versioning::ApiVersion).
+pub mod v1;
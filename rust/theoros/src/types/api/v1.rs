@@ -0,0 +1,318 @@
+//! Payloads of the `/v1` API.
+
+use serde::{Deserialize, Serialize};
+use utoipa::{ToResponse, ToSchema};
+
+use pragma_feeds::Feed;
+
+use crate::{
+    configs::{evm_config::EvmChainName, feed_lifecycle::FeedLifecycle},
+    types::{build_info::EncoderVersion, staleness::FeedFreshness, update_view::UpdateView},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToResponse, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct CalldataResponse {
+    pub feed_id: String,
+    /// Deterministic id of the calldata (keccak256 of its cleartext bytes).
+    /// Can be used to retrieve it again through `/v1/calldata/by-id/{calldata_id}`.
+    pub calldata_id: String,
+    /// The calldata represented as a hex string, encrypted when a consumer was provided.
+    pub encoded_calldata: String,
+    /// Identifier of the consumer key used to encrypt the calldata, if encrypted.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key_id: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToResponse, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct ChainCalldataResponse {
+    #[serde(flatten)]
+    pub calldata: CalldataResponse,
+    pub chain: EvmChainName,
+    /// Nonce of the Dispatch message of the update.
+    pub nonce: u32,
+    /// Number of validators signatures included in the calldata.
+    pub num_signatures: u8,
+    /// Pragma contract to send the update to, if configured for the chain.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contract_address: Option<String>,
+    /// ABI-encoded `updateDataFeeds([calldata])` call of the Pragma contract, as a hex string.
+    /// Only served for cleartext calldata.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transaction_data: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToResponse, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct GetCalldataByIdResponse {
+    /// The chain the calldata was built for.
+    pub chain: EvmChainName,
+    /// Version of the encoder that produced the calldata layout.
+    pub encoder: EncoderVersion,
+    /// Commit of the Theoros build that encoded the calldata.
+    pub git_commit: String,
+    #[serde(flatten)]
+    pub calldata: CalldataResponse,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct DataFeed {
+    #[serde(flatten)]
+    pub feed: Feed,
+    /// Current stage of the feed in its lifecycle, & the feed to migrate to when deprecated.
+    pub lifecycle: FeedLifecycle,
+    #[serde(flatten)]
+    pub freshness: FeedFreshness,
+    /// Names of the configured emitters which dispatched an update of the feed.
+    pub emitters: Vec<String>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize, ToResponse, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct GetDataFeedsResponse {
+    /// A page of the feeds matching the filters, sorted by feed ID.
+    pub data_feeds: Vec<DataFeed>,
+    /// Number of feeds matching the filters, over all the pages.
+    pub total: usize,
+    /// Cursor of the next page, absent on the last one.
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct LatestFeedUpdate {
+    /// Nonce of the Dispatch message containing the update.
+    pub nonce: u32,
+    pub emitter_chain_id: u32,
+    pub emitter_address: String,
+    /// Name of the Pragma dispatcher of the update, when the emitters are configured.
+    pub emitter: Option<String>,
+    pub update: UpdateView,
+    /// Signing status of the update, for each served chain.
+    pub checkpoints: Vec<ChainCheckpointStatus>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct ChainCheckpointStatus {
+    pub chain: EvmChainName,
+    /// Number of validators of the chain that signed the checkpoint of the update.
+    pub signed: usize,
+    pub validators: usize,
+    /// Whether all the validators signed, i.e. the calldata of the update can be served for the chain.
+    pub fully_signed: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToResponse, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct GetDataFeedResponse {
+    #[serde(flatten)]
+    pub feed: Feed,
+    /// Current stage of the feed in its lifecycle, & the feed to migrate to when deprecated.
+    pub lifecycle: FeedLifecycle,
+    #[serde(flatten)]
+    pub freshness: FeedFreshness,
+    /// The latest update of the feed, absent until one is indexed.
+    pub latest_update: Option<LatestFeedUpdate>,
+}
+
+/// A chain served by Theoros.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct ChainInfo {
+    pub name: EvmChainName,
+    /// EIP-155 id of the chain.
+    pub chain_id: u64,
+    /// Address of the Hyperlane contract verifying the calldata on the chain, its ISM.
+    pub ism_address: String,
+    /// Number of validators of the ISM.
+    pub validators: usize,
+    /// Number of signatures required by the ISM.
+    pub threshold: usize,
+    /// Highest nonce whose calldata was served for the chain since Theoros started.
+    pub last_served_nonce: Option<u32>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize, ToResponse, ToSchema)]
+pub struct GetChainsResponse(pub Vec<ChainInfo>);
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct RpcDataFeed {
+    pub feed_id: String,
+    /// Deterministic id of the calldata, see `/v1/calldata/by-id/{calldata_id}`.
+    pub calldata_id: String,
+    /// The calldata binary represented as a hex string.
+    pub encoded_calldata: String,
+    /// The update contained in the calldata.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub update: Option<UpdateView>,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt::Debug;
+
+    use chrono::{TimeZone, Utc};
+    use serde::de::DeserializeOwned;
+    use serde_json::Value;
+
+    use crate::configs::feed_lifecycle::FeedLifecycleState;
+    use crate::types::staleness::FeedStatus;
+
+    use super::*;
+
+    const FEED_ID: &str = "0x4254432f555344";
+    const CALLDATA_ID: &str = "0x5c0b7cbf5e4a7ff8e9e2f1a6c9d83d4e0b3e9f2d8a1c6b7e4f0d2a9c8b7e6f5a";
+    /// Largest integer a TypeScript `number` holds exactly.
+    const MAX_SAFE_INTEGER: u64 = (1 << 53) - 1;
+
+    /// Round-trips the payload through its fixture: it must serialize into the fixture & be parsed back from it.
+    fn assert_locked<T>(payload: &T, fixture: &str)
+    where
+        T: Serialize + DeserializeOwned + PartialEq + Debug,
+    {
+        let fixture: Value = serde_json::from_str(fixture).unwrap();
+        assert_eq!(serde_json::to_value(payload).unwrap(), fixture, "The payload no longer matches its fixture");
+        assert_eq!(&serde_json::from_value::<T>(fixture.clone()).unwrap(), payload);
+        assert_numbers_fit_in_typescript(&fixture);
+    }
+
+    fn assert_numbers_fit_in_typescript(value: &Value) {
+        match value {
+            Value::Number(number) => {
+                assert!(number.as_u64().is_some_and(|n| n <= MAX_SAFE_INTEGER), "{number} doesn't fit in a number")
+            }
+            Value::Array(values) => values.iter().for_each(assert_numbers_fit_in_typescript),
+            Value::Object(fields) => fields.values().for_each(assert_numbers_fit_in_typescript),
+            Value::Null | Value::Bool(_) | Value::String(_) => {}
+        }
+    }
+
+    fn calldata(key_id: Option<&str>) -> CalldataResponse {
+        CalldataResponse {
+            feed_id: FEED_ID.to_owned(),
+            calldata_id: CALLDATA_ID.to_owned(),
+            encoded_calldata: String::from("01000000"),
+            key_id: key_id.map(str::to_owned),
+        }
+    }
+
+    fn lifecycle() -> FeedLifecycle {
+        FeedLifecycle {
+            state: FeedLifecycleState::Deprecated,
+            deprecated_at: Some(Utc.with_ymd_and_hms(2024, 6, 1, 0, 0, 0).unwrap()),
+            retired_at: None,
+            replaced_by: Some(String::from("0x4254432f55534443")),
+        }
+    }
+
+    fn freshness() -> FeedFreshness {
+        FeedFreshness { status: FeedStatus::Fresh, last_update_timestamp: Some(1_717_200_000) }
+    }
+
+    fn spot_median() -> UpdateView {
+        UpdateView::SpotMedian {
+            timestamp: 1_717_200_000,
+            num_sources_aggregated: 5,
+            decimals: 8,
+            price: String::from("6500000000000"),
+            volume: String::from("0"),
+        }
+    }
+
+    #[test]
+    fn test_calldata_payloads_are_locked() {
+        assert_locked(&calldata(Some("consumer-1")), include_str!("../../../fixtures/api/v1/calldata.json"));
+        assert_locked(
+            &ChainCalldataResponse {
+                calldata: calldata(None),
+                chain: EvmChainName::Sepolia,
+                nonce: 42,
+                num_signatures: 2,
+                contract_address: Some(String::from("0x36df4070e048a752c5abd7efd22178ce8ef92535")),
+                transaction_data: Some(String::from("0x5c4d53b2")),
+            },
+            include_str!("../../../fixtures/api/v1/chain_calldata.json"),
+        );
+        assert_locked(
+            &GetCalldataByIdResponse {
+                chain: EvmChainName::Sepolia,
+                encoder: EncoderVersion {
+                    hyperlane_version: 3,
+                    pragma_major_version: 1,
+                    pragma_minor_version: 0,
+                    trailing_header_size: 0,
+                },
+                git_commit: String::from("31cefe6"),
+                calldata: calldata(None),
+            },
+            include_str!("../../../fixtures/api/v1/calldata_by_id.json"),
+        );
+    }
+
+    #[test]
+    fn test_data_feed_payloads_are_locked() {
+        let feed: Feed = FEED_ID.parse().unwrap();
+        assert_locked(
+            &GetDataFeedsResponse {
+                data_feeds: vec![DataFeed {
+                    feed: feed.clone(),
+                    lifecycle: lifecycle(),
+                    freshness: freshness(),
+                    emitters: vec![String::from("mainnet")],
+                }],
+                total: 1,
+                next_cursor: None,
+            },
+            include_str!("../../../fixtures/api/v1/data_feeds.json"),
+        );
+        assert_locked(
+            &GetDataFeedResponse {
+                feed,
+                lifecycle: FeedLifecycle::default(),
+                freshness: freshness(),
+                latest_update: Some(LatestFeedUpdate {
+                    nonce: 42,
+                    emitter_chain_id: 6_363_709,
+                    emitter_address: String::from("0x2a85a4a1b7c4c1a6e5fa3e5b8c0e8d1a4f5c2b3e9d0a6f7c8b1e4d3a2f5c6b7"),
+                    emitter: Some(String::from("mainnet")),
+                    update: spot_median(),
+                    checkpoints: vec![ChainCheckpointStatus {
+                        chain: EvmChainName::Sepolia,
+                        signed: 2,
+                        validators: 3,
+                        fully_signed: false,
+                    }],
+                }),
+            },
+            include_str!("../../../fixtures/api/v1/data_feed.json"),
+        );
+    }
+
+    #[test]
+    fn test_chains_payloads_are_locked() {
+        assert_locked(
+            &GetChainsResponse(vec![ChainInfo {
+                name: EvmChainName::Sepolia,
+                chain_id: 11_155_111,
+                ism_address: String::from("0x8e8b2b5e3e5c0f2a1d6b4a9c7e3f1d0b2a4c6e8f"),
+                validators: 3,
+                threshold: 2,
+                last_served_nonce: Some(42),
+            }]),
+            include_str!("../../../fixtures/api/v1/chains.json"),
+        );
+        assert_locked(
+            &RpcDataFeed {
+                feed_id: FEED_ID.to_owned(),
+                calldata_id: CALLDATA_ID.to_owned(),
+                encoded_calldata: String::from("01000000"),
+                update: Some(spot_median()),
+            },
+            include_str!("../../../fixtures/api/v1/rpc_data_feed.json"),
+        );
+    }
+}
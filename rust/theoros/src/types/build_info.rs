@@ -0,0 +1,52 @@
+use serde::{Deserialize, Serialize};
+use utoipa::{ToResponse, ToSchema};
+
+use crate::constants::{HYPERLANE_VERSION, PRAGMA_MAJOR_VERSION, PRAGMA_MINOR_VERSION, TRAILING_HEADER_SIZE};
+
+/// Commit Theoros was built from, see `build.rs`.
+pub const GIT_COMMIT: &str = env!("THEOROS_GIT_COMMIT");
+
+/// Versions defining the byte layout of the encoded calldata.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct EncoderVersion {
+    pub hyperlane_version: u8,
+    pub pragma_major_version: u8,
+    pub pragma_minor_version: u8,
+    pub trailing_header_size: u8,
+}
+
+impl EncoderVersion {
+    pub const CURRENT: Self = Self {
+        hyperlane_version: HYPERLANE_VERSION,
+        pragma_major_version: PRAGMA_MAJOR_VERSION,
+        pragma_minor_version: PRAGMA_MINOR_VERSION,
+        trailing_header_size: TRAILING_HEADER_SIZE,
+    };
+}
+
+/// Identifies the running Theoros build.
+#[derive(Debug, Clone, Serialize, Deserialize, ToResponse, ToSchema)]
+pub struct BuildInfo {
+    /// Version of the Theoros crate.
+    pub version: String,
+    pub git_commit: String,
+    /// Optional cargo features Theoros was built with.
+    pub features: Vec<String>,
+    pub encoder: EncoderVersion,
+}
+
+impl BuildInfo {
+    pub fn current() -> Self {
+        let features = [("jemalloc", cfg!(feature = "jemalloc")), ("mimalloc", cfg!(feature = "mimalloc"))]
+            .into_iter()
+            .filter(|(_, enabled)| *enabled)
+            .map(|(feature, _)| feature.to_owned())
+            .collect();
+        Self {
+            version: env!("CARGO_PKG_VERSION").to_owned(),
+            git_commit: GIT_COMMIT.to_owned(),
+            features,
+            encoder: EncoderVersion::CURRENT,
+        }
+    }
+}
@@ -1,15 +1,20 @@
 use std::str::FromStr;
+use std::time::Instant;
 
 use alloy::{primitives::U256, signers::Signature};
 use anyhow::Context;
 use pragma_utils::conversions::alloy::hex_str_to_u256;
 use serde::{Deserialize, Serialize};
 use starknet::core::types::Felt;
+use utoipa::ToSchema;
 
 use crate::{
     configs::evm_config::EvmChainName,
     constants::{HYPERLANE_VERSION, PRAGMA_MAJOR_VERSION, PRAGMA_MINOR_VERSION, TRAILING_HEADER_SIZE},
+    services::HyperlaneService,
     types::hyperlane::{CheckpointWithMessageId, DispatchUpdate},
+    types::post_processors::PostProcessingContext,
+    types::quorum::agreeing_checkpoints,
     types::state::AppState,
 };
 
@@ -17,6 +22,30 @@ pub trait AsCalldata {
     fn as_bytes(&self) -> Vec<u8>;
 }
 
+/// Order in which the calldata of multiple feeds are returned.
+///
+/// Whatever the ordering, it is deterministic: the same request always yields the
+/// calldata in the same order, and any payload packing multiple updates must follow it.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CalldataOrdering {
+    /// Same order as the requested feed ids
+    #[default]
+    Requested,
+    /// Ascending order of the feed ids numerical values
+    FeedId,
+}
+
+impl CalldataOrdering {
+    /// Sorts the feed ids according to the ordering. The sort is stable.
+    pub fn apply(&self, feed_ids: &mut [String]) {
+        match self {
+            CalldataOrdering::Requested => {}
+            CalldataOrdering::FeedId => feed_ids.sort_by_cached_key(|feed_id| hex_str_to_u256(feed_id).ok()),
+        }
+    }
+}
+
 #[derive(Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
 pub struct Calldata {
     /// Major version of Pragma (should only be updated if there are breaking changes)
@@ -31,8 +60,29 @@ pub struct Calldata {
     pub hyperlane_msg: HyperlaneMessage,
 }
 
+/// Returned when the signatures collected before the deadline of the calldata request don't reach the threshold
+/// of the chain.
+#[derive(Debug, thiserror::Error)]
+#[error(
+    "Partial quorum for nonce #{nonce}: {signed}/{required} validators signed, timed out: [{}]",
+    .timed_out.join(", ")
+)]
+pub struct PartialQuorumError {
+    pub nonce: u32,
+    pub signed: usize,
+    pub required: usize,
+    pub timed_out: Vec<String>,
+}
+
 impl Calldata {
-    pub async fn build_from(state: &AppState, chain_name: EvmChainName, feed_id: String) -> anyhow::Result<Calldata> {
+    /// Builds the calldata of the latest update of the feed. While the signatures don't reach the threshold of
+    /// the chain, the checkpoints of the validators that didn't sign it yet are fetched until the `deadline`.
+    pub async fn build_from(
+        state: &AppState,
+        chain_name: EvmChainName,
+        feed_id: String,
+        deadline: Instant,
+    ) -> anyhow::Result<Calldata> {
         let feed_id = hex_str_to_u256(&feed_id)?;
         let update_info = state.storage.latest_update_per_feed().get(&feed_id).context("No update found")?;
 
@@ -40,17 +90,40 @@ impl Calldata {
             state.hyperlane_validators_mapping.get_validators(&chain_name).context("No validators found")?;
 
         let validators: Vec<Felt> = validator_index_map.keys().copied().collect();
-        let checkpoints = state.storage.signed_checkpoints().get(&validators, update_info.nonce);
-        anyhow::ensure!(!checkpoints.is_empty(), "No signatures found");
+        if !state.quorum_tracker.is_reached(chain_name, update_info.nonce) {
+            let missing: Vec<Felt> = validators
+                .iter()
+                .filter(|validator| {
+                    !state.storage.signed_checkpoints().validator_signed_nonce(**validator, update_info.nonce)
+                })
+                .copied()
+                .collect();
+            let hyperlane = HyperlaneService::new(state.storage.clone(), state.metrics.clone())
+                .with_fetch_timeout(state.validator_fetch_timeout);
+            let timed_out = hyperlane
+                .fetch_missing_checkpoints(&missing, update_info.nonce, deadline, || {
+                    state.quorum_tracker.is_reached(chain_name, update_info.nonce)
+                })
+                .await;
+            let status = state.quorum_tracker.status(chain_name, update_info.nonce).context("No validators found")?;
+            if !status.reached {
+                return Err(PartialQuorumError {
+                    nonce: update_info.nonce,
+                    signed: status.signers.len(),
+                    required: status.threshold,
+                    timed_out: timed_out.iter().map(|validator| format!("{:#x}", validator)).collect(),
+                }
+                .into());
+            }
+        }
 
-        // Ensure all nonce have the same checkpoint
+        // Only the signatures of the checkpoint value counted towards the quorum can be aggregated.
+        let checkpoints = agreeing_checkpoints(state.storage.signed_checkpoints().get(&validators, update_info.nonce));
+        anyhow::ensure!(!checkpoints.is_empty(), "No signatures found");
         let nonce_checkpoint = &checkpoints[0].1.value;
-        anyhow::ensure!(
-            checkpoints.iter().all(|(_, checkpoint)| &(checkpoint.value) == nonce_checkpoint),
-            "Inconsistent checkpoint values found"
-        );
 
-        let signatures: Vec<ValidatorSignature> = checkpoints
+        // The Hyperlane contract expects the signatures in ascending order of validator index.
+        let mut signatures: Vec<ValidatorSignature> = checkpoints
             .iter()
             .filter_map(|(validator, signed_checkpoint)| {
                 validator_index_map
@@ -58,9 +131,14 @@ impl Calldata {
                     .map(|&idx| ValidatorSignature { validator_index: idx, signature: signed_checkpoint.signature })
             })
             .collect();
+        signatures.sort_by_key(|signature| signature.validator_index);
 
-        let update = match update_info.update {
-            DispatchUpdate::SpotMedian { update, .. } => update,
+        let (update_data, timestamp) = match update_info.update {
+            DispatchUpdate::SpotMedian { update, .. } => (update.to_bytes(), update.metadata.timestamp),
+            DispatchUpdate::Perp { update, .. } => (update.to_bytes(), update.metadata.timestamp),
+            DispatchUpdate::Opaque { feed_type, .. } => {
+                anyhow::bail!("Feed type {feed_type} is not supported by this version of Theoros")
+            }
         };
 
         let payload = Payload {
@@ -69,11 +147,11 @@ impl Calldata {
             // TODO: proof should be deleted
             proof_len: 0,
             proof: vec![],
-            update_data_len: update.to_bytes().len() as u16,
-            update_data: update.to_bytes(),
+            update_data_len: update_data.len() as u16,
+            update_data,
             feed_id,
             // TODO: publish_time is a duplicated of update timestamp - remove?
-            publish_time: update.metadata.timestamp,
+            publish_time: timestamp,
         };
 
         let hyperlane_message = HyperlaneMessage {
@@ -84,9 +162,10 @@ impl Calldata {
             signers_len: signatures.len() as u8,
             signatures,
             // TODO: timestamp is a duplicated of update timestamp - remove?
-            timestamp: update.metadata.timestamp,
+            timestamp,
             payload,
         };
+        state.quorum_tracker.record_served(chain_name, update_info.nonce);
 
         Ok(Calldata {
             major_version: PRAGMA_MAJOR_VERSION,
@@ -98,6 +177,14 @@ impl Calldata {
     }
 }
 
+impl Calldata {
+    /// Encodes the calldata & applies the post-processors configured for the destination chain.
+    pub fn encode_for_chain(&self, state: &AppState, chain_name: &EvmChainName) -> Vec<u8> {
+        let context = PostProcessingContext { num_feeds: self.hyperlane_msg.payload.num_updates };
+        state.post_processors.apply(chain_name, self.as_bytes(), &context)
+    }
+}
+
 impl AsCalldata for Calldata {
     fn as_bytes(&self) -> Vec<u8> {
         let mut bytes = vec![self.major_version, self.minor_version, self.trailing_header_size];
@@ -195,3 +282,27 @@ impl AsCalldata for Payload {
         bytes
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn feed_ids(ids: &[&str]) -> Vec<String> {
+        ids.iter().map(|id| id.to_string()).collect()
+    }
+
+    #[test]
+    fn test_requested_ordering_keeps_order() {
+        let mut ids = feed_ids(&["0x4254432f555344", "0x01", "0x4554482f555344"]);
+        CalldataOrdering::Requested.apply(&mut ids);
+        assert_eq!(ids, feed_ids(&["0x4254432f555344", "0x01", "0x4554482f555344"]));
+    }
+
+    #[test]
+    fn test_feed_id_ordering_sorts_by_numerical_value() {
+        // Lexicographic order would put "0x4554..." before "0x4254432f55534400".
+        let mut ids = feed_ids(&["0x4254432f55534400", "0x4554482f555344", "0x01", "0x0A"]);
+        CalldataOrdering::FeedId.apply(&mut ids);
+        assert_eq!(ids, feed_ids(&["0x01", "0x0A", "0x4554482f555344", "0x4254432f55534400"]));
+    }
+}
@@ -0,0 +1,51 @@
+use dashmap::DashMap;
+
+use crate::configs::evm_config::{ChainStatus, EvmChainName, EvmConfig};
+
+/// Current status of each configured chain, initialized from the EVM config.
+#[derive(Debug, Default)]
+pub struct ChainStatuses(DashMap<EvmChainName, ChainStatus>);
+
+impl ChainStatuses {
+    pub fn from_config(config: &EvmConfig) -> Self {
+        Self(config.chains().iter().map(|(chain_name, chain_config)| (*chain_name, chain_config.status)).collect())
+    }
+
+    /// Marks all the chains as enabled.
+    pub fn all_enabled(chains: impl IntoIterator<Item = EvmChainName>) -> Self {
+        Self(chains.into_iter().map(|chain_name| (chain_name, ChainStatus::default())).collect())
+    }
+
+    pub fn get(&self, chain_name: &EvmChainName) -> Option<ChainStatus> {
+        self.0.get(chain_name).map(|status| *status)
+    }
+
+    /// Returns the statuses of all the configured chains.
+    pub fn all(&self) -> Vec<(EvmChainName, ChainStatus)> {
+        self.0.iter().map(|entry| (*entry.key(), *entry.value())).collect()
+    }
+
+    /// Updates the status of a configured chain. Returns `false` if the chain isn't configured.
+    pub fn set(&self, chain_name: EvmChainName, status: ChainStatus) -> bool {
+        match self.0.get_mut(&chain_name) {
+            Some(mut current) => {
+                *current = status;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Replaces the statuses of all the chains by the ones of `other`.
+    pub fn replace(&self, other: Self) {
+        self.0.retain(|chain_name, _| other.0.contains_key(chain_name));
+        for (chain_name, status) in other.0 {
+            self.0.insert(chain_name, status);
+        }
+    }
+
+    /// Whether calldata can currently be served for the chain.
+    pub fn is_served(&self, chain_name: &EvmChainName) -> bool {
+        self.get(chain_name).is_some_and(|status| status.serves())
+    }
+}
@@ -0,0 +1,348 @@
+use std::sync::{Arc, RwLock};
+
+use pragma_utils::redaction::redactor;
+use serde::{Deserialize, Serialize};
+use starknet::core::types::Felt;
+use tokio::sync::Mutex;
+use utoipa::ToSchema;
+
+use crate::{
+    configs::evm_config::{ChainStatus, ConfigError, EvmChainConfig, EvmChainName, EvmConfig},
+    rpc::evm::{pragma::PragmaContractsMapping, HyperlaneValidatorsMapping, ValidatorSet},
+    storage::SettingKind,
+    types::{chain_statuses::ChainStatuses, post_processors::PostProcessorsMapping},
+    AppState,
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigDeploymentError {
+    #[error(transparent)]
+    Invalid(#[from] ConfigError),
+    #[error("the default chain {0} would not be served anymore")]
+    DefaultChainRemoved(EvmChainName),
+    #[error("could not resolve the validators of chain {0}: {1}")]
+    ValidatorsResolution(EvmChainName, String),
+    #[error("could not build the Pragma contracts: {0}")]
+    PragmaContracts(String),
+}
+
+/// A chain whose configuration changed, with the fields that changed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct ChainConfigChange {
+    pub chain: EvmChainName,
+    /// e.g. `rpc_url`, `hyperlane_address` or `status`. Values are left out, RPC URLs may contain secrets.
+    pub fields: Vec<String>,
+}
+
+/// Validators & threshold of a chain re-resolved from its Hyperlane contract, compared to the ones currently loaded.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct ValidatorsChange {
+    pub chain: EvmChainName,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    /// Number of validators after the change.
+    pub validators: usize,
+    /// Number of signatures required after the change.
+    pub threshold: usize,
+}
+
+/// What applying a candidate EVM config changes, compared to the running one.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct ConfigDiff {
+    pub chains_added: Vec<EvmChainName>,
+    pub chains_removed: Vec<EvmChainName>,
+    pub chains_changed: Vec<ChainConfigChange>,
+    pub validators: Vec<ValidatorsChange>,
+}
+
+impl ConfigDiff {
+    /// Diffs the chains of the configs. The running statuses are used rather than the configured ones, as they
+    /// may have been updated through the admin API.
+    fn between(running: &EvmConfig, candidate: &EvmConfig, statuses: &ChainStatuses) -> Self {
+        let mut diff = Self::default();
+        for (chain_name, chain_config) in candidate.chains() {
+            match running.chains().get(chain_name) {
+                None => diff.chains_added.push(*chain_name),
+                Some(running_config) => {
+                    let fields = changed_fields(running_config, chain_config, statuses.get(chain_name));
+                    if !fields.is_empty() {
+                        diff.chains_changed.push(ChainConfigChange { chain: *chain_name, fields });
+                    }
+                }
+            }
+        }
+        diff.chains_removed = running
+            .chains()
+            .keys()
+            .filter(|chain_name| !candidate.chains().contains_key(chain_name))
+            .copied()
+            .collect();
+
+        diff.chains_added.sort_by_key(|chain| chain.to_string());
+        diff.chains_removed.sort_by_key(|chain| chain.to_string());
+        diff.chains_changed.sort_by_key(|change| change.chain.to_string());
+        diff
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.chains_added.is_empty()
+            && self.chains_removed.is_empty()
+            && self.chains_changed.is_empty()
+            && self.validators.is_empty()
+    }
+}
+
+fn changed_fields(
+    running: &EvmChainConfig,
+    candidate: &EvmChainConfig,
+    running_status: Option<ChainStatus>,
+) -> Vec<String> {
+    let mut fields = Vec::new();
+    if running.rpc_url != candidate.rpc_url {
+        fields.push("rpc_url");
+    }
+    if running.fallback_rpc_urls != candidate.fallback_rpc_urls {
+        fields.push("fallback_rpc_urls");
+    }
+    if !running.hyperlane_address.eq_ignore_ascii_case(&candidate.hyperlane_address) {
+        fields.push("hyperlane_address");
+    }
+    if running.hyperlane_domain != candidate.hyperlane_domain {
+        fields.push("hyperlane_domain");
+    }
+    if running.pragma_address != candidate.pragma_address {
+        fields.push("pragma_address");
+    }
+    if running.post_processors != candidate.post_processors {
+        fields.push("post_processors");
+    }
+    if running_status.unwrap_or(running.status) != candidate.status {
+        fields.push("status");
+    }
+    fields.into_iter().map(String::from).collect()
+}
+
+fn validators_change(
+    chain: EvmChainName,
+    current: Option<&ValidatorSet>,
+    resolved: &ValidatorSet,
+) -> Option<ValidatorsChange> {
+    let is_current = |validator: &Felt| current.is_some_and(|current| current.validators.contains_key(validator));
+    let mut added: Vec<String> =
+        resolved.validators.keys().filter(|validator| !is_current(validator)).map(|v| format!("{:#x}", v)).collect();
+    let mut removed: Vec<String> = current
+        .into_iter()
+        .flat_map(|current| current.validators.keys())
+        .filter(|validator| !resolved.validators.contains_key(validator))
+        .map(|v| format!("{:#x}", v))
+        .collect();
+    let same_threshold = current.is_some_and(|current| current.threshold == resolved.threshold);
+    if added.is_empty() && removed.is_empty() && same_threshold {
+        return None;
+    }
+    added.sort();
+    removed.sort();
+    Some(ValidatorsChange {
+        chain,
+        added,
+        removed,
+        validators: resolved.validators.len(),
+        threshold: resolved.threshold,
+    })
+}
+
+/// The components built from a candidate config, ready to replace the running ones.
+struct ResolvedConfig {
+    config: EvmConfig,
+    validators: HyperlaneValidatorsMapping,
+    pragma_contracts: PragmaContractsMapping,
+    post_processors: PostProcessorsMapping,
+    diff: ConfigDiff,
+}
+
+/// The EVM config currently running, which candidate configs are diffed against & applied over.
+#[derive(Debug, Default)]
+pub struct RunningEvmConfig {
+    config: Mutex<EvmConfig>,
+    /// Copy of the running config, readable while a candidate is being resolved.
+    snapshot: RwLock<Arc<EvmConfig>>,
+}
+
+impl RunningEvmConfig {
+    pub fn new(config: EvmConfig) -> Self {
+        Self { snapshot: RwLock::new(Arc::new(config.clone())), config: Mutex::new(config) }
+    }
+
+    /// The running config, without waiting for a candidate being resolved or the validators being refreshed.
+    pub fn current(&self) -> Arc<EvmConfig> {
+        self.snapshot.read().expect("Poisoned EVM config snapshot").clone()
+    }
+
+    /// Reports what applying the candidate would change, without applying it.
+    pub async fn validate(&self, state: &AppState, candidate: EvmConfig) -> Result<ConfigDiff, ConfigDeploymentError> {
+        let running = self.config.lock().await;
+        Ok(resolve(state, &running, candidate).await?.diff)
+    }
+
+    /// Applies the candidate once everything it requires was resolved, so a config that can't be resolved
+    /// leaves the running one untouched. Applies are serialized.
+    pub async fn apply(&self, state: &AppState, candidate: EvmConfig) -> Result<ConfigDiff, ConfigDeploymentError> {
+        let mut running = self.config.lock().await;
+        let resolved = resolve(state, &running, candidate).await?;
+
+        state.hyperlane_validators_mapping.replace(resolved.validators);
+        state.pragma_contracts.replace(resolved.pragma_contracts);
+        state.post_processors.replace(resolved.post_processors);
+        state.chain_statuses.replace(ChainStatuses::from_config(&resolved.config));
+        // The persisted statuses would otherwise override the applied ones on the next sync.
+        for (chain_name, status) in state.chain_statuses.all() {
+            state.storage.save_setting(SettingKind::ChainStatus, &chain_name.to_string(), status).await;
+        }
+        *self.snapshot.write().expect("Poisoned EVM config snapshot") = Arc::new(resolved.config.clone());
+        *running = resolved.config;
+        Ok(resolved.diff)
+    }
+
+    /// Fetches again the validators of the enabled chains & replaces the ones that changed. Runs under the lock of
+    /// the running config, so validators resolved for a config being replaced are never swapped in.
+    pub async fn refresh_validators(&self, state: &AppState) -> Vec<(EvmChainName, anyhow::Result<ValidatorsChange>)> {
+        let running = self.config.lock().await;
+        let mut changes = Vec::new();
+        for (chain_name, chain_config) in running.chains() {
+            if !state.chain_statuses.get(chain_name).is_some_and(|status| status.enabled) {
+                continue;
+            }
+            let current = state.hyperlane_validators_mapping.get_validator_set(chain_name);
+            let resolved = match HyperlaneValidatorsMapping::fetch_validators(chain_name, chain_config).await {
+                Ok(resolved) => resolved,
+                Err(e) => {
+                    changes.push((*chain_name, Err(e)));
+                    continue;
+                }
+            };
+            if let Some(change) = validators_change(*chain_name, current.as_ref(), &resolved) {
+                state.hyperlane_validators_mapping.insert(*chain_name, resolved);
+                changes.push((*chain_name, Ok(change)));
+            }
+        }
+        changes.sort_by_key(|(chain_name, _)| chain_name.to_string());
+        changes
+    }
+}
+
+/// Builds the components of the candidate config. The validators are only fetched again for the enabled chains
+/// whose RPC or Hyperlane contract changed, or which weren't loaded yet.
+async fn resolve(
+    state: &AppState,
+    running: &EvmConfig,
+    candidate: EvmConfig,
+) -> Result<ResolvedConfig, ConfigDeploymentError> {
+    for rpc_url in candidate.chains().values().flat_map(|chain_config| chain_config.rpc_urls()) {
+        redactor().add_url_secrets(rpc_url);
+    }
+    candidate.validate()?;
+
+    let mut diff = ConfigDiff::between(running, &candidate, &state.chain_statuses);
+    let validators = HyperlaneValidatorsMapping::default();
+    for (chain_name, chain_config) in candidate.chains() {
+        let current = state.hyperlane_validators_mapping.get_validator_set(chain_name);
+        let unchanged = running.chains().get(chain_name).is_some_and(|running_config| {
+            running_config.rpc_urls().eq(chain_config.rpc_urls())
+                && running_config.hyperlane_address.eq_ignore_ascii_case(&chain_config.hyperlane_address)
+        });
+        match current {
+            Some(current) if unchanged => validators.insert(*chain_name, current),
+            current if chain_config.status.enabled => {
+                let resolved = HyperlaneValidatorsMapping::fetch_validators(chain_name, chain_config)
+                    .await
+                    .map_err(|e| ConfigDeploymentError::ValidatorsResolution(*chain_name, e.to_string()))?;
+                diff.validators.extend(validators_change(*chain_name, current.as_ref(), &resolved));
+                validators.insert(*chain_name, resolved);
+            }
+            _ => {}
+        }
+    }
+    diff.validators.sort_by_key(|change| change.chain.to_string());
+
+    if let Some(default_chain) = state.default_chain {
+        if !validators.is_supported_chain(&default_chain) {
+            return Err(ConfigDeploymentError::DefaultChainRemoved(default_chain));
+        }
+    }
+
+    Ok(ResolvedConfig {
+        pragma_contracts: PragmaContractsMapping::from_config(&candidate)
+            .map_err(|e| ConfigDeploymentError::PragmaContracts(e.to_string()))?,
+        post_processors: PostProcessorsMapping::from_config(&candidate),
+        validators,
+        config: candidate,
+        diff,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashMap, HashSet};
+
+    use super::*;
+
+    const RUNNING: &str = "
+sepolia:
+  rpc_url: https://sepolia.example.com
+  hyperlane_address: '0x0000000000000000000000000000000000000001'
+holesky:
+  rpc_url: https://holesky.example.com
+  hyperlane_address: '0x0000000000000000000000000000000000000002'
+";
+
+    const CANDIDATE: &str = "
+sepolia:
+  rpc_url: https://sepolia.example.com
+  hyperlane_address: '0x0000000000000000000000000000000000000001'
+  enabled: false
+base:
+  rpc_url: https://base.example.com
+  hyperlane_address: '0x0000000000000000000000000000000000000003'
+";
+
+    #[test]
+    fn test_config_diff() {
+        let running = EvmConfig::from_yaml(RUNNING).unwrap();
+        let candidate = EvmConfig::from_yaml(CANDIDATE).unwrap();
+        let diff = ConfigDiff::between(&running, &candidate, &ChainStatuses::from_config(&running));
+        assert_eq!(diff.chains_added, vec![EvmChainName::Base]);
+        assert_eq!(diff.chains_removed, vec![EvmChainName::Holesky]);
+        assert_eq!(
+            diff.chains_changed,
+            vec![ChainConfigChange { chain: EvmChainName::Sepolia, fields: vec!["status".into()] }]
+        );
+        assert!(ConfigDiff::between(&running, &running, &ChainStatuses::from_config(&running)).is_empty());
+    }
+
+    #[test]
+    fn test_destination_domains() {
+        let running = EvmConfig::from_yaml(RUNNING).unwrap();
+        assert_eq!(running.destination_domains(), HashSet::from([11155111, 17000]));
+
+        let candidate = format!("{CANDIDATE}  hyperlane_domain: 1000\n");
+        let candidate = EvmConfig::from_yaml(&candidate).unwrap();
+        // Sepolia is disabled.
+        assert_eq!(candidate.destination_domains(), HashSet::from([1000]));
+    }
+
+    #[test]
+    fn test_validators_change() {
+        let current = ValidatorSet::new(HashMap::from([(Felt::ONE, 0), (Felt::TWO, 1)]));
+        let resolved = ValidatorSet::new(HashMap::from([(Felt::TWO, 0), (Felt::THREE, 1)]));
+        let change = validators_change(EvmChainName::Sepolia, Some(&current), &resolved).unwrap();
+        assert_eq!(change.added, vec!["0x3"]);
+        assert_eq!(change.removed, vec!["0x1"]);
+        assert_eq!((change.validators, change.threshold), (2, 2));
+        assert!(validators_change(EvmChainName::Sepolia, Some(&current), &current).is_none());
+
+        let lowered = current.clone().with_threshold(1);
+        let change = validators_change(EvmChainName::Sepolia, Some(&current), &lowered).unwrap();
+        assert!(change.added.is_empty() && change.removed.is_empty());
+        assert_eq!(change.threshold, 1);
+    }
+}
@@ -0,0 +1,123 @@
+use alloy::hex;
+use anyhow::{anyhow, Result};
+use ring::{
+    aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN},
+    agreement::{agree_ephemeral, EphemeralPrivateKey, UnparsedPublicKey, X25519},
+    digest::{digest, SHA256},
+    hkdf::{Salt, HKDF_SHA256},
+    rand::{SecureRandom, SystemRandom},
+};
+
+/// Length of a X25519 public key.
+pub const PUBLIC_KEY_LEN: usize = 32;
+
+/// Info used when deriving the AES key from the shared secret.
+const HKDF_INFO: &[u8] = b"theoros-calldata-encryption-v1";
+
+/// X25519 public key registered by a consumer of private feeds.
+/// Calldata served to this consumer is encrypted with ECIES (X25519 + HKDF-SHA256 + AES-256-GCM).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConsumerPublicKey([u8; PUBLIC_KEY_LEN]);
+
+impl ConsumerPublicKey {
+    /// Parses a hex-encoded X25519 public key.
+    pub fn from_hex(s: &str) -> Result<Self> {
+        let bytes = hex::decode(s)?;
+        let key: [u8; PUBLIC_KEY_LEN] =
+            bytes.try_into().map_err(|_| anyhow!("A public key must be {PUBLIC_KEY_LEN} bytes long"))?;
+        Ok(Self(key))
+    }
+
+    pub fn to_hex(&self) -> String {
+        hex::encode(self.0)
+    }
+
+    /// Identifier of the key, returned with the encrypted payloads so consumers know
+    /// which key to use when rotating them.
+    pub fn key_id(&self) -> String {
+        hex::encode(&digest(&SHA256, &self.0).as_ref()[..8])
+    }
+
+    /// Encrypts the payload for this key.
+    /// The output is `ephemeral public key (32 bytes) || nonce (12 bytes) || ciphertext || tag (16 bytes)`.
+    pub fn encrypt(&self, payload: &[u8]) -> Result<Vec<u8>> {
+        let rng = SystemRandom::new();
+        let ephemeral_private_key =
+            EphemeralPrivateKey::generate(&X25519, &rng).map_err(|_| anyhow!("Could not generate an ephemeral key"))?;
+        let ephemeral_public_key = ephemeral_private_key
+            .compute_public_key()
+            .map_err(|_| anyhow!("Could not compute the ephemeral public key"))?;
+
+        let mut salt = Vec::with_capacity(2 * PUBLIC_KEY_LEN);
+        salt.extend_from_slice(ephemeral_public_key.as_ref());
+        salt.extend_from_slice(&self.0);
+        let key = agree_ephemeral(ephemeral_private_key, &UnparsedPublicKey::new(&X25519, &self.0), |shared_secret| {
+            derive_aes_key(shared_secret, &salt)
+        })
+        .map_err(|_| anyhow!("Key agreement failed"))??;
+
+        let mut nonce = [0u8; NONCE_LEN];
+        rng.fill(&mut nonce).map_err(|_| anyhow!("Could not generate a nonce"))?;
+
+        let mut in_out = payload.to_vec();
+        key.seal_in_place_append_tag(Nonce::assume_unique_for_key(nonce), Aad::empty(), &mut in_out)
+            .map_err(|_| anyhow!("Could not encrypt the payload"))?;
+
+        let mut encrypted = Vec::with_capacity(PUBLIC_KEY_LEN + NONCE_LEN + in_out.len());
+        encrypted.extend_from_slice(ephemeral_public_key.as_ref());
+        encrypted.extend_from_slice(&nonce);
+        encrypted.extend_from_slice(&in_out);
+        Ok(encrypted)
+    }
+}
+
+/// Derives the AES-256-GCM key from the X25519 shared secret.
+fn derive_aes_key(shared_secret: &[u8], salt: &[u8]) -> Result<LessSafeKey> {
+    let mut key_bytes = [0u8; 32];
+    Salt::new(HKDF_SHA256, salt)
+        .extract(shared_secret)
+        .expand(&[HKDF_INFO], &AES_256_GCM)
+        .and_then(|okm| okm.fill(&mut key_bytes))
+        .map_err(|_| anyhow!("Could not derive the encryption key"))?;
+    let key = UnboundKey::new(&AES_256_GCM, &key_bytes).map_err(|_| anyhow!("Invalid encryption key"))?;
+    Ok(LessSafeKey::new(key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_consumer_can_decrypt_payload() {
+        let rng = SystemRandom::new();
+        let consumer_private_key = EphemeralPrivateKey::generate(&X25519, &rng).unwrap();
+        let consumer_public_key = consumer_private_key.compute_public_key().unwrap();
+        let key = ConsumerPublicKey::from_hex(&hex::encode(consumer_public_key.as_ref())).unwrap();
+
+        let payload = b"calldata".to_vec();
+        let encrypted = key.encrypt(&payload).unwrap();
+
+        // Decrypt as the consumer would.
+        let (ephemeral_public_key, rest) = encrypted.split_at(PUBLIC_KEY_LEN);
+        let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+        let mut salt = ephemeral_public_key.to_vec();
+        salt.extend_from_slice(consumer_public_key.as_ref());
+        let aes_key =
+            agree_ephemeral(consumer_private_key, &UnparsedPublicKey::new(&X25519, ephemeral_public_key), |s| {
+                derive_aes_key(s, &salt)
+            })
+            .unwrap()
+            .unwrap();
+        let mut in_out = ciphertext.to_vec();
+        let nonce = Nonce::try_assume_unique_for_key(nonce).unwrap();
+        let decrypted = aes_key.open_in_place(nonce, Aad::empty(), &mut in_out).unwrap();
+
+        assert_eq!(decrypted, payload.as_slice());
+    }
+
+    #[test]
+    fn test_invalid_public_key_is_rejected() {
+        assert!(ConsumerPublicKey::from_hex("0x1234").is_err());
+        assert!(ConsumerPublicKey::from_hex("not hex").is_err());
+    }
+}
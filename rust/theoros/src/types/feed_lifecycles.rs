@@ -0,0 +1,36 @@
+use chrono::Utc;
+use dashmap::DashMap;
+
+use crate::configs::feed_lifecycle::{FeedLifecycle, FeedLifecycleConfig, FeedLifecycleState};
+
+/// Lifecycle of the feeds, initialized from the feed lifecycle config. Feeds without one are active.
+#[derive(Debug, Default)]
+pub struct FeedLifecycles(DashMap<String, FeedLifecycle>);
+
+impl FeedLifecycles {
+    pub fn from_config(config: &FeedLifecycleConfig) -> Self {
+        Self(config.feeds().iter().map(|(feed_id, lifecycle)| (feed_id.clone(), lifecycle.clone())).collect())
+    }
+
+    /// Returns the lifecycle of the feed, as configured.
+    pub fn get(&self, feed_id: &str) -> FeedLifecycle {
+        self.0.get(feed_id).map(|lifecycle| lifecycle.clone()).unwrap_or_default()
+    }
+
+    /// Returns the lifecycles of the feeds that were configured or updated.
+    pub fn all(&self) -> Vec<(String, FeedLifecycle)> {
+        self.0.iter().map(|entry| (entry.key().clone(), entry.value().clone())).collect()
+    }
+
+    pub fn set(&self, feed_id: String, lifecycle: FeedLifecycle) {
+        self.0.insert(feed_id, lifecycle);
+    }
+
+    /// Returns the first of the feeds that is currently retired, if any.
+    pub fn first_retired<'a>(&self, feed_ids: &'a [String]) -> Option<&'a String> {
+        let now = Utc::now();
+        feed_ids.iter().find(|feed_id| {
+            self.0.get(feed_id.as_str()).is_some_and(|lifecycle| lifecycle.state_at(now) == FeedLifecycleState::Retired)
+        })
+    }
+}
@@ -0,0 +1,100 @@
+//! Heap statistics & profiling, only available when Theoros is built with the `jemalloc` feature.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Memory statistics of the allocator, in bytes.
+/// A large gap between `resident` and `allocated` is a sign of fragmentation.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct HeapStats {
+    /// Bytes allocated by the application
+    pub allocated: usize,
+    /// Bytes in active pages allocated by the application
+    pub active: usize,
+    /// Bytes in physically resident data pages mapped by the allocator
+    pub resident: usize,
+    /// Bytes in active extents mapped by the allocator
+    pub mapped: usize,
+    /// Bytes in virtual memory mappings retained for future reuse
+    pub retained: usize,
+}
+
+/// Returns whether the heap statistics & profiling are available in this build.
+pub const fn is_available() -> bool {
+    cfg!(feature = "jemalloc")
+}
+
+#[cfg(feature = "jemalloc")]
+mod jemalloc {
+    use std::ffi::CString;
+    use std::path::Path;
+
+    use anyhow::{anyhow, Context, Result};
+    use tikv_jemalloc_ctl::{epoch, raw, stats};
+
+    use super::HeapStats;
+
+    pub fn heap_stats() -> Result<HeapStats> {
+        // Statistics are cached & only refreshed when the epoch is advanced.
+        epoch::advance().map_err(|e| anyhow!("Refreshing jemalloc stats: {e}"))?;
+        let read_err = |e| anyhow!("Reading jemalloc stats: {e}");
+        Ok(HeapStats {
+            allocated: stats::allocated::read().map_err(read_err)?,
+            active: stats::active::read().map_err(read_err)?,
+            resident: stats::resident::read().map_err(read_err)?,
+            mapped: stats::mapped::read().map_err(read_err)?,
+            retained: stats::retained::read().map_err(read_err)?,
+        })
+    }
+
+    pub fn set_profiling_active(active: bool) -> Result<()> {
+        // SAFETY: `prof.active` is a boolean option.
+        unsafe { raw::write(b"prof.active\0", active) }.map_err(|e| anyhow!("Setting prof.active: {e}"))
+    }
+
+    pub fn dump_heap_profile(path: &Path) -> Result<()> {
+        let path = CString::new(path.to_string_lossy().as_bytes()).context("Invalid heap profile path")?;
+        // SAFETY: `prof.dump` expects a pointer to a nul-terminated path, kept alive during the call.
+        unsafe { raw::write(b"prof.dump\0", path.as_ptr()) }.map_err(|e| anyhow!("Dumping heap profile: {e}"))
+    }
+}
+
+#[cfg(not(feature = "jemalloc"))]
+mod jemalloc {
+    use std::path::Path;
+
+    use anyhow::{bail, Result};
+
+    use super::HeapStats;
+
+    pub fn heap_stats() -> Result<HeapStats> {
+        bail!("Theoros was not built with the `jemalloc` feature")
+    }
+
+    pub fn set_profiling_active(_active: bool) -> Result<()> {
+        bail!("Theoros was not built with the `jemalloc` feature")
+    }
+
+    pub fn dump_heap_profile(_path: &Path) -> Result<()> {
+        bail!("Theoros was not built with the `jemalloc` feature")
+    }
+}
+
+pub fn heap_stats() -> Result<HeapStats> {
+    jemalloc::heap_stats()
+}
+
+/// Starts or stops sampling the allocations for the heap profiles.
+pub fn set_profiling_active(active: bool) -> Result<()> {
+    jemalloc::set_profiling_active(active)
+}
+
+/// Dumps a heap profile, readable by `jeprof`, and returns its content.
+pub fn dump_heap_profile() -> Result<Vec<u8>> {
+    let path = std::env::temp_dir().join(format!("theoros_heap_{}.prof", chrono::Utc::now().timestamp_millis()));
+    jemalloc::dump_heap_profile(&path)?;
+    let profile = std::fs::read(&path)?;
+    std::fs::remove_file(&path)?;
+    Ok(profile)
+}
@@ -0,0 +1,75 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::types::hyperlane::{DispatchUpdate, DispatchUpdateInfos};
+
+/// Price of a feed at the time of an update.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct HistoryPoint {
+    /// Nonce of the dispatch of the update.
+    pub nonce: u32,
+    /// Timestamp of the update, as a unix timestamp in seconds.
+    pub timestamp: u64,
+    /// Decimal string, to be scaled by `decimals`. Mark price for perp updates.
+    pub price: String,
+    pub decimals: u8,
+}
+
+impl HistoryPoint {
+    /// Opaque updates have no price.
+    pub fn from_update(infos: &DispatchUpdateInfos) -> Option<Self> {
+        let (price, metadata) = match &infos.update {
+            DispatchUpdate::SpotMedian { update, feed_id: _ } => (update.price, &update.metadata),
+            DispatchUpdate::Perp { update, feed_id: _ } => (update.mark_price, &update.metadata),
+            DispatchUpdate::Opaque { .. } => return None,
+        };
+        Some(Self {
+            nonce: infos.nonce,
+            timestamp: metadata.timestamp,
+            price: price.to_string(),
+            decimals: metadata.decimals,
+        })
+    }
+}
+
+/// Selects the updates of a feed by timestamp: the `limit` most recent ones between `from` & `to`, inclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HistoryRange {
+    pub from: Option<u64>,
+    pub to: Option<u64>,
+    pub limit: usize,
+}
+
+impl HistoryRange {
+    pub fn contains(&self, timestamp: u64) -> bool {
+        self.from.map_or(true, |from| timestamp >= from) && self.to.map_or(true, |to| timestamp <= to)
+    }
+
+    /// Selects the points in range, sorted by timestamp then nonce.
+    pub fn select(&self, points: impl IntoIterator<Item = HistoryPoint>) -> Vec<HistoryPoint> {
+        let mut points: Vec<HistoryPoint> = points.into_iter().filter(|point| self.contains(point.timestamp)).collect();
+        points.sort_by_key(|point| (point.timestamp, point.nonce));
+        let skipped = points.len().saturating_sub(self.limit);
+        points.split_off(skipped)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point(nonce: u32, timestamp: u64) -> HistoryPoint {
+        HistoryPoint { nonce, timestamp, price: nonce.to_string(), decimals: 8 }
+    }
+
+    #[test]
+    fn test_select_history_range() {
+        let points = vec![point(1, 10), point(3, 30), point(2, 20), point(4, 40), point(5, 50)];
+
+        let range = HistoryRange { from: Some(20), to: Some(40), limit: 10 };
+        assert_eq!(range.select(points.clone()), vec![point(2, 20), point(3, 30), point(4, 40)]);
+
+        let range = HistoryRange { from: None, to: None, limit: 2 };
+        assert_eq!(range.select(points), vec![point(4, 40), point(5, 50)]);
+    }
+}
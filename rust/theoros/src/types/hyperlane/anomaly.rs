@@ -0,0 +1,51 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use strum_macros::IntoStaticStr;
+use utoipa::ToSchema;
+
+use super::CheckpointWithMessageId;
+
+/// A checkpoint signed by a validator that shouldn't have been signed.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CheckpointAnomaly {
+    /// Address of the validator that signed the checkpoint.
+    pub validator: String,
+    /// Index of the checkpoint.
+    pub index: u32,
+    pub kind: CheckpointAnomalyKind,
+    pub detected_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema, IntoStaticStr)]
+#[serde(tag = "type", rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum CheckpointAnomalyKind {
+    /// The validator signed two different checkpoints for the same index.
+    Equivocation { first: SignedValue, second: SignedValue },
+    /// The validator signed a message id diverging from the one computed from the indexed dispatch.
+    RootDivergence { expected: SignedValue, signed: SignedValue },
+}
+
+/// The signed (root, message id) couple of a checkpoint.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+pub struct SignedValue {
+    pub root: String,
+    pub message_id: String,
+}
+
+impl From<&CheckpointWithMessageId> for SignedValue {
+    fn from(value: &CheckpointWithMessageId) -> Self {
+        Self { root: value.checkpoint.root.clone(), message_id: format!("{:#x}", value.message_id) }
+    }
+}
+
+impl CheckpointAnomaly {
+    pub fn new(validator: String, index: u32, kind: CheckpointAnomalyKind) -> Self {
+        Self { validator, index, kind, detected_at: Utc::now() }
+    }
+
+    /// Name of the anomaly kind, used as a metric label.
+    pub fn kind_name(&self) -> &'static str {
+        (&self.kind).into()
+    }
+}
@@ -1,6 +1,9 @@
+use std::str::FromStr;
+
+use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 
-use alloy::primitives::U256;
+use alloy::primitives::{keccak256, Address, B256, U256};
 
 use super::SignedType;
 
@@ -29,8 +32,96 @@ pub struct CheckpointWithMessageId {
     pub message_id: U256,
 }
 
+impl CheckpointWithMessageId {
+    /// Hash signed by the validators, computed as Hyperlane does:
+    /// `keccak256(domain_hash || root || index || message_id)`.
+    pub fn signing_hash(&self) -> Result<B256> {
+        let root = B256::from_str(&self.checkpoint.root).context("Invalid checkpoint root")?;
+        let mut data = Vec::with_capacity(32 + 32 + 4 + 32);
+        data.extend_from_slice(self.domain_hash().as_slice());
+        data.extend_from_slice(root.as_slice());
+        data.extend_from_slice(&self.checkpoint.index.to_be_bytes());
+        data.extend_from_slice(&self.message_id.to_be_bytes::<32>());
+        Ok(keccak256(data))
+    }
+
+    /// `keccak256(mailbox_domain || merkle_tree_hook_address || "HYPERLANE")`
+    fn domain_hash(&self) -> B256 {
+        let mut data = Vec::with_capacity(4 + 32 + 9);
+        data.extend_from_slice(&self.checkpoint.mailbox_domain.to_be_bytes());
+        data.extend_from_slice(&self.checkpoint.merkle_tree_hook_address.to_be_bytes::<32>());
+        data.extend_from_slice(b"HYPERLANE");
+        keccak256(data)
+    }
+}
+
+impl SignedCheckpointWithMessageId {
+    /// Recovers the address of the validator that signed the checkpoint.
+    /// Validators sign the EIP-191 message of the signing hash.
+    pub fn recover_signer(&self) -> Result<Address> {
+        let signing_hash = self.value.signing_hash()?;
+        Ok(self.signature.recover_address_from_msg(signing_hash)?)
+    }
+}
+
 /// An event that is emitted when we find a match between a checkpoint and a message
 #[derive(Clone, PartialEq, Debug)]
 pub enum NewUpdatesAvailableEvent {
-    New,
+    /// The updates of the dispatch reached quorum & were stored.
+    New { nonce: u32, feed_ids: Vec<String> },
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::address;
+    use alloy::signers::{local::PrivateKeySigner, SignerSync};
+
+    use super::*;
+    use crate::types::hyperlane::bytes_to_hex;
+
+    /// Checkpoint #247352 of the Pragma devnet, as published by its validator, which also signed the encoded
+    /// update of the `PragmaDecoder` Solidity tests.
+    const SIGNED_CHECKPOINT_FIXTURE: &str = include_str!("../../../fixtures/signed_checkpoint.json");
+
+    fn checkpoint() -> CheckpointWithMessageId {
+        CheckpointWithMessageId {
+            checkpoint: Checkpoint {
+                merkle_tree_hook_address: U256::from(1),
+                mailbox_domain: 6363709,
+                root: format!("{:#x}", B256::repeat_byte(2)),
+                index: 12,
+            },
+            message_id: U256::from(3),
+        }
+    }
+
+    #[test]
+    fn test_recover_checkpoint_signer() {
+        let signer = PrivateKeySigner::from_bytes(&B256::repeat_byte(7)).unwrap();
+        let value = checkpoint();
+        let signature = signer.sign_message_sync(value.signing_hash().unwrap().as_slice()).unwrap();
+        let signed = SignedCheckpointWithMessageId { value, signature };
+        assert_eq!(signed.recover_signer().unwrap(), signer.address());
+
+        // Any change to the signed value changes the recovered signer.
+        let mut tampered = signed.clone();
+        tampered.value.checkpoint.index += 1;
+        assert_ne!(tampered.recover_signer().unwrap(), signer.address());
+
+        let mut invalid_root = signed;
+        invalid_root.value.checkpoint.root = String::from("not a root");
+        assert!(invalid_root.recover_signer().is_err());
+    }
+
+    #[test]
+    fn test_recover_validator_checkpoint_signer() {
+        let signed: SignedCheckpointWithMessageId = serde_json::from_str(SIGNED_CHECKPOINT_FIXTURE).unwrap();
+        let serialized: [u8; 65] = signed.signature.into();
+        assert_eq!(
+            bytes_to_hex(&serialized),
+            "0x02c8960ad17eddf3ec435aea12d031c6a6298e5c8e786550c1238fb423a4f5661455ef5cac013d6cc34a4ce472ba2031cb2ae84dfd35d0ecd0b51b4d437dc0b81b"
+        );
+        // The devnet validator, see `typescript/pragma-deployer/config/presets/devnet.pragma.yaml`.
+        assert_eq!(signed.recover_signer().unwrap(), address!("F6311461A6d8b44cb3F62b2FCd47570A28443ca0"));
+    }
 }
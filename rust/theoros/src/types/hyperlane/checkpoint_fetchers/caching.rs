@@ -0,0 +1,215 @@
+use std::collections::BTreeMap;
+use std::num::NonZeroUsize;
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use lru::LruCache;
+use prometheus::IntCounterVec;
+use starknet::core::types::Felt;
+
+use crate::services::metrics::TheorosMetrics;
+use crate::types::hyperlane::{FetchFromStorage, SignedCheckpointWithMessageId};
+
+/// Checkpoints fetched from the validators, the least recently used being evicted once full.
+///
+/// A signed checkpoint never changes, so the entries never expire.
+#[derive(Debug)]
+pub struct CheckpointCache {
+    checkpoints: Mutex<LruCache<(Felt, u32), SignedCheckpointWithMessageId>>,
+    /// Lookups by outcome, not counted when `None`.
+    lookups: Option<IntCounterVec>,
+}
+
+impl CheckpointCache {
+    pub fn new(capacity: NonZeroUsize) -> Self {
+        Self { checkpoints: Mutex::new(LruCache::new(capacity)), lookups: None }
+    }
+
+    /// Counts the hits & misses of the lookups.
+    pub fn with_metrics(mut self, metrics: &TheorosMetrics) -> Self {
+        self.lookups = Some(metrics.checkpoint_cache_lookups.clone());
+        self
+    }
+
+    pub fn get(&self, validator: Felt, nonce: u32) -> Option<SignedCheckpointWithMessageId> {
+        let checkpoint = self.checkpoints.lock().expect("Poisoned checkpoint cache").get(&(validator, nonce)).cloned();
+        if let Some(lookups) = &self.lookups {
+            let outcome = if checkpoint.is_some() { "hit" } else { "miss" };
+            lookups.with_label_values(&[outcome]).inc();
+        }
+        checkpoint
+    }
+
+    pub fn insert(&self, validator: Felt, nonce: u32, checkpoint: SignedCheckpointWithMessageId) {
+        self.checkpoints.lock().expect("Poisoned checkpoint cache").put((validator, nonce), checkpoint);
+    }
+
+    /// Evicts the checkpoints of the nonce, or all of them, & returns how many were evicted.
+    pub fn evict(&self, nonce: Option<u32>) -> usize {
+        let mut checkpoints = self.checkpoints.lock().expect("Poisoned checkpoint cache");
+        let Some(nonce) = nonce else {
+            let evicted = checkpoints.len();
+            checkpoints.clear();
+            return evicted;
+        };
+        let keys: Vec<(Felt, u32)> =
+            checkpoints.iter().map(|(key, _)| *key).filter(|(_, cached_nonce)| *cached_nonce == nonce).collect();
+        for key in &keys {
+            checkpoints.pop(key);
+        }
+        keys.len()
+    }
+
+    pub fn len(&self) -> usize {
+        self.checkpoints.lock().expect("Poisoned checkpoint cache").len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Serves the checkpoints of a validator from the [CheckpointCache], only fetching the missing ones from
+/// its storage.
+///
+/// Checkpoints not signed yet aren't cached, so they are fetched again until the validator signs them.
+#[derive(Debug, Clone)]
+pub struct CachingFetcher {
+    validator: Felt,
+    fetcher: Arc<dyn FetchFromStorage + Send + Sync>,
+    cache: Arc<CheckpointCache>,
+}
+
+impl CachingFetcher {
+    pub fn new(validator: Felt, fetcher: Arc<dyn FetchFromStorage + Send + Sync>, cache: Arc<CheckpointCache>) -> Self {
+        Self { validator, fetcher, cache }
+    }
+}
+
+#[async_trait]
+impl FetchFromStorage for CachingFetcher {
+    async fn fetch(&self, index: u32) -> Result<Option<SignedCheckpointWithMessageId>> {
+        if let Some(checkpoint) = self.cache.get(self.validator, index) {
+            return Ok(Some(checkpoint));
+        }
+        let checkpoint = self.fetcher.fetch(index).await?;
+        if let Some(checkpoint) = &checkpoint {
+            self.cache.insert(self.validator, index, checkpoint.clone());
+        }
+        Ok(checkpoint)
+    }
+
+    /// The latest index moves as the validator signs, so it is never cached.
+    async fn fetch_latest_index(&self) -> Result<Option<u32>> {
+        self.fetcher.fetch_latest_index().await
+    }
+
+    /// Caches the fetched range, as backends may fetch it at once.
+    async fn fetch_range(&self, from: u32, to: u32) -> Result<BTreeMap<u32, SignedCheckpointWithMessageId>> {
+        let checkpoints = self.fetcher.fetch_range(from, to).await?;
+        for (index, checkpoint) in &checkpoints {
+            self.cache.insert(self.validator, *index, checkpoint.clone());
+        }
+        Ok(checkpoints)
+    }
+
+    fn announcement_location(&self) -> String {
+        self.fetcher.announcement_location()
+    }
+
+    fn backend_name(&self) -> String {
+        self.fetcher.backend_name()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    use alloy::primitives::{Parity, U256};
+    use alloy::signers::Signature;
+    use prometheus::Registry;
+
+    use super::*;
+    use crate::types::hyperlane::{Checkpoint, CheckpointWithMessageId, SignedType};
+
+    /// Signs the nonces up to `latest`, counting the fetches.
+    #[derive(Debug)]
+    struct CountingBackend {
+        latest: u32,
+        fetches: AtomicU32,
+    }
+
+    #[async_trait]
+    impl FetchFromStorage for CountingBackend {
+        async fn fetch(&self, index: u32) -> Result<Option<SignedCheckpointWithMessageId>> {
+            self.fetches.fetch_add(1, Ordering::SeqCst);
+            Ok((index <= self.latest).then(|| signed_checkpoint(index)))
+        }
+
+        async fn fetch_latest_index(&self) -> Result<Option<u32>> {
+            Ok(Some(self.latest))
+        }
+
+        fn announcement_location(&self) -> String {
+            String::from("mock://counting")
+        }
+    }
+
+    fn signed_checkpoint(index: u32) -> SignedCheckpointWithMessageId {
+        SignedType {
+            value: CheckpointWithMessageId {
+                checkpoint: Checkpoint {
+                    merkle_tree_hook_address: U256::from(1),
+                    mailbox_domain: 6363709,
+                    root: String::from("0x01"),
+                    index,
+                },
+                message_id: U256::from(2),
+            },
+            signature: Signature::new(U256::from(3), U256::from(4), Parity::Parity(false)),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fetched_checkpoints_are_cached() {
+        let metrics = TheorosMetrics::register(&Registry::new()).unwrap();
+        let cache = Arc::new(CheckpointCache::new(NonZeroUsize::new(2).unwrap()).with_metrics(&metrics));
+        let backend = Arc::new(CountingBackend { latest: 5, fetches: AtomicU32::new(0) });
+        let fetcher = CachingFetcher::new(Felt::ONE, backend.clone(), cache.clone());
+
+        assert!(fetcher.fetch(1).await.unwrap().is_some());
+        assert!(fetcher.fetch(1).await.unwrap().is_some());
+        assert_eq!(backend.fetches.load(Ordering::SeqCst), 1);
+
+        // Checkpoints not signed yet are fetched again.
+        assert!(fetcher.fetch(7).await.unwrap().is_none());
+        assert!(fetcher.fetch(7).await.unwrap().is_none());
+        assert_eq!(backend.fetches.load(Ordering::SeqCst), 3);
+
+        // The least recently used checkpoint is evicted.
+        assert_eq!(fetcher.fetch_range(2, 3).await.unwrap().len(), 2);
+        assert_eq!(cache.len(), 2);
+        assert!(cache.get(Felt::ONE, 1).is_none());
+
+        // Checkpoints are cached per validator.
+        assert!(cache.get(Felt::TWO, 3).is_none());
+
+        let lookups = |outcome| metrics.checkpoint_cache_lookups.with_label_values(&[outcome]).get();
+        assert_eq!((lookups("hit"), lookups("miss")), (1, 5));
+    }
+
+    #[test]
+    fn test_cached_checkpoints_are_evicted() {
+        let cache = CheckpointCache::new(NonZeroUsize::new(4).unwrap());
+        cache.insert(Felt::ONE, 1, signed_checkpoint(1));
+        cache.insert(Felt::TWO, 1, signed_checkpoint(1));
+        cache.insert(Felt::ONE, 2, signed_checkpoint(2));
+
+        assert_eq!(cache.evict(Some(1)), 2);
+        assert!(cache.get(Felt::ONE, 2).is_some());
+        assert_eq!(cache.evict(None), 1);
+        assert!(cache.is_empty());
+    }
+}
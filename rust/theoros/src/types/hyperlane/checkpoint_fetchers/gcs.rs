@@ -50,7 +50,6 @@ pub struct GcsStorageClient {
     bucket: String,
 }
 
-#[allow(unused)]
 impl GcsStorageClient {
     fn get_checkpoint_key(index: u32) -> String {
         format!("checkpoint_{index}_with_id.json")
@@ -68,6 +67,11 @@ impl FetchFromStorage for GcsStorageClient {
         Ok(Some(serde_json::from_slice(res.as_ref())?))
     }
 
+    async fn fetch_latest_index(&self) -> Result<Option<u32>> {
+        let res = self.inner.get_object(&self.bucket, GcsStorageClient::get_latest_checkpoint_key()).await?;
+        Ok(Some(serde_json::from_slice(res.as_ref())?))
+    }
+
     fn announcement_location(&self) -> String {
         format!("gs://{}/{}", &self.bucket, ANNOUNCEMENT_KEY)
     }
@@ -0,0 +1,161 @@
+use std::env;
+
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use reqwest::{Client, StatusCode};
+
+use crate::constants::IPFS_REQUEST_TIMEOUT;
+use crate::types::hyperlane::{FetchFromStorage, SignedCheckpointWithMessageId};
+
+/// Base URL of the IPFS HTTP gateway the IPNS names are resolved through, e.g. `https://ipfs.io`.
+pub const IPFS_GATEWAY_URL: &str = "IPFS_GATEWAY_URL";
+/// Base URL of the RPC API of an IPFS node, e.g. `http://127.0.0.1:5001`, used instead of the gateway if set.
+pub const IPFS_API_URL: &str = "IPFS_API_URL";
+
+const DEFAULT_IPFS_GATEWAY_URL: &str = "https://ipfs.io";
+
+/// Where the IPNS names are resolved & the checkpoints read from.
+#[derive(Debug, Clone, Eq, Hash, PartialEq)]
+pub enum IpfsEndpoint {
+    /// A public or private HTTP gateway.
+    Gateway(String),
+    /// The RPC API of an IPFS node, e.g. a local Kubo node.
+    NodeApi(String),
+}
+
+impl IpfsEndpoint {
+    /// The node API if [IPFS_API_URL] is set, else the gateway of [IPFS_GATEWAY_URL], `https://ipfs.io` by default.
+    pub fn from_env() -> Self {
+        match env::var(IPFS_API_URL) {
+            Ok(url) => Self::NodeApi(url),
+            Err(_) => Self::Gateway(env::var(IPFS_GATEWAY_URL).unwrap_or_else(|_| DEFAULT_IPFS_GATEWAY_URL.to_owned())),
+        }
+    }
+}
+
+/// Reads the checkpoints published by a validator on IPFS, under an IPNS name pointing to the latest version
+/// of its checkpoints directory.
+#[derive(Debug, Clone)]
+pub struct IpfsStorage {
+    /// IPNS name of the checkpoints directory.
+    name: String,
+    endpoint: IpfsEndpoint,
+    client: Client,
+}
+
+impl IpfsStorage {
+    pub fn new(name: String, endpoint: IpfsEndpoint) -> Result<Self> {
+        let client = Client::builder().timeout(IPFS_REQUEST_TIMEOUT).build()?;
+        Ok(Self { name, endpoint, client })
+    }
+
+    /// Reads a file of the checkpoints directory, returning `None` if it doesn't exist.
+    async fn read(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        let path = format!("/ipns/{}/{}", self.name, key);
+        let request = match &self.endpoint {
+            IpfsEndpoint::Gateway(url) => self.client.get(format!("{}{}", url.trim_end_matches('/'), path)),
+            IpfsEndpoint::NodeApi(url) => {
+                self.client.post(format!("{}/api/v0/cat", url.trim_end_matches('/'))).query(&[("arg", &path)])
+            }
+        };
+        let response = request.send().await.with_context(|| format!("Failed to read {path} from IPFS"))?;
+        let status = response.status();
+        if status.is_success() {
+            return Ok(Some(response.bytes().await?.to_vec()));
+        }
+        let body = response.text().await.unwrap_or_default();
+        // Nodes answer a `500` when the directory has no such file.
+        if status == StatusCode::NOT_FOUND || body.contains("no link named") {
+            return Ok(None);
+        }
+        bail!("Failed to read {path} from IPFS: {status} {body}")
+    }
+
+    fn checkpoint_key(index: u32) -> String {
+        format!("checkpoint_{index}_with_id.json")
+    }
+
+    fn latest_index_key() -> String {
+        "checkpoint_latest_index.json".to_owned()
+    }
+}
+
+#[async_trait]
+impl FetchFromStorage for IpfsStorage {
+    async fn fetch(&self, index: u32) -> Result<Option<SignedCheckpointWithMessageId>> {
+        self.read(&Self::checkpoint_key(index))
+            .await?
+            .map(|data| serde_json::from_slice(&data))
+            .transpose()
+            .map_err(Into::into)
+    }
+
+    async fn fetch_latest_index(&self) -> Result<Option<u32>> {
+        self.read(&Self::latest_index_key())
+            .await?
+            .map(|data| serde_json::from_slice(&data))
+            .transpose()
+            .map_err(Into::into)
+    }
+
+    fn announcement_location(&self) -> String {
+        format!("ipns://{}", self.name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use axum::{
+        extract::{Path, Query},
+        http::StatusCode,
+        routing::{get, post},
+        Router,
+    };
+    use tokio::net::TcpListener;
+
+    use super::*;
+
+    const NAME: &str = "k51qzi5uqu5dlvj2baxnqndepeb86cbk3ng7n3i46uzyxzyqj2xjonzllnv0v8";
+
+    /// Serves a checkpoints directory holding the latest index only, through a gateway & a node API.
+    async fn ipfs_server() -> String {
+        let app = Router::new()
+            .route(
+                "/ipns/:name/:key",
+                get(|Path((name, key)): Path<(String, String)>| async move {
+                    match (name == NAME, key.as_str()) {
+                        (true, "checkpoint_latest_index.json") => (StatusCode::OK, "42"),
+                        _ => (StatusCode::NOT_FOUND, "not found"),
+                    }
+                }),
+            )
+            .route(
+                "/api/v0/cat",
+                post(|Query(query): Query<HashMap<String, String>>| async move {
+                    match query.get("arg").map(String::as_str) {
+                        Some(arg) if arg == format!("/ipns/{NAME}/checkpoint_latest_index.json") => {
+                            (StatusCode::OK, "42")
+                        }
+                        _ => (StatusCode::INTERNAL_SERVER_ERROR, r#"{"Message":"no link named \"x\" under Qm"}"#),
+                    }
+                }),
+            );
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        url
+    }
+
+    #[tokio::test]
+    async fn test_fetch_from_ipfs() {
+        let url = ipfs_server().await;
+        for endpoint in [IpfsEndpoint::Gateway(url.clone()), IpfsEndpoint::NodeApi(url.clone())] {
+            let storage = IpfsStorage::new(NAME.to_owned(), endpoint).unwrap();
+            assert_eq!(storage.fetch_latest_index().await.unwrap(), Some(42));
+            assert_eq!(storage.fetch(42).await.unwrap(), None);
+            assert_eq!(storage.backend_name(), "ipns");
+        }
+    }
+}
@@ -1,6 +1,7 @@
 // Source:
 // https://github.com/hyperlane-xyz/hyperlane-monorepo/blob/3e90734310fb1ca9a607ce3d334015fa7aaa9208/rust/hyperlane-base/src/types/local_storage.rs#L51
-use std::path::PathBuf;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result};
 use async_trait::async_trait;
@@ -8,7 +9,11 @@ use async_trait::async_trait;
 use crate::types::hyperlane::{FetchFromStorage, SignedCheckpointWithMessageId};
 
 #[derive(Debug, Clone)]
-/// Type for reading/write to LocalStorage
+/// Reads the checkpoints from a local directory, e.g. for local devnets, CI or airgapped setups.
+///
+/// Checkpoints are expected to follow the layout of the S3 & GCS buckets (`checkpoint_{index}_with_id.json`),
+/// so a bucket can simply be synced to the directory. The layout written by the Hyperlane local
+/// checkpoint syncer (`{index}_with_id.json` & `index.json`) is also supported.
 pub struct LocalStorage {
     path: PathBuf,
 }
@@ -24,21 +29,133 @@ impl LocalStorage {
     }
 
     fn checkpoint_file_path(&self, index: u32) -> PathBuf {
-        self.path.join(format!("{}_with_id.json", index))
+        self.path.join(format!("checkpoint_{index}_with_id.json"))
+    }
+
+    fn hyperlane_checkpoint_file_path(&self, index: u32) -> PathBuf {
+        self.path.join(format!("{index}_with_id.json"))
+    }
+
+    fn latest_index_file_path(&self) -> PathBuf {
+        self.path.join("checkpoint_latest_index.json")
+    }
+
+    fn hyperlane_latest_index_file_path(&self) -> PathBuf {
+        self.path.join("index.json")
+    }
+
+    /// Reads the first of the files that exists, returning `None` if none does.
+    async fn read_first(paths: &[PathBuf]) -> Result<Option<Vec<u8>>> {
+        for path in paths {
+            if let Some(data) = Self::read(path).await? {
+                return Ok(Some(data));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Reads the file, returning `None` if it doesn't exist.
+    async fn read(path: &Path) -> Result<Option<Vec<u8>>> {
+        match tokio::fs::read(path).await {
+            Ok(data) => Ok(Some(data)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e).with_context(|| format!("Failed to read checkpoint at {:?}", path)),
+        }
     }
 }
 
 #[async_trait]
 impl FetchFromStorage for LocalStorage {
     async fn fetch(&self, index: u32) -> Result<Option<SignedCheckpointWithMessageId>> {
-        let Ok(data) = tokio::fs::read(self.checkpoint_file_path(index)).await else {
+        let paths = [self.checkpoint_file_path(index), self.hyperlane_checkpoint_file_path(index)];
+        let Some(data) = Self::read_first(&paths).await? else {
             return Ok(None);
         };
-        let checkpoint = serde_json::from_slice(&data)?;
+        let checkpoint = serde_json::from_slice(&data)
+            .with_context(|| format!("Invalid checkpoint {index} in local storage {:?}", self.path))?;
         Ok(Some(checkpoint))
     }
 
+    async fn fetch_latest_index(&self) -> Result<Option<u32>> {
+        let paths = [self.latest_index_file_path(), self.hyperlane_latest_index_file_path()];
+        let Some(data) = Self::read_first(&paths).await? else {
+            return Ok(None);
+        };
+        let index = serde_json::from_slice(&data)
+            .with_context(|| format!("Invalid latest index in local storage {:?}", self.path))?;
+        Ok(Some(index))
+    }
+
     fn announcement_location(&self) -> String {
         format!("file://{}", self.path.to_str().unwrap())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::{Parity, U256};
+    use alloy::signers::Signature;
+
+    use super::*;
+    use crate::types::hyperlane::{Checkpoint, CheckpointWithMessageId, SignedType};
+
+    fn signed_checkpoint(index: u32) -> SignedCheckpointWithMessageId {
+        SignedType {
+            value: CheckpointWithMessageId {
+                checkpoint: Checkpoint {
+                    merkle_tree_hook_address: U256::from(1),
+                    mailbox_domain: 6363709,
+                    root: String::from("0x01"),
+                    index,
+                },
+                message_id: U256::from(2),
+            },
+            signature: Signature::new(U256::from(3), U256::from(4), Parity::Parity(false)),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fetch_from_local_storage() {
+        let path = std::env::temp_dir().join(format!("theoros_local_storage_{}", std::process::id()));
+        let storage = LocalStorage::new(path.clone()).unwrap();
+
+        std::fs::write(path.join("checkpoint_1_with_id.json"), serde_json::to_vec(&signed_checkpoint(1)).unwrap())
+            .unwrap();
+        std::fs::write(path.join("2_with_id.json"), serde_json::to_vec(&signed_checkpoint(2)).unwrap()).unwrap();
+        std::fs::write(path.join("checkpoint_3_with_id.json"), b"not a checkpoint").unwrap();
+
+        assert_eq!(storage.fetch(1).await.unwrap(), Some(signed_checkpoint(1)));
+        assert_eq!(storage.fetch(2).await.unwrap(), Some(signed_checkpoint(2)));
+        assert!(storage.fetch(3).await.is_err());
+        assert_eq!(storage.fetch(4).await.unwrap(), None);
+
+        assert_eq!(storage.fetch_latest_index().await.unwrap(), None);
+        std::fs::write(path.join("index.json"), b"2").unwrap();
+        assert_eq!(storage.fetch_latest_index().await.unwrap(), Some(2));
+        std::fs::write(path.join("checkpoint_latest_index.json"), b"3").unwrap();
+        assert_eq!(storage.fetch_latest_index().await.unwrap(), Some(3));
+
+        std::fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_fetch_range_from_local_storage() {
+        let path = std::env::temp_dir().join(format!("theoros_local_storage_range_{}", std::process::id()));
+        let storage = LocalStorage::new(path.clone()).unwrap();
+
+        for index in [1, 2, 4, 5] {
+            let checkpoint = serde_json::to_vec(&signed_checkpoint(index)).unwrap();
+            std::fs::write(path.join(format!("checkpoint_{index}_with_id.json")), checkpoint).unwrap();
+        }
+        let range = storage.fetch_range(0, 10).await.unwrap();
+        assert_eq!(range.keys().copied().collect::<Vec<_>>(), vec![1, 2, 4, 5]);
+        assert_eq!(range[&4], signed_checkpoint(4));
+
+        // The range is bounded by the latest index of the validator.
+        std::fs::write(path.join("checkpoint_latest_index.json"), b"4").unwrap();
+        assert_eq!(storage.fetch_range(0, 10).await.unwrap().keys().copied().collect::<Vec<_>>(), vec![1, 2, 4]);
+        assert!(storage.fetch_range(5, 10).await.unwrap().is_empty());
+
+        std::fs::remove_dir_all(&path).unwrap();
+    }
+}
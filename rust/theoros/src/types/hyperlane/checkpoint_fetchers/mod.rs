@@ -1,10 +1,17 @@
+pub mod caching;
 pub mod gcs;
+pub mod ipfs;
 pub mod local;
+pub mod multi;
+pub mod opendal_storage;
+pub mod retrying;
 pub mod s3;
+pub mod sharded;
 
 // Source:
 // https://github.com/hyperlane-xyz/hyperlane-monorepo/blob/3e90734310fb1ca9a607ce3d334015fa7aaa9208/rust/hyperlane-base/src/settings/checkpoint_syncer.rs#L14
 
+use std::collections::BTreeMap;
 use std::fmt::Debug;
 use std::sync::Arc;
 use std::{env, path::PathBuf};
@@ -12,13 +19,17 @@ use std::{env, path::PathBuf};
 use anyhow::{anyhow, bail, Context, Error, Result};
 use async_trait::async_trait;
 use core::str::FromStr;
+use futures::{StreamExt, TryStreamExt};
 use rusoto_core::Region;
 use ya_gcp::{AuthFlow, ServiceAccountAuth};
 
+use crate::constants::FETCH_RANGE_CONCURRENCY;
 use crate::types::hyperlane::{
     gcs::{GcsStorageClientBuilder, GCS_SERVICE_ACCOUNT_KEY, GCS_USER_SECRET},
+    ipfs::{IpfsEndpoint, IpfsStorage},
     local::LocalStorage,
-    s3::S3Storage,
+    opendal_storage::{OpendalStorage, CHECKPOINT_STORAGE_BACKEND},
+    s3::{S3Storage, S3_AUTHENTICATED},
 };
 
 use super::SignedCheckpointWithMessageId;
@@ -29,9 +40,36 @@ pub trait FetchFromStorage: Debug + Send + Sync {
     /// Attempt to fetch the signed (checkpoint, messageId) tuple at this index
     #[allow(unused)]
     async fn fetch(&self, index: u32) -> Result<Option<SignedCheckpointWithMessageId>>;
+    /// Attempt to fetch the index of the latest checkpoint signed by the validator
+    async fn fetch_latest_index(&self) -> Result<Option<u32>>;
+    /// Attempt to fetch the signed checkpoints from `from` to `to` (inclusive), keyed by index.
+    /// Checkpoints not signed yet are left out.
+    ///
+    /// By default, the range is bounded by the latest index of the validator when it is known,
+    /// and the checkpoints are fetched concurrently.
+    async fn fetch_range(&self, from: u32, to: u32) -> Result<BTreeMap<u32, SignedCheckpointWithMessageId>> {
+        let to = match self.fetch_latest_index().await {
+            Ok(Some(latest_index)) => to.min(latest_index),
+            _ => to,
+        };
+        if from > to {
+            return Ok(BTreeMap::new());
+        }
+        futures::stream::iter(from..=to)
+            .map(|index| async move { Ok((index, self.fetch(index).await?)) })
+            .buffer_unordered(FETCH_RANGE_CONCURRENCY)
+            .try_filter_map(|(index, checkpoint)| async move { Ok(checkpoint.map(|c| (index, c))) })
+            .try_collect()
+            .await
+    }
     /// Return the announcement storage location for this syncer
     #[allow(unused)]
     fn announcement_location(&self) -> String;
+    /// Kind of storage the checkpoints are read from, e.g. `s3`, to label the metrics.
+    fn backend_name(&self) -> String {
+        let location = self.announcement_location();
+        location.split_once("://").map_or_else(|| String::from("unknown"), |(scheme, _)| scheme.to_owned())
+    }
 }
 
 #[derive(Debug, Clone, Eq, Hash, PartialEq)]
@@ -49,6 +87,8 @@ pub enum CheckpointStorage {
         folder: Option<String>,
         /// S3 Region
         region: Region,
+        /// Sign the requests with the default AWS credentials instead of reading the bucket anonymously
+        authenticated: bool,
     },
     /// A checkpoint storage on Google Cloud
     Gcs {
@@ -62,6 +102,27 @@ pub enum CheckpointStorage {
         /// `gcloud auth application-default login`
         user_secrets: Option<String>,
     },
+    /// A checkpoint storage on Azure Blob Storage, only read through opendal
+    Azure {
+        /// Storage account name
+        account: String,
+        /// Container name
+        container: String,
+        /// Folder name inside container - defaults to the root of the container
+        folder: Option<String>,
+    },
+    /// A checkpoint storage served over HTTP, only read through opendal
+    Http {
+        /// Base URL of the checkpoints
+        url: String,
+    },
+    /// A checkpoint storage on IPFS, resolved under an IPNS name
+    Ipfs {
+        /// IPNS name of the checkpoints directory
+        name: String,
+        /// Gateway or node API the checkpoints are read through
+        endpoint: IpfsEndpoint,
+    },
 }
 
 /// Builds a [CheckpointStorage] from a storage location.
@@ -89,6 +150,7 @@ impl FromStr for CheckpointStorage {
                     bucket: bucket.into(),
                     folder,
                     region: region.parse().context("Invalid region when parsing storage location")?,
+                    authenticated: env::var(S3_AUTHENTICATED).is_ok_and(|v| v.eq_ignore_ascii_case("true")),
                 })
             }
             "file" => Ok(CheckpointStorage::LocalStorage { path: suffix.into() }),
@@ -109,6 +171,23 @@ impl FromStr for CheckpointStorage {
                     Ok(Self::Gcs { bucket: suffix.into(), folder: None, service_account_key, user_secrets })
                 }
             }
+            "az" => {
+                let mut url_components = suffix.splitn(3, '/');
+                let (Some(account), Some(container)) = (url_components.next(), url_components.next()) else {
+                    bail!("Error parsing storage location; could not split account and container ({suffix})");
+                };
+                let folder = url_components.next().map(Into::into);
+                Ok(Self::Azure { account: account.into(), container: container.into(), folder })
+            }
+            "http" | "https" => Ok(Self::Http { url: s.into() }),
+            // the gateway or node API is picked from the env variables
+            "ipns" => {
+                let name = suffix.trim_end_matches('/');
+                if name.is_empty() || name.contains('/') {
+                    bail!("Error parsing storage location; invalid IPNS name ({suffix})");
+                }
+                Ok(Self::Ipfs { name: name.into(), endpoint: IpfsEndpoint::from_env() })
+            }
             _ => bail!("Unknown storage location prefix `{prefix}`"),
         }
     }
@@ -117,10 +196,19 @@ impl FromStr for CheckpointStorage {
 impl CheckpointStorage {
     /// Turn conf info a Checkpoint Syncer
     pub async fn build(&self) -> Result<Arc<dyn FetchFromStorage + Send + Sync>> {
+        let use_opendal = env::var(CHECKPOINT_STORAGE_BACKEND).is_ok_and(|v| v.eq_ignore_ascii_case("opendal"));
+        if use_opendal {
+            return Ok(Arc::new(OpendalStorage::new(self)?));
+        }
         Ok(match self {
             CheckpointStorage::LocalStorage { path } => Arc::new(LocalStorage::new(path.clone())?),
-            CheckpointStorage::S3 { bucket, folder, region } => {
-                Arc::new(S3Storage::new(bucket.clone(), folder.clone(), region.clone()))
+            CheckpointStorage::S3 { bucket, folder, region, authenticated } => {
+                let storage = S3Storage::new(bucket.clone(), folder.clone(), region.clone());
+                if *authenticated {
+                    Arc::new(storage.with_default_credentials()?)
+                } else {
+                    Arc::new(storage)
+                }
             }
             CheckpointStorage::Gcs { bucket, folder, service_account_key, user_secrets } => {
                 let auth = if let Some(path) = service_account_key {
@@ -134,6 +222,79 @@ impl CheckpointStorage {
 
                 Arc::new(GcsStorageClientBuilder::new(auth).build(bucket, folder.to_owned()).await?)
             }
+            CheckpointStorage::Azure { .. } | CheckpointStorage::Http { .. } => Arc::new(OpendalStorage::new(self)?),
+            CheckpointStorage::Ipfs { name, endpoint } => Arc::new(IpfsStorage::new(name.clone(), endpoint.clone())?),
         })
     }
+
+    /// The storage location, as announced by the validators.
+    pub fn location(&self) -> String {
+        let with_folder = |base: String, folder: &Option<String>| match folder.as_deref() {
+            None | Some("") => base,
+            Some(folder) => format!("{}/{}", base, folder.trim_start_matches('/')),
+        };
+        match self {
+            CheckpointStorage::LocalStorage { path } => format!("file://{}", path.display()),
+            CheckpointStorage::S3 { bucket, folder, region, .. } => {
+                with_folder(format!("s3://{}/{}", bucket, region.name()), folder)
+            }
+            CheckpointStorage::Gcs { bucket, folder, .. } => with_folder(format!("gs://{bucket}"), folder),
+            CheckpointStorage::Azure { account, container, folder } => {
+                with_folder(format!("az://{account}/{container}"), folder)
+            }
+            CheckpointStorage::Http { url } => url.clone(),
+            CheckpointStorage::Ipfs { name, .. } => format!("ipns://{name}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_s3_storage_location() {
+        let storage: CheckpointStorage = "s3://hyperlane-validator/us-east-1/pragma/checkpoints".parse().unwrap();
+        assert_eq!(
+            storage,
+            CheckpointStorage::S3 {
+                bucket: "hyperlane-validator".into(),
+                folder: Some("pragma/checkpoints".into()),
+                region: Region::UsEast1,
+                authenticated: false,
+            }
+        );
+        assert!("s3://hyperlane-validator".parse::<CheckpointStorage>().is_err());
+    }
+
+    #[test]
+    fn test_parse_opendal_only_storage_locations() {
+        let storage: CheckpointStorage = "az://pragma/validator/checkpoints".parse().unwrap();
+        assert_eq!(
+            storage,
+            CheckpointStorage::Azure {
+                account: "pragma".into(),
+                container: "validator".into(),
+                folder: Some("checkpoints".into()),
+            }
+        );
+        assert_eq!(storage.location(), "az://pragma/validator/checkpoints");
+        assert!("az://pragma".parse::<CheckpointStorage>().is_err());
+
+        let storage: CheckpointStorage = "https://checkpoints.pragma.build/validator".parse().unwrap();
+        assert_eq!(storage, CheckpointStorage::Http { url: "https://checkpoints.pragma.build/validator".into() });
+    }
+
+    #[test]
+    fn test_parse_ipfs_storage_location() {
+        let storage: CheckpointStorage =
+            "ipns://k51qzi5uqu5dlvj2baxnqndepeb86cbk3ng7n3i46uzyxzyqj2xjonzllnv0v8".parse().unwrap();
+        let CheckpointStorage::Ipfs { name, .. } = &storage else {
+            panic!("Expected an IPFS storage, got {storage:?}");
+        };
+        assert_eq!(name, "k51qzi5uqu5dlvj2baxnqndepeb86cbk3ng7n3i46uzyxzyqj2xjonzllnv0v8");
+        assert_eq!(storage.location(), "ipns://k51qzi5uqu5dlvj2baxnqndepeb86cbk3ng7n3i46uzyxzyqj2xjonzllnv0v8");
+        assert!("ipns://".parse::<CheckpointStorage>().is_err());
+        assert!("ipns://name/folder".parse::<CheckpointStorage>().is_err());
+    }
 }
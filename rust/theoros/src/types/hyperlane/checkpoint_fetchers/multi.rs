@@ -0,0 +1,215 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+
+use crate::types::hyperlane::{FetchFromStorage, SignedCheckpointWithMessageId};
+
+/// Tries, in order, a list of checkpoint fetchers - e.g. all the storage locations announced by a validator.
+///
+/// A backend failing or not answering within the timeout is skipped, so a single slow or misconfigured
+/// bucket doesn't block the collection of the signature.
+#[derive(Debug, Clone)]
+pub struct MultiStorageFetcher {
+    fetchers: Vec<Arc<dyn FetchFromStorage + Send + Sync>>,
+    /// Time allowed to each backend
+    timeout: Duration,
+}
+
+impl MultiStorageFetcher {
+    pub fn new(fetchers: Vec<Arc<dyn FetchFromStorage + Send + Sync>>, timeout: Duration) -> Self {
+        Self { fetchers, timeout }
+    }
+
+    /// Returns a fetcher trying `fetcher` first, then the current backends. A backend with the same
+    /// location as `fetcher` is replaced.
+    pub fn with_first(&self, fetcher: Arc<dyn FetchFromStorage + Send + Sync>) -> Self {
+        let location = fetcher.announcement_location();
+        let mut fetchers = Vec::with_capacity(self.fetchers.len() + 1);
+        fetchers.push(fetcher);
+        fetchers.extend(self.fetchers.iter().filter(|f| f.announcement_location() != location).cloned());
+        Self { fetchers, timeout: self.timeout }
+    }
+
+    pub fn len(&self) -> usize {
+        self.fetchers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fetchers.is_empty()
+    }
+}
+
+#[async_trait]
+impl FetchFromStorage for MultiStorageFetcher {
+    /// Returns the checkpoint of the first backend having it. Fails only if no backend could answer.
+    async fn fetch(&self, index: u32) -> Result<Option<SignedCheckpointWithMessageId>> {
+        let mut answered = false;
+        let mut last_error = None;
+        for fetcher in &self.fetchers {
+            match tokio::time::timeout(self.timeout, fetcher.fetch(index)).await {
+                Ok(Ok(Some(checkpoint))) => return Ok(Some(checkpoint)),
+                Ok(Ok(None)) => answered = true,
+                Ok(Err(e)) => {
+                    tracing::debug!(
+                        "Backend {} failed to fetch checkpoint #{}: {:?}",
+                        fetcher.announcement_location(),
+                        index,
+                        e
+                    );
+                    last_error = Some(e);
+                }
+                Err(_) => {
+                    tracing::debug!(
+                        "Backend {} timed out fetching checkpoint #{}",
+                        fetcher.announcement_location(),
+                        index
+                    );
+                    last_error =
+                        Some(anyhow!("{} timed out after {:?}", fetcher.announcement_location(), self.timeout));
+                }
+            }
+        }
+        match last_error {
+            Some(e) if !answered => Err(e),
+            _ => Ok(None),
+        }
+    }
+
+    /// Returns the highest latest index among the backends, as a validator may write to some of its locations
+    /// before the others. Fails only if no backend could answer.
+    async fn fetch_latest_index(&self) -> Result<Option<u32>> {
+        let futures = self.fetchers.iter().map(|fetcher| async move {
+            match tokio::time::timeout(self.timeout, fetcher.fetch_latest_index()).await {
+                Ok(result) => result,
+                Err(_) => Err(anyhow!("{} timed out after {:?}", fetcher.announcement_location(), self.timeout)),
+            }
+        });
+
+        let mut answered = false;
+        let mut latest_index = None;
+        let mut last_error = None;
+        for (fetcher, result) in self.fetchers.iter().zip(futures::future::join_all(futures).await) {
+            match result {
+                Ok(index) => {
+                    answered = true;
+                    latest_index = latest_index.max(index);
+                }
+                Err(e) => {
+                    tracing::debug!(
+                        "Backend {} failed to fetch the latest checkpoint index: {:?}",
+                        fetcher.announcement_location(),
+                        e
+                    );
+                    last_error = Some(e);
+                }
+            }
+        }
+        match last_error {
+            Some(e) if !answered => Err(e),
+            _ => Ok(latest_index),
+        }
+    }
+
+    fn announcement_location(&self) -> String {
+        self.fetchers.first().map(|fetcher| fetcher.announcement_location()).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::{Parity, U256};
+    use alloy::signers::Signature;
+
+    use super::*;
+    use crate::types::hyperlane::{Checkpoint, CheckpointWithMessageId, SignedType};
+
+    #[derive(Debug)]
+    enum MockBackend {
+        Slow,
+        Failing,
+        Empty,
+        Holding(&'static str),
+        Latest(u32),
+    }
+
+    #[async_trait]
+    impl FetchFromStorage for MockBackend {
+        async fn fetch(&self, index: u32) -> Result<Option<SignedCheckpointWithMessageId>> {
+            match self {
+                Self::Slow => {
+                    tokio::time::sleep(Duration::from_secs(60)).await;
+                    Ok(None)
+                }
+                Self::Failing => Err(anyhow!("access denied")),
+                Self::Empty | Self::Latest(_) => Ok(None),
+                Self::Holding(root) => Ok(Some(SignedType {
+                    value: CheckpointWithMessageId {
+                        checkpoint: Checkpoint {
+                            merkle_tree_hook_address: U256::ZERO,
+                            mailbox_domain: 0,
+                            root: root.to_string(),
+                            index,
+                        },
+                        message_id: U256::ZERO,
+                    },
+                    signature: Signature::new(U256::from(1), U256::from(2), Parity::Parity(false)),
+                })),
+            }
+        }
+
+        async fn fetch_latest_index(&self) -> Result<Option<u32>> {
+            match self {
+                Self::Slow => {
+                    tokio::time::sleep(Duration::from_secs(60)).await;
+                    Ok(None)
+                }
+                Self::Failing => Err(anyhow!("access denied")),
+                Self::Empty | Self::Holding(_) => Ok(None),
+                Self::Latest(index) => Ok(Some(*index)),
+            }
+        }
+
+        fn announcement_location(&self) -> String {
+            format!("mock://{:?}", self)
+        }
+    }
+
+    fn multi(backends: Vec<MockBackend>) -> MultiStorageFetcher {
+        let fetchers = backends.into_iter().map(|b| Arc::new(b) as Arc<dyn FetchFromStorage + Send + Sync>).collect();
+        MultiStorageFetcher::new(fetchers, Duration::from_millis(20))
+    }
+
+    #[tokio::test]
+    async fn test_falls_back_to_the_next_backend() {
+        let fetcher = multi(vec![MockBackend::Slow, MockBackend::Failing, MockBackend::Holding("0x01")]);
+        let checkpoint = fetcher.fetch(7).await.unwrap().unwrap();
+        assert_eq!(checkpoint.value.checkpoint.root, "0x01");
+        assert_eq!(checkpoint.value.checkpoint.index, 7);
+
+        assert_eq!(multi(vec![MockBackend::Failing, MockBackend::Empty]).fetch(7).await.unwrap(), None);
+        assert!(multi(vec![MockBackend::Slow, MockBackend::Failing]).fetch(7).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_with_first_replaces_the_same_location() {
+        let fetcher = multi(vec![MockBackend::Holding("0x01"), MockBackend::Empty])
+            .with_first(Arc::new(MockBackend::Holding("0x01")));
+        assert_eq!(fetcher.len(), 2);
+
+        let fetcher = fetcher.with_first(Arc::new(MockBackend::Holding("0x02")));
+        assert_eq!(fetcher.len(), 3);
+        assert_eq!(fetcher.fetch(1).await.unwrap().unwrap().value.checkpoint.root, "0x02");
+    }
+
+    #[tokio::test]
+    async fn test_latest_index_is_the_highest_among_backends() {
+        let fetcher =
+            multi(vec![MockBackend::Latest(4), MockBackend::Slow, MockBackend::Latest(9), MockBackend::Empty]);
+        assert_eq!(fetcher.fetch_latest_index().await.unwrap(), Some(9));
+
+        assert_eq!(multi(vec![MockBackend::Failing, MockBackend::Empty]).fetch_latest_index().await.unwrap(), None);
+        assert!(multi(vec![MockBackend::Slow, MockBackend::Failing]).fetch_latest_index().await.is_err());
+    }
+}
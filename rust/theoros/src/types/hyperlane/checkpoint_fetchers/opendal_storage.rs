@@ -0,0 +1,155 @@
+use std::fmt;
+
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use opendal::{
+    layers::{RetryLayer, TimeoutLayer},
+    services, ErrorKind, Operator,
+};
+use rusoto_core::Region;
+
+use crate::constants::{STORAGE_REQUEST_MAX_RETRIES, STORAGE_REQUEST_TIMEOUT};
+use crate::types::hyperlane::{ipfs::IpfsEndpoint, CheckpointStorage, FetchFromStorage, SignedCheckpointWithMessageId};
+
+/// Selects the implementation of the checkpoint fetchers: `native` (default) uses a dedicated client per
+/// backend, `opendal` reads every backend through [OpendalStorage].
+pub const CHECKPOINT_STORAGE_BACKEND: &str = "CHECKPOINT_STORAGE_BACKEND";
+
+/// Reads the checkpoints of any storage location through [opendal], so all the backends share the same
+/// implementation, retries & timeouts.
+///
+/// Also reads the locations only supported through opendal: Azure Blob Storage containers & HTTP servers.
+pub struct OpendalStorage {
+    operator: Operator,
+    location: String,
+    /// Whether the Hyperlane local checkpoint syncer layout (`{index}_with_id.json` & `index.json`) is
+    /// also tried, as for [super::local::LocalStorage].
+    hyperlane_layout: bool,
+}
+
+impl fmt::Debug for OpendalStorage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OpendalStorage").field("location", &self.location).finish()
+    }
+}
+
+impl OpendalStorage {
+    pub fn new(storage: &CheckpointStorage) -> Result<Self> {
+        let operator = match storage {
+            CheckpointStorage::LocalStorage { path } => {
+                std::fs::create_dir_all(path).with_context(|| {
+                    format!("Failed to create local checkpoint fetcher storage directory at {:?}", path)
+                })?;
+                Operator::new(services::Fs::default().root(&path.to_string_lossy()))?.finish()
+            }
+            CheckpointStorage::S3 { bucket, folder, region, authenticated } => {
+                let mut builder =
+                    services::S3::default().bucket(bucket).region(region.name()).root(folder.as_deref().unwrap_or("/"));
+                if let Region::Custom { endpoint, .. } = region {
+                    builder = builder.endpoint(endpoint);
+                }
+                if !authenticated {
+                    builder = builder.allow_anonymous().disable_config_load().disable_ec2_metadata();
+                }
+                Operator::new(builder)?.finish()
+            }
+            CheckpointStorage::Gcs { bucket, folder, service_account_key, user_secrets } => {
+                let mut builder = services::Gcs::default().bucket(bucket).root(folder.as_deref().unwrap_or("/"));
+                match service_account_key.as_ref().or(user_secrets.as_ref()) {
+                    Some(credential_path) => builder = builder.credential_path(credential_path),
+                    None => builder = builder.allow_anonymous(),
+                }
+                Operator::new(builder)?.finish()
+            }
+            CheckpointStorage::Azure { account, container, folder } => {
+                let builder = services::Azblob::default()
+                    .account_name(account)
+                    .container(container)
+                    .endpoint(&format!("https://{account}.blob.core.windows.net"))
+                    .root(folder.as_deref().unwrap_or("/"));
+                Operator::new(builder)?.finish()
+            }
+            CheckpointStorage::Http { url } => Operator::new(services::Http::default().endpoint(url))?.finish(),
+            CheckpointStorage::Ipfs { name, endpoint: IpfsEndpoint::Gateway(url) } => {
+                let endpoint = format!("{}/ipns/{name}", url.trim_end_matches('/'));
+                Operator::new(services::Http::default().endpoint(&endpoint))?.finish()
+            }
+            CheckpointStorage::Ipfs { endpoint: IpfsEndpoint::NodeApi(_), .. } => {
+                bail!("The IPFS node API can't be read through opendal, use a gateway instead")
+            }
+        };
+
+        let operator = operator
+            .layer(TimeoutLayer::new().with_io_timeout(STORAGE_REQUEST_TIMEOUT))
+            .layer(RetryLayer::new().with_max_times(STORAGE_REQUEST_MAX_RETRIES).with_jitter());
+        let hyperlane_layout = matches!(storage, CheckpointStorage::LocalStorage { .. });
+        Ok(Self { operator, location: storage.location(), hyperlane_layout })
+    }
+
+    /// Reads the first of the objects that exists, returning `None` if none does.
+    async fn read_first(&self, keys: &[String]) -> Result<Option<Vec<u8>>> {
+        for key in keys {
+            match self.operator.read(key).await {
+                Ok(data) => return Ok(Some(data.to_vec())),
+                Err(e) if e.kind() == ErrorKind::NotFound => continue,
+                Err(e) => return Err(e).with_context(|| format!("Failed to read {key} from {}", self.location)),
+            }
+        }
+        Ok(None)
+    }
+}
+
+#[async_trait]
+impl FetchFromStorage for OpendalStorage {
+    async fn fetch(&self, index: u32) -> Result<Option<SignedCheckpointWithMessageId>> {
+        let mut keys = vec![format!("checkpoint_{index}_with_id.json")];
+        if self.hyperlane_layout {
+            keys.push(format!("{index}_with_id.json"));
+        }
+        let Some(data) = self.read_first(&keys).await? else {
+            return Ok(None);
+        };
+        let checkpoint = serde_json::from_slice(&data)
+            .with_context(|| format!("Invalid checkpoint {index} in storage {}", self.location))?;
+        Ok(Some(checkpoint))
+    }
+
+    async fn fetch_latest_index(&self) -> Result<Option<u32>> {
+        let mut keys = vec!["checkpoint_latest_index.json".to_owned()];
+        if self.hyperlane_layout {
+            keys.push("index.json".to_owned());
+        }
+        let Some(data) = self.read_first(&keys).await? else {
+            return Ok(None);
+        };
+        let index = serde_json::from_slice(&data)
+            .with_context(|| format!("Invalid latest index in storage {}", self.location))?;
+        Ok(Some(index))
+    }
+
+    fn announcement_location(&self) -> String {
+        self.location.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_reads_local_checkpoints_through_opendal() {
+        let path = std::env::temp_dir().join(format!("theoros-opendal-storage-{}", std::process::id()));
+        let storage = OpendalStorage::new(&CheckpointStorage::LocalStorage { path: path.clone() }).unwrap();
+        assert!(storage.fetch(1).await.unwrap().is_none());
+        assert_eq!(storage.fetch_latest_index().await.unwrap(), None);
+
+        std::fs::write(path.join("index.json"), b"2").unwrap();
+        assert_eq!(storage.fetch_latest_index().await.unwrap(), Some(2));
+        std::fs::write(path.join("checkpoint_latest_index.json"), b"3").unwrap();
+        assert_eq!(storage.fetch_latest_index().await.unwrap(), Some(3));
+
+        std::fs::write(path.join("checkpoint_3_with_id.json"), b"not a checkpoint").unwrap();
+        assert!(storage.fetch(3).await.is_err());
+        std::fs::remove_dir_all(&path).unwrap();
+    }
+}
@@ -0,0 +1,201 @@
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use rand::Rng;
+
+use crate::constants::{
+    DEFAULT_CHECKPOINT_FETCH_MAX_ATTEMPTS, DEFAULT_CHECKPOINT_FETCH_RETRY_DELAY, DEFAULT_CHECKPOINT_FETCH_RETRY_JITTER,
+};
+use crate::types::hyperlane::{FetchFromStorage, SignedCheckpointWithMessageId};
+
+/// How the checkpoint fetches failing with a transient error are retried.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetryPolicy {
+    /// Attempts of a fetch, the first one included. `1` disables the retries.
+    pub max_attempts: u32,
+    /// Delay before the first retry, doubled at each retry.
+    pub base_delay: Duration,
+    /// Fraction of the delay randomly added or removed, so the fetches failing together don't retry together.
+    pub jitter: f64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: DEFAULT_CHECKPOINT_FETCH_MAX_ATTEMPTS,
+            base_delay: DEFAULT_CHECKPOINT_FETCH_RETRY_DELAY,
+            jitter: DEFAULT_CHECKPOINT_FETCH_RETRY_JITTER,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Delay before the retry following the `attempt`th attempt, starting at 1.
+    fn delay(&self, attempt: u32) -> Duration {
+        let delay = self.base_delay.saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)));
+        if self.jitter == 0.0 {
+            return delay;
+        }
+        delay.mul_f64(1.0 + rand::thread_rng().gen_range(-self.jitter..=self.jitter))
+    }
+}
+
+/// A malformed checkpoint stays malformed: only the errors of the storage itself are retried.
+fn is_transient(error: &anyhow::Error) -> bool {
+    !error.chain().any(|cause| cause.is::<serde_json::Error>())
+}
+
+/// Retries the fetches of a checkpoint fetcher failing with a transient error, with an exponential backoff.
+///
+/// A checkpoint missing from the storage isn't an error, so it isn't retried: the validator didn't sign it yet.
+#[derive(Debug, Clone)]
+pub struct RetryingFetcher {
+    fetcher: Arc<dyn FetchFromStorage + Send + Sync>,
+    policy: RetryPolicy,
+}
+
+impl RetryingFetcher {
+    pub fn new(fetcher: Arc<dyn FetchFromStorage + Send + Sync>, policy: RetryPolicy) -> Self {
+        Self { fetcher, policy }
+    }
+
+    /// Runs the fetch until it succeeds, fails with an error retrying won't fix, or runs out of attempts.
+    async fn retry<T, F, Fut>(&self, what: &str, fetch: F) -> Result<T>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let mut attempt = 1;
+        loop {
+            match fetch().await {
+                Err(e) if attempt < self.policy.max_attempts && is_transient(&e) => {
+                    let delay = self.policy.delay(attempt);
+                    tracing::debug!(
+                        "Fetching {} from {} failed (attempt {}/{}), retrying in {:?}: {:?}",
+                        what,
+                        self.fetcher.announcement_location(),
+                        attempt,
+                        self.policy.max_attempts,
+                        delay,
+                        e
+                    );
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl FetchFromStorage for RetryingFetcher {
+    async fn fetch(&self, index: u32) -> Result<Option<SignedCheckpointWithMessageId>> {
+        self.retry(&format!("checkpoint #{index}"), || self.fetcher.fetch(index)).await
+    }
+
+    async fn fetch_latest_index(&self) -> Result<Option<u32>> {
+        self.retry("the latest checkpoint index", || self.fetcher.fetch_latest_index()).await
+    }
+
+    /// Retries the whole range, as backends may fetch it at once.
+    async fn fetch_range(&self, from: u32, to: u32) -> Result<BTreeMap<u32, SignedCheckpointWithMessageId>> {
+        self.retry(&format!("checkpoints #{from} to #{to}"), || self.fetcher.fetch_range(from, to)).await
+    }
+
+    fn announcement_location(&self) -> String {
+        self.fetcher.announcement_location()
+    }
+
+    fn backend_name(&self) -> String {
+        self.fetcher.backend_name()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    use anyhow::anyhow;
+
+    use super::*;
+
+    /// Fails with the given error for the first `failures` attempts, then finds no checkpoint.
+    #[derive(Debug)]
+    struct FlakyBackend {
+        failures: u32,
+        malformed: bool,
+        attempts: AtomicU32,
+    }
+
+    impl FlakyBackend {
+        fn new(failures: u32, malformed: bool) -> Arc<Self> {
+            Arc::new(Self { failures, malformed, attempts: AtomicU32::new(0) })
+        }
+    }
+
+    #[async_trait]
+    impl FetchFromStorage for FlakyBackend {
+        async fn fetch(&self, _index: u32) -> Result<Option<SignedCheckpointWithMessageId>> {
+            if self.attempts.fetch_add(1, Ordering::SeqCst) >= self.failures {
+                return Ok(None);
+            }
+            if self.malformed {
+                return Err(serde_json::from_str::<SignedCheckpointWithMessageId>("{}").unwrap_err().into());
+            }
+            Err(anyhow!("503 Service Unavailable"))
+        }
+
+        async fn fetch_latest_index(&self) -> Result<Option<u32>> {
+            Ok(None)
+        }
+
+        fn announcement_location(&self) -> String {
+            String::from("mock://flaky")
+        }
+    }
+
+    fn retrying(backend: Arc<FlakyBackend>) -> RetryingFetcher {
+        RetryingFetcher::new(
+            backend,
+            RetryPolicy { max_attempts: 3, base_delay: Duration::from_millis(1), jitter: 0.5 },
+        )
+    }
+
+    #[tokio::test]
+    async fn test_transient_errors_are_retried() {
+        let backend = FlakyBackend::new(2, false);
+        assert_eq!(retrying(backend.clone()).fetch(7).await.unwrap(), None);
+        assert_eq!(backend.attempts.load(Ordering::SeqCst), 3);
+
+        let backend = FlakyBackend::new(3, false);
+        assert!(retrying(backend.clone()).fetch(7).await.is_err());
+        assert_eq!(backend.attempts.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_missing_and_malformed_checkpoints_are_not_retried() {
+        let backend = FlakyBackend::new(0, false);
+        assert_eq!(retrying(backend.clone()).fetch(7).await.unwrap(), None);
+        assert_eq!(backend.attempts.load(Ordering::SeqCst), 1);
+
+        let backend = FlakyBackend::new(1, true);
+        assert!(retrying(backend.clone()).fetch(7).await.is_err());
+        assert_eq!(backend.attempts.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_retry_delays() {
+        let policy = RetryPolicy { max_attempts: 4, base_delay: Duration::from_millis(100), jitter: 0.0 };
+        assert_eq!(policy.delay(1), Duration::from_millis(100));
+        assert_eq!(policy.delay(3), Duration::from_millis(400));
+
+        let policy = RetryPolicy { jitter: 0.2, ..policy };
+        let delay = policy.delay(2);
+        assert!(delay >= Duration::from_millis(160) && delay <= Duration::from_millis(240), "{delay:?}");
+    }
+}
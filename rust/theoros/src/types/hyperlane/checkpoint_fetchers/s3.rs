@@ -6,7 +6,7 @@ use anyhow::{bail, Result};
 use async_trait::async_trait;
 use futures_util::TryStreamExt;
 use rusoto_core::{
-    credential::{Anonymous, AwsCredentials, StaticProvider},
+    credential::{Anonymous, AwsCredentials, DefaultCredentialsProvider, StaticProvider},
     Region, RusotoError,
 };
 use rusoto_s3::{GetObjectError, GetObjectRequest, S3Client, S3};
@@ -21,6 +21,10 @@ use crate::types::hyperlane::{FetchFromStorage, SignedCheckpointWithMessageId};
 /// See https://github.com/rusoto/rusoto/issues/1795.
 const S3_REQUEST_TIMEOUT_SECONDS: u64 = 30;
 
+/// When set to `true`, S3 requests are signed with the default AWS credentials
+/// (environment, profile or instance metadata) instead of being anonymous.
+pub const S3_AUTHENTICATED: &str = "S3_AUTHENTICATED";
+
 #[derive(Clone)]
 /// Type for reading/writing to S3
 pub struct S3Storage {
@@ -32,6 +36,8 @@ pub struct S3Storage {
     region: Region,
     /// A client without credentials for anonymous requests.
     anonymous_client: OnceLock<S3Client>,
+    /// A client signing requests with the default AWS credentials, used instead of the anonymous one if set.
+    authenticated_client: Option<S3Client>,
 }
 
 impl fmt::Debug for S3Storage {
@@ -40,22 +46,33 @@ impl fmt::Debug for S3Storage {
             .field("bucket", &self.bucket)
             .field("folder", &self.folder)
             .field("region", &self.region)
+            .field("authenticated", &self.authenticated_client.is_some())
             .finish()
     }
 }
 
 impl S3Storage {
-    /// Creates a new S3Storage.
+    /// Creates a new S3Storage, reading the bucket anonymously.
     pub fn new(bucket: String, folder: Option<String>, region: Region) -> Self {
-        S3Storage { bucket, folder, region, anonymous_client: Default::default() }
+        S3Storage { bucket, folder, region, anonymous_client: Default::default(), authenticated_client: None }
+    }
+
+    /// Signs the requests with the default AWS credentials, for buckets that aren't publicly accessible.
+    pub fn with_default_credentials(mut self) -> Result<Self> {
+        let client =
+            S3Client::new_with(http_client_with_timeout()?, DefaultCredentialsProvider::new()?, self.region.clone());
+        self.authenticated_client = Some(client);
+        Ok(self)
     }
 
-    /// Uses an anonymous client. This should only be used for publicly accessible buckets.
-    async fn anonymously_read_from_bucket(&self, key: String) -> Result<Option<Vec<u8>>> {
+    /// Reads an object of the bucket, using the authenticated client if configured.
+    /// Otherwise, the bucket must be publicly accessible.
+    async fn read_from_bucket(&self, key: String) -> Result<Option<Vec<u8>>> {
         let req =
             GetObjectRequest { key: self.get_composite_key(key), bucket: self.bucket.clone(), ..Default::default() };
+        let client = self.authenticated_client.as_ref().unwrap_or_else(|| self.anonymous_client());
         let get_object_result =
-            timeout(Duration::from_secs(S3_REQUEST_TIMEOUT_SECONDS), self.anonymous_client().get_object(req)).await?;
+            timeout(Duration::from_secs(S3_REQUEST_TIMEOUT_SECONDS), client.get_object(req)).await?;
 
         match get_object_result {
             Ok(res) => match res.body {
@@ -98,12 +115,24 @@ impl S3Storage {
     fn checkpoint_key(index: u32) -> String {
         format!("checkpoint_{index}_with_id.json")
     }
+
+    fn latest_index_key() -> String {
+        "checkpoint_latest_index.json".to_owned()
+    }
 }
 
 #[async_trait]
 impl FetchFromStorage for S3Storage {
     async fn fetch(&self, index: u32) -> Result<Option<SignedCheckpointWithMessageId>> {
-        self.anonymously_read_from_bucket(S3Storage::checkpoint_key(index))
+        self.read_from_bucket(S3Storage::checkpoint_key(index))
+            .await?
+            .map(|data| serde_json::from_slice(&data))
+            .transpose()
+            .map_err(Into::into)
+    }
+
+    async fn fetch_latest_index(&self) -> Result<Option<u32>> {
+        self.read_from_bucket(S3Storage::latest_index_key())
             .await?
             .map(|data| serde_json::from_slice(&data))
             .transpose()
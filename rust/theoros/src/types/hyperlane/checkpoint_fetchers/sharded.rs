@@ -0,0 +1,238 @@
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use alloy::primitives::keccak256;
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use dashmap::DashMap;
+use starknet::core::types::Felt;
+use url::Url;
+
+use crate::constants::{CHECKPOINT_SHARD_SIZE, SHARD_TAKEOVER_DELAY};
+use crate::types::hyperlane::{
+    opendal_storage::OpendalStorage, CheckpointStorage, FetchFromStorage, SignedCheckpointWithMessageId,
+};
+
+/// Age after which a checkpoint still missing from its owner is forgotten: it is taken over by the first fetch
+/// after [SHARD_TAKEOVER_DELAY], so it is only that old if it isn't fetched anymore, e.g. never signed.
+const FORGET_MISSING_AFTER: Duration = SHARD_TAKEOVER_DELAY.saturating_mul(4);
+
+/// Another instance polling the same validators, sharing the checkpoints it fetched through
+/// `/v1/checkpoints`. Parsed from `{instance_id}={url}`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PeerInstance {
+    pub id: String,
+    pub url: Url,
+}
+
+impl FromStr for PeerInstance {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (id, url) = s.split_once('=').ok_or_else(|| anyhow!("Expected `{{instance_id}}={{url}}`, got `{s}`"))?;
+        Ok(Self { id: id.trim().to_owned(), url: Url::parse(url.trim())? })
+    }
+}
+
+/// Shards the checkpoints to fetch between cooperating instances, so each checkpoint is read from the
+/// validators storage by a single instance.
+///
+/// Checkpoints are grouped by validator & by ranges of [CHECKPOINT_SHARD_SIZE] indexes, each group being
+/// assigned to an instance through rendezvous hashing on the instance ids: every instance computes the
+/// same assignment, & only the groups of a leaving instance move. The checkpoints of the other instances
+/// are read from them, & from the validators storage if they are unreachable or still don't have them
+/// after [SHARD_TAKEOVER_DELAY].
+#[derive(Debug, Clone)]
+pub struct FetchSharding {
+    instance_id: String,
+    peers: Arc<Vec<PeerInstance>>,
+    /// Fetchers reading the checkpoints of a validator shared by a peer, by peer id.
+    peer_fetchers: Arc<DashMap<(String, Felt), Arc<dyn FetchFromStorage + Send + Sync>>>,
+    /// When the checkpoints were first missing from the peer owning them.
+    missing_since: Arc<DashMap<(Felt, u32), Instant>>,
+}
+
+impl FetchSharding {
+    pub fn new(instance_id: String, peers: Vec<PeerInstance>) -> Self {
+        let peers = peers.into_iter().filter(|peer| peer.id != instance_id).collect();
+        Self {
+            instance_id,
+            peers: Arc::new(peers),
+            peer_fetchers: Arc::new(DashMap::new()),
+            missing_since: Arc::new(DashMap::new()),
+        }
+    }
+
+    /// Returns the peer owning the checkpoint, or `None` if it is owned by this instance.
+    pub fn owner(&self, validator: Felt, index: u32) -> Option<&PeerInstance> {
+        let shard = index / CHECKPOINT_SHARD_SIZE;
+        let own_score = Self::score(&self.instance_id, validator, shard);
+        self.peers
+            .iter()
+            .map(|peer| (peer, Self::score(&peer.id, validator, shard)))
+            .filter(|(_, score)| *score > own_score)
+            .max_by_key(|(_, score)| *score)
+            .map(|(peer, _)| peer)
+    }
+
+    fn score(instance_id: &str, validator: Felt, shard: u32) -> u64 {
+        let mut key = Vec::with_capacity(instance_id.len() + 36);
+        key.extend_from_slice(instance_id.as_bytes());
+        key.extend_from_slice(&validator.to_bytes_be());
+        key.extend_from_slice(&shard.to_be_bytes());
+        let hash = keccak256(key);
+        u64::from_be_bytes(hash[..8].try_into().expect("hash is 32 bytes"))
+    }
+
+    /// Wraps the fetcher of the validator so it only reads the checkpoints owned by this instance.
+    pub fn shard(
+        &self,
+        validator: Felt,
+        fetcher: Arc<dyn FetchFromStorage + Send + Sync>,
+    ) -> Arc<dyn FetchFromStorage + Send + Sync> {
+        if self.peers.is_empty() {
+            return fetcher;
+        }
+        Arc::new(ShardedFetcher { validator, fetcher, sharding: self.clone() })
+    }
+
+    fn peer_fetcher(&self, peer: &PeerInstance, validator: Felt) -> Result<Arc<dyn FetchFromStorage + Send + Sync>> {
+        let key = (peer.id.clone(), validator);
+        if let Some(fetcher) = self.peer_fetchers.get(&key) {
+            return Ok(fetcher.clone());
+        }
+        let url = format!("{}/v1/checkpoints/{:#x}", peer.url.as_str().trim_end_matches('/'), validator);
+        let fetcher: Arc<dyn FetchFromStorage + Send + Sync> =
+            Arc::new(OpendalStorage::new(&CheckpointStorage::Http { url })?);
+        self.peer_fetchers.insert(key, fetcher.clone());
+        Ok(fetcher)
+    }
+
+    /// Whether the checkpoint has been missing from its owner for too long, in which case it is read
+    /// from the validator storage.
+    fn owner_missed(&self, validator: Felt, index: u32) -> bool {
+        let since = match self.missing_since.get(&(validator, index)).map(|since| *since) {
+            Some(since) => since,
+            None => {
+                self.forget_stale_misses();
+                *self.missing_since.entry((validator, index)).or_insert_with(Instant::now)
+            }
+        };
+        if since.elapsed() < SHARD_TAKEOVER_DELAY {
+            return false;
+        }
+        self.missing_since.remove(&(validator, index));
+        true
+    }
+
+    /// Forgets the checkpoints missing for longer than [FORGET_MISSING_AFTER], so the indexes a validator never
+    /// signs don't pile up.
+    fn forget_stale_misses(&self) {
+        self.missing_since.retain(|_, since| since.elapsed() < FORGET_MISSING_AFTER);
+    }
+}
+
+/// Reads the checkpoints owned by this instance from the validator storage & the others from their owner.
+#[derive(Debug)]
+pub struct ShardedFetcher {
+    validator: Felt,
+    fetcher: Arc<dyn FetchFromStorage + Send + Sync>,
+    sharding: FetchSharding,
+}
+
+#[async_trait]
+impl FetchFromStorage for ShardedFetcher {
+    async fn fetch(&self, index: u32) -> Result<Option<SignedCheckpointWithMessageId>> {
+        let Some(owner) = self.sharding.owner(self.validator, index) else {
+            return self.fetcher.fetch(index).await;
+        };
+        let fetched = match self.sharding.peer_fetcher(owner, self.validator) {
+            Ok(peer_fetcher) => peer_fetcher.fetch(index).await,
+            Err(e) => Err(e),
+        };
+        match fetched {
+            Ok(Some(checkpoint)) => {
+                self.sharding.missing_since.remove(&(self.validator, index));
+                Ok(Some(checkpoint))
+            }
+            Ok(None) if !self.sharding.owner_missed(self.validator, index) => Ok(None),
+            Ok(None) => self.fetcher.fetch(index).await,
+            Err(e) => {
+                tracing::debug!(
+                    "🌉 [Hyperlane] Instance {} could not share checkpoint #{} of validator {:#x}: {:?}",
+                    owner.id,
+                    index,
+                    self.validator,
+                    e
+                );
+                self.fetcher.fetch(index).await
+            }
+        }
+    }
+
+    /// The latest index is always read from the validator storage, to know which checkpoints to fetch.
+    async fn fetch_latest_index(&self) -> Result<Option<u32>> {
+        self.fetcher.fetch_latest_index().await
+    }
+
+    fn announcement_location(&self) -> String {
+        self.fetcher.announcement_location()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn peer(id: &str) -> PeerInstance {
+        PeerInstance::from_str(&format!("{id}=http://{id}:3000")).unwrap()
+    }
+
+    #[test]
+    fn test_each_checkpoint_is_owned_by_a_single_instance() {
+        let ids = ["theoros-0", "theoros-1", "theoros-2"];
+        let instances: Vec<FetchSharding> =
+            ids.iter().map(|id| FetchSharding::new(id.to_string(), ids.iter().map(|id| peer(id)).collect())).collect();
+
+        let mut owned = [0; 3];
+        for validator in 1..=8_u64 {
+            for index in (0..100 * CHECKPOINT_SHARD_SIZE).step_by(CHECKPOINT_SHARD_SIZE as usize) {
+                let owners: Vec<usize> = instances
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, instance)| instance.owner(Felt::from(validator), index).is_none())
+                    .map(|(i, _)| i)
+                    .collect();
+                assert_eq!(owners.len(), 1);
+                // The other instances agree on the owner.
+                let owner_id = ids[owners[0]];
+                for (i, instance) in instances.iter().enumerate().filter(|(i, _)| *i != owners[0]) {
+                    assert_eq!(instance.owner(Felt::from(validator), index).unwrap().id, owner_id, "{i}");
+                }
+                owned[owners[0]] += 1;
+            }
+        }
+        assert!(owned.iter().all(|&count| count > 200), "{owned:?}");
+    }
+
+    #[test]
+    fn test_stale_misses_are_forgotten() {
+        let sharding = FetchSharding::new(String::from("theoros-0"), vec![peer("theoros-1")]);
+        let stale = Instant::now().checked_sub(FORGET_MISSING_AFTER + Duration::from_secs(1)).unwrap();
+        sharding.missing_since.insert((Felt::ONE, 1), stale);
+        let recent = Instant::now().checked_sub(SHARD_TAKEOVER_DELAY / 2).unwrap();
+        sharding.missing_since.insert((Felt::ONE, 2), recent);
+
+        assert!(!sharding.owner_missed(Felt::ONE, 3));
+        assert!(!sharding.missing_since.contains_key(&(Felt::ONE, 1)));
+        assert!(sharding.missing_since.contains_key(&(Felt::ONE, 2)));
+        assert!(sharding.missing_since.contains_key(&(Felt::ONE, 3)));
+    }
+
+    #[test]
+    fn test_parse_peer_instance() {
+        assert_eq!(peer("a").url.as_str(), "http://a:3000/");
+        assert!(PeerInstance::from_str("http://a:3000").is_err());
+    }
+}
@@ -1,14 +1,22 @@
-use alloy::primitives::hex;
+use alloy::primitives::{hex, keccak256, B256};
 use anyhow::{Context, Result};
-use pragma_feeds::FeedType;
+use bytes::Bytes;
 use starknet::core::types::{Felt, U256};
 
 use pragma_utils::conversions::apibara::FromFieldBytes;
 
-use super::FromStarknetEventData;
+use crate::rpc::evm::calldata;
 
+use super::{ByteReader, DispatchParseError, FromStarknetEventData, UPDATE_CODECS};
+
+/// Felts of the header, followed by the size of the body in bytes & its number of words.
 const MESSAGE_HEADER_FELT_SIZE: usize = 10;
-const SPOT_MEDIAN_UPDATE_SIZE: usize = 107;
+/// Size of the header of a message packed to compute its id.
+const PACKED_HEADER_SIZE: usize = 1 + 4 + 4 + 32 + 4 + 32;
+/// Set on the feed type of length-prefixed updates, which can be skipped when their feed type is unknown.
+const OPAQUE_FEED_TYPE_FLAG: u16 = 0x8000;
+/// Size of the asset class, feed type & pair id starting every update.
+const UPDATE_HEADER_SIZE: usize = 2 + 2 + 28;
 
 #[derive(Debug, Clone)]
 pub struct DispatchEvent {
@@ -48,6 +56,10 @@ pub struct DispatchEvent {
 //            - decimals
 //            - timestamp
 //            - sources_aggregated
+//            [if the data_type has the OPAQUE_FEED_TYPE_FLAG set, the update is length-prefixed:]
+//            - pair_id
+//            - length (2 bytes)
+//            - data (length bytes, e.g. the SpotMedian fields after pair_id)
 impl FromStarknetEventData for DispatchEvent {
     fn from_starknet_event_data(data: Vec<Felt>) -> Result<Self> {
         let mut data = data.iter();
@@ -64,9 +76,12 @@ impl FromStarknetEventData for DispatchEvent {
             u128::from_field_bytes(data.next().context("Missing recipient part 2")?.to_bytes_be()),
         );
 
-        let header = DispatchMessageHeader::from_starknet_event_data(data.clone().cloned().collect())?;
-        let body_data: Vec<Felt> = data.skip(MESSAGE_HEADER_FELT_SIZE).cloned().collect();
-        let body = DispatchMessageBody::from_starknet_event_data(body_data)?;
+        let message_data = data.as_slice();
+        let header = DispatchMessageHeader::from_felts(message_data)?;
+        let body_size =
+            message_data.get(MESSAGE_HEADER_FELT_SIZE - 2).map(|size| u32::from_field_bytes(size.to_bytes_be()));
+        let body_words = message_data.get(MESSAGE_HEADER_FELT_SIZE..).unwrap_or_default();
+        let body = DispatchMessageBody::from_bytes(body_bytes(body_words, body_size))?;
 
         let message = DispatchMessage { header, body };
 
@@ -75,27 +90,67 @@ impl FromStarknetEventData for DispatchEvent {
     }
 }
 
+impl DispatchEvent {
+    /// Reads the destination domain of a Dispatch starknet event data, without decoding its message.
+    pub fn destination_domain_of(data: &[Felt]) -> Option<u32> {
+        data.get(2).map(|destination| u32::from_field_bytes(destination.to_bytes_be()))
+    }
+
+    /// Id of the message dispatched by a Dispatch starknet event data, signed by the validators along with the
+    /// checkpoint of its nonce.
+    pub fn message_id_of(data: &[Felt]) -> Result<B256> {
+        Ok(Self::from_starknet_event_data(data.to_vec())?.message.id())
+    }
+
+    /// Address of the Pragma dispatcher which sent the message.
+    pub fn emitter_address(&self) -> Felt {
+        Felt::from_bytes_be(&u256_to_be_bytes(&self.message.header.sender))
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct DispatchMessage {
     pub header: DispatchMessageHeader,
     pub body: DispatchMessageBody,
 }
 
+impl DispatchMessage {
+    /// Id of the message, signed by the validators along with the checkpoint of its nonce: the keccak256 hash of
+    /// the message packed as on every Hyperlane chain, i.e. its version (1 byte), nonce (4), origin (4), sender
+    /// (32), destination (4), recipient (32) & body.
+    pub fn id(&self) -> B256 {
+        let header = &self.header;
+        let mut packed = Vec::with_capacity(PACKED_HEADER_SIZE + self.body.bytes.len());
+        packed.push(header.version);
+        packed.extend_from_slice(&header.nonce.to_be_bytes());
+        packed.extend_from_slice(&header.origin.to_be_bytes());
+        packed.extend_from_slice(&u256_to_be_bytes(&header.sender));
+        packed.extend_from_slice(&header.destination.to_be_bytes());
+        packed.extend_from_slice(&u256_to_be_bytes(&header.recipient));
+        packed.extend_from_slice(&self.body.bytes);
+        keccak256(packed)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct DispatchMessageHeader {
-    #[allow(unused)]
     pub version: u8,
     pub nonce: u32,
     pub origin: u32,
     pub sender: U256,
-    #[allow(unused)]
     pub destination: u32,
-    #[allow(unused)]
     pub recipient: U256,
 }
 
 impl FromStarknetEventData for DispatchMessageHeader {
     fn from_starknet_event_data(data: Vec<Felt>) -> Result<Self> {
+        Self::from_felts(&data)
+    }
+}
+
+impl DispatchMessageHeader {
+    /// Decodes the header from the felts of the message, without copying them.
+    pub fn from_felts(data: &[Felt]) -> Result<Self> {
         let mut data = data.iter();
         Ok(Self {
             version: u8::from_field_bytes(data.next().context("Missing version")?.to_bytes_be()),
@@ -119,44 +174,101 @@ pub struct DispatchMessageBody {
     #[allow(unused)]
     pub nb_updated: u8,
     pub updates: Vec<DispatchUpdate>,
+    /// Updates that could not be parsed. The updates parsed successfully are kept.
+    pub parse_failures: Vec<UpdateParseFailure>,
+    /// Bytes of the body as dispatched, hashed into the id of the message.
+    pub bytes: Bytes,
+}
+
+/// An update of a dispatch that could not be parsed.
+#[derive(Debug, Clone)]
+pub struct UpdateParseFailure {
+    /// Position of the update in the dispatch.
+    pub update_index: u8,
+    /// Offset of the update in the message body, in bytes.
+    pub offset: usize,
+    /// Bytes of the update, sharing the buffer of the body. When the end of the update is unknown, all the
+    /// remaining bytes of the body.
+    pub raw: Bytes,
+    pub error: String,
 }
 
 impl FromStarknetEventData for DispatchMessageBody {
     fn from_starknet_event_data(data: Vec<Felt>) -> Result<Self> {
-        // Flatten the Felt data by removing the first 32 bytes and concatenating the rest
-        let mut data: Vec<u8> = data
-            .iter()
-            .flat_map(|fe| {
-                let bytes = fe.to_bytes_be();
-                // Skip the first 32 bytes, if applicable
-                bytes.split_at(16).1.to_vec()
-            })
-            .collect();
+        Self::from_felts(&data)
+    }
+}
 
-        let nb_updated = u8::from_be_bytes(data.drain(..1).collect::<Vec<u8>>().try_into().unwrap());
-        let mut updates = Vec::with_capacity(nb_updated as usize);
+impl DispatchMessageBody {
+    /// Decodes the body from its words, all of them full.
+    pub fn from_felts(data: &[Felt]) -> Result<Self> {
+        Self::from_bytes(body_bytes(data, None))
+    }
 
-        for _ in 0..nb_updated {
-            let update = DispatchUpdate::from_starknet_event_data(data.clone()).context("Failed to parse update")?;
-            match update {
-                DispatchUpdate::SpotMedian { update: _, feed_id: _ } => {
-                    data.drain(..SPOT_MEDIAN_UPDATE_SIZE);
+    /// Decodes the body from its bytes, flattened once into a buffer shared by the opaque updates & the parse
+    /// failures, & read through a cursor.
+    pub fn from_bytes(data: Bytes) -> Result<Self> {
+        let mut reader = ByteReader::new(&data);
+        let nb_updated = reader.read_u8().map_err(|_| DispatchParseError::EmptyBody)?;
+        let mut updates = Vec::with_capacity(nb_updated as usize);
+        let mut parse_failures = Vec::new();
+
+        for update_index in 0..nb_updated {
+            let offset = reader.position();
+            let remaining = reader.rest();
+            match DispatchUpdate::read(&mut reader, &data) {
+                Ok(update) => {
+                    if let DispatchUpdate::Opaque { feed_id, feed_type, .. } = &update {
+                        tracing::warn!("Stored raw update of feed {} with unknown feed type {}", feed_id, feed_type);
+                    }
+                    updates.push(update);
+                }
+                // The update was length-prefixed: the next updates can still be parsed.
+                Err(UpdateParseError::Skippable(e)) => {
+                    let raw = data.slice(offset..reader.position());
+                    parse_failures.push(UpdateParseFailure { update_index, offset, raw, error: e.to_string() });
+                }
+                // The end of the update is unknown, so are the next updates.
+                Err(UpdateParseError::Fatal(e)) => {
+                    let raw = data.slice_ref(remaining);
+                    parse_failures.push(UpdateParseFailure { update_index, offset, raw, error: e.to_string() });
+                    break;
                 }
             }
-            updates.push(update);
         }
 
-        // Check that the number of parsed updates matches the declared number
-        if updates.len() != nb_updated as usize {
-            anyhow::bail!(
-                "Mismatch between declared number of updates ({}) and actual updates parsed ({})",
-                nb_updated,
-                updates.len()
-            );
-        }
+        Ok(Self { nb_updated, updates, parse_failures, bytes: data })
+    }
+}
 
-        // Return the result
-        Ok(Self { nb_updated, updates })
+/// Concatenates the bytes of the body in a single allocation. The body is packed into the low 16 bytes of each
+/// felt, except its last word which only holds the remaining bytes of its size, in its low bytes. When the size
+/// doesn't match the number of words, all the words are read as full.
+fn body_bytes(data: &[Felt], size: Option<u32>) -> Bytes {
+    let mut bytes = Vec::with_capacity(data.len() * 16);
+    for felt in data {
+        bytes.extend_from_slice(&felt.to_bytes_be()[16..]);
+    }
+    let size = size.map(|size| size as usize).filter(|size| size.div_ceil(16) == data.len());
+    if let Some(padding) = size.map(|size| bytes.len() - size).filter(|padding| *padding > 0) {
+        let last_word = bytes.len() - 16;
+        bytes.drain(last_word..last_word + padding);
+    }
+    Bytes::from(bytes)
+}
+
+/// Error while parsing an update of a dispatch.
+#[derive(Debug)]
+enum UpdateParseError {
+    /// The bytes of the update were consumed, the next update can be parsed.
+    Skippable(DispatchParseError),
+    /// The end of the update is unknown.
+    Fatal(DispatchParseError),
+}
+
+impl From<DispatchParseError> for UpdateParseError {
+    fn from(e: DispatchParseError) -> Self {
+        Self::Fatal(e)
     }
 }
 
@@ -173,7 +285,7 @@ impl DispatchUpdateInfos {
         DispatchUpdateInfos {
             nonce: event.message.header.nonce,
             emitter_chain_id: event.message.header.origin,
-            emitter_address: Felt::from_dec_str(&event.message.header.sender.to_string()).unwrap(),
+            emitter_address: event.emitter_address(),
             update: update.clone(),
         }
     }
@@ -182,43 +294,94 @@ impl DispatchUpdateInfos {
 // TODO: Should be a trait?
 #[derive(Debug, Clone)]
 pub enum DispatchUpdate {
-    SpotMedian { update: SpotMedianUpdate, feed_id: String },
+    SpotMedian {
+        update: SpotMedianUpdate,
+        feed_id: String,
+    },
+    Perp {
+        update: PerpUpdate,
+        feed_id: String,
+    },
+    /// Length-prefixed update of a feed type unknown to this build, stored raw. Its data shares the buffer of
+    /// the body it was read from.
+    Opaque {
+        feed_id: String,
+        feed_type: u16,
+        data: Bytes,
+    },
 }
 
 impl DispatchUpdate {
     pub fn feed_id(&self) -> String {
         match self {
             DispatchUpdate::SpotMedian { feed_id, update: _ } => feed_id.clone(),
+            DispatchUpdate::Perp { feed_id, update: _ } => feed_id.clone(),
+            DispatchUpdate::Opaque { feed_id, .. } => feed_id.clone(),
         }
     }
 
-    fn from_starknet_event_data(mut data: Vec<u8>) -> Result<Self> {
-        let raw_asset_class = u16::from_be_bytes(data.drain(..2).collect::<Vec<u8>>().try_into().unwrap());
+    /// Unix timestamp of the update, in seconds. Unknown for opaque updates.
+    pub fn timestamp(&self) -> Option<u64> {
+        match self {
+            DispatchUpdate::SpotMedian { update, .. } => Some(update.metadata.timestamp),
+            DispatchUpdate::Perp { update, .. } => Some(update.metadata.timestamp),
+            DispatchUpdate::Opaque { .. } => None,
+        }
+    }
+
+    /// Whether the update has a feed type unknown to this build.
+    pub fn is_opaque(&self) -> bool {
+        matches!(self, DispatchUpdate::Opaque { .. })
+    }
+
+    /// Reads the update at the position of the reader, over the bytes of the `body`.
+    /// On a fatal error, the position of the reader is unspecified.
+    fn read(reader: &mut ByteReader, body: &Bytes) -> Result<Self, UpdateParseError> {
+        reader.ensure_remaining(UPDATE_HEADER_SIZE)?;
+        let raw_asset_class = reader.read_u16()?;
 
-        let raw_feed_type = u16::from_be_bytes(data.drain(..2).collect::<Vec<u8>>().try_into().unwrap());
-        let feed_type = FeedType::try_from(raw_feed_type)?;
+        let raw_feed_type = reader.read_u16()?;
+        let is_length_prefixed = raw_feed_type & OPAQUE_FEED_TYPE_FLAG != 0;
+        let raw_feed_type = raw_feed_type & !OPAQUE_FEED_TYPE_FLAG;
+        let codec = UPDATE_CODECS.get(raw_asset_class, raw_feed_type);
 
-        let pair_id_high = u128::from_be_bytes(data.drain(..16).collect::<Vec<u8>>().try_into().unwrap());
+        let pair_id_high = reader.read_u128()?;
         let mut padded_data = [0u8; 16];
-        let extracted_data = data.drain(..12).collect::<Vec<u8>>();
-        padded_data[4..].copy_from_slice(&extracted_data);
+        padded_data[4..].copy_from_slice(reader.read_bytes(12)?);
         let pair_id_low = u128::from_be_bytes(padded_data);
         let pair_id = U256::from_words(pair_id_low, pair_id_high);
 
         let feed_id = build_feed_id(raw_asset_class, raw_feed_type, pair_id_high, pair_id_low);
 
-        let update = match feed_type {
-            FeedType::UniqueSpotMedian => {
-                let mut res = SpotMedianUpdate::from_starknet_event_data(data)?;
-                res.pair_id = pair_id;
-                DispatchUpdate::SpotMedian { update: res, feed_id }
-            }
-        };
+        if !is_length_prefixed {
+            let codec = codec.ok_or(DispatchParseError::UnknownUpdateType {
+                asset_class: raw_asset_class,
+                feed_type: raw_feed_type,
+            })?;
+            let (update, consumed) = codec.decode(feed_id, pair_id, reader.rest())?;
+            reader.skip(consumed)?;
+            return Ok(update);
+        }
 
-        Ok(update)
+        let length = reader.read_u16()? as usize;
+        let update_data = reader.read_bytes(length)?;
+        match codec {
+            Some(codec) => codec
+                .decode(feed_id, pair_id, update_data)
+                .map(|(update, _)| update)
+                .map_err(UpdateParseError::Skippable),
+            None => Ok(DispatchUpdate::Opaque { feed_id, feed_type: raw_feed_type, data: body.slice_ref(update_data) }),
+        }
     }
 }
 
+fn u256_to_be_bytes(value: &U256) -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    bytes[..16].copy_from_slice(&value.high().to_be_bytes());
+    bytes[16..].copy_from_slice(&value.low().to_be_bytes());
+    bytes
+}
+
 fn build_feed_id(raw_asset_class: u16, raw_feed_type: u16, pair_id_high: u128, pair_id_low: u128) -> String {
     let mut bytes: Vec<u8> = Vec::new();
     bytes.extend_from_slice(&raw_asset_class.to_be_bytes());
@@ -245,56 +408,44 @@ pub struct SpotMedianUpdate {
 }
 
 impl SpotMedianUpdate {
-    fn from_starknet_event_data(mut data: Vec<u8>) -> Result<Self> {
-        let timestamp = u64::from_be_bytes(data.drain(..8).collect::<Vec<u8>>().try_into().unwrap());
-        let num_sources_aggregated = u16::from_be_bytes(data.drain(..2).collect::<Vec<u8>>().try_into().unwrap());
-        let decimals = u8::from_be_bytes(data.drain(..1).collect::<Vec<u8>>().try_into().unwrap());
-        let price_high = u128::from_be_bytes(data.drain(..16).collect::<Vec<u8>>().try_into().unwrap()); // U256
-        let price_low = u128::from_be_bytes(data.drain(..16).collect::<Vec<u8>>().try_into().unwrap());
-        let price = U256::from_words(price_low, price_high);
-        let volume_high = u128::from_be_bytes(data.drain(..16).collect::<Vec<u8>>().try_into().unwrap()); // U256
-        let volume_low = u128::from_be_bytes(data.drain(..16).collect::<Vec<u8>>().try_into().unwrap());
-        let volume = U256::from_words(volume_low, volume_high);
-
-        Ok(Self {
-            pair_id: U256::from(0_u8), // This will get populated later
-            metadata: MetadataUpdate { decimals, timestamp, num_sources_aggregated },
-            price,
-            volume,
-        })
-    }
-
+    /// Update data of the calldata, see [calldata::encode_spot_median].
     pub fn to_bytes(&self) -> Vec<u8> {
-        let mut bytes = Vec::new();
-
-        bytes.extend_from_slice(&self.pair_id.low().to_be_bytes());
-        bytes.extend_from_slice(&self.pair_id.high().to_be_bytes());
-
-        bytes.extend_from_slice(&self.metadata.timestamp.to_be_bytes());
-        bytes.extend_from_slice(&self.metadata.num_sources_aggregated.to_be_bytes());
-        bytes.extend_from_slice(&self.metadata.decimals.to_be_bytes());
+        calldata::encode_spot_median(self)
+    }
+}
 
-        bytes.extend_from_slice(&self.price.high().to_be_bytes());
-        bytes.extend_from_slice(&self.price.low().to_be_bytes());
+#[derive(Debug, Clone)]
+pub struct PerpUpdate {
+    pub pair_id: U256,
+    pub metadata: MetadataUpdate,
+    pub mark_price: U256,
+    pub funding_rate: U256,
+    pub open_interest: U256,
+    pub volume: U256,
+}
 
-        bytes.extend_from_slice(&self.volume.high().to_be_bytes());
-        bytes.extend_from_slice(&self.volume.low().to_be_bytes());
-        bytes
+impl PerpUpdate {
+    /// Update data of the calldata, see [calldata::encode_perp].
+    pub fn to_bytes(&self) -> Vec<u8> {
+        calldata::encode_perp(self)
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use alloy::primitives::b256;
+    use proptest::prelude::*;
+
     use super::*;
 
     fn create_event_data(raw_data: Vec<&str>) -> Vec<Felt> {
         raw_data.iter().map(|hex_str| Felt::from_hex(hex_str).unwrap()).collect()
     }
 
-    // TODO: Fix this test!
-    #[test]
-    fn test_dispatch_event_from_event_data() {
-        let event_data = create_event_data(vec![
+    /// Dispatch of the BTC/USD & ETH/USD spot median updates. Its body was captured with a stray leading byte,
+    /// read as the count of updates: it is packed as emitted by the dispatcher, starting with the count.
+    fn spot_median_dispatch_data() -> Vec<Felt> {
+        create_event_data(vec![
             "0x00000000000000000000000000000000e12de834144d9e90044ac03f6024267e",
             "0x0000000000000000000000000000000004d997c57f63d509f483927ce74135a4",
             "0x0000000000000000000000000000000000000000000000000000000000000000",
@@ -308,51 +459,366 @@ mod tests {
             "0x0000000000000000000000000000000000000000000000000000000000000000",
             "0x0000000000000000000000000000000000000000000000000000000000000000",
             "0x0000000000000000000000000000000000000000000000000000000000000000",
-            "0x00000000000000000000000000000000000000000000000000000000000000d8",
+            "0x00000000000000000000000000000000000000000000000000000000000000d7",
             "0x000000000000000000000000000000000000000000000000000000000000000e",
-            "0x0000000000000000000000000000000000020000000000000000000000000000",
-            "0x0000000000000000000000000000000000000000000000000000004254432f55",
-            "0x0000000000000000000000000000000053440000000067094ce4000108000000",
+            "0x0000000000000000000000000000000002000000000000000000000000000000",
+            "0x00000000000000000000000000000000000000000000000000004254432f5553",
+            "0x00000000000000000000000000000000440000000067094ce400010800000000",
             "0x0000000000000000000000000000000000000000000000000000000000000000",
-            "0x000000000000000000000000000000000000000000000005a9d39c70a7000000",
+            "0x0000000000000000000000000000000000000000000005a9d39c70a700000000",
             "0x0000000000000000000000000000000000000000000000000000000000000000",
             "0x0000000000000000000000000000000000000000000000000000000000000000",
             "0x0000000000000000000000000000000000000000000000000000000000000000",
-            "0x000000000000000000000000000000000000000000004554482f555344000000",
-            "0x000000000000000000000000000000000067094ce40001080000000000000000",
+            "0x0000000000000000000000000000000000000000004554482f55534400000000",
+            "0x0000000000000000000000000000000067094ce4000108000000000000000000",
             "0x0000000000000000000000000000000000000000000000000000000000000000",
-            "0x0000000000000000000000000000000000000038f1e274c20000000000000000",
+            "0x00000000000000000000000000000000000038f1e274c2000000000000000000",
             "0x0000000000000000000000000000000000000000000000000000000000000000",
             "0x0000000000000000000000000000000000000000000000000000000000000000",
-        ]);
-
-        let dispatch_event = DispatchEvent::from_starknet_event_data(event_data).unwrap();
-
-        // assert_eq!(dispatch_event.sender, U256::from_words(299314662055416172851006310266400155262, 0)); // ça
-        // assert_eq!(dispatch_event.destination_domain, 0);
-        // assert_eq!(dispatch_event.recipient_address, U256::from(0_u32));
+        ])
+    }
 
-        // let header = &dispatch_event.message.header;
-        // assert_eq!(header.version, 1);
-        // assert_eq!(header.nonce, 4);
-        // assert_eq!(header.origin, 5);
-        // assert_eq!(header.sender, U256::from_words(299314662055416172851006310266400155262, 0));
-        // assert_eq!(header.destination, 7);
-        // assert_eq!(header.recipient, U256::from(0_u32));
+    #[test]
+    fn test_dispatch_event_from_event_data() {
+        let dispatch_event = DispatchEvent::from_starknet_event_data(spot_median_dispatch_data()).unwrap();
+
+        let dispatcher =
+            U256::from_words(0xe12de834144d9e90044ac03f6024267e_u128, 0x04d997c57f63d509f483927ce74135a4_u128);
+        assert_eq!(dispatch_event.sender, dispatcher);
+        assert_eq!(dispatch_event.destination_domain, 0);
+        assert_eq!(dispatch_event.recipient_address, U256::from(0_u32));
+
+        let header = &dispatch_event.message.header;
+        assert_eq!(header.version, 3);
+        assert_eq!(header.nonce, 0);
+        assert_eq!(header.origin, 6363709);
+        assert_eq!(header.sender, dispatcher);
+        assert_eq!(header.destination, 0);
+        assert_eq!(header.recipient, U256::from(0_u32));
 
         let body = &dispatch_event.message.body;
         assert_eq!(body.nb_updated, 2);
-        assert_eq!(body.updates.len(), 2);
-
-        // match &body.updates[0] {
-        //     DispatchUpdate::SpotMedian { feed_id: _, update } => {
-        //         assert_eq!(update.pair_id, U256::from(9_u32));
-        //         assert_eq!(update.price, BigDecimal::from(10)); // 1000 / 10^2
-        //         assert_eq!(update.volume, 0_u128);
-        //         assert_eq!(update.metadata.decimals, 2);
-        //         assert_eq!(update.metadata.timestamp, 1234567890);
-        //         assert_eq!(update.metadata.num_sources_aggregated, 5);
-        //     }
-        // }
+        assert!(body.parse_failures.is_empty());
+        let expected = [(0x4254432f555344_u64, 6_226_957_856_935_u64), (0x4554482f555344, 244_576_318_658)];
+        assert_eq!(body.updates.len(), expected.len());
+        for (update, (pair_id, price)) in body.updates.iter().zip(expected) {
+            let DispatchUpdate::SpotMedian { feed_id, update } = update else {
+                panic!("Expected a spot median update, got {:?}", update);
+            };
+            assert!(feed_id.ends_with(&format!("{:x}", pair_id)));
+            assert_eq!(update.pair_id, U256::from(pair_id));
+            assert_eq!(update.price, U256::from(price));
+            assert_eq!(update.volume, U256::from(0_u32));
+            assert_eq!(update.metadata.decimals, 8);
+            assert_eq!(update.metadata.timestamp, 1_728_662_756);
+            assert_eq!(update.metadata.num_sources_aggregated, 1);
+        }
+    }
+
+    /// The id is computed apart from the crate, by hashing the message packed by hand: the 77 bytes of its header
+    /// then the 215 bytes of its body, i.e. its 13 full words & the 7 low bytes of its last word.
+    #[test]
+    fn test_message_id_is_the_hash_of_the_packed_message() {
+        let data = spot_median_dispatch_data();
+        let dispatch_event = DispatchEvent::from_starknet_event_data(data.clone()).unwrap();
+        assert_eq!(dispatch_event.message.body.bytes.len(), 0xd7);
+
+        let expected = b256!("f0d9d349f882a7c1b9a2e200dffa08e8f61817a38237bd9f91360057ddcd5d1d");
+        assert_eq!(dispatch_event.message.id(), expected);
+        assert_eq!(DispatchEvent::message_id_of(&data).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_last_word_of_the_body_holds_its_remaining_bytes() {
+        let body = [[2u8; 16].as_slice(), &[3, 4, 5]].concat();
+        let words = [Felt::from(u128::from_be_bytes([2; 16])), Felt::from(0x030405_u32)];
+        assert_eq!(body_bytes(&words, Some(body.len() as u32))[..], body[..]);
+        // A size not matching the number of words is ignored.
+        assert_eq!(body_bytes(&words, Some(3)).len(), 32);
+    }
+
+    #[test]
+    fn test_opaque_update_does_not_break_parsing() {
+        let pair_id = [[0u8; 16].as_slice(), b"BTC/USD\0\0\0\0\0"].concat();
+        let spot_median_fields = [
+            1_700_000_000_u64.to_be_bytes().as_slice(),
+            &5_u16.to_be_bytes(),
+            &[8],
+            &[0u8; 16],
+            &42_u128.to_be_bytes(),
+            &[0u8; 32],
+        ]
+        .concat();
+
+        // An update of a feed type unknown to this build, followed by a plain spot median.
+        let mut data =
+            [[0, 0].as_slice(), &(OPAQUE_FEED_TYPE_FLAG | 7).to_be_bytes(), &pair_id, &3_u16.to_be_bytes()].concat();
+        data.extend_from_slice(&[0xaa, 0xbb, 0xcc]);
+        data.extend_from_slice(&[[0, 0, 0, 0].as_slice(), &pair_id, &spot_median_fields].concat());
+        // A length-prefixed update of a known feed type is parsed.
+        data.extend_from_slice(&[[0, 0].as_slice(), &OPAQUE_FEED_TYPE_FLAG.to_be_bytes(), &pair_id].concat());
+        data.extend_from_slice(&(spot_median_fields.len() as u16).to_be_bytes());
+        data.extend_from_slice(&spot_median_fields);
+
+        let data = Bytes::from(data);
+        let mut reader = ByteReader::new(&data);
+        let opaque = DispatchUpdate::read(&mut reader, &data).unwrap();
+        assert!(matches!(&opaque, DispatchUpdate::Opaque { feed_type: 7, data, .. } if data[..] == [0xaa, 0xbb, 0xcc]));
+        // The data of the opaque update isn't copied, it shares the buffer of the body.
+        if let DispatchUpdate::Opaque { data: opaque_data, .. } = &opaque {
+            assert_eq!(opaque_data.as_ptr(), data[UPDATE_HEADER_SIZE + 2..].as_ptr());
+        }
+        for _ in 0..2 {
+            match DispatchUpdate::read(&mut reader, &data).unwrap() {
+                DispatchUpdate::SpotMedian { update, .. } => assert_eq!(update.price, U256::from(42_u8)),
+                _ => panic!("Expected a spot median update"),
+            }
+        }
+        assert_eq!(reader.remaining(), 0);
+    }
+
+    /// Packs the body bytes into felts of 16 bytes, as emitted by the Starknet contract.
+    fn body_felts(bytes: &[u8]) -> Vec<Felt> {
+        bytes
+            .chunks(16)
+            .map(|chunk| {
+                let mut padded = [0u8; 32];
+                padded[16..16 + chunk.len()].copy_from_slice(chunk);
+                Felt::from_bytes_be(&padded)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_partially_parsed_dispatch_keeps_valid_updates() {
+        let pair_id = [[0u8; 16].as_slice(), b"BTC/USD\0\0\0\0\0"].concat();
+        let spot_median_fields =
+            [1_700_000_000_u64.to_be_bytes().as_slice(), &5_u16.to_be_bytes(), &[8], &[0u8; 64]].concat();
+        let spot_median = [[0, 0, 0, 0].as_slice(), &pair_id, &spot_median_fields].concat();
+        // A length-prefixed spot median too short to be parsed.
+        let invalid_prefixed =
+            [[0, 0].as_slice(), &OPAQUE_FEED_TYPE_FLAG.to_be_bytes(), &pair_id, &2_u16.to_be_bytes(), &[0xaa, 0xbb]]
+                .concat();
+
+        let body = [[3].as_slice(), &spot_median, &invalid_prefixed, &spot_median].concat();
+        let parsed = DispatchMessageBody::from_starknet_event_data(body_felts(&body)).unwrap();
+        assert_eq!(parsed.updates.len(), 2);
+        assert_eq!(parsed.parse_failures.len(), 1);
+        let failure = &parsed.parse_failures[0];
+        assert_eq!((failure.update_index, failure.offset), (1, 1 + spot_median.len()));
+        assert_eq!(failure.raw, invalid_prefixed);
+
+        // A truncated update: its end & the following updates are unknown.
+        let truncated = &spot_median[..40];
+        let body = [[3].as_slice(), &spot_median, truncated].concat();
+        let parsed = DispatchMessageBody::from_starknet_event_data(body_felts(&body)).unwrap();
+        assert_eq!(parsed.updates.len(), 1);
+        assert_eq!(parsed.parse_failures.len(), 1);
+        assert_eq!(parsed.parse_failures[0].update_index, 1);
+        assert!(parsed.parse_failures[0].raw.starts_with(truncated));
+    }
+
+    #[test]
+    fn test_perp_update_roundtrip() {
+        let pair_id = [[0u8; 16].as_slice(), b"BTC/USD\0\0\0\0\0"].concat();
+        let perp_fields = [
+            1_700_000_000_u64.to_be_bytes().as_slice(),
+            &3_u16.to_be_bytes(),
+            &[6],
+            &[0u8; 16],
+            &65_000_000_000_u128.to_be_bytes(),
+            &[0u8; 16],
+            &100_u128.to_be_bytes(),
+            &[0u8; 16],
+            &1_000_000_u128.to_be_bytes(),
+            &[0u8; 32],
+        ]
+        .concat();
+
+        let data = Bytes::from([[0, 0, 0, 1].as_slice(), &pair_id, &perp_fields].concat());
+        let mut reader = ByteReader::new(&data);
+        let DispatchUpdate::Perp { update, feed_id } = DispatchUpdate::read(&mut reader, &data).unwrap() else {
+            panic!("Expected a perp update");
+        };
+        assert_eq!(reader.remaining(), 0);
+        assert!(feed_id.starts_with("0x00000001"));
+        assert_eq!(update.metadata.decimals, 6);
+        assert_eq!(update.mark_price, U256::from(65_000_000_000_u64));
+        assert_eq!(update.funding_rate, U256::from(100_u8));
+        assert_eq!(update.open_interest, U256::from(1_000_000_u32));
+        assert_eq!(update.to_bytes()[32..], perp_fields[..]);
+    }
+
+    /// An update generated well-formed, as it must be decoded.
+    #[derive(Debug, Clone)]
+    enum ExpectedUpdate {
+        SpotMedian { timestamp: u64, price: U256 },
+        Perp { timestamp: u64, mark_price: U256 },
+        Opaque { feed_type: u16, data: Vec<u8> },
+    }
+
+    impl ExpectedUpdate {
+        fn matches(&self, update: &DispatchUpdate) -> bool {
+            match (self, update) {
+                (ExpectedUpdate::SpotMedian { timestamp, price }, DispatchUpdate::SpotMedian { update, .. }) => {
+                    update.metadata.timestamp == *timestamp && update.price == *price
+                }
+                (ExpectedUpdate::Perp { timestamp, mark_price }, DispatchUpdate::Perp { update, .. }) => {
+                    update.metadata.timestamp == *timestamp && update.mark_price == *mark_price
+                }
+                (
+                    ExpectedUpdate::Opaque { feed_type, data },
+                    DispatchUpdate::Opaque { feed_type: decoded_feed_type, data: decoded_data, .. },
+                ) => feed_type == decoded_feed_type && decoded_data[..] == data[..],
+                _ => false,
+            }
+        }
+    }
+
+    /// Bytes of an update of the crypto asset class, length-prefixed or not.
+    fn update_bytes(feed_type: u16, pair_id: &[u8; 28], fields: &[u8], length_prefixed: bool) -> Vec<u8> {
+        let mut bytes = vec![0, 0];
+        if length_prefixed {
+            bytes.extend_from_slice(&(feed_type | OPAQUE_FEED_TYPE_FLAG).to_be_bytes());
+            bytes.extend_from_slice(pair_id);
+            bytes.extend_from_slice(&(fields.len() as u16).to_be_bytes());
+        } else {
+            bytes.extend_from_slice(&feed_type.to_be_bytes());
+            bytes.extend_from_slice(pair_id);
+        }
+        bytes.extend_from_slice(fields);
+        bytes
+    }
+
+    fn u256_from_be_bytes(bytes: &[u8; 32]) -> U256 {
+        let high = u128::from_be_bytes(bytes[..16].try_into().unwrap());
+        let low = u128::from_be_bytes(bytes[16..].try_into().unwrap());
+        U256::from_words(low, high)
+    }
+
+    prop_compose! {
+        /// A well-formed spot median or perp update, plain or length-prefixed.
+        fn known_update()(
+            is_perp in any::<bool>(),
+            length_prefixed in any::<bool>(),
+            pair_id in any::<[u8; 28]>(),
+            timestamp in any::<u64>(),
+            num_sources_aggregated in any::<u16>(),
+            decimals in any::<u8>(),
+            words in any::<[[u8; 32]; 4]>(),
+        ) -> (Vec<u8>, ExpectedUpdate) {
+            let num_words = if is_perp { 4 } else { 2 };
+            let mut fields =
+                [timestamp.to_be_bytes().as_slice(), &num_sources_aggregated.to_be_bytes(), &[decimals]].concat();
+            for word in &words[..num_words] {
+                fields.extend_from_slice(word);
+            }
+            let first_word = u256_from_be_bytes(&words[0]);
+            let (feed_type, expected) = if is_perp {
+                (1, ExpectedUpdate::Perp { timestamp, mark_price: first_word })
+            } else {
+                (0, ExpectedUpdate::SpotMedian { timestamp, price: first_word })
+            };
+            (update_bytes(feed_type, &pair_id, &fields, length_prefixed), expected)
+        }
+    }
+
+    prop_compose! {
+        /// A length-prefixed update of a feed type unknown to this build.
+        fn opaque_update()(
+            feed_type in 2_u16..OPAQUE_FEED_TYPE_FLAG,
+            pair_id in any::<[u8; 28]>(),
+            data in proptest::collection::vec(any::<u8>(), 0..64),
+        ) -> (Vec<u8>, ExpectedUpdate) {
+            (update_bytes(feed_type, &pair_id, &data, true), ExpectedUpdate::Opaque { feed_type, data })
+        }
+    }
+
+    prop_compose! {
+        /// A well-formed message body & the updates it must be decoded into.
+        fn well_formed_body()(
+            updates in proptest::collection::vec(prop_oneof![3 => known_update(), 1 => opaque_update()], 1..16),
+        ) -> (Vec<u8>, Vec<ExpectedUpdate>) {
+            let mut bytes = vec![updates.len() as u8];
+            let mut expected = Vec::with_capacity(updates.len());
+            for (update, expected_update) in updates {
+                bytes.extend_from_slice(&update);
+                expected.push(expected_update);
+            }
+            (bytes, expected)
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn test_well_formed_bodies_are_decoded((body, expected) in well_formed_body()) {
+            let parsed = DispatchMessageBody::from_felts(&body_felts(&body)).unwrap();
+            prop_assert!(parsed.parse_failures.is_empty(), "{:?}", parsed.parse_failures);
+            prop_assert_eq!(parsed.nb_updated as usize, expected.len());
+            prop_assert_eq!(parsed.updates.len(), expected.len());
+            for (update, expected_update) in parsed.updates.iter().zip(expected.iter()) {
+                prop_assert!(expected_update.matches(update), "{:?} != {:?}", update, expected_update);
+            }
+        }
+
+        #[test]
+        fn test_well_formed_events_are_decoded(
+            nonce in any::<u32>(),
+            origin in any::<u32>(),
+            destination in any::<u32>(),
+            sender in any::<u128>(),
+            (body, expected) in well_formed_body(),
+        ) {
+            let header = [
+                Felt::from(3_u8),
+                Felt::from(nonce),
+                Felt::from(origin),
+                Felt::from(sender),
+                Felt::ZERO,
+                Felt::from(destination),
+                Felt::ZERO,
+                Felt::ZERO,
+                Felt::ZERO,
+                Felt::ZERO,
+            ];
+            let event_fields = [Felt::from(sender), Felt::ZERO, Felt::from(destination), Felt::ZERO, Felt::ZERO];
+            let data: Vec<Felt> = event_fields.into_iter().chain(header).chain(body_felts(&body)).collect();
+            prop_assert_eq!(DispatchEvent::destination_domain_of(&data), Some(destination));
+
+            let event = DispatchEvent::from_starknet_event_data(data).unwrap();
+            let header = &event.message.header;
+            prop_assert_eq!((header.nonce, header.origin, header.destination), (nonce, origin, destination));
+            prop_assert_eq!(event.emitter_address(), Felt::from(sender));
+            prop_assert_eq!(event.message.body.updates.len(), expected.len());
+        }
+
+        #[test]
+        fn test_random_headers_never_panic(felts in proptest::collection::vec(any::<[u8; 32]>(), 0..12)) {
+            let felts: Vec<Felt> = felts.iter().map(Felt::from_bytes_be).collect();
+            let _ = DispatchMessageHeader::from_felts(&felts);
+        }
+
+        #[test]
+        fn test_random_bodies_never_panic(bytes in proptest::collection::vec(any::<u8>(), 0..512)) {
+            let _ = DispatchMessageBody::from_starknet_event_data(body_felts(&bytes));
+        }
+
+        #[test]
+        fn test_random_events_never_panic(felts in proptest::collection::vec(any::<[u8; 32]>(), 0..48)) {
+            let felts = felts.iter().map(Felt::from_bytes_be).collect();
+            let _ = DispatchEvent::from_starknet_event_data(felts);
+        }
+
+        /// Known update headers followed by random fields, possibly truncated or length-prefixed.
+        #[test]
+        fn test_random_update_fields_never_panic(
+            nb_updated in 1_u8..4,
+            feed_type in prop_oneof![Just(0_u16), Just(1), Just(OPAQUE_FEED_TYPE_FLAG), Just(OPAQUE_FEED_TYPE_FLAG | 1)],
+            fields in proptest::collection::vec(any::<u8>(), 0..300),
+        ) {
+            let body = [[nb_updated, 0, 0].as_slice(), &feed_type.to_be_bytes(), &[0u8; 28], &fields].concat();
+            let parsed = DispatchMessageBody::from_starknet_event_data(body_felts(&body)).unwrap();
+            prop_assert!(parsed.updates.len() + parsed.parse_failures.len() <= nb_updated as usize);
+        }
     }
 }
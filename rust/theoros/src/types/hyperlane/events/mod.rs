@@ -1,7 +1,11 @@
 pub mod dispatch_event;
+pub mod reader;
+pub mod update_codec;
 pub mod validator_announcement_event;
 
 pub use dispatch_event::*;
+pub use reader::*;
+pub use update_codec::*;
 pub use validator_announcement_event::*;
 
 use starknet::core::types::Felt;
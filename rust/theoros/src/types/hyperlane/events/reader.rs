@@ -0,0 +1,106 @@
+use starknet::core::types::U256;
+
+/// Error while parsing the bytes of a dispatch.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum DispatchParseError {
+    #[error("Truncated data: needs {needed} bytes, {got} remaining")]
+    Truncated { needed: usize, got: usize },
+    #[error("Unknown update type: asset class {asset_class}, feed type {feed_type}")]
+    UnknownUpdateType { asset_class: u16, feed_type: u16 },
+    #[error("Empty message body")]
+    EmptyBody,
+}
+
+/// Reads big-endian values from a byte slice, failing instead of panicking when the slice is too short.
+#[derive(Debug, Clone)]
+pub struct ByteReader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data, position: 0 }
+    }
+
+    /// Number of bytes read so far.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Number of bytes left to read.
+    pub fn remaining(&self) -> usize {
+        self.data.len() - self.position
+    }
+
+    /// Ensures at least `needed` bytes are left to read.
+    pub fn ensure_remaining(&self, needed: usize) -> Result<(), DispatchParseError> {
+        if self.remaining() < needed {
+            return Err(DispatchParseError::Truncated { needed, got: self.remaining() });
+        }
+        Ok(())
+    }
+
+    /// Reads the next `len` bytes.
+    pub fn read_bytes(&mut self, len: usize) -> Result<&'a [u8], DispatchParseError> {
+        self.ensure_remaining(len)?;
+        let bytes = &self.data[self.position..self.position + len];
+        self.position += len;
+        Ok(bytes)
+    }
+
+    /// Skips the next `len` bytes.
+    pub fn skip(&mut self, len: usize) -> Result<(), DispatchParseError> {
+        self.read_bytes(len).map(|_| ())
+    }
+
+    /// Returns the bytes left to read, without consuming them.
+    pub fn rest(&self) -> &'a [u8] {
+        &self.data[self.position..]
+    }
+
+    pub fn read_array<const N: usize>(&mut self) -> Result<[u8; N], DispatchParseError> {
+        let mut array = [0u8; N];
+        array.copy_from_slice(self.read_bytes(N)?);
+        Ok(array)
+    }
+
+    pub fn read_u8(&mut self) -> Result<u8, DispatchParseError> {
+        self.read_array().map(u8::from_be_bytes)
+    }
+
+    pub fn read_u16(&mut self) -> Result<u16, DispatchParseError> {
+        self.read_array().map(u16::from_be_bytes)
+    }
+
+    pub fn read_u64(&mut self) -> Result<u64, DispatchParseError> {
+        self.read_array().map(u64::from_be_bytes)
+    }
+
+    pub fn read_u128(&mut self) -> Result<u128, DispatchParseError> {
+        self.read_array().map(u128::from_be_bytes)
+    }
+
+    /// Reads a U256, high word first.
+    pub fn read_u256(&mut self) -> Result<U256, DispatchParseError> {
+        let high = self.read_u128()?;
+        let low = self.read_u128()?;
+        Ok(U256::from_words(low, high))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reads_are_bounds_checked() {
+        let mut reader = ByteReader::new(&[0x01, 0x02, 0x03]);
+        assert_eq!(reader.read_u16().unwrap(), 0x0102);
+        assert_eq!(reader.read_u16(), Err(DispatchParseError::Truncated { needed: 2, got: 1 }));
+        // A failed read consumes nothing.
+        assert_eq!(reader.position(), 2);
+        assert_eq!(reader.read_u8().unwrap(), 0x03);
+        assert_eq!(reader.remaining(), 0);
+    }
+}
@@ -0,0 +1,173 @@
+use std::collections::HashMap;
+
+use pragma_feeds::{AssetClass, FeedType};
+use starknet::core::types::U256;
+
+use super::{
+    dispatch_event::{DispatchUpdate, MetadataUpdate, PerpUpdate, SpotMedianUpdate},
+    reader::{ByteReader, DispatchParseError},
+};
+
+/// Size of the metadata starting every update after its pair id: timestamp, sources & decimals.
+const METADATA_SIZE: usize = 8 + 2 + 1;
+/// Size of the spot median fields following the pair id: metadata, price & volume.
+const SPOT_MEDIAN_UPDATE_SIZE: usize = METADATA_SIZE + 32 + 32;
+/// Size of the perp fields following the pair id: metadata, mark price, funding rate, open interest & volume.
+const PERP_UPDATE_SIZE: usize = METADATA_SIZE + 32 + 32 + 32 + 32;
+
+lazy_static::lazy_static! {
+    /// Codecs of the update types known to this build.
+    pub static ref UPDATE_CODECS: UpdateCodecs = UpdateCodecs::default();
+}
+
+/// Decodes the updates of an `(asset_class, feed_type)`.
+pub trait UpdateCodec: Send + Sync {
+    /// Decodes the update fields at the start of `data`, following the pair id.
+    /// Returns the update & the number of bytes it spans.
+    fn decode(
+        &self,
+        feed_id: String,
+        pair_id: U256,
+        data: &[u8],
+    ) -> Result<(DispatchUpdate, usize), DispatchParseError>;
+}
+
+/// Registry of the [UpdateCodec]s, keyed by the raw `(asset_class, feed_type)` of the updates.
+pub struct UpdateCodecs(HashMap<(u16, u16), Box<dyn UpdateCodec>>);
+
+impl Default for UpdateCodecs {
+    fn default() -> Self {
+        let mut codecs = Self(HashMap::new());
+        codecs.register(AssetClass::Crypto as u16, FeedType::UniqueSpotMedian as u16, SpotMedianCodec);
+        codecs.register(AssetClass::Crypto as u16, FeedType::UniquePerpMedian as u16, PerpCodec);
+        codecs
+    }
+}
+
+impl UpdateCodecs {
+    /// Registers the codec of an `(asset_class, feed_type)`, replacing the previous one.
+    pub fn register(&mut self, asset_class: u16, feed_type: u16, codec: impl UpdateCodec + 'static) {
+        self.0.insert((asset_class, feed_type), Box::new(codec));
+    }
+
+    /// Returns the codec of an `(asset_class, feed_type)`, if known to this build.
+    pub fn get(&self, asset_class: u16, feed_type: u16) -> Option<&dyn UpdateCodec> {
+        self.0.get(&(asset_class, feed_type)).map(|codec| codec.as_ref())
+    }
+
+    /// The `(asset_class, feed_type)` of the codecs, sorted.
+    pub fn keys(&self) -> Vec<(u16, u16)> {
+        let mut keys: Vec<_> = self.0.keys().copied().collect();
+        keys.sort();
+        keys
+    }
+}
+
+struct SpotMedianCodec;
+
+impl UpdateCodec for SpotMedianCodec {
+    fn decode(
+        &self,
+        feed_id: String,
+        pair_id: U256,
+        data: &[u8],
+    ) -> Result<(DispatchUpdate, usize), DispatchParseError> {
+        let mut reader = ByteReader::new(data);
+        reader.ensure_remaining(SPOT_MEDIAN_UPDATE_SIZE)?;
+        let metadata = read_metadata(&mut reader)?;
+        let price = reader.read_u256()?;
+        let volume = reader.read_u256()?;
+
+        let update = SpotMedianUpdate { pair_id, metadata, price, volume };
+        Ok((DispatchUpdate::SpotMedian { update, feed_id }, reader.position()))
+    }
+}
+
+struct PerpCodec;
+
+impl UpdateCodec for PerpCodec {
+    fn decode(
+        &self,
+        feed_id: String,
+        pair_id: U256,
+        data: &[u8],
+    ) -> Result<(DispatchUpdate, usize), DispatchParseError> {
+        let mut reader = ByteReader::new(data);
+        reader.ensure_remaining(PERP_UPDATE_SIZE)?;
+        let metadata = read_metadata(&mut reader)?;
+        let mark_price = reader.read_u256()?;
+        let funding_rate = reader.read_u256()?;
+        let open_interest = reader.read_u256()?;
+        let volume = reader.read_u256()?;
+
+        let update = PerpUpdate { pair_id, metadata, mark_price, funding_rate, open_interest, volume };
+        Ok((DispatchUpdate::Perp { update, feed_id }, reader.position()))
+    }
+}
+
+fn read_metadata(reader: &mut ByteReader) -> Result<MetadataUpdate, DispatchParseError> {
+    let timestamp = reader.read_u64()?;
+    let num_sources_aggregated = reader.read_u16()?;
+    let decimals = reader.read_u8()?;
+    Ok(MetadataUpdate { timestamp, num_sources_aggregated, decimals })
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use proptest::prelude::*;
+
+    use super::*;
+
+    struct FixedCodec;
+
+    impl UpdateCodec for FixedCodec {
+        fn decode(
+            &self,
+            feed_id: String,
+            _pair_id: U256,
+            data: &[u8],
+        ) -> Result<(DispatchUpdate, usize), DispatchParseError> {
+            Ok((DispatchUpdate::Opaque { feed_id, feed_type: 42, data: Bytes::copy_from_slice(&data[..2]) }, 2))
+        }
+    }
+
+    #[test]
+    fn test_codecs_are_keyed_by_asset_class_and_feed_type() {
+        let mut codecs = UpdateCodecs::default();
+        assert!(codecs.get(0, 0).is_some());
+        assert!(codecs.get(0, 1).is_some());
+        assert!(codecs.get(1, 0).is_none());
+
+        codecs.register(1, 0, FixedCodec);
+        let (update, consumed) = codecs.get(1, 0).unwrap().decode("0x01".into(), U256::from(0_u8), &[1, 2, 3]).unwrap();
+        assert!(matches!(update, DispatchUpdate::Opaque { feed_type: 42, data, .. } if data[..] == [1, 2]));
+        assert_eq!(consumed, 2);
+    }
+
+    #[test]
+    fn test_spot_median_codec_reports_its_size() {
+        let codec = UPDATE_CODECS.get(0, 0).unwrap();
+        let data = [0u8; SPOT_MEDIAN_UPDATE_SIZE + 3];
+        let (_, consumed) = codec.decode("0x00".into(), U256::from(0_u8), &data).unwrap();
+        assert_eq!(consumed, SPOT_MEDIAN_UPDATE_SIZE);
+        let truncated = codec.decode("0x00".into(), U256::from(0_u8), &data[..10]).map(|_| ()).unwrap_err();
+        assert_eq!(truncated, DispatchParseError::Truncated { needed: SPOT_MEDIAN_UPDATE_SIZE, got: 10 });
+    }
+
+    proptest! {
+        /// Every codec decodes random fields, possibly truncated, without panicking nor reading past them.
+        #[test]
+        fn test_codecs_never_panic(
+            codec_index in any::<proptest::sample::Index>(),
+            data in proptest::collection::vec(any::<u8>(), 0..256),
+        ) {
+            let keys = UPDATE_CODECS.keys();
+            let (asset_class, feed_type) = keys[codec_index.index(keys.len())];
+            let codec = UPDATE_CODECS.get(asset_class, feed_type).unwrap();
+            if let Ok((_, consumed)) = codec.decode("0x00".into(), U256::from(0_u8), &data) {
+                prop_assert!(consumed <= data.len());
+            }
+        }
+    }
+}
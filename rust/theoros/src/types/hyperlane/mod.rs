@@ -1,8 +1,10 @@
+pub mod anomaly;
 pub mod checkpoint;
 pub mod checkpoint_fetchers;
 pub mod events;
 pub mod signing;
 
+pub use anomaly::*;
 pub use checkpoint::*;
 pub use checkpoint_fetchers::*;
 pub use events::*;
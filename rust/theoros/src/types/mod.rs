@@ -1,3 +1,21 @@
+pub mod api;
+pub mod build_info;
 pub mod calldata;
+pub mod chain_statuses;
+pub mod config_deployment;
+pub mod encryption;
+pub mod feed_lifecycles;
+pub mod heap;
+pub mod history;
 pub mod hyperlane;
+pub mod ohlc;
+pub mod post_processors;
+pub mod private_feeds;
+pub mod quorum;
+pub mod relayer;
+pub mod retention;
+pub mod staleness;
 pub mod state;
+pub mod timeline;
+pub mod units;
+pub mod update_view;
@@ -0,0 +1,136 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use starknet::core::types::U256;
+use utoipa::ToSchema;
+
+use crate::types::hyperlane::{DispatchUpdate, DispatchUpdateInfos};
+
+/// Open/high/low/close prices of a feed over a time bucket.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct Candle {
+    /// Start of the bucket, as a unix timestamp in seconds.
+    pub open_time: u64,
+    /// Prices are decimal strings, to be scaled by `decimals`.
+    pub open: String,
+    pub high: String,
+    pub low: String,
+    pub close: String,
+    pub decimals: u8,
+    /// Number of updates aggregated in the bucket.
+    pub updates: usize,
+}
+
+struct CandleBuilder {
+    open: U256,
+    high: U256,
+    low: U256,
+    close: U256,
+    decimals: u8,
+    updates: usize,
+}
+
+impl CandleBuilder {
+    fn new(price: U256, decimals: u8) -> Self {
+        Self { open: price, high: price, low: price, close: price, decimals, updates: 1 }
+    }
+
+    fn push(&mut self, price: U256) {
+        self.high = self.high.max(price);
+        self.low = self.low.min(price);
+        self.close = price;
+        self.updates += 1;
+    }
+
+    fn build(self, open_time: u64) -> Candle {
+        Candle {
+            open_time,
+            open: self.open.to_string(),
+            high: self.high.to_string(),
+            low: self.low.to_string(),
+            close: self.close.to_string(),
+            decimals: self.decimals,
+            updates: self.updates,
+        }
+    }
+}
+
+/// Aggregates the updates into candles of `interval`, bucketed by the timestamp of the updates.
+/// Candles are sorted by open time & empty buckets are omitted. Perp updates are aggregated on their mark
+/// price & opaque updates are ignored.
+pub fn aggregate_candles(updates: &[DispatchUpdateInfos], interval: Duration) -> Vec<Candle> {
+    let interval_secs = interval.as_secs().max(1);
+
+    let mut prices: Vec<(u64, U256, u8)> = updates
+        .iter()
+        .filter_map(|infos| match &infos.update {
+            DispatchUpdate::SpotMedian { update, feed_id: _ } => {
+                Some((update.metadata.timestamp, update.price, update.metadata.decimals))
+            }
+            DispatchUpdate::Perp { update, feed_id: _ } => {
+                Some((update.metadata.timestamp, update.mark_price, update.metadata.decimals))
+            }
+            DispatchUpdate::Opaque { .. } => None,
+        })
+        .collect();
+    // Stable sort: updates sharing the same timestamp stay in dispatch order.
+    prices.sort_by_key(|(timestamp, _, _)| *timestamp);
+
+    let mut buckets: BTreeMap<u64, CandleBuilder> = BTreeMap::new();
+    for (timestamp, price, decimals) in prices {
+        let open_time = timestamp - timestamp % interval_secs;
+        buckets
+            .entry(open_time)
+            .and_modify(|candle| candle.push(price))
+            .or_insert_with(|| CandleBuilder::new(price, decimals));
+    }
+    buckets.into_iter().map(|(open_time, candle)| candle.build(open_time)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use starknet::core::types::Felt;
+
+    use super::*;
+    use crate::types::hyperlane::{MetadataUpdate, SpotMedianUpdate};
+
+    fn update(nonce: u32, timestamp: u64, price: u64) -> DispatchUpdateInfos {
+        DispatchUpdateInfos {
+            nonce,
+            emitter_chain_id: 0,
+            emitter_address: Felt::ZERO,
+            update: DispatchUpdate::SpotMedian {
+                update: SpotMedianUpdate {
+                    pair_id: U256::from(0_u8),
+                    metadata: MetadataUpdate { timestamp, num_sources_aggregated: 1, decimals: 8 },
+                    price: U256::from(price),
+                    volume: U256::from(0_u8),
+                },
+                feed_id: "0x1".to_string(),
+            },
+        }
+    }
+
+    #[test]
+    fn test_aggregate_candles() {
+        let updates = vec![update(1, 60, 10), update(3, 119, 12), update(2, 90, 30), update(4, 185, 7)];
+
+        let candles = aggregate_candles(&updates, Duration::from_secs(60));
+
+        assert_eq!(candles.len(), 2);
+        assert_eq!(
+            candles[0],
+            Candle {
+                open_time: 60,
+                open: "10".into(),
+                high: "30".into(),
+                low: "10".into(),
+                close: "12".into(),
+                decimals: 8,
+                updates: 3
+            }
+        );
+        assert_eq!((candles[1].open_time, candles[1].open.as_str(), candles[1].updates), (180, "7", 1));
+    }
+}
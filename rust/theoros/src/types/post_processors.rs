@@ -0,0 +1,139 @@
+use std::fmt::Debug;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use dashmap::DashMap;
+
+use crate::configs::evm_config::{EvmChainName, EvmConfig, PostProcessorConfig};
+
+/// Informations about the calldata being post-processed.
+#[derive(Debug, Clone, Copy)]
+pub struct PostProcessingContext {
+    /// Number of feeds updated by the calldata
+    pub num_feeds: u8,
+}
+
+/// Chain-specific transformation applied to the calldata once it has been encoded.
+pub trait PostProcessor: Debug + Send + Sync {
+    fn process(&self, calldata: Vec<u8>, context: &PostProcessingContext) -> Vec<u8>;
+}
+
+/// Prepends the number of feeds contained in the calldata, as a single byte.
+#[derive(Debug)]
+pub struct PrependFeedCount;
+
+impl PostProcessor for PrependFeedCount {
+    fn process(&self, calldata: Vec<u8>, context: &PostProcessingContext) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(calldata.len() + 1);
+        bytes.push(context.num_feeds);
+        bytes.extend_from_slice(&calldata);
+        bytes
+    }
+}
+
+/// Appends a deadline (unix timestamp in seconds, 8 bytes big endian) after which
+/// the consumer contract should reject the calldata.
+#[derive(Debug)]
+pub struct AppendDeadline {
+    validity: Duration,
+}
+
+impl AppendDeadline {
+    pub fn new(validity: Duration) -> Self {
+        Self { validity }
+    }
+
+    fn deadline(&self) -> u64 {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+        (now + self.validity).as_secs()
+    }
+}
+
+impl PostProcessor for AppendDeadline {
+    fn process(&self, mut calldata: Vec<u8>, _context: &PostProcessingContext) -> Vec<u8> {
+        calldata.extend_from_slice(&self.deadline().to_be_bytes());
+        calldata
+    }
+}
+
+impl From<&PostProcessorConfig> for Arc<dyn PostProcessor> {
+    fn from(config: &PostProcessorConfig) -> Self {
+        match config {
+            PostProcessorConfig::PrependFeedCount => Arc::new(PrependFeedCount),
+            PostProcessorConfig::AppendDeadline { validity_secs } => {
+                Arc::new(AppendDeadline::new(Duration::from_secs(*validity_secs)))
+            }
+        }
+    }
+}
+
+/// Mapping between the chains and the post-processors applied to their calldata.
+#[derive(Debug, Default)]
+pub struct PostProcessorsMapping(DashMap<EvmChainName, Vec<Arc<dyn PostProcessor>>>);
+
+impl PostProcessorsMapping {
+    pub fn from_config(config: &EvmConfig) -> Self {
+        let mapping = config
+            .chains()
+            .iter()
+            .filter(|(_, chain_config)| !chain_config.post_processors.is_empty())
+            .map(|(chain_name, chain_config)| {
+                (*chain_name, chain_config.post_processors.iter().map(Arc::<dyn PostProcessor>::from).collect())
+            })
+            .collect();
+        Self(mapping)
+    }
+
+    /// Applies, in order, all the post-processors configured for the chain.
+    pub fn apply(&self, chain_name: &EvmChainName, calldata: Vec<u8>, context: &PostProcessingContext) -> Vec<u8> {
+        match self.0.get(chain_name) {
+            Some(post_processors) => post_processors.iter().fold(calldata, |bytes, p| p.process(bytes, context)),
+            None => calldata,
+        }
+    }
+
+    /// Replaces the post-processors of all the chains by the ones of `other`.
+    pub fn replace(&self, other: Self) {
+        self.0.retain(|chain_name, _| other.0.contains_key(chain_name));
+        for (chain_name, post_processors) in other.0 {
+            self.0.insert(chain_name, post_processors);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_post_processors_are_applied_in_order() {
+        let config: EvmConfig = serde_yaml::from_str(
+            r#"
+            sepolia:
+              rpc_url: "http://localhost:8545"
+              hyperlane_address: "0x0000000000000000000000000000000000000000"
+              post_processors:
+                - name: prepend_feed_count
+                - name: append_deadline
+                  params:
+                    validity_secs: 60
+            mainnet:
+              rpc_url: "http://localhost:8545"
+              hyperlane_address: "0x0000000000000000000000000000000000000000"
+            "#,
+        )
+        .unwrap();
+        let mapping = PostProcessorsMapping::from_config(&config);
+        let context = PostProcessingContext { num_feeds: 2 };
+
+        let processed = mapping.apply(&EvmChainName::Sepolia, vec![0xaa, 0xbb], &context);
+        assert_eq!(processed.len(), 1 + 2 + 8);
+        assert_eq!(&processed[..3], &[2, 0xaa, 0xbb]);
+        let deadline = u64::from_be_bytes(processed[3..].try_into().unwrap());
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+        assert!(deadline > now && deadline <= now + 60);
+
+        let untouched = mapping.apply(&EvmChainName::Mainnet, vec![0xaa, 0xbb], &context);
+        assert_eq!(untouched, vec![0xaa, 0xbb]);
+    }
+}
@@ -0,0 +1,65 @@
+use dashmap::DashSet;
+
+use crate::configs::feed_lifecycle::FeedLifecycleConfig;
+
+/// Feeds whose calldata is only served encrypted for a registered consumer, initialized from the feed lifecycle
+/// config. The other feeds are public.
+#[derive(Debug, Default)]
+pub struct PrivateFeeds(DashSet<String>);
+
+impl PrivateFeeds {
+    pub fn from_config(config: &FeedLifecycleConfig) -> Self {
+        Self(config.private_feeds().iter().cloned().collect())
+    }
+
+    pub fn is_private(&self, feed_id: &str) -> bool {
+        self.0.contains(feed_id)
+    }
+
+    /// Whether all the feeds are public.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Makes the feed private or public.
+    pub fn set(&self, feed_id: String, private: bool) {
+        if private {
+            self.0.insert(feed_id);
+        } else {
+            self.0.remove(&feed_id);
+        }
+    }
+
+    /// Returns the private feeds, sorted.
+    pub fn all(&self) -> Vec<String> {
+        let mut feed_ids: Vec<String> = self.0.iter().map(|feed_id| feed_id.clone()).collect();
+        feed_ids.sort();
+        feed_ids
+    }
+
+    /// Returns the first of the feeds that is private, if any.
+    pub fn first_private<'a>(&self, feed_ids: &'a [String]) -> Option<&'a String> {
+        feed_ids.iter().find(|feed_id| self.is_private(feed_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_feeds_are_made_private_then_public_again() {
+        let private_feeds = PrivateFeeds::default();
+        let feed_ids = vec!["0x01".to_string(), "0x02".to_string(), "0x03".to_string()];
+        assert_eq!(private_feeds.first_private(&feed_ids), None);
+
+        private_feeds.set("0x03".into(), true);
+        private_feeds.set("0x02".into(), true);
+        assert_eq!(private_feeds.all(), vec!["0x02".to_string(), "0x03".to_string()]);
+        assert_eq!(private_feeds.first_private(&feed_ids), Some(&feed_ids[1]));
+
+        private_feeds.set("0x02".into(), false);
+        assert!(!private_feeds.is_private("0x02"));
+        assert_eq!(private_feeds.first_private(&feed_ids), Some(&feed_ids[2]));
+    }
+}
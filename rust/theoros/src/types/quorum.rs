@@ -0,0 +1,234 @@
+use std::cmp::Reverse;
+use std::sync::Arc;
+
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use starknet::core::types::Felt;
+use utoipa::ToSchema;
+
+use crate::{
+    configs::evm_config::EvmChainName,
+    rpc::evm::{HyperlaneValidatorsMapping, ValidatorSet},
+    storage::TheorosStorage,
+    types::hyperlane::{CheckpointWithMessageId, SignedCheckpointWithMessageId},
+};
+
+/// Minimum number of signatures accepted by the Pragma Hyperlane contract of a chain with `num_validators`
+/// validators, for contracts not exposing their threshold. Mirrors `Hyperlane.verifyHyMsg`: more than two thirds of the validators, computed with one decimal.
+pub fn quorum_threshold(num_validators: usize) -> usize {
+    (num_validators * 10 / 3 * 2) / 10 + 1
+}
+
+/// Keeps the checkpoints of the value most validators signed, dropping e.g. the one of a validator signing another
+/// `message_id`: only the signatures of the same `(root, message_id)` can be aggregated into a calldata. Ties are
+/// broken by the lowest validator, so the quorum & the calldata are computed from the same checkpoints.
+pub fn agreeing_checkpoints(
+    checkpoints: Vec<(Felt, SignedCheckpointWithMessageId)>,
+) -> Vec<(Felt, SignedCheckpointWithMessageId)> {
+    // Value, number of signers & lowest signer.
+    let mut values: Vec<(&CheckpointWithMessageId, usize, Felt)> = Vec::new();
+    for (validator, checkpoint) in &checkpoints {
+        match values.iter_mut().find(|(value, ..)| **value == checkpoint.value) {
+            Some((_, signers, lowest)) => {
+                *signers += 1;
+                *lowest = (*lowest).min(*validator);
+            }
+            None => values.push((&checkpoint.value, 1, *validator)),
+        }
+    }
+    let agreed = values.into_iter().max_by_key(|(_, signers, lowest)| (*signers, Reverse(*lowest)));
+    let Some(agreed) = agreed.map(|(value, ..)| value.clone()) else {
+        return Vec::new();
+    };
+    checkpoints.into_iter().filter(|(_, checkpoint)| checkpoint.value == agreed).collect()
+}
+
+/// Signatures collected for a nonce, against the validators of a destination chain.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct QuorumStatus {
+    pub chain: EvmChainName,
+    /// Number of validators of the chain.
+    pub validators: usize,
+    /// Number of signatures required by the chain.
+    pub threshold: usize,
+    /// Validators of the chain which signed the checkpoint of the nonce.
+    pub signers: Vec<String>,
+    /// Whether the calldata of the nonce can be served for the chain.
+    pub reached: bool,
+}
+
+/// Tells, per destination chain, whether the validators which signed a nonce reach the threshold of the chain.
+/// The validators & threshold of each chain are the ones of its Hyperlane contract, the signatures the collected
+/// checkpoints.
+pub struct QuorumTracker {
+    validators: Arc<HyperlaneValidatorsMapping>,
+    storage: Arc<TheorosStorage>,
+    /// Highest nonce whose calldata was served, per chain.
+    last_served: DashMap<EvmChainName, u32>,
+}
+
+impl QuorumTracker {
+    pub fn new(validators: Arc<HyperlaneValidatorsMapping>, storage: Arc<TheorosStorage>) -> Self {
+        Self { validators, storage, last_served: DashMap::new() }
+    }
+
+    /// Records that the calldata of the nonce, which reached quorum, was served for the chain.
+    pub fn record_served(&self, chain_name: EvmChainName, nonce: u32) {
+        let mut last_served = self.last_served.entry(chain_name).or_insert(nonce);
+        *last_served = (*last_served).max(nonce);
+    }
+
+    /// Highest nonce whose calldata was served for the chain since the start.
+    pub fn last_served(&self, chain_name: &EvmChainName) -> Option<u32> {
+        self.last_served.get(chain_name).map(|nonce| *nonce)
+    }
+
+    pub fn status(&self, chain_name: EvmChainName, nonce: u32) -> Option<QuorumStatus> {
+        let ValidatorSet { validators, threshold } = self.validators.get_validator_set(&chain_name)?;
+        let checkpoints = validators
+            .keys()
+            .filter_map(|validator| {
+                let checkpoint = self.storage.signed_checkpoints().get_for_validator(*validator, nonce)?;
+                Some((*validator, checkpoint))
+            })
+            .collect();
+        // The signers of another value can't be aggregated with the others, so don't count towards the quorum.
+        let mut signers: Vec<Felt> =
+            agreeing_checkpoints(checkpoints).into_iter().map(|(validator, _)| validator).collect();
+        signers.sort_by_key(|validator| validators[validator]);
+        Some(QuorumStatus {
+            chain: chain_name,
+            validators: validators.len(),
+            threshold,
+            reached: signers.len() >= threshold,
+            signers: signers.iter().map(|validator| format!("{:#x}", validator)).collect(),
+        })
+    }
+
+    /// Statuses of the nonce on all the chains, sorted by chain name.
+    pub fn statuses(&self, nonce: u32) -> Vec<QuorumStatus> {
+        let mut statuses: Vec<QuorumStatus> =
+            self.validators.chain_names().into_iter().filter_map(|chain_name| self.status(chain_name, nonce)).collect();
+        statuses.sort_by_key(|status| status.chain.to_string());
+        statuses
+    }
+
+    /// Whether the calldata of the nonce can be served for at least one chain.
+    pub fn reached_on_any_chain(&self, nonce: u32) -> bool {
+        self.validators.chain_names().into_iter().any(|chain_name| self.is_reached(chain_name, nonce))
+    }
+
+    pub fn is_reached(&self, chain_name: EvmChainName, nonce: u32) -> bool {
+        self.status(chain_name, nonce).is_some_and(|status| status.reached)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use alloy::primitives::{Parity, U256};
+    use alloy::signers::Signature;
+
+    use super::*;
+    use crate::storage::{FeedIdsStorage, ValidatorsFetchersStorage};
+    use crate::types::hyperlane::Checkpoint;
+
+    #[test]
+    fn test_quorum_threshold_matches_the_contract() {
+        let thresholds: Vec<usize> = (1..=7).map(quorum_threshold).collect();
+        assert_eq!(thresholds, vec![1, 2, 3, 3, 4, 5, 5]);
+    }
+
+    fn signed_checkpoint(nonce: u32) -> SignedCheckpointWithMessageId {
+        signed_message_id(nonce, U256::ZERO)
+    }
+
+    fn signed_message_id(nonce: u32, message_id: U256) -> SignedCheckpointWithMessageId {
+        SignedCheckpointWithMessageId {
+            value: CheckpointWithMessageId {
+                checkpoint: Checkpoint {
+                    merkle_tree_hook_address: U256::ZERO,
+                    mailbox_domain: 0,
+                    root: String::new(),
+                    index: nonce,
+                },
+                message_id,
+            },
+            signature: Signature::new(U256::from(1), U256::from(2), Parity::Parity(false)),
+        }
+    }
+
+    #[test]
+    fn test_quorum_status() {
+        let storage = Arc::new(TheorosStorage::new(FeedIdsStorage::default(), ValidatorsFetchersStorage::default()));
+        let validators = Arc::new(HyperlaneValidatorsMapping::default());
+        let chain_validators = (0..4).map(|index| (Felt::from(index + 1), index)).collect::<HashMap<Felt, u8>>();
+        validators.insert(EvmChainName::Sepolia, ValidatorSet::new(chain_validators));
+        validators.insert(EvmChainName::Holesky, ValidatorSet::new(HashMap::new()).with_threshold(1));
+        let tracker = QuorumTracker::new(validators, storage.clone());
+
+        storage.signed_checkpoints().add(Felt::from(3), 7, signed_checkpoint(7));
+        storage.signed_checkpoints().add(Felt::from(1), 7, signed_checkpoint(7));
+        let status = tracker.status(EvmChainName::Sepolia, 7).unwrap();
+        assert_eq!((status.validators, status.threshold, status.reached), (4, 3, false));
+        assert_eq!(status.signers, vec!["0x1", "0x3"]);
+
+        storage.signed_checkpoints().add(Felt::from(4), 7, signed_checkpoint(7));
+        assert!(tracker.is_reached(EvmChainName::Sepolia, 7));
+        assert!(tracker.reached_on_any_chain(7));
+        assert!(!tracker.reached_on_any_chain(8));
+        assert!(!tracker.is_reached(EvmChainName::Holesky, 7));
+        assert_eq!(tracker.statuses(7).len(), 2);
+        assert_eq!(tracker.status(EvmChainName::Mainnet, 7), None);
+
+        tracker.record_served(EvmChainName::Sepolia, 7);
+        tracker.record_served(EvmChainName::Sepolia, 5);
+        assert_eq!(tracker.last_served(&EvmChainName::Sepolia), Some(7));
+        assert_eq!(tracker.last_served(&EvmChainName::Holesky), None);
+    }
+
+    #[test]
+    fn test_signers_of_another_message_id_are_not_counted() {
+        let storage = Arc::new(TheorosStorage::new(FeedIdsStorage::default(), ValidatorsFetchersStorage::default()));
+        let validators = Arc::new(HyperlaneValidatorsMapping::default());
+        let chain_validators = (0..4).map(|index| (Felt::from(index + 1), index)).collect::<HashMap<Felt, u8>>();
+        validators.insert(EvmChainName::Sepolia, ValidatorSet::new(chain_validators));
+        let tracker = QuorumTracker::new(validators, storage.clone());
+
+        storage.signed_checkpoints().add(Felt::from(1), 7, signed_message_id(7, U256::from(1)));
+        storage.signed_checkpoints().add(Felt::from(2), 7, signed_message_id(7, U256::from(2)));
+        storage.signed_checkpoints().add(Felt::from(3), 7, signed_message_id(7, U256::from(1)));
+        // 2 of the 3 required signatures agree.
+        let status = tracker.status(EvmChainName::Sepolia, 7).unwrap();
+        assert_eq!((status.threshold, status.reached), (3, false));
+        assert_eq!(status.signers, vec!["0x1", "0x3"]);
+
+        storage.signed_checkpoints().add(Felt::from(4), 7, signed_message_id(7, U256::from(1)));
+        let status = tracker.status(EvmChainName::Sepolia, 7).unwrap();
+        assert!(status.reached);
+        assert_eq!(status.signers, vec!["0x1", "0x3", "0x4"]);
+
+        // The calldata is built from the agreeing signatures only.
+        let checkpoints =
+            storage.signed_checkpoints().get(&[Felt::from(1), Felt::from(2), Felt::from(3), Felt::from(4)], 7);
+        let mut agreeing: Vec<Felt> =
+            agreeing_checkpoints(checkpoints).into_iter().map(|(validator, _)| validator).collect();
+        agreeing.sort();
+        assert_eq!(agreeing, vec![Felt::from(1), Felt::from(3), Felt::from(4)]);
+    }
+
+    #[test]
+    fn test_agreeing_checkpoints_ties_are_broken_by_the_lowest_validator() {
+        let checkpoints = vec![
+            (Felt::from(4), signed_message_id(7, U256::from(1))),
+            (Felt::from(2), signed_message_id(7, U256::from(2))),
+            (Felt::from(3), signed_message_id(7, U256::from(2))),
+            (Felt::from(1), signed_message_id(7, U256::from(1))),
+        ];
+        let agreeing = agreeing_checkpoints(checkpoints);
+        assert!(agreeing.iter().all(|(_, checkpoint)| checkpoint.value.message_id == U256::from(1)));
+        assert_eq!(agreeing.len(), 2);
+        assert!(agreeing_checkpoints(Vec::new()).is_empty());
+    }
+}
@@ -0,0 +1,201 @@
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::{configs::evm_config::EvmChainName, constants::MAX_RELAYED_TRANSACTIONS_KEPT};
+
+/// Where a transaction sent by the relayer stands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RelayedTransactionStatus {
+    /// Sent, its receipt is awaited.
+    Pending,
+    /// Mined, the feeds were updated.
+    Confirmed,
+    /// Mined, but the update reverted.
+    Reverted,
+    /// No receipt before the timeout: the transaction is stuck or was dropped.
+    TimedOut,
+}
+
+impl RelayedTransactionStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RelayedTransactionStatus::Pending => "pending",
+            RelayedTransactionStatus::Confirmed => "confirmed",
+            RelayedTransactionStatus::Reverted => "reverted",
+            RelayedTransactionStatus::TimedOut => "timed_out",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct RelayedTransaction {
+    pub hash: String,
+    /// Nonce of the transaction, for the account of the relayer.
+    pub nonce: u64,
+    /// Nonce of the Dispatch message whose updates were sent.
+    pub dispatch_nonce: u32,
+    pub feed_ids: Vec<String>,
+    pub status: RelayedTransactionStatus,
+    /// Unix timestamp at which the transaction was sent, in seconds.
+    pub sent_at: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block_number: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gas_used: Option<u64>,
+    /// Price paid per unit of gas, in wei.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub effective_gas_price: Option<u64>,
+}
+
+/// Receipt of a transaction sent by the relayer, or its absence once timed out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RelayedReceipt {
+    pub status: RelayedTransactionStatus,
+    pub block_number: Option<u64>,
+    pub gas_used: Option<u64>,
+    pub effective_gas_price: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct ChainRelayerStatus {
+    pub chain: EvmChainName,
+    /// Account sending the updates.
+    pub address: String,
+    /// Feeds kept up to date on the chain.
+    pub feed_ids: Vec<String>,
+    /// Nonce of the next transaction, unknown until fetched from the chain.
+    pub next_nonce: Option<u64>,
+    /// Nonce of the transaction stuck with fees which can't be bumped under the cap, holding back the updates.
+    pub stuck_nonce: Option<u64>,
+    pub sent: u64,
+    pub confirmed: u64,
+    pub reverted: u64,
+    pub timed_out: u64,
+    /// Why the latest update couldn't be sent, cleared once one is.
+    pub last_error: Option<String>,
+    /// The latest transactions sent, the most recent first.
+    pub transactions: Vec<RelayedTransaction>,
+}
+
+/// Status of the relayer of each chain, updated as its transactions are sent & mined.
+#[derive(Debug, Default)]
+pub struct RelayerStatuses(DashMap<EvmChainName, ChainRelayerStatus>);
+
+impl RelayerStatuses {
+    pub fn register(&self, chain_name: EvmChainName, address: String, feed_ids: Vec<String>) {
+        let status = ChainRelayerStatus {
+            chain: chain_name,
+            address,
+            feed_ids,
+            next_nonce: None,
+            stuck_nonce: None,
+            sent: 0,
+            confirmed: 0,
+            reverted: 0,
+            timed_out: 0,
+            last_error: None,
+            transactions: Vec::new(),
+        };
+        self.0.insert(chain_name, status);
+    }
+
+    pub fn set_next_nonce(&self, chain_name: EvmChainName, next_nonce: Option<u64>) {
+        if let Some(mut status) = self.0.get_mut(&chain_name) {
+            status.next_nonce = next_nonce;
+        }
+    }
+
+    pub fn set_stuck_nonce(&self, chain_name: EvmChainName, stuck_nonce: Option<u64>) {
+        if let Some(mut status) = self.0.get_mut(&chain_name) {
+            status.stuck_nonce = stuck_nonce;
+        }
+    }
+
+    pub fn record_failure(&self, chain_name: EvmChainName, error: String) {
+        if let Some(mut status) = self.0.get_mut(&chain_name) {
+            status.last_error = Some(error);
+        }
+    }
+
+    pub fn record_sent(&self, chain_name: EvmChainName, transaction: RelayedTransaction) {
+        if let Some(mut status) = self.0.get_mut(&chain_name) {
+            status.sent += 1;
+            status.last_error = None;
+            status.transactions.insert(0, transaction);
+            status.transactions.truncate(MAX_RELAYED_TRANSACTIONS_KEPT);
+        }
+    }
+
+    pub fn record_receipt(&self, chain_name: EvmChainName, hash: &str, receipt: RelayedReceipt) {
+        let Some(mut status) = self.0.get_mut(&chain_name) else {
+            return;
+        };
+        match receipt.status {
+            RelayedTransactionStatus::Pending => return,
+            RelayedTransactionStatus::Confirmed => status.confirmed += 1,
+            RelayedTransactionStatus::Reverted => status.reverted += 1,
+            RelayedTransactionStatus::TimedOut => status.timed_out += 1,
+        }
+        if let Some(transaction) = status.transactions.iter_mut().find(|transaction| transaction.hash == hash) {
+            transaction.status = receipt.status;
+            transaction.block_number = receipt.block_number;
+            transaction.gas_used = receipt.gas_used;
+            transaction.effective_gas_price = receipt.effective_gas_price;
+        }
+    }
+
+    /// Statuses of all the chains the relayer runs on, sorted by chain name.
+    pub fn all(&self) -> Vec<ChainRelayerStatus> {
+        let mut statuses: Vec<ChainRelayerStatus> = self.0.iter().map(|entry| entry.value().clone()).collect();
+        statuses.sort_by_key(|status| status.chain.to_string());
+        statuses
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transaction(hash: &str, nonce: u64) -> RelayedTransaction {
+        RelayedTransaction {
+            hash: hash.to_owned(),
+            nonce,
+            dispatch_nonce: 7,
+            feed_ids: vec![String::from("0x4254432f555344")],
+            status: RelayedTransactionStatus::Pending,
+            sent_at: 1_700_000_000,
+            block_number: None,
+            gas_used: None,
+            effective_gas_price: None,
+        }
+    }
+
+    #[test]
+    fn test_relayed_transactions_are_tracked_until_mined() {
+        let statuses = RelayerStatuses::default();
+        statuses.register(EvmChainName::Sepolia, String::from("0x01"), vec![String::from("0x4254432f555344")]);
+
+        statuses.record_failure(EvmChainName::Sepolia, String::from("insufficient funds"));
+        for nonce in 0..MAX_RELAYED_TRANSACTIONS_KEPT as u64 + 1 {
+            statuses.record_sent(EvmChainName::Sepolia, transaction(&format!("0x{nonce:x}"), nonce));
+        }
+        let receipt = RelayedReceipt {
+            status: RelayedTransactionStatus::Confirmed,
+            block_number: Some(42),
+            gas_used: Some(80_000),
+            effective_gas_price: Some(1_000_000_000),
+        };
+        let latest = format!("0x{:x}", MAX_RELAYED_TRANSACTIONS_KEPT);
+        statuses.record_receipt(EvmChainName::Sepolia, &latest, receipt);
+
+        let status = &statuses.all()[0];
+        let sent = MAX_RELAYED_TRANSACTIONS_KEPT as u64 + 1;
+        assert_eq!((status.sent, status.confirmed, status.last_error.as_deref()), (sent, 1, None));
+        assert_eq!(status.transactions.len(), MAX_RELAYED_TRANSACTIONS_KEPT);
+        assert_eq!(status.transactions[0].status, RelayedTransactionStatus::Confirmed);
+        assert_eq!(status.transactions[0].block_number, Some(42));
+        assert_eq!(status.transactions.last().unwrap().nonce, 1);
+    }
+}
@@ -0,0 +1,70 @@
+use std::time::Duration;
+
+/// How long the updates of the feeds are kept, with their dispatches & signed checkpoints.
+///
+/// An update is pruned once it is beyond the `keep_updates` most recent ones of its feed or older than
+/// `keep_for`, whichever comes first. The latest update of each feed & the dispatches pending quorum are
+/// always kept. Nothing is pruned when neither limit is set.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RetentionPolicy {
+    pub keep_updates: Option<usize>,
+    pub keep_for: Option<Duration>,
+}
+
+impl RetentionPolicy {
+    pub fn is_enabled(&self) -> bool {
+        self.keep_updates.is_some() || self.keep_for.is_some()
+    }
+
+    /// Whether the update, the `rank`-th most recent of its feed (the latest one being `0`), is kept at `now`.
+    pub fn keeps_update(&self, rank: usize, timestamp: Option<u64>, now: u64) -> bool {
+        rank == 0
+            || (self.keep_updates.map_or(true, |keep_updates| rank < keep_updates) && !self.is_expired(timestamp, now))
+    }
+
+    /// Whether something timestamped, in seconds, is older than the retention period at `now`. Never
+    /// expired when not timestamped or without a retention period.
+    pub fn is_expired(&self, timestamp: Option<u64>, now: u64) -> bool {
+        match (self.keep_for, timestamp) {
+            (Some(keep_for), Some(timestamp)) => timestamp.saturating_add(keep_for.as_secs()) < now,
+            _ => false,
+        }
+    }
+}
+
+/// What a compaction pruned.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct PrunedCounts {
+    pub updates: u64,
+    pub dispatches: u64,
+    pub checkpoints: u64,
+}
+
+impl PrunedCounts {
+    /// Each count, labelled with the kind of pruned entries.
+    pub fn by_kind(&self) -> [(&'static str, u64); 3] {
+        [("update", self.updates), ("dispatch", self.dispatches), ("checkpoint", self.checkpoints)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retention_policy() {
+        let now = 1_700_000_000;
+        let policy = RetentionPolicy { keep_updates: Some(2), keep_for: Some(Duration::from_secs(60)) };
+        assert!(policy.keeps_update(1, Some(now - 60), now));
+        // Beyond the most recent updates of the feed, or too old.
+        assert!(!policy.keeps_update(2, Some(now), now));
+        assert!(!policy.keeps_update(1, Some(now - 61), now));
+        // The latest update is always kept.
+        assert!(policy.keeps_update(0, Some(now - 3600), now));
+        // Updates without a timestamp are only pruned when beyond the most recent ones.
+        assert!(policy.keeps_update(1, None, now));
+
+        assert!(!RetentionPolicy::default().is_enabled());
+        assert!(RetentionPolicy::default().keeps_update(1000, Some(0), now));
+    }
+}
@@ -0,0 +1,114 @@
+use std::{collections::HashMap, str::FromStr, time::Duration};
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::{configs::indexer_start::parse_duration, constants::DEFAULT_FEED_MAX_AGE};
+
+/// Whether a feed is still updated, according to the age of its latest update.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FeedStatus {
+    /// The latest update is not older than the max age of the feed.
+    Fresh,
+    /// The latest update is older than the max age of the feed, which stopped updating.
+    Stale,
+    /// No timestamped update of the feed was indexed yet.
+    Unknown,
+}
+
+impl FeedStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FeedStatus::Fresh => "fresh",
+            FeedStatus::Stale => "stale",
+            FeedStatus::Unknown => "unknown",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct FeedFreshness {
+    pub status: FeedStatus,
+    /// Unix timestamp of the latest update of the feed, in seconds.
+    pub last_update_timestamp: Option<u64>,
+}
+
+/// Max age of a feed, overriding the default one, e.g. `0x4254432f555344=1h`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeedMaxAge {
+    pub feed_id: String,
+    pub max_age: Duration,
+}
+
+impl FromStr for FeedMaxAge {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let (feed_id, max_age) =
+            s.split_once('=').with_context(|| format!("Invalid feed max age `{s}`, expected feed_id=duration"))?;
+        let max_age = parse_duration(max_age.trim()).with_context(|| format!("Invalid max age in `{s}`"))?;
+        Ok(Self { feed_id: feed_id.trim().to_owned(), max_age })
+    }
+}
+
+/// Max age of the latest update of the feeds, past which they are considered stale.
+#[derive(Debug, Clone)]
+pub struct StalenessPolicy {
+    default_max_age: Duration,
+    max_ages: HashMap<String, Duration>,
+}
+
+impl Default for StalenessPolicy {
+    fn default() -> Self {
+        Self::new(DEFAULT_FEED_MAX_AGE, Vec::new())
+    }
+}
+
+impl StalenessPolicy {
+    pub fn new(default_max_age: Duration, max_ages: Vec<FeedMaxAge>) -> Self {
+        Self {
+            default_max_age,
+            max_ages: max_ages.into_iter().map(|FeedMaxAge { feed_id, max_age }| (feed_id, max_age)).collect(),
+        }
+    }
+
+    /// Returns the max age of the feed, as configured.
+    pub fn max_age(&self, feed_id: &str) -> Duration {
+        self.max_ages.get(feed_id).copied().unwrap_or(self.default_max_age)
+    }
+
+    /// Returns the freshness at `now` of the feed whose latest update has the given timestamp, in seconds.
+    /// Updates from the future, i.e. published by a source whose clock is ahead, are fresh.
+    pub fn freshness(&self, feed_id: &str, last_update_timestamp: Option<u64>, now: u64) -> FeedFreshness {
+        let status = match last_update_timestamp {
+            None => FeedStatus::Unknown,
+            Some(timestamp) if now.saturating_sub(timestamp) > self.max_age(feed_id).as_secs() => FeedStatus::Stale,
+            Some(_) => FeedStatus::Fresh,
+        };
+        FeedFreshness { status, last_update_timestamp }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_feed_status_follows_the_max_age_of_the_feed() {
+        let max_ages = vec!["0x1=1h".parse().unwrap()];
+        let policy = StalenessPolicy::new(Duration::from_secs(60), max_ages);
+        let now = 1_700_000_000;
+
+        assert_eq!(policy.freshness("0x2", None, now).status, FeedStatus::Unknown);
+        assert_eq!(policy.freshness("0x2", Some(now - 60), now).status, FeedStatus::Fresh);
+        assert_eq!(policy.freshness("0x2", Some(now - 61), now).status, FeedStatus::Stale);
+        assert_eq!(policy.freshness("0x2", Some(now + 10), now).status, FeedStatus::Fresh);
+        assert_eq!(policy.freshness("0x1", Some(now - 61), now).status, FeedStatus::Fresh);
+        assert_eq!(policy.freshness("0x1", Some(now - 3601), now).status, FeedStatus::Stale);
+
+        assert!("0x1".parse::<FeedMaxAge>().is_err());
+        assert!("0x1=soon".parse::<FeedMaxAge>().is_err());
+    }
+}
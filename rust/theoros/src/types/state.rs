@@ -1,29 +1,384 @@
+use std::collections::HashMap;
+use std::str::FromStr;
 use std::sync::{atomic::AtomicUsize, Arc};
+use std::time::Duration;
 
+use anyhow::Context;
+use pragma_utils::tracing::{LogLevel, SamplingRule, TracingSampler};
 use prometheus::Registry;
+use tokio::sync::watch;
+use tokio_util::{sync::CancellationToken, task::TaskTracker};
 
 use crate::{
-    rpc::{evm::HyperlaneValidatorsMapping, starknet::StarknetRpc},
-    storage::TheorosStorage,
+    configs::{
+        evm_config::{ChainStatus, EvmChainName, EvmConfig},
+        feed_lifecycle::FeedLifecycle,
+        runtime_settings::RuntimeSettings,
+    },
+    constants::{
+        DEFAULT_CALLDATA_DEADLINE, DEFAULT_MAX_BATCH_FEEDS, DEFAULT_MAX_REQUEST_BODY_SIZE,
+        DEFAULT_VALIDATOR_FETCH_TIMEOUT,
+    },
+    handlers::{admin::tracing_sampling::TracingSamplingRule, websocket::fanout::FeedUpdatesFanout},
+    rpc::{
+        evm::{pragma::PragmaContractsMapping, HyperlaneValidatorsMapping},
+        starknet::StarknetCalls,
+    },
+    services::{api::rate_limit::RateLimiter, metrics::TheorosMetrics},
+    storage::{SettingKind, TheorosStorage},
+    types::{
+        chain_statuses::ChainStatuses, config_deployment::RunningEvmConfig, encryption::ConsumerPublicKey,
+        feed_lifecycles::FeedLifecycles, post_processors::PostProcessorsMapping, private_feeds::PrivateFeeds,
+        quorum::QuorumTracker, relayer::RelayerStatuses, staleness::StalenessPolicy,
+    },
 };
 
 #[derive(Clone)]
 pub struct AppState {
-    pub starknet_rpc: Arc<StarknetRpc>,
+    pub starknet_rpc: Arc<dyn StarknetCalls>,
     pub hyperlane_validators_mapping: Arc<HyperlaneValidatorsMapping>,
+    /// Whether each chain is currently indexed and/or served.
+    pub chain_statuses: Arc<ChainStatuses>,
+    /// Whether each feed is active, deprecated or retired.
+    pub feed_lifecycles: Arc<FeedLifecycles>,
+    /// Feeds whose calldata is only served encrypted for a registered consumer.
+    pub private_feeds: Arc<PrivateFeeds>,
+    /// Max age of the latest update of each feed, past which the feed is stale.
+    pub staleness_policy: Arc<StalenessPolicy>,
+    pub post_processors: Arc<PostProcessorsMapping>,
+    /// Pragma contracts of the chains, used to simulate updates.
+    pub pragma_contracts: Arc<PragmaContractsMapping>,
+    /// EVM config the chain components above were built from, which can be replaced through the admin API.
+    pub evm_config: Arc<RunningEvmConfig>,
+    /// Whether the dispatches sent to a domain missing from the EVM config are skipped.
+    pub filter_dispatch_destinations: bool,
     pub storage: Arc<TheorosStorage>,
+    /// Whether the signatures collected for a nonce reach the threshold of each chain.
+    pub quorum_tracker: Arc<QuorumTracker>,
+    /// Transactions sent by the relayer on each chain it runs on, if enabled.
+    pub relayer_statuses: Arc<RelayerStatuses>,
     #[allow(unused)]
     pub metrics_registry: Registry, // already wrapped into an Arc
+    pub metrics: Arc<TheorosMetrics>,
     pub ws: Arc<WsState>,
+    /// Chain used when a calldata request doesn't specify one.
+    pub default_chain: Option<EvmChainName>,
+    pub tracing_sampler: TracingSampler,
+    /// Maximum level of the logs, which can be changed at runtime.
+    pub log_level: LogLevel,
+    /// Settings which can be changed at runtime, watched by the components using them.
+    pub runtime_settings: Arc<watch::Sender<RuntimeSettings>>,
+    /// Whether the indexer is paused through the admin API.
+    pub indexer_paused: Arc<watch::Sender<bool>>,
+    /// Bearer token protecting the admin API. The admin API is disabled when `None`.
+    pub admin_api_key: Option<String>,
+    /// Overall time allowed to assemble the calldata of a request, including the checkpoints fetched on demand.
+    pub calldata_deadline: Duration,
+    /// Maximum time spent fetching a checkpoint from a single validator.
+    pub validator_fetch_timeout: Duration,
+    /// Maximum size, in bytes, of the body of an API request.
+    pub max_request_body_size: usize,
+    /// Maximum number of feeds requested at once.
+    pub max_batch_feeds: usize,
+    /// Rate limits of the calldata & history endpoints, which are expensive to serve.
+    pub rate_limiter: Arc<RateLimiter>,
+    /// Hash of the configuration, to tell whether two instances run the same one.
+    pub config_fingerprint: Option<String>,
+    /// Cancelled on SIGTERM/SIGINT: every service stops once done with its current work.
+    pub shutdown: CancellationToken,
+}
+
+impl AppState {
+    pub fn builder() -> AppStateBuilder {
+        AppStateBuilder::default()
+    }
+
+    /// Applies the settings changed through the admin API & persisted in the storage backend, possibly by another
+    /// process sharing it, over the configured ones. Returns the number of settings persisted.
+    pub async fn sync_settings(&self) -> anyhow::Result<usize> {
+        let settings = self.storage.load_settings().await?;
+
+        let consumer_keys = settings
+            .of::<String>(SettingKind::ConsumerKey)?
+            .into_iter()
+            .map(|(consumer_id, key)| {
+                let key = ConsumerPublicKey::from_hex(&key)
+                    .with_context(|| format!("Invalid persisted key of consumer {consumer_id}"))?;
+                Ok((consumer_id, key))
+            })
+            .collect::<anyhow::Result<HashMap<_, _>>>()?;
+        self.storage.consumer_keys().replace(consumer_keys);
+
+        for (feed_id, private) in settings.of::<bool>(SettingKind::PrivateFeed)? {
+            self.private_feeds.set(feed_id, private);
+        }
+        for (feed_id, lifecycle) in settings.of::<FeedLifecycle>(SettingKind::FeedLifecycle)? {
+            self.feed_lifecycles.set(feed_id, lifecycle);
+        }
+        for (chain_name, status) in settings.of::<ChainStatus>(SettingKind::ChainStatus)? {
+            // Chains unknown to this version of Theoros are left out, like the ones missing from the EVM config.
+            if let Ok(chain_name) = EvmChainName::from_str(&chain_name) {
+                self.chain_statuses.set(chain_name, status);
+            }
+        }
+        for (_, rules) in settings.of::<Vec<TracingSamplingRule>>(SettingKind::TracingSampling)? {
+            let rules = rules
+                .into_iter()
+                .map(|rule| SamplingRule::new(rule.target, rule.rate))
+                .collect::<anyhow::Result<Vec<_>>>()
+                .context("Invalid persisted tracing sampling rule")?;
+            // Replacing the rules resets their sampling.
+            if rules != self.tracing_sampler.rules() {
+                self.tracing_sampler.set_rules(rules);
+            }
+        }
+        Ok(settings.len())
+    }
+}
+
+/// Builds an [AppState]. The Starknet RPC & the storage are required, every other
+/// component defaults to an empty or disabled implementation.
+#[derive(Default)]
+pub struct AppStateBuilder {
+    starknet_rpc: Option<Arc<dyn StarknetCalls>>,
+    hyperlane_validators_mapping: Option<HyperlaneValidatorsMapping>,
+    chain_statuses: Option<ChainStatuses>,
+    feed_lifecycles: Option<FeedLifecycles>,
+    private_feeds: Option<PrivateFeeds>,
+    staleness_policy: Option<StalenessPolicy>,
+    post_processors: Option<PostProcessorsMapping>,
+    pragma_contracts: Option<PragmaContractsMapping>,
+    evm_config: Option<EvmConfig>,
+    filter_dispatch_destinations: bool,
+    storage: Option<TheorosStorage>,
+    metrics_registry: Option<Registry>,
+    metrics: Option<Arc<TheorosMetrics>>,
+    default_chain: Option<EvmChainName>,
+    tracing_sampler: Option<TracingSampler>,
+    log_level: Option<LogLevel>,
+    runtime_settings: Option<RuntimeSettings>,
+    admin_api_key: Option<String>,
+    calldata_deadline: Option<Duration>,
+    validator_fetch_timeout: Option<Duration>,
+    max_request_body_size: Option<usize>,
+    max_batch_feeds: Option<usize>,
+    rate_limiter: Option<RateLimiter>,
+    config_fingerprint: Option<String>,
+    shutdown: Option<CancellationToken>,
+}
+
+impl AppStateBuilder {
+    pub fn with_starknet_rpc(mut self, starknet_rpc: Arc<dyn StarknetCalls>) -> Self {
+        self.starknet_rpc = Some(starknet_rpc);
+        self
+    }
+
+    pub fn with_hyperlane_validators_mapping(mut self, mapping: HyperlaneValidatorsMapping) -> Self {
+        self.hyperlane_validators_mapping = Some(mapping);
+        self
+    }
+
+    /// Statuses of the chains. Defaults to all the chains of the validators mapping being enabled.
+    pub fn with_feed_lifecycles(mut self, feed_lifecycles: FeedLifecycles) -> Self {
+        self.feed_lifecycles = Some(feed_lifecycles);
+        self
+    }
+
+    /// Private feeds. Defaults to all the feeds being public.
+    pub fn with_private_feeds(mut self, private_feeds: PrivateFeeds) -> Self {
+        self.private_feeds = Some(private_feeds);
+        self
+    }
+
+    pub fn with_staleness_policy(mut self, staleness_policy: StalenessPolicy) -> Self {
+        self.staleness_policy = Some(staleness_policy);
+        self
+    }
+
+    pub fn with_chain_statuses(mut self, chain_statuses: ChainStatuses) -> Self {
+        self.chain_statuses = Some(chain_statuses);
+        self
+    }
+
+    pub fn with_post_processors(mut self, post_processors: PostProcessorsMapping) -> Self {
+        self.post_processors = Some(post_processors);
+        self
+    }
+
+    pub fn with_pragma_contracts(mut self, pragma_contracts: PragmaContractsMapping) -> Self {
+        self.pragma_contracts = Some(pragma_contracts);
+        self
+    }
+
+    pub fn with_evm_config(mut self, evm_config: EvmConfig) -> Self {
+        self.evm_config = Some(evm_config);
+        self
+    }
+
+    /// Skips the dispatches sent to a domain missing from the EVM config. Disabled by default.
+    pub fn with_filter_dispatch_destinations(mut self, filter_dispatch_destinations: bool) -> Self {
+        self.filter_dispatch_destinations = filter_dispatch_destinations;
+        self
+    }
+
+    pub fn with_storage(mut self, storage: TheorosStorage) -> Self {
+        self.storage = Some(storage);
+        self
+    }
+
+    /// Registry where the metrics are exposed. If no metrics are provided, they are registered into it.
+    pub fn with_metrics_registry(mut self, registry: Registry) -> Self {
+        self.metrics_registry = Some(registry);
+        self
+    }
+
+    pub fn with_metrics(mut self, metrics: Arc<TheorosMetrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    pub fn with_default_chain(mut self, default_chain: Option<EvmChainName>) -> Self {
+        self.default_chain = default_chain;
+        self
+    }
+
+    pub fn with_tracing_sampler(mut self, tracing_sampler: TracingSampler) -> Self {
+        self.tracing_sampler = Some(tracing_sampler);
+        self
+    }
+
+    pub fn with_log_level(mut self, log_level: LogLevel) -> Self {
+        self.log_level = Some(log_level);
+        self
+    }
+
+    /// Applied to the log level & the rate limiter once built.
+    pub fn with_runtime_settings(mut self, runtime_settings: RuntimeSettings) -> Self {
+        self.runtime_settings = Some(runtime_settings);
+        self
+    }
+
+    pub fn with_admin_api_key(mut self, admin_api_key: Option<String>) -> Self {
+        self.admin_api_key = admin_api_key;
+        self
+    }
+
+    pub fn with_calldata_deadline(mut self, calldata_deadline: Duration) -> Self {
+        self.calldata_deadline = Some(calldata_deadline);
+        self
+    }
+
+    pub fn with_validator_fetch_timeout(mut self, validator_fetch_timeout: Duration) -> Self {
+        self.validator_fetch_timeout = Some(validator_fetch_timeout);
+        self
+    }
+
+    pub fn with_max_request_body_size(mut self, max_request_body_size: usize) -> Self {
+        self.max_request_body_size = Some(max_request_body_size);
+        self
+    }
+
+    pub fn with_max_batch_feeds(mut self, max_batch_feeds: usize) -> Self {
+        self.max_batch_feeds = Some(max_batch_feeds);
+        self
+    }
+
+    /// Rate limiter of the expensive endpoints, whose limits are the ones of the runtime settings.
+    pub fn with_rate_limiter(mut self, rate_limiter: RateLimiter) -> Self {
+        self.rate_limiter = Some(rate_limiter);
+        self
+    }
+
+    pub fn with_config_fingerprint(mut self, config_fingerprint: String) -> Self {
+        self.config_fingerprint = Some(config_fingerprint);
+        self
+    }
+
+    /// Token stopping the services of the state. Defaults to a token of its own.
+    pub fn with_shutdown(mut self, shutdown: CancellationToken) -> Self {
+        self.shutdown = Some(shutdown);
+        self
+    }
+
+    pub fn build(self) -> anyhow::Result<AppState> {
+        let starknet_rpc = self.starknet_rpc.context("Missing Starknet RPC")?;
+        let storage = self.storage.context("Missing storage")?;
+        let hyperlane_validators_mapping = self.hyperlane_validators_mapping.unwrap_or_default();
+
+        if let Some(default_chain) = self.default_chain {
+            anyhow::ensure!(
+                hyperlane_validators_mapping.is_supported_chain(&default_chain),
+                "The default chain {default_chain} is not present in the EVM config"
+            );
+        }
+
+        let chain_statuses = self
+            .chain_statuses
+            .unwrap_or_else(|| ChainStatuses::all_enabled(hyperlane_validators_mapping.chain_names()));
+
+        let metrics_registry = self.metrics_registry.unwrap_or_default();
+        let metrics = match self.metrics {
+            Some(metrics) => metrics,
+            None => Arc::new(TheorosMetrics::register(&metrics_registry)?),
+        };
+
+        let hyperlane_validators_mapping = Arc::new(hyperlane_validators_mapping);
+        let storage = Arc::new(storage);
+        let quorum_tracker = Arc::new(QuorumTracker::new(hyperlane_validators_mapping.clone(), storage.clone()));
+
+        let runtime_settings = self.runtime_settings.unwrap_or_default();
+        let rate_limiter = self.rate_limiter.unwrap_or_else(|| RateLimiter::new(metrics.clone()));
+
+        let state = AppState {
+            starknet_rpc,
+            hyperlane_validators_mapping,
+            chain_statuses: Arc::new(chain_statuses),
+            feed_lifecycles: Arc::new(self.feed_lifecycles.unwrap_or_default()),
+            private_feeds: Arc::new(self.private_feeds.unwrap_or_default()),
+            staleness_policy: Arc::new(self.staleness_policy.unwrap_or_default()),
+            post_processors: Arc::new(self.post_processors.unwrap_or_default()),
+            pragma_contracts: Arc::new(self.pragma_contracts.unwrap_or_default()),
+            evm_config: Arc::new(RunningEvmConfig::new(self.evm_config.unwrap_or_default())),
+            filter_dispatch_destinations: self.filter_dispatch_destinations,
+            storage,
+            quorum_tracker,
+            relayer_statuses: Arc::new(RelayerStatuses::default()),
+            metrics_registry,
+            metrics,
+            ws: Arc::new(WsState::new()),
+            default_chain: self.default_chain,
+            tracing_sampler: self.tracing_sampler.unwrap_or_default(),
+            log_level: self.log_level.unwrap_or_default(),
+            runtime_settings: Arc::new(watch::Sender::new(runtime_settings)),
+            indexer_paused: Arc::new(watch::Sender::new(false)),
+            admin_api_key: self.admin_api_key,
+            calldata_deadline: self.calldata_deadline.unwrap_or(DEFAULT_CALLDATA_DEADLINE),
+            validator_fetch_timeout: self.validator_fetch_timeout.unwrap_or(DEFAULT_VALIDATOR_FETCH_TIMEOUT),
+            max_request_body_size: self.max_request_body_size.unwrap_or(DEFAULT_MAX_REQUEST_BODY_SIZE),
+            max_batch_feeds: self.max_batch_feeds.unwrap_or(DEFAULT_MAX_BATCH_FEEDS),
+            rate_limiter: Arc::new(rate_limiter),
+            config_fingerprint: self.config_fingerprint,
+            shutdown: self.shutdown.unwrap_or_default(),
+        };
+        runtime_settings.apply(&state);
+        Ok(state)
+    }
 }
 
 pub struct WsState {
     pub subscriber_counter: AtomicUsize,
+    pub fanout: FeedUpdatesFanout,
+    /// The open WebSocket connections, waited for on shutdown once sent a close frame.
+    pub connections: TaskTracker,
 }
 
 #[allow(clippy::new_without_default)]
 impl WsState {
     pub fn new() -> Self {
-        Self { subscriber_counter: AtomicUsize::new(0) }
+        Self {
+            subscriber_counter: AtomicUsize::new(0),
+            fanout: FeedUpdatesFanout::new(),
+            connections: TaskTracker::new(),
+        }
     }
 }
@@ -0,0 +1,34 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::configs::evm_config::EvmChainName;
+
+/// A step of the lifecycle of a feed update, from its dispatch on Pragma chain to its delivery.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct FeedTimelineEvent {
+    pub at: DateTime<Utc>,
+    #[serde(flatten)]
+    pub kind: FeedTimelineEventKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum FeedTimelineEventKind {
+    /// The Dispatch event containing an update of the feed was indexed.
+    DispatchIndexed { nonce: u32, block_number: Option<u64> },
+    /// The block of the Dispatch event was orphaned by a reorg & the update rolled back.
+    DispatchOrphaned { nonce: u32, block_number: u64 },
+    /// The checkpoint of the Dispatch message was fetched from a validator.
+    CheckpointFetched { nonce: u32, validator: String },
+    /// The Dispatch message was signed by the validators & the update stored as the latest one.
+    UpdateStored { nonce: u32 },
+    /// The calldata of the update was served for the first time for this chain.
+    CalldataFirstServed { nonce: u32, chain: EvmChainName },
+}
+
+impl FeedTimelineEvent {
+    pub fn now(kind: FeedTimelineEventKind) -> Self {
+        Self { at: Utc::now(), kind }
+    }
+}
@@ -0,0 +1,68 @@
+use alloy::primitives::{utils::format_units, U256};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::configs::evm_config::{EvmChainName, NativeToken};
+
+/// Decimals of the gwei unit.
+const GWEI_DECIMALS: u8 = 9;
+
+/// An amount of the native token of a chain, formatted in its different units so responses mixing chains
+/// can be read without looking up each chain.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct NativeAmount {
+    /// Raw amount, in the smallest unit of the token (wei).
+    pub wei: String,
+    /// Amount in gwei, as a decimal string.
+    pub gwei: String,
+    /// Amount in the native token, as a decimal string scaled by its decimals.
+    pub amount: String,
+    #[serde(flatten)]
+    pub token: NativeToken,
+}
+
+impl NativeAmount {
+    pub fn new(chain: EvmChainName, wei: U256) -> Self {
+        let token = chain.native_token();
+        Self {
+            wei: wei.to_string(),
+            gwei: format_decimal(wei, GWEI_DECIMALS),
+            amount: format_decimal(wei, token.decimals),
+            token,
+        }
+    }
+}
+
+/// Formats `value` scaled down by `decimals`, without the trailing zeros of its fractional part.
+fn format_decimal(value: U256, decimals: u8) -> String {
+    let formatted = format_units(value, decimals).unwrap_or_else(|_| value.to_string());
+    match formatted.split_once('.') {
+        Some((integer, fraction)) => match fraction.trim_end_matches('0') {
+            "" => integer.to_owned(),
+            fraction => format!("{integer}.{fraction}"),
+        },
+        None => formatted,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_native_amount_is_formatted_with_the_chain_token() {
+        let amount = NativeAmount::new(EvmChainName::Bsc, U256::from(1_500_000_000_000_000_u64));
+        assert_eq!(amount.wei, "1500000000000000");
+        assert_eq!(amount.gwei, "1500000");
+        assert_eq!(amount.amount, "0.0015");
+        assert_eq!(amount.token.symbol, "BNB");
+        assert_eq!(amount.token.decimals, 18);
+
+        let amount = NativeAmount::new(EvmChainName::Arbitrum, U256::from(1_u8));
+        assert_eq!(amount.gwei, "0.000000001");
+        assert_eq!(amount.amount, "0.000000000000000001");
+        assert_eq!(amount.token.symbol, "ETH");
+
+        assert_eq!(NativeAmount::new(EvmChainName::Mainnet, U256::ZERO).amount, "0");
+    }
+}
@@ -0,0 +1,133 @@
+use alloy::hex;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::types::hyperlane::DispatchUpdate;
+
+/// JSON view of an update, tagged by the `type` of its feed so each kind can be handled
+/// without guessing the semantics of its fields.
+///
+/// Numbers that don't fit in a JSON number are decimal strings, to be scaled by `decimals`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum UpdateView {
+    SpotMedian {
+        /// Unix timestamp of the update, in seconds.
+        timestamp: u64,
+        num_sources_aggregated: u16,
+        decimals: u8,
+        price: String,
+        volume: String,
+    },
+    Perp {
+        /// Unix timestamp of the update, in seconds.
+        timestamp: u64,
+        num_sources_aggregated: u16,
+        decimals: u8,
+        mark_price: String,
+        funding_rate: String,
+        open_interest: String,
+        volume: String,
+    },
+    /// Update of a feed type unknown to this version of Theoros.
+    Opaque {
+        feed_type: u16,
+        /// Raw update data, as a hex string.
+        data: String,
+    },
+}
+
+impl From<&DispatchUpdate> for UpdateView {
+    fn from(update: &DispatchUpdate) -> Self {
+        match update {
+            DispatchUpdate::SpotMedian { update, .. } => UpdateView::SpotMedian {
+                timestamp: update.metadata.timestamp,
+                num_sources_aggregated: update.metadata.num_sources_aggregated,
+                decimals: update.metadata.decimals,
+                price: update.price.to_string(),
+                volume: update.volume.to_string(),
+            },
+            DispatchUpdate::Perp { update, .. } => UpdateView::Perp {
+                timestamp: update.metadata.timestamp,
+                num_sources_aggregated: update.metadata.num_sources_aggregated,
+                decimals: update.metadata.decimals,
+                mark_price: update.mark_price.to_string(),
+                funding_rate: update.funding_rate.to_string(),
+                open_interest: update.open_interest.to_string(),
+                volume: update.volume.to_string(),
+            },
+            DispatchUpdate::Opaque { feed_type, data, .. } => {
+                UpdateView::Opaque { feed_type: *feed_type, data: hex::encode_prefixed(data) }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use serde_json::json;
+    use starknet::core::types::U256;
+
+    use super::*;
+    use crate::types::hyperlane::{MetadataUpdate, PerpUpdate, SpotMedianUpdate};
+
+    #[test]
+    fn test_update_views_are_tagged_by_type() {
+        let spot = DispatchUpdate::SpotMedian {
+            feed_id: String::from("0x01"),
+            update: SpotMedianUpdate {
+                pair_id: U256::from(1_u8),
+                metadata: MetadataUpdate { timestamp: 1_700_000_000, num_sources_aggregated: 5, decimals: 8 },
+                price: U256::from(6_500_000_000_000_u64),
+                volume: U256::from(0_u8),
+            },
+        };
+        assert_eq!(
+            serde_json::to_value(UpdateView::from(&spot)).unwrap(),
+            json!({
+                "type": "spot_median",
+                "timestamp": 1_700_000_000,
+                "num_sources_aggregated": 5,
+                "decimals": 8,
+                "price": "6500000000000",
+                "volume": "0",
+            })
+        );
+
+        let perp = DispatchUpdate::Perp {
+            feed_id: String::from("0x03"),
+            update: PerpUpdate {
+                pair_id: U256::from(1_u8),
+                metadata: MetadataUpdate { timestamp: 1_700_000_000, num_sources_aggregated: 3, decimals: 6 },
+                mark_price: U256::from(65_000_000_000_u64),
+                funding_rate: U256::from(100_u8),
+                open_interest: U256::from(1_000_000_u32),
+                volume: U256::from(0_u8),
+            },
+        };
+        assert_eq!(
+            serde_json::to_value(UpdateView::from(&perp)).unwrap(),
+            json!({
+                "type": "perp",
+                "timestamp": 1_700_000_000,
+                "num_sources_aggregated": 3,
+                "decimals": 6,
+                "mark_price": "65000000000",
+                "funding_rate": "100",
+                "open_interest": "1000000",
+                "volume": "0",
+            })
+        );
+
+        let opaque = DispatchUpdate::Opaque {
+            feed_id: String::from("0x02"),
+            feed_type: 7,
+            data: Bytes::from_static(&[0xca, 0xfe]),
+        };
+        assert_eq!(
+            serde_json::to_value(UpdateView::from(&opaque)).unwrap(),
+            json!({ "type": "opaque", "feed_type": 7, "data": "0xcafe" })
+        );
+    }
+}
@@ -6,6 +6,18 @@ import "../src/libraries/DataParser.sol";
 import "../src/libraries/ErrorsLib.sol";
 
 contract DataParserTest is Test {
+    // Update data encoded by Theoros (`rust/theoros/src/rpc/evm/calldata.rs`), which must match the packed encoding
+    // of the entries below.
+    bytes constant SPOT_MEDIAN_GOLDEN_VECTOR =
+        hex"000000004254432f5553440000000000000000000000000000000000000000000000000060dd05800003080000000000"
+        hex"000000000000000000000000000000000007695a92c20d6fe00000000000000000000000000000000000000000000000"
+        hex"0000056bc75e2d63100000";
+    bytes constant PERP_GOLDEN_VECTOR =
+        hex"000004004254432f5553440000000000000000000000000000000000000000000000000060dd05800003080000000000"
+        hex"000000000000000000000000000000000007695a92c20d6fe00000000000000000000000000000000000000000000000"
+        hex"00000000038d7ea4c6800000000000000000000000000000000000000000000000003635c9adc5dea000000000000000"
+        hex"0000000000000000000000000000000000001b1ae4d6e2ef500000";
+
     function testParseSpotMedianEntry() public pure {
         bytes32 feedId = bytes32(
             abi.encodePacked(
@@ -24,6 +36,7 @@ contract DataParserTest is Test {
             uint256(35000 ether), // price
             uint256(100 ether) // volume
         );
+        assertEq(data, SPOT_MEDIAN_GOLDEN_VECTOR);
 
         ParsedData memory result = DataParser.parse(data);
 
@@ -182,6 +195,7 @@ contract DataParserTest is Test {
             uint256(1000 ether), // openInterest
             uint256(500 ether) // volume
         );
+        assertEq(data, PERP_GOLDEN_VECTOR);
 
         ParsedData memory result = DataParser.parse(data);
 
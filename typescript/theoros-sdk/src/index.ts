@@ -18,6 +18,12 @@ export interface Feed {
   pair_id: string;
 }
 
+export interface DataFeedsPage {
+  data_feeds: Feed[];
+  total: number;
+  next_cursor: string | null;
+}
+
 export interface RpcDataFeed {
   feed_id: string;
   encoded_calldata: string;
@@ -178,13 +184,22 @@ export class TheorosSDK {
   }
 
   /**
-   * Retrieves all available data feeds.
+   * Retrieves all available data feeds, going through all the pages.
    * @returns A promise that resolves to an array of Feed objects.
    */
   async getAvailableFeeds(): Promise<Feed[]> {
     try {
-      const response = await this.httpClient.get<Feed[]>("/data_feeds");
-      return response.data;
+      const feeds: Feed[] = [];
+      let cursor: string | null = null;
+      do {
+        const response: { data: DataFeedsPage } =
+          await this.httpClient.get<DataFeedsPage>("/data_feeds", {
+            params: { limit: 1000, ...(cursor ? { cursor } : {}) },
+          });
+        feeds.push(...response.data.data_feeds);
+        cursor = response.data.next_cursor;
+      } while (cursor);
+      return feeds;
     } catch (error) {
       throw new TheorosSDKError("Error fetching data feeds", error);
     }